@@ -1,8 +1,272 @@
-use crate::git::GitOperations;
-use tui::widgets::ListState;
+pub mod commit;
+mod input;
 
+use crate::app::commit::{CommitContents, CommitFlags, CommitLintConfig, CommitPrefix};
+use crate::askpass;
+use crate::binary;
+use crate::clipboard;
+use crate::command_log::{self, CommandRecord};
+use crate::config::{Config, LayoutConfig, NotifyConfig, PreviewPosition};
+use crate::diff_cache::{DiffCache, DiffKey, Fingerprint};
+use crate::diff_lines::DiffLines;
+use crate::doctor::{self, Check};
+use crate::error::GitError;
+use crate::file_tree::{self, DirNode};
+use crate::forge::{self, ForgeRepo};
+use crate::git::{
+    archive_format, ignore_candidates, BisectStatus, BranchEntry, CommitDetails, CommitFile,
+    CommitOptions, ConfigChange, ConfigEntry, ConfigScope, ConflictHunk, ConflictSide,
+    DetachedHead, DiffOptions, DiffStat, DiffView, FileEntry, FileVersion, GitOperations,
+    HeadSummary, Identity, InitOptions, LogQuery, MaintenanceTask, MergeStrategy, PullStrategy,
+    ReflogEntry, RepoStats, ResetMode, Section, StashEntry, StashOptions, StatusOptions,
+    UntrackedFiles, UpstreamStatus, GITIGNORE_TEMPLATES,
+};
+use crate::history::{History, HistoryEntry};
+use crate::notify;
+use crate::patch::FilePatch;
+use crate::repo::{RecentRepos, RepoContext};
+use crate::sort::{self, SortMode, SortModes};
+use crate::status_line::StatusLine;
+use crate::strings::{Language, Message, Msg};
+use crate::text_input::TextInput;
+use crate::theme::{ColorDepth, Theme};
+use crate::worker::{Task, Worker};
+use ratatui::layout::Rect;
+use ratatui::widgets::ListState;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::mem::Discriminant;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Maximum number of commits loaded into the log view
+const LOG_MAX_COUNT: usize = 500;
+
+/// Maximum number of HEAD movements loaded into the reflog view
+const REFLOG_MAX_COUNT: usize = 500;
+
+/// Commits whose authors the trailer picker offers
+const RECENT_AUTHOR_COMMITS: usize = 200;
+
+/// Diff lines the preview panel loads at a time; more are loaded when scrolling near the end
+pub const PREVIEW_PAGE_LINES: usize = 1000;
+/// How many rows above and below the selection have their preview loaded ahead of time
+const PREFETCH_ROWS: usize = 3;
+/// Prefetching waits while this many preview diffs run; the selected file's never waits
+const MAX_PREVIEW_DIFFS: usize = 3;
+
+/// A full `git status` slower than this switches to `--untracked-files=no`
+const SLOW_STATUS: Duration = Duration::from_millis(1500);
+
+/// Ticks between upstream status checks (about two seconds with the 250ms tick)
+const UPSTREAM_REFRESH_TICKS: u32 = 8;
+
+/// Ticks a toast stays on screen (about four seconds with the 250ms tick)
+const TOAST_TICKS: u16 = 16;
+
+/// A short notice drawn over the corner of the screen until it expires
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toast {
+    pub message: String,
+    pub ticks_left: u16,
+}
+
+/// A fixed list of choices with its selection and screen area
+#[derive(Debug, Default)]
+pub struct Picker {
+    pub state: ListState,
+    pub area: ListArea,
+}
+
+impl Picker {
+    /// Selects the clicked one of the `len` choices
+    fn click(&mut self, column: u16, row: u16, len: usize) {
+        if let Some(i) = self.area.row_at(column, row).filter(|&i| i < len) {
+            self.state.select(Some(i));
+        }
+    }
+}
+
+/// Where the last frame drew each screen, used for mouse handling
+#[derive(Debug, Default)]
+pub struct ScreenAreas {
+    pub file_list: ListArea,
+    pub preview_panel: Rect,
+    /// The fullscreen preview or file viewer, and the help
+    pub fullscreen: Rect,
+    pub help: Rect,
+    pub command_log: Rect,
+    pub log: ListArea,
+    pub reflog: ListArea,
+    pub stash_list: ListArea,
+    pub branch: ListArea,
+    pub repo_list: ListArea,
+    pub line_stage: ListArea,
+}
+
+/// Who commits are made as, and the profiles the identity picker offers
+#[derive(Debug, Default)]
+pub struct IdentityState {
+    /// `[[identities]]` from the config
+    pub profiles: Vec<Identity>,
+    /// `user.name` and `user.email` of the repository, read when a commit starts
+    pub configured: Option<Identity>,
+    /// Profile the commit being written is made as instead of `configured`
+    pub commit_override: Option<Identity>,
+    /// Row 0 is the repository's own setting, the profiles follow
+    pub picker: Picker,
+}
+
+impl IdentityState {
+    /// The identity the next commit is made as
+    pub fn active(&self) -> Option<&Identity> {
+        self.commit_override.as_ref().or(self.configured.as_ref())
+    }
+
+    /// The profile on the selected picker row; `None` for the repository's own setting
+    fn selected_profile(&self) -> Option<&Identity> {
+        let row = self.picker.state.selected()?;
+        self.profiles.get(row.checked_sub(1)?)
+    }
+}
+
+/// Trailers appended to the commit being written, and the authors the trailer picker offers
+#[derive(Debug, Default)]
+pub struct TrailerState {
+    /// `Signed-off-by:` with the identity the commit is made as
+    pub signoff: bool,
+    /// `Key: Name <email>` lines picked in the trailer picker, in the order they were added
+    pub added: Vec<String>,
+    /// Recent authors other than the one committing, read when the picker opens
+    pub authors: Vec<Identity>,
+    /// Index into `[commit.trailers] keys` of the key the picker adds
+    pub key: usize,
+    pub picker: Picker,
+}
+
+/// The repository statistics and the task list of the maintenance screen
+#[derive(Debug, Default)]
+pub struct MaintenanceScreen {
+    pub stats: RepoStats,
+    /// Selected entry of `MaintenanceTask::ALL`
+    pub tasks: ListState,
+    pub area: ListArea,
+}
+
+/// Keys the config screen lists first, with the value in effect, whether set or not
+pub const COMMON_CONFIG_KEYS: [&str; 4] = ["user.name", "user.email", "pull.rebase", "core.editor"];
+
+/// Every git config value and where it is set. The rows are `COMMON_CONFIG_KEYS`, then
+/// `entries`.
+#[derive(Debug, Default)]
+pub struct ConfigScreen {
+    pub entries: Vec<ConfigEntry>,
+    pub state: ListState,
+    pub area: ListArea,
+}
+
+impl ConfigScreen {
+    pub fn rows(&self) -> usize {
+        COMMON_CONFIG_KEYS.len() + self.entries.len()
+    }
+
+    /// The entry whose value is in effect for `key`: the last one git reads
+    pub fn effective(&self, key: &str) -> Option<&ConfigEntry> {
+        self.entries.iter().rev().find(|entry| entry.key == key)
+    }
+
+    /// Whether a later entry in another scope overrides `entries[index]`. Keys set more
+    /// than once in the same file are multi-valued, like `remote.origin.fetch`.
+    pub fn is_overridden(&self, index: usize) -> bool {
+        let entry = &self.entries[index];
+        self.entries[index + 1..]
+            .iter()
+            .any(|later| later.key == entry.key && later.scope != entry.scope)
+    }
+
+    /// The key on row `row` and the entry shown there, `None` for an unset common key
+    pub fn row(&self, row: usize) -> Option<(&str, Option<&ConfigEntry>)> {
+        if let Some(key) = COMMON_CONFIG_KEYS.get(row) {
+            return Some((key, self.effective(key)));
+        }
+        let entry = self.entries.get(row - COMMON_CONFIG_KEYS.len())?;
+        Some((&entry.key, Some(entry)))
+    }
+
+    fn click(&mut self, column: u16, row: u16) {
+        if let Some(i) = self.area.row_at(column, row).filter(|&i| i < self.rows()) {
+            self.state.select(Some(i));
+        }
+    }
+}
+
+/// A row of the file list of the commit details view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitDetailsRow {
+    /// Index into `CommitDetails::files`
+    File(usize),
+    /// Line of the expanded diff of a file
+    Diff(usize, usize),
+}
+
+impl CommitDetailsRow {
+    pub const fn file(self) -> usize {
+        match self {
+            Self::File(file) | Self::Diff(file, _) => file,
+        }
+    }
+}
+
+/// The commit opened from the log, with the diffs of its files expanded in place
+#[derive(Debug, Default)]
+pub struct CommitDetailsScreen {
+    pub details: CommitDetails,
+    /// Diffs of the expanded files by file index, loaded on first expansion
+    pub diffs: HashMap<usize, DiffLines>,
+    pub expanded: BTreeSet<usize>,
+    /// Selected entry of `rows()`
+    pub state: ListState,
+    pub area: ListArea,
+}
+
+impl CommitDetailsScreen {
+    /// Every file followed by the lines of its diff when it is expanded
+    pub fn rows(&self) -> Vec<CommitDetailsRow> {
+        let mut rows = Vec::new();
+        for file in 0..self.details.files.len() {
+            rows.push(CommitDetailsRow::File(file));
+            if let Some(diff) = self
+                .diffs
+                .get(&file)
+                .filter(|_| self.expanded.contains(&file))
+            {
+                rows.extend((0..diff.line_count()).map(|line| CommitDetailsRow::Diff(file, line)));
+            }
+        }
+        rows
+    }
+
+    pub fn selected_row(&self) -> Option<CommitDetailsRow> {
+        self.rows().get(self.state.selected()?).copied()
+    }
+}
+
+/// Two refs compared with `git diff A..B`, listing the files that differ between them
+#[derive(Debug, Default)]
+pub struct CompareScreen {
+    /// `A..B` or `A...B`
+    pub range: String,
+    pub files: Vec<CommitFile>,
+    pub state: ListState,
+    pub area: ListArea,
+    /// The view the comparison was started from, which leaving it goes back to
+    pub origin: InputMode,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub enum InputMode {
+    #[default]
     Normal,
     Commit,
     StashMessage,
@@ -11,32 +275,511 @@ pub enum InputMode {
         action: ConfirmAction,
     },
     Preview {
-        content: String,
+        content: DiffLines,
         file_path: String,
     },
     Help,
+    Log,
+    /// Full content of a file as of HEAD, in the index or in the working tree
+    FileVersion {
+        file_path: String,
+        version: FileVersion,
+        content: DiffLines,
+    },
+    /// Typing a search over the log: message text, `author:NAME` or `path:PATH`
+    LogSearch {
+        query: TextInput,
+    },
+    /// HEAD movements from `git reflog`
+    Reflog,
+    StashList,
+    RepoSwitcher,
+    /// Started outside a repository: offers `git init` or one of the recent repositories
+    NoRepository {
+        dir: PathBuf,
+    },
+    /// `git init` in `dir` with options, asked one step at a time from the no-repository
+    /// screen; the `.gitignore` template is the selection of `App::init`
+    InitSetup {
+        dir: PathBuf,
+        step: InitStep,
+        branch: TextInput,
+    },
+    /// Ours/theirs view of a file with merge conflicts
+    Conflict {
+        file_path: String,
+    },
+    /// Selecting individual lines of a file to stage (or unstage, for the staged view)
+    LineStage {
+        file_path: String,
+        view: DiffView,
+    },
+    /// Choosing which `.gitignore` pattern to add for an untracked file
+    IgnoreChooser {
+        file_path: String,
+    },
+    /// Local and remote-tracking branches
+    Branches,
+    /// Typing the new name or the upstream of the local branch `branch`, or the name of a
+    /// branch to create at the detached HEAD, `branch` then being its hash
+    BranchPrompt {
+        branch: String,
+        prompt: BranchPrompt,
+        input: TextInput,
+    },
+    /// Choosing how to merge `branch` into the current branch
+    MergePicker {
+        branch: String,
+    },
+    /// Choosing between `git pull --rebase`, a merge and `--ff-only`; `default` is what plain
+    /// `git pull` would do
+    PullPicker {
+        default: PullStrategy,
+    },
+    /// Object store size and `git gc`, `git prune` and `git maintenance run`
+    Maintenance,
+    /// Metadata and changed files of the commit selected in the log
+    CommitDetails,
+    /// Typing the two refs to compare, `A..B` or `A...B`
+    CompareInput {
+        input: TextInput,
+    },
+    /// Files that differ between two refs
+    Compare,
+    /// Profiles from `[[identities]]` to commit as, opened from commit mode
+    IdentityPicker,
+    /// Recent authors to add as `Co-authored-by:` and other trailers, opened from commit mode
+    TrailerPicker,
+    /// What a commit hook printed when it rejected the commit
+    HookOutput {
+        hook: String,
+        output: String,
+    },
+    /// Every git command run so far, with its exit status and stderr
+    CommandLog,
+    /// The operations done this session and how they ended
+    History,
+    /// Environment checks, the same as `--doctor`
+    Doctor,
+    /// Every git config value with its scope and origin; the common keys can be edited
+    Config,
+    /// Typing the value of `key` to write to the `scope` config
+    ConfigEdit {
+        key: String,
+        scope: ConfigScope,
+        input: TextInput,
+    },
+    /// Choosing a Conventional Commits type or gitmoji for the commit message
+    PrefixPicker,
+    /// Typing the optional scope of the chosen commit type
+    PrefixScope {
+        prefix: String,
+        scope: TextInput,
+    },
+    /// Typing the file to export `target` to with `git archive`; `previous` comes back
+    /// afterwards
+    ArchivePrompt {
+        target: String,
+        input: TextInput,
+        previous: Box<Self>,
+    },
+    /// git or ssh asking for a username, password or passphrase during a remote operation;
+    /// `previous` comes back once it is answered
+    Credential {
+        prompt: String,
+        input: TextInput,
+        previous: Box<Self>,
+    },
+    /// A confirmation for something hard to undo: `expected` has to be typed before Enter
+    TypedConfirm {
+        message: String,
+        expected: String,
+        typed: TextInput,
+        action: ConfirmAction,
+    },
+}
+
+/// The step `git init` with options is at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitStep {
+    /// Typing the name of the initial branch
+    Branch,
+    /// Choosing a `.gitignore` template, or none
+    Gitignore,
+    /// Answering whether to make an initial commit
+    InitialCommit,
+}
+
+/// What the text typed for a branch is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchPrompt {
+    /// `git checkout -b` at a detached HEAD, typed from the file list
+    Create,
+    /// `git branch -m`
+    Rename,
+    /// `git branch --set-upstream-to`; left empty, `--unset-upstream`
+    SetUpstream,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConfirmAction {
     StageAll,
     UnstageAll,
+    Push,
+    DropStash(usize),
+    DiscardFile(String),
+    MergeAbort,
+    Quit,
+    /// `git bisect reset`
+    BisectReset,
+    /// `git reset --hard` to a reflog entry
+    ResetHard(String),
+    /// Detached checkout of a reflog entry
+    Checkout(String),
+    /// Leaving a detached HEAD for the branch it came from
+    SwitchBranch(String),
+    /// `git reset` of the current branch to a commit picked in the log
+    Reset {
+        mode: ResetMode,
+        target: String,
+    },
+    /// `git rebase -i --autosquash` from a commit picked in the log
+    Autosquash(String),
+    /// Commits the draft again with `--no-verify` after a hook rejected it
+    CommitNoVerify,
+    /// `git push --set-upstream <remote> <current branch>`
+    PushSetUpstream {
+        remote: String,
+    },
+    /// `git push --force-with-lease`
+    ForcePush,
+    /// `git branch -d`, or `-D` with `force`
+    DeleteBranch {
+        name: String,
+        force: bool,
+    },
+    /// `git push <remote> --delete <branch>`
+    DeleteRemoteBranch {
+        remote: String,
+        branch: String,
+    },
+    /// `git branch -D` of every local branch whose upstream is gone
+    DeleteGoneBranches(Vec<String>),
+    /// `git prune`, which deletes unreachable objects without a grace period
+    Prune,
+}
+
+/// A visible line of the grouped file list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileRow {
+    Header {
+        section: Section,
+        count: usize,
+        collapsed: bool,
+    },
+    /// A directory in the tree view, `depth` levels below the section. `index` is any file
+    /// inside it, which `App::dir_path` takes the directory's path from.
+    Dir {
+        section: Section,
+        index: usize,
+        depth: usize,
+        count: usize,
+        collapsed: bool,
+    },
+    /// Index into `App::files`, shown under `section`
+    File { section: Section, index: usize },
+}
+
+/// How the files of each section are listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileListView {
+    /// One row per file with its full path
+    #[default]
+    Flat,
+    /// Rows for directories with their files indented below them
+    Tree,
+}
+
+/// The selected file list row by what it shows rather than by position, taken before
+/// `files` changes so the same row can be selected again afterwards
+struct RowAnchor {
+    row: usize,
+    section: Section,
+    /// `None` for a section header and a directory
+    path: Option<String>,
+    /// Path of a directory row
+    dir: Option<String>,
+}
+
+/// Where a bordered list was last drawn and which row is at its top. Recorded while
+/// rendering so mouse clicks can be mapped back to list rows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListArea {
+    pub area: Rect,
+    pub offset: usize,
+}
+
+impl ListArea {
+    /// Records the area and follows the scrolling `ratatui::widgets::List` applies to
+    /// single-line items, since the widget does not expose its offset.
+    pub fn update(&mut self, area: Rect, selected: Option<usize>, len: usize) {
+        self.area = area;
+        let height = usize::from(area.height.saturating_sub(2));
+        let Some(selected) = selected.filter(|_| len > 0 && height > 0) else {
+            self.offset = 0;
+            return;
+        };
+        let selected = selected.min(len - 1);
+        self.offset = self.offset.min(len - 1);
+        if selected >= self.offset + height {
+            self.offset = selected + 1 - height;
+        } else if selected < self.offset {
+            self.offset = selected;
+        }
+    }
+
+    /// List row under the given screen cell, if it is inside the list's borders
+    pub fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        let inner_x = self.area.x + 1..self.area.right().saturating_sub(1);
+        let inner_y = self.area.y + 1..self.area.bottom().saturating_sub(1);
+        if !inner_x.contains(&column) || !inner_y.contains(&row) {
+            return None;
+        }
+        Some(self.offset + usize::from(row - inner_y.start))
+    }
+
+    pub fn contains(&self, column: u16, row: u16) -> bool {
+        rect_contains(self.area, column, row)
+    }
+}
+
+/// The view `--view` opens at startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartView {
+    /// The file list
+    #[default]
+    Status,
+    Log,
+    Branches,
+    Stash,
+}
+
+impl StartView {
+    pub const NAMES: [&'static str; 4] = ["status", "log", "branches", "stash"];
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "status" => Ok(Self::Status),
+            "log" => Ok(Self::Log),
+            "branches" => Ok(Self::Branches),
+            "stash" => Ok(Self::Stash),
+            _ => Err(format!(
+                "Unknown view: {name} (expected one of {})",
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Page-wise and top/bottom moves in the scrolled views
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollJump {
+    PageDown,
+    PageUp,
+    /// Half a page, like `Ctrl+d`/`Ctrl+u` in vim
+    HalfPageDown,
+    HalfPageUp,
+    Top,
+    Bottom,
+}
+
+impl ScrollJump {
+    /// The offset after moving from `offset` with `page` lines on screen, at most `max`
+    pub const fn apply(self, offset: usize, page: usize, max: usize) -> usize {
+        let half = if page > 1 { page / 2 } else { 1 };
+        let next = match self {
+            Self::PageDown => offset.saturating_add(page),
+            Self::PageUp => offset.saturating_sub(page),
+            Self::HalfPageDown => offset.saturating_add(half),
+            Self::HalfPageUp => offset.saturating_sub(half),
+            Self::Top => 0,
+            Self::Bottom => max,
+        };
+        if next < max {
+            next
+        } else {
+            max
+        }
+    }
+
+    const fn is_forward(self) -> bool {
+        matches!(self, Self::PageDown | Self::HalfPageDown | Self::Bottom)
+    }
+}
+
+/// Lines shown inside the borders of `area` and the largest scroll offset that still fills
+/// it with `lines` lines of content
+fn page_bounds(area: Rect, lines: usize) -> (usize, usize) {
+    let page = usize::from(area.height.saturating_sub(2)).max(1);
+    (page, lines.saturating_sub(page))
+}
+
+fn scroll_offset(offset: usize) -> u16 {
+    u16::try_from(offset).unwrap_or(u16::MAX)
+}
+
+fn rect_contains(area: Rect, column: u16, row: u16) -> bool {
+    (area.x..area.right()).contains(&column) && (area.y..area.bottom()).contains(&row)
 }
 
 #[derive(Debug)]
 pub struct App {
-    pub files: Vec<String>,
+    pub files: Vec<FileEntry>,
+    /// Selected row of `file_rows()` (section headers included)
     pub files_state: ListState,
+    /// Changed files that are tracked although an ignore rule matches them
+    pub ignored_tracked: HashSet<String>,
+    pub collapsed_sections: HashSet<Section>,
+    /// Untracked directories (`dir/`) listed file by file instead of as a whole
+    pub expanded_dirs: HashSet<String>,
+    pub file_list_view: FileListView,
+    /// Folded directories of the tree view, by section
+    pub collapsed_dirs: HashSet<(Section, String)>,
+    /// Order of the files within each section of the list
+    pub sort_mode: SortMode,
+    /// Modification times `SortMode::Modified` orders by, read when `files` change
+    pub file_mtimes: HashMap<String, SystemTime>,
+    /// `file_rows()` as last built, until `invalidate_file_rows` drops it
+    file_rows: RefCell<Option<Rc<[FileRow]>>>,
+    /// Sort mode of every repository, persisted to `sort_file`
+    pub sort_modes: SortModes,
+    /// `None` keeps sort mode changes for this session
+    pub sort_file: Option<PathBuf>,
+    /// `git diff --numstat` counts by path, read on every refresh
+    pub staged_stats: HashMap<String, DiffStat>,
+    pub unstaged_stats: HashMap<String, DiffStat>,
     pub input_mode: InputMode,
-    pub commit_message: String,
-    pub stash_message: String,
-    pub status_message: String,
+    pub commit_message: TextInput,
+    /// Sign the next commit; starts from `commit.gpgsign` and can be toggled per commit
+    pub commit_flags: CommitFlags,
+    /// Staged files left out of the next commit, toggled in commit mode
+    pub commit_excluded: HashSet<String>,
+    /// `git diff --cached --stat` of the files the commit being written includes, empty
+    /// when it includes none
+    pub commit_summary: String,
+    /// Rules from the `[commit]` config table, checked before committing
+    pub commit_lint: CommitLintConfig,
+    /// `confirm_quit` from the config file
+    pub confirm_quit: bool,
+    /// `untracked_files` from the config file. Without it a slow status switches to `No`.
+    pub untracked_files: Option<UntrackedFiles>,
+    pub stash_message: TextInput,
+    /// Files the stash being written is limited to; empty stashes every change
+    pub stash_paths: Vec<String>,
+    /// Flags of `git stash push`, kept for the session
+    pub stash_options: StashOptions,
+    /// Messages of the status line; the newest is shown
+    pub status: StatusLine,
     pub current_branch: String,
+    /// Tracking branch and ahead/behind counts, `None` without an upstream
+    pub upstream: Option<UpstreamStatus>,
+    ticks_since_upstream_check: u32,
+    /// The commit HEAD points at, `None` before the first commit
+    pub head: Option<HeadSummary>,
     pub repo_name: String,
     pub preview_scroll: u16,
-    pub preview_content: String,
+    pub preview_content: DiffLines,
+    /// Search typed in the fullscreen preview; `Some` while the prompt is open
+    pub preview_search_input: Option<TextInput>,
+    /// The submitted preview search, the lines it matched and the current match
+    pub preview_query: String,
+    pub preview_matches: Vec<usize>,
+    pub preview_match: Option<usize>,
+    /// How many diff lines the preview panel asked for; reaching it means there may be more
+    pub preview_line_limit: usize,
+    /// Preview diffs already loaded, emptied on every full refresh
+    pub diff_cache: DiffCache,
+    /// The fullscreen preview shows a hex dump of a binary file instead of its diff
+    pub preview_hex: bool,
     pub show_preview_panel: bool,
+    /// Position and size of the preview panel
+    pub layout: LayoutConfig,
+    /// Whether the preview shows unstaged or staged changes (toggled with Tab)
+    pub diff_view: DiffView,
+    /// Context size and whitespace handling of file diffs
+    pub diff_options: DiffOptions,
     pub help_scroll: u16,
+    /// The last `command_log::CAPACITY` git commands, oldest first
+    pub command_log: VecDeque<CommandRecord>,
+    /// Commands still running in the background, with their output so far
+    pub running_commands: Vec<CommandRecord>,
+    pub command_log_scroll: u16,
+    pub history: History,
+    pub log_entries: Vec<String>,
+    pub log_state: ListState,
+    /// The last log search and the abbreviated hashes of the commits it matched
+    pub log_search: String,
+    pub log_matches: HashSet<String>,
+    /// Mode to return to when the fullscreen preview is closed
+    pub preview_return_mode: InputMode,
+    pub worker: Worker,
+    /// When each running background operation started and in which view
+    task_started: Vec<(Task, Instant, Discriminant<InputMode>)>,
+    pub notify: NotifyConfig,
+    pub toast: Option<Toast>,
+    /// Advanced on every tick to animate the spinner of running tasks
+    pub spinner_frame: usize,
+    pub stashes: Vec<StashEntry>,
+    pub stash_state: ListState,
+    /// Progress of a running `git bisect`, `None` when not bisecting
+    pub bisect: Option<BisectStatus>,
+    /// Set while HEAD is detached, for the warning banner and its actions
+    pub detached: Option<DetachedHead>,
+    pub reflog_entries: Vec<ReflogEntry>,
+    pub reflog_state: ListState,
+    pub branches: Vec<BranchEntry>,
+    pub branch_state: ListState,
+    /// Selected entry of `MergeStrategy::ALL` in the merge picker
+    pub merge: Picker,
+    /// Selected entry of `PullStrategy::ALL` in the pull picker
+    pub pull: Picker,
+    /// Selected `.gitignore` template of `git init` with options: 0 for none, then
+    /// `GITIGNORE_TEMPLATES`
+    pub init: Picker,
+    pub maintenance: MaintenanceScreen,
+    /// Results of the diagnostics screen, run each time it opens
+    pub doctor: Vec<Check>,
+    pub commit_details: CommitDetailsScreen,
+    pub compare: CompareScreen,
+    pub config: ConfigScreen,
+    pub identity: IdentityState,
+    pub trailers: TrailerState,
+    /// Screen areas of the last frame, used for mouse handling
+    pub areas: ScreenAreas,
+    pub theme: Theme,
+    /// UI language from config.toml; `None` until set there or from the locale
+    pub language: Option<Language>,
+    /// Repository all git operations run against
+    pub repo: RepoContext,
+    pub recent_repos: RecentRepos,
+    pub repo_state: ListState,
+    /// Where recently opened repositories are persisted; `None` disables persistence
+    pub state_file: Option<PathBuf>,
+    /// Config file that layout changes are written to; `None` keeps them for this session
+    pub config_file: Option<PathBuf>,
+    pub conflict_hunks: Vec<ConflictHunk>,
+    pub conflict_scroll: u16,
+    /// File to open in `$EDITOR`; the event loop owns the terminal, so it picks this up
+    pub pending_editor: Option<PathBuf>,
+    /// Text for the event loop to put on the clipboard through the terminal
+    pub pending_clipboard: Option<String>,
+    pub line_patch: FilePatch,
+    pub line_selection: BTreeSet<usize>,
+    pub line_state: ListState,
+    pub ignore_options: Vec<String>,
+    pub ignore: Picker,
+    pub prefix_options: Vec<CommitPrefix>,
+    pub prefix: Picker,
 }
 
 impl Default for App {
@@ -46,337 +789,4868 @@ impl Default for App {
 }
 
 impl App {
+    /// Opens the repository of the process working directory
     pub fn new() -> Self {
+        Self::with_repo(RepoContext::current())
+    }
+
+    // Long only because it lists every field
+    #[allow(clippy::too_many_lines)]
+    pub fn with_repo(repo: RepoContext) -> Self {
         let mut app = Self {
             files: Vec::new(),
             files_state: ListState::default(),
+            ignored_tracked: HashSet::new(),
+            collapsed_sections: HashSet::new(),
+            expanded_dirs: HashSet::new(),
+            file_list_view: FileListView::default(),
+            collapsed_dirs: HashSet::new(),
+            sort_mode: SortMode::default(),
+            file_mtimes: HashMap::new(),
+            file_rows: RefCell::new(None),
+            sort_modes: SortModes::default(),
+            sort_file: None,
+            staged_stats: HashMap::new(),
+            unstaged_stats: HashMap::new(),
             input_mode: InputMode::Normal,
-            commit_message: String::new(),
-            stash_message: String::new(),
-            status_message: String::from(
-                "準備完了。[h]でヘルプ、[j/k]でファイル移動できます",
-            ),
-            current_branch: GitOperations::get_current_branch()
-                .unwrap_or_else(|_| "unknown".to_string()),
-            repo_name: GitOperations::get_repo_name().unwrap_or_else(|_| "repository".to_string()),
+            commit_message: TextInput::default(),
+            commit_flags: CommitFlags::default(),
+            commit_excluded: HashSet::new(),
+            commit_summary: String::new(),
+            commit_lint: CommitLintConfig::default(),
+            confirm_quit: true,
+            untracked_files: None,
+            stash_message: TextInput::default(),
+            stash_paths: Vec::new(),
+            stash_options: StashOptions::default(),
+            status: StatusLine::new(Msg::Ready.text(Language::default())),
+            current_branch: String::new(),
+            upstream: None,
+            ticks_since_upstream_check: 0,
+            head: None,
+            repo_name: String::new(),
             preview_scroll: 0,
-            preview_content: String::new(),
+            preview_search_input: None,
+            preview_query: String::new(),
+            preview_matches: Vec::new(),
+            preview_match: None,
+            preview_content: DiffLines::default(),
+            preview_line_limit: PREVIEW_PAGE_LINES,
+            diff_cache: DiffCache::default(),
+            preview_hex: false,
             show_preview_panel: true,
+            layout: LayoutConfig::default(),
+            diff_view: DiffView::default(),
+            diff_options: DiffOptions::default(),
             help_scroll: 0,
+            command_log: VecDeque::new(),
+            running_commands: Vec::new(),
+            command_log_scroll: 0,
+            history: History::default(),
+            log_entries: Vec::new(),
+            log_state: ListState::default(),
+            log_search: String::new(),
+            log_matches: HashSet::new(),
+            preview_return_mode: InputMode::Normal,
+            worker: Worker::new(),
+            task_started: Vec::new(),
+            notify: NotifyConfig::default(),
+            toast: None,
+            spinner_frame: 0,
+            stashes: Vec::new(),
+            stash_state: ListState::default(),
+            bisect: None,
+            detached: None,
+            reflog_entries: Vec::new(),
+            reflog_state: ListState::default(),
+            branches: Vec::new(),
+            branch_state: ListState::default(),
+            merge: Picker::default(),
+            pull: Picker::default(),
+            init: Picker::default(),
+            maintenance: MaintenanceScreen::default(),
+            doctor: Vec::new(),
+            commit_details: CommitDetailsScreen::default(),
+            compare: CompareScreen::default(),
+            config: ConfigScreen::default(),
+            identity: IdentityState::default(),
+            trailers: TrailerState::default(),
+            areas: ScreenAreas::default(),
+            theme: Theme::default(),
+            language: None,
+            repo,
+            recent_repos: RecentRepos::default(),
+            repo_state: ListState::default(),
+            state_file: None,
+            config_file: None,
+            conflict_hunks: Vec::new(),
+            conflict_scroll: 0,
+            pending_editor: None,
+            pending_clipboard: None,
+            line_patch: FilePatch::default(),
+            line_selection: BTreeSet::new(),
+            line_state: ListState::default(),
+            ignore_options: Vec::new(),
+            ignore: Picker::default(),
+            prefix_options: Vec::new(),
+            prefix: Picker::default(),
         };
-        app.refresh_files();
-        if !app.files.is_empty() {
-            app.files_state.select(Some(0));
-        }
+        app.load_config();
+        app.load_repo();
         app
     }
 
+    /// Applies config.toml, reporting a broken one in the status line
+    fn load_config(&mut self) {
+        if let Err(e) = Config::load().and_then(|config| self.apply_config(config)) {
+            self.status
+                .error(Msg::ConfigError.fill(self.language(), &[&e]));
+        }
+    }
+
+    fn apply_config(&mut self, config: Config) -> Result<(), String> {
+        self.theme = Theme::from_config(
+            &config.theme,
+            ColorDepth::detect(|name| std::env::var(name).ok()),
+        )?;
+        if let Some(language) = config.language {
+            self.set_language(language);
+        }
+        self.commit_lint = config.commit;
+        self.confirm_quit = config.confirm_quit;
+        self.untracked_files = config.untracked_files;
+        self.layout = config.layout;
+        self.notify = config.notify;
+        self.diff_options = config.diff;
+        self.identity.profiles = config.identities;
+        self.history.file = config.history_file;
+        self.show_preview_panel = config.layout.preview != PreviewPosition::Hidden;
+        Ok(())
+    }
+
+    /// Reads the branch, the repository name and the file list, or shows the `git init` /
+    /// recent repositories screen outside a repository
+    fn load_repo(&mut self) {
+        self.current_branch =
+            GitOperations::get_current_branch(&self.repo).unwrap_or_else(|_| "unknown".to_string());
+        self.repo_name =
+            GitOperations::get_repo_name(&self.repo).unwrap_or_else(|_| "repository".to_string());
+        if GitOperations::get_repo_root(&self.repo).is_some() {
+            self.refresh_files();
+            return;
+        }
+        self.show_no_repository();
+    }
+
+    fn show_no_repository(&mut self) {
+        let dir = self.repo.dir();
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        self.status
+            .info(Msg::NotARepository.fill(self.language(), &[&dir.display()]));
+        self.files.clear();
+        self.invalidate_file_rows();
+        self.clamp_file_selection();
+        self.input_mode = InputMode::NoRepository { dir };
+    }
+
+    /// The language the UI is drawn in
+    pub fn language(&self) -> Language {
+        self.language.unwrap_or_default()
+    }
+
+    /// Switches the UI language, translating the startup message if it is still shown
+    pub fn set_language(&mut self, language: Language) {
+        if self.status.text() == Msg::Ready.text(self.language()) {
+            self.status.info(Msg::Ready.text(language).to_string());
+        }
+        self.language = Some(language);
+    }
+
     pub fn refresh_files(&mut self) {
-        match GitOperations::get_status() {
+        let options = StatusOptions {
+            untracked: self.untracked_files,
+            paths: Vec::new(),
+        };
+        let started = Instant::now();
+        let anchor = self.row_anchor();
+        self.diff_cache.clear();
+        match GitOperations::get_status_with(&self.repo, &options) {
             Ok(files) => {
-                self.files = files;
-                if self.files.is_empty() {
-                    self.files_state = ListState::default();
-                } else if self.files_state.selected().is_none() {
-                    self.files_state.select(Some(0));
-                }
+                self.fall_back_if_status_slow(started.elapsed());
+                self.files = self.expand_untracked_dirs(files);
+                self.invalidate_file_rows();
+                self.staged_stats =
+                    GitOperations::get_diff_stats(&self.repo, DiffView::Staged, &[])
+                        .unwrap_or_default();
+                self.unstaged_stats =
+                    GitOperations::get_diff_stats(&self.repo, DiffView::Unstaged, &[])
+                        .unwrap_or_default();
+                self.files_changed(anchor);
+            },
+            // The repository was removed or moved while the app was open
+            Err(e) if e.is_fatal() => {
+                self.show_no_repository();
+                return;
             },
             Err(e) => {
-                self.status_message = format!("Error: {e}");
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
             },
         }
         // Also refresh branch info
         self.current_branch =
-            GitOperations::get_current_branch().unwrap_or_else(|_| "unknown".to_string());
+            GitOperations::get_current_branch(&self.repo).unwrap_or_else(|_| "unknown".to_string());
+        self.head = GitOperations::get_head_summary(&self.repo).unwrap_or(None);
+        self.refresh_upstream();
+        self.bisect = GitOperations::get_bisect_status(&self.repo).unwrap_or(None);
+        self.detached = GitOperations::get_detached_head(&self.repo).unwrap_or(None);
         self.update_preview();
     }
 
-    pub fn next(&mut self) {
-        if self.files.is_empty() {
+    /// Re-reads only `paths` and patches `files`, which is much cheaper than a full status
+    /// on a large working tree. Untracked files and directories take a full refresh, since
+    /// git lists them differently when asked about single paths.
+    fn refresh_paths(&mut self, paths: Vec<String>) {
+        if paths.iter().any(|path| path.ends_with('/')) {
+            self.refresh_files();
             return;
         }
-        let i = match self.files_state.selected() {
-            Some(i) => {
-                if i >= self.files.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+        let options = StatusOptions {
+            untracked: self.untracked_files,
+            paths,
+        };
+        let entries = match GitOperations::get_status_with(&self.repo, &options) {
+            Ok(entries) if !entries.iter().any(FileEntry::is_untracked) => entries,
+            _ => {
+                self.refresh_files();
+                return;
             },
-            None => 0,
         };
-        self.files_state.select(Some(i));
+        let anchor = self.row_anchor();
+        let paths = options.paths;
+        self.diff_cache.invalidate(&paths);
+        let touched = |path: &String| paths.contains(path);
+        self.files.retain(|entry| {
+            !touched(&entry.path) && !entry.orig_path.as_ref().is_some_and(touched)
+        });
+        self.files.extend(entries);
+        self.files.sort_by(|a, b| a.path.cmp(&b.path));
+        self.invalidate_file_rows();
+        for (view, stats) in [
+            (DiffView::Staged, &mut self.staged_stats),
+            (DiffView::Unstaged, &mut self.unstaged_stats),
+        ] {
+            stats.retain(|path, _| !touched(path));
+            stats.extend(
+                GitOperations::get_diff_stats(&self.repo, view, &paths).unwrap_or_default(),
+            );
+        }
+        self.files_changed(anchor);
         self.update_preview();
     }
 
-    pub fn previous(&mut self) {
-        if self.files.is_empty() {
-            return;
+    /// Replaces the untracked entries under the directories in `expanded_dirs` by every
+    /// file inside them. Directories without untracked files left are forgotten.
+    fn expand_untracked_dirs(&mut self, mut files: Vec<FileEntry>) -> Vec<FileEntry> {
+        let under = |dir: &String, entry: &FileEntry| {
+            entry.is_untracked() && entry.path.starts_with(dir.as_str())
+        };
+        self.expanded_dirs
+            .retain(|dir| files.iter().any(|entry| under(dir, entry)));
+        if self.expanded_dirs.is_empty() {
+            return files;
         }
-        let i = match self.files_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.files.len() - 1
-                } else {
-                    i - 1
-                }
-            },
-            None => 0,
+        let options = StatusOptions {
+            untracked: Some(UntrackedFiles::All),
+            paths: self.expanded_dirs.iter().cloned().collect(),
         };
-        self.files_state.select(Some(i));
-        self.update_preview();
+        match GitOperations::get_status_with(&self.repo, &options) {
+            Ok(entries) => {
+                files.retain(|entry| !self.expanded_dirs.iter().any(|dir| under(dir, entry)));
+                files.extend(entries.into_iter().filter(FileEntry::is_untracked));
+                files.sort_by(|a, b| a.path.cmp(&b.path));
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+        files
     }
 
-    pub fn stage_file(&mut self) {
-        if let Some(i) = self.files_state.selected() {
-            if i < self.files.len() {
-                let file_status = &self.files[i];
-                match GitOperations::stage_file(file_status) {
-                    Ok(message) => {
-                        self.status_message = message;
-                        self.refresh_files();
-                    },
-                    Err(e) => {
-                        self.status_message = format!("Error: {e}");
-                    },
-                }
-            }
+    /// The untracked directory `Enter` expands or collapses at the selection: the selected
+    /// `dir/` itself, or the expanded directory a selected untracked file is listed from
+    pub fn selected_untracked_dir(&self) -> Option<String> {
+        let entry = self.selected_file().filter(|entry| entry.is_untracked())?;
+        if entry.path.ends_with('/') {
+            return Some(entry.path.clone());
         }
+        self.expanded_dirs
+            .iter()
+            .find(|dir| entry.path.starts_with(dir.as_str()))
+            .cloned()
     }
 
-    pub fn stage_all_files(&mut self) {
-        if self.files.is_empty() {
-            self.status_message = String::from("No files to stage");
+    /// Lists the selected untracked directory file by file, or folds it back into one row
+    pub fn toggle_untracked_dir(&mut self) {
+        let Some(dir) = self.selected_untracked_dir() else {
             return;
+        };
+        let expand = self.expanded_dirs.insert(dir.clone());
+        if !expand {
+            self.expanded_dirs.remove(&dir);
         }
-
-        // Check if we need confirmation
-        let has_unstaged = self
+        self.refresh_files();
+        let first = self
             .files
             .iter()
-            .any(|f| f.len() >= 2 && f.chars().next().unwrap_or(' ').is_whitespace());
-
-        if has_unstaged && self.files.len() > 5 {
-            // Many files to stage, ask for confirmation
-            self.input_mode = InputMode::Confirm {
-                message: format!("Stage all {} files? (y/n)", self.files.len()),
-                action: ConfirmAction::StageAll,
-            };
-        } else if !has_unstaged && self.files.len() > 5 {
-            // Many files to unstage, ask for confirmation
-            self.input_mode = InputMode::Confirm {
-                message: format!("Unstage all {} files? (y/n)", self.files.len()),
-                action: ConfirmAction::UnstageAll,
-            };
+            .find(|entry| entry.path.starts_with(&dir))
+            .map(|entry| entry.path.clone());
+        if let Some(path) = first {
+            self.select_path(&path);
+        }
+        let message = if expand {
+            Msg::ShowingDirFiles
         } else {
-            // Proceed without confirmation for small numbers of files
-            self.execute_stage_all();
+            Msg::CollapsedDir
+        };
+        self.status.info(message.fill(self.language(), &[&dir]));
+    }
+
+    /// Keeps the selection and everything derived from `files` in step after they changed
+    fn files_changed(&mut self, anchor: Option<RowAnchor>) {
+        self.read_file_mtimes();
+        let files = &self.files;
+        self.commit_excluded.retain(|path| {
+            files
+                .iter()
+                .any(|entry| entry.is_staged() && &entry.path == path)
+        });
+        self.reselect(anchor);
+        self.refresh_ignored_tracked();
+        if self.input_mode == InputMode::Commit {
+            self.refresh_commit_summary();
         }
     }
 
-    fn execute_stage_all(&mut self) {
-        match GitOperations::stage_all_files(&self.files) {
-            Ok(message) => {
-                self.status_message = message;
-                self.refresh_files();
-            },
-            Err(e) => {
-                self.status_message = format!("Error: {e}");
-            },
+    /// Without an `untracked_files` setting, a slow status leaves out untracked files from
+    /// then on (`-uno`), which is what makes status slow on most large working trees
+    fn fall_back_if_status_slow(&mut self, elapsed: Duration) {
+        if self.untracked_files.is_none() && elapsed >= SLOW_STATUS {
+            self.untracked_files = Some(UntrackedFiles::No);
+            self.status
+                .info(Msg::SlowStatus.fill(self.language(), &[&elapsed.as_millis()]));
         }
     }
 
-    pub fn stash_changes(&mut self) {
-        let message = if self.stash_message.trim().is_empty() {
-            None
+    fn read_file_mtimes(&mut self) {
+        self.file_mtimes = if self.sort_mode.uses_mtime() {
+            sort::modification_times(&self.files, |path| self.repo.path(path))
         } else {
-            Some(self.stash_message.as_str())
+            HashMap::new()
         };
+    }
 
-        match GitOperations::stash_changes(message) {
-            Ok(result_message) => {
-                self.status_message = result_message;
-                self.stash_message.clear();
-                self.input_mode = InputMode::Normal;
-                self.refresh_files();
-            },
-            Err(e) => {
-                self.status_message = format!("Error: {e}");
-            },
+    /// Switches to the next sort mode, keeping the selected file selected, and remembers
+    /// it for this repository
+    pub fn cycle_sort_mode(&mut self) {
+        let anchor = self.row_anchor();
+        self.sort_mode = self.sort_mode.next();
+        self.read_file_mtimes();
+        self.invalidate_file_rows();
+        self.reselect(anchor);
+        self.update_preview();
+        let label = self.sort_mode.label().text(self.language());
+        self.status
+            .info(Msg::SortedBy.fill(self.language(), &[&label]));
+        let Some(root) = GitOperations::get_repo_root(&self.repo) else {
+            return;
+        };
+        self.sort_modes.set(&root, self.sort_mode);
+        if let Some(path) = &self.sort_file {
+            if let Err(e) = self.sort_modes.save(path) {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            }
         }
     }
 
-    pub fn list_stashes(&mut self) {
-        match GitOperations::list_stashes() {
-            Ok(message) => {
-                self.status_message = message;
-            },
-            Err(e) => {
-                self.status_message = format!("Error: {e}");
-            },
-        }
+    /// Loads the sort modes from `path`, uses the one saved for this repository and keeps
+    /// the file updated from now on
+    pub fn enable_sort_modes(&mut self, path: PathBuf) {
+        self.sort_modes = SortModes::load(&path);
+        self.sort_file = Some(path);
+        let anchor = self.row_anchor();
+        self.sort_mode = self.saved_sort_mode();
+        self.read_file_mtimes();
+        self.invalidate_file_rows();
+        self.reselect(anchor);
     }
 
-    pub fn apply_latest_stash(&mut self) {
-        match GitOperations::apply_latest_stash() {
-            Ok(message) => {
-                self.status_message = message;
-                self.refresh_files();
-            },
-            Err(e) => {
-                self.status_message = format!("Error: {e}");
-            },
-        }
+    fn saved_sort_mode(&self) -> SortMode {
+        GitOperations::get_repo_root(&self.repo)
+            .map(|root| self.sort_modes.get(&root))
+            .unwrap_or_default()
     }
 
-    pub fn commit(&mut self) {
-        if self.commit_message.trim().is_empty() {
-            self.status_message = String::from("Commit message cannot be empty");
-            return;
-        }
+    /// Line counts shown next to a file in `section`
+    pub fn diff_stat(&self, entry: &FileEntry, section: Section) -> Option<DiffStat> {
+        let stats = match section {
+            Section::Staged => &self.staged_stats,
+            Section::Unstaged => &self.unstaged_stats,
+            Section::Conflicted | Section::Untracked => return None,
+        };
+        stats.get(&entry.path).copied()
+    }
 
-        match GitOperations::commit(&self.commit_message) {
-            Ok(message) => {
-                self.status_message = message;
-                self.commit_message.clear();
+    fn refresh_ignored_tracked(&mut self) {
+        let tracked: Vec<&str> = self
+            .files
+            .iter()
+            .filter(|entry| !entry.is_untracked())
+            .map(|entry| entry.path.as_str())
+            .collect();
+        self.ignored_tracked = GitOperations::check_ignore(&self.repo, &tracked)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+    }
+
+    /// Re-reads the tracking branch, e.g. after a fetch or a commit elsewhere
+    pub fn refresh_upstream(&mut self) {
+        self.upstream = GitOperations::get_upstream_status(&self.repo).unwrap_or(None);
+        self.ticks_since_upstream_check = 0;
+    }
+
+    /// Loads the recent repositories from `path`, records the current one and keeps the file
+    /// updated from now on
+    pub fn enable_recent_repos(&mut self, path: PathBuf) {
+        self.recent_repos = RecentRepos::load(&path);
+        self.state_file = Some(path);
+        self.remember_repo();
+        if matches!(self.input_mode, InputMode::NoRepository { .. }) {
+            self.select_first_repo();
+        }
+    }
+
+    fn remember_repo(&mut self) {
+        let Some(root) = GitOperations::get_repo_root(&self.repo) else {
+            return;
+        };
+        self.recent_repos.push(&root);
+        if let Some(path) = &self.state_file {
+            if let Err(e) = self.recent_repos.save(path) {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            }
+        }
+    }
+
+    pub fn show_repo_switcher(&mut self) {
+        self.input_mode = InputMode::RepoSwitcher;
+        self.select_first_repo();
+    }
+
+    fn select_first_repo(&mut self) {
+        let selected = (!self.recent_repos.repos.is_empty()).then_some(0);
+        self.repo_state.select(selected);
+    }
+
+    /// `git init` in the directory the app was started in, then opens the new repository
+    pub fn init_repo(&mut self) {
+        match GitOperations::init_repo(&self.repo) {
+            Ok(repo) => {
+                self.input_mode = InputMode::Normal;
+                self.switch_repo(repo);
+                self.status.success(
+                    Msg::InitializedEmpty.fill(self.language(), &[&self.repo.dir().display()]),
+                );
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    /// `git init` with options: asks for the initial branch, prefilled with
+    /// `init.defaultBranch` or `main`, then a `.gitignore` template and an initial commit
+    pub fn start_init_setup(&mut self) {
+        let InputMode::NoRepository { dir } = &self.input_mode else {
+            return;
+        };
+        let branch = GitOperations::get_config(&self.repo, "init.defaultBranch")
+            .unwrap_or_else(|| String::from("main"));
+        self.init.state.select(Some(0));
+        self.input_mode = InputMode::InitSetup {
+            dir: dir.clone(),
+            step: InitStep::Branch,
+            branch: TextInput::new(branch),
+        };
+    }
+
+    /// Moves on to the next step of `git init` with options
+    pub fn next_init_step(&mut self) {
+        if let InputMode::InitSetup { step, .. } = &mut self.input_mode {
+            *step = match step {
+                InitStep::Branch => InitStep::Gitignore,
+                InitStep::Gitignore | InitStep::InitialCommit => InitStep::InitialCommit,
+            };
+        }
+    }
+
+    /// Goes back a step, or to the no-repository screen from the first one
+    pub fn previous_init_step(&mut self) {
+        let InputMode::InitSetup { dir, step, .. } = &mut self.input_mode else {
+            return;
+        };
+        match step {
+            InitStep::Branch => {
+                let dir = std::mem::take(dir);
+                self.input_mode = InputMode::NoRepository { dir };
+            },
+            InitStep::Gitignore => *step = InitStep::Branch,
+            InitStep::InitialCommit => *step = InitStep::Gitignore,
+        }
+    }
+
+    pub fn next_init_template(&mut self) {
+        if let Some(i) = self.init.state.selected() {
+            if i < GITIGNORE_TEMPLATES.len() {
+                self.init.state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_init_template(&mut self) {
+        if let Some(i) = self.init.state.selected() {
+            self.init.state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    /// Runs `git init` with the options chosen so far and opens the new repository. A
+    /// failure after `git init`, such as a commit without `user.name`, still opens it.
+    pub fn finish_init_setup(&mut self, initial_commit: bool) {
+        let InputMode::InitSetup { branch, .. } = &self.input_mode else {
+            return;
+        };
+        let template = self
+            .init
+            .state
+            .selected()
+            .and_then(|i| i.checked_sub(1))
+            .and_then(|i| GITIGNORE_TEMPLATES.get(i));
+        let options = InitOptions {
+            branch: branch.as_str().trim().to_string(),
+            gitignore: template,
+            initial_commit,
+        };
+        match GitOperations::init_with_options(&self.repo, &options) {
+            Ok(repo) => {
+                self.input_mode = InputMode::Normal;
+                self.switch_repo(repo);
+                let language = self.language();
+                let gitignore = template.map_or_else(String::new, |template| {
+                    Msg::InitGitignore.fill(language, &[&template.name])
+                });
+                let commit = if initial_commit {
+                    Msg::InitCommit.text(language)
+                } else {
+                    ""
+                };
+                self.status.success(Msg::InitializedWith.fill(
+                    language,
+                    &[
+                        &self.repo.dir().display(),
+                        &self.current_branch,
+                        &gitignore,
+                        &commit,
+                    ],
+                ));
+            },
+            Err(e) => {
+                if let Ok(repo) = self.repo.reopen() {
+                    self.input_mode = InputMode::Normal;
+                    self.switch_repo(repo);
+                }
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    pub fn exit_repo_switcher(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn next_repo(&mut self) {
+        if let Some(i) = self.repo_state.selected() {
+            if i + 1 < self.recent_repos.repos.len() {
+                self.repo_state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_repo(&mut self) {
+        if let Some(i) = self.repo_state.selected() {
+            self.repo_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn switch_to_selected_repo(&mut self) {
+        let Some(path) = self
+            .repo_state
+            .selected()
+            .and_then(|i| self.recent_repos.repos.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        match RepoContext::open(&path) {
+            Ok(repo) => {
+                self.input_mode = InputMode::Normal;
+                self.switch_repo(repo);
+            },
+            // Outside a repository there is no file list to fall back to
+            Err(e) => {
+                if self.input_mode == InputMode::RepoSwitcher {
+                    self.input_mode = InputMode::Normal;
+                }
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    /// Points the app at another repository and reloads everything shown
+    pub fn switch_repo(&mut self, repo: RepoContext) {
+        self.repo = repo;
+        self.repo_name =
+            GitOperations::get_repo_name(&self.repo).unwrap_or_else(|_| "repository".to_string());
+        self.files.clear();
+        self.files_state.select(None);
+        self.collapsed_sections.clear();
+        self.collapsed_dirs.clear();
+        self.expanded_dirs.clear();
+        self.stashes.clear();
+        self.log_entries.clear();
+        self.status
+            .info(Msg::Opened.fill(self.language(), &[&self.repo_name]));
+        self.remember_repo();
+        self.sort_mode = self.saved_sort_mode();
+        self.invalidate_file_rows();
+        self.refresh_files();
+    }
+
+    /// Rows of the file list: a header per non-empty section followed by its files, in
+    /// `sort_mode` order, unless the section is collapsed. The tree view puts the files
+    /// under rows for their directories. The rows are built once and kept until
+    /// `invalidate_file_rows` is called.
+    pub fn file_rows(&self) -> Rc<[FileRow]> {
+        self.file_rows
+            .borrow_mut()
+            .get_or_insert_with(|| self.build_file_rows().into())
+            .clone()
+    }
+
+    /// Drops the cached `file_rows()`; called whenever `files`, `sort_mode`, the view or
+    /// what is collapsed changes
+    pub fn invalidate_file_rows(&mut self) {
+        *self.file_rows.get_mut() = None;
+    }
+
+    fn build_file_rows(&self) -> Vec<FileRow> {
+        let mut rows = Vec::new();
+        for section in Section::ALL {
+            let mut indices: Vec<usize> = self
+                .files
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.in_section(section))
+                .map(|(i, _)| i)
+                .collect();
+            if indices.is_empty() {
+                continue;
+            }
+            sort::sort_section(self.sort_mode, &self.files, section, &mut indices, |path| {
+                self.file_mtimes.get(path).copied()
+            });
+            let collapsed = self.collapsed_sections.contains(&section);
+            rows.push(FileRow::Header {
+                section,
+                count: indices.len(),
+                collapsed,
+            });
+            if collapsed {
+                continue;
+            }
+            if self.file_list_view == FileListView::Tree {
+                let folded = |dir: &str| self.collapsed_dirs.contains(&(section, dir.to_string()));
+                DirNode::build(&self.files, &indices).flatten(section, "", &folded, &mut rows);
+            } else {
+                rows.extend(
+                    indices
+                        .into_iter()
+                        .map(|index| FileRow::File { section, index }),
+                );
+            }
+        }
+        rows
+    }
+
+    /// Path of a directory row, with a trailing slash
+    pub fn dir_path(&self, index: usize, depth: usize) -> String {
+        file_tree::ancestor(&self.files[index].path, depth)
+    }
+
+    /// The files a header or directory row stands for, or the file of a file row
+    fn row_files(&self, row: FileRow) -> Vec<&FileEntry> {
+        let (section, dir) = match row {
+            FileRow::File { index, .. } => return vec![&self.files[index]],
+            FileRow::Header { section, .. } => (section, None),
+            FileRow::Dir {
+                section,
+                index,
+                depth,
+                ..
+            } => (section, Some(self.dir_path(index, depth))),
+        };
+        self.files
+            .iter()
+            .filter(|entry| entry.in_section(section))
+            .filter(|entry| {
+                dir.as_ref()
+                    .map_or(true, |dir| entry.path.starts_with(dir.as_str()))
+            })
+            .collect()
+    }
+
+    /// Switches between the flat list and the directory tree, keeping the selected file
+    pub fn toggle_tree_view(&mut self) {
+        let anchor = self.row_anchor();
+        self.file_list_view = match self.file_list_view {
+            FileListView::Flat => FileListView::Tree,
+            FileListView::Tree => FileListView::Flat,
+        };
+        self.invalidate_file_rows();
+        self.reselect(anchor);
+        self.update_preview();
+        let message = match self.file_list_view {
+            FileListView::Flat => Msg::ShowingList,
+            FileListView::Tree => Msg::ShowingTree,
+        };
+        self.status.info(message.text(self.language()));
+    }
+
+    /// Selects the changed file at `path` (relative to the repository root), expanding its
+    /// section and directories if they are collapsed. Returns false when the file has no
+    /// changes.
+    pub fn select_path(&mut self, path: &str) -> bool {
+        let path = path.trim_start_matches("./");
+        let Some(index) = self
+            .files
+            .iter()
+            .position(|entry| entry.paths().any(|p| p == path))
+        else {
+            self.status
+                .info(Msg::NoChangesIn.fill(self.language(), &[&path]));
+            return false;
+        };
+        for section in Section::ALL {
+            if self.files[index].in_section(section) {
+                self.collapsed_sections.remove(&section);
+            }
+        }
+        let file = &self.files[index].path;
+        self.collapsed_dirs
+            .retain(|(_, dir)| !file.starts_with(dir.as_str()));
+        self.invalidate_file_rows();
+        let row = self
+            .file_rows()
+            .iter()
+            .position(|row| matches!(row, FileRow::File { index: i, .. } if *i == index));
+        self.files_state.select(row);
+        self.update_preview();
+        true
+    }
+
+    /// Opens the view given with `--view`
+    pub fn open_start_view(&mut self, view: StartView) {
+        match view {
+            StartView::Status => {},
+            StartView::Log => self.show_log(),
+            StartView::Branches => self.show_branches(),
+            StartView::Stash => self.list_stashes(),
+        }
+    }
+
+    pub fn selected_row(&self) -> Option<FileRow> {
+        self.file_rows().get(self.files_state.selected()?).copied()
+    }
+
+    pub fn selected_file(&self) -> Option<&FileEntry> {
+        match self.selected_row()? {
+            FileRow::File { index, .. } => self.files.get(index),
+            FileRow::Header { .. } | FileRow::Dir { .. } => None,
+        }
+    }
+
+    pub fn selected_section(&self) -> Option<Section> {
+        match self.selected_row()? {
+            FileRow::Header { section, .. }
+            | FileRow::Dir { section, .. }
+            | FileRow::File { section, .. } => Some(section),
+        }
+    }
+
+    fn row_anchor(&self) -> Option<RowAnchor> {
+        let row = self.files_state.selected()?;
+        Some(match self.selected_row()? {
+            FileRow::Header { section, .. } => RowAnchor {
+                row,
+                section,
+                path: None,
+                dir: None,
+            },
+            FileRow::Dir {
+                section,
+                index,
+                depth,
+                ..
+            } => RowAnchor {
+                row,
+                section,
+                path: None,
+                dir: Some(self.dir_path(index, depth)),
+            },
+            FileRow::File { section, index } => RowAnchor {
+                row,
+                section,
+                path: Some(self.files[index].path.clone()),
+                dir: None,
+            },
+        })
+    }
+
+    /// Selects the row `anchor` was taken from: the same file, in the same section when it
+    /// is still listed there (staging moves it to another one), or the same header or
+    /// directory. When the file is gone its neighbour at the old position is selected instead.
+    fn reselect(&mut self, anchor: Option<RowAnchor>) {
+        let Some(anchor) = anchor else {
+            self.clamp_file_selection();
+            return;
+        };
+        let rows = self.file_rows();
+        let shows = |row: &FileRow, any_section: bool| match (*row, &anchor.path, &anchor.dir) {
+            (FileRow::Header { section, .. }, None, None) => section == anchor.section,
+            (
+                FileRow::Dir {
+                    section,
+                    index,
+                    depth,
+                    ..
+                },
+                None,
+                Some(dir),
+            ) => section == anchor.section && self.dir_path(index, depth) == *dir,
+            (FileRow::File { section, index }, Some(path), _) => {
+                self.files[index].path == *path && (any_section || section == anchor.section)
+            },
+            _ => false,
+        };
+        let found = rows
+            .iter()
+            .position(|row| shows(row, false))
+            .or_else(|| rows.iter().position(|row| shows(row, true)));
+        if let Some(row) = found {
+            self.files_state.select(Some(row));
+            return;
+        }
+        self.files_state.select(Some(anchor.row));
+        self.clamp_file_selection();
+        // A file's neighbour is the next file, not the header of the section after it
+        if anchor.path.is_some() {
+            if let Some(i) = self.files_state.selected() {
+                if matches!(rows[i], FileRow::Header { .. }) {
+                    let next = rows.get(i + 1).map(|_| i + 1);
+                    self.files_state
+                        .select(next.or_else(|| i.checked_sub(1)).or(Some(i)));
+                }
+            }
+        }
+    }
+
+    /// Keeps the selection inside the current rows, preferring the first file over a header
+    fn clamp_file_selection(&mut self) {
+        let rows = self.file_rows();
+        let selected = match self.files_state.selected() {
+            _ if rows.is_empty() => None,
+            Some(i) => Some(i.min(rows.len() - 1)),
+            None => Some(usize::from(rows.len() > 1)),
+        };
+        self.files_state.select(selected);
+    }
+
+    pub fn next(&mut self) {
+        let len = self.file_rows().len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.files_state.selected() {
+            Some(i) if i + 1 < len => i + 1,
+            _ => 0,
+        };
+        self.files_state.select(Some(i));
+        self.update_preview();
+    }
+
+    pub fn previous(&mut self) {
+        let len = self.file_rows().len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.files_state.selected() {
+            Some(i) if i > 0 && i < len => i - 1,
+            _ => len - 1,
+        };
+        self.files_state.select(Some(i));
+        self.update_preview();
+    }
+
+    /// Collapses or expands the section of the selected row, or the selected directory of
+    /// the tree view
+    pub fn toggle_section(&mut self) {
+        if let Some(FileRow::Dir {
+            section,
+            index,
+            depth,
+            ..
+        }) = self.selected_row()
+        {
+            let dir = (section, self.dir_path(index, depth));
+            if !self.collapsed_dirs.remove(&dir) {
+                self.collapsed_dirs.insert(dir);
+            }
+            self.invalidate_file_rows();
+            self.update_preview();
+            return;
+        }
+        let Some(section) = self.selected_section() else {
+            return;
+        };
+        if !self.collapsed_sections.remove(&section) {
+            self.collapsed_sections.insert(section);
+        }
+        self.invalidate_file_rows();
+        // Keep the cursor on the section header so the toggle can be undone
+        let header = self
+            .file_rows()
+            .iter()
+            .position(|row| matches!(row, FileRow::Header { section: s, .. } if *s == section));
+        self.files_state.select(header);
+        self.update_preview();
+    }
+
+    /// Stages or unstages the selected file depending on the section it is listed in.
+    /// On a section header, the whole section is staged or unstaged, and on a directory of
+    /// the tree view its files in that section.
+    pub fn stage_file(&mut self) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+        // Paths whose status can change, when only one file is touched
+        let mut touched = None;
+        let result = match row {
+            FileRow::File { section, index } => {
+                let entry = &self.files[index];
+                touched = Some(entry.paths().cloned().collect());
+                if section == Section::Staged {
+                    GitOperations::unstage_entry(&self.repo, entry)
+                } else {
+                    GitOperations::stage_entry(&self.repo, entry)
+                }
+            },
+            FileRow::Header { section, count, .. } | FileRow::Dir { section, count, .. } => self
+                .row_files(row)
+                .into_iter()
+                .try_for_each(|entry| {
+                    if section == Section::Staged {
+                        GitOperations::unstage_entry(&self.repo, entry).map(drop)
+                    } else {
+                        GitOperations::stage_entry(&self.repo, entry).map(drop)
+                    }
+                })
+                .map(|()| {
+                    let message = if section == Section::Staged {
+                        Msg::UnstagedFiles
+                    } else {
+                        Msg::StagedFiles
+                    };
+                    Message::new(message, &[&count])
+                }),
+        };
+        match result {
+            Ok(message) => {
+                self.status.success(message.text(self.language()));
+                match touched {
+                    Some(paths) => self.refresh_paths(paths),
+                    None => self.refresh_files(),
+                }
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    pub fn stage_all_files(&mut self) {
+        if self.files.is_empty() {
+            self.status.info(Msg::NoFilesToStage.text(self.language()));
+            return;
+        }
+
+        // Check if we need confirmation
+        let has_unstaged = self
+            .files
+            .iter()
+            .any(|f| f.is_unstaged() || f.is_untracked());
+
+        if has_unstaged && self.files.len() > 5 {
+            // Many files to stage, ask for confirmation
+            self.input_mode = InputMode::Confirm {
+                message: Msg::ConfirmStageAll.fill(self.language(), &[&self.files.len()]),
+                action: ConfirmAction::StageAll,
+            };
+        } else if !has_unstaged && self.files.len() > 5 {
+            // Many files to unstage, ask for confirmation
+            self.input_mode = InputMode::Confirm {
+                message: Msg::ConfirmUnstageAll.fill(self.language(), &[&self.files.len()]),
+                action: ConfirmAction::UnstageAll,
+            };
+        } else {
+            // Proceed without confirmation for small numbers of files
+            self.execute_stage_all();
+        }
+    }
+
+    /// Runs `git add -N` on the selected untracked file so its lines can be staged one by
+    /// one, or makes an intent-to-add file untracked again
+    pub fn toggle_intent_to_add(&mut self) {
+        let Some(entry) = self.selected_file() else {
+            self.status.info(Msg::NoFileSelected.text(self.language()));
+            return;
+        };
+        let path = entry.path.clone();
+        let result = if entry.is_untracked() {
+            GitOperations::intent_to_add(&self.repo, &entry.path)
+        } else if entry.is_intent_to_add() {
+            GitOperations::undo_intent_to_add(&self.repo, &entry.path)
+        } else {
+            self.status
+                .info(Msg::IntentToAddUntrackedOnly.text(self.language()));
+            return;
+        };
+        match result {
+            Ok(message) => self.status.success(message.text(self.language())),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+        self.refresh_paths(vec![path]);
+    }
+
+    /// Asks for confirmation before discarding the selected file's working tree changes
+    pub fn discard_selected_file(&mut self) {
+        let Some(entry) = self.selected_file() else {
+            self.status.info(Msg::NoFileSelected.text(self.language()));
+            return;
+        };
+        let message = if entry.is_untracked() || entry.is_intent_to_add() {
+            Msg::ConfirmDeleteUntracked
+        } else {
+            Msg::ConfirmDiscard
+        }
+        .fill(self.language(), &[&entry.path]);
+        let action = ConfirmAction::DiscardFile(entry.path.clone());
+        self.input_mode = InputMode::Confirm { message, action };
+    }
+
+    /// Opens the conflict view for the selected file when it has unmerged changes
+    pub fn show_conflict(&mut self) {
+        let Some(entry) = self.selected_file() else {
+            self.status.info(Msg::NoFileSelected.text(self.language()));
+            return;
+        };
+        if !entry.is_conflicted() {
+            self.status
+                .info(Msg::NoConflictsIn.fill(self.language(), &[&entry.path]));
+            return;
+        }
+        self.input_mode = InputMode::Conflict {
+            file_path: entry.path.clone(),
+        };
+        self.reload_conflict();
+    }
+
+    /// Re-reads the conflict markers, e.g. after the file was edited
+    pub fn reload_conflict(&mut self) {
+        let InputMode::Conflict { file_path } = &self.input_mode else {
+            return;
+        };
+        self.conflict_scroll = 0;
+        match GitOperations::get_conflicts(&self.repo, file_path) {
+            Ok(hunks) => self.conflict_hunks = hunks,
+            Err(e) => {
+                self.conflict_hunks.clear();
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+        self.refresh_files();
+    }
+
+    pub fn exit_conflict(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.conflict_hunks.clear();
+        self.conflict_scroll = 0;
+    }
+
+    pub fn scroll_conflict_up(&mut self) {
+        self.conflict_scroll = self.conflict_scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_conflict_down(&mut self) {
+        self.conflict_scroll += 1;
+    }
+
+    /// Keeps one side of the conflicted file and marks it resolved
+    pub fn resolve_conflict(&mut self, side: ConflictSide) {
+        let InputMode::Conflict { file_path } = &self.input_mode else {
+            return;
+        };
+        let path = file_path.clone();
+        match GitOperations::resolve_conflict(&self.repo, file_path, side) {
+            Ok(message) => {
+                self.status.success(message.text(self.language()));
+                self.exit_conflict();
+                self.refresh_paths(vec![path]);
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    pub fn edit_conflict(&mut self) {
+        if let InputMode::Conflict { file_path } = &self.input_mode {
+            self.pending_editor = Some(self.repo.path(file_path));
+        }
+    }
+
+    /// Opens the line staging editor for the selected file. Files in the staged section
+    /// have their lines unstaged instead.
+    pub fn start_line_staging(&mut self) {
+        let Some(FileRow::File { section, index }) = self.selected_row() else {
+            self.status.info(Msg::NoFileSelected.text(self.language()));
+            return;
+        };
+        let view = match section {
+            Section::Staged => DiffView::Staged,
+            Section::Unstaged => DiffView::Unstaged,
+            Section::Untracked => {
+                self.status
+                    .info(Msg::LineStageUntracked.text(self.language()));
+                return;
+            },
+            Section::Conflicted => {
+                self.status
+                    .info(Msg::LineStageModifiedOnly.text(self.language()));
+                return;
+            },
+        };
+        self.input_mode = InputMode::LineStage {
+            file_path: self.files[index].path.clone(),
+            view,
+        };
+        self.reload_line_patch();
+    }
+
+    /// Reads the file's diff again and clears the selection
+    fn reload_line_patch(&mut self) {
+        let InputMode::LineStage { file_path, view } = &self.input_mode else {
+            return;
+        };
+        self.line_selection.clear();
+        match GitOperations::get_patch(&self.repo, file_path, *view) {
+            Ok(patch) if patch.has_changes() => {
+                self.line_patch = patch;
+                let first =
+                    (0..self.line_patch.lines.len()).find(|&i| self.line_patch.is_change(i));
+                self.line_state.select(first);
+            },
+            Ok(_) => self.exit_line_staging(),
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+                self.exit_line_staging();
+            },
+        }
+    }
+
+    pub fn exit_line_staging(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.line_patch = FilePatch::default();
+        self.line_selection.clear();
+        self.line_state.select(None);
+    }
+
+    /// Moves the cursor to the next added or removed line
+    pub fn next_line(&mut self) {
+        let current = self.line_state.selected().unwrap_or(0);
+        if let Some(next) =
+            (current + 1..self.line_patch.lines.len()).find(|&i| self.line_patch.is_change(i))
+        {
+            self.line_state.select(Some(next));
+        }
+    }
+
+    pub fn previous_line(&mut self) {
+        let current = self.line_state.selected().unwrap_or(0);
+        if let Some(previous) = (0..current).rev().find(|&i| self.line_patch.is_change(i)) {
+            self.line_state.select(Some(previous));
+        }
+    }
+
+    pub fn toggle_line(&mut self) {
+        let Some(i) = self.line_state.selected() else {
+            return;
+        };
+        if self.line_patch.is_change(i) && !self.line_selection.remove(&i) {
+            self.line_selection.insert(i);
+        }
+    }
+
+    /// Selects every change of the current hunk, or clears them when all are selected
+    pub fn toggle_hunk(&mut self) {
+        let Some(i) = self.line_state.selected() else {
+            return;
+        };
+        let lines = self.line_patch.hunk_changes(i);
+        if lines.iter().all(|line| self.line_selection.contains(line)) {
+            for line in &lines {
+                self.line_selection.remove(line);
+            }
+        } else {
+            self.line_selection.extend(lines);
+        }
+    }
+
+    pub fn apply_line_selection(&mut self) {
+        let InputMode::LineStage { file_path, view } = &self.input_mode else {
+            return;
+        };
+        let (path, view) = (file_path.clone(), *view);
+        match GitOperations::stage_lines(&self.repo, &self.line_patch, &self.line_selection, view) {
+            Ok(message) => {
+                self.status.success(message.text(self.language()));
+                self.refresh_paths(vec![path]);
+                self.reload_line_patch();
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    /// Offers `.gitignore` patterns for the selected untracked file
+    pub fn show_ignore_chooser(&mut self) {
+        let Some(entry) = self.selected_file() else {
+            self.status.info(Msg::NoFileSelected.text(self.language()));
+            return;
+        };
+        if !entry.is_untracked() {
+            self.status
+                .info(Msg::IgnoreUntrackedOnly.text(self.language()));
+            return;
+        }
+        let file_path = entry.path.clone();
+        self.ignore_options = ignore_candidates(&file_path);
+        self.input_mode = InputMode::IgnoreChooser { file_path };
+        self.ignore.state.select(Some(0));
+    }
+
+    pub fn exit_ignore_chooser(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.ignore_options.clear();
+    }
+
+    pub fn next_ignore_option(&mut self) {
+        if let Some(i) = self.ignore.state.selected() {
+            if i + 1 < self.ignore_options.len() {
+                self.ignore.state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_ignore_option(&mut self) {
+        if let Some(i) = self.ignore.state.selected() {
+            self.ignore.state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    /// Appends the chosen pattern to `.gitignore` and refreshes the file list
+    pub fn add_selected_ignore_pattern(&mut self) {
+        let Some(pattern) = self
+            .ignore
+            .state
+            .selected()
+            .and_then(|i| self.ignore_options.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        self.exit_ignore_chooser();
+        match GitOperations::add_to_gitignore(&self.repo, &pattern) {
+            Ok(message) => self.status.success(message.text(self.language())),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+        self.refresh_files();
+    }
+
+    pub fn abort_merge(&mut self) {
+        let operation = if GitOperations::is_rebasing(&self.repo) {
+            "rebase"
+        } else {
+            "merge"
+        };
+        self.input_mode = InputMode::Confirm {
+            message: Msg::ConfirmAbortMerge.fill(self.language(), &[&operation]),
+            action: ConfirmAction::MergeAbort,
+        };
+    }
+
+    pub fn continue_merge(&mut self) {
+        if self.files.iter().any(FileEntry::is_conflicted) {
+            self.status
+                .info(Msg::ResolveBeforeContinue.text(self.language()));
+            return;
+        }
+        let result = GitOperations::merge_continue(&self.repo);
+        self.refresh_files();
+        let conflicts = self
+            .files
+            .iter()
+            .filter(|entry| entry.is_conflicted())
+            .count();
+        match result {
+            Ok(message) => self.status.success(message.text(self.language())),
+            // A rebase replaying the next commit can stop at new conflicts
+            Err(_) if conflicts > 0 => {
+                self.status
+                    .error(Msg::StoppedAgain.fill(self.language(), &[&conflicts]));
+                self.select_first_conflict();
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    /// Runs on the worker: `git add .` in a large working tree can take a while
+    fn execute_stage_all(&mut self) {
+        let task = Task::StageAll;
+        if self.worker.is_running(&task) {
+            self.status
+                .info(Msg::AlreadyRunning.fill(self.language(), &[&task.label()]));
+            return;
+        }
+        self.status
+            .info(Msg::RunningTask.fill(self.language(), &[&task.label()]));
+        self.track_task(&task);
+        let (repo, language) = (self.repo.clone(), self.language());
+        let files = self.files.clone();
+        self.worker.spawn(task, move || {
+            GitOperations::stage_all_files(&repo, &files).map(|message| message.text(language))
+        });
+    }
+
+    pub fn stash_changes(&mut self) {
+        let message = if self.stash_message.trim().is_empty() {
+            None
+        } else {
+            Some(self.stash_message.as_str())
+        };
+
+        let result = if self.stash_paths.is_empty() {
+            GitOperations::stash_changes(&self.repo, message, self.stash_options)
+        } else {
+            GitOperations::stash_paths(&self.repo, &self.stash_paths, message, self.stash_options)
+        };
+        match result {
+            Ok(result_message) => {
+                self.status.success(result_message.text(self.language()));
+                self.stash_message.clear();
+                self.stash_paths.clear();
+                self.input_mode = InputMode::Normal;
+                self.refresh_files();
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    /// Starts a stash limited to the selected file, or to every file of the section or
+    /// directory when its row is selected
+    pub fn start_stash_selected(&mut self) {
+        let Some(row) = self.selected_row() else {
+            self.status.info(Msg::NoFileSelected.text(self.language()));
+            return;
+        };
+        let paths: Vec<String> = self
+            .row_files(row)
+            .into_iter()
+            .flat_map(FileEntry::paths)
+            .cloned()
+            .collect();
+        self.stash_paths = paths;
+        self.input_mode = InputMode::StashMessage;
+    }
+
+    /// Toggles `--keep-index` for the stashes made from now on
+    pub fn toggle_stash_keep_index(&mut self) {
+        self.stash_options.keep_index = !self.stash_options.keep_index;
+        let message = if self.stash_options.keep_index {
+            Msg::StashKeepIndex
+        } else {
+            Msg::StashResetIndex
+        };
+        self.status.info(message.text(self.language()).to_string());
+    }
+
+    /// Toggles `--include-untracked` for the stashes made from now on
+    pub fn toggle_stash_untracked(&mut self) {
+        self.stash_options.include_untracked = !self.stash_options.include_untracked;
+        let message = if self.stash_options.include_untracked {
+            Msg::StashUntracked
+        } else {
+            Msg::StashTrackedOnly
+        };
+        self.status.info(message.text(self.language()).to_string());
+    }
+
+    pub fn cancel_stash(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.stash_message.clear();
+        self.stash_paths.clear();
+    }
+
+    pub fn list_stashes(&mut self) {
+        self.refresh_stashes();
+        self.input_mode = InputMode::StashList;
+    }
+
+    pub fn exit_stash_list(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn refresh_stashes(&mut self) {
+        match GitOperations::get_stashes(&self.repo) {
+            Ok(stashes) => {
+                self.stashes = stashes;
+                let selected = match self.stash_state.selected() {
+                    _ if self.stashes.is_empty() => None,
+                    Some(i) => Some(i.min(self.stashes.len() - 1)),
+                    None => Some(0),
+                };
+                self.stash_state.select(selected);
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    pub fn next_stash(&mut self) {
+        if let Some(i) = self.stash_state.selected() {
+            if i + 1 < self.stashes.len() {
+                self.stash_state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_stash(&mut self) {
+        if let Some(i) = self.stash_state.selected() {
+            self.stash_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn selected_stash(&self) -> Option<&StashEntry> {
+        self.stashes.get(self.stash_state.selected()?)
+    }
+
+    pub fn apply_selected_stash(&mut self) {
+        self.run_on_selected_stash(GitOperations::apply_stash);
+    }
+
+    pub fn pop_selected_stash(&mut self) {
+        self.run_on_selected_stash(GitOperations::pop_stash);
+    }
+
+    pub fn drop_selected_stash(&mut self) {
+        if let Some(stash) = self.selected_stash() {
+            self.input_mode = InputMode::Confirm {
+                message: Msg::ConfirmDropStash
+                    .fill(self.language(), &[&stash.reference(), &stash.message]),
+                action: ConfirmAction::DropStash(stash.index),
+            };
+        }
+    }
+
+    fn run_on_selected_stash(&mut self, op: fn(&RepoContext, usize) -> Result<Message, GitError>) {
+        let Some(index) = self.selected_stash().map(|stash| stash.index) else {
+            self.status.info(Msg::NoStashSelected.text(self.language()));
+            return;
+        };
+        match op(&self.repo, index) {
+            Ok(message) => {
+                self.status.success(message.text(self.language()));
+                self.refresh_files();
+                self.refresh_stashes();
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    pub fn show_stash_preview(&mut self) {
+        let Some(stash) = self.selected_stash().cloned() else {
+            self.status.info(Msg::NoStashSelected.text(self.language()));
+            return;
+        };
+        match GitOperations::show_stash(&self.repo, stash.index) {
+            Ok(content) => {
+                self.input_mode = InputMode::Preview {
+                    content: content.into(),
+                    file_path: stash.reference(),
+                };
+                self.preview_return_mode = InputMode::StashList;
+                self.preview_scroll = 0;
+            },
+            Err(e) => {
+                self.status
+                    .error(Msg::PreviewError.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    pub fn apply_latest_stash(&mut self) {
+        match GitOperations::apply_latest_stash(&self.repo) {
+            Ok(message) => {
+                self.status.success(message.text(self.language()));
+                self.refresh_files();
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    /// Enters commit mode, pre-filling the message from the commit template unless a draft
+    /// is already being edited
+    pub fn start_commit(&mut self) {
+        self.input_mode = InputMode::Commit;
+        self.identity.configured = GitOperations::get_identity(&self.repo);
+        if self.commit_message.is_empty() {
+            self.commit_flags.sign = GitOperations::is_commit_signing_enabled(&self.repo);
+            self.commit_flags.no_verify = self.commit_lint.no_verify;
+            self.commit_flags.all = self.commit_lint.contents == CommitContents::All;
+            self.trailers.signoff = self.commit_lint.trailers.signoff;
+            self.trailers.added.clear();
+            match GitOperations::get_commit_template(&self.repo) {
+                Ok(Some(template)) => self
+                    .commit_message
+                    .set(self.commit_lint.expand(&template, &self.current_branch)),
+                Ok(None) => {},
+                Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+            }
+        }
+        self.refresh_commit_summary();
+        if self.commit_summary.is_empty() && !self.files.iter().any(FileEntry::is_staged) {
+            self.status
+                .info(Msg::NothingStagedYet.text(self.language()));
+        }
+    }
+
+    /// The staged files the commit being written includes, and with `--all` the modified
+    /// tracked ones
+    fn commit_included(&self) -> impl Iterator<Item = &FileEntry> {
+        self.files.iter().filter(|entry| {
+            (entry.is_staged() || (self.commit_flags.all && entry.is_unstaged()))
+                && !self.commit_excluded.contains(&entry.path)
+        })
+    }
+
+    /// Reloads `commit_summary` after the staged files or the included ones changed
+    pub fn refresh_commit_summary(&mut self) {
+        let paths: Vec<String> = self
+            .commit_included()
+            .flat_map(|entry| entry.orig_path.iter().chain([&entry.path]))
+            .cloned()
+            .collect();
+        if paths.is_empty() {
+            self.commit_summary.clear();
+            return;
+        }
+        match GitOperations::get_staged_summary(&self.repo, &paths, self.commit_flags.all) {
+            Ok(summary) => self.commit_summary = summary,
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    /// Hands the draft to the external editor through `COMMIT_EDITMSG`, for messages too
+    /// long for the input box
+    pub fn edit_commit_message(&mut self) {
+        let files: Vec<&str> = self
+            .commit_included()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        match GitOperations::write_commit_editmsg(&self.repo, &self.commit_message, &files) {
+            Ok(path) => self.pending_editor = Some(path),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    /// Picks up the result of `$EDITOR` once it exits successfully
+    pub fn editor_closed(&mut self, path: &Path) {
+        match &self.input_mode {
+            InputMode::Commit => match GitOperations::read_commit_editmsg(path) {
+                Ok(message) => self.commit_message.set(message),
+                Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+            },
+            InputMode::Conflict { .. } => self.reload_conflict(),
+            _ => {},
+        }
+    }
+
+    /// Replaces a `{branch}`, `{ticket}` or snippet placeholder just typed in the commit
+    /// message with its value
+    pub fn expand_commit_snippet(&mut self) {
+        if self.input_mode != InputMode::Commit {
+            return;
+        }
+        let Some(name) = commit::typed_placeholder(self.commit_message.before_cursor()) else {
+            return;
+        };
+        let name = name.to_string();
+        match self.commit_lint.placeholder(&name, &self.current_branch) {
+            Some(value) => {
+                for _ in 0..name.len() + 2 {
+                    self.commit_message.backspace();
+                }
+                self.commit_message.insert_str(&value);
+            },
+            None if name == "ticket" => {
+                self.status
+                    .info(Msg::NoTicketId.fill(self.language(), &[&self.current_branch]));
+            },
+            None => {},
+        }
+    }
+
+    /// Offers commit types (or gitmojis) to put in front of the commit message
+    pub fn show_prefix_picker(&mut self) {
+        self.prefix_options = self.commit_lint.prefix_choices(self.language());
+        if self.prefix_options.is_empty() {
+            self.status
+                .info(Msg::NoCommitPrefixes.text(self.language()));
+            return;
+        }
+        self.input_mode = InputMode::PrefixPicker;
+        self.prefix.state.select(Some(0));
+    }
+
+    /// Back to the commit message without changing it
+    pub fn exit_prefix_picker(&mut self) {
+        self.input_mode = InputMode::Commit;
+        self.prefix_options.clear();
+    }
+
+    pub fn next_prefix(&mut self) {
+        if let Some(i) = self.prefix.state.selected() {
+            if i + 1 < self.prefix_options.len() {
+                self.prefix.state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_prefix(&mut self) {
+        if let Some(i) = self.prefix.state.selected() {
+            self.prefix.state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    /// Inserts the selected prefix, asking for a scope first when the style has one
+    pub fn choose_prefix(&mut self) {
+        let Some(prefix) = self
+            .prefix
+            .state
+            .selected()
+            .and_then(|i| self.prefix_options.get(i))
+            .map(|option| option.prefix.clone())
+        else {
+            return;
+        };
+        if self.commit_lint.asks_scope() {
+            self.input_mode = InputMode::PrefixScope {
+                prefix,
+                scope: TextInput::default(),
+            };
+        } else {
+            self.apply_prefix(&prefix, "");
+        }
+    }
+
+    /// Inserts the prefix with the scope typed so far; an empty scope is left out
+    pub fn submit_prefix_scope(&mut self) {
+        if let InputMode::PrefixScope { prefix, scope } = &self.input_mode {
+            let (prefix, scope) = (prefix.clone(), scope.clone());
+            self.apply_prefix(&prefix, &scope);
+        }
+    }
+
+    fn apply_prefix(&mut self, prefix: &str, scope: &str) {
+        let message = self
+            .commit_lint
+            .apply_prefix(&self.commit_message, prefix, scope);
+        self.commit_message.set(message);
+        self.exit_prefix_picker();
+    }
+
+    /// Lists the `[[identities]]` profiles, starting at the one the commit is made as
+    pub fn show_identity_picker(&mut self) {
+        if self.identity.profiles.is_empty() {
+            self.status.info(Msg::NoIdentities.text(self.language()));
+            return;
+        }
+        let row = self.identity.commit_override.as_ref().map_or(0, |active| {
+            self.identity
+                .profiles
+                .iter()
+                .position(|profile| profile == active)
+                .map_or(0, |i| i + 1)
+        });
+        self.identity.picker.state.select(Some(row));
+        self.input_mode = InputMode::IdentityPicker;
+    }
+
+    pub fn exit_identity_picker(&mut self) {
+        self.input_mode = InputMode::Commit;
+    }
+
+    pub fn next_identity(&mut self) {
+        if let Some(i) = self.identity.picker.state.selected() {
+            if i < self.identity.profiles.len() {
+                self.identity.picker.state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_identity(&mut self) {
+        if let Some(i) = self.identity.picker.state.selected() {
+            self.identity.picker.state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    /// Makes the commit being written as the selected profile, or back as the repository's
+    /// own identity on the first row
+    pub fn use_selected_identity(&mut self) {
+        let profile = self.identity.selected_profile().cloned();
+        self.status.info(profile.as_ref().map_or_else(
+            || Msg::CommitAsRepo.text(self.language()).to_string(),
+            |profile| Msg::CommitAsProfile.fill(self.language(), &[profile]),
+        ));
+        self.identity.commit_override = profile;
+        self.exit_identity_picker();
+    }
+
+    /// Writes the selected profile to the repository's `user.name` and `user.email`
+    pub fn set_selected_identity_for_repo(&mut self) {
+        let Some(profile) = self.identity.selected_profile().cloned() else {
+            self.status
+                .info(Msg::SelectProfileToSave.text(self.language()));
+            return;
+        };
+        match GitOperations::set_identity(&self.repo, &profile) {
+            Ok(message) => {
+                self.status.success(message.text(self.language()));
+                self.identity.configured = GitOperations::get_identity(&self.repo);
+                self.identity.commit_override = None;
+                self.exit_identity_picker();
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    /// Commit lint problems of the message being typed
+    pub fn commit_violations(&self) -> Vec<String> {
+        self.commit_lint.lint(&self.commit_message)
+    }
+
+    pub fn commit(&mut self) {
+        self.submit_commit(false);
+    }
+
+    /// Commits even if the message breaks the commit lint rules
+    pub fn force_commit(&mut self) {
+        self.submit_commit(true);
+    }
+
+    fn submit_commit(&mut self, ignore_lint: bool) {
+        if self.commit_message.trim().is_empty() {
+            self.status
+                .info(Msg::EmptyCommitMessage.text(self.language()));
+            return;
+        }
+        let violations = self.commit_violations();
+        if !ignore_lint && !violations.is_empty() {
+            self.status
+                .info(Msg::CommitProblems.fill(self.language(), &[&violations.len()]));
+            return;
+        }
+        let staged = self.files.iter().filter(|entry| entry.is_staged()).count();
+        if staged > 0 && self.commit_included().next().is_none() {
+            self.status.info(Msg::NoFilesIncluded.text(self.language()));
+            return;
+        }
+
+        self.run_commit(self.commit_flags.no_verify);
+    }
+
+    fn run_commit(&mut self, no_verify: bool) {
+        let message = match self.commit_trailers() {
+            Ok(trailers) => commit::append_trailers(&self.commit_message, &trailers),
+            Err(e) => {
+                self.status.info(e);
+                return;
+            },
+        };
+        let exclude: Vec<FileEntry> = self
+            .files
+            .iter()
+            .filter(|entry| entry.is_staged() && self.commit_excluded.contains(&entry.path))
+            .cloned()
+            .collect();
+        let options = CommitOptions {
+            sign: self.commit_flags.sign,
+            no_verify,
+            all: self.commit_flags.all,
+            exclude: &exclude,
+            identity: self.identity.commit_override.as_ref(),
+        };
+        match GitOperations::commit_with(&self.repo, &message, &options) {
+            Ok(message) => {
+                let message = message.text(self.language());
+                self.status.success(if no_verify {
+                    Msg::HooksSkipped.fill(self.language(), &[&message])
+                } else {
+                    message
+                });
+                self.commit_message.clear();
+                self.commit_excluded.clear();
+                self.identity.commit_override = None;
+                self.trailers.added.clear();
+                self.input_mode = InputMode::Normal;
+                self.refresh_files();
+            },
+            Err(GitError::Hook { hook, output }) => {
+                self.status
+                    .error(Msg::CommitRejected.fill(self.language(), &[&hook]));
+                self.input_mode = InputMode::HookOutput { hook, output };
+                self.preview_scroll = 0;
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    /// The trailer lines the commit gets: the picked ones, then the sign-off of whoever the
+    /// commit is made as
+    fn commit_trailers(&self) -> Result<Vec<String>, String> {
+        let mut trailers = self.trailers.added.clone();
+        if self.trailers.signoff {
+            let identity = self
+                .identity
+                .active()
+                .ok_or_else(|| Msg::SignOffNeedsIdentity.text(self.language()).to_string())?;
+            trailers.push(format!("Signed-off-by: {identity}"));
+        }
+        Ok(trailers)
+    }
+
+    /// Includes or leaves out the selected staged file from the commit being written
+    pub fn toggle_commit_file(&mut self) {
+        let Some(FileRow::File {
+            section: Section::Staged,
+            index,
+        }) = self.selected_row()
+        else {
+            self.status
+                .info(Msg::SelectStagedFile.text(self.language()));
+            return;
+        };
+        let path = self.files[index].path.clone();
+        let message = if self.commit_excluded.remove(&path) {
+            Msg::FileIncluded
+        } else {
+            self.commit_excluded.insert(path.clone());
+            Msg::FileLeftOut
+        };
+        self.status.info(message.fill(self.language(), &[&path]));
+        self.refresh_commit_summary();
+    }
+
+    /// Asks before committing the draft again without running the hooks
+    pub fn retry_commit_no_verify(&mut self) {
+        let InputMode::HookOutput { hook, .. } = &self.input_mode else {
+            return;
+        };
+        self.input_mode = InputMode::Confirm {
+            message: Msg::ConfirmCommitNoVerify.fill(self.language(), &[hook]),
+            action: ConfirmAction::CommitNoVerify,
+        };
+    }
+
+    /// Back to the commit message so it can be fixed and committed again
+    pub fn exit_hook_output(&mut self) {
+        self.input_mode = InputMode::Commit;
+        self.preview_scroll = 0;
+    }
+
+    pub fn toggle_commit_signing(&mut self) {
+        self.commit_flags.sign = !self.commit_flags.sign;
+        let message = if self.commit_flags.sign {
+            Msg::SignCommit
+        } else {
+            Msg::NoSignCommit
+        };
+        self.status.info(message.text(self.language()).to_string());
+    }
+
+    /// Skips the commit hooks for this commit, e.g. when their environment is broken
+    pub fn toggle_skip_hooks(&mut self) {
+        self.commit_flags.no_verify = !self.commit_flags.no_verify;
+        let message = if self.commit_flags.no_verify {
+            Msg::SkipHooks
+        } else {
+            Msg::RunHooks
+        };
+        self.status.info(message.text(self.language()).to_string());
+    }
+
+    /// Toggles the `Signed-off-by:` trailer (the DCO sign-off) for the commit being written
+    pub fn toggle_signoff(&mut self) {
+        self.trailers.signoff = !self.trailers.signoff;
+        let message = if self.trailers.signoff {
+            Msg::SignOff
+        } else {
+            Msg::NoSignOff
+        };
+        self.status.info(message.text(self.language()).to_string());
+    }
+
+    /// Lists the authors of recent commits to add as trailers of the commit being written
+    pub fn show_trailer_picker(&mut self) {
+        if self.commit_lint.trailers.keys.is_empty() {
+            self.status.info(Msg::NoTrailers.text(self.language()));
+            return;
+        }
+        let own_email = self
+            .identity
+            .active()
+            .map(|identity| identity.email.clone());
+        match GitOperations::recent_authors(&self.repo, RECENT_AUTHOR_COMMITS) {
+            Ok(mut authors) => {
+                authors.retain(|author| Some(&author.email) != own_email.as_ref());
+                if authors.is_empty() {
+                    self.status.info(Msg::NoOtherAuthors.text(self.language()));
+                    return;
+                }
+                self.trailers.authors = authors;
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+                return;
+            },
+        }
+        self.trailers.key %= self.commit_lint.trailers.keys.len();
+        self.trailers.picker.state.select(Some(0));
+        self.input_mode = InputMode::TrailerPicker;
+    }
+
+    pub fn exit_trailer_picker(&mut self) {
+        self.input_mode = InputMode::Commit;
+    }
+
+    pub fn next_trailer_author(&mut self) {
+        if let Some(i) = self.trailers.picker.state.selected() {
+            if i + 1 < self.trailers.authors.len() {
+                self.trailers.picker.state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_trailer_author(&mut self) {
+        if let Some(i) = self.trailers.picker.state.selected() {
+            self.trailers.picker.state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    /// The key the picker adds next, e.g. from `Co-authored-by` to `Reviewed-by`
+    pub fn cycle_trailer_key(&mut self) {
+        let keys = &self.commit_lint.trailers.keys;
+        self.trailers.key = (self.trailers.key + 1) % keys.len().max(1);
+    }
+
+    /// The trailer the picker adds for the selected author with the current key
+    pub fn selected_trailer(&self) -> Option<String> {
+        let key = self.commit_lint.trailers.keys.get(self.trailers.key)?;
+        let author = self
+            .trailers
+            .authors
+            .get(self.trailers.picker.state.selected()?)?;
+        Some(format!("{key}: {author}"))
+    }
+
+    /// Adds the selected author's trailer to the commit being written, or takes it out again
+    pub fn toggle_selected_trailer(&mut self) {
+        let Some(trailer) = self.selected_trailer() else {
+            return;
+        };
+        if let Some(i) = self
+            .trailers
+            .added
+            .iter()
+            .position(|added| *added == trailer)
+        {
+            self.trailers.added.remove(i);
+            self.status
+                .info(Msg::TrailerRemoved.fill(self.language(), &[&trailer]));
+        } else {
+            self.status
+                .info(Msg::TrailerAdded.fill(self.language(), &[&trailer]));
+            self.trailers.added.push(trailer);
+        }
+    }
+
+    /// Toggles `--all` for the commit being written
+    pub fn toggle_auto_stage(&mut self) {
+        self.commit_flags.all = !self.commit_flags.all;
+        let message = if self.commit_flags.all {
+            Msg::AutoStage
+        } else {
+            Msg::NoAutoStage
+        };
+        self.status.info(message.text(self.language()).to_string());
+        self.refresh_commit_summary();
+    }
+
+    /// Asks before pushing; a branch without an upstream is offered `--set-upstream`
+    pub fn push(&mut self) {
+        let Some(upstream) = &self.upstream else {
+            self.offer_set_upstream();
+            return;
+        };
+        self.input_mode = InputMode::Confirm {
+            message: Msg::ConfirmPush.fill(
+                self.language(),
+                &[&upstream.ahead, &self.current_branch, &upstream.name],
+            ),
+            action: ConfirmAction::Push,
+        };
+    }
+
+    /// Pushing a new branch: `origin`, or else the first remote, becomes its upstream
+    fn offer_set_upstream(&mut self) {
+        if self.current_branch.starts_with('(') {
+            self.status.info(Msg::PushNeedsBranch.text(self.language()));
+            return;
+        }
+        let remotes = match GitOperations::get_remotes(&self.repo) {
+            Ok(remotes) => remotes,
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+                return;
+            },
+        };
+        let Some(remote) = remotes
+            .iter()
+            .find(|remote| *remote == "origin")
+            .or_else(|| remotes.first())
+            .cloned()
+        else {
+            self.status.info(Msg::NoRemoteToPush.text(self.language()));
+            return;
+        };
+        self.input_mode = InputMode::Confirm {
+            message: Msg::ConfirmPushSetUpstream.fill(
+                self.language(),
+                &[&self.current_branch, &remote, &self.current_branch],
+            ),
+            action: ConfirmAction::PushSetUpstream { remote },
+        };
+    }
+
+    /// Asks for the branch name to be typed before `git push --force-with-lease`
+    pub fn force_push(&mut self) {
+        let Some(upstream) = &self.upstream else {
+            self.status
+                .info(Msg::NoUpstreamToForcePush.fill(self.language(), &[&self.current_branch]));
+            return;
+        };
+        self.input_mode = InputMode::TypedConfirm {
+            message: Msg::ConfirmForcePush
+                .fill(self.language(), &[&self.current_branch, &upstream.name]),
+            expected: self.current_branch.clone(),
+            typed: TextInput::default(),
+            action: ConfirmAction::ForcePush,
+        };
+    }
+
+    /// Enter in a typed confirmation: carries out the action once the expected text matches
+    pub fn submit_typed_confirm(&mut self) -> bool {
+        let InputMode::TypedConfirm {
+            message,
+            expected,
+            typed,
+            action,
+        } = &self.input_mode
+        else {
+            return false;
+        };
+        if typed.trim() != expected {
+            self.status
+                .info(Msg::TypedConfirmMismatch.fill(self.language(), &[expected]));
+            return false;
+        }
+        self.input_mode = InputMode::Confirm {
+            message: message.clone(),
+            action: action.clone(),
+        };
+        self.handle_confirm(true)
+    }
+
+    pub fn cancel_typed_confirm(&mut self) {
+        if let InputMode::TypedConfirm {
+            message, action, ..
+        } = &self.input_mode
+        {
+            self.input_mode = InputMode::Confirm {
+                message: message.clone(),
+                action: action.clone(),
+            };
+            self.handle_confirm(false);
+        }
+    }
+
+    /// Offers `--rebase`, a merge and `--ff-only`, starting from what `git pull` would do
+    pub fn pull(&mut self) {
+        let default = GitOperations::get_pull_strategy(&self.repo);
+        let index = PullStrategy::ALL.iter().position(|&s| s == default);
+        self.pull.state.select(index);
+        self.input_mode = InputMode::PullPicker { default };
+    }
+
+    pub fn next_pull_strategy(&mut self) {
+        if let Some(i) = self.pull.state.selected() {
+            if i + 1 < PullStrategy::ALL.len() {
+                self.pull.state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_pull_strategy(&mut self) {
+        if let Some(i) = self.pull.state.selected() {
+            self.pull.state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn exit_pull_picker(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.status.info(Msg::Cancelled.text(self.language()));
+    }
+
+    /// A pull that stopped at conflicts leads to them, with abort and continue one key away
+    fn pull_finished(&mut self, result: Result<String, GitError>) {
+        self.refresh_files();
+        let conflicts = self
+            .files
+            .iter()
+            .filter(|entry| entry.is_conflicted())
+            .count();
+        match result {
+            Ok(message) => self.status.success(message),
+            Err(_) if conflicts > 0 => {
+                self.status
+                    .error(Msg::PullConflicts.fill(self.language(), &[&conflicts]));
+                if self.input_mode == InputMode::Normal {
+                    self.select_first_conflict();
+                }
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    fn select_first_conflict(&mut self) {
+        let first_conflict = self.file_rows().iter().position(|row| {
+            matches!(
+                row,
+                FileRow::File {
+                    section: Section::Conflicted,
+                    ..
+                }
+            )
+        });
+        self.files_state.select(first_conflict);
+        self.update_preview();
+    }
+
+    /// Pulls with the chosen strategy in the background
+    pub fn pull_with_selected_strategy(&mut self) {
+        let Some(strategy) = self
+            .pull
+            .state
+            .selected()
+            .and_then(|i| PullStrategy::ALL.get(i).copied())
+        else {
+            return;
+        };
+        self.input_mode = InputMode::Normal;
+        self.start_remote_task(Task::Pull, move |repo, on_progress| {
+            GitOperations::pull(repo, strategy, on_progress)
+        });
+    }
+
+    pub fn fetch(&mut self) {
+        self.start_remote_task(Task::Fetch, |repo, on_progress| {
+            GitOperations::fetch(repo, false, on_progress)
+        });
+    }
+
+    /// `git fetch --prune`, after which local branches of deleted remote branches show up
+    /// as gone in the branch list
+    pub fn fetch_prune(&mut self) {
+        self.start_remote_task(Task::Fetch, |repo, on_progress| {
+            GitOperations::fetch(repo, true, on_progress)
+        });
+    }
+
+    fn execute_push(&mut self) {
+        self.start_remote_task(Task::Push, GitOperations::push);
+    }
+
+    /// Runs a network operation on the worker so the UI keeps responding while it waits.
+    /// The job's callback receives progress in percent.
+    fn start_remote_task<F>(&mut self, task: Task, job: F)
+    where
+        F: FnOnce(&RepoContext, &dyn Fn(u8)) -> Result<Message, GitError> + Send + 'static,
+    {
+        if self.worker.is_running(&task) {
+            self.status
+                .info(Msg::AlreadyRunning.fill(self.language(), &[&task.label()]));
+            return;
+        }
+        self.status
+            .info(Msg::RunningTask.fill(self.language(), &[&task.label()]));
+        self.track_task(&task);
+        let (repo, language) = (self.repo.clone(), self.language());
+        self.worker.spawn_with_progress(task, move |progress| {
+            job(&repo, &|percent| progress.report(percent)).map(|message| message.text(language))
+        });
+    }
+
+    /// Asks for the credential a running remote command is waiting for, over whatever is open
+    fn show_credential_prompt(&mut self) {
+        if matches!(self.input_mode, InputMode::Credential { .. }) {
+            return;
+        }
+        if let Some(prompt) = askpass::next_prompt() {
+            let previous = std::mem::replace(&mut self.input_mode, InputMode::Normal);
+            self.input_mode = InputMode::Credential {
+                prompt,
+                input: TextInput::default(),
+                previous: Box::new(previous),
+            };
+        }
+    }
+
+    /// Hands the typed credential to git and goes back to what was open
+    pub fn submit_credential(&mut self) {
+        self.finish_credential(true);
+    }
+
+    /// Lets git fail the operation instead of answering
+    pub fn cancel_credential(&mut self) {
+        self.finish_credential(false);
+        self.status
+            .info(Msg::CredentialCancelled.text(self.language()));
+    }
+
+    fn finish_credential(&mut self, submit: bool) {
+        let InputMode::Credential {
+            input, previous, ..
+        } = std::mem::replace(&mut self.input_mode, InputMode::Normal)
+        else {
+            return;
+        };
+        askpass::answer(submit.then(|| input.to_string()));
+        self.input_mode = *previous;
+    }
+
+    fn track_task(&mut self, task: &Task) {
+        self.task_started.push((
+            task.clone(),
+            Instant::now(),
+            std::mem::discriminant(&self.input_mode),
+        ));
+    }
+
+    /// Shows the result of a finished operation in a toast when another view was opened
+    /// since it started, so it is not missed, and rings the bell or sends a desktop
+    /// notification for long ones as configured
+    fn announce_finished(&mut self, task: &Task) {
+        let Some(i) = self.task_started.iter().position(|(t, ..)| t == task) else {
+            return;
+        };
+        let (_, started, mode) = self.task_started.remove(i);
+        let message = self.status.text().to_string();
+        if self.notify.toast && std::mem::discriminant(&self.input_mode) != mode {
+            self.toast = Some(Toast {
+                message: message.clone(),
+                ticks_left: TOAST_TICKS,
+            });
+        }
+        if started.elapsed() < Duration::from_secs(self.notify.min_seconds) {
+            return;
+        }
+        // Failing to notify must not hide the result of the operation itself
+        if self.notify.bell {
+            let _ = notify::bell();
+        }
+        if self.notify.desktop {
+            let _ = notify::desktop("pretty-git-ui", &message);
+        }
+    }
+
+    /// Called from the event loop on every tick to apply results of background tasks
+    pub fn on_tick(&mut self) {
+        self.spinner_frame = self.spinner_frame.wrapping_add(1);
+        self.status.expire(Instant::now());
+        if let Some(toast) = &mut self.toast {
+            toast.ticks_left = toast.ticks_left.saturating_sub(1);
+            if toast.ticks_left == 0 {
+                self.toast = None;
+            }
+        }
+        self.collect_command_log();
+        self.collect_history();
+        self.show_credential_prompt();
+        self.ticks_since_upstream_check += 1;
+        if self.ticks_since_upstream_check >= UPSTREAM_REFRESH_TICKS {
+            self.refresh_upstream();
+        }
+        for finished in self.worker.drain() {
+            let task = finished.task.clone();
+            match finished.task {
+                Task::Diff(key) | Task::Prefetch(key) => {
+                    self.preview_loaded(&key, finished.result);
+                },
+                Task::Fetch if self.input_mode == InputMode::Branches => {
+                    match finished.result {
+                        Ok(message) => self.status.success(message),
+                        Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+                    }
+                    self.refresh_files();
+                    self.refresh_branches();
+                },
+                Task::Pull => self.pull_finished(finished.result),
+                Task::Push | Task::Fetch | Task::StageAll => {
+                    match finished.result {
+                        Ok(message) => self.status.success(message),
+                        Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+                    }
+                    self.refresh_files();
+                },
+                Task::DeleteRemoteBranch(_) => {
+                    match finished.result {
+                        Ok(message) => self.status.success(message),
+                        Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+                    }
+                    self.refresh_branches();
+                },
+                Task::Maintenance(_) => {
+                    match finished.result {
+                        Ok(message) => self.status.success(message),
+                        Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+                    }
+                    self.refresh_repo_stats();
+                },
+                Task::Archive(_) => match finished.result {
+                    Ok(message) => self.status.success(message),
+                    Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+                },
+            }
+            self.announce_finished(&task);
+        }
+    }
+
+    pub fn show_help(&mut self) {
+        self.input_mode = InputMode::Help;
+        self.help_scroll = 0;
+    }
+
+    pub fn exit_help(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.help_scroll = 0;
+    }
+
+    pub fn scroll_help_up(&mut self) {
+        if self.help_scroll > 0 {
+            self.help_scroll -= 1;
+        }
+    }
+
+    pub fn scroll_help_down(&mut self) {
+        self.jump_help_to(usize::from(self.help_scroll) + 1);
+    }
+
+    pub fn jump_help(&mut self, jump: ScrollJump) {
+        let (page, max) = self.help_bounds();
+        self.jump_help_to(jump.apply(usize::from(self.help_scroll), page, max));
+    }
+
+    fn jump_help_to(&mut self, offset: usize) {
+        let (_, max) = self.help_bounds();
+        self.help_scroll = scroll_offset(offset.min(max));
+    }
+
+    /// Keeps the scrolled views within their content after the terminal was resized. Called
+    /// once the new size has been drawn, since the limits come from the drawn areas.
+    pub fn on_resize(&mut self) {
+        self.clamp_preview_scroll();
+        self.jump_help_to(usize::from(self.help_scroll));
+        self.jump_command_log_to(usize::from(self.command_log_scroll));
+        self.jump_history_to(usize::from(self.history.scroll));
+    }
+
+    fn help_bounds(&self) -> (usize, usize) {
+        page_bounds(
+            self.areas.help,
+            crate::ui_help::help_line_count(&self.theme, self.language()),
+        )
+    }
+
+    /// Moves the commands recorded since the last tick into `command_log`
+    pub fn collect_command_log(&mut self) {
+        for record in command_log::take() {
+            command_log::push_bounded(&mut self.command_log, record);
+        }
+        self.running_commands = command_log::running();
+    }
+
+    /// Opens the command log, newest command first, or closes it again
+    pub fn toggle_command_log(&mut self) {
+        if self.input_mode == InputMode::CommandLog {
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+        self.collect_command_log();
+        self.input_mode = InputMode::CommandLog;
+        self.command_log_scroll = 0;
+    }
+
+    pub fn scroll_command_log_up(&mut self) {
+        self.command_log_scroll = self.command_log_scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_command_log_down(&mut self) {
+        self.jump_command_log_to(usize::from(self.command_log_scroll) + 1);
+    }
+
+    pub fn jump_command_log(&mut self, jump: ScrollJump) {
+        let (page, max) = self.command_log_bounds();
+        self.jump_command_log_to(jump.apply(usize::from(self.command_log_scroll), page, max));
+    }
+
+    fn jump_command_log_to(&mut self, offset: usize) {
+        let (_, max) = self.command_log_bounds();
+        self.command_log_scroll = scroll_offset(offset.min(max));
+    }
+
+    fn command_log_bounds(&self) -> (usize, usize) {
+        let lines = self
+            .command_log
+            .iter()
+            .chain(&self.running_commands)
+            .map(CommandRecord::line_count)
+            .sum();
+        page_bounds(self.areas.command_log, lines)
+    }
+
+    /// Moves the successes and errors reported since the last tick into the history
+    pub fn collect_history(&mut self) {
+        for message in self.status.take_outcomes() {
+            let Some(entry) = HistoryEntry::from_message(&message, &self.repo_name) else {
+                continue;
+            };
+            if let Err(e) = self.history.record(entry) {
+                self.status
+                    .error(Msg::HistoryWriteError.fill(self.language(), &[&e]));
+            }
+        }
+    }
+
+    /// Opens the history of this session, newest operation first, or closes it again
+    pub fn toggle_history(&mut self) {
+        if self.input_mode == InputMode::History {
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+        self.collect_history();
+        self.input_mode = InputMode::History;
+        self.history.scroll = 0;
+    }
+
+    /// Checks git, its config and the terminal and shows the results
+    pub fn show_doctor(&mut self) {
+        self.doctor = doctor::run(&self.repo);
+        self.input_mode = InputMode::Doctor;
+    }
+
+    pub fn exit_doctor(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// The diagnostics as text, e.g. to paste into a bug report
+    pub fn copy_doctor_report(&mut self) {
+        let report = doctor::format_report(&self.doctor);
+        self.copy(report, Msg::CopyDoctorReport.text(self.language()));
+    }
+
+    pub fn scroll_history_up(&mut self) {
+        self.history.scroll = self.history.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_history_down(&mut self) {
+        self.jump_history_to(usize::from(self.history.scroll) + 1);
+    }
+
+    pub fn jump_history(&mut self, jump: ScrollJump) {
+        let (page, max) = page_bounds(self.history.area, self.history.entries.len());
+        self.jump_history_to(jump.apply(usize::from(self.history.scroll), page, max));
+    }
+
+    fn jump_history_to(&mut self, offset: usize) {
+        let (_, max) = page_bounds(self.history.area, self.history.entries.len());
+        self.history.scroll = scroll_offset(offset.min(max));
+    }
+
+    /// Whether the app can quit right away. When `confirm_quit` is on and a commit message
+    /// draft or a running git operation would be lost, asks for confirmation instead.
+    pub fn request_quit(&mut self) -> bool {
+        let language = self.language();
+        let mut reasons = Vec::new();
+        if !self.commit_message.trim().is_empty() {
+            reasons.push(Msg::QuitUnsavedMessage.text(language).to_string());
+        }
+        let running: Vec<String> = self
+            .worker
+            .in_flight()
+            .iter()
+            .filter(|task| !task.is_preview())
+            .map(Task::label)
+            .collect();
+        if !running.is_empty() {
+            reasons.push(Msg::QuitStillRunning.fill(language, &[&running.join(", ")]));
+        }
+        if !self.confirm_quit || reasons.is_empty() {
+            return true;
+        }
+        self.input_mode = InputMode::Confirm {
+            message: Msg::ConfirmQuit.fill(language, &[&reasons.join(Msg::And.text(language))]),
+            action: ConfirmAction::Quit,
+        };
+        false
+    }
+
+    /// Answers the pending confirmation. Returns true when quitting was confirmed.
+    pub fn handle_confirm(&mut self, confirmed: bool) -> bool {
+        if let InputMode::Confirm { action, .. } = &self.input_mode {
+            let action = action.clone();
+            self.input_mode = InputMode::Normal;
+            if !confirmed {
+                self.status.info(Msg::Cancelled.text(self.language()));
+                match action {
+                    ConfirmAction::DropStash(_) => self.input_mode = InputMode::StashList,
+                    ConfirmAction::ResetHard(_) | ConfirmAction::Checkout(_) => {
+                        self.input_mode = InputMode::Reflog;
+                    },
+                    ConfirmAction::Reset { .. } | ConfirmAction::Autosquash(_) => {
+                        self.input_mode = InputMode::Log;
+                    },
+                    ConfirmAction::CommitNoVerify => self.input_mode = InputMode::Commit,
+                    ConfirmAction::DeleteBranch { .. }
+                    | ConfirmAction::DeleteRemoteBranch { .. }
+                    | ConfirmAction::DeleteGoneBranches(_) => {
+                        self.input_mode = InputMode::Branches;
+                    },
+                    ConfirmAction::Prune => self.input_mode = InputMode::Maintenance,
+                    _ => {},
+                }
+                return false;
+            }
+            match action {
+                ConfirmAction::StageAll | ConfirmAction::UnstageAll => {
+                    self.execute_stage_all();
+                },
+                ConfirmAction::Push => self.execute_push(),
+                ConfirmAction::PushSetUpstream { remote } => {
+                    let branch = self.current_branch.clone();
+                    self.start_remote_task(Task::Push, move |repo, on_progress| {
+                        GitOperations::push_set_upstream(repo, &remote, &branch, on_progress)
+                    });
+                },
+                ConfirmAction::ForcePush => {
+                    self.start_remote_task(Task::Push, GitOperations::force_push);
+                },
+                ConfirmAction::DropStash(index) => {
+                    self.input_mode = InputMode::StashList;
+                    match GitOperations::drop_stash(&self.repo, index) {
+                        Ok(message) => self.status.success(message.text(self.language())),
+                        Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+                    }
+                    self.refresh_stashes();
+                },
+                ConfirmAction::DiscardFile(path) => {
+                    match GitOperations::discard_changes(&self.repo, &path) {
+                        Ok(message) => self.status.success(message.text(self.language())),
+                        Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+                    }
+                    self.refresh_paths(vec![path]);
+                },
+                ConfirmAction::MergeAbort => {
+                    match GitOperations::merge_abort(&self.repo) {
+                        Ok(message) => self.status.success(message.text(self.language())),
+                        Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+                    }
+                    self.refresh_files();
+                },
+                ConfirmAction::Quit => return true,
+                ConfirmAction::BisectReset => self.run_bisect_command(GitOperations::bisect_reset),
+                ConfirmAction::ResetHard(target) => {
+                    self.run_on_reflog_target(GitOperations::reset_hard, &target);
+                },
+                ConfirmAction::Checkout(target) => {
+                    self.run_on_reflog_target(GitOperations::checkout_detached, &target);
+                },
+                ConfirmAction::SwitchBranch(name) => self.execute_checkout_branch(&name),
+                ConfirmAction::Reset { mode, target } => self.execute_reset(mode, &target),
+                ConfirmAction::Autosquash(target) => self.execute_autosquash(&target),
+                ConfirmAction::CommitNoVerify => {
+                    self.input_mode = InputMode::Commit;
+                    self.run_commit(true);
+                },
+                ConfirmAction::DeleteBranch { name, force } => {
+                    self.input_mode = InputMode::Branches;
+                    self.execute_delete_branch(&name, force);
+                },
+                ConfirmAction::Prune => {
+                    self.input_mode = InputMode::Maintenance;
+                    self.execute_maintenance(MaintenanceTask::Prune);
+                },
+                ConfirmAction::DeleteRemoteBranch { remote, branch } => {
+                    self.input_mode = InputMode::Branches;
+                    self.execute_delete_remote_branch(remote, branch);
+                },
+                ConfirmAction::DeleteGoneBranches(names) => {
+                    self.input_mode = InputMode::Branches;
+                    self.execute_delete_gone_branches(&names);
+                },
+            }
+        }
+        false
+    }
+
+    pub fn show_preview(&mut self) {
+        let Some(file_path) = self.get_current_file_path() else {
+            self.status.info(Msg::NoFileToPreview.text(self.language()));
+            return;
+        };
+        match GitOperations::get_diff(&self.repo, &file_path, self.diff_view, self.diff_options) {
+            Ok(content) => {
+                self.input_mode = InputMode::Preview {
+                    content: content.into(),
+                    file_path,
+                };
+                self.preview_return_mode = InputMode::Normal;
+                self.preview_scroll = 0;
+            },
+            Err(e) => {
+                self.status
+                    .error(Msg::PreviewError.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    /// Switches the fullscreen preview of a binary file between its description and a hex
+    /// dump of the changed copy, for files up to `binary::HEX_DUMP_LIMIT`
+    pub fn toggle_hex_dump(&mut self) {
+        let InputMode::Preview { file_path, .. } = &self.input_mode else {
+            return;
+        };
+        if self.preview_return_mode != InputMode::Normal {
+            self.status
+                .info(Msg::HexDumpWorktreeOnly.text(self.language()));
+            return;
+        }
+        let file_path = file_path.clone();
+        if self.preview_hex {
+            self.show_preview();
+            self.preview_hex = false;
+            return;
+        }
+        let (before, after) = self.diff_view.versions();
+        let bytes = match GitOperations::get_file_bytes(&self.repo, &file_path, after) {
+            Ok(None) => GitOperations::get_file_bytes(&self.repo, &file_path, before),
+            other => other,
+        };
+        match bytes {
+            Ok(Some(bytes)) if !binary::is_binary(&bytes) => {
+                self.status
+                    .info(Msg::NotBinary.fill(self.language(), &[&file_path]));
+            },
+            Ok(Some(bytes)) if bytes.len() > binary::HEX_DUMP_LIMIT => {
+                let size = binary::format_size(bytes.len());
+                self.status
+                    .info(Msg::TooLargeForHexDump.fill(self.language(), &[&file_path, &size]));
+            },
+            Ok(Some(bytes)) => {
+                let content = format!(
+                    "{}\n\n{}",
+                    Msg::HexDumpTitle
+                        .fill(self.language(), &[&file_path, &binary::describe(&bytes)]),
+                    binary::hex_dump(&bytes)
+                );
+                self.input_mode = InputMode::Preview {
+                    content: content.into(),
+                    file_path,
+                };
+                self.preview_scroll = 0;
+                self.preview_hex = true;
+            },
+            Ok(None) => self
+                .status
+                .info(Msg::FileMissing.fill(self.language(), &[&file_path])),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    /// Left click: selects the clicked row of the file, log, stash or repository list
+    pub fn on_click(&mut self, column: u16, row: u16) {
+        match self.input_mode {
+            InputMode::Normal => {
+                if let Some(i) = self.areas.file_list.row_at(column, row) {
+                    if i < self.file_rows().len() {
+                        self.files_state.select(Some(i));
+                        self.update_preview();
+                    }
+                }
+            },
+            InputMode::Log | InputMode::LogSearch { .. } => {
+                if let Some(i) = self.areas.log.row_at(column, row) {
+                    if self
+                        .log_entries
+                        .get(i)
+                        .is_some_and(|line| GitOperations::extract_commit_hash(line).is_some())
+                    {
+                        self.log_state.select(Some(i));
+                    }
+                }
+            },
+            InputMode::Branches => {
+                if let Some(i) = self.areas.branch.row_at(column, row) {
+                    if i < self.branches.len() {
+                        self.branch_state.select(Some(i));
+                    }
+                }
+            },
+            InputMode::PullPicker { .. } => self.pull.click(column, row, PullStrategy::ALL.len()),
+            InputMode::CommitDetails => {
+                if let Some(i) = self.commit_details.area.row_at(column, row) {
+                    if i < self.commit_details.rows().len() {
+                        self.commit_details.state.select(Some(i));
+                    }
+                }
+            },
+            InputMode::Compare => {
+                if let Some(i) = self.compare.area.row_at(column, row) {
+                    if i < self.compare.files.len() {
+                        self.compare.state.select(Some(i));
+                    }
+                }
+            },
+            InputMode::Maintenance => {
+                if let Some(i) = self.maintenance.area.row_at(column, row) {
+                    if i < MaintenanceTask::ALL.len() {
+                        self.maintenance.tasks.select(Some(i));
+                    }
+                }
+            },
+            InputMode::MergePicker { .. } => {
+                self.merge.click(column, row, MergeStrategy::ALL.len());
+            },
+            InputMode::Reflog => {
+                if let Some(i) = self.areas.reflog.row_at(column, row) {
+                    if i < self.reflog_entries.len() {
+                        self.reflog_state.select(Some(i));
+                    }
+                }
+            },
+            InputMode::StashList => {
+                if let Some(i) = self.areas.stash_list.row_at(column, row) {
+                    if i < self.stashes.len() {
+                        self.stash_state.select(Some(i));
+                    }
+                }
+            },
+            // The templates are listed at every step, so a click picks one at any of them
+            InputMode::InitSetup { .. } => {
+                self.init.click(column, row, GITIGNORE_TEMPLATES.len() + 1);
+            },
+            InputMode::RepoSwitcher | InputMode::NoRepository { .. } => {
+                if let Some(i) = self.areas.repo_list.row_at(column, row) {
+                    if i < self.recent_repos.repos.len() {
+                        self.repo_state.select(Some(i));
+                    }
+                }
+            },
+            InputMode::IgnoreChooser { .. } => {
+                self.ignore.click(column, row, self.ignore_options.len());
+            },
+            InputMode::IdentityPicker => {
+                let len = self.identity.profiles.len() + 1;
+                self.identity.picker.click(column, row, len);
+            },
+            InputMode::TrailerPicker => {
+                let len = self.trailers.authors.len();
+                self.trailers.picker.click(column, row, len);
+            },
+            InputMode::Config => self.config.click(column, row),
+            InputMode::PrefixPicker => self.prefix.click(column, row, self.prefix_options.len()),
+            InputMode::LineStage { .. } => {
+                if let Some(i) = self.areas.line_stage.row_at(column, row) {
+                    if self.line_patch.is_change(i) {
+                        self.line_state.select(Some(i));
+                        self.toggle_line();
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Mouse wheel: scrolls the help or preview, or moves through the list under the cursor
+    pub fn on_scroll(&mut self, column: u16, row: u16, down: bool) {
+        match self.input_mode {
+            InputMode::Help if down => self.scroll_help_down(),
+            InputMode::Help => self.scroll_help_up(),
+            InputMode::CommandLog if down => self.scroll_command_log_down(),
+            InputMode::CommandLog => self.scroll_command_log_up(),
+            InputMode::History if down => self.scroll_history_down(),
+            InputMode::History => self.scroll_history_up(),
+            InputMode::Preview { .. }
+            | InputMode::FileVersion { .. }
+            | InputMode::HookOutput { .. }
+                if down =>
+            {
+                self.scroll_preview_down();
+            },
+            InputMode::Preview { .. }
+            | InputMode::FileVersion { .. }
+            | InputMode::HookOutput { .. } => self.scroll_preview_up(),
+            InputMode::Normal
+                if self.show_preview_panel
+                    && rect_contains(self.areas.preview_panel, column, row) =>
+            {
+                if down {
+                    self.scroll_preview_down();
+                } else {
+                    self.scroll_preview_up();
+                }
+            },
+            InputMode::Normal if self.areas.file_list.contains(column, row) => {
+                if down {
+                    self.next();
+                } else {
+                    self.previous();
+                }
+            },
+            InputMode::Log if down => self.next_log_entry(),
+            InputMode::Log => self.previous_log_entry(),
+            InputMode::PullPicker { .. } if down => self.next_pull_strategy(),
+            InputMode::PullPicker { .. } => self.previous_pull_strategy(),
+            InputMode::CommitDetails if down => self.next_commit_details_row(),
+            InputMode::CommitDetails => self.previous_commit_details_row(),
+            InputMode::Compare if down => self.next_compare_file(),
+            InputMode::Compare => self.previous_compare_file(),
+            InputMode::Maintenance if down => self.next_maintenance_task(),
+            InputMode::Maintenance => self.previous_maintenance_task(),
+            InputMode::MergePicker { .. } if down => self.next_merge_strategy(),
+            InputMode::MergePicker { .. } => self.previous_merge_strategy(),
+            InputMode::Branches if down => self.next_branch(),
+            InputMode::Branches => self.previous_branch(),
+            InputMode::Reflog if down => self.next_reflog_entry(),
+            InputMode::Reflog => self.previous_reflog_entry(),
+            InputMode::StashList if down => self.next_stash(),
+            InputMode::StashList => self.previous_stash(),
+            InputMode::RepoSwitcher | InputMode::NoRepository { .. } if down => self.next_repo(),
+            InputMode::RepoSwitcher | InputMode::NoRepository { .. } => self.previous_repo(),
+            InputMode::InitSetup {
+                step: InitStep::Gitignore,
+                ..
+            } if down => self.next_init_template(),
+            InputMode::InitSetup {
+                step: InitStep::Gitignore,
+                ..
+            } => self.previous_init_template(),
+            InputMode::Conflict { .. } if down => self.scroll_conflict_down(),
+            InputMode::Conflict { .. } => self.scroll_conflict_up(),
+            InputMode::IgnoreChooser { .. } if down => self.next_ignore_option(),
+            InputMode::IgnoreChooser { .. } => self.previous_ignore_option(),
+            InputMode::IdentityPicker if down => self.next_identity(),
+            InputMode::IdentityPicker => self.previous_identity(),
+            InputMode::TrailerPicker if down => self.next_trailer_author(),
+            InputMode::TrailerPicker => self.previous_trailer_author(),
+            InputMode::PrefixPicker if down => self.next_prefix(),
+            InputMode::PrefixPicker => self.previous_prefix(),
+            InputMode::Config if down => self.next_config_row(),
+            InputMode::Config => self.previous_config_row(),
+            InputMode::LineStage { .. } if down => self.next_line(),
+            InputMode::LineStage { .. } => self.previous_line(),
+            _ => {},
+        }
+    }
+
+    /// The text being typed in the current mode, if it takes text
+    pub fn text_input_mut(&mut self) -> Option<&mut TextInput> {
+        match &mut self.input_mode {
+            InputMode::Commit => Some(&mut self.commit_message),
+            InputMode::StashMessage => Some(&mut self.stash_message),
+            InputMode::LogSearch { query } => Some(query),
+            InputMode::PrefixScope { scope, .. } => Some(scope),
+            InputMode::TypedConfirm { typed, .. } => Some(typed),
+            InputMode::InitSetup {
+                step: InitStep::Branch,
+                branch: input,
+                ..
+            }
+            | InputMode::BranchPrompt { input, .. }
+            | InputMode::Credential { input, .. }
+            | InputMode::CompareInput { input }
+            | InputMode::ArchivePrompt { input, .. }
+            | InputMode::ConfigEdit { input, .. } => Some(input),
+            InputMode::Preview { .. } => self.preview_search_input.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Inserts pasted text at the cursor. Line breaks are kept in the commit message, which
+    /// can have a body, and become spaces in the one-line inputs.
+    pub fn paste(&mut self, text: &str) {
+        let multiline = self.input_mode == InputMode::Commit;
+        let Some(input) = self.text_input_mut() else {
+            return;
+        };
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        if multiline {
+            input.insert_str(&text);
+        } else {
+            input.insert_str(&text.replace('\n', " "));
+        }
+    }
+
+    pub fn scroll_preview_up(&mut self) {
+        if self.preview_scroll > 0 {
+            self.preview_scroll -= 1;
+        }
+    }
+
+    pub fn scroll_preview_down(&mut self) {
+        self.jump_preview_to(usize::from(self.preview_scroll) + 1);
+        if self.input_mode == InputMode::Normal && self.preview_has_more() {
+            let remaining = self
+                .preview_content
+                .line_count()
+                .saturating_sub(usize::from(self.preview_scroll));
+            // While a page is still loading its result would be mistaken for the end
+            let loading = self
+                .preview_key()
+                .is_some_and(|key| self.diff_cache.is_loading(&key));
+            if remaining < PREVIEW_PAGE_LINES / 2 && !loading {
+                self.preview_line_limit += PREVIEW_PAGE_LINES;
+                self.load_preview();
+            }
+        }
+    }
+
+    pub fn jump_preview(&mut self, jump: ScrollJump) {
+        let (page, max) = self.preview_bounds();
+        self.jump_preview_to(jump.apply(usize::from(self.preview_scroll), page, max));
+    }
+
+    /// Keeps the preview scrolled within its content after the content or the screen changed
+    pub fn clamp_preview_scroll(&mut self) {
+        self.jump_preview_to(usize::from(self.preview_scroll));
+    }
+
+    fn jump_preview_to(&mut self, offset: usize) {
+        let (_, max) = self.preview_bounds();
+        self.preview_scroll = scroll_offset(offset.min(max));
+    }
+
+    /// Page size and largest offset of the preview being scrolled: the fullscreen preview or
+    /// file viewer, otherwise the preview panel
+    fn preview_bounds(&self) -> (usize, usize) {
+        match &self.input_mode {
+            InputMode::Preview { content, .. } | InputMode::FileVersion { content, .. } => {
+                page_bounds(self.areas.fullscreen, content.line_count())
+            },
+            _ => page_bounds(self.areas.preview_panel, self.preview_content.line_count()),
+        }
+    }
+
+    /// Whether the preview panel shows only the first part of a longer diff
+    pub fn preview_has_more(&self) -> bool {
+        self.preview_content.line_count() >= self.preview_line_limit
+    }
+
+    /// Opens the full working tree content of the selected file
+    pub fn show_file_versions(&mut self) {
+        let Some(entry) = self.selected_file() else {
+            self.status.info(Msg::NoFileSelected.text(self.language()));
+            return;
+        };
+        let file_path = entry.path.clone();
+        self.preview_scroll = 0;
+        self.show_file_version(file_path, FileVersion::Worktree);
+    }
+
+    /// Switches the file viewer to another version, keeping the scroll position so the same
+    /// lines can be compared
+    pub fn select_file_version(&mut self, version: FileVersion) {
+        if let InputMode::FileVersion { file_path, .. } = &self.input_mode {
+            let file_path = file_path.clone();
+            self.show_file_version(file_path, version);
+            self.clamp_preview_scroll();
+        }
+    }
+
+    pub fn next_file_version(&mut self) {
+        if let InputMode::FileVersion { version, .. } = self.input_mode {
+            self.select_file_version(version.next());
+        }
+    }
+
+    fn show_file_version(&mut self, file_path: String, version: FileVersion) {
+        let content = match GitOperations::get_file_version(&self.repo, &file_path, version) {
+            Ok(Some(content)) => content,
+            Ok(None) => Msg::NotInVersion.fill(self.language(), &[&file_path]),
+            Err(e) => Msg::Error.fill(self.language(), &[&e]),
+        };
+        self.input_mode = InputMode::FileVersion {
+            file_path,
+            version,
+            content: content.into(),
+        };
+    }
+
+    pub fn exit_file_versions(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.preview_scroll = 0;
+    }
+
+    pub fn exit_preview(&mut self) {
+        self.input_mode = std::mem::replace(&mut self.preview_return_mode, InputMode::Normal);
+        self.preview_scroll = 0;
+        self.preview_hex = false;
+        self.preview_search_input = None;
+        self.preview_query.clear();
+        self.preview_matches.clear();
+        self.preview_match = None;
+    }
+
+    /// Opens the search prompt of the fullscreen preview with the previous query
+    pub fn start_preview_search(&mut self) {
+        self.preview_search_input = Some(TextInput::new(self.preview_query.clone()));
+    }
+
+    pub fn cancel_preview_search(&mut self) {
+        self.preview_search_input = None;
+    }
+
+    /// Finds the lines of the fullscreen preview containing the typed text and jumps to the
+    /// first one at or below the top of the screen
+    pub fn submit_preview_search(&mut self) {
+        let Some(query) = self.preview_search_input.take() else {
+            return;
+        };
+        let InputMode::Preview { content, .. } = &self.input_mode else {
+            return;
+        };
+        self.preview_matches = content.search(&query);
+        self.preview_query = query.to_string();
+        self.preview_match = None;
+        if self.preview_query.is_empty() {
+            return;
+        }
+        if self.preview_matches.is_empty() {
+            self.status
+                .info(Msg::NotFound.fill(self.language(), &[&self.preview_query]));
+            return;
+        }
+        let top = usize::from(self.preview_scroll);
+        let first = self
+            .preview_matches
+            .iter()
+            .position(|&line| line >= top)
+            .unwrap_or(0);
+        self.jump_to_preview_match(first);
+    }
+
+    /// Moves to the next (or previous) search match, wrapping around at the ends
+    pub fn next_preview_match(&mut self, forward: bool) {
+        let count = self.preview_matches.len();
+        if count == 0 {
+            return;
+        }
+        let next = match self.preview_match {
+            Some(i) if forward => (i + 1) % count,
+            Some(i) => (i + count - 1) % count,
+            None if forward => 0,
+            None => count - 1,
+        };
+        self.jump_to_preview_match(next);
+    }
+
+    fn jump_to_preview_match(&mut self, index: usize) {
+        /// Lines kept visible above the match
+        const CONTEXT: usize = 3;
+        self.preview_match = Some(index);
+        let line = self.preview_matches[index];
+        self.jump_preview_to(line.saturating_sub(CONTEXT));
+    }
+
+    /// Switches the preview between unstaged and staged changes
+    pub fn toggle_diff_view(&mut self) {
+        self.diff_view = self.diff_view.toggled();
+        self.update_preview();
+    }
+
+    /// Shows more (positive `step`) or fewer lines of context around each change
+    pub fn change_diff_context(&mut self, step: i32) {
+        let options = self.diff_options.with_context_step(step);
+        let context = options.context.unwrap_or(DiffOptions::DEFAULT_CONTEXT);
+        let message = Msg::DiffContext.fill(self.language(), &[&context]);
+        self.set_diff_options(options, message);
+    }
+
+    /// Toggles `-w`, hiding changes that only touch whitespace
+    pub fn toggle_ignore_whitespace(&mut self) {
+        let ignore = !self.diff_options.ignore_whitespace;
+        let message = if ignore {
+            Msg::IgnoringWhitespace
+        } else {
+            Msg::ShowingWhitespace
+        };
+        self.set_diff_options(
+            DiffOptions {
+                ignore_whitespace: ignore,
+                ..self.diff_options
+            },
+            message.text(self.language()).to_string(),
+        );
+    }
+
+    /// Toggles `--ignore-blank-lines`, hiding added or removed blank lines
+    pub fn toggle_ignore_blank_lines(&mut self) {
+        let ignore = !self.diff_options.ignore_blank_lines;
+        let message = if ignore {
+            Msg::IgnoringBlankLines
+        } else {
+            Msg::ShowingBlankLines
+        };
+        self.set_diff_options(
+            DiffOptions {
+                ignore_blank_lines: ignore,
+                ..self.diff_options
+            },
+            message.text(self.language()).to_string(),
+        );
+    }
+
+    /// Reloads the preview panel, or the fullscreen diff of a working tree file, with
+    /// `options`
+    fn set_diff_options(&mut self, options: DiffOptions, message: String) {
+        let fullscreen = matches!(self.input_mode, InputMode::Preview { .. });
+        if fullscreen && self.preview_return_mode != InputMode::Normal {
+            self.status
+                .info(Msg::DiffOptionsWorktreeOnly.text(self.language()));
+            return;
+        }
+        self.diff_options = options;
+        self.status.info(message);
+        if fullscreen {
+            self.preview_hex = false;
+            self.show_preview();
+        } else {
+            self.update_preview();
+        }
+    }
+
+    pub fn update_preview(&mut self) {
+        self.preview_scroll = 0;
+        if !self.show_preview_panel {
+            return;
+        }
+
+        self.preview_line_limit = PREVIEW_PAGE_LINES;
+        if self.get_current_file_path().is_some() {
+            self.preview_content = DiffLines::from(Msg::Loading.text(self.language()));
+            self.load_preview();
+        } else {
+            self.preview_content = DiffLines::default();
+        }
+    }
+
+    /// The diff the preview panel shows for the selected file
+    fn preview_key(&self) -> Option<DiffKey> {
+        Some(DiffKey {
+            path: self.get_current_file_path()?,
+            view: self.diff_view,
+            options: self.diff_options,
+            max_lines: self.preview_line_limit,
+        })
+    }
+
+    /// Loads the first `preview_line_limit` lines of the selected file's diff in the
+    /// background, or from `diff_cache` when it was loaded before
+    fn load_preview(&mut self) {
+        let (Some(key), Some(entry)) = (self.preview_key(), self.selected_file()) else {
+            return;
+        };
+        let fingerprint = Fingerprint::of(&self.repo, entry);
+        if let Some(diff) = self.diff_cache.get(&key, fingerprint) {
+            self.preview_content = diff.clone();
+            self.clamp_preview_scroll();
+        } else if !self.diff_cache.is_loading(&key) {
+            // A prefetch of this file that is still running is shown once it finishes
+            self.spawn_diff(Task::Diff(key.clone()), key, fingerprint);
+        }
+        self.prefetch_previews();
+    }
+
+    /// Loads the diffs of the files around the selection into `diff_cache`, so moving
+    /// there shows them right away. At most `MAX_PREVIEW_DIFFS` run at once.
+    fn prefetch_previews(&mut self) {
+        let Some(selected) = self.files_state.selected() else {
+            return;
+        };
+        let rows = self.file_rows();
+        let nearby = (1..=PREFETCH_ROWS)
+            .flat_map(|distance| {
+                [
+                    selected.checked_add(distance),
+                    selected.checked_sub(distance),
+                ]
+            })
+            .flatten();
+        for row in nearby {
+            let running = self
+                .worker
+                .in_flight()
+                .iter()
+                .filter(|task| task.is_preview());
+            if running.count() >= MAX_PREVIEW_DIFFS {
+                break;
+            }
+            let Some(FileRow::File { index, .. }) = rows.get(row) else {
+                continue;
+            };
+            let entry = &self.files[*index];
+            let key = DiffKey {
+                path: entry.path.clone(),
+                view: self.diff_view,
+                options: self.diff_options,
+                max_lines: PREVIEW_PAGE_LINES,
+            };
+            let fingerprint = Fingerprint::of(&self.repo, entry);
+            if self.diff_cache.get(&key, fingerprint).is_none() && !self.diff_cache.is_loading(&key)
+            {
+                self.spawn_diff(Task::Prefetch(key.clone()), key, fingerprint);
+            }
+        }
+    }
+
+    fn spawn_diff(&mut self, task: Task, key: DiffKey, fingerprint: Fingerprint) {
+        if self.worker.is_running(&task) {
+            return;
+        }
+        self.diff_cache.start(key.clone(), fingerprint);
+        let repo = self.repo.clone();
+        self.worker.spawn(task, move || {
+            GitOperations::get_diff_head(&repo, &key.path, key.view, key.options, key.max_lines)
+        });
+    }
+
+    /// Caches a loaded diff and shows it when it is for the selected file
+    fn preview_loaded(&mut self, key: &DiffKey, result: Result<String, GitError>) {
+        let diff = result.ok().map(DiffLines::from);
+        self.diff_cache.finish(key, diff.clone());
+        // Ignore stale results for a file, view or options no longer selected
+        if self.preview_key().as_ref() == Some(key) {
+            self.preview_content =
+                diff.unwrap_or_else(|| Msg::NoPreview.text(self.language()).into());
+            self.clamp_preview_scroll();
+        }
+        if self.show_preview_panel {
+            self.prefetch_previews();
+        }
+    }
+
+    pub fn toggle_preview_panel(&mut self) {
+        self.show_preview_panel = !self.show_preview_panel;
+        if self.show_preview_panel {
+            self.update_preview();
+        }
+    }
+
+    /// Moves the preview panel from the right to the bottom to hidden and back
+    pub fn cycle_preview_position(&mut self) {
+        self.layout.preview = self.layout.preview.next();
+        let was_shown = self.show_preview_panel;
+        self.show_preview_panel = self.layout.preview != PreviewPosition::Hidden;
+        if self.show_preview_panel && !was_shown {
+            self.update_preview();
+        }
+        let message = match self.layout.preview {
+            PreviewPosition::Right => Msg::PreviewRight,
+            PreviewPosition::Bottom => Msg::PreviewBottom,
+            PreviewPosition::Hidden => Msg::PreviewHidden,
+        };
+        self.status.info(message.text(self.language()));
+        self.save_layout();
+    }
+
+    /// Grows (positive `step`) or shrinks the preview panel by `step` percent
+    pub fn resize_preview(&mut self, step: i16) {
+        if !self.show_preview_panel {
+            self.status.info(Msg::PreviewIsHidden.text(self.language()));
+            return;
+        }
+        let size = self.layout.preview_percent().saturating_add_signed(step);
+        self.layout.preview_size = size.clamp(
+            LayoutConfig::MIN_PREVIEW_SIZE,
+            LayoutConfig::MAX_PREVIEW_SIZE,
+        );
+        self.status
+            .info(Msg::PreviewSize.fill(self.language(), &[&self.layout.preview_size]));
+        self.save_layout();
+    }
+
+    fn save_layout(&mut self) {
+        if let Some(path) = &self.config_file {
+            if let Err(e) = Config::save_layout(path, &self.layout) {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            }
+        }
+    }
+
+    pub fn show_log(&mut self) {
+        self.refresh_log();
+        self.input_mode = InputMode::Log;
+    }
+
+    pub fn exit_log(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.log_search.clear();
+        self.log_matches.clear();
+    }
+
+    /// Opens the search prompt with the previous query
+    pub fn start_log_search(&mut self) {
+        self.input_mode = InputMode::LogSearch {
+            query: TextInput::new(self.log_search.clone()),
+        };
+    }
+
+    pub fn cancel_log_search(&mut self) {
+        self.input_mode = InputMode::Log;
+    }
+
+    /// Highlights the commits matching the typed query and jumps to the first one. An empty
+    /// query clears the search.
+    pub fn submit_log_search(&mut self) {
+        let InputMode::LogSearch { query } = &self.input_mode else {
+            return;
+        };
+        self.log_search = query.trim().to_string();
+        self.input_mode = InputMode::Log;
+        let Some(query) = LogQuery::parse(&self.log_search) else {
+            self.log_matches.clear();
+            self.status.info(Msg::SearchCleared.text(self.language()));
+            return;
+        };
+        match GitOperations::search_log(&self.repo, &query, LOG_MAX_COUNT) {
+            Ok(hashes) => self.log_matches = hashes.into_iter().collect(),
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+                return;
+            },
+        }
+        let first = self
+            .log_entries
+            .iter()
+            .position(|line| self.is_log_match(line));
+        if first.is_some() {
+            self.log_state.select(first);
+        }
+        let message = match self.log_match_positions().len() {
+            0 => Msg::NoCommitsMatch.fill(self.language(), &[&self.log_search]),
+            count => Msg::CommitsMatch.fill(self.language(), &[&count, &self.log_search]),
+        };
+        self.status.info(message);
+    }
+
+    /// Whether a `git log --oneline --graph` line is a commit found by the search
+    pub fn is_log_match(&self, line: &str) -> bool {
+        GitOperations::extract_commit_hash(line).is_some_and(|hash| self.log_matches.contains(hash))
+    }
+
+    fn log_match_positions(&self) -> Vec<usize> {
+        self.log_entries
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| self.is_log_match(line))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Moves to the next (or previous) search match, wrapping around at the ends
+    pub fn next_log_match(&mut self, forward: bool) {
+        let positions = self.log_match_positions();
+        if positions.is_empty() {
+            self.status.info(if self.log_search.is_empty() {
+                Msg::NoLogSearch.text(self.language()).to_string()
+            } else {
+                Msg::NoCommitsMatch.fill(self.language(), &[&self.log_search])
+            });
+            return;
+        }
+        let current = self.log_state.selected().unwrap_or(0);
+        let target = if forward {
+            positions.iter().position(|&i| i > current).unwrap_or(0)
+        } else {
+            positions
+                .iter()
+                .rposition(|&i| i < current)
+                .unwrap_or(positions.len() - 1)
+        };
+        self.log_state.select(Some(positions[target]));
+        self.status
+            .info(Msg::MatchPosition.fill(self.language(), &[&(target + 1), &positions.len()]));
+    }
+
+    pub fn refresh_log(&mut self) {
+        match GitOperations::get_log(&self.repo, LOG_MAX_COUNT) {
+            Ok(entries) => {
+                self.log_entries = entries;
+                let first_commit = self
+                    .log_entries
+                    .iter()
+                    .position(|line| GitOperations::extract_commit_hash(line).is_some());
+                self.log_state.select(first_commit);
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    /// Moves to the next line that represents a commit, skipping graph-only lines
+    pub fn next_log_entry(&mut self) {
+        let start = self.log_state.selected().map_or(0, |i| i + 1);
+        if let Some(offset) = self
+            .log_entries
+            .iter()
+            .skip(start)
+            .position(|line| GitOperations::extract_commit_hash(line).is_some())
+        {
+            self.log_state.select(Some(start + offset));
+        }
+    }
+
+    /// Moves to the previous line that represents a commit, skipping graph-only lines
+    pub fn previous_log_entry(&mut self) {
+        let end = self.log_state.selected().unwrap_or(0);
+        if let Some(i) = self.log_entries[..end.min(self.log_entries.len())]
+            .iter()
+            .rposition(|line| GitOperations::extract_commit_hash(line).is_some())
+        {
+            self.log_state.select(Some(i));
+        }
+    }
+
+    /// Moves the log selection by a page or to either end, landing on a commit line
+    pub fn jump_log(&mut self, jump: ScrollJump) {
+        let Some(last) = self.log_entries.len().checked_sub(1) else {
+            return;
+        };
+        let (page, _) = page_bounds(self.areas.log.area, 0);
+        let selected = self.log_state.selected().unwrap_or(0);
+        let target = jump.apply(selected, page, last);
+        let is_commit =
+            |i: &usize| GitOperations::extract_commit_hash(&self.log_entries[*i]).is_some();
+        let found = if jump.is_forward() {
+            (target..=last)
+                .find(is_commit)
+                .or_else(|| (0..target).rev().find(is_commit))
+        } else {
+            (0..=target)
+                .rev()
+                .find(is_commit)
+                .or_else(|| (target..=last).find(is_commit))
+        };
+        if found.is_some() {
+            self.log_state.select(found);
+        }
+    }
+
+    /// Starts a bisect, or asks to end the running one
+    pub fn toggle_bisect(&mut self) {
+        if self.bisect.is_none() {
+            self.run_bisect_command(GitOperations::bisect_start);
+        } else {
+            self.input_mode = InputMode::Confirm {
+                message: Msg::ConfirmEndBisect.text(self.language()).to_string(),
+                action: ConfirmAction::BisectReset,
+            };
+        }
+    }
+
+    /// Marks the checked-out commit as good
+    pub fn bisect_good(&mut self) {
+        self.mark_bisect(GitOperations::bisect_good);
+    }
+
+    /// Marks the checked-out commit as bad
+    pub fn bisect_bad(&mut self) {
+        self.mark_bisect(GitOperations::bisect_bad);
+    }
+
+    /// Skips the checked-out commit, e.g. when it cannot be tested
+    pub fn bisect_skip(&mut self) {
+        self.mark_bisect(GitOperations::bisect_skip);
+    }
+
+    /// Marks the commit selected in the log as good or bad, starting a bisect if needed.
+    /// This is the usual way to begin: pick a known good and a known bad commit.
+    pub fn mark_selected_commit(&mut self, good: bool) {
+        let Some(hash) = self.selected_commit_hash() else {
+            self.status
+                .info(Msg::NoCommitSelected.text(self.language()));
+            return;
+        };
+        if self.bisect.is_none() {
+            if let Err(e) = GitOperations::bisect_start(&self.repo) {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+                return;
+            }
+        }
+        let op = if good {
+            GitOperations::bisect_good
+        } else {
+            GitOperations::bisect_bad
+        };
+        self.run_bisect_command(|repo| op(repo, Some(&hash)));
+        self.refresh_log();
+    }
+
+    fn mark_bisect(&mut self, op: fn(&RepoContext, Option<&str>) -> Result<Message, GitError>) {
+        if self.bisect.is_none() {
+            self.status.info(Msg::NotBisecting.text(self.language()));
+            return;
+        }
+        self.run_bisect_command(|repo| op(repo, None));
+    }
+
+    fn run_bisect_command(&mut self, op: impl FnOnce(&RepoContext) -> Result<Message, GitError>) {
+        let result = op(&self.repo);
+        self.refresh_files();
+        match result {
+            Ok(message) => self.status.success(message.text(self.language())),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    pub fn show_branches(&mut self) {
+        self.refresh_branches();
+        self.input_mode = InputMode::Branches;
+    }
+
+    pub fn exit_branches(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Reloads the branch list, keeping the selection on the same row where possible
+    pub fn refresh_branches(&mut self) {
+        match GitOperations::get_branches(&self.repo) {
+            Ok(branches) => {
+                self.branches = branches;
+                let selected = self.branch_state.selected().unwrap_or(0);
+                self.branch_state.select(
+                    (!self.branches.is_empty()).then(|| selected.min(self.branches.len() - 1)),
+                );
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    pub fn next_branch(&mut self) {
+        if let Some(i) = self.branch_state.selected() {
+            if i + 1 < self.branches.len() {
+                self.branch_state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_branch(&mut self) {
+        if let Some(i) = self.branch_state.selected() {
+            self.branch_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn selected_branch(&self) -> Option<&BranchEntry> {
+        self.branches.get(self.branch_state.selected()?)
+    }
+
+    /// Asks before deleting the selected branch, saying whether its commits are merged
+    pub fn delete_selected_branch(&mut self) {
+        let Some(branch) = self.selected_branch() else {
+            self.status
+                .info(Msg::NoBranchSelected.text(self.language()));
+            return;
+        };
+        if branch.is_head {
+            self.status
+                .info(Msg::CannotDeleteHead.text(self.language()));
+            return;
+        }
+        let language = self.language();
+        let merge_status = if branch.merged {
+            Msg::BranchMerged
+        } else {
+            Msg::BranchNotMerged
+        }
+        .text(language);
+        let (message, action) = match (&branch.remote, branch.remote_branch()) {
+            (Some(remote), Some(remote_branch)) => (
+                Msg::ConfirmDeleteRemoteBranch
+                    .fill(language, &[&remote_branch, remote, &merge_status]),
+                ConfirmAction::DeleteRemoteBranch {
+                    remote: remote.clone(),
+                    branch: remote_branch.to_string(),
+                },
+            ),
+            _ => (
+                Msg::ConfirmDeleteBranch.fill(language, &[&branch.name, &merge_status]),
+                ConfirmAction::DeleteBranch {
+                    name: branch.name.clone(),
+                    force: false,
+                },
+            ),
+        };
+        self.input_mode = InputMode::Confirm { message, action };
+    }
+
+    /// `git branch -d` refuses branches that are merged into neither HEAD nor their upstream;
+    /// for those it asks again before `-D`
+    fn execute_delete_branch(&mut self, name: &str, force: bool) {
+        match GitOperations::delete_branch(&self.repo, name, force) {
+            Ok(message) => self.status.success(message.text(self.language())),
+            Err(e) => {
+                let merged = self
+                    .branches
+                    .iter()
+                    .any(|branch| branch.remote.is_none() && branch.name == name && branch.merged);
+                if force || merged {
+                    self.status.error(Msg::Error.fill(self.language(), &[&e]));
+                } else {
+                    self.input_mode = InputMode::Confirm {
+                        message: Msg::ConfirmForceDeleteBranch.fill(self.language(), &[&name]),
+                        action: ConfirmAction::DeleteBranch {
+                            name: name.to_string(),
+                            force: true,
+                        },
+                    };
+                }
+            },
+        }
+        self.refresh_branches();
+    }
+
+    /// Asks for a new name for the selected local branch, starting from the current one
+    pub fn start_rename_branch(&mut self) {
+        let Some(branch) = self.selected_local_branch() else {
+            return;
+        };
+        self.input_mode = InputMode::BranchPrompt {
+            branch: branch.name.clone(),
+            prompt: BranchPrompt::Rename,
+            input: TextInput::new(branch.name),
+        };
+    }
+
+    /// Asks which branch the selected local branch tracks: its upstream, or else the branch
+    /// of the same name on `origin` (or the first remote)
+    pub fn start_set_upstream(&mut self) {
+        let Some(branch) = self.selected_local_branch() else {
+            return;
+        };
+        let upstream = branch.upstream.clone().unwrap_or_else(|| {
+            let remotes = GitOperations::get_remotes(&self.repo).unwrap_or_default();
+            remotes
+                .iter()
+                .find(|remote| *remote == "origin")
+                .or_else(|| remotes.first())
+                .map_or_else(String::new, |remote| format!("{remote}/{}", branch.name))
+        });
+        self.input_mode = InputMode::BranchPrompt {
+            branch: branch.name,
+            prompt: BranchPrompt::SetUpstream,
+            input: TextInput::new(upstream),
+        };
+    }
+
+    /// The selected branch if it is local; remote-tracking branches have no name or upstream
+    /// of their own to change
+    fn selected_local_branch(&mut self) -> Option<BranchEntry> {
+        let Some(branch) = self.selected_branch().cloned() else {
+            self.status
+                .info(Msg::NoBranchSelected.text(self.language()));
+            return None;
+        };
+        if branch.remote.is_some() {
+            self.status
+                .info(Msg::SelectLocalBranch.text(self.language()));
+            return None;
+        }
+        Some(branch)
+    }
+
+    pub fn cancel_branch_prompt(&mut self) {
+        self.input_mode = match self.input_mode {
+            InputMode::BranchPrompt {
+                prompt: BranchPrompt::Create,
+                ..
+            } => InputMode::Normal,
+            _ => InputMode::Branches,
+        };
+    }
+
+    /// Renames the branch or changes its upstream, then selects it again in the list
+    pub fn submit_branch_prompt(&mut self) {
+        let InputMode::BranchPrompt {
+            branch,
+            prompt,
+            input,
+        } = &self.input_mode
+        else {
+            return;
+        };
+        let (branch, prompt, value) = (branch.clone(), *prompt, input.trim().to_string());
+        let result = match prompt {
+            BranchPrompt::Create | BranchPrompt::Rename if value.is_empty() => {
+                self.status.info(Msg::EmptyBranchName.text(self.language()));
+                return;
+            },
+            BranchPrompt::Rename if value == branch => {
+                self.input_mode = InputMode::Branches;
+                return;
+            },
+            BranchPrompt::Create => GitOperations::create_branch(&self.repo, &value),
+            BranchPrompt::Rename => GitOperations::rename_branch(&self.repo, &branch, &value),
+            BranchPrompt::SetUpstream => {
+                GitOperations::set_branch_upstream(&self.repo, &branch, &value)
+            },
+        };
+        match result {
+            Ok(message) => self.status.success(message.text(self.language())),
+            Err(e) => {
+                // Keep the prompt open so the typo can be fixed
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+                return;
+            },
+        }
+        self.refresh_files();
+        if prompt == BranchPrompt::Create {
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+        self.input_mode = InputMode::Branches;
+        self.refresh_branches();
+        let name = match prompt {
+            BranchPrompt::Create | BranchPrompt::Rename => value,
+            BranchPrompt::SetUpstream => branch,
+        };
+        if let Some(i) = self
+            .branches
+            .iter()
+            .position(|entry| entry.remote.is_none() && entry.name == name)
+        {
+            self.branch_state.select(Some(i));
+        }
+    }
+
+    /// Asks for the name of a branch to create at the detached HEAD, keeping the commits
+    /// made there
+    pub fn create_branch_at_head(&mut self) {
+        if self.detached.is_none() {
+            self.status.info(Msg::HeadNotDetached.text(self.language()));
+            return;
+        }
+        let hash = self
+            .head
+            .as_ref()
+            .map_or_else(|| String::from("HEAD"), |head| head.hash.clone());
+        self.input_mode = InputMode::BranchPrompt {
+            branch: hash,
+            prompt: BranchPrompt::Create,
+            input: TextInput::default(),
+        };
+    }
+
+    /// Goes back from a detached HEAD to the branch checked out before; asks first when
+    /// commits made on the detached HEAD would be left on no branch
+    pub fn switch_to_previous_branch(&mut self) {
+        let Some(detached) = &self.detached else {
+            self.status.info(Msg::HeadNotDetached.text(self.language()));
+            return;
+        };
+        let Some(branch) = detached.previous_branch.clone() else {
+            self.status
+                .info(Msg::NoPreviousBranch.text(self.language()));
+            return;
+        };
+        if detached.unreachable == 0 {
+            self.execute_checkout_branch(&branch);
+            return;
+        }
+        self.input_mode = InputMode::Confirm {
+            message: Msg::ConfirmLeaveDetached
+                .fill(self.language(), &[&detached.unreachable, &branch]),
+            action: ConfirmAction::SwitchBranch(branch),
+        };
+    }
+
+    fn execute_checkout_branch(&mut self, name: &str) {
+        match GitOperations::checkout_branch(&self.repo, name) {
+            Ok(message) => self.status.success(message.text(self.language())),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+        self.refresh_files();
+    }
+
+    /// Offers fast-forward, `--no-ff` and squash merges of the selected branch
+    pub fn show_merge_picker(&mut self) {
+        let Some(branch) = self.selected_branch() else {
+            self.status
+                .info(Msg::NoBranchSelected.text(self.language()));
+            return;
+        };
+        if branch.is_head {
+            self.status.info(Msg::CannotMergeHead.text(self.language()));
+            return;
+        }
+        self.input_mode = InputMode::MergePicker {
+            branch: branch.name.clone(),
+        };
+        self.merge.state.select(Some(0));
+    }
+
+    pub fn exit_merge_picker(&mut self) {
+        self.input_mode = InputMode::Branches;
+    }
+
+    pub fn next_merge_strategy(&mut self) {
+        if let Some(i) = self.merge.state.selected() {
+            if i + 1 < MergeStrategy::ALL.len() {
+                self.merge.state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_merge_strategy(&mut self) {
+        if let Some(i) = self.merge.state.selected() {
+            self.merge.state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    /// Runs the chosen merge. Conflicts lead to the file list, where `Enter` opens the
+    /// conflict view and `A` aborts the merge.
+    pub fn merge_with_selected_strategy(&mut self) {
+        let InputMode::MergePicker { branch } = &self.input_mode else {
+            return;
+        };
+        let branch = branch.clone();
+        let Some(strategy) = self
+            .merge
+            .state
+            .selected()
+            .and_then(|i| MergeStrategy::ALL.get(i).copied())
+        else {
+            return;
+        };
+        let result = GitOperations::merge_branch(&self.repo, &branch, strategy);
+        self.refresh_files();
+        let conflicts = self
+            .files
+            .iter()
+            .filter(|entry| entry.is_conflicted())
+            .count();
+        match result {
+            Ok(message) => {
+                self.status.success(message.text(self.language()));
+                if strategy == MergeStrategy::Squash {
+                    self.input_mode = InputMode::Normal;
+                } else {
+                    self.show_branches();
+                }
+            },
+            Err(_) if conflicts > 0 => {
                 self.input_mode = InputMode::Normal;
-                self.refresh_files();
+                let message = if strategy == MergeStrategy::Squash {
+                    Msg::SquashConflicts
+                } else {
+                    Msg::MergeConflicts
+                };
+                self.status
+                    .error(message.fill(self.language(), &[&branch, &conflicts]));
+                self.select_first_conflict();
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+                self.input_mode = InputMode::Branches;
+            },
+        }
+    }
+
+    /// Asks before deleting every local branch whose upstream was deleted on the remote.
+    /// Those are often merged by squash or rebase, so `-D` is used and the prompt says how
+    /// many are not merged into HEAD.
+    pub fn delete_gone_branches(&mut self) {
+        let gone: Vec<&BranchEntry> = self
+            .branches
+            .iter()
+            .filter(|branch| branch.upstream_gone && !branch.is_head)
+            .collect();
+        if gone.is_empty() {
+            self.status.info(Msg::NoGoneBranches.text(self.language()));
+            return;
+        }
+        let names: Vec<String> = gone.iter().map(|branch| branch.name.clone()).collect();
+        let unmerged = gone.iter().filter(|branch| !branch.merged).count();
+        self.input_mode = InputMode::Confirm {
+            message: Msg::ConfirmDeleteGone.fill(
+                self.language(),
+                &[&names.len(), &names.join(", "), &unmerged],
+            ),
+            action: ConfirmAction::DeleteGoneBranches(names),
+        };
+    }
+
+    fn execute_delete_gone_branches(&mut self, names: &[String]) {
+        let errors: Vec<GitError> = names
+            .iter()
+            .filter_map(|name| GitOperations::delete_branch(&self.repo, name, true).err())
+            .collect();
+        match errors.first() {
+            None => self
+                .status
+                .success(Msg::DeletedGone.fill(self.language(), &[&names.len()])),
+            Some(e) => self.status.error(Msg::DeleteGoneFailed.fill(
+                self.language(),
+                &[&(names.len() - errors.len()), &names.len(), e],
+            )),
+        }
+        self.refresh_branches();
+    }
+
+    fn execute_delete_remote_branch(&mut self, remote: String, branch: String) {
+        let task = Task::DeleteRemoteBranch(format!("{remote}/{branch}"));
+        self.start_remote_task(task, move |repo, on_progress| {
+            GitOperations::delete_remote_branch(repo, &remote, &branch, on_progress)
+        });
+    }
+
+    pub fn show_maintenance(&mut self) {
+        self.refresh_repo_stats();
+        if self.maintenance.tasks.selected().is_none() {
+            self.maintenance.tasks.select(Some(0));
+        }
+        self.input_mode = InputMode::Maintenance;
+    }
+
+    pub fn exit_maintenance(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn refresh_repo_stats(&mut self) {
+        match GitOperations::get_repo_stats(&self.repo) {
+            Ok(stats) => self.maintenance.stats = stats,
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    pub fn next_maintenance_task(&mut self) {
+        if let Some(i) = self.maintenance.tasks.selected() {
+            if i + 1 < MaintenanceTask::ALL.len() {
+                self.maintenance.tasks.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_maintenance_task(&mut self) {
+        if let Some(i) = self.maintenance.tasks.selected() {
+            self.maintenance.tasks.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    /// Runs the selected command in the background; `git prune` asks first
+    pub fn run_selected_maintenance(&mut self) {
+        let Some(task) = self
+            .maintenance
+            .tasks
+            .selected()
+            .and_then(|i| MaintenanceTask::ALL.get(i).copied())
+        else {
+            return;
+        };
+        if task == MaintenanceTask::Prune {
+            self.input_mode = InputMode::Confirm {
+                message: Msg::ConfirmPrune.text(self.language()).to_string(),
+                action: ConfirmAction::Prune,
+            };
+            return;
+        }
+        self.execute_maintenance(task);
+    }
+
+    fn execute_maintenance(&mut self, task: MaintenanceTask) {
+        self.start_remote_task(Task::Maintenance(task), move |repo, on_progress| {
+            GitOperations::run_maintenance(repo, task, on_progress)
+        });
+    }
+
+    /// Asks where to export a snapshot of the selected commit in the log, the selected
+    /// branch in the branch list, or HEAD. The file starts out as a zip next to the
+    /// repository.
+    pub fn start_archive(&mut self) {
+        let target = match self.input_mode {
+            InputMode::Log => self.selected_commit_hash(),
+            InputMode::Branches => self.selected_branch().map(|branch| branch.name.clone()),
+            _ => Some(String::from("HEAD")),
+        };
+        let Some(target) = target else {
+            self.status.info(Msg::NothingToExport.text(self.language()));
+            return;
+        };
+        let label = match target.as_str() {
+            "HEAD" => match &self.detached {
+                Some(_) => self
+                    .head
+                    .as_ref()
+                    .map_or_else(|| target.clone(), |head| head.hash.clone()),
+                None => self.current_branch.replace('/', "-"),
+            },
+            _ => target.replace('/', "-"),
+        };
+        let root = std::fs::canonicalize(self.repo.dir())
+            .unwrap_or_else(|_| self.repo.dir().to_path_buf());
+        let name = root.file_name().map_or_else(
+            || String::from("snapshot"),
+            |name| name.to_string_lossy().to_string(),
+        );
+        let path = root
+            .parent()
+            .unwrap_or(&root)
+            .join(format!("{name}-{label}.zip"));
+        let previous = std::mem::take(&mut self.input_mode);
+        self.input_mode = InputMode::ArchivePrompt {
+            target,
+            input: TextInput::new(path.to_string_lossy().to_string()),
+            previous: Box::new(previous),
+        };
+    }
+
+    pub fn cancel_archive_prompt(&mut self) {
+        if let InputMode::ArchivePrompt { previous, .. } = &mut self.input_mode {
+            self.input_mode = std::mem::take(previous.as_mut());
+        }
+    }
+
+    /// Starts `git archive` in the background. A file that exists already or a name without
+    /// a known extension keeps the prompt open; relative paths are taken from the repository.
+    pub fn submit_archive_prompt(&mut self) {
+        let InputMode::ArchivePrompt { target, input, .. } = &self.input_mode else {
+            return;
+        };
+        let output = self.repo.path(input.trim());
+        if input.trim().is_empty() || archive_format(&output.to_string_lossy()).is_none() {
+            self.status.info(Msg::ArchiveFormat.text(self.language()));
+            return;
+        }
+        if output.exists() {
+            self.status
+                .info(Msg::AlreadyExists.fill(self.language(), &[&output.display()]));
+            return;
+        }
+        let target = target.clone();
+        self.cancel_archive_prompt();
+        self.start_remote_task(Task::Archive(target.clone()), move |repo, on_progress| {
+            GitOperations::archive(repo, &target, &output, on_progress)
+        });
+    }
+
+    pub fn show_reflog(&mut self) {
+        self.refresh_reflog();
+        self.input_mode = InputMode::Reflog;
+    }
+
+    pub fn exit_reflog(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn refresh_reflog(&mut self) {
+        match GitOperations::get_reflog(&self.repo, REFLOG_MAX_COUNT) {
+            Ok(entries) => {
+                self.reflog_entries = entries;
+                self.reflog_state
+                    .select((!self.reflog_entries.is_empty()).then_some(0));
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    pub fn next_reflog_entry(&mut self) {
+        if let Some(i) = self.reflog_state.selected() {
+            if i + 1 < self.reflog_entries.len() {
+                self.reflog_state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous_reflog_entry(&mut self) {
+        if let Some(i) = self.reflog_state.selected() {
+            self.reflog_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn selected_reflog_entry(&self) -> Option<&ReflogEntry> {
+        self.reflog_entries.get(self.reflog_state.selected()?)
+    }
+
+    pub fn show_reflog_preview(&mut self) {
+        let Some(entry) = self.selected_reflog_entry().cloned() else {
+            self.status
+                .info(Msg::NoReflogEntrySelected.text(self.language()));
+            return;
+        };
+        match GitOperations::get_commit_diff(&self.repo, &entry.hash) {
+            Ok(content) => {
+                self.input_mode = InputMode::Preview {
+                    content: content.into(),
+                    file_path: format!("{} {}", entry.selector, entry.hash),
+                };
+                self.preview_return_mode = InputMode::Reflog;
+                self.preview_scroll = 0;
+            },
+            Err(e) => {
+                self.status
+                    .error(Msg::PreviewError.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    /// Asks before `git reset --hard` to the selected entry, which throws away local changes
+    pub fn reset_to_reflog_entry(&mut self) {
+        if let Some(entry) = self.selected_reflog_entry() {
+            self.input_mode = InputMode::Confirm {
+                message: Msg::ConfirmResetToReflog
+                    .fill(self.language(), &[&entry.selector, &entry.message]),
+                action: ConfirmAction::ResetHard(entry.hash.clone()),
+            };
+        }
+    }
+
+    pub fn checkout_reflog_entry(&mut self) {
+        if let Some(entry) = self.selected_reflog_entry() {
+            self.input_mode = InputMode::Confirm {
+                message: Msg::ConfirmCheckoutReflog
+                    .fill(self.language(), &[&entry.selector, &entry.message]),
+                action: ConfirmAction::Checkout(entry.hash.clone()),
+            };
+        }
+    }
+
+    fn run_on_reflog_target(
+        &mut self,
+        op: fn(&RepoContext, &str) -> Result<Message, GitError>,
+        target: &str,
+    ) {
+        match op(&self.repo, target) {
+            Ok(message) => self.status.success(message.text(self.language())),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+        self.refresh_files();
+        self.show_reflog();
+    }
+
+    /// Asks before resetting the current branch to the selected commit. The dialog gets
+    /// more insistent with what the mode throws away: `--hard` wants the hash typed.
+    pub fn reset_to_selected_commit(&mut self, mode: ResetMode) {
+        let Some(target) = self.selected_commit_hash() else {
+            self.status
+                .info(Msg::SelectCommitToReset.text(self.language()));
+            return;
+        };
+        let language = self.language();
+        let branch = &self.current_branch;
+        let flag = mode.flag();
+        let action = ConfirmAction::Reset {
+            mode,
+            target: target.clone(),
+        };
+        self.input_mode = match mode {
+            ResetMode::Soft => InputMode::Confirm {
+                message: Msg::ConfirmSoftReset.fill(language, &[branch, &target, &flag]),
+                action,
+            },
+            ResetMode::Mixed => InputMode::Confirm {
+                message: Msg::ConfirmMixedReset.fill(language, &[branch, &target, &flag]),
+                action,
+            },
+            ResetMode::Hard => InputMode::TypedConfirm {
+                message: Msg::ConfirmHardReset
+                    .fill(language, &[branch, &target, &flag, &self.files.len()]),
+                expected: target,
+                typed: TextInput::default(),
+                action,
+            },
+        };
+    }
+
+    fn execute_reset(&mut self, mode: ResetMode, target: &str) {
+        match GitOperations::reset(&self.repo, mode, target) {
+            Ok(message) => self.status.success(message.text(self.language())),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+        self.refresh_files();
+        self.show_log();
+    }
+
+    /// Commits the staged changes, or all tracked changes when nothing is staged, as a
+    /// `fixup!` of the selected commit, which stays selected
+    pub fn fixup_selected_commit(&mut self) {
+        let Some(target) = self.selected_commit_hash() else {
+            self.status
+                .info(Msg::SelectCommitToFixup.text(self.language()));
+            return;
+        };
+        let all = !self.files.iter().any(FileEntry::is_staged);
+        if all && !self.files.iter().any(FileEntry::is_unstaged) {
+            self.status.info(Msg::NoFixupChanges.text(self.language()));
+            return;
+        }
+        let sign = GitOperations::is_commit_signing_enabled(&self.repo);
+        match GitOperations::commit_fixup(&self.repo, &target, sign, all) {
+            Ok(message) => self.status.success(message.text(self.language())),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+        self.refresh_files();
+        self.refresh_log();
+        if let Some(i) = self.log_entries.iter().position(|line| {
+            GitOperations::extract_commit_hash(line).is_some_and(|hash| hash == target)
+        }) {
+            self.log_state.select(Some(i));
+        }
+    }
+
+    /// Asks before rebasing from the selected commit to fold in the `fixup!` commits above it
+    pub fn autosquash_from_selected_commit(&mut self) {
+        let Some(target) = self.selected_commit_hash() else {
+            self.status
+                .info(Msg::SelectAutosquashBase.text(self.language()));
+            return;
+        };
+        let selected = self.log_state.selected().unwrap_or(0);
+        let fixups = self.log_entries[..selected]
+            .iter()
+            .filter(|line| line.contains("fixup! ") || line.contains("squash! "))
+            .count();
+        if fixups == 0 {
+            self.status
+                .info(Msg::NoFixupCommits.fill(self.language(), &[&target]));
+            return;
+        }
+        self.input_mode = InputMode::Confirm {
+            message: Msg::ConfirmAutosquash
+                .fill(self.language(), &[&self.current_branch, &target, &fixups]),
+            action: ConfirmAction::Autosquash(target),
+        };
+    }
+
+    fn execute_autosquash(&mut self, target: &str) {
+        let result = GitOperations::autosquash(&self.repo, target);
+        self.refresh_files();
+        let conflicts = self
+            .files
+            .iter()
+            .filter(|entry| entry.is_conflicted())
+            .count();
+        match result {
+            Ok(message) => {
+                self.status.success(message.text(self.language()));
+                self.show_log();
+            },
+            Err(_) if conflicts > 0 => {
+                self.input_mode = InputMode::Normal;
+                self.status
+                    .error(Msg::RebaseConflicts.fill(self.language(), &[&conflicts]));
+                self.select_first_conflict();
+            },
+            Err(e) => {
+                self.status.error(Msg::Error.fill(self.language(), &[&e]));
+                self.show_log();
+            },
+        }
+    }
+
+    pub fn selected_commit_hash(&self) -> Option<String> {
+        let line = self.log_entries.get(self.log_state.selected()?)?;
+        GitOperations::extract_commit_hash(line).map(String::from)
+    }
+
+    /// Web page of the selected commit in the log, or else of the selected file at the
+    /// current branch, on the forge `origin` points to
+    pub fn forge_url(&self) -> Result<String, String> {
+        let remote =
+            GitOperations::get_remote_url(&self.repo, "origin").map_err(|e| e.to_string())?;
+        let forge = ForgeRepo::from_remote_url(&remote)?;
+        if self.input_mode == InputMode::Log {
+            let hash = self
+                .selected_commit_hash()
+                .ok_or_else(|| Msg::NoCommitSelected.text(self.language()))?;
+            let hash =
+                GitOperations::resolve_commit(&self.repo, &hash).map_err(|e| e.to_string())?;
+            return Ok(forge.commit_url(&hash));
+        }
+        let file = self
+            .selected_file()
+            .ok_or_else(|| Msg::NoFileSelected.text(self.language()))?;
+        if file.index_status == '?' {
+            return Err(Msg::UntrackedNotOnRemote.fill(self.language(), &[&file.path]));
+        }
+        // A file renamed in the working tree is still at its old path on the branch
+        let path = file.orig_path.as_deref().unwrap_or(&file.path);
+        let rev = if self.current_branch.starts_with('(') {
+            GitOperations::resolve_commit(&self.repo, "HEAD").map_err(|e| e.to_string())?
+        } else {
+            self.current_branch.clone()
+        };
+        Ok(forge.file_url(&rev, path))
+    }
+
+    fn copy(&mut self, text: String, what: &str) {
+        if text.is_empty() {
+            self.status.info(Msg::NothingToCopy.text(self.language()));
+        } else if text.len() > clipboard::MAX_BYTES {
+            let size = binary::format_size(text.len());
+            let limit = binary::format_size(clipboard::MAX_BYTES);
+            self.status
+                .error(Msg::TooLargeToCopy.fill(self.language(), &[&what, &size, &limit]));
+        } else {
+            self.status
+                .success(Msg::Copied.fill(self.language(), &[&what]));
+            self.pending_clipboard = Some(text);
+        }
+    }
+
+    pub fn copy_selected_path(&mut self) {
+        match self.selected_file() {
+            Some(file) => {
+                let path = file.path.clone();
+                let what = Msg::CopyPath.fill(self.language(), &[&path]);
+                self.copy(path, &what);
+            },
+            None => self.status.info(Msg::NoFileSelected.text(self.language())),
+        }
+    }
+
+    /// The diff in the preview panel or the fullscreen preview, as shown
+    pub fn copy_preview(&mut self) {
+        let text = self.preview_content.to_string();
+        let what = Msg::CopyDiff.fill(self.language(), &[&self.preview_content.line_count()]);
+        self.copy(text, &what);
+    }
+
+    /// Full hash of the commit selected in the log
+    pub fn copy_selected_commit_hash(&mut self) {
+        let Some(hash) = self.selected_commit_hash() else {
+            self.status
+                .info(Msg::NoCommitSelected.text(self.language()));
+            return;
+        };
+        match GitOperations::resolve_commit(&self.repo, &hash) {
+            Ok(full) => {
+                let what = Msg::CopyHash.fill(self.language(), &[&hash]);
+                self.copy(full, &what);
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    pub fn open_on_forge(&mut self) {
+        match self
+            .forge_url()
+            .and_then(|url| forge::open_url(&url).map(|()| url))
+        {
+            Ok(url) => self
+                .status
+                .success(Msg::Opened.fill(self.language(), &[&url])),
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+    }
+
+    /// Opens the whole `git show` of the selected commit fullscreen, from the log or the
+    /// commit details view
+    pub fn show_commit_preview(&mut self) {
+        let hash = if self.input_mode == InputMode::CommitDetails {
+            Some(self.commit_details.details.hash.clone())
+        } else {
+            self.selected_commit_hash()
+        };
+        let Some(hash) = hash else {
+            self.status
+                .info(Msg::NoCommitSelected.text(self.language()));
+            return;
+        };
+        match GitOperations::get_commit_diff(&self.repo, &hash) {
+            Ok(content) => {
+                self.preview_return_mode =
+                    std::mem::replace(&mut self.input_mode, InputMode::Normal);
+                self.input_mode = InputMode::Preview {
+                    content: content.into(),
+                    file_path: format!("commit {hash}"),
+                };
+                self.preview_scroll = 0;
+            },
+            Err(e) => {
+                self.status
+                    .error(Msg::PreviewError.fill(self.language(), &[&e]));
+            },
+        }
+    }
+
+    /// Opens the metadata and changed files of the commit selected in the log
+    pub fn show_commit_details(&mut self) {
+        let Some(hash) = self.selected_commit_hash() else {
+            self.status
+                .info(Msg::NoCommitSelected.text(self.language()));
+            return;
+        };
+        match GitOperations::get_commit_details(&self.repo, &hash) {
+            Ok(details) => {
+                let selected = (!details.files.is_empty()).then_some(0);
+                self.commit_details = CommitDetailsScreen {
+                    details,
+                    ..CommitDetailsScreen::default()
+                };
+                self.commit_details.state.select(selected);
+                self.input_mode = InputMode::CommitDetails;
+            },
+            Err(e) => self
+                .status
+                .error(Msg::CommitDetailsError.fill(self.language(), &[&e])),
+        }
+    }
+
+    pub fn exit_commit_details(&mut self) {
+        self.input_mode = InputMode::Log;
+        self.commit_details = CommitDetailsScreen::default();
+    }
+
+    pub fn next_commit_details_row(&mut self) {
+        self.jump_commit_details_by(1);
+    }
+
+    pub fn previous_commit_details_row(&mut self) {
+        self.jump_commit_details_by(-1);
+    }
+
+    fn jump_commit_details_by(&mut self, step: isize) {
+        let Some(last) = self.commit_details.rows().len().checked_sub(1) else {
+            return;
+        };
+        let selected = self.commit_details.state.selected().unwrap_or(0);
+        let target = selected.saturating_add_signed(step).min(last);
+        self.commit_details.state.select(Some(target));
+    }
+
+    pub fn jump_commit_details(&mut self, jump: ScrollJump) {
+        let Some(last) = self.commit_details.rows().len().checked_sub(1) else {
+            return;
+        };
+        let (page, _) = page_bounds(self.commit_details.area.area, 0);
+        let selected = self.commit_details.state.selected().unwrap_or(0);
+        self.commit_details
+            .state
+            .select(Some(jump.apply(selected, page, last)));
+    }
+
+    /// Moves to the first row of the next or previous file, skipping over expanded diffs
+    pub fn next_commit_file(&mut self, forward: bool) {
+        let rows = self.commit_details.rows();
+        let Some(selected) = self.commit_details.state.selected() else {
+            return;
+        };
+        let is_file = |i: &usize| matches!(rows[*i], CommitDetailsRow::File(_));
+        let found = if forward {
+            (selected + 1..rows.len()).find(is_file)
+        } else {
+            (0..selected).rev().find(is_file)
+        };
+        if found.is_some() {
+            self.commit_details.state.select(found);
+        }
+    }
+
+    /// Expands the selected file to its diff, or collapses it back onto the file row
+    pub fn toggle_commit_file_diff(&mut self) {
+        let Some(file) = self
+            .commit_details
+            .selected_row()
+            .map(CommitDetailsRow::file)
+        else {
+            return;
+        };
+        if self.commit_details.expanded.remove(&file) {
+            let row = self
+                .commit_details
+                .rows()
+                .iter()
+                .position(|&row| row == CommitDetailsRow::File(file));
+            self.commit_details.state.select(row);
+            return;
+        }
+        if !self.commit_details.diffs.contains_key(&file) {
+            let screen = &self.commit_details;
+            match GitOperations::get_commit_file_diff(
+                &self.repo,
+                &screen.details,
+                &screen.details.files[file],
+                self.diff_options,
+            ) {
+                Ok(diff) => {
+                    self.commit_details.diffs.insert(file, diff.into());
+                },
+                Err(e) => {
+                    self.status
+                        .error(Msg::PreviewError.fill(self.language(), &[&e]));
+                    return;
+                },
+            }
+        }
+        self.commit_details.expanded.insert(file);
+    }
+
+    /// Opens the diff of the selected file of the commit fullscreen
+    pub fn show_commit_file_diff(&mut self) {
+        let screen = &self.commit_details;
+        let Some(file) = screen
+            .selected_row()
+            .map(|row| &screen.details.files[row.file()])
+        else {
+            self.status.info(Msg::NoFileSelected.text(self.language()));
+            return;
+        };
+        match GitOperations::get_commit_file_diff(
+            &self.repo,
+            &screen.details,
+            file,
+            self.diff_options,
+        ) {
+            Ok(content) => {
+                let file_path = file.path.clone();
+                self.preview_return_mode = InputMode::CommitDetails;
+                self.input_mode = InputMode::Preview {
+                    content: content.into(),
+                    file_path,
+                };
+                self.preview_scroll = 0;
+            },
+            Err(e) => self
+                .status
+                .error(Msg::PreviewError.fill(self.language(), &[&e])),
+        }
+    }
+
+    /// Opens the prompt for the refs to compare, starting from `range` and coming back to the
+    /// current view when cancelled
+    pub fn start_compare(&mut self, range: &str) {
+        self.compare.origin = std::mem::take(&mut self.input_mode);
+        self.input_mode = InputMode::CompareInput {
+            input: TextInput::new(range.to_string()),
+        };
+    }
+
+    /// Compares the branch checked out with the selected one in the branch list
+    pub fn compare_selected_branch(&mut self) {
+        let Some(branch) = self.selected_branch() else {
+            return;
+        };
+        let range = format!("{}..{}", self.current_branch, branch.name);
+        self.start_compare(&range);
+    }
+
+    /// Compares the selected commit in the log with HEAD
+    pub fn compare_selected_commit(&mut self) {
+        let Some(hash) = self.selected_commit_hash() else {
+            self.status
+                .info(Msg::NoCommitSelected.text(self.language()));
+            return;
+        };
+        self.start_compare(&format!("{hash}..HEAD"));
+    }
+
+    pub fn cancel_compare_input(&mut self) {
+        self.input_mode = std::mem::take(&mut self.compare.origin);
+    }
+
+    /// Lists the files that differ across the typed range. A single ref is compared with
+    /// HEAD; a range git cannot resolve keeps the prompt open.
+    pub fn submit_compare_input(&mut self) {
+        let InputMode::CompareInput { input } = &self.input_mode else {
+            return;
+        };
+        let range = match input.trim() {
+            "" => {
+                self.status.info(Msg::TypeCompareRefs.text(self.language()));
+                return;
             },
-            Err(e) => {
-                self.status_message = format!("Error: {e}");
+            range if range.contains("..") => range.to_string(),
+            single => format!("{single}..HEAD"),
+        };
+        match GitOperations::get_range_files(&self.repo, &range) {
+            Ok(files) => {
+                let selected = (!files.is_empty()).then_some(0);
+                self.compare.range = range;
+                self.compare.files = files;
+                self.compare.state.select(selected);
+                self.input_mode = InputMode::Compare;
             },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
         }
     }
 
-    pub fn show_help(&mut self) {
-        self.input_mode = InputMode::Help;
-        self.help_scroll = 0;
+    pub fn exit_compare(&mut self) {
+        self.input_mode = std::mem::take(&mut self.compare.origin);
+        self.compare.files.clear();
     }
 
-    pub fn exit_help(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.help_scroll = 0;
+    pub fn next_compare_file(&mut self) {
+        if let Some(last) = self.compare.files.len().checked_sub(1) {
+            let selected = self
+                .compare
+                .state
+                .selected()
+                .map_or(0, |i| (i + 1).min(last));
+            self.compare.state.select(Some(selected));
+        }
     }
 
-    pub fn scroll_help_up(&mut self) {
-        if self.help_scroll > 0 {
-            self.help_scroll -= 1;
+    pub fn previous_compare_file(&mut self) {
+        if !self.compare.files.is_empty() {
+            let selected = self.compare.state.selected().unwrap_or(0);
+            self.compare.state.select(Some(selected.saturating_sub(1)));
         }
     }
 
-    pub fn scroll_help_down(&mut self) {
-        self.help_scroll += 1;
+    pub fn jump_compare(&mut self, jump: ScrollJump) {
+        let Some(last) = self.compare.files.len().checked_sub(1) else {
+            return;
+        };
+        let (page, _) = page_bounds(self.compare.area.area, 0);
+        let selected = self.compare.state.selected().unwrap_or(0);
+        self.compare
+            .state
+            .select(Some(jump.apply(selected, page, last)));
     }
 
-    pub fn handle_confirm(&mut self, confirmed: bool) {
-        if let InputMode::Confirm { action, .. } = &self.input_mode {
-            if confirmed {
-                match action {
-                    ConfirmAction::StageAll | ConfirmAction::UnstageAll => {
-                        self.execute_stage_all();
-                    },
-                }
-            } else {
-                self.status_message = String::from("Operation cancelled");
-            }
-            self.input_mode = InputMode::Normal;
+    /// Reloads the files after the refs moved, e.g. after a fetch
+    pub fn refresh_compare(&mut self) {
+        match GitOperations::get_range_files(&self.repo, &self.compare.range) {
+            Ok(files) => {
+                let selected = self
+                    .compare
+                    .state
+                    .selected()
+                    .map(|i| i.min(files.len().saturating_sub(1)))
+                    .filter(|_| !files.is_empty());
+                self.compare.files = files;
+                self.compare.state.select(selected);
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
         }
     }
 
-    pub fn show_preview(&mut self) {
-        if let Some(i) = self.files_state.selected() {
-            if i < self.files.len() {
-                let file_status = &self.files[i];
-                let chars: Vec<char> = file_status.chars().collect();
-                if chars.len() >= 3 {
-                    let file_path: String =
-                        chars.iter().skip(2).collect::<String>().trim().to_string();
-                    match GitOperations::get_file_diff(&file_path) {
-                        Ok(content) => {
-                            self.input_mode = InputMode::Preview {
-                                content,
-                                file_path: file_path.to_string(),
-                            };
-                            self.preview_scroll = 0;
-                        },
-                        Err(e) => {
-                            self.status_message = format!("Preview error: {}", e);
-                        },
-                    }
-                }
-            }
+    /// Opens the diff of the selected file across the range fullscreen, or with `whole` the
+    /// diff of every file
+    pub fn show_compare_diff(&mut self, whole: bool) {
+        let file = if whole {
+            None
         } else {
-            self.status_message = String::from("No file selected for preview");
+            let Some(file) = self
+                .compare
+                .state
+                .selected()
+                .and_then(|i| self.compare.files.get(i))
+            else {
+                self.status.info(Msg::NoFileSelected.text(self.language()));
+                return;
+            };
+            Some(file)
+        };
+        let range = &self.compare.range;
+        match GitOperations::get_range_diff(&self.repo, range, file, self.diff_options) {
+            Ok(content) => {
+                let file_path = file.map_or_else(|| range.clone(), |file| file.path.clone());
+                self.preview_return_mode = InputMode::Compare;
+                self.input_mode = InputMode::Preview {
+                    content: content.into(),
+                    file_path,
+                };
+                self.preview_scroll = 0;
+            },
+            Err(e) => self
+                .status
+                .error(Msg::PreviewError.fill(self.language(), &[&e])),
         }
     }
 
-    pub fn scroll_preview_up(&mut self) {
-        if self.preview_scroll > 0 {
-            self.preview_scroll -= 1;
+    /// Shows the git config of the repository, read each time the screen opens
+    pub fn show_config(&mut self) {
+        self.refresh_config();
+        if self.config.state.selected().is_none() {
+            self.config.state.select(Some(0));
         }
+        self.input_mode = InputMode::Config;
     }
 
-    pub fn scroll_preview_down(&mut self) {
-        self.preview_scroll += 1;
-    }
-
-    pub fn exit_preview(&mut self) {
+    pub fn exit_config(&mut self) {
         self.input_mode = InputMode::Normal;
-        self.preview_scroll = 0;
+        self.config.entries.clear();
     }
 
-    pub fn update_preview(&mut self) {
-        if !self.show_preview_panel {
-            return;
+    pub fn refresh_config(&mut self) {
+        match GitOperations::get_config_list(&self.repo) {
+            Ok(entries) => self.config.entries = entries,
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
+        }
+        if let Some(i) = self.config.state.selected() {
+            self.config
+                .state
+                .select(Some(i.min(self.config.rows() - 1)));
         }
+    }
 
-        if let Some(i) = self.files_state.selected() {
-            if i < self.files.len() {
-                let file_status = &self.files[i];
-                let chars: Vec<char> = file_status.chars().collect();
-                if chars.len() >= 3 {
-                    let file_path: String =
-                        chars.iter().skip(2).collect::<String>().trim().to_string();
-                    match GitOperations::get_file_diff(&file_path) {
-                        Ok(content) => {
-                            self.preview_content = content;
-                        },
-                        Err(_) => {
-                            self.preview_content = "No preview available".to_string();
-                        },
-                    }
-                } else {
-                    self.preview_content = "Invalid file status".to_string();
-                }
-            } else {
-                self.preview_content = String::new();
+    pub fn next_config_row(&mut self) {
+        if let Some(i) = self.config.state.selected() {
+            if i + 1 < self.config.rows() {
+                self.config.state.select(Some(i + 1));
             }
-        } else {
-            self.preview_content = String::new();
         }
-        self.preview_scroll = 0;
     }
 
-    pub fn toggle_preview_panel(&mut self) {
-        self.show_preview_panel = !self.show_preview_panel;
-        if self.show_preview_panel {
-            self.update_preview();
+    pub fn previous_config_row(&mut self) {
+        if let Some(i) = self.config.state.selected() {
+            self.config.state.select(Some(i.saturating_sub(1)));
         }
     }
 
-    pub fn get_current_file_path(&self) -> Option<String> {
-        if let Some(i) = self.files_state.selected() {
-            if i < self.files.len() {
-                let file_status = &self.files[i];
-                let chars: Vec<char> = file_status.chars().collect();
-                if chars.len() >= 3 {
-                    return Some(chars.iter().skip(2).collect::<String>().trim().to_string());
-                }
-            }
+    pub fn jump_config(&mut self, jump: ScrollJump) {
+        let last = self.config.rows() - 1;
+        let (page, _) = page_bounds(self.config.area.area, 0);
+        let selected = self.config.state.selected().unwrap_or(0);
+        self.config
+            .state
+            .select(Some(jump.apply(selected, page, last)));
+    }
+
+    /// Starts typing a new value for the selected key, written to the scope it is set in
+    /// (the local config for an unset key or one only set on the command line)
+    pub fn edit_selected_config(&mut self) {
+        let Some((key, entry)) = self
+            .config
+            .state
+            .selected()
+            .and_then(|i| self.config.row(i))
+        else {
+            return;
+        };
+        let scope = entry
+            .map(|entry| entry.scope)
+            .filter(|scope| ConfigScope::EDITABLE.contains(scope))
+            .unwrap_or(ConfigScope::Local);
+        let value = entry.map(|entry| entry.value.clone()).unwrap_or_default();
+        self.input_mode = InputMode::ConfigEdit {
+            key: key.to_string(),
+            scope,
+            input: TextInput::new(value),
+        };
+    }
+
+    /// Tab in the value prompt: the local, global or system config
+    pub fn cycle_config_scope(&mut self) {
+        if let InputMode::ConfigEdit { scope, .. } = &mut self.input_mode {
+            *scope = scope.next();
+        }
+    }
+
+    pub fn cancel_config_edit(&mut self) {
+        self.input_mode = InputMode::Config;
+    }
+
+    /// Writes the typed value, or unsets the key when it was emptied. An error keeps the
+    /// prompt open.
+    pub fn submit_config_edit(&mut self) {
+        let InputMode::ConfigEdit { key, scope, input } = &self.input_mode else {
+            return;
+        };
+        let value = input.as_str().trim();
+        match GitOperations::set_config(&self.repo, *scope, key, value) {
+            Ok(change) => {
+                let (language, scope) = (self.language(), scope.as_str());
+                let message = match change {
+                    ConfigChange::Set => Msg::ConfigSet.fill(language, &[key, &value, &scope]),
+                    ConfigChange::Unset => Msg::ConfigUnset.fill(language, &[key, &scope]),
+                    ConfigChange::NotSet => Msg::ConfigNotSetIn.fill(language, &[key, &scope]),
+                };
+                self.status.success(message);
+                self.input_mode = InputMode::Config;
+                self.refresh_config();
+            },
+            Err(e) => self.status.error(Msg::Error.fill(self.language(), &[&e])),
         }
-        None
     }
 
+    pub fn get_current_file_path(&self) -> Option<String> {
+        self.selected_file().map(|entry| entry.path.clone())
+    }
 }
 
 #[cfg(test)]
@@ -387,9 +5661,55 @@ mod tests {
     fn test_app_new() {
         let app = App::new();
         assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.commit_message.is_empty());
-        assert!(app.stash_message.is_empty());
-        assert!(app.status_message.contains("SYSTEM_INIT"));
+        assert_eq!(app.commit_message, "");
+        assert_eq!(app.stash_message, "");
+        assert!(app.status.text().contains("Ready"));
+    }
+
+    #[test]
+    fn test_set_language_translates_the_startup_message() {
+        let mut app = App::new();
+        app.set_language(Language::En);
+        assert_eq!(app.status.text(), "Ready");
+        assert_eq!(app.language(), Language::En);
+
+        app.toggle_commit_signing();
+        app.set_language(Language::Ja);
+        assert_eq!(app.status.text(), "This commit will be signed");
+        app.toggle_commit_signing();
+        assert_eq!(app.status.text(), "このコミットには署名しません");
+    }
+
+    #[test]
+    fn test_command_log_keeps_the_stderr_of_failed_commands() {
+        use crate::command_log::LoggedCommand;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = std::process::Command::new("git")
+            .args(["rev-parse", "--verify", "no-such-ref"])
+            .current_dir(dir.path())
+            .logged_output()
+            .unwrap();
+        assert!(!output.status.success());
+
+        let mut app = App::new();
+        app.toggle_command_log();
+        assert_eq!(app.input_mode, InputMode::CommandLog);
+        let failed = app
+            .command_log
+            .iter()
+            .find(|record| record.command == "git rev-parse --verify no-such-ref")
+            .unwrap();
+        assert!(!failed.succeeded());
+        assert!(failed.exit_code.is_some());
+        assert!(failed.stderr.starts_with("fatal:"), "{}", failed.stderr);
+
+        app.areas.command_log = Rect::new(0, 0, 80, 4);
+        app.jump_command_log(ScrollJump::Bottom);
+        let lines: usize = app.command_log.iter().map(CommandRecord::line_count).sum();
+        assert_eq!(usize::from(app.command_log_scroll), lines.saturating_sub(2));
+        app.toggle_command_log();
+        assert_eq!(app.input_mode, InputMode::Normal);
     }
 
     #[test]
@@ -406,11 +5726,87 @@ mod tests {
         assert_eq!(app.input_mode, InputMode::Normal);
     }
 
+    #[test]
+    fn test_preview_layout_keys_are_saved() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut app = App::new();
+        app.layout = LayoutConfig::default();
+        app.show_preview_panel = true;
+        app.config_file = Some(path.clone());
+
+        app.resize_preview(-5);
+        app.cycle_preview_position();
+        assert_eq!(app.layout.preview, PreviewPosition::Bottom);
+        let saved = Config::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.layout, app.layout);
+        assert_eq!(saved.layout.preview_size, 45);
+
+        for _ in 0..20 {
+            app.resize_preview(5);
+        }
+        assert_eq!(app.layout.preview_size, LayoutConfig::MAX_PREVIEW_SIZE);
+
+        app.cycle_preview_position();
+        assert!(!app.show_preview_panel);
+        app.resize_preview(5);
+        assert_eq!(app.status.text(), "Preview panel is hidden");
+        app.cycle_preview_position();
+        assert!(app.show_preview_panel);
+        assert_eq!(app.layout.preview, PreviewPosition::Right);
+    }
+
+    #[test]
+    fn test_search_fullscreen_preview() {
+        let mut app = App::new();
+        let text: String = (0..20)
+            .map(|i| {
+                if i % 5 == 0 {
+                    format!("+match {i}\n")
+                } else {
+                    format!(" line {i}\n")
+                }
+            })
+            .collect();
+        app.input_mode = InputMode::Preview {
+            content: text.into(),
+            file_path: "a.txt".to_string(),
+        };
+        app.preview_scroll = 6;
+
+        app.start_preview_search();
+        app.preview_search_input = Some("MATCH".into());
+        app.submit_preview_search();
+        assert_eq!(app.status.text(), "Not found: MATCH");
+        assert_eq!(app.preview_matches, Vec::<usize>::new());
+
+        app.start_preview_search();
+        assert_eq!(app.preview_search_input.as_deref(), Some("MATCH"));
+        app.preview_search_input = Some("match".into());
+        app.submit_preview_search();
+        assert_eq!(app.preview_matches, vec![0, 5, 10, 15]);
+        assert_eq!(app.preview_match, Some(2));
+        assert_eq!(app.preview_scroll, 7);
+
+        app.next_preview_match(true);
+        app.next_preview_match(true);
+        assert_eq!(app.preview_match, Some(0));
+        assert_eq!(app.preview_scroll, 0);
+        app.next_preview_match(false);
+        assert_eq!(app.preview_match, Some(3));
+
+        app.exit_preview();
+        assert_eq!(app.preview_query, "");
+        assert_eq!(app.preview_matches, Vec::<usize>::new());
+        assert_eq!(app.preview_match, None);
+    }
+
     #[test]
     fn test_navigation_empty_files() {
         let mut app = App::new();
         app.files.clear();
-        app.files_state = tui::widgets::ListState::default();
+        app.invalidate_file_rows();
+        app.files_state = ratatui::widgets::ListState::default();
 
         app.next();
         assert_eq!(app.files_state.selected(), None);
@@ -423,35 +5819,479 @@ mod tests {
     fn test_navigation_with_files() {
         let mut app = App::new();
         app.files = vec![
-            "file1".to_string(),
-            "file2".to_string(),
-            "file3".to_string(),
+            FileEntry::new("file1", ' ', 'M'),
+            FileEntry::new("file2", ' ', 'M'),
+            FileEntry::new("file3", ' ', 'M'),
         ];
-        app.files_state.select(Some(0));
+        app.invalidate_file_rows();
+        // Row 0 is the "Unstaged" section header
+        app.files_state.select(Some(1));
 
         app.next();
-        assert_eq!(app.files_state.selected(), Some(1));
+        assert_eq!(app.files_state.selected(), Some(2));
 
         app.next();
-        assert_eq!(app.files_state.selected(), Some(2));
+        assert_eq!(app.files_state.selected(), Some(3));
 
         app.next();
         assert_eq!(app.files_state.selected(), Some(0));
 
         app.previous();
+        assert_eq!(app.files_state.selected(), Some(3));
+    }
+
+    #[test]
+    fn test_file_rows_grouped_by_section() {
+        let mut app = App::new();
+        app.files = vec![
+            FileEntry::new("partial.rs", 'M', 'M'),
+            FileEntry::new("new.rs", '?', '?'),
+        ];
+        app.collapsed_sections.clear();
+        app.invalidate_file_rows();
+
+        let rows = app.file_rows();
+        assert_eq!(rows.len(), 6);
+        assert_eq!(
+            rows[0],
+            FileRow::Header {
+                section: Section::Staged,
+                count: 1,
+                collapsed: false
+            }
+        );
+        assert_eq!(
+            rows[3],
+            FileRow::File {
+                section: Section::Unstaged,
+                index: 0
+            }
+        );
+
+        app.files_state.select(Some(5));
+        assert_eq!(app.selected_file().map(|f| f.path.as_str()), Some("new.rs"));
+        assert_eq!(app.selected_section(), Some(Section::Untracked));
+
+        // Collapsing keeps the cursor on the header and hides the files
+        app.files_state.select(Some(1));
+        app.toggle_section();
+        assert_eq!(app.files_state.selected(), Some(0));
+        assert_eq!(app.file_rows().len(), 5);
+        assert_eq!(app.selected_file(), None);
+
+        app.toggle_section();
+        assert_eq!(app.file_rows().len(), 6);
+    }
+
+    #[test]
+    fn test_list_area_follows_scrolling() {
+        let mut list = ListArea::default();
+        // 5 visible rows inside the borders
+        let area = Rect::new(0, 0, 20, 7);
+        list.update(area, Some(2), 10);
+        assert_eq!(list.offset, 0);
+        list.update(area, Some(7), 10);
+        assert_eq!(list.offset, 3);
+        list.update(area, Some(4), 10);
+        assert_eq!(list.offset, 3);
+        list.update(area, Some(1), 10);
+        assert_eq!(list.offset, 1);
+
+        assert_eq!(list.row_at(5, 1), Some(1));
+        assert_eq!(list.row_at(5, 5), Some(5));
+        assert_eq!(list.row_at(5, 0), None);
+        assert_eq!(list.row_at(25, 3), None);
+    }
+
+    #[test]
+    fn test_mouse_click_and_scroll() {
+        let mut app = App::new();
+        app.files = vec![
+            FileEntry::new("a.txt", ' ', 'M'),
+            FileEntry::new("b.txt", ' ', 'M'),
+        ];
+        app.collapsed_sections.clear();
+        app.invalidate_file_rows();
+        app.files_state.select(Some(1));
+        app.areas
+            .file_list
+            .update(Rect::new(0, 3, 40, 10), Some(1), 3);
+        app.areas.preview_panel = Rect::new(40, 3, 40, 10);
+
+        // Header is on screen row 4, files on rows 5 and 6
+        app.on_click(10, 6);
+        assert_eq!(app.selected_file().map(|f| f.path.as_str()), Some("b.txt"));
+        app.on_click(10, 9);
         assert_eq!(app.files_state.selected(), Some(2));
+
+        app.on_scroll(10, 5, false);
+        assert_eq!(app.files_state.selected(), Some(1));
+        app.preview_content = "line\n".repeat(20).into();
+        app.on_scroll(50, 5, true);
+        assert_eq!(app.files_state.selected(), Some(1));
+        assert_eq!(app.preview_scroll, 1);
+
+        app.input_mode = InputMode::Help;
+        app.on_scroll(0, 0, true);
+        assert_eq!(app.help_scroll, 1);
+    }
+
+    #[test]
+    fn test_toggle_diff_view() {
+        let mut app = App::new();
+        assert_eq!(app.diff_view, DiffView::Unstaged);
+        app.toggle_diff_view();
+        assert_eq!(app.diff_view, DiffView::Staged);
+        app.toggle_diff_view();
+        assert_eq!(app.diff_view, DiffView::Unstaged);
+    }
+
+    #[test]
+    fn test_push_pull_require_confirmation() {
+        let mut app = App::new();
+        // Without an upstream, push offers --set-upstream instead
+        app.upstream = Some(UpstreamStatus {
+            name: "origin/main".to_string(),
+            ahead: 1,
+            behind: 0,
+        });
+
+        app.push();
+        assert!(matches!(
+            app.input_mode,
+            InputMode::Confirm {
+                action: ConfirmAction::Push,
+                ..
+            }
+        ));
+
+        app.handle_confirm(false);
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.status.text().contains("cancelled"));
+
+        // Pull asks for a strategy instead of a yes/no
+        app.pull();
+        assert!(matches!(app.input_mode, InputMode::PullPicker { .. }));
+        assert!(app.pull.state.selected().is_some());
+        app.next_pull_strategy();
+        app.next_pull_strategy();
+        app.next_pull_strategy();
+        assert_eq!(app.pull.state.selected(), Some(PullStrategy::ALL.len() - 1));
+        app.exit_pull_picker();
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.status.text().contains("cancelled"));
+    }
+
+    #[test]
+    fn test_quit_confirms_commit_draft() {
+        let mut app = App::new();
+        app.confirm_quit = true;
+        assert!(app.request_quit());
+
+        app.commit_message = "WIP".into();
+        assert!(!app.request_quit());
+        assert!(matches!(
+            &app.input_mode,
+            InputMode::Confirm {
+                action: ConfirmAction::Quit,
+                message,
+            } if message.contains("unsaved commit message")
+        ));
+        assert!(!app.handle_confirm(false));
+        assert_eq!(app.input_mode, InputMode::Normal);
+
+        app.request_quit();
+        assert!(app.handle_confirm(true));
+
+        app.confirm_quit = false;
+        assert!(app.request_quit());
+    }
+
+    #[test]
+    fn test_discard_requires_confirmation() {
+        let mut app = App::new();
+        app.files = vec![FileEntry::new("notes.txt", '?', '?')];
+        app.invalidate_file_rows();
+        app.files_state.select(Some(0));
+        app.discard_selected_file();
+        assert_eq!(app.input_mode, InputMode::Normal);
+
+        app.files_state.select(Some(1));
+        app.discard_selected_file();
+        assert!(matches!(
+            &app.input_mode,
+            InputMode::Confirm {
+                message,
+                action: ConfirmAction::DiscardFile(path),
+            } if path == "notes.txt" && message.contains("Delete untracked")
+        ));
+
+        app.handle_confirm(false);
+        assert_eq!(app.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn test_stash_navigation_and_drop_confirmation() {
+        let mut app = App::new();
+        app.stashes = vec![
+            StashEntry {
+                index: 0,
+                message: "On main: first".to_string(),
+            },
+            StashEntry {
+                index: 1,
+                message: "On main: second".to_string(),
+            },
+        ];
+        app.stash_state.select(Some(0));
+        app.input_mode = InputMode::StashList;
+
+        app.next_stash();
+        assert_eq!(app.selected_stash().map(|s| s.index), Some(1));
+        app.next_stash();
+        assert_eq!(app.selected_stash().map(|s| s.index), Some(1));
+        app.previous_stash();
+        assert_eq!(app.selected_stash().map(|s| s.index), Some(0));
+
+        app.drop_selected_stash();
+        assert!(matches!(
+            app.input_mode,
+            InputMode::Confirm {
+                action: ConfirmAction::DropStash(0),
+                ..
+            }
+        ));
+        app.handle_confirm(false);
+        assert_eq!(app.input_mode, InputMode::StashList);
+    }
+
+    #[test]
+    fn test_log_navigation_skips_graph_lines() {
+        let mut app = App::new();
+        app.log_entries = vec![
+            "* aaaaaaa Merge branch".to_string(),
+            "|\\".to_string(),
+            "| * bbbbbbb Feature".to_string(),
+            "|/".to_string(),
+            "* ccccccc Initial".to_string(),
+        ];
+        app.log_state.select(Some(0));
+
+        app.next_log_entry();
+        assert_eq!(app.log_state.selected(), Some(2));
+        assert_eq!(app.selected_commit_hash().as_deref(), Some("bbbbbbb"));
+
+        app.next_log_entry();
+        assert_eq!(app.log_state.selected(), Some(4));
+
+        // Stays on the last commit
+        app.next_log_entry();
+        assert_eq!(app.log_state.selected(), Some(4));
+
+        app.previous_log_entry();
+        assert_eq!(app.log_state.selected(), Some(2));
+    }
+
+    #[test]
+    fn test_scroll_jumps_stay_in_bounds() {
+        assert_eq!(ScrollJump::PageDown.apply(5, 10, 12), 12);
+        assert_eq!(ScrollJump::PageUp.apply(5, 10, 12), 0);
+        assert_eq!(ScrollJump::HalfPageDown.apply(0, 10, 12), 5);
+        assert_eq!(ScrollJump::HalfPageUp.apply(6, 1, 12), 5);
+        assert_eq!(ScrollJump::Bottom.apply(0, 10, 12), 12);
+        assert_eq!(ScrollJump::Top.apply(7, 10, 12), 0);
+
+        let mut app = App::new();
+        let text: String = "line\n".repeat(30);
+        app.input_mode = InputMode::Preview {
+            content: text.into(),
+            file_path: "a.txt".to_string(),
+        };
+        app.areas.fullscreen = Rect::new(0, 0, 80, 12);
+        app.jump_preview(ScrollJump::PageDown);
+        assert_eq!(app.preview_scroll, 10);
+        app.jump_preview(ScrollJump::Bottom);
+        assert_eq!(app.preview_scroll, 20);
+        app.scroll_preview_down();
+        assert_eq!(app.preview_scroll, 20);
+        app.jump_preview(ScrollJump::HalfPageUp);
+        assert_eq!(app.preview_scroll, 15);
+        // A taller screen shows more lines, so the last page starts higher
+        app.areas.fullscreen = Rect::new(0, 0, 80, 22);
+        app.clamp_preview_scroll();
+        assert_eq!(app.preview_scroll, 10);
+
+        app.areas.help = Rect::new(0, 0, 80, 12);
+        app.jump_help(ScrollJump::Bottom);
+        let last = app.help_scroll;
+        app.scroll_help_down();
+        assert_eq!(app.help_scroll, last);
+        assert_eq!(
+            usize::from(last) + 10,
+            crate::ui_help::help_line_count(&app.theme, app.language())
+        );
+
+        app.log_entries = vec![
+            "* aaaaaaa Merge branch".to_string(),
+            "|\\".to_string(),
+            "| * bbbbbbb Feature".to_string(),
+            "|/".to_string(),
+            "* ccccccc Initial".to_string(),
+        ];
+        app.areas.log.area = Rect::new(0, 0, 80, 5);
+        app.log_state.select(Some(0));
+        app.jump_log(ScrollJump::PageDown);
+        assert_eq!(app.log_state.selected(), Some(4));
+        app.jump_log(ScrollJump::HalfPageUp);
+        assert_eq!(app.log_state.selected(), Some(2));
+        app.jump_log(ScrollJump::Top);
+        assert_eq!(app.log_state.selected(), Some(0));
     }
 
     #[test]
     fn test_commit_message_validation() {
         let mut app = App::new();
 
-        app.commit_message = String::new();
+        app.commit_message.clear();
+        app.commit();
+        assert!(app.status.text().contains("cannot be empty"));
+
+        app.commit_message = "   ".into();
         app.commit();
-        assert!(app.status_message.contains("cannot be empty"));
+        assert!(app.status.text().contains("cannot be empty"));
 
-        app.commit_message = "   ".to_string();
+        app.input_mode = InputMode::Commit;
+        app.commit_lint = CommitLintConfig {
+            conventional: true,
+            ..CommitLintConfig::default()
+        };
+        app.commit_message = "Add feature.".into();
+        assert_eq!(app.commit_violations().len(), 2);
         app.commit();
-        assert!(app.status_message.contains("cannot be empty"));
+        assert!(app.status.text().contains("2 problem(s)"));
+        assert_eq!(app.input_mode, InputMode::Commit);
+        assert_eq!(app.commit_message, "Add feature.");
+    }
+
+    #[test]
+    fn test_paste_goes_to_the_focused_input() {
+        let mut app = App::new();
+        app.paste("ignored");
+        assert_eq!(app.commit_message, "");
+
+        app.input_mode = InputMode::Commit;
+        app.commit_message = "fix: ".into();
+        app.paste("subject\r\n\nbody");
+        assert_eq!(app.commit_message, "fix: subject\n\nbody");
+
+        app.input_mode = InputMode::StashMessage;
+        app.stash_message = "wip".into();
+        app.stash_message.move_home();
+        app.paste("two\nlines ");
+        assert_eq!(app.stash_message, "two lines wip");
+
+        app.input_mode = InputMode::Preview {
+            content: "text".into(),
+            file_path: "a.txt".to_string(),
+        };
+        assert!(app.text_input_mut().is_none());
+        app.start_preview_search();
+        app.paste("needle");
+        assert_eq!(app.preview_search_input.as_deref(), Some("needle"));
+    }
+
+    #[test]
+    fn test_prefix_picker_inserts_type_and_scope() {
+        let mut app = App::new();
+        app.input_mode = InputMode::Commit;
+        app.commit_message = "add x".into();
+
+        app.show_prefix_picker();
+        assert_eq!(app.input_mode, InputMode::PrefixPicker);
+        app.next_prefix();
+        app.choose_prefix();
+        let InputMode::PrefixScope { prefix, scope } = &mut app.input_mode else {
+            panic!("expected the scope prompt, got {:?}", app.input_mode);
+        };
+        assert_eq!(prefix, "fix");
+        scope.insert_str("ui");
+        app.submit_prefix_scope();
+        assert_eq!(app.input_mode, InputMode::Commit);
+        assert_eq!(app.commit_message, "fix(ui): add x");
+
+        app.commit_lint.prefix_style = commit::PrefixStyle::Gitmoji;
+        app.show_prefix_picker();
+        app.choose_prefix();
+        assert_eq!(app.input_mode, InputMode::Commit);
+        assert_eq!(app.commit_message, ":sparkles: fix(ui): add x");
+    }
+
+    #[test]
+    fn test_commit_snippet_expands_when_typed() {
+        let mut app = App::new();
+        app.current_branch = "JIRA-123-login".into();
+        app.input_mode = InputMode::Commit;
+        app.commit_message = "fix: login".into();
+        app.commit_message.move_home();
+        app.commit_message.insert_str("{ticket}");
+        app.expand_commit_snippet();
+        assert_eq!(app.commit_message, "JIRA-123fix: login");
+        assert_eq!(app.commit_message.before_cursor(), "JIRA-123");
+
+        app.commit_message.set("{nope}");
+        app.expand_commit_snippet();
+        assert_eq!(app.commit_message, "{nope}");
+
+        app.current_branch = "main".into();
+        app.commit_message.set("{ticket}");
+        app.expand_commit_snippet();
+        assert_eq!(app.commit_message, "{ticket}");
+        assert_eq!(app.status.text(), "No ticket id in branch name 'main'");
+    }
+
+    #[test]
+    fn test_credential_prompt_returns_to_previous_mode() {
+        let mut app = App::new();
+        app.input_mode = InputMode::Credential {
+            prompt: "Password for 'https://example.com': ".into(),
+            input: TextInput::default(),
+            previous: Box::new(InputMode::Commit),
+        };
+        app.paste("secret");
+        assert!(
+            matches!(&app.input_mode, InputMode::Credential { input, .. } if input == "secret")
+        );
+        app.submit_credential();
+        assert_eq!(app.input_mode, InputMode::Commit);
+
+        app.input_mode = InputMode::Credential {
+            prompt: "Username for 'https://example.com': ".into(),
+            input: TextInput::default(),
+            previous: Box::new(InputMode::Log),
+        };
+        app.cancel_credential();
+        assert_eq!(app.input_mode, InputMode::Log);
+        assert_eq!(app.status.text(), "Credential prompt cancelled");
+    }
+
+    #[test]
+    fn test_handle_event_dispatches_by_mode() {
+        use crate::event::{AppEvent, KeyCode, KeyEvent, KeyModifiers};
+
+        let key = |code| AppEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        let mut app = App::new();
+        assert!(!app.handle_event(key(KeyCode::Char('h'))));
+        assert_eq!(app.input_mode, InputMode::Help);
+        // `q` leaves the help instead of quitting
+        assert!(!app.handle_event(key(KeyCode::Char('q'))));
+        assert_eq!(app.input_mode, InputMode::Normal);
+
+        app.input_mode = InputMode::StashMessage;
+        app.handle_event(AppEvent::Paste("wip".into()));
+        app.handle_event(key(KeyCode::Char('!')));
+        assert_eq!(app.stash_message, "wip!");
+        app.handle_event(key(KeyCode::Esc));
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(!app.handle_event(AppEvent::Tick));
     }
 }
@@ -0,0 +1,608 @@
+use crate::strings::Language;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Commit types accepted when `conventional = true` and no `types` are configured
+const DEFAULT_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// What the built-in commit types are for, in Japanese and English, shown in the prefix
+/// picker
+const TYPE_DESCRIPTIONS: &[(&str, &str, &str)] = &[
+    ("feat", "新機能", "a new feature"),
+    ("fix", "バグ修正", "a bug fix"),
+    ("docs", "ドキュメントのみの変更", "documentation only"),
+    (
+        "style",
+        "動作に影響しない書式の変更",
+        "formatting that does not change behavior",
+    ),
+    (
+        "refactor",
+        "機能追加でもバグ修正でもないコードの変更",
+        "a code change that neither adds a feature nor fixes a bug",
+    ),
+    ("perf", "パフォーマンス改善", "a performance improvement"),
+    ("test", "テストの追加・修正", "adding or fixing tests"),
+    (
+        "build",
+        "ビルドシステムや依存関係の変更",
+        "build system or dependencies",
+    ),
+    ("ci", "CI設定の変更", "CI configuration"),
+    ("chore", "その他の雑務", "other chores"),
+    ("revert", "コミットの取り消し", "reverts a commit"),
+];
+
+/// The most used gitmojis, offered when `prefix_style = "gitmoji"`
+const GITMOJIS: &[(&str, &str, &str)] = &[
+    (":sparkles:", "✨ 新機能", "✨ new feature"),
+    (":bug:", "🐛 バグ修正", "🐛 bug fix"),
+    (":memo:", "📝 ドキュメント", "📝 documentation"),
+    (
+        ":art:",
+        "🎨 コードの構造・書式の改善",
+        "🎨 code structure or format",
+    ),
+    (":recycle:", "♻️ リファクタリング", "♻️ refactoring"),
+    (":zap:", "⚡️ パフォーマンス改善", "⚡️ performance"),
+    (
+        ":white_check_mark:",
+        "✅ テストの追加・更新",
+        "✅ add or update tests",
+    ),
+    (
+        ":fire:",
+        "🔥 コードやファイルの削除",
+        "🔥 remove code or files",
+    ),
+    (":lipstick:", "💄 UIやスタイルの更新", "💄 UI and style"),
+    (
+        ":wrench:",
+        "🔧 設定ファイルの変更",
+        "🔧 configuration files",
+    ),
+    (":arrow_up:", "⬆️ 依存関係の更新", "⬆️ upgrade dependencies"),
+    (":construction_worker:", "👷 CIの変更", "👷 CI changes"),
+    (":rewind:", "⏪️ 変更の取り消し", "⏪️ revert changes"),
+];
+
+/// How a prefix chosen in the picker is written into the subject
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrefixStyle {
+    /// `type(scope): `, asking for an optional scope
+    #[default]
+    Conventional,
+    /// `:emoji: `
+    Gitmoji,
+}
+
+/// What a new commit starts out containing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitContents {
+    /// Only what is staged
+    #[default]
+    Staged,
+    /// The staged changes and every modified tracked file, like `git commit -a`
+    All,
+}
+
+/// One entry of the commit prefix picker
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommitPrefix {
+    pub prefix: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl CommitPrefix {
+    fn new(prefix: &str, description: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            description: description.to_string(),
+        }
+    }
+}
+
+/// `ticket_pattern`: finds the ticket id in a branch name such as `JIRA-123-foo`, taking the
+/// first capture group if the regex has one
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TicketPattern(Regex);
+
+impl TicketPattern {
+    pub fn find(&self, branch: &str) -> Option<String> {
+        let captures = self.0.captures(branch)?;
+        let ticket = captures.get(1).or_else(|| captures.get(0))?;
+        Some(ticket.as_str().to_string())
+    }
+}
+
+impl TryFrom<String> for TicketPattern {
+    type Error = String;
+
+    fn try_from(pattern: String) -> Result<Self, String> {
+        Regex::new(&pattern)
+            .map(Self)
+            .map_err(|e| format!("invalid ticket_pattern: {e}"))
+    }
+}
+
+impl Default for TicketPattern {
+    fn default() -> Self {
+        Self(Regex::new("[A-Z][A-Z0-9]+-[0-9]+").expect("the default ticket pattern is valid"))
+    }
+}
+
+impl PartialEq for TicketPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for TicketPattern {}
+
+/// Switches of the commit being written, toggled in commit mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommitFlags {
+    /// `-S`, starting from `commit.gpgsign`
+    pub sign: bool,
+    /// `--no-verify`, skipping the pre-commit and commit-msg hooks
+    pub no_verify: bool,
+    /// `--all`, staging modified and deleted tracked files first
+    pub all: bool,
+}
+
+/// The `[commit]` table of the config file: rules checked before a commit is created and
+/// the prefixes offered by the picker
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommitLintConfig {
+    /// Longest allowed subject line in characters; `0` disables the check
+    pub max_subject_length: usize,
+    pub no_trailing_period: bool,
+    /// Require `type(scope)!: description` subjects
+    pub conventional: bool,
+    /// Allowed Conventional Commits types (defaults to the commitlint conventional set)
+    pub types: Option<Vec<String>>,
+    pub prefix_style: PrefixStyle,
+    /// Picker entries; defaults to `types` or the gitmoji list, depending on `prefix_style`
+    pub prefixes: Option<Vec<CommitPrefix>>,
+    /// Start every commit with the hooks skipped (`--no-verify`)
+    pub no_verify: bool,
+    /// `all` starts every commit with modified tracked files staged automatically
+    pub contents: CommitContents,
+    pub ticket_pattern: TicketPattern,
+    /// Text typing `{name}` expands to; it can use `{branch}` and `{ticket}` itself
+    pub snippets: BTreeMap<String, String>,
+    pub trailers: TrailerConfig,
+}
+
+impl Default for CommitLintConfig {
+    fn default() -> Self {
+        Self {
+            max_subject_length: 72,
+            no_trailing_period: true,
+            conventional: false,
+            types: None,
+            prefix_style: PrefixStyle::default(),
+            prefixes: None,
+            no_verify: false,
+            contents: CommitContents::default(),
+            ticket_pattern: TicketPattern::default(),
+            snippets: BTreeMap::new(),
+            trailers: TrailerConfig::default(),
+        }
+    }
+}
+
+/// `[commit.trailers]`: the trailers the commit input can append to the message
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrailerConfig {
+    /// Start every commit with `Signed-off-by:` (the DCO sign-off) turned on
+    pub signoff: bool,
+    /// Keys the trailer picker adds with a recent author, e.g. `Co-authored-by`
+    pub keys: Vec<String>,
+}
+
+impl Default for TrailerConfig {
+    fn default() -> Self {
+        Self {
+            signoff: false,
+            keys: vec![String::from("Co-authored-by"), String::from("Reviewed-by")],
+        }
+    }
+}
+
+impl CommitLintConfig {
+    /// Problems with `message`, empty when it passes every enabled rule
+    pub fn lint(&self, message: &str) -> Vec<String> {
+        let subject = message.lines().next().unwrap_or("").trim_end();
+        let mut violations = Vec::new();
+        if subject.is_empty() {
+            return violations;
+        }
+
+        let length = subject.chars().count();
+        if self.max_subject_length > 0 && length > self.max_subject_length {
+            violations.push(format!(
+                "Subject is {length} characters (max {})",
+                self.max_subject_length
+            ));
+        }
+        if self.no_trailing_period && subject.ends_with('.') {
+            violations.push("Subject must not end with a period".to_string());
+        }
+        if self.conventional {
+            if let Err(e) = self.check_conventional(subject) {
+                violations.push(e);
+            }
+        }
+        violations
+    }
+
+    /// Entries of the prefix picker, with the built-in descriptions in `language`
+    pub fn prefix_choices(&self, language: Language) -> Vec<CommitPrefix> {
+        let described = |&(prefix, ja, en): &(&str, &str, &str)| {
+            CommitPrefix::new(prefix, language.pick(ja, en))
+        };
+        if let Some(prefixes) = &self.prefixes {
+            return prefixes.clone();
+        }
+        match self.prefix_style {
+            PrefixStyle::Conventional => self.types.as_ref().map_or_else(
+                || TYPE_DESCRIPTIONS.iter().map(described).collect(),
+                |types| {
+                    types
+                        .iter()
+                        .map(|prefix| CommitPrefix::new(prefix, ""))
+                        .collect()
+                },
+            ),
+            PrefixStyle::Gitmoji => GITMOJIS.iter().map(described).collect(),
+        }
+    }
+
+    /// Whether choosing a prefix asks for a scope
+    pub fn asks_scope(&self) -> bool {
+        self.prefix_style == PrefixStyle::Conventional
+    }
+
+    /// `message` with `prefix` (and `scope`, if not empty) at the start of the subject,
+    /// replacing a prefix that is already there
+    pub fn apply_prefix(&self, message: &str, prefix: &str, scope: &str) -> String {
+        let (subject, body) = message
+            .split_once('\n')
+            .map_or((message, None), |(subject, body)| (subject, Some(body)));
+        let scope = scope.trim();
+        let (head, rest) = match self.prefix_style {
+            PrefixStyle::Conventional if scope.is_empty() => {
+                (format!("{prefix}: "), strip_type(subject))
+            },
+            PrefixStyle::Conventional => (format!("{prefix}({scope}): "), strip_type(subject)),
+            PrefixStyle::Gitmoji => (format!("{prefix} "), strip_gitmoji(subject)),
+        };
+        let mut applied = head + rest;
+        if let Some(body) = body {
+            applied.push('\n');
+            applied.push_str(body);
+        }
+        applied
+    }
+
+    /// Value of `{name}` while on `branch`: the branch, its ticket id or a snippet
+    pub fn placeholder(&self, name: &str, branch: &str) -> Option<String> {
+        self.variable(name, branch).or_else(|| {
+            let snippet = self.snippets.get(name)?;
+            Some(replace_placeholders(snippet, |name| {
+                self.variable(name, branch)
+            }))
+        })
+    }
+
+    /// `template` with the placeholders that have a value filled in; the rest are kept as
+    /// they are
+    pub fn expand(&self, template: &str, branch: &str) -> String {
+        replace_placeholders(template, |name| self.placeholder(name, branch))
+    }
+
+    fn variable(&self, name: &str, branch: &str) -> Option<String> {
+        match name {
+            "branch" => Some(branch.to_string()),
+            "ticket" => self.ticket_pattern.find(branch),
+            _ => None,
+        }
+    }
+
+    fn check_conventional(&self, subject: &str) -> Result<(), String> {
+        let format_error =
+            || "Subject must follow Conventional Commits: type(scope): description".to_string();
+        let (prefix, description) = subject.split_once(": ").ok_or_else(format_error)?;
+        if description.trim().is_empty() {
+            return Err(format_error());
+        }
+
+        let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+        let commit_type = match prefix.split_once('(') {
+            Some((commit_type, scope)) => {
+                let scope = scope.strip_suffix(')').ok_or_else(format_error)?;
+                if scope.is_empty() || scope.contains(['(', ')']) {
+                    return Err(format_error());
+                }
+                commit_type
+            },
+            None => prefix,
+        };
+        if commit_type.is_empty() || !commit_type.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format_error());
+        }
+
+        let allowed = self.types.as_ref().map_or_else(
+            || DEFAULT_TYPES.iter().map(ToString::to_string).collect(),
+            Clone::clone,
+        );
+        if allowed.iter().any(|allowed| allowed == commit_type) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Unknown commit type '{commit_type}' (allowed: {})",
+                allowed.join(", ")
+            ))
+        }
+    }
+}
+
+/// The name of the `{name}` placeholder `text` ends with, i.e. the one just typed
+pub fn typed_placeholder(text: &str) -> Option<&str> {
+    let open = text.strip_suffix('}')?;
+    let name = &open[open.rfind('{')? + 1..];
+    is_placeholder_name(name).then_some(name)
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// `template` with each `{name}` replaced by `value(name)`, where it has one
+fn replace_placeholders(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        expanded.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let replaced = rest[1..].find('}').and_then(|close| {
+            let name = &rest[1..=close];
+            is_placeholder_name(name)
+                .then(|| value(name))
+                .flatten()
+                .map(|value| (value, close + 2))
+        });
+        if let Some((value, len)) = replaced {
+            expanded.push_str(&value);
+            rest = &rest[len..];
+        } else {
+            expanded.push('{');
+            rest = &rest[1..];
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// `message` with `trailers` (`Key: value` lines) at the end, added to the trailer block it
+/// already ends with or after a blank line. Trailers the message already has are skipped.
+pub fn append_trailers(message: &str, trailers: &[String]) -> String {
+    let message = message.trim_end();
+    let mut appended = message.to_string();
+    let mut new = trailers
+        .iter()
+        .filter(|trailer| {
+            !message
+                .lines()
+                .any(|line| line.trim_end() == trailer.as_str())
+        })
+        .peekable();
+    if new.peek().is_none() {
+        return appended;
+    }
+    let ends_with_trailers = message
+        .rsplit_once("\n\n")
+        .is_some_and(|(_, last)| last.lines().all(is_trailer));
+    appended.push_str(if ends_with_trailers { "\n" } else { "\n\n" });
+    for (i, trailer) in new.enumerate() {
+        if i > 0 {
+            appended.push('\n');
+        }
+        appended.push_str(trailer);
+    }
+    appended
+}
+
+/// Whether `line` is a `Key: value` trailer such as `Signed-off-by: Jane <jane@example.com>`
+fn is_trailer(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(key, value)| {
+        !key.is_empty()
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && !value.trim().is_empty()
+    })
+}
+
+/// The subject without a leading `type(scope)!: `
+fn strip_type(subject: &str) -> &str {
+    match subject.split_once(": ") {
+        Some((prefix, rest)) if !prefix.is_empty() && !prefix.contains(char::is_whitespace) => rest,
+        _ => subject,
+    }
+}
+
+/// The subject without a leading `:emoji: `
+fn strip_gitmoji(subject: &str) -> &str {
+    subject
+        .strip_prefix(':')
+        .and_then(|rest| rest.split_once(": "))
+        .filter(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace))
+        .map_or(subject, |(_, rest)| rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_length_and_period() {
+        let lint = CommitLintConfig::default();
+        assert_eq!(
+            lint.lint("Add feature\n\nLonger body that is fine."),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            lint.lint("Add feature."),
+            vec!["Subject must not end with a period"]
+        );
+        assert_eq!(
+            lint.lint(&"x".repeat(73)),
+            vec!["Subject is 73 characters (max 72)"]
+        );
+
+        let relaxed = CommitLintConfig {
+            max_subject_length: 0,
+            no_trailing_period: false,
+            ..CommitLintConfig::default()
+        };
+        assert_eq!(
+            relaxed.lint(&format!("{}.", "x".repeat(100))),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_conventional_commits() {
+        let lint = CommitLintConfig {
+            conventional: true,
+            ..CommitLintConfig::default()
+        };
+        for ok in [
+            "feat: add x",
+            "fix(ui): y",
+            "refactor(core)!: drop z",
+            "chore!: bump",
+        ] {
+            assert_eq!(lint.lint(ok), Vec::<String>::new(), "{ok}");
+        }
+        for bad in ["add x", "feat:add", "feat(): x", "feat(ui: x", "feat: "] {
+            assert_eq!(lint.lint(bad).len(), 1, "{bad}");
+        }
+        assert!(lint.lint("wip: x")[0].starts_with("Unknown commit type 'wip'"));
+
+        let custom = CommitLintConfig {
+            types: Some(vec!["wip".to_string()]),
+            ..lint
+        };
+        assert_eq!(custom.lint("wip: x"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_prefix_choices_and_apply() {
+        let config = CommitLintConfig::default();
+        let choices = config.prefix_choices(Language::Ja);
+        assert_eq!(choices[0], CommitPrefix::new("feat", "新機能"));
+        assert_eq!(
+            config.prefix_choices(Language::En)[1],
+            CommitPrefix::new("fix", "a bug fix")
+        );
+        assert_eq!(choices.len(), DEFAULT_TYPES.len());
+        assert!(config.asks_scope());
+        assert_eq!(config.apply_prefix("add x", "feat", ""), "feat: add x");
+        assert_eq!(
+            config.apply_prefix("feat: add x", "fix", "ui"),
+            "fix(ui): add x"
+        );
+        assert_eq!(config.apply_prefix("", "docs", ""), "docs: ");
+        assert_eq!(
+            config.apply_prefix("fix(ui)!: y\n\nbody: text", "feat", ""),
+            "feat: y\n\nbody: text"
+        );
+        assert_eq!(config.apply_prefix("Note: x y", "fix", ""), "fix: x y");
+
+        let gitmoji = CommitLintConfig {
+            prefix_style: PrefixStyle::Gitmoji,
+            ..CommitLintConfig::default()
+        };
+        assert!(!gitmoji.asks_scope());
+        assert_eq!(gitmoji.prefix_choices(Language::Ja)[1].prefix, ":bug:");
+        assert_eq!(
+            gitmoji.apply_prefix(":bug: y", ":sparkles:", ""),
+            ":sparkles: y"
+        );
+
+        let custom = CommitLintConfig {
+            types: Some(vec!["wip".to_string()]),
+            ..CommitLintConfig::default()
+        };
+        assert_eq!(
+            custom.prefix_choices(Language::En),
+            vec![CommitPrefix::new("wip", "")]
+        );
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut config = CommitLintConfig::default();
+        config
+            .snippets
+            .insert("refs".to_string(), "Refs: {ticket}".to_string());
+        let feature = "feature/JIRA-123-login";
+        assert_eq!(
+            config.expand("[{ticket}] {x} {refs}\n\n{branch} {", feature),
+            "[JIRA-123] {x} Refs: JIRA-123\n\nfeature/JIRA-123-login {"
+        );
+        assert_eq!(config.placeholder("ticket", "main"), None);
+        assert_eq!(config.expand("{ticket}: ", "main"), "{ticket}: ");
+
+        config.ticket_pattern = TicketPattern::try_from(String::from("^(\\d+)-")).unwrap();
+        assert_eq!(
+            config.placeholder("ticket", "42-fix").as_deref(),
+            Some("42")
+        );
+        assert!(TicketPattern::try_from(String::from("(")).is_err());
+
+        assert_eq!(typed_placeholder("fix {ticket}"), Some("ticket"));
+        assert_eq!(typed_placeholder("{a}{b}"), Some("b"));
+        assert_eq!(typed_placeholder("{a b}"), None);
+        assert_eq!(typed_placeholder("{}"), None);
+        assert_eq!(typed_placeholder("{ticket"), None);
+    }
+
+    #[test]
+    fn test_append_trailers() {
+        let trailers = [
+            String::from("Reviewed-by: Bob <bob@example.com>"),
+            String::from("Signed-off-by: Jane <jane@example.com>"),
+        ];
+        let signoff = &trailers[1..];
+        assert_eq!(
+            append_trailers("fix: x\n", signoff),
+            "fix: x\n\nSigned-off-by: Jane <jane@example.com>"
+        );
+        assert_eq!(
+            append_trailers("Add x\n\nRefs: JIRA-123", signoff),
+            "Add x\n\nRefs: JIRA-123\nSigned-off-by: Jane <jane@example.com>"
+        );
+        assert_eq!(
+            append_trailers("Add x\n\nA longer body.", &trailers),
+            "Add x\n\nA longer body.\n\nReviewed-by: Bob <bob@example.com>\nSigned-off-by: Jane <jane@example.com>"
+        );
+        let signed = append_trailers("Add x", signoff);
+        assert_eq!(append_trailers(&signed, signoff), signed);
+        assert_eq!(append_trailers("Add x", &[]), "Add x");
+    }
+}
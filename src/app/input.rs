@@ -0,0 +1,681 @@
+//! Event dispatch: what each key, click or tick does in the current mode.
+
+use super::{App, InitStep, InputMode};
+use crate::event::{AppEvent, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseKind};
+use crate::git::{ConflictSide, FileVersion, ResetMode};
+use crate::keymap::{self, Action};
+
+/// How many percent `<` and `>` resize the preview panel by
+const PREVIEW_RESIZE_STEP: i16 = 5;
+
+impl App {
+    /// Applies `event` to the current mode. Returns true when the app should quit.
+    ///
+    /// The caller still owns the terminal: it draws before `Resize`, sends `Tick` on its timer
+    /// and runs `pending_editor` and `pending_clipboard` after keys.
+    pub fn handle_event(&mut self, event: AppEvent) -> bool {
+        match event {
+            AppEvent::Key(key) => return handle_key(self, key),
+            AppEvent::Mouse(mouse) => handle_mouse(self, mouse),
+            AppEvent::Paste(text) => self.paste(&text),
+            AppEvent::Resize => self.on_resize(),
+            AppEvent::Tick => self.on_tick(),
+        }
+        false
+    }
+}
+
+/// 現在のモードに応じてキー入力を振り分ける。終了する場合は true を返す
+fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    // Text inputs take the editing keys (typing, cursor movement, Ctrl+W/Ctrl+U) first
+    if app
+        .text_input_mut()
+        .is_some_and(|input| input.handle_key(key))
+    {
+        if key.code == KeyCode::Char('}') {
+            app.expand_commit_snippet();
+        }
+        return false;
+    }
+    match app.input_mode {
+        InputMode::Normal => return handle_normal_key(app, key),
+        InputMode::Commit => handle_commit_key(app, key),
+        InputMode::StashMessage => handle_stash_message_key(app, key),
+        InputMode::Confirm { .. } => return handle_confirm_key(app, key),
+        InputMode::Help => handle_help_key(app, key),
+        InputMode::CommandLog => handle_command_log_key(app, key),
+        InputMode::History => handle_history_key(app, key),
+        InputMode::Doctor => handle_doctor_key(app, key),
+        InputMode::Config => handle_config_key(app, key),
+        InputMode::ConfigEdit { .. } => handle_config_edit_key(app, key),
+        InputMode::Preview { .. } => handle_preview_key(app, key),
+        InputMode::FileVersion { .. } => handle_file_version_key(app, key),
+        InputMode::Log => handle_log_key(app, key),
+        InputMode::Reflog => handle_reflog_key(app, key),
+        InputMode::Branches => handle_branches_key(app, key),
+        InputMode::BranchPrompt { .. } => handle_branch_prompt_key(app, key),
+        InputMode::MergePicker { .. } => handle_merge_picker_key(app, key),
+        InputMode::PullPicker { .. } => handle_pull_picker_key(app, key),
+        InputMode::Maintenance => handle_maintenance_key(app, key),
+        InputMode::CommitDetails => handle_commit_details_key(app, key),
+        InputMode::CompareInput { .. } => handle_compare_input_key(app, key),
+        InputMode::ArchivePrompt { .. } => handle_archive_prompt_key(app, key),
+        InputMode::Compare => handle_compare_key(app, key),
+        InputMode::StashList => handle_stash_list_key(app, key),
+        InputMode::RepoSwitcher => handle_repo_switcher_key(app, key),
+        InputMode::NoRepository { .. } => return handle_no_repository_key(app, key),
+        InputMode::InitSetup { step, .. } => handle_init_setup_key(app, step, key),
+        InputMode::Conflict { .. } => handle_conflict_key(app, key),
+        InputMode::LineStage { .. } => handle_line_stage_key(app, key),
+        InputMode::IgnoreChooser { .. } => handle_ignore_chooser_key(app, key),
+        InputMode::HookOutput { .. } => handle_hook_output_key(app, key),
+        InputMode::PrefixPicker => handle_prefix_picker_key(app, key),
+        InputMode::IdentityPicker => handle_identity_picker_key(app, key),
+        InputMode::TrailerPicker => handle_trailer_picker_key(app, key),
+        InputMode::PrefixScope { .. } => handle_prefix_scope_key(app, key),
+        InputMode::TypedConfirm { .. } => return handle_typed_confirm_key(app, key),
+        InputMode::LogSearch { .. } => handle_log_search_key(app, key),
+        InputMode::Credential { .. } => handle_credential_key(app, key),
+    }
+    false
+}
+
+/// マウスのクリックとホイールを現在のモードの操作に振り分ける
+fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    match mouse.kind {
+        MouseKind::Click => app.on_click(mouse.column, mouse.row),
+        MouseKind::ScrollDown => app.on_scroll(mouse.column, mouse.row, true),
+        MouseKind::ScrollUp => app.on_scroll(mouse.column, mouse.row, false),
+    }
+}
+
+// 通常モードのキー処理。キーと操作の対応は keymap::NORMAL_BINDINGS にある
+fn handle_normal_key(app: &mut App, key: KeyEvent) -> bool {
+    // Shift+j/k scrolls the preview panel instead of moving the selection
+    let scroll_preview = app.show_preview_panel && key.modifiers.contains(KeyModifiers::SHIFT);
+    let Some(action) = keymap::normal_action(key.code) else {
+        return false;
+    };
+    match action {
+        Action::Quit => return app.request_quit(),
+        Action::Next if scroll_preview => app.scroll_preview_down(),
+        Action::Next => app.next(),
+        Action::Previous if scroll_preview => app.scroll_preview_up(),
+        Action::Previous => app.previous(),
+        Action::Stage => app.stage_file(),
+        Action::LineStage => app.start_line_staging(),
+        Action::StageAll => app.stage_all_files(),
+        Action::Discard => app.discard_selected_file(),
+        Action::Ignore => app.show_ignore_chooser(),
+        Action::IntentToAdd => app.toggle_intent_to_add(),
+        Action::ToggleSection => app.toggle_section(),
+        Action::CycleSort => app.cycle_sort_mode(),
+        Action::ToggleTree => app.toggle_tree_view(),
+        Action::Open if app.selected_file().is_none() => app.toggle_section(),
+        Action::Open if app.selected_untracked_dir().is_some() => app.toggle_untracked_dir(),
+        Action::Open => app.show_conflict(),
+        Action::AbortMerge => app.abort_merge(),
+        Action::ContinueMerge => app.continue_merge(),
+        Action::Commit => app.start_commit(),
+        Action::Stash => app.input_mode = InputMode::StashMessage,
+        Action::StashSelected => app.start_stash_selected(),
+        Action::ListStashes => app.list_stashes(),
+        Action::Log => app.show_log(),
+        Action::Reflog => app.show_reflog(),
+        Action::Branches => app.show_branches(),
+        Action::Compare => app.start_compare(""),
+        Action::Archive => app.start_archive(),
+        Action::CreateBranchAtHead => app.create_branch_at_head(),
+        Action::SwitchBack => app.switch_to_previous_branch(),
+        Action::Bisect => app.toggle_bisect(),
+        Action::BisectGood => app.bisect_good(),
+        Action::BisectBad => app.bisect_bad(),
+        Action::BisectSkip => app.bisect_skip(),
+        Action::RepoSwitcher => app.show_repo_switcher(),
+        Action::ApplyStash => app.apply_latest_stash(),
+        Action::OpenOnForge => app.open_on_forge(),
+        Action::CopyPath => app.copy_selected_path(),
+        Action::CopyDiff => app.copy_preview(),
+        Action::Push => app.push(),
+        Action::ForcePush => app.force_push(),
+        Action::Pull => app.pull(),
+        Action::Fetch => app.fetch(),
+        Action::Refresh => app.refresh_files(),
+        Action::Help => app.show_help(),
+        Action::CommandLog => app.toggle_command_log(),
+        Action::History => app.toggle_history(),
+        Action::Doctor => app.show_doctor(),
+        Action::Config => app.show_config(),
+        Action::Maintenance => app.show_maintenance(),
+        Action::Preview => app.show_preview(),
+        Action::FileVersions => app.show_file_versions(),
+        Action::TogglePreviewPanel => app.toggle_preview_panel(),
+        Action::CyclePreviewPosition => app.cycle_preview_position(),
+        Action::ShrinkPreview => app.resize_preview(-PREVIEW_RESIZE_STEP),
+        Action::GrowPreview => app.resize_preview(PREVIEW_RESIZE_STEP),
+        Action::ToggleDiffView => app.toggle_diff_view(),
+        Action::LessContext => app.change_diff_context(-1),
+        Action::MoreContext => app.change_diff_context(1),
+        Action::IgnoreWhitespace => app.toggle_ignore_whitespace(),
+        Action::IgnoreBlankLines => app.toggle_ignore_blank_lines(),
+        Action::DismissMessage => app.status.dismiss(),
+    }
+    false
+}
+
+// コミットモードのキー処理
+fn handle_commit_key(app: &mut App, key: KeyEvent) {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        match key.code {
+            KeyCode::Char('s') => app.toggle_commit_signing(),
+            KeyCode::Char('n') => app.toggle_skip_hooks(),
+            KeyCode::Char('a') => app.toggle_auto_stage(),
+            KeyCode::Char('o') => app.force_commit(),
+            KeyCode::Char('t') => app.toggle_commit_file(),
+            KeyCode::Char('e') => app.edit_commit_message(),
+            KeyCode::Char('p') => app.show_identity_picker(),
+            KeyCode::Char('r') => app.show_trailer_picker(),
+            KeyCode::Char('d') => app.toggle_signoff(),
+            _ => {},
+        }
+        return;
+    }
+    match key.code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::Normal;
+        },
+        KeyCode::Down => app.next(),
+        KeyCode::Up => app.previous(),
+        KeyCode::Tab => app.show_prefix_picker(),
+        KeyCode::Enter => {
+            app.commit();
+        },
+        _ => {},
+    }
+}
+
+// スタッシュメッセージモードのキー処理
+fn handle_stash_message_key(app: &mut App, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Char('k') if ctrl => app.toggle_stash_keep_index(),
+        KeyCode::Tab => app.toggle_stash_untracked(),
+        KeyCode::Esc => app.cancel_stash(),
+        KeyCode::Enter => {
+            app.stash_changes();
+        },
+        _ => {},
+    }
+}
+
+// Confirm mode key processing
+fn handle_confirm_key(app: &mut App, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('y' | 'Y') => app.handle_confirm(true),
+        KeyCode::Char('n' | 'N') | KeyCode::Esc => app.handle_confirm(false),
+        _ => false,
+    }
+}
+
+// Help mode key processing
+fn handle_help_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q' | 'h') | KeyCode::Esc => {
+            app.exit_help();
+        },
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.scroll_help_down();
+        },
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.scroll_help_up();
+        },
+        _ => {
+            if let Some(jump) = keymap::scroll_jump(key) {
+                app.jump_help(jump);
+            }
+        },
+    }
+}
+
+fn handle_command_log_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q' | '@') | KeyCode::Esc => app.toggle_command_log(),
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_command_log_down(),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_command_log_up(),
+        _ => {
+            if let Some(jump) = keymap::scroll_jump(key) {
+                app.jump_command_log(jump);
+            }
+        },
+    }
+}
+
+fn handle_history_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q' | 'H') | KeyCode::Esc => app.toggle_history(),
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_history_down(),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_history_up(),
+        _ => {
+            if let Some(jump) = keymap::scroll_jump(key) {
+                app.jump_history(jump);
+            }
+        },
+    }
+}
+
+fn handle_doctor_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q' | 'I') | KeyCode::Esc => app.exit_doctor(),
+        KeyCode::Char('r') => app.show_doctor(),
+        KeyCode::Char('y') => app.copy_doctor_report(),
+        _ => {},
+    }
+}
+
+fn handle_config_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_config(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_config_row(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_config_row(),
+        KeyCode::Enter | KeyCode::Char('e') => app.edit_selected_config(),
+        KeyCode::Char('r') => app.refresh_config(),
+        _ => {
+            if let Some(jump) = keymap::scroll_jump(key) {
+                app.jump_config(jump);
+            }
+        },
+    }
+}
+
+fn handle_config_edit_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.cancel_config_edit(),
+        KeyCode::Enter => app.submit_config_edit(),
+        KeyCode::Tab => app.cycle_config_scope(),
+        _ => {},
+    }
+}
+
+// Preview mode key processing (fullscreen)
+fn handle_preview_key(app: &mut App, key: KeyEvent) {
+    if app.preview_search_input.is_some() {
+        match key.code {
+            KeyCode::Esc => app.cancel_preview_search(),
+            KeyCode::Enter => app.submit_preview_search(),
+            _ => {},
+        }
+        return;
+    }
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => {
+            app.exit_preview();
+        },
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.scroll_preview_down();
+        },
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.scroll_preview_up();
+        },
+        KeyCode::Char('/') => app.start_preview_search(),
+        KeyCode::Char('n') => app.next_preview_match(true),
+        KeyCode::Char('N') => app.next_preview_match(false),
+        KeyCode::Char('x') => app.toggle_hex_dump(),
+        KeyCode::Char('y') => app.copy_preview(),
+        KeyCode::Char('{') => app.change_diff_context(-1),
+        KeyCode::Char('}') => app.change_diff_context(1),
+        KeyCode::Char('W') => app.toggle_ignore_whitespace(),
+        KeyCode::Char('E') => app.toggle_ignore_blank_lines(),
+        _ => {
+            if let Some(jump) = keymap::scroll_jump(key) {
+                app.jump_preview(jump);
+            }
+        },
+    }
+}
+
+fn handle_file_version_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_file_versions(),
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_preview_down(),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_preview_up(),
+        KeyCode::Tab => app.next_file_version(),
+        KeyCode::Char('1') => app.select_file_version(FileVersion::Head),
+        KeyCode::Char('2') => app.select_file_version(FileVersion::Index),
+        KeyCode::Char('3') => app.select_file_version(FileVersion::Worktree),
+        _ => {
+            if let Some(jump) = keymap::scroll_jump(key) {
+                app.jump_preview(jump);
+            }
+        },
+    }
+}
+
+// Commit log key processing
+fn handle_log_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => {
+            app.exit_log();
+        },
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.next_log_entry();
+        },
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.previous_log_entry();
+        },
+        KeyCode::Enter => app.show_commit_details(),
+        KeyCode::Char('d') => app.show_commit_preview(),
+        KeyCode::Char('/') => app.start_log_search(),
+        KeyCode::Char('n') => app.next_log_match(true),
+        KeyCode::Char('N') => app.next_log_match(false),
+        KeyCode::Char('g') => app.mark_selected_commit(true),
+        KeyCode::Char('b') => app.mark_selected_commit(false),
+        KeyCode::Char('O') => app.open_on_forge(),
+        KeyCode::Char('y') => app.copy_selected_commit_hash(),
+        KeyCode::Char('S') => app.reset_to_selected_commit(ResetMode::Soft),
+        KeyCode::Char('M') => app.reset_to_selected_commit(ResetMode::Mixed),
+        KeyCode::Char('X') => app.reset_to_selected_commit(ResetMode::Hard),
+        KeyCode::Char('f') => app.fixup_selected_commit(),
+        KeyCode::Char('A') => app.autosquash_from_selected_commit(),
+        KeyCode::Char('c') => app.compare_selected_commit(),
+        KeyCode::Char('Z') => app.start_archive(),
+        KeyCode::Char('r') => {
+            app.refresh_log();
+        },
+        // `g` marks a good commit above, so the log goes to the top with Home only
+        _ => {
+            if let Some(jump) = keymap::scroll_jump(key) {
+                app.jump_log(jump);
+            }
+        },
+    }
+}
+
+fn handle_commit_details_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_commit_details(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_commit_details_row(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_commit_details_row(),
+        KeyCode::Char('n') => app.next_commit_file(true),
+        KeyCode::Char('N') => app.next_commit_file(false),
+        KeyCode::Enter | KeyCode::Char(' ') => app.toggle_commit_file_diff(),
+        KeyCode::Char('d') => app.show_commit_file_diff(),
+        KeyCode::Char('D') => app.show_commit_preview(),
+        KeyCode::Char('y') => app.copy_selected_commit_hash(),
+        _ => {
+            if let Some(jump) = keymap::scroll_jump(key) {
+                app.jump_commit_details(jump);
+            }
+        },
+    }
+}
+
+fn handle_compare_input_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.cancel_compare_input(),
+        KeyCode::Enter => app.submit_compare_input(),
+        _ => {},
+    }
+}
+
+fn handle_archive_prompt_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.cancel_archive_prompt(),
+        KeyCode::Enter => app.submit_archive_prompt(),
+        _ => {},
+    }
+}
+
+fn handle_compare_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_compare(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_compare_file(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_compare_file(),
+        KeyCode::Enter | KeyCode::Char('d') => app.show_compare_diff(false),
+        KeyCode::Char('D') => app.show_compare_diff(true),
+        KeyCode::Char('r') => app.refresh_compare(),
+        _ => {
+            if let Some(jump) = keymap::scroll_jump(key) {
+                app.jump_compare(jump);
+            }
+        },
+    }
+}
+
+// Reflog key processing
+fn handle_reflog_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_reflog(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_reflog_entry(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_reflog_entry(),
+        KeyCode::Enter | KeyCode::Char('d') => app.show_reflog_preview(),
+        KeyCode::Char('c') => app.checkout_reflog_entry(),
+        KeyCode::Char('x') => app.reset_to_reflog_entry(),
+        KeyCode::Char('r') => app.refresh_reflog(),
+        _ => {},
+    }
+}
+
+fn handle_branches_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_branches(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_branch(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_branch(),
+        KeyCode::Char('x' | 'D') => app.delete_selected_branch(),
+        KeyCode::Char('m') => app.show_merge_picker(),
+        KeyCode::Char('R') => app.start_rename_branch(),
+        KeyCode::Char('u') => app.start_set_upstream(),
+        KeyCode::Char('c') => app.compare_selected_branch(),
+        KeyCode::Char('Z') => app.start_archive(),
+        KeyCode::Char('f') => app.fetch_prune(),
+        KeyCode::Char('X') => app.delete_gone_branches(),
+        KeyCode::Char('r') => app.refresh_branches(),
+        _ => {},
+    }
+}
+
+fn handle_branch_prompt_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.cancel_branch_prompt(),
+        KeyCode::Enter => app.submit_branch_prompt(),
+        _ => {},
+    }
+}
+
+fn handle_merge_picker_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_merge_picker(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_merge_strategy(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_merge_strategy(),
+        KeyCode::Enter => app.merge_with_selected_strategy(),
+        _ => {},
+    }
+}
+
+fn handle_pull_picker_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_pull_picker(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_pull_strategy(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_pull_strategy(),
+        KeyCode::Enter => app.pull_with_selected_strategy(),
+        _ => {},
+    }
+}
+
+fn handle_maintenance_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_maintenance(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_maintenance_task(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_maintenance_task(),
+        KeyCode::Enter => app.run_selected_maintenance(),
+        KeyCode::Char('r') => app.refresh_repo_stats(),
+        _ => {},
+    }
+}
+
+// Stash list key processing
+fn handle_stash_list_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_stash_list(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_stash(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_stash(),
+        KeyCode::Enter | KeyCode::Char('d') => app.show_stash_preview(),
+        KeyCode::Char('a') => app.apply_selected_stash(),
+        KeyCode::Char('p') => app.pop_selected_stash(),
+        KeyCode::Char('x' | 'D') => app.drop_selected_stash(),
+        KeyCode::Char('r') => app.refresh_stashes(),
+        _ => {},
+    }
+}
+
+// Repository switcher key processing
+fn handle_repo_switcher_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_repo_switcher(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_repo(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_repo(),
+        KeyCode::Enter => app.switch_to_selected_repo(),
+        _ => {},
+    }
+}
+
+/// Keys of the screen shown outside a repository. Returns true to quit.
+fn handle_no_repository_key(app: &mut App, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => return true,
+        KeyCode::Char('i') => app.init_repo(),
+        KeyCode::Char('I') => app.start_init_setup(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_repo(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_repo(),
+        KeyCode::Enter => app.switch_to_selected_repo(),
+        _ => {},
+    }
+    false
+}
+
+// `git init` with options: the branch name is typed, then a template chosen, then y/n
+fn handle_init_setup_key(app: &mut App, step: InitStep, key: KeyEvent) {
+    match (step, key.code) {
+        (_, KeyCode::Esc) => app.previous_init_step(),
+        (InitStep::Branch | InitStep::Gitignore, KeyCode::Enter) => app.next_init_step(),
+        (InitStep::Gitignore, KeyCode::Char('j') | KeyCode::Down) => app.next_init_template(),
+        (InitStep::Gitignore, KeyCode::Char('k') | KeyCode::Up) => app.previous_init_template(),
+        (InitStep::InitialCommit, KeyCode::Char('y') | KeyCode::Enter) => {
+            app.finish_init_setup(true);
+        },
+        (InitStep::InitialCommit, KeyCode::Char('n')) => app.finish_init_setup(false),
+        _ => {},
+    }
+}
+
+// Conflict view key processing
+fn handle_conflict_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_conflict(),
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_conflict_down(),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_conflict_up(),
+        KeyCode::Char('o') => app.resolve_conflict(ConflictSide::Ours),
+        KeyCode::Char('t') => app.resolve_conflict(ConflictSide::Theirs),
+        KeyCode::Char('e') => app.edit_conflict(),
+        _ => {},
+    }
+}
+
+// Rejected commit hook output key processing
+fn handle_hook_output_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_hook_output(),
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_preview_down(),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_preview_up(),
+        KeyCode::Char('n') => app.retry_commit_no_verify(),
+        _ => {},
+    }
+}
+
+// Line staging editor key processing
+fn handle_line_stage_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_line_staging(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_line(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_line(),
+        KeyCode::Char(' ') => app.toggle_line(),
+        KeyCode::Char('a') => app.toggle_hunk(),
+        KeyCode::Enter | KeyCode::Char('s') => app.apply_line_selection(),
+        _ => {},
+    }
+}
+
+// .gitignore chooser key processing
+fn handle_prefix_picker_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_prefix_picker(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_prefix(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_prefix(),
+        KeyCode::Enter => app.choose_prefix(),
+        _ => {},
+    }
+}
+
+fn handle_identity_picker_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_identity_picker(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_identity(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_identity(),
+        KeyCode::Enter => app.use_selected_identity(),
+        KeyCode::Char('s') => app.set_selected_identity_for_repo(),
+        _ => {},
+    }
+}
+
+fn handle_trailer_picker_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_trailer_picker(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_trailer_author(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_trailer_author(),
+        KeyCode::Enter | KeyCode::Char(' ') => app.toggle_selected_trailer(),
+        KeyCode::Tab => app.cycle_trailer_key(),
+        _ => {},
+    }
+}
+
+fn handle_log_search_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.cancel_log_search(),
+        KeyCode::Enter => app.submit_log_search(),
+        _ => {},
+    }
+}
+
+fn handle_credential_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.cancel_credential(),
+        KeyCode::Enter => app.submit_credential(),
+        _ => {},
+    }
+}
+
+fn handle_typed_confirm_key(app: &mut App, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Esc => app.cancel_typed_confirm(),
+        KeyCode::Enter => return app.submit_typed_confirm(),
+        _ => {},
+    }
+    false
+}
+
+fn handle_prefix_scope_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => app.exit_prefix_picker(),
+        KeyCode::Enter => app.submit_prefix_scope(),
+        _ => {},
+    }
+}
+
+fn handle_ignore_chooser_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.exit_ignore_chooser(),
+        KeyCode::Char('j') | KeyCode::Down => app.next_ignore_option(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_ignore_option(),
+        KeyCode::Enter => app.add_selected_ignore_pattern(),
+        _ => {},
+    }
+}
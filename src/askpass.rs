@@ -0,0 +1,246 @@
+//! Answering git's credential prompts and SSH passphrase prompts from inside the TUI.
+//!
+//! Remote commands run with `GIT_ASKPASS` and `SSH_ASKPASS` pointing at this executable. Started that
+//! way it is a helper: it forwards the prompt over a Unix socket to the running app and prints
+//! the typed reply for git or ssh to read.
+
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+
+/// Set for the helper: the socket of the app waiting for the reply
+pub const SOCKET_ENV: &str = "PRETTY_GIT_UI_ASKPASS";
+
+/// A prompt from git or ssh waiting for the user
+#[derive(Debug)]
+pub struct Prompt {
+    pub text: String,
+    reply: Sender<Option<String>>,
+}
+
+impl Prompt {
+    /// Sends the reply back to the helper; `None` makes the helper fail, so git gives up
+    pub fn answer(self, reply: Option<String>) {
+        let _ = self.reply.send(reply);
+    }
+}
+
+/// Whether the reply to `prompt` should be masked: everything but usernames and ssh's yes/no
+/// questions
+pub fn is_secret(prompt: &str) -> bool {
+    let prompt = prompt.to_ascii_lowercase();
+    !(prompt.starts_with("username") || prompt.contains("(yes/no"))
+}
+
+/// The socket prompts arrive on, in a directory only the current user can enter
+#[derive(Debug)]
+pub struct Server {
+    dir: PathBuf,
+    prompts: Mutex<Receiver<Prompt>>,
+}
+
+impl Server {
+    /// Listens in a new directory `dir`, which is removed again by `stop`
+    #[cfg(unix)]
+    pub fn bind(dir: PathBuf) -> Result<Self, String> {
+        use std::os::unix::fs::DirBuilderExt;
+        use std::os::unix::net::UnixListener;
+
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        let listener = UnixListener::bind(dir.join("socket"))
+            .map_err(|e| format!("Failed to listen for credential prompts: {e}"))?;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                std::thread::spawn(move || serve(stream, &sender));
+            }
+        });
+        Ok(Self {
+            dir,
+            prompts: Mutex::new(receiver),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind(_dir: PathBuf) -> Result<Self, String> {
+        Err(String::from("Credential prompts need Unix sockets"))
+    }
+
+    pub fn socket(&self) -> PathBuf {
+        self.dir.join("socket")
+    }
+
+    /// The oldest prompt nobody took yet
+    pub fn next_prompt(&self) -> Option<Prompt> {
+        self.prompts.lock().ok()?.try_recv().ok()
+    }
+
+    pub fn stop(&self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Reads one prompt line from the helper, waits for the reply and writes it back. Closing
+/// without a reply tells the helper the prompt was cancelled.
+fn serve<S: Read + Write>(mut stream: S, prompts: &Sender<Prompt>) {
+    let mut text = String::new();
+    if BufReader::new(&mut stream).read_line(&mut text).is_err() {
+        return;
+    }
+    let (reply, answer) = mpsc::channel();
+    let prompt = Prompt {
+        text: text.trim_end().to_string(),
+        reply,
+    };
+    if prompts.send(prompt).is_err() {
+        return;
+    }
+    if let Ok(Some(answer)) = answer.recv() {
+        let _ = stream.write_all(format!("{answer}\n").as_bytes());
+    }
+}
+
+static SERVER: OnceLock<Server> = OnceLock::new();
+/// The prompt shown to the user, answered by `answer`
+static CURRENT: Mutex<Option<Prompt>> = Mutex::new(None);
+
+/// Starts answering prompts for this process. Without it remote commands keep failing on
+/// prompts instead of asking.
+pub fn start() -> Result<(), String> {
+    let dir = std::env::temp_dir().join(format!("pretty-git-ui-{}", std::process::id()));
+    let server = Server::bind(dir)?;
+    if let Err(server) = SERVER.set(server) {
+        server.stop();
+    }
+    Ok(())
+}
+
+/// Removes the socket when the app exits
+pub fn stop() {
+    if let Some(server) = SERVER.get() {
+        server.stop();
+    }
+}
+
+/// Points git's and ssh's prompts of `command` at the helper once `start` ran
+pub fn configure(command: &mut Command) {
+    let (Some(server), Ok(exe)) = (SERVER.get(), std::env::current_exe()) else {
+        return;
+    };
+    command
+        .env("GIT_ASKPASS", &exe)
+        .env("SSH_ASKPASS", &exe)
+        // OpenSSH 8.4+: use the helper even though a terminal is attached
+        .env("SSH_ASKPASS_REQUIRE", "force")
+        .env(SOCKET_ENV, server.socket());
+}
+
+/// Text of the oldest prompt waiting for the user, which becomes the one `answer` replies to.
+/// `None` while another prompt is unanswered.
+pub fn next_prompt() -> Option<String> {
+    let mut current = CURRENT.lock().ok()?;
+    if current.is_some() {
+        return None;
+    }
+    let prompt = SERVER.get()?.next_prompt()?;
+    let text = prompt.text.clone();
+    *current = Some(prompt);
+    drop(current);
+    Some(text)
+}
+
+/// Replies to the prompt `next_prompt` returned; `None` cancels it
+pub fn answer(reply: Option<String>) {
+    let prompt = CURRENT.lock().ok().and_then(|mut current| current.take());
+    if let Some(prompt) = prompt {
+        prompt.answer(reply);
+    }
+}
+
+/// Sends `prompt` to the app listening on `socket` and waits for the reply
+#[cfg(unix)]
+pub fn request(socket: &Path, prompt: &str) -> Result<String, String> {
+    use std::os::unix::net::UnixStream;
+
+    let mut stream =
+        UnixStream::connect(socket).map_err(|e| format!("Failed to reach the app: {e}"))?;
+    stream
+        .write_all(format!("{}\n", prompt.replace('\n', " ")).as_bytes())
+        .map_err(|e| format!("Failed to send the prompt: {e}"))?;
+    let mut reply = String::new();
+    stream
+        .read_to_string(&mut reply)
+        .map_err(|e| format!("Failed to read the reply: {e}"))?;
+    reply
+        .strip_suffix('\n')
+        .map(ToString::to_string)
+        .ok_or_else(|| String::from("Cancelled"))
+}
+
+#[cfg(not(unix))]
+pub fn request(_socket: &Path, _prompt: &str) -> Result<String, String> {
+    Err(String::from("Credential prompts need Unix sockets"))
+}
+
+/// The helper's `main`: prints the reply to `prompt` and returns the exit status
+pub fn run_helper(socket: &OsStr, prompt: &str) -> i32 {
+    match request(Path::new(socket), prompt) {
+        Ok(reply) => {
+            println!("{reply}");
+            0
+        },
+        Err(e) => {
+            eprintln!("pretty-git-ui: {e}");
+            1
+        },
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let server = Server::bind(temp.path().join("askpass")).unwrap();
+        let socket = server.socket();
+        let helper =
+            std::thread::spawn(move || request(&socket, "Password for 'https://me@example.com': "));
+
+        let prompt = loop {
+            if let Some(prompt) = server.next_prompt() {
+                break prompt;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert_eq!(prompt.text, "Password for 'https://me@example.com':");
+        assert!(is_secret(&prompt.text));
+        prompt.answer(Some(String::from("hunter2")));
+        assert_eq!(helper.join().unwrap(), Ok(String::from("hunter2")));
+
+        let socket = server.socket();
+        let helper = std::thread::spawn(move || request(&socket, "Username for 'x': "));
+        let prompt = loop {
+            if let Some(prompt) = server.next_prompt() {
+                break prompt;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert!(!is_secret(&prompt.text));
+        assert!(!is_secret(
+            "Are you sure you want to continue connecting (yes/no/[fingerprint])?"
+        ));
+        prompt.answer(None);
+        assert_eq!(helper.join().unwrap(), Err(String::from("Cancelled")));
+        server.stop();
+        assert!(!temp.path().join("askpass").exists());
+    }
+}
@@ -0,0 +1,401 @@
+use crate::command_log::LoggedCommand;
+use crate::error::GitError;
+use crate::git::{FileEntry, StatusOptions};
+use crate::repo::RepoContext;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Environment variable used to pick the git backend at run time (`command` or `libgit2`).
+pub const BACKEND_ENV: &str = "PRETTY_GIT_UI_BACKEND";
+
+/// Low-level repository access used by `GitOperations`.
+///
+/// The status-related queries that run on every refresh go through this trait so they can be
+/// served either by the `git` executable or, when built with the `libgit2` feature, in-process.
+/// Paths are passed as `OsStr` so they reach git exactly as listed by `status`.
+pub trait GitBackend: std::fmt::Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+    fn status(
+        &self,
+        repo: &RepoContext,
+        options: &StatusOptions,
+    ) -> Result<Vec<FileEntry>, GitError>;
+    fn stage_path(&self, repo: &RepoContext, path: &OsStr) -> Result<(), GitError>;
+    fn unstage_path(&self, repo: &RepoContext, path: &OsStr) -> Result<(), GitError>;
+    fn current_branch(&self, repo: &RepoContext) -> Result<String, GitError>;
+    fn repo_root(&self, repo: &RepoContext) -> Result<Option<PathBuf>, GitError>;
+}
+
+/// Returns the backend selected for this process.
+pub fn active() -> &'static dyn GitBackend {
+    static BACKEND: OnceLock<Box<dyn GitBackend>> = OnceLock::new();
+    BACKEND
+        .get_or_init(|| select_backend(std::env::var(BACKEND_ENV).ok().as_deref()))
+        .as_ref()
+}
+
+/// Chooses a backend by name. Unknown names, and `libgit2` in builds without the feature,
+/// fall back to the subprocess backend.
+pub fn select_backend(name: Option<&str>) -> Box<dyn GitBackend> {
+    match name {
+        #[cfg(feature = "libgit2")]
+        Some("libgit2" | "git2") => Box::new(Git2Backend),
+        _ => Box::new(CommandBackend),
+    }
+}
+
+/// Backend that shells out to the `git` executable.
+#[derive(Debug, Default)]
+pub struct CommandBackend;
+
+impl CommandBackend {
+    fn run<S: AsRef<OsStr>>(
+        repo: &RepoContext,
+        args: &[S],
+        action: &str,
+    ) -> Result<std::process::Output, GitError> {
+        repo.git()
+            .args(args)
+            .logged_output()
+            .map_err(|e| GitError::io(action, e))
+    }
+}
+
+impl GitBackend for CommandBackend {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn status(
+        &self,
+        repo: &RepoContext,
+        options: &StatusOptions,
+    ) -> Result<Vec<FileEntry>, GitError> {
+        let mut command = repo.git();
+        command.args(["status", "--porcelain=v2", "-z"]);
+        if let Some(untracked) = options.untracked {
+            command.arg(untracked.flag());
+        }
+        if !options.paths.is_empty() {
+            command
+                .env("GIT_LITERAL_PATHSPECS", "1")
+                .arg("--")
+                .args(&options.paths);
+        }
+        let output = command
+            .logged_output()
+            .map_err(|e| GitError::io("run git status", e))?;
+
+        if !output.status.success() {
+            if String::from_utf8_lossy(&output.stderr).contains("not a git repository") {
+                return Err(GitError::NotARepo(repo.dir().to_path_buf()));
+            }
+            return Err(GitError::command("status", &output));
+        }
+
+        Ok(FileEntry::parse_porcelain_v2(&output.stdout))
+    }
+
+    fn stage_path(&self, repo: &RepoContext, path: &OsStr) -> Result<(), GitError> {
+        let output = Self::run(
+            repo,
+            &[OsStr::new("add"), OsStr::new("--"), path],
+            "add file",
+        )?;
+        if !output.status.success() {
+            return Err(GitError::command("add", &output));
+        }
+        Ok(())
+    }
+
+    fn unstage_path(&self, repo: &RepoContext, path: &OsStr) -> Result<(), GitError> {
+        let output = Self::run(
+            repo,
+            &[OsStr::new("reset"), OsStr::new("--"), path],
+            "reset file",
+        )?;
+        if !output.status.success() {
+            return Err(GitError::command("reset", &output));
+        }
+        Ok(())
+    }
+
+    fn current_branch(&self, repo: &RepoContext) -> Result<String, GitError> {
+        let output = Self::run(repo, &["branch", "--show-current"], "get branch")?;
+
+        if !output.status.success() {
+            return Ok("(no branch)".to_string());
+        }
+
+        let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(if branch.is_empty() {
+            "(detached HEAD)".to_string()
+        } else {
+            branch
+        })
+    }
+
+    fn repo_root(&self, repo: &RepoContext) -> Result<Option<PathBuf>, GitError> {
+        let output = Self::run(repo, &["rev-parse", "--show-toplevel"], "get repo path")?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Some(PathBuf::from(path)))
+    }
+}
+
+/// In-process backend built on libgit2. Avoids spawning a process per query, which matters on
+/// large repositories, and is not affected by the user's locale or git configuration output.
+#[cfg(feature = "libgit2")]
+#[derive(Debug, Default)]
+pub struct Git2Backend;
+
+#[cfg(feature = "libgit2")]
+impl Git2Backend {
+    fn open(repo: &RepoContext) -> Result<git2::Repository, GitError> {
+        let error = |e: git2::Error| {
+            if e.code() == git2::ErrorCode::NotFound {
+                GitError::NotARepo(repo.dir().to_path_buf())
+            } else {
+                Self::error("open")(e)
+            }
+        };
+        let Some(git_dir) = repo.env("GIT_DIR") else {
+            return git2::Repository::discover(repo.dir()).map_err(error);
+        };
+        let repository = git2::Repository::open(git_dir).map_err(error)?;
+        if let Some(work_tree) = repo.env("GIT_WORK_TREE") {
+            repository
+                .set_workdir(std::path::Path::new(work_tree), false)
+                .map_err(error)?;
+        }
+        Ok(repository)
+    }
+
+    /// The error the `git <command>` equivalent of a failed libgit2 call reports
+    fn error(command: &'static str) -> impl Fn(git2::Error) -> GitError {
+        move |e| GitError::CommandFailed {
+            command: command.to_string(),
+            code: Some(e.raw_code()),
+            stderr: e.message().to_string(),
+        }
+    }
+
+    /// Converts libgit2 status flags into the two-letter porcelain code. Conflicts get theirs
+    /// from `conflict_code`.
+    fn porcelain_code(status: git2::Status) -> String {
+        use git2::Status;
+
+        if status.contains(Status::WT_NEW) && !status.intersects(Status::INDEX_NEW) {
+            return "??".to_string();
+        }
+
+        let index = if status.contains(Status::INDEX_NEW) {
+            'A'
+        } else if status.contains(Status::INDEX_MODIFIED) {
+            'M'
+        } else if status.contains(Status::INDEX_DELETED) {
+            'D'
+        } else if status.contains(Status::INDEX_RENAMED) {
+            'R'
+        } else if status.contains(Status::INDEX_TYPECHANGE) {
+            'T'
+        } else {
+            ' '
+        };
+        let worktree = if status.contains(Status::WT_MODIFIED) {
+            'M'
+        } else if status.contains(Status::WT_DELETED) {
+            'D'
+        } else if status.contains(Status::WT_RENAMED) {
+            'R'
+        } else if status.contains(Status::WT_TYPECHANGE) {
+            'T'
+        } else {
+            ' '
+        };
+        format!("{index}{worktree}")
+    }
+
+    /// The porcelain code of a conflicted path, from which of the common ancestor (stage 1),
+    /// ours (2) and theirs (3) the index holds, e.g. `AA` for a file both sides added
+    fn conflict_code(index: &git2::Index, path: &std::path::Path) -> &'static str {
+        let stage = |stage| index.get_path(path, stage).is_some();
+        match (stage(1), stage(2), stage(3)) {
+            (true, false, false) => "DD",
+            (false, true, false) => "AU",
+            (true, true, false) => "UD",
+            (false, false, true) => "UA",
+            (true, false, true) => "DU",
+            (false, true, true) => "AA",
+            _ => "UU",
+        }
+    }
+}
+
+#[cfg(feature = "libgit2")]
+impl GitBackend for Git2Backend {
+    fn name(&self) -> &'static str {
+        "libgit2"
+    }
+
+    fn status(
+        &self,
+        repo: &RepoContext,
+        options: &StatusOptions,
+    ) -> Result<Vec<FileEntry>, GitError> {
+        use crate::git::UntrackedFiles;
+
+        let repository = Self::open(repo)?;
+        let untracked = options.untracked.unwrap_or(UntrackedFiles::Normal);
+        let mut status_options = git2::StatusOptions::new();
+        status_options
+            .include_untracked(untracked != UntrackedFiles::No)
+            .recurse_untracked_dirs(untracked == UntrackedFiles::All)
+            .include_ignored(false)
+            .renames_head_to_index(true)
+            .disable_pathspec_match(true);
+        for path in &options.paths {
+            status_options.pathspec(path);
+        }
+        let statuses = repository
+            .statuses(Some(&mut status_options))
+            .map_err(Self::error("status"))?;
+        let index = repository.index().map_err(Self::error("read-index"))?;
+        // libgit2 reports a `git add -N` entry as an added empty file; git shows it as ` A`
+        let intent_to_add = |path: &std::path::Path| {
+            index.get_path(path, 0).is_some_and(|entry| {
+                git2::IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended)
+                    .is_intent_to_add()
+            })
+        };
+
+        Ok(statuses
+            .iter()
+            .map(|entry| {
+                let path = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+                let file_path = path(entry.path_bytes());
+                let index_path = std::path::Path::new(&file_path);
+                let code: Vec<char> = if entry.status().is_conflicted() {
+                    Self::conflict_code(&index, index_path).chars().collect()
+                } else if intent_to_add(index_path) {
+                    vec![' ', 'A']
+                } else {
+                    Self::porcelain_code(entry.status()).chars().collect()
+                };
+                let mut file = FileEntry::new(file_path.clone(), code[0], code[1]);
+                if let Some(delta) = entry.head_to_index() {
+                    let (old, new) = (delta.old_file().path_bytes(), delta.new_file().path_bytes());
+                    if let (Some(old), Some(new)) = (old, new) {
+                        if old != new {
+                            file.path = path(new);
+                            file.orig_path = Some(path(old));
+                        }
+                    }
+                }
+                file
+            })
+            .collect())
+    }
+
+    fn stage_path(&self, repo: &RepoContext, path: &OsStr) -> Result<(), GitError> {
+        let repository = Self::open(repo)?;
+        let mut index = repository.index().map_err(Self::error("read-index"))?;
+        let file = std::path::Path::new(path);
+        let on_disk = repository
+            .workdir()
+            .is_some_and(|dir| dir.join(file).exists());
+        // The error `git add` gives, so both backends report a missing path alike
+        if !on_disk && index.get_path(file, 0).is_none() {
+            return Err(GitError::CommandFailed {
+                command: "add".to_string(),
+                code: Some(128),
+                stderr: format!(
+                    "fatal: pathspec '{}' did not match any files",
+                    file.display()
+                ),
+            });
+        }
+        if on_disk {
+            index.add_path(file)
+        } else {
+            index.remove_path(file)
+        }
+        .map_err(Self::error("add"))?;
+        index.write().map_err(Self::error("write-index"))
+    }
+
+    fn unstage_path(&self, repo: &RepoContext, path: &OsStr) -> Result<(), GitError> {
+        let repository = Self::open(repo)?;
+        let head = repository
+            .head()
+            .ok()
+            .and_then(|head| head.peel(git2::ObjectType::Commit).ok());
+        repository
+            .reset_default(head.as_ref(), [std::path::Path::new(path)])
+            .map_err(Self::error("reset"))
+    }
+
+    fn current_branch(&self, repo: &RepoContext) -> Result<String, GitError> {
+        let Ok(repository) = Self::open(repo) else {
+            return Ok("(no branch)".to_string());
+        };
+        if repository.head_detached().unwrap_or(false) {
+            return Ok("(detached HEAD)".to_string());
+        }
+        // An unborn branch has no HEAD commit yet but still has a name
+        let name = repository.head().map_or_else(
+            |_| {
+                repository
+                    .find_reference("HEAD")
+                    .ok()
+                    .and_then(|head| head.symbolic_target().map(String::from))
+                    .map(|target| target.trim_start_matches("refs/heads/").to_string())
+            },
+            |head| head.shorthand().map(String::from),
+        );
+        Ok(name.unwrap_or_else(|| "(no branch)".to_string()))
+    }
+
+    fn repo_root(&self, repo: &RepoContext) -> Result<Option<PathBuf>, GitError> {
+        Ok(Self::open(repo)
+            .ok()
+            .and_then(|repository| repository.workdir().map(std::path::Path::to_path_buf)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_backend_defaults_to_command() {
+        assert_eq!(select_backend(None).name(), "command");
+        assert_eq!(select_backend(Some("command")).name(), "command");
+        assert_eq!(select_backend(Some("unknown")).name(), "command");
+    }
+
+    #[cfg(feature = "libgit2")]
+    #[test]
+    fn test_select_libgit2_backend() {
+        assert_eq!(select_backend(Some("libgit2")).name(), "libgit2");
+    }
+
+    #[cfg(feature = "libgit2")]
+    #[test]
+    fn test_porcelain_code_from_git2_status() {
+        use git2::Status;
+
+        assert_eq!(Git2Backend::porcelain_code(Status::WT_NEW), "??");
+        assert_eq!(Git2Backend::porcelain_code(Status::INDEX_NEW), "A ");
+        assert_eq!(
+            Git2Backend::porcelain_code(Status::INDEX_MODIFIED | Status::WT_MODIFIED),
+            "MM"
+        );
+        assert_eq!(Git2Backend::porcelain_code(Status::WT_DELETED), " D");
+    }
+}
@@ -0,0 +1,256 @@
+//! Binary files in previews: what kind of file it is and how big, instead of raw bytes, and a
+//! hex dump for small ones.
+
+use std::fmt::Write;
+
+/// Binaries up to this size can be shown as a hex dump
+pub const HEX_DUMP_LIMIT: usize = 64 * 1024;
+
+/// Git's heuristic: a NUL byte near the start means binary
+pub fn is_binary(bytes: &[u8]) -> bool {
+    const BINARY_CHECK_LEN: usize = 8000;
+
+    bytes[..bytes.len().min(BINARY_CHECK_LEN)].contains(&0)
+}
+
+/// Known file signatures, checked against the first bytes
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "PNG image"),
+    (b"\xff\xd8\xff", "JPEG image"),
+    (b"GIF87a", "GIF image"),
+    (b"GIF89a", "GIF image"),
+    (b"%PDF-", "PDF document"),
+    (b"PK\x03\x04", "ZIP archive"),
+    (b"\x1f\x8b", "gzip archive"),
+    (b"BZh", "bzip2 archive"),
+    (b"\xfd7zXZ\x00", "xz archive"),
+    (b"7z\xbc\xaf\x27\x1c", "7-Zip archive"),
+    (b"\x7fELF", "ELF executable"),
+    (b"MZ", "Windows executable"),
+    (b"\xcf\xfa\xed\xfe", "Mach-O executable"),
+    (b"\x00asm", "WebAssembly module"),
+    (b"SQLite format 3\x00", "SQLite database"),
+    (b"\x00\x00\x01\x00", "ICO image"),
+    (b"OggS", "Ogg media"),
+    (b"ID3", "MP3 audio"),
+    (b"wOFF", "WOFF font"),
+    (b"wOF2", "WOFF2 font"),
+];
+
+/// What kind of file `bytes` hold, from their signature
+pub fn file_type(bytes: &[u8]) -> &'static str {
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" {
+        match &bytes[8..12] {
+            b"WEBP" => return "WebP image",
+            b"WAVE" => return "WAV audio",
+            _ => {},
+        }
+    }
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+        .map_or("binary data", |&(_, name)| name)
+}
+
+/// `532 bytes`, or `1.2 KiB (1234 bytes)` from a KiB on
+pub fn format_size(len: usize) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+    if len < 1024 {
+        return format!("{len} bytes");
+    }
+    let mut unit = 0;
+    let mut scale = 1024;
+    while len / scale >= 1024 && unit + 1 < UNITS.len() {
+        scale *= 1024;
+        unit += 1;
+    }
+    let tenths = len.saturating_mul(10) / scale;
+    format!(
+        "{}.{} {} ({len} bytes)",
+        tenths / 10,
+        tenths % 10,
+        UNITS[unit]
+    )
+}
+
+/// `+2 bytes`, `-1.2 KiB (1234 bytes)` or `unchanged`
+pub fn format_size_delta(before: usize, after: usize) -> String {
+    match after.cmp(&before) {
+        std::cmp::Ordering::Equal => "unchanged".to_string(),
+        std::cmp::Ordering::Greater => format!("+{}", format_size(after - before)),
+        std::cmp::Ordering::Less => format!("-{}", format_size(before - after)),
+    }
+}
+
+fn be16(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u32::from(u16::from_be_bytes([b[0], b[1]])))
+}
+
+fn le16(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u32::from(u16::from_le_bytes([b[0], b[1]])))
+}
+
+fn le24(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+}
+
+fn be32(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 4)?;
+    Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Width and height from the first frame header, walking the segments up to it
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut at = 2;
+    loop {
+        if *bytes.get(at)? != 0xff {
+            return None;
+        }
+        let marker = *bytes.get(at + 1)?;
+        match marker {
+            // Fill byte before a marker
+            0xff => at += 1,
+            // Markers without a length
+            0x01 | 0xd0..=0xd8 => at += 2,
+            // Start of frame, except DHT, JPG and DAC which share the range
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                return Some((be16(bytes, at + 7)?, be16(bytes, at + 5)?));
+            },
+            0xd9 | 0xda => return None,
+            _ => at += 2 + usize::try_from(be16(bytes, at + 2)?).ok()?,
+        }
+    }
+}
+
+/// Canvas size of a lossy, lossless or extended WebP
+fn webp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    match bytes.get(12..16)? {
+        b"VP8 " => Some((le16(bytes, 26)? & 0x3fff, le16(bytes, 28)? & 0x3fff)),
+        b"VP8L" => {
+            let bits = u32::from_le_bytes(bytes.get(21..25)?.try_into().ok()?);
+            Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+        },
+        b"VP8X" => Some((le24(bytes, 24)? + 1, le24(bytes, 27)? + 1)),
+        _ => None,
+    }
+}
+
+/// Width and height of a PNG, GIF, JPEG or WebP image, read from its header
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    match file_type(bytes) {
+        "PNG image" if bytes.get(12..16)? == b"IHDR" => Some((be32(bytes, 16)?, be32(bytes, 20)?)),
+        "GIF image" => Some((le16(bytes, 6)?, le16(bytes, 8)?)),
+        "JPEG image" => jpeg_dimensions(bytes),
+        "WebP image" => webp_dimensions(bytes),
+        _ => None,
+    }
+}
+
+/// `PNG image, 640×480, 1.2 KiB (1234 bytes)`; the dimensions only for images
+pub fn describe(bytes: &[u8]) -> String {
+    match image_dimensions(bytes) {
+        Some((width, height)) => format!(
+            "{}, {width}×{height}, {}",
+            file_type(bytes),
+            format_size(bytes.len())
+        ),
+        None => format!("{}, {}", file_type(bytes), format_size(bytes.len())),
+    }
+}
+
+/// What changed between two versions of a binary file: `+2 bytes`, with
+/// `640×480 → 800×600` when an image was resized
+pub fn describe_change(before: &[u8], after: &[u8]) -> String {
+    let delta = format_size_delta(before.len(), after.len());
+    match (image_dimensions(before), image_dimensions(after)) {
+        (Some((w1, h1)), Some((w2, h2))) if (w1, h1) != (w2, h2) => {
+            format!("{delta}, {w1}×{h1} → {w2}×{h2}")
+        },
+        _ => delta,
+    }
+}
+
+/// `hexdump -C` style lines: offset, 16 bytes in hex and the printable ones as text
+pub fn hex_dump(bytes: &[u8]) -> String {
+    let mut dump = String::new();
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        let _ = write!(dump, "{:08x} ", i * 16);
+        for j in 0..16 {
+            if j % 8 == 0 {
+                dump.push(' ');
+            }
+            match chunk.get(j) {
+                Some(byte) => {
+                    let _ = write!(dump, "{byte:02x} ");
+                },
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str(" |");
+        dump.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                char::from(byte)
+            } else {
+                '.'
+            }
+        }));
+        dump.push_str("|\n");
+    }
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_type_and_size() {
+        assert_eq!(file_type(b"\x89PNG\r\n\x1a\n\x00\x00"), "PNG image");
+        assert_eq!(file_type(b"RIFF\x10\x00\x00\x00WEBPVP8 "), "WebP image");
+        assert_eq!(file_type(b"\x00\x01\x02"), "binary data");
+        assert_eq!(format_size(532), "532 bytes");
+        assert_eq!(format_size(1234), "1.2 KiB (1234 bytes)");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB (3145728 bytes)");
+        assert!(is_binary(b"GIF89a\x00"));
+        assert!(!is_binary("テキスト".as_bytes()));
+    }
+
+    #[test]
+    fn test_image_dimensions() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR\x00\x00\x02\x80\x00\x00\x01\xe0\x08\x06";
+        assert_eq!(image_dimensions(png), Some((640, 480)));
+        assert_eq!(describe(png), "PNG image, 640×480, 26 bytes");
+        assert_eq!(image_dimensions(b"GIF89a\x20\x00\x10\x00"), Some((32, 16)));
+        // SOI, an APP0 segment, then SOF0 with height 16 and width 32
+        let jpeg = b"\xff\xd8\xff\xe0\x00\x04JF\xff\xc0\x00\x11\x08\x00\x10\x00\x20";
+        assert_eq!(image_dimensions(jpeg), Some((32, 16)));
+        let webp =
+            b"RIFF\x00\x00\x00\x00WEBPVP8X\x0a\x00\x00\x00\x00\x00\x00\x00\x3f\x00\x00\x1f\x00\x00";
+        assert_eq!(image_dimensions(webp), Some((64, 32)));
+        assert_eq!(image_dimensions(b"\x89PNG\r\n\x1a\n"), None);
+
+        let mut larger = png.to_vec();
+        larger[19] = 0x00;
+        larger.extend_from_slice(&[0; 1300]);
+        assert_eq!(
+            describe_change(png, &larger),
+            "+1.2 KiB (1300 bytes), 640×480 → 512×480"
+        );
+        assert_eq!(describe_change(&larger, &larger), "unchanged");
+        assert_eq!(format_size_delta(10, 4), "-6 bytes");
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let dump = hex_dump(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR\x00\x01");
+        assert_eq!(
+            dump,
+            "00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|\n\
+             00000010  00 01                                             |..|\n"
+        );
+    }
+}
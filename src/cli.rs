@@ -0,0 +1,151 @@
+use crate::error::GitError;
+use crate::git::GitOperations;
+use crate::model;
+use crate::repo::RepoContext;
+use crate::strings::Language;
+use serde_json::{json, Value};
+
+/// Subcommands that run without the TUI and print JSON, for scripts and CI. Their messages
+/// are always in English so scripts can rely on them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `status [--json]`: branch, upstream and changed files
+    Status,
+    /// `stage <path>...`
+    Stage(Vec<String>),
+    /// `unstage <path>...`
+    Unstage(Vec<String>),
+    /// `commit -m <message>`
+    Commit(String),
+}
+
+impl Command {
+    /// Names that start a subcommand instead of naming a repository to open
+    pub const NAMES: [&'static str; 4] = ["status", "stage", "unstage", "commit"];
+
+    /// Parses a subcommand name and the arguments after it
+    pub fn parse(name: &str, args: &[String]) -> Result<Self, String> {
+        match name {
+            "status" => match args {
+                [] => Ok(Self::Status),
+                [flag] if flag == "--json" => Ok(Self::Status),
+                [other, ..] => Err(format!("Unknown argument for status: {other}")),
+            },
+            "stage" | "unstage" => {
+                if args.is_empty() {
+                    return Err(format!("Usage: pretty-git-ui {name} <path>..."));
+                }
+                let paths = args.to_vec();
+                Ok(if name == "stage" {
+                    Self::Stage(paths)
+                } else {
+                    Self::Unstage(paths)
+                })
+            },
+            "commit" => match args {
+                [flag, message] if flag == "-m" || flag == "--message" => {
+                    Ok(Self::Commit(message.clone()))
+                },
+                _ => Err("Usage: pretty-git-ui commit -m <message>".to_string()),
+            },
+            _ => Err(format!("Unknown command: {name}")),
+        }
+    }
+
+    /// Runs the command and returns the JSON document to print
+    pub fn run(&self, repo: &RepoContext) -> Result<Value, GitError> {
+        match self {
+            Self::Status => status(repo),
+            Self::Stage(paths) => stage_paths(repo, paths, true),
+            Self::Unstage(paths) => stage_paths(repo, paths, false),
+            Self::Commit(message) => {
+                if message.trim().is_empty() {
+                    return Err(GitError::Invalid(
+                        "Commit message cannot be empty".to_string(),
+                    ));
+                }
+                let sign = GitOperations::is_commit_signing_enabled(repo);
+                let message = GitOperations::commit(repo, message, sign, false)?;
+                Ok(json!({ "ok": true, "message": message.text(Language::En) }))
+            },
+        }
+    }
+}
+
+fn status(repo: &RepoContext) -> Result<Value, GitError> {
+    let snapshot = model::snapshot(repo)?;
+    Ok(json!({
+        "branch": snapshot.branch.name,
+        "upstream": snapshot.branch.upstream,
+        "summary": snapshot.summary,
+        "files": snapshot.files,
+    }))
+}
+
+/// Stages or unstages each path. Paths listed in the status go through the entry so both
+/// sides of a rename are handled; anything else (e.g. a directory) is passed to git as is.
+fn stage_paths(repo: &RepoContext, paths: &[String], stage: bool) -> Result<Value, GitError> {
+    let files = GitOperations::get_status(repo)?;
+    let messages = paths
+        .iter()
+        .map(|path| {
+            let entry = files.iter().find(|entry| &entry.path == path);
+            match (entry, stage) {
+                (Some(entry), true) => GitOperations::stage_entry(repo, entry),
+                (Some(entry), false) => GitOperations::unstage_entry(repo, entry),
+                (None, true) => GitOperations::stage_path(repo, path),
+                (None, false) => GitOperations::unstage_path(repo, path),
+            }
+            .map(|message| message.text(Language::En))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(json!({ "ok": true, "messages": messages }))
+}
+
+/// The JSON printed when a command fails
+pub fn error_json(error: &str) -> Value {
+    json!({ "ok": false, "error": error })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::FileEntry;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            Command::parse("status", &args(&["--json"])),
+            Ok(Command::Status)
+        );
+        assert_eq!(
+            Command::parse("stage", &args(&["a.txt", "b.txt"])),
+            Ok(Command::Stage(args(&["a.txt", "b.txt"])))
+        );
+        assert_eq!(
+            Command::parse("commit", &args(&["-m", "Fix bug"])),
+            Ok(Command::Commit("Fix bug".to_string()))
+        );
+        assert!(Command::parse("unstage", &[]).is_err());
+        assert!(Command::parse("status", &args(&["--yaml"])).is_err());
+        assert!(Command::parse("commit", &args(&["Fix bug"])).is_err());
+    }
+
+    #[test]
+    fn test_file_entry_json() {
+        let entry = FileEntry::new("src/main.rs", 'M', ' ');
+        assert_eq!(
+            serde_json::to_value(&entry).unwrap(),
+            json!({
+                "orig_path": null,
+                "path": "src/main.rs",
+                "index_status": "M",
+                "worktree_status": " ",
+            })
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Every git command the app runs, with how long it took, how it exited and everything it
+//! wrote to stderr, so a short `Error: Git add failed` can be looked up in full.
+//!
+//! Commands run on the UI thread and in background tasks alike, so they are recorded into a
+//! process-wide queue that `App` collects into its own log on every tick. Long-running
+//! commands can also publish their output while they run.
+
+use std::collections::VecDeque;
+use std::io;
+use std::process::{Command, Output};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How many commands are kept; the oldest are dropped first
+pub const CAPACITY: usize = 200;
+
+/// Commands recorded since `take` was last called
+static PENDING: Mutex<VecDeque<CommandRecord>> = Mutex::new(VecDeque::new());
+
+/// Commands still running, with the output they wrote so far
+static RUNNING: Mutex<Vec<CommandRecord>> = Mutex::new(Vec::new());
+
+/// One finished command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRecord {
+    /// Program and arguments, e.g. `git add -- a.txt`
+    pub command: String,
+    pub duration: Duration,
+    /// `None` when the command could not be started or was killed
+    pub exit_code: Option<i32>,
+    /// Everything written to stderr, or why the command could not be started
+    pub stderr: String,
+    /// How many identical successful runs in a row this entry stands for
+    pub repeats: usize,
+}
+
+impl CommandRecord {
+    pub fn new(command: &Command, started: Instant, exit_code: Option<i32>, stderr: &str) -> Self {
+        Self {
+            command: describe(command),
+            duration: started.elapsed(),
+            exit_code,
+            stderr: stderr.trim_end().to_string(),
+            repeats: 1,
+        }
+    }
+
+    pub const fn succeeded(&self) -> bool {
+        matches!(self.exit_code, Some(0))
+    }
+
+    /// Lines the entry takes in the log pane: the command, then its stderr
+    pub fn line_count(&self) -> usize {
+        1 + self.stderr.lines().count()
+    }
+}
+
+/// `git -c a=b log --format=%h "two words"`, quoting arguments that contain spaces
+pub fn describe(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| {
+            let arg = arg.to_string_lossy();
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("\"{arg}\"")
+            } else {
+                arg.into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Appends `record`, dropping the oldest entry once `CAPACITY` is reached.
+///
+/// A successful run of the same command as the last entry only bumps its count, so the
+/// periodic upstream check does not push real failures out of the log.
+pub fn push_bounded(log: &mut VecDeque<CommandRecord>, record: CommandRecord) {
+    if let Some(last) = log.back_mut() {
+        if last.command == record.command && last.succeeded() && record.succeeded() {
+            last.repeats += 1;
+            last.duration = record.duration;
+            last.stderr = record.stderr;
+            return;
+        }
+    }
+    if log.len() >= CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(record);
+}
+
+pub fn record(record: CommandRecord) {
+    if let Ok(mut running) = RUNNING.lock() {
+        running.retain(|entry| entry.command != record.command);
+    }
+    if let Ok(mut pending) = PENDING.lock() {
+        push_bounded(&mut pending, record);
+    }
+}
+
+/// Publishes what a running command wrote so far. `record` replaces it once it exits.
+pub fn update_running(command: &Command, started: Instant, output: &str) {
+    let entry = CommandRecord::new(command, started, None, output);
+    if let Ok(mut running) = RUNNING.lock() {
+        match running.iter_mut().find(|r| r.command == entry.command) {
+            Some(existing) => *existing = entry,
+            None => running.push(entry),
+        }
+    }
+}
+
+/// The commands that are still running, oldest first
+pub fn running() -> Vec<CommandRecord> {
+    RUNNING
+        .lock()
+        .map(|running| running.clone())
+        .unwrap_or_default()
+}
+
+/// The commands recorded since the last call, oldest first
+pub fn take() -> Vec<CommandRecord> {
+    PENDING
+        .lock()
+        .map(|mut pending| pending.drain(..).collect())
+        .unwrap_or_default()
+}
+
+/// `Command::output` that records the command
+pub trait LoggedCommand {
+    fn logged_output(&mut self) -> io::Result<Output>;
+}
+
+impl LoggedCommand for Command {
+    fn logged_output(&mut self) -> io::Result<Output> {
+        let started = Instant::now();
+        let output = self.output();
+        let entry = match &output {
+            Ok(output) => CommandRecord::new(
+                self,
+                started,
+                output.status.code(),
+                &String::from_utf8_lossy(&output.stderr),
+            ),
+            Err(e) => CommandRecord::new(self, started, None, &e.to_string()),
+        };
+        record(entry);
+        output
+    }
+}
+
+/// `12ms`, or `1.5s` from a second on
+pub fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(command: &str, exit_code: Option<i32>) -> CommandRecord {
+        CommandRecord {
+            command: command.to_string(),
+            duration: Duration::from_millis(5),
+            exit_code,
+            stderr: String::new(),
+            repeats: 1,
+        }
+    }
+
+    #[test]
+    fn test_log_is_bounded_and_merges_repeats() {
+        let mut log = VecDeque::new();
+        push_bounded(&mut log, entry("git fetch", Some(0)));
+        push_bounded(&mut log, entry("git fetch", Some(0)));
+        push_bounded(&mut log, entry("git fetch", Some(1)));
+        push_bounded(&mut log, entry("git fetch", Some(1)));
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[0].repeats, 2);
+
+        for i in 0..CAPACITY {
+            push_bounded(&mut log, entry(&format!("git show {i}"), Some(0)));
+        }
+        assert_eq!(log.len(), CAPACITY);
+        assert_eq!(log[0].command, "git show 0");
+    }
+
+    #[test]
+    fn test_describe_and_format() {
+        let mut command = Command::new("git");
+        command.args(["commit", "-m", "fix the bug", ""]);
+        assert_eq!(describe(&command), "git commit -m \"fix the bug\" \"\"");
+        assert_eq!(format_duration(Duration::from_millis(42)), "42ms");
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1.5s");
+
+        let failed = CommandRecord::new(&command, Instant::now(), Some(128), "fatal: x\nhint: y\n");
+        assert!(!failed.succeeded());
+        assert_eq!(failed.line_count(), 3);
+    }
+
+    #[test]
+    fn test_running_output_is_replaced_by_the_record() {
+        let mut command = Command::new("git");
+        command.args(["gc", "--test-running-output"]);
+        let started = Instant::now();
+        update_running(&command, started, "Counting objects: 10%");
+        update_running(&command, started, "Counting objects: 50%");
+        let in_progress: Vec<_> = running()
+            .into_iter()
+            .filter(|r| r.command == "git gc --test-running-output")
+            .collect();
+        assert_eq!(in_progress.len(), 1);
+        assert_eq!(in_progress[0].stderr, "Counting objects: 50%");
+
+        record(CommandRecord::new(&command, started, Some(0), ""));
+        assert!(!running()
+            .iter()
+            .any(|r| r.command == "git gc --test-running-output"));
+    }
+}
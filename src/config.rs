@@ -0,0 +1,329 @@
+use crate::app::commit::CommitLintConfig;
+use crate::git::{DiffOptions, Identity, UntrackedFiles};
+use crate::strings::Language;
+use crate::theme::ThemeConfig;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Environment variable that points at an explicit config file
+pub const CONFIG_ENV: &str = "PRETTY_GIT_UI_CONFIG";
+
+/// Where the preview panel sits next to the file list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewPosition {
+    #[default]
+    Right,
+    Bottom,
+    /// Not shown at startup; `v` brings it back on the right
+    Hidden,
+}
+
+impl PreviewPosition {
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Right => Self::Bottom,
+            Self::Bottom => Self::Hidden,
+            Self::Hidden => Self::Right,
+        }
+    }
+}
+
+/// The `[layout]` table. Changes made with the layout keys are written back to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    pub preview: PreviewPosition,
+    /// Share of the main area taken by the preview panel, in percent
+    pub preview_size: u16,
+}
+
+impl LayoutConfig {
+    pub const MIN_PREVIEW_SIZE: u16 = 20;
+    pub const MAX_PREVIEW_SIZE: u16 = 80;
+
+    /// `preview_size` limited to the range that leaves both panels usable
+    pub fn preview_percent(&self) -> u16 {
+        self.preview_size
+            .clamp(Self::MIN_PREVIEW_SIZE, Self::MAX_PREVIEW_SIZE)
+    }
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            preview: PreviewPosition::Right,
+            preview_size: 50,
+        }
+    }
+}
+
+/// The `[notify]` table: how background operations (push, pull, fetch, stage all) announce
+/// that they finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// A toast in the corner when the operation finishes while another view is open
+    pub toast: bool,
+    /// The terminal bell
+    pub bell: bool,
+    /// A desktop notification through `notify-send` or `osascript`
+    pub desktop: bool,
+    /// The bell and desktop notifications are only for operations that ran this long
+    pub min_seconds: u64,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            toast: true,
+            bell: false,
+            desktop: false,
+            min_seconds: 5,
+        }
+    }
+}
+
+/// Settings read from `config.toml`. A missing file means every default applies.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Ask before quitting while a commit message draft exists or a git operation is running
+    pub confirm_quit: bool,
+    /// `ja` or `en`. Unset means the language of the locale.
+    pub language: Option<Language>,
+    pub theme: ThemeConfig,
+    pub commit: CommitLintConfig,
+    /// Which untracked files the status lists. Unset means `normal`, switching to `no` when
+    /// status turns out slow.
+    pub untracked_files: Option<UntrackedFiles>,
+    pub layout: LayoutConfig,
+    pub notify: NotifyConfig,
+    pub diff: DiffOptions,
+    /// `[[identities]]`: the name and email profiles offered by the identity picker
+    pub identities: Vec<Identity>,
+    /// File every operation's outcome is appended to, besides the history pane
+    pub history_file: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            confirm_quit: true,
+            language: None,
+            theme: ThemeConfig::default(),
+            commit: CommitLintConfig::default(),
+            untracked_files: None,
+            layout: LayoutConfig::default(),
+            notify: NotifyConfig::default(),
+            diff: DiffOptions::default(),
+            identities: Vec::new(),
+            history_file: None,
+        }
+    }
+}
+
+impl Config {
+    /// `$PRETTY_GIT_UI_CONFIG`, else `$XDG_CONFIG_HOME/pretty-git-ui/config.toml`,
+    /// else `~/.config/pretty-git-ui/config.toml`
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_ENV) {
+            return Some(PathBuf::from(path));
+        }
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("pretty-git-ui").join("config.toml"))
+    }
+
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                Self::parse(&content).map_err(|e| format!("Invalid config {}: {e}", path.display()))
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read config {}: {e}", path.display())),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        toml::from_str(content).map_err(|e| e.message().to_string())
+    }
+
+    /// Replaces the `[layout]` table of the config file at `path`, creating the file if
+    /// needed. The rest of the file, comments included, is kept as it is.
+    pub fn save_layout(path: &Path, layout: &LayoutConfig) -> Result<(), String> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Failed to read config {}: {e}", path.display())),
+        };
+        let table = toml::to_string(layout).map_err(|e| format!("Failed to write layout: {e}"))?;
+        let updated = replace_table(&content, "layout", &table);
+        // Never leave behind a file that no longer loads, e.g. one with an inline layout table
+        Self::parse(&updated)
+            .map_err(|e| format!("Cannot update config {}: {e}", path.display()))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        std::fs::write(path, updated)
+            .map_err(|e| format!("Failed to write config {}: {e}", path.display()))
+    }
+}
+
+/// `content` without the `[name]` table, with `[name]` and `body` appended at the end
+fn replace_table(content: &str, name: &str, body: &str) -> String {
+    let header = format!("[{name}]");
+    let mut in_table = false;
+    let mut kept = String::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_table = trimmed == header;
+        }
+        if !in_table {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+    let mut updated = kept.trim_end().to_string();
+    if !updated.is_empty() {
+        updated.push_str("\n\n");
+    }
+    updated.push_str(&header);
+    updated.push('\n');
+    updated.push_str(body);
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::commit::{CommitContents, PrefixStyle};
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(Config::parse(""), Ok(Config::default()));
+
+        let config = Config::parse("[theme]\npreset = \"light\"\nstaged = \"#00aa00\"\n").unwrap();
+        assert_eq!(config.theme.preset.as_deref(), Some("light"));
+        assert_eq!(config.theme.staged.as_deref(), Some("#00aa00"));
+
+        assert!(Config::parse("[theme]\nstagged = \"green\"\n").is_err());
+
+        let config =
+            Config::parse("[commit]\nconventional = true\nmax_subject_length = 50\n").unwrap();
+        assert!(config.commit.conventional);
+        assert_eq!(config.commit.max_subject_length, 50);
+        assert!(config.commit.no_trailing_period);
+        assert!(config.confirm_quit);
+
+        let config =
+            Config::parse("confirm_quit = false\n[commit]\nconventional = true\n").unwrap();
+        assert!(!config.confirm_quit);
+        assert!(config.commit.conventional);
+        assert_eq!(config.untracked_files, None);
+
+        let config = Config::parse("untracked_files = \"no\"\n").unwrap();
+        assert_eq!(config.untracked_files, Some(UntrackedFiles::No));
+        assert!(Config::parse("untracked_files = \"some\"\n").is_err());
+
+        let config = Config::parse("language = \"en\"\n").unwrap();
+        assert_eq!(config.language, Some(Language::En));
+        assert!(Config::parse("language = \"fr\"\n").is_err());
+
+        let config = Config::parse(
+            "[commit]\nprefix_style = \"gitmoji\"\nprefixes = [{ prefix = \":tada:\" }]\ncontents = \"all\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.commit.prefix_style, PrefixStyle::Gitmoji);
+        assert_eq!(config.commit.contents, CommitContents::All);
+        assert_eq!(
+            config.commit.prefix_choices(Language::Ja)[0].prefix,
+            ":tada:"
+        );
+
+        let config = Config::parse("[layout]\npreview = \"bottom\"\npreview_size = 95\n").unwrap();
+        assert_eq!(config.layout.preview, PreviewPosition::Bottom);
+        assert_eq!(
+            config.layout.preview_percent(),
+            LayoutConfig::MAX_PREVIEW_SIZE
+        );
+        assert!(Config::parse("[layout]\npreview = \"left\"\n").is_err());
+
+        let config = Config::parse("[notify]\nbell = true\nmin_seconds = 10\n").unwrap();
+        assert!(config.notify.toast);
+        assert!(config.notify.bell);
+        assert!(!config.notify.desktop);
+        assert_eq!(config.notify.min_seconds, 10);
+
+        let config = Config::parse("[diff]\ncontext = 1\nignore_whitespace = true\n").unwrap();
+        assert_eq!(config.diff.context, Some(1));
+        assert!(config.diff.ignore_whitespace);
+        assert!(!config.diff.ignore_blank_lines);
+        assert!(Config::parse("[diff]\nwhitespace = true\n").is_err());
+
+        let config = Config::parse(
+            "[[identities]]\nlabel = \"work\"\nname = \"Jane\"\nemail = \"jane@corp.example\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.identities.len(), 1);
+        assert_eq!(config.identities[0].to_string(), "Jane <jane@corp.example>");
+        assert!(Config::parse("[[identities]]\nname = \"Jane\"\n").is_err());
+
+        let config = Config::parse(
+            "[commit]\nticket_pattern = \"#([0-9]+)\"\n[commit.snippets]\nrefs = \"Refs: {ticket}\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.commit.expand("{refs}", "fix/#42-crash"), "Refs: 42");
+        assert!(Config::parse("[commit]\nticket_pattern = \"[\"\n").is_err());
+
+        let config = Config::parse("history_file = \"/tmp/history.log\"\n").unwrap();
+        assert_eq!(config.history_file, Some(PathBuf::from("/tmp/history.log")));
+    }
+
+    #[test]
+    fn test_save_layout_keeps_other_settings() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pretty-git-ui").join("config.toml");
+        let layout = LayoutConfig {
+            preview: PreviewPosition::Bottom,
+            preview_size: 40,
+        };
+        Config::save_layout(&path, &layout).unwrap();
+        assert_eq!(
+            Config::parse(&std::fs::read_to_string(&path).unwrap())
+                .unwrap()
+                .layout,
+            layout
+        );
+
+        let original = "# my settings\nconfirm_quit = false\n\n[layout]\npreview = \"right\"\n\n[theme]\npreset = \"light\"\n";
+        std::fs::write(&path, original).unwrap();
+        Config::save_layout(&path, &layout).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            content.starts_with("# my settings\nconfirm_quit = false\n\n[theme]\n"),
+            "{content}"
+        );
+        let config = Config::parse(&content).unwrap();
+        assert_eq!(config.layout, layout);
+        assert!(!config.confirm_quit);
+        assert_eq!(config.theme.preset.as_deref(), Some("light"));
+
+        // An inline table cannot be replaced line by line; the file is left alone
+        std::fs::write(&path, "layout = { preview = \"right\" }\n").unwrap();
+        assert!(Config::save_layout(&path, &layout).is_err());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "layout = { preview = \"right\" }\n"
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! Unified diff output parsed into files, hunks and lines.
+//!
+//! Everything that draws or edits a diff classifies its lines here instead of looking at
+//! their first character, so `--- a/x` headers, `--stat` lines and commit messages are
+//! never taken for removed or added lines.
+
+/// Role of one line of diff output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineKind {
+    /// Anything outside a file's diff: commit headers and messages, `--stat` lines, notes
+    #[default]
+    Text,
+    /// `diff --git`, `index`, `---`/`+++`, mode, rename and `Binary files` lines
+    FileHeader,
+    /// `@@ -a,b +c,d @@`
+    HunkHeader,
+    Context,
+    Added,
+    Removed,
+    /// `\ No newline at end of file`
+    NoNewline,
+}
+
+impl LineKind {
+    /// An added or removed line
+    pub const fn is_change(self) -> bool {
+        matches!(self, Self::Added | Self::Removed)
+    }
+}
+
+/// `start,count` of one side of a hunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: u32,
+    pub count: u32,
+}
+
+impl LineRange {
+    /// `12,3`, or `12` for a single line
+    fn parse(text: &str) -> Option<Self> {
+        let (start, count) = text.split_once(',').unwrap_or((text, "1"));
+        Some(Self {
+            start: start.parse().ok()?,
+            count: count.parse().ok()?,
+        })
+    }
+}
+
+/// The ranges of `@@ -a,b +c,d @@`. The combined diff of a merge (`@@@ -a,b -c,d +e,f @@@`)
+/// has one old range per parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HunkHeader {
+    pub old: Vec<LineRange>,
+    pub new: LineRange,
+}
+
+impl HunkHeader {
+    pub fn parse(line: &str) -> Option<Self> {
+        let marks = line.bytes().take_while(|&b| b == b'@').count();
+        if marks < 2 {
+            return None;
+        }
+        let mut parts = line[marks..].split_whitespace();
+        let old = (1..marks)
+            .map(|_| LineRange::parse(parts.next()?.strip_prefix('-')?))
+            .collect::<Option<Vec<_>>>()?;
+        let new = LineRange::parse(parts.next()?.strip_prefix('+')?)?;
+        let closing = parts.next()?;
+        (closing.len() == marks && closing.bytes().all(|b| b == b'@')).then_some(Self { old, new })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub kind: LineKind,
+    /// The whole line, prefix included
+    pub text: String,
+    /// Line numbers in the old and new file. Only the new side is numbered in combined diffs.
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The `@@` line as written, including the function name git puts after it
+    pub header: String,
+    pub range: HunkHeader,
+    /// Context, added, removed and `\ No newline` lines
+    pub lines: Vec<Line>,
+}
+
+/// The diff of one file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffFile {
+    /// The lines before the first hunk
+    pub header: Vec<String>,
+    /// From `--- a/path`; `None` for a new file or when the header has no `---` line
+    pub old_path: Option<String>,
+    /// From `+++ b/path`; `None` for a deleted file or when the header has no `+++` line
+    pub new_path: Option<String>,
+    /// git printed `Binary files ... differ` instead of hunks
+    pub binary: bool,
+    pub hunks: Vec<Hunk>,
+}
+
+impl DiffFile {
+    fn add_header(&mut self, line: &str) {
+        let path = |path: &str, prefix: &str| {
+            (path != "/dev/null").then(|| path.strip_prefix(prefix).unwrap_or(path).to_string())
+        };
+        if let Some(old) = line.strip_prefix("--- ") {
+            self.old_path = path(old, "a/");
+        } else if let Some(new) = line.strip_prefix("+++ ") {
+            self.new_path = path(new, "b/");
+        } else if line.starts_with("Binary files ") && line.ends_with(" differ") {
+            self.binary = true;
+        }
+        self.header.push(line.to_string());
+    }
+
+    /// Every line in order: the header, then each hunk header followed by its lines
+    pub fn lines(&self) -> impl Iterator<Item = Line> + '_ {
+        let header = self.header.iter().map(|text| Line {
+            kind: LineKind::FileHeader,
+            text: text.clone(),
+            old_line: None,
+            new_line: None,
+        });
+        let hunks = self.hunks.iter().flat_map(|hunk| {
+            std::iter::once(Line {
+                kind: LineKind::HunkHeader,
+                text: hunk.header.clone(),
+                old_line: None,
+                new_line: None,
+            })
+            .chain(hunk.lines.iter().cloned())
+        });
+        header.chain(hunks)
+    }
+}
+
+/// Classifies diff output line by line. Hunks end once the line counts of their header are
+/// used up, so whatever follows a diff (the next commit of `git log -p`) is plain text again.
+#[derive(Debug, Default)]
+struct Classifier {
+    /// After `diff --git` or a hunk header, until a line that belongs to neither
+    in_file: bool,
+    seen_hunk: bool,
+    /// Lines still expected in the current hunk on each parent's side and on the new side
+    old_left: Vec<u32>,
+    new_left: u32,
+}
+
+impl Classifier {
+    fn in_hunk(&self) -> bool {
+        self.new_left > 0 || self.old_left.iter().any(|&left| left > 0)
+    }
+
+    fn next(&mut self, line: &str) -> LineKind {
+        if self.in_hunk() {
+            if line.starts_with('\\') {
+                return LineKind::NoNewline;
+            }
+            // One prefix column per parent; an empty line is context with its space trimmed
+            let bytes = line.as_bytes();
+            let prefix = &bytes[..self.old_left.len().min(bytes.len())];
+            if prefix.iter().all(|b| matches!(b, b' ' | b'+' | b'-')) {
+                // A removed line is only in the parents marked `-`; any other line is in
+                // the parents not marked `+`
+                let removed = prefix.contains(&b'-');
+                for (i, left) in self.old_left.iter_mut().enumerate() {
+                    let column = prefix.get(i).copied().unwrap_or(b' ');
+                    if (removed && column == b'-') || (!removed && column == b' ') {
+                        *left = left.saturating_sub(1);
+                    }
+                }
+                if !removed {
+                    self.new_left = self.new_left.saturating_sub(1);
+                }
+                return if removed {
+                    LineKind::Removed
+                } else if prefix.contains(&b'+') {
+                    LineKind::Added
+                } else {
+                    LineKind::Context
+                };
+            }
+            // The hunk is shorter than its header said
+            self.old_left.clear();
+            self.new_left = 0;
+        } else if line.starts_with('\\') && self.seen_hunk && self.in_file {
+            return LineKind::NoNewline;
+        }
+
+        if ["diff --git ", "diff --cc ", "diff --combined "]
+            .iter()
+            .any(|start| line.starts_with(start))
+        {
+            self.in_file = true;
+            self.seen_hunk = false;
+            return LineKind::FileHeader;
+        }
+        if let Some(header) = HunkHeader::parse(line) {
+            self.in_file = true;
+            self.seen_hunk = true;
+            self.old_left = header.old.iter().map(|range| range.count).collect();
+            self.new_left = header.new.count;
+            return LineKind::HunkHeader;
+        }
+        if self.in_file && !self.seen_hunk {
+            return LineKind::FileHeader;
+        }
+        self.in_file = false;
+        LineKind::Text
+    }
+}
+
+/// The kind of each line of `text`, as split by `str::lines`
+pub fn classify(text: &str) -> Vec<LineKind> {
+    let mut classifier = Classifier::default();
+    text.lines().map(|line| classifier.next(line)).collect()
+}
+
+/// The files of `text`, e.g. `git diff` or `git show` output. Lines outside any file's diff
+/// are skipped; a hunk without a `diff --git` header gets a file of its own.
+pub fn parse(text: &str) -> Vec<DiffFile> {
+    let mut files: Vec<DiffFile> = Vec::new();
+    let mut classifier = Classifier::default();
+    let mut open = false;
+    let (mut old_line, mut new_line) = (None, 0);
+    for line in text.lines() {
+        let kind = classifier.next(line);
+        match kind {
+            LineKind::Text => open = false,
+            LineKind::FileHeader => {
+                if line.starts_with("diff ") || !open {
+                    files.push(DiffFile::default());
+                    open = true;
+                }
+                if let Some(file) = files.last_mut() {
+                    file.add_header(line);
+                }
+            },
+            LineKind::HunkHeader => {
+                if !open {
+                    files.push(DiffFile::default());
+                    open = true;
+                }
+                let Some(range) = HunkHeader::parse(line) else {
+                    continue;
+                };
+                // Combined diffs have no single old side to number
+                old_line = match range.old.as_slice() {
+                    [old] => Some(old.start),
+                    _ => None,
+                };
+                new_line = range.new.start;
+                if let Some(file) = files.last_mut() {
+                    file.hunks.push(Hunk {
+                        header: line.to_string(),
+                        range,
+                        lines: Vec::new(),
+                    });
+                }
+            },
+            LineKind::Context | LineKind::Added | LineKind::Removed | LineKind::NoNewline => {
+                let numbers = match kind {
+                    LineKind::Context => (old_line, Some(new_line)),
+                    LineKind::Added => (None, Some(new_line)),
+                    LineKind::Removed => (old_line, None),
+                    _ => (None, None),
+                };
+                if matches!(kind, LineKind::Context | LineKind::Removed) {
+                    old_line = old_line.map(|n| n + 1);
+                }
+                if matches!(kind, LineKind::Context | LineKind::Added) {
+                    new_line += 1;
+                }
+                if let Some(hunk) = files.last_mut().and_then(|file| file.hunks.last_mut()) {
+                    hunk.lines.push(Line {
+                        kind,
+                        text: line.to_string(),
+                        old_line: numbers.0,
+                        new_line: numbers.1,
+                    });
+                }
+            },
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/f.txt b/f.txt
+index 1111111..2222222 100644
+--- a/f.txt
++++ b/f.txt
+@@ -1,3 +1,3 @@ fn main
+ one
+-two
++TWO
+ three
+@@ -10,2 +10,3 @@
+ ten
++new
+ eleven
+\\ No newline at end of file
+";
+
+    #[test]
+    fn test_parse_files_hunks_and_lines() {
+        let files = parse(DIFF);
+        assert_eq!(files.len(), 1);
+        let file = &files[0];
+        assert_eq!(file.header.len(), 4);
+        assert_eq!(file.old_path.as_deref(), Some("f.txt"));
+        assert_eq!(file.new_path.as_deref(), Some("f.txt"));
+        assert!(!file.binary);
+        assert_eq!(file.hunks.len(), 2);
+
+        let hunk = &file.hunks[0];
+        assert_eq!(hunk.header, "@@ -1,3 +1,3 @@ fn main");
+        assert_eq!(hunk.range.old, vec![LineRange { start: 1, count: 3 }]);
+        let kinds: Vec<LineKind> = hunk.lines.iter().map(|line| line.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                LineKind::Context,
+                LineKind::Removed,
+                LineKind::Added,
+                LineKind::Context
+            ]
+        );
+        let numbers: Vec<(Option<u32>, Option<u32>)> = hunk
+            .lines
+            .iter()
+            .map(|line| (line.old_line, line.new_line))
+            .collect();
+        assert_eq!(
+            numbers,
+            vec![
+                (Some(1), Some(1)),
+                (Some(2), None),
+                (None, Some(2)),
+                (Some(3), Some(3))
+            ]
+        );
+        let last = file.hunks[1].lines.last().unwrap();
+        assert_eq!(last.kind, LineKind::NoNewline);
+        assert_eq!(file.lines().count(), DIFF.lines().count());
+    }
+
+    #[test]
+    fn test_headers_and_text_around_a_diff_are_not_changes() {
+        let show = "commit abc\n\n    -- not a removal\n\n f.txt | 2 +-\n".to_string() + DIFF;
+        let kinds = classify(&show);
+        assert_eq!(&kinds[..5], &[LineKind::Text; 5]);
+        assert_eq!(kinds[5], LineKind::FileHeader);
+        // `--- a/f.txt` and `+++ b/f.txt`
+        assert_eq!(kinds[7], LineKind::FileHeader);
+        assert_eq!(kinds[8], LineKind::FileHeader);
+        assert_eq!(kinds[9], LineKind::HunkHeader);
+        assert_eq!(kinds[11], LineKind::Removed);
+
+        // Once the counts are used up, `git log -p` moves on to the next commit
+        let log = "@@ -1 +1 @@\n-a\n+b\ncommit def\n-- signature\n";
+        assert_eq!(
+            classify(log),
+            vec![
+                LineKind::HunkHeader,
+                LineKind::Removed,
+                LineKind::Added,
+                LineKind::Text,
+                LineKind::Text
+            ]
+        );
+    }
+
+    #[test]
+    fn test_binary_new_and_combined_diffs() {
+        let binary = "diff --git a/i.png b/i.png\nindex 1..2 100644\nBinary files a/i.png and b/i.png differ\n";
+        assert!(parse(binary)[0].binary);
+
+        let new =
+            "diff --git a/n b/n\nnew file mode 100644\n--- /dev/null\n+++ b/n\n@@ -0,0 +1 @@\n+a\n";
+        let file = &parse(new)[0];
+        assert_eq!(file.old_path, None);
+        assert_eq!(file.new_path.as_deref(), Some("n"));
+        assert_eq!(file.hunks[0].lines[0].new_line, Some(1));
+
+        let combined = "diff --cc m.txt\nindex 1,2..3\n--- a/m.txt\n+++ b/m.txt\n@@@ -1,2 -1,2 +1,2 @@@\n  same\n- ours\n -theirs\n++merged\n";
+        let kinds = classify(combined);
+        assert_eq!(kinds[4], LineKind::HunkHeader);
+        assert_eq!(kinds[5], LineKind::Context);
+        assert_eq!(kinds[6], LineKind::Removed);
+        assert_eq!(kinds[7], LineKind::Removed);
+        assert_eq!(kinds[8], LineKind::Added);
+        let hunk = &parse(combined)[0].hunks[0];
+        assert_eq!(hunk.range.old.len(), 2);
+        assert_eq!(hunk.lines[3].new_line, Some(2));
+        assert_eq!(hunk.lines[3].old_line, None);
+
+        assert_eq!(HunkHeader::parse("@@ -1 +1 @"), None);
+        assert_eq!(HunkHeader::parse("@@ -x +1 @@"), None);
+    }
+}
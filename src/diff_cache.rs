@@ -0,0 +1,176 @@
+//! Preview diffs that were already loaded, so moving up and down the file list does not
+//! run `git diff` again for files that did not change in between.
+
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use crate::diff_lines::DiffLines;
+use crate::git::{DiffOptions, DiffView, FileEntry};
+use crate::repo::RepoContext;
+
+/// Beyond this many diffs the cache starts over rather than growing with the working tree
+const MAX_ENTRIES: usize = 256;
+
+/// Which diff was loaded
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiffKey {
+    pub path: String,
+    pub view: DiffView,
+    pub options: DiffOptions,
+    /// How many lines were asked for; scrolling further loads a longer diff
+    pub max_lines: usize,
+}
+
+/// Cheap stand-in for a file's contents: a cached diff is only used while it is unchanged.
+/// Staging part of a file changes neither, so `App` also invalidates paths it operated on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fingerprint {
+    /// Working tree modification time and size, `None` when the file was deleted
+    modified: Option<(SystemTime, u64)>,
+    index_status: char,
+    worktree_status: char,
+}
+
+impl Fingerprint {
+    pub fn of(repo: &RepoContext, entry: &FileEntry) -> Self {
+        Self {
+            modified: std::fs::symlink_metadata(repo.path(&entry.path))
+                .and_then(|metadata| Ok((metadata.modified()?, metadata.len())))
+                .ok(),
+            index_status: entry.index_status,
+            worktree_status: entry.worktree_status,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DiffCache {
+    entries: HashMap<DiffKey, (Fingerprint, DiffLines)>,
+    /// Diffs being loaded in the background and the fingerprint taken when they started.
+    /// Invalidating forgets them too, so a result that arrives afterwards is not cached.
+    loading: HashMap<DiffKey, Fingerprint>,
+}
+
+impl DiffCache {
+    /// The diff loaded for `key`, unless the file changed since
+    pub fn get(&self, key: &DiffKey, fingerprint: Fingerprint) -> Option<&DiffLines> {
+        self.entries
+            .get(key)
+            .filter(|(cached, _)| *cached == fingerprint)
+            .map(|(_, diff)| diff)
+    }
+
+    /// Records that the diff for `key` is being loaded; `finish` caches it
+    pub fn start(&mut self, key: DiffKey, fingerprint: Fingerprint) {
+        self.loading.insert(key, fingerprint);
+    }
+
+    pub fn is_loading(&self, key: &DiffKey) -> bool {
+        self.loading.contains_key(key)
+    }
+
+    /// Caches a diff `start`ed earlier, unless it was invalidated meanwhile. `None` when
+    /// loading failed.
+    pub fn finish(&mut self, key: &DiffKey, diff: Option<DiffLines>) {
+        if let (Some(fingerprint), Some(diff)) = (self.loading.remove(key), diff) {
+            self.insert(key.clone(), fingerprint, diff);
+        }
+    }
+
+    pub fn insert(&mut self, key: DiffKey, fingerprint: Fingerprint, diff: DiffLines) {
+        if self.entries.len() >= MAX_ENTRIES && !self.entries.contains_key(&key) {
+            self.entries.clear();
+        }
+        self.entries.insert(key, (fingerprint, diff));
+    }
+
+    /// Forgets the diffs of `paths`, e.g. after some of their lines were staged
+    pub fn invalidate(&mut self, paths: &[String]) {
+        self.entries.retain(|key, _| !paths.contains(&key.path));
+        self.loading.retain(|key, _| !paths.contains(&key.path));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.loading.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(path: &str) -> DiffKey {
+        DiffKey {
+            path: path.to_string(),
+            view: DiffView::Unstaged,
+            options: DiffOptions::default(),
+            max_lines: 1000,
+        }
+    }
+
+    const fn fingerprint(worktree_status: char) -> Fingerprint {
+        Fingerprint {
+            modified: None,
+            index_status: ' ',
+            worktree_status,
+        }
+    }
+
+    #[test]
+    fn test_changed_fingerprint_misses() {
+        let mut cache = DiffCache::default();
+        cache.insert(key("a"), fingerprint('M'), DiffLines::from("+a"));
+
+        assert_eq!(
+            cache.get(&key("a"), fingerprint('M')),
+            Some(&DiffLines::from("+a"))
+        );
+        assert_eq!(cache.get(&key("a"), fingerprint('D')), None);
+        let longer = DiffKey {
+            max_lines: 2000,
+            ..key("a")
+        };
+        assert_eq!(cache.get(&longer, fingerprint('M')), None);
+    }
+
+    #[test]
+    fn test_invalidate_forgets_only_given_paths() {
+        let mut cache = DiffCache::default();
+        cache.insert(key("a"), fingerprint('M'), DiffLines::from("+a"));
+        cache.insert(key("b"), fingerprint('M'), DiffLines::from("+b"));
+
+        cache.invalidate(&["a".to_string()]);
+        assert_eq!(cache.get(&key("a"), fingerprint('M')), None);
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_results_invalidated_while_loading_are_dropped() {
+        let mut cache = DiffCache::default();
+        cache.start(key("a"), fingerprint('M'));
+        cache.start(key("b"), fingerprint('M'));
+        assert!(cache.is_loading(&key("a")));
+
+        cache.invalidate(&["a".to_string()]);
+        cache.finish(&key("a"), Some(DiffLines::from("+old")));
+        cache.finish(&key("b"), Some(DiffLines::from("+b")));
+        assert_eq!(cache.get(&key("a"), fingerprint('M')), None);
+        assert_eq!(
+            cache.get(&key("b"), fingerprint('M')),
+            Some(&DiffLines::from("+b"))
+        );
+        assert!(!cache.is_loading(&key("b")));
+    }
+}
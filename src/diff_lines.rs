@@ -0,0 +1,143 @@
+use crate::diff::{self, LineKind};
+use std::ops::{Deref, Range};
+
+/// Diff text split into lines and classified once, so drawing a screenful only touches the
+/// visible lines instead of walking the whole text on every frame
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffLines {
+    text: String,
+    /// Byte offset where each line starts
+    starts: Vec<usize>,
+    kinds: Vec<LineKind>,
+}
+
+impl DiffLines {
+    pub fn new(text: String) -> Self {
+        let mut starts = Vec::new();
+        if !text.is_empty() {
+            starts.push(0);
+        }
+        starts.extend(
+            text.match_indices('\n')
+                .map(|(i, _)| i + 1)
+                .filter(|&start| start < text.len()),
+        );
+        let kinds = diff::classify(&text);
+        Self {
+            text,
+            starts,
+            kinds,
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Line `index` without its line ending
+    pub fn line(&self, index: usize) -> Option<&str> {
+        let start = *self.starts.get(index)?;
+        let end = self
+            .starts
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.text.len());
+        let line = &self.text[start..end];
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+
+    /// What line `index` is in the diff; plain text for anything that is not a diff
+    pub fn kind(&self, index: usize) -> LineKind {
+        self.kinds.get(index).copied().unwrap_or_default()
+    }
+
+    /// Up to `count` lines starting at `start`
+    pub fn window(&self, start: usize, count: usize) -> impl Iterator<Item = &str> {
+        (start..self.line_count().min(start.saturating_add(count))).filter_map(|i| self.line(i))
+    }
+
+    /// Indices of the lines containing `query`, see `match_ranges`
+    pub fn search(&self, query: &str) -> Vec<usize> {
+        (0..self.line_count())
+            .filter(|&i| {
+                self.line(i)
+                    .is_some_and(|line| !match_ranges(line, query).is_empty())
+            })
+            .collect()
+    }
+}
+
+/// Byte ranges of the occurrences of `query` in `line`. Case is ignored unless the query
+/// contains an uppercase letter; only ASCII is folded, so the ranges are valid in `line`.
+pub fn match_ranges(line: &str, query: &str) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let matches = |haystack: &str, needle: &str| {
+        haystack
+            .match_indices(needle)
+            .map(|(start, found)| start..start + found.len())
+            .collect()
+    };
+    if query.chars().any(char::is_uppercase) {
+        matches(line, query)
+    } else {
+        matches(&line.to_ascii_lowercase(), query)
+    }
+}
+
+impl From<String> for DiffLines {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<&str> for DiffLines {
+    fn from(text: &str) -> Self {
+        Self::new(text.to_string())
+    }
+}
+
+impl Deref for DiffLines {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_match_str_lines() {
+        for text in ["", "one", "one\n", "one\r\ntwo\n\nfour", "\n\n"] {
+            let lines = DiffLines::from(text);
+            let expected: Vec<&str> = text.lines().collect();
+            assert_eq!(lines.line_count(), expected.len(), "{text:?}");
+            assert_eq!(lines.window(0, usize::MAX).collect::<Vec<_>>(), expected);
+        }
+        let lines = DiffLines::from("a\nb\nc\nd\n");
+        assert_eq!(lines.window(1, 2).collect::<Vec<_>>(), vec!["b", "c"]);
+        assert_eq!(lines.window(3, 5).collect::<Vec<_>>(), vec!["d"]);
+        assert_eq!(lines.window(9, 5).count(), 0);
+
+        let lines = DiffLines::from("new file: a\n@@ -0,0 +1 @@\n+a\n");
+        assert_eq!(lines.kind(0), LineKind::Text);
+        assert_eq!(lines.kind(2), LineKind::Added);
+        assert_eq!(lines.kind(3), LineKind::Text);
+    }
+
+    #[test]
+    fn test_search_is_smart_case() {
+        let lines = DiffLines::from("fn main() {\n+    Main::run();\n-    main_loop();\n}");
+        assert_eq!(lines.search("main"), vec![0, 1, 2]);
+        assert_eq!(lines.search("Main"), vec![1]);
+        assert_eq!(lines.search("nothing"), Vec::<usize>::new());
+        assert_eq!(match_ranges("a main main", "main"), vec![2..6, 7..11]);
+        assert_eq!(match_ranges("Ünïcode MAIN", "main"), vec![10..14]);
+        assert_eq!(match_ranges("main", ""), Vec::<Range<usize>>::new());
+    }
+}
@@ -0,0 +1,276 @@
+//! Environment checks for `pretty-git-ui --doctor` and the diagnostics screen.
+//!
+//! They cover git and its configuration, the terminal, and the optional tools some features
+//! run. The report is plain text so it can be pasted into a bug report.
+
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::backend;
+use crate::config::Config;
+use crate::git::GitOperations;
+use crate::repo::RepoContext;
+use crate::theme::ColorDepth;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Ok,
+    /// Works, but some feature will not
+    Warning,
+    /// The app cannot work properly
+    Error,
+}
+
+impl Level {
+    pub const fn mark(self) -> &'static str {
+        match self {
+            Self::Ok => "✓",
+            Self::Warning => "!",
+            Self::Error => "✗",
+        }
+    }
+}
+
+/// One line of the report, e.g. `✓ git: git version 2.43.0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub level: Level,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, level: Level, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            level,
+            detail: detail.into(),
+        }
+    }
+
+    /// `Ok` with what was found, otherwise `level` with `missing`
+    fn found(name: &'static str, found: Option<String>, level: Level, missing: String) -> Self {
+        found.map_or_else(
+            || Self::new(name, level, missing),
+            |detail| Self::new(name, Level::Ok, detail),
+        )
+    }
+}
+
+/// Runs every check for `repo`
+pub fn run(repo: &RepoContext) -> Vec<Check> {
+    let mut checks = vec![Check::new(
+        "pretty-git-ui",
+        Level::Ok,
+        format!(
+            "{} (git backend: {})",
+            env!("CARGO_PKG_VERSION"),
+            backend::active().name()
+        ),
+    )];
+    checks.push(match GitOperations::get_git_version(repo) {
+        Ok(version) => Check::new("git", Level::Ok, version),
+        Err(e) => Check::new("git", Level::Error, e.to_string()),
+    });
+    checks.push(Check::found(
+        "git on PATH",
+        find_in_path("git").map(|path| path.display().to_string()),
+        Level::Error,
+        "not found".to_string(),
+    ));
+    checks.push(Check::found(
+        "repository",
+        GitOperations::get_repo_root(repo).map(|root| root.display().to_string()),
+        Level::Warning,
+        format!("{} is not inside a repository", repo.dir().display()),
+    ));
+    for key in ["user.name", "user.email"] {
+        checks.push(Check::found(
+            key,
+            GitOperations::get_config(repo, key),
+            Level::Warning,
+            "not set; git commit will fail".to_string(),
+        ));
+    }
+    checks.push(config_check());
+    checks.extend(terminal_checks(|name| std::env::var(name).ok()));
+    checks.push(Check::found(
+        "git-lfs",
+        tool_version(repo.git().args(["lfs", "version"])),
+        Level::Ok,
+        "not installed".to_string(),
+    ));
+    checks.push(signing_check(repo));
+    checks
+}
+
+/// The report as printed by `--doctor` and copied from the diagnostics screen
+pub fn format_report(checks: &[Check]) -> String {
+    let mut report = String::new();
+    for check in checks {
+        let _ = writeln!(
+            report,
+            "{} {}: {}",
+            check.level.mark(),
+            check.name,
+            check.detail
+        );
+    }
+    report
+}
+
+fn config_check() -> Check {
+    let path = Config::path().map_or_else(
+        || "no config directory".to_string(),
+        |path| path.display().to_string(),
+    );
+    match Config::load() {
+        Ok(_) => Check::new("config", Level::Ok, path),
+        Err(e) => Check::new("config", Level::Error, e),
+    }
+}
+
+/// `TERM`, the color depth themes are fitted to and a UTF-8 locale for the box drawing and
+/// status marks. `env` looks up an environment variable.
+pub fn terminal_checks(env: impl Fn(&str) -> Option<String>) -> Vec<Check> {
+    let term = env("TERM").filter(|term| !term.is_empty());
+    let depth = ColorDepth::detect(&env);
+    // The first of these that is set decides the character encoding
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| env(name).filter(|value| !value.is_empty()));
+    vec![
+        match term {
+            Some(term) if term != "dumb" => Check::new("terminal", Level::Ok, term),
+            _ => Check::new("terminal", Level::Warning, "TERM is not set or dumb"),
+        },
+        match depth {
+            ColorDepth::TrueColor | ColorDepth::Ansi256 => {
+                Check::new("colors", Level::Ok, depth.name())
+            },
+            ColorDepth::Ansi16 => Check::new(
+                "colors",
+                Level::Warning,
+                "16 colors; RGB theme colors are approximated",
+            ),
+        },
+        match locale {
+            Some(locale) if is_utf8(&locale) => Check::new("unicode", Level::Ok, locale),
+            Some(locale) => Check::new(
+                "unicode",
+                Level::Warning,
+                format!("{locale} is not a UTF-8 locale"),
+            ),
+            None => Check::new("unicode", Level::Warning, "no locale set"),
+        },
+    ]
+}
+
+fn is_utf8(locale: &str) -> bool {
+    let locale = locale.to_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
+
+/// The program commits are signed with: `gpg.program`, or `ssh-keygen` for
+/// `gpg.format = ssh`. Only a problem when `commit.gpgsign` is on.
+fn signing_check(repo: &RepoContext) -> Check {
+    let ssh = GitOperations::get_config(repo, "gpg.format").as_deref() == Some("ssh");
+    let program = if ssh {
+        GitOperations::get_config(repo, "gpg.ssh.program")
+            .unwrap_or_else(|| "ssh-keygen".to_string())
+    } else {
+        GitOperations::get_config(repo, "gpg.program").unwrap_or_else(|| "gpg".to_string())
+    };
+    let found = if ssh {
+        find_in_path(&program).map(|path| path.display().to_string())
+    } else {
+        tool_version(Command::new(&program).arg("--version"))
+    };
+    let signing = GitOperations::is_commit_signing_enabled(repo);
+    match found {
+        Some(detail) => Check::new("signing", Level::Ok, format!("{program}: {detail}")),
+        None if signing => Check::new(
+            "signing",
+            Level::Error,
+            format!("{program} not found but commit.gpgsign is on"),
+        ),
+        None => Check::new(
+            "signing",
+            Level::Ok,
+            format!("{program} not found; only needed for signed commits"),
+        ),
+    }
+}
+
+/// First line of what a `--version` style command prints, `None` when it fails
+fn tool_version(command: &mut Command) -> Option<String> {
+    let output = command
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(stdout.lines().next().unwrap_or_default().trim().to_string())
+}
+
+/// Where `program` would be run from, searching `PATH` like a shell
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .flat_map(|dir| {
+            let plain = dir.join(program);
+            let exe = dir.join(format!("{program}.exe"));
+            [plain, exe]
+        })
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_string())
+        }
+    }
+
+    #[test]
+    fn test_terminal_checks() {
+        let checks = terminal_checks(env(&[
+            ("TERM", "xterm-256color"),
+            ("COLORTERM", "truecolor"),
+            ("LANG", "ja_JP.UTF-8"),
+        ]));
+        assert!(checks.iter().all(|check| check.level == Level::Ok));
+
+        let checks = terminal_checks(env(&[
+            ("TERM", "dumb"),
+            ("LC_ALL", "C"),
+            ("LANG", "en_US.UTF-8"),
+        ]));
+        assert_eq!(
+            checks.iter().map(|check| check.level).collect::<Vec<_>>(),
+            vec![Level::Warning; 3]
+        );
+        assert_eq!(checks[2].detail, "C is not a UTF-8 locale");
+    }
+
+    #[test]
+    fn test_format_report() {
+        let checks = [
+            Check::new("git", Level::Ok, "git version 2.43.0"),
+            Check::new(
+                "user.email",
+                Level::Warning,
+                "not set; git commit will fail",
+            ),
+        ];
+        assert_eq!(
+            format_report(&checks),
+            "✓ git: git version 2.43.0\n! user.email: not set; git commit will fail\n"
+        );
+    }
+}
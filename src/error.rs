@@ -0,0 +1,127 @@
+//! The error type of git operations.
+//!
+//! Every `GitOperations` call and `GitBackend` query fails with a `GitError`. Its `Display`
+//! text is what the status line shows; the variants let callers tell a repository that is
+//! gone (`is_fatal`) from a single command that failed.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::Output;
+
+#[derive(Debug)]
+pub enum GitError {
+    /// The directory is not inside a git repository, or the repository was removed
+    NotARepo(PathBuf),
+    /// git ran and exited with an error
+    CommandFailed {
+        /// The git subcommand, e.g. `stash pop`
+        command: String,
+        /// Exit code; `None` when git was killed by a signal or libgit2 gave no code
+        code: Option<i32>,
+        stderr: String,
+    },
+    /// git could not be started, or a file could not be read or written
+    Io {
+        /// What was being done, e.g. `write .gitignore`
+        action: String,
+        source: io::Error,
+    },
+    /// Output of git, or a file it wrote, is not in the expected format
+    Parse(String),
+    /// gpg or ssh-keygen could not sign the commit; the most useful line of their output
+    Signing(String),
+    /// A hook rejected the commit; `output` is what it printed
+    Hook { hook: String, output: String },
+    /// Refused before running git, e.g. a line selection with no changed lines
+    Invalid(String),
+}
+
+impl GitError {
+    /// `CommandFailed` for a git command that exited with an error
+    pub fn command(command: impl Into<String>, output: &Output) -> Self {
+        Self::CommandFailed {
+            command: command.into(),
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }
+    }
+
+    pub fn io(action: impl Into<String>, source: io::Error) -> Self {
+        Self::Io {
+            action: action.into(),
+            source,
+        }
+    }
+
+    /// Whether the repository itself is unusable, so other operations will fail as well.
+    /// Everything else only concerns the operation that failed.
+    pub const fn is_fatal(&self) -> bool {
+        matches!(self, Self::NotARepo(_))
+    }
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotARepo(path) => write!(f, "Not a git repository: {}", path.display()),
+            Self::CommandFailed {
+                command, stderr, ..
+            } => write!(f, "Git {command} failed: {stderr}"),
+            Self::Io { action, source } => write!(f, "Failed to {action}: {source}"),
+            Self::Parse(message) | Self::Invalid(message) => f.write_str(message),
+            Self::Signing(reason) => write!(f, "Commit signing failed: {reason}"),
+            Self::Hook { hook, output } => {
+                let last_line = output.lines().last().unwrap_or("");
+                write!(f, "Commit rejected by the {hook} hook: {last_line}")
+            },
+        }
+    }
+}
+
+impl std::error::Error for GitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_matches_status_line_messages() {
+        let failed = GitError::CommandFailed {
+            command: "stash pop".to_string(),
+            code: Some(1),
+            stderr: "error: conflict".to_string(),
+        };
+        assert_eq!(failed.to_string(), "Git stash pop failed: error: conflict");
+
+        let io = GitError::io(
+            "write .gitignore",
+            io::Error::new(io::ErrorKind::PermissionDenied, "denied"),
+        );
+        assert_eq!(io.to_string(), "Failed to write .gitignore: denied");
+        assert!(std::error::Error::source(&io).is_some());
+
+        let hook = GitError::Hook {
+            hook: "pre-commit".to_string(),
+            output: "checking\nlint failed".to_string(),
+        };
+        assert_eq!(
+            hook.to_string(),
+            "Commit rejected by the pre-commit hook: lint failed"
+        );
+    }
+
+    #[test]
+    fn test_only_a_missing_repository_is_fatal() {
+        assert!(GitError::NotARepo(PathBuf::from("/tmp")).is_fatal());
+        assert!(!GitError::Parse("bad output".to_string()).is_fatal());
+        assert!(!GitError::Invalid("No lines selected".to_string()).is_fatal());
+    }
+}
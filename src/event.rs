@@ -0,0 +1,279 @@
+//! Input events of the app, independent of the terminal library.
+//!
+//! `App::handle_event` takes these, so the app can be embedded in another TUI or driven from
+//! tests. Events from crossterm convert with `AppEvent::from_crossterm`.
+//! `FrameScheduler` decides when an event loop draws between them.
+
+use std::ops::{BitOr, BitOrAssign};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    Char(char),
+    Enter,
+    Esc,
+    Tab,
+    BackTab,
+    Backspace,
+    Delete,
+    Insert,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+}
+
+/// Shift, Ctrl and Alt held with a key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct KeyModifiers(u8);
+
+impl KeyModifiers {
+    pub const NONE: Self = Self(0);
+    pub const SHIFT: Self = Self(1);
+    pub const CONTROL: Self = Self(1 << 1);
+    pub const ALT: Self = Self(1 << 2);
+
+    /// Whether every modifier of `other` is held
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for KeyModifiers {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for KeyModifiers {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyEvent {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// The key for a crossterm key; `None` for keys the app has no use for (media keys, ...)
+    pub fn from_crossterm(key: crossterm::event::KeyEvent) -> Option<Self> {
+        use crossterm::event::{KeyCode as Code, KeyModifiers as Modifiers};
+
+        let code = match key.code {
+            Code::Char(c) => KeyCode::Char(c),
+            Code::Enter => KeyCode::Enter,
+            Code::Esc => KeyCode::Esc,
+            Code::Tab => KeyCode::Tab,
+            Code::BackTab => KeyCode::BackTab,
+            Code::Backspace => KeyCode::Backspace,
+            Code::Delete => KeyCode::Delete,
+            Code::Insert => KeyCode::Insert,
+            Code::Up => KeyCode::Up,
+            Code::Down => KeyCode::Down,
+            Code::Left => KeyCode::Left,
+            Code::Right => KeyCode::Right,
+            Code::Home => KeyCode::Home,
+            Code::End => KeyCode::End,
+            Code::PageUp => KeyCode::PageUp,
+            Code::PageDown => KeyCode::PageDown,
+            Code::F(n) => KeyCode::F(n),
+            _ => return None,
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for (from, to) in [
+            (Modifiers::SHIFT, KeyModifiers::SHIFT),
+            (Modifiers::CONTROL, KeyModifiers::CONTROL),
+            (Modifiers::ALT, KeyModifiers::ALT),
+        ] {
+            if key.modifiers.contains(from) {
+                modifiers |= to;
+            }
+        }
+        Some(Self::new(code, modifiers))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseKind {
+    /// Left button pressed
+    Click,
+    ScrollDown,
+    ScrollUp,
+}
+
+/// A mouse event at a terminal cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseEvent {
+    pub kind: MouseKind,
+    pub column: u16,
+    pub row: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppEvent {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    /// Text pasted in one piece (bracketed paste)
+    Paste(String),
+    /// The terminal was resized and drawn again at the new size
+    Resize,
+    /// Time passed; applies results of background tasks
+    Tick,
+}
+
+impl AppEvent {
+    /// The event for a crossterm event; `None` for those the app does not use, such as
+    /// focus changes, key releases or right clicks
+    pub fn from_crossterm(event: crossterm::event::Event) -> Option<Self> {
+        use crossterm::event::{Event, KeyEventKind, MouseButton, MouseEventKind};
+
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                KeyEvent::from_crossterm(key).map(Self::Key)
+            },
+            Event::Mouse(mouse) => {
+                let kind = match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) => MouseKind::Click,
+                    MouseEventKind::ScrollDown => MouseKind::ScrollDown,
+                    MouseEventKind::ScrollUp => MouseKind::ScrollUp,
+                    _ => return None,
+                };
+                Some(Self::Mouse(MouseEvent {
+                    kind,
+                    column: mouse.column,
+                    row: mouse.row,
+                }))
+            },
+            Event::Paste(text) => Some(Self::Paste(text)),
+            Event::Resize(..) => Some(Self::Resize),
+            _ => None,
+        }
+    }
+}
+
+/// When the event loop draws: only after something changed, and while input keeps arriving
+/// (key repeat, a burst of wheel events) at most once per frame
+#[derive(Debug)]
+pub struct FrameScheduler {
+    frame: Duration,
+    needs_draw: bool,
+    frame_end: Option<Instant>,
+}
+
+impl FrameScheduler {
+    /// A scheduler drawing at most once per `frame`; the first frame is drawn right away
+    pub const fn new(frame: Duration) -> Self {
+        Self {
+            frame,
+            needs_draw: true,
+            frame_end: None,
+        }
+    }
+
+    /// Starts handling the input that arrived at `now`; input keeps being read for one frame
+    pub fn start_frame(&mut self, now: Instant) {
+        self.frame_end = Some(now + self.frame);
+    }
+
+    /// Whether the frame started last is over at `now`, so waiting input has to wait for the
+    /// next draw
+    pub fn frame_over(&self, now: Instant) -> bool {
+        self.frame_end.map_or(true, |end| now >= end)
+    }
+
+    /// Marks the screen out of date, after an event or a tick the app handled
+    pub fn invalidate(&mut self) {
+        self.needs_draw = true;
+    }
+
+    /// Whether to draw now. Clears the mark, so each change is drawn once
+    pub fn take_draw(&mut self) -> bool {
+        std::mem::take(&mut self.needs_draw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event as ct;
+
+    #[test]
+    fn test_from_crossterm() {
+        let key = ct::KeyEvent::new(
+            ct::KeyCode::Char('w'),
+            ct::KeyModifiers::CONTROL | ct::KeyModifiers::SHIFT,
+        );
+        let event = AppEvent::from_crossterm(ct::Event::Key(key)).unwrap();
+        let AppEvent::Key(key) = event else {
+            panic!("not a key: {event:?}");
+        };
+        assert_eq!(key.code, KeyCode::Char('w'));
+        assert!(key
+            .modifiers
+            .contains(KeyModifiers::CONTROL | KeyModifiers::SHIFT));
+        assert!(!key.modifiers.contains(KeyModifiers::ALT));
+
+        let scroll = ct::MouseEvent {
+            kind: ct::MouseEventKind::ScrollUp,
+            column: 3,
+            row: 4,
+            modifiers: ct::KeyModifiers::NONE,
+        };
+        assert_eq!(
+            AppEvent::from_crossterm(ct::Event::Mouse(scroll)),
+            Some(AppEvent::Mouse(MouseEvent {
+                kind: MouseKind::ScrollUp,
+                column: 3,
+                row: 4,
+            }))
+        );
+        assert_eq!(AppEvent::from_crossterm(ct::Event::FocusGained), None);
+        assert_eq!(
+            AppEvent::from_crossterm(ct::Event::Resize(80, 24)),
+            Some(AppEvent::Resize)
+        );
+    }
+
+    #[test]
+    fn test_frame_scheduler_draws_once_per_change() {
+        let mut frames = FrameScheduler::new(Duration::from_millis(16));
+        assert!(frames.take_draw());
+        assert!(!frames.take_draw());
+
+        // Events handled in one batch are drawn together
+        frames.invalidate();
+        frames.invalidate();
+        assert!(frames.take_draw());
+        assert!(!frames.take_draw());
+    }
+
+    #[test]
+    fn test_frame_scheduler_reads_input_for_one_frame() {
+        let mut frames = FrameScheduler::new(Duration::from_millis(16));
+        let start = Instant::now();
+        assert!(frames.frame_over(start));
+
+        frames.start_frame(start);
+        assert!(!frames.frame_over(start));
+        assert!(!frames.frame_over(start + Duration::from_millis(15)));
+        assert!(frames.frame_over(start + Duration::from_millis(16)));
+
+        let next = start + Duration::from_millis(40);
+        frames.start_frame(next);
+        assert!(!frames.frame_over(next + Duration::from_millis(10)));
+    }
+}
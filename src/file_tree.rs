@@ -0,0 +1,167 @@
+//! Changed files as a directory tree, for the tree view of the file list.
+//!
+//! `DirNode::build` groups the files listed under one section by directory and `flatten`
+//! turns the tree back into list rows, leaving out what is inside collapsed directories.
+
+use std::collections::BTreeMap;
+
+use crate::app::FileRow;
+use crate::git::{FileEntry, Section};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DirNode {
+    /// Subdirectories by name
+    pub dirs: BTreeMap<String, Self>,
+    /// Indexes into `App::files` of the files directly inside, in list order
+    pub files: Vec<usize>,
+    /// Files in this directory and below it
+    pub count: usize,
+}
+
+impl DirNode {
+    /// The tree of `indices` into `files`, whose order is kept within each directory
+    pub fn build(files: &[FileEntry], indices: &[usize]) -> Self {
+        let mut root = Self::default();
+        for &index in indices {
+            let mut node = &mut root;
+            node.count += 1;
+            for dir in parent_dirs(&files[index].path) {
+                node = node.dirs.entry(dir.to_string()).or_default();
+                node.count += 1;
+            }
+            node.files.push(index);
+        }
+        root
+    }
+
+    /// Rows for what is inside this directory, `prefix` being its path: subdirectories
+    /// first, each followed by its own rows unless `collapsed` says it is folded, then files
+    pub fn flatten(
+        &self,
+        section: Section,
+        prefix: &str,
+        collapsed: &impl Fn(&str) -> bool,
+        rows: &mut Vec<FileRow>,
+    ) {
+        let depth = prefix.matches('/').count();
+        for (name, dir) in &self.dirs {
+            let path = format!("{prefix}{name}/");
+            let folded = collapsed(&path);
+            rows.push(FileRow::Dir {
+                section,
+                index: dir.first_file(),
+                depth,
+                count: dir.count,
+                collapsed: folded,
+            });
+            if !folded {
+                dir.flatten(section, &path, collapsed, rows);
+            }
+        }
+        rows.extend(
+            self.files
+                .iter()
+                .map(|&index| FileRow::File { section, index }),
+        );
+    }
+
+    /// Any file below this directory; every directory in the tree has one
+    fn first_file(&self) -> usize {
+        self.files
+            .first()
+            .copied()
+            .unwrap_or_else(|| self.dirs.values().next().map_or(0, Self::first_file))
+    }
+}
+
+/// The directories `path` is in, outermost first. An untracked directory (`dir/`) is in
+/// its parent like a file.
+pub fn parent_dirs(path: &str) -> impl Iterator<Item = &str> {
+    path.trim_end_matches('/')
+        .rsplit_once('/')
+        .map_or("", |(parent, _)| parent)
+        .split('/')
+        .filter(|dir| !dir.is_empty())
+}
+
+/// The directory `depth` levels down that `path` is in, with a trailing slash:
+/// `src/ui/` for depth 1 of `src/ui/list.rs`
+pub fn ancestor(path: &str, depth: usize) -> String {
+    parent_dirs(path)
+        .take(depth + 1)
+        .fold(String::new(), |mut dir, name| {
+            dir.push_str(name);
+            dir.push('/');
+            dir
+        })
+}
+
+/// What a tree row shows of `path`: `list.rs`, or `logs/` for an untracked directory
+pub fn file_name(path: &str) -> &str {
+    path.trim_end_matches('/')
+        .rsplit_once('/')
+        .map_or(path, |(parent, _)| &path[parent.len() + 1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_helpers() {
+        assert_eq!(
+            parent_dirs("src/ui/list.rs").collect::<Vec<_>>(),
+            ["src", "ui"]
+        );
+        assert_eq!(parent_dirs("logs/").count(), 0);
+        assert_eq!(parent_dirs("README.md").count(), 0);
+        assert_eq!(ancestor("src/ui/list.rs", 0), "src/");
+        assert_eq!(ancestor("src/ui/list.rs", 1), "src/ui/");
+        assert_eq!(file_name("src/ui/list.rs"), "list.rs");
+        assert_eq!(file_name("src/logs/"), "logs/");
+        assert_eq!(file_name("README.md"), "README.md");
+    }
+
+    #[test]
+    fn test_flatten_skips_collapsed_directories() {
+        let files = [
+            FileEntry::new("README.md", ' ', 'M'),
+            FileEntry::new("src/main.rs", ' ', 'M'),
+            FileEntry::new("src/ui/list.rs", ' ', 'M'),
+            FileEntry::new("src/ui/mod.rs", ' ', 'M'),
+        ];
+        let tree = DirNode::build(&files, &[0, 1, 2, 3]);
+        assert_eq!(tree.count, 4);
+        assert_eq!(tree.dirs["src"].count, 3);
+
+        let section = Section::Unstaged;
+        let mut rows = Vec::new();
+        tree.flatten(section, "", &|_| false, &mut rows);
+        let dir = |index, depth, count, collapsed| FileRow::Dir {
+            section,
+            index,
+            depth,
+            count,
+            collapsed,
+        };
+        let file = |index| FileRow::File { section, index };
+        assert_eq!(
+            rows,
+            vec![
+                dir(1, 0, 3, false),
+                dir(2, 1, 2, false),
+                file(2),
+                file(3),
+                file(1),
+                file(0),
+            ]
+        );
+
+        rows.clear();
+        tree.flatten(section, "", &|path| path == "src/ui/", &mut rows);
+        assert_eq!(
+            rows,
+            vec![dir(1, 0, 3, false), dir(2, 1, 2, true), file(1), file(0)]
+        );
+    }
+}
@@ -1,256 +1,3806 @@
-use std::process::Command;
+use crate::askpass;
+use crate::backend;
+use crate::binary;
+use crate::command_log::{self, CommandRecord, LoggedCommand};
+use crate::diff;
+use crate::error::GitError;
+use crate::patch::FilePatch;
+use crate::repo::RepoContext;
+use crate::strings::{Message, Msg};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Instant, SystemTime};
 
 #[derive(Debug)]
 pub struct GitOperations;
 
+/// Section of the file list a change belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Section {
+    /// Unmerged paths; listed first because they block committing
+    Conflicted,
+    Staged,
+    Unstaged,
+    Untracked,
+}
+
+impl Section {
+    pub const ALL: [Self; 4] = [
+        Self::Conflicted,
+        Self::Staged,
+        Self::Unstaged,
+        Self::Untracked,
+    ];
+}
+
+/// Which side of a file's changes the preview shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DiffView {
+    /// Working tree vs index (`git diff`)
+    #[default]
+    Unstaged,
+    /// Index vs HEAD (`git diff --cached`)
+    Staged,
+}
+
+impl DiffView {
+    #[must_use]
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::Unstaged => Self::Staged,
+            Self::Staged => Self::Unstaged,
+        }
+    }
+
+    /// The copies of a file compared: before and after the change
+    pub const fn versions(self) -> (FileVersion, FileVersion) {
+        match self {
+            Self::Unstaged => (FileVersion::Index, FileVersion::Worktree),
+            Self::Staged => (FileVersion::Head, FileVersion::Index),
+        }
+    }
+}
+
+/// How file diffs are computed for the preview: the `[diff]` table, changed at runtime
+/// with `{`/`}`, `W` and `E`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiffOptions {
+    /// Lines of context around each change (`-U<n>`). Unset means git's default, which
+    /// `diff.context` can change.
+    pub context: Option<u32>,
+    /// Ignore changes in whitespace (`-w`)
+    pub ignore_whitespace: bool,
+    /// Ignore added or removed blank lines (`--ignore-blank-lines`)
+    pub ignore_blank_lines: bool,
+}
+
+impl DiffOptions {
+    /// git's context size when `diff.context` is not set
+    pub const DEFAULT_CONTEXT: u32 = 3;
+    pub const MAX_CONTEXT: u32 = 99;
+
+    /// `context` changed by `step` lines, kept within `0..=MAX_CONTEXT`
+    #[must_use]
+    pub fn with_context_step(self, step: i32) -> Self {
+        let current = self.context.unwrap_or(Self::DEFAULT_CONTEXT);
+        let context = current.saturating_add_signed(step).min(Self::MAX_CONTEXT);
+        Self {
+            context: Some(context),
+            ..self
+        }
+    }
+
+    /// The `git diff` arguments, e.g. `["-U1", "-w"]`; empty for the defaults
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(context) = self.context {
+            args.push(format!("-U{context}"));
+        }
+        if self.ignore_whitespace {
+            args.push("-w".to_string());
+        }
+        if self.ignore_blank_lines {
+            args.push("--ignore-blank-lines".to_string());
+        }
+        args
+    }
+}
+
+/// A changed path with its index (X) and worktree (Y) status codes from `git status`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub path: String,
+    pub index_status: char,
+    pub worktree_status: char,
+    /// Source path of a rename or copy (`R  old -> new`)
+    pub orig_path: Option<String>,
+}
+
+impl FileEntry {
+    pub fn new(path: impl Into<String>, index_status: char, worktree_status: char) -> Self {
+        Self {
+            path: path.into(),
+            index_status,
+            worktree_status,
+            orig_path: None,
+        }
+    }
+
+    /// Parses a `git status --porcelain` line (`XY path`, or `XY old -> new` for renames
+    /// and copies)
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut chars = line.chars();
+        let index_status = chars.next()?;
+        let worktree_status = chars.next()?;
+        let path = chars.as_str().trim();
+        if path.is_empty() {
+            return None;
+        }
+        let is_rename = matches!(index_status, 'R' | 'C') || matches!(worktree_status, 'R' | 'C');
+        match path.split_once(" -> ").filter(|_| is_rename) {
+            Some((orig, path)) => Some(Self {
+                orig_path: Some(orig.to_string()),
+                ..Self::new(path, index_status, worktree_status)
+            }),
+            None => Some(Self::new(path, index_status, worktree_status)),
+        }
+    }
+
+    /// Parses the NUL-separated records of `git status --porcelain=v2 -z`. Paths come
+    /// verbatim instead of C-quoted, so names with spaces, quotes or non-ASCII characters
+    /// need no unescaping.
+    pub fn parse_porcelain_v2(output: &[u8]) -> Vec<Self> {
+        let mut records = output.split(|&b| b == 0).filter(|r| !r.is_empty());
+        let mut entries = Vec::new();
+        while let Some(record) = records.next() {
+            // Ordinary, renamed/copied and unmerged records have 8, 9 and 10 fields before the path
+            let (fields, kind) = match record.first() {
+                Some(b'1') => (9, b'1'),
+                Some(b'2') => (10, b'2'),
+                Some(b'u') => (11, b'u'),
+                Some(b'?') => (2, b'?'),
+                _ => continue,
+            };
+            let mut parts = record.splitn(fields, |&b| b == b' ');
+            let (xy, path) = if kind == b'?' {
+                (&b"??"[..], parts.nth(1))
+            } else {
+                (parts.nth(1).unwrap_or_default(), parts.last())
+            };
+            let (Some(path), [x, y]) = (path, xy) else {
+                continue;
+            };
+            let status = |c: u8| if c == b'.' { ' ' } else { char::from(c) };
+            let mut entry = Self::new(decode_path(path), status(*x), status(*y));
+            if kind == b'2' {
+                entry.orig_path = records.next().map(decode_path);
+            }
+            entries.push(entry);
+        }
+        entries
+    }
+
+    /// `old → new` for renames, otherwise just the path
+    pub fn display_path(&self) -> String {
+        self.orig_path.as_ref().map_or_else(
+            || self.path.clone(),
+            |orig| format!("{orig} → {}", self.path),
+        )
+    }
+
+    /// The path and, for renames, the original path: every path git has to be given to
+    /// act on the whole entry
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.path).chain(&self.orig_path)
+    }
+
+    pub const fn is_untracked(&self) -> bool {
+        self.index_status == '?'
+    }
+
+    /// Added with `git add -N`: tracked, but none of its content is staged yet
+    pub const fn is_intent_to_add(&self) -> bool {
+        self.index_status == ' ' && self.worktree_status == 'A'
+    }
+
+    /// Unmerged status codes as listed in `git status --help`
+    pub const fn is_conflicted(&self) -> bool {
+        matches!(
+            (self.index_status, self.worktree_status),
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D')
+        )
+    }
+
+    pub const fn is_staged(&self) -> bool {
+        !self.is_conflicted() && !matches!(self.index_status, ' ' | '?')
+    }
+
+    pub const fn is_unstaged(&self) -> bool {
+        !self.is_conflicted() && !matches!(self.worktree_status, ' ' | '?')
+    }
+
+    /// Whether the entry appears in the given section. A partially staged file
+    /// shows up in both the staged and the unstaged section.
+    pub const fn in_section(&self, section: Section) -> bool {
+        match section {
+            Section::Conflicted => self.is_conflicted(),
+            Section::Staged => self.is_staged(),
+            Section::Unstaged => self.is_unstaged(),
+            Section::Untracked => self.is_untracked(),
+        }
+    }
+
+    /// Two-letter porcelain status code, e.g. `"M "` or `"??"`
+    pub fn status_code(&self) -> String {
+        format!("{}{}", self.index_status, self.worktree_status)
+    }
+}
+
+fn decode_path(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// `git status --untracked-files`: how untracked files are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UntrackedFiles {
+    /// Leave them out, which is much faster on large working trees
+    No,
+    /// Untracked directories are listed as a whole
+    Normal,
+    /// Every untracked file, also inside untracked directories
+    All,
+}
+
+impl UntrackedFiles {
+    pub const fn flag(self) -> &'static str {
+        match self {
+            Self::No => "--untracked-files=no",
+            Self::Normal => "--untracked-files=normal",
+            Self::All => "--untracked-files=all",
+        }
+    }
+}
+
+/// What `git reset` does besides moving the current branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Keeps the index and the working tree, so the undone commits end up staged
+    Soft,
+    /// Resets the index but keeps the working tree
+    Mixed,
+    /// Resets the index and the working tree, discarding every uncommitted change
+    Hard,
+}
+
+impl ResetMode {
+    pub const fn flag(self) -> &'static str {
+        match self {
+            Self::Soft => "--soft",
+            Self::Mixed => "--mixed",
+            Self::Hard => "--hard",
+        }
+    }
+}
+
+/// Flags for `git stash push`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StashOptions {
+    /// `--keep-index`: staged changes are stashed but also stay staged
+    pub keep_index: bool,
+    /// `--include-untracked`: untracked files are stashed and removed as well
+    pub include_untracked: bool,
+}
+
+impl StashOptions {
+    fn args(self) -> Vec<&'static str> {
+        let mut args = Vec::new();
+        if self.keep_index {
+            args.push("--keep-index");
+        }
+        if self.include_untracked {
+            args.push("--include-untracked");
+        }
+        args
+    }
+}
+
+/// Flags for `git commit`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommitOptions<'a> {
+    /// `-S`, or else `--no-gpg-sign` so the per-commit toggle overrides `commit.gpgsign` in
+    /// both directions
+    pub sign: bool,
+    /// `--no-verify`: skips the pre-commit and commit-msg hooks
+    pub no_verify: bool,
+    /// Stages modified and deleted tracked files first, like `git commit -a`
+    pub all: bool,
+    /// Entries whose staged changes are left out of the commit and stay staged
+    pub exclude: &'a [FileEntry],
+    /// Author and committer of the commit instead of the configured identity
+    pub identity: Option<&'a Identity>,
+}
+
+/// What a status query looks at
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusOptions {
+    /// `None` leaves it to git (`status.showUntrackedFiles`)
+    pub untracked: Option<UntrackedFiles>,
+    /// Only report these paths (taken literally); empty means the whole working tree
+    pub paths: Vec<String>,
+}
+
+/// How many files have each kind of change. A partially staged file counts as both staged
+/// and modified.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusSummary {
+    pub staged: usize,
+    pub modified: usize,
+    pub untracked: usize,
+    pub conflicted: usize,
+}
+
+impl StatusSummary {
+    pub fn from_entries(files: &[FileEntry]) -> Self {
+        let count = |section| files.iter().filter(|f| f.in_section(section)).count();
+        Self {
+            staged: count(Section::Staged),
+            modified: count(Section::Unstaged),
+            untracked: count(Section::Untracked),
+            conflicted: count(Section::Conflicted),
+        }
+    }
+
+    pub const fn is_clean(&self) -> bool {
+        self.staged == 0 && self.modified == 0 && self.untracked == 0 && self.conflicted == 0
+    }
+}
+
+/// The upstream of the current branch and how far the branch has diverged from it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpstreamStatus {
+    /// Short name such as `origin/main`
+    pub name: String,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// The commit HEAD points at, for the status bar
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeadSummary {
+    /// Abbreviated hash
+    pub hash: String,
+    pub subject: String,
+    /// Commits reachable from HEAD, i.e. the length of the branch's history
+    pub commit_count: usize,
+}
+
+impl HeadSummary {
+    /// Parses the `hash<TAB>subject` line of `--format=%h%x09%s` and the output of
+    /// `git rev-list --count`
+    fn parse(line: &str, count: &str) -> Option<Self> {
+        let (hash, subject) = line.trim_end().split_once('\t')?;
+        Some(Self {
+            hash: hash.to_string(),
+            subject: subject.to_string(),
+            commit_count: count.trim().parse().ok()?,
+        })
+    }
+}
+
+/// A detached HEAD: the branch to go back to and what would be left behind by leaving
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DetachedHead {
+    /// The branch checked out last before HEAD was detached, found in the reflog
+    pub previous_branch: Option<String>,
+    /// Commits made on the detached HEAD that no branch contains
+    pub unreachable: usize,
+}
+
+/// Where a running `git bisect` stands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BisectStatus {
+    /// Started, but a good and a bad commit are not both marked yet
+    Waiting,
+    /// `current` is checked out for testing; `remaining` revisions are left after it,
+    /// roughly `steps` more marks
+    Testing {
+        current: String,
+        remaining: usize,
+        steps: usize,
+    },
+    /// Narrowed down to the first bad commit
+    Found { first_bad: String },
+}
+
+impl BisectStatus {
+    /// Builds the status from `git rev-list --bisect-vars` output
+    fn from_bisect_vars(vars: &str, current: String) -> Option<Self> {
+        let value = |name: &str| {
+            vars.lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+                .map(|value| value.trim_matches('\''))
+        };
+        let rev = value("bisect_rev")?;
+        let all: usize = value("bisect_all")?.parse().ok()?;
+        if all <= 1 {
+            return Some(Self::Found {
+                first_bad: short_hash(rev).to_string(),
+            });
+        }
+        Some(Self::Testing {
+            current,
+            remaining: value("bisect_nr")?.parse().ok()?,
+            steps: value("bisect_steps")?.parse().ok()?,
+        })
+    }
+
+    /// The commit to highlight in the log: the one being tested or the culprit
+    pub fn commit(&self) -> Option<&str> {
+        match self {
+            Self::Waiting => None,
+            Self::Testing { current, .. } => Some(current),
+            Self::Found { first_bad } => Some(first_bad),
+        }
+    }
+}
+
+fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(7)]
+}
+
+/// Which copy of a file the file viewer shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileVersion {
+    /// The last commit (`git show HEAD:path`)
+    Head,
+    /// What is staged (`git show :path`)
+    Index,
+    Worktree,
+}
+
+impl FileVersion {
+    pub const ALL: [Self; 3] = [Self::Head, Self::Index, Self::Worktree];
+
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Head => Self::Index,
+            Self::Index => Self::Worktree,
+            Self::Worktree => Self::Head,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Head => "HEAD",
+            Self::Index => "index",
+            Self::Worktree => "working tree",
+        }
+    }
+
+    /// Object name for `git cat-file`; the working tree has none
+    fn revision(self, path: &str) -> Option<String> {
+        match self {
+            Self::Head => Some(format!("HEAD:{path}")),
+            Self::Index => Some(format!(":{path}")),
+            Self::Worktree => None,
+        }
+    }
+}
+
+/// What the log search looks for, typed as `author:NAME`, `path:PATH` or plain text that is
+/// matched against commit messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogQuery {
+    /// `git log --grep`, case-insensitive and literal
+    Message(String),
+    /// `git log --author`
+    Author(String),
+    /// `git log -- <path>`
+    Path(String),
+}
+
+impl LogQuery {
+    /// `None` for an empty query, which clears the search
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let query = match input.split_once(':') {
+            Some(("author", name)) => Self::Author(name.trim().to_string()),
+            Some(("path", path)) => Self::Path(path.trim().to_string()),
+            _ => Self::Message(input.to_string()),
+        };
+        let (Self::Message(text) | Self::Author(text) | Self::Path(text)) = &query;
+        (!text.is_empty()).then_some(query)
+    }
+
+    fn args(&self) -> Vec<String> {
+        match self {
+            Self::Message(text) => vec![
+                "--regexp-ignore-case".to_string(),
+                "--fixed-strings".to_string(),
+                format!("--grep={text}"),
+            ],
+            Self::Author(name) => vec![
+                "--regexp-ignore-case".to_string(),
+                format!("--author={name}"),
+            ],
+            Self::Path(path) => vec!["--".to_string(), path.clone()],
+        }
+    }
+}
+
+/// How `merge_branch` brings another branch into the current one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// `--ff-only`: only moves the branch forward, never creates a merge commit
+    FastForward,
+    /// `--no-ff`: always records a merge commit
+    NoFastForward,
+    /// `--squash`: stages the combined changes to be committed as one commit
+    Squash,
+}
+
+impl MergeStrategy {
+    pub const ALL: [Self; 3] = [Self::FastForward, Self::NoFastForward, Self::Squash];
+
+    const fn flag(self) -> &'static str {
+        match self {
+            Self::FastForward => "--ff-only",
+            Self::NoFastForward => "--no-ff",
+            Self::Squash => "--squash",
+        }
+    }
+}
+
+/// How `git pull` combines the upstream commits with local ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PullStrategy {
+    /// `--rebase`: replays local commits on top of the upstream branch
+    Rebase,
+    /// `--no-rebase`: merges the upstream branch, with a merge commit if both moved
+    Merge,
+    /// `--ff-only`: only moves the branch forward, failing if it has diverged
+    FastForwardOnly,
+}
+
+impl PullStrategy {
+    pub const ALL: [Self; 3] = [Self::Rebase, Self::Merge, Self::FastForwardOnly];
+
+    const fn flag(self) -> &'static str {
+        match self {
+            Self::Rebase => "--rebase",
+            Self::Merge => "--no-rebase",
+            Self::FastForwardOnly => "--ff-only",
+        }
+    }
+
+    /// What plain `git pull` would do, from `pull.rebase` and then `pull.ff`
+    fn from_config(rebase: &str, ff: &str) -> Self {
+        match (rebase, ff) {
+            ("true" | "merges" | "interactive" | "i" | "m", _) => Self::Rebase,
+            ("", "only") => Self::FastForwardOnly,
+            _ => Self::Merge,
+        }
+    }
+}
+
+/// Housekeeping commands offered on the maintenance screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceTask {
+    /// `git gc`: packs loose objects and removes unreachable ones past their expiry
+    Gc,
+    /// `git prune`: removes every unreachable loose object right away
+    Prune,
+    /// `git maintenance run`: the tasks configured for `git maintenance`, gc by default
+    MaintenanceRun,
+}
+
+impl MaintenanceTask {
+    pub const ALL: [Self; 3] = [Self::Gc, Self::Prune, Self::MaintenanceRun];
+
+    pub const fn args(self) -> &'static [&'static str] {
+        match self {
+            Self::Gc => &["gc"],
+            Self::Prune => &["prune", "--progress", "--verbose"],
+            // Quiet by default when stderr is not a terminal
+            Self::MaintenanceRun => &["maintenance", "run", "--no-quiet"],
+        }
+    }
+
+    /// The git subcommand, e.g. `maintenance run`
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Gc => "gc",
+            Self::Prune => "prune",
+            Self::MaintenanceRun => "maintenance run",
+        }
+    }
+}
+
+/// A `.gitignore` offered when initializing a repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GitignoreTemplate {
+    pub name: &'static str,
+    pub content: &'static str,
+}
+
+/// The `.gitignore` templates offered by `git init` with options
+pub const GITIGNORE_TEMPLATES: &[GitignoreTemplate] = &[
+    GitignoreTemplate {
+        name: "Rust",
+        content: "/target\n",
+    },
+    GitignoreTemplate {
+        name: "Node",
+        content: "node_modules/\ndist/\n.env\nnpm-debug.log*\nyarn-debug.log*\nyarn-error.log*\n",
+    },
+    GitignoreTemplate {
+        name: "Python",
+        content:
+            "__pycache__/\n*.py[cod]\n.venv/\nvenv/\nbuild/\ndist/\n*.egg-info/\n.pytest_cache/\n",
+    },
+    GitignoreTemplate {
+        name: "Go",
+        content: "*.exe\n*.test\n*.out\n/vendor/\n",
+    },
+    GitignoreTemplate {
+        name: "Java",
+        content: "*.class\n*.jar\n*.war\ntarget/\nbuild/\n.gradle/\n",
+    },
+    GitignoreTemplate {
+        name: "macOS / Windows / editors",
+        content: ".DS_Store\nThumbs.db\nDesktop.ini\n.idea/\n.vscode/\n*.swp\n*~\n",
+    },
+];
+
+/// What `GitOperations::init_with_options` sets up besides the empty repository
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitOptions {
+    /// `--initial-branch`; empty leaves it to git (`init.defaultBranch`)
+    pub branch: String,
+    /// Written as `.gitignore` unless the directory already has one
+    pub gitignore: Option<&'static GitignoreTemplate>,
+    /// Commits the `.gitignore`, or nothing when there is none, as `Initial commit`
+    pub initial_commit: bool,
+}
+
+/// Size of the object store, from `git count-objects -v`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoStats {
+    pub loose_objects: u64,
+    /// In bytes, like the other sizes
+    pub loose_size: u64,
+    pub packed_objects: u64,
+    pub packs: u64,
+    pub pack_size: u64,
+    /// Files in the object directory that are neither objects nor packs
+    pub garbage: u64,
+    /// When objects were last repacked, which `git gc` does; `None` if never
+    pub last_gc: Option<SystemTime>,
+}
+
+impl RepoStats {
+    /// Parses the `key: value` lines of `git count-objects -v`, where sizes are in KiB
+    fn parse(output: &str) -> Self {
+        let mut stats = Self::default();
+        for (key, value) in output.lines().filter_map(|line| line.split_once(": ")) {
+            let Ok(value) = value.trim().parse::<u64>() else {
+                continue;
+            };
+            match key {
+                "count" => stats.loose_objects = value,
+                "size" => stats.loose_size = value * 1024,
+                "in-pack" => stats.packed_objects = value,
+                "packs" => stats.packs = value,
+                "size-pack" => stats.pack_size = value * 1024,
+                "garbage" => stats.garbage = value,
+                _ => {},
+            }
+        }
+        stats
+    }
+
+    pub const fn total_size(&self) -> u64 {
+        self.loose_size + self.pack_size
+    }
+}
+
+/// A name and email commits are made as. Profiles come from `[[identities]]` in the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Identity {
+    /// What the picker calls the profile, e.g. `work`
+    #[serde(default)]
+    pub label: String,
+    pub name: String,
+    pub email: String,
+}
+
+impl std::fmt::Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)
+    }
+}
+
+/// A level of git configuration, as `git config --show-scope` names it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigScope {
+    /// `/etc/gitconfig`
+    System,
+    /// `~/.gitconfig` or `~/.config/git/config`
+    Global,
+    /// `.git/config`
+    Local,
+    /// `.git/config.worktree`, with `extensions.worktreeConfig`
+    Worktree,
+    /// `git -c` and `GIT_CONFIG_*`, which cannot be written
+    Command,
+}
+
+impl ConfigScope {
+    /// The scopes the config editor writes to, in the order Tab goes through them
+    pub const EDITABLE: [Self; 3] = [Self::Local, Self::Global, Self::System];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::Global => "global",
+            Self::Local => "local",
+            Self::Worktree => "worktree",
+            Self::Command => "command",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [
+            Self::System,
+            Self::Global,
+            Self::Local,
+            Self::Worktree,
+            Self::Command,
+        ]
+        .into_iter()
+        .find(|scope| scope.as_str() == value)
+    }
+
+    /// The next of `EDITABLE`; the scopes that cannot be chosen go back to `Local`
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Local => Self::Global,
+            Self::Global => Self::System,
+            Self::System | Self::Worktree | Self::Command => Self::Local,
+        }
+    }
+}
+
+/// One value from `git config --list`, with the file (or command line) it comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    /// As git lists it: section and name lowercased, e.g. `user.name`
+    pub key: String,
+    /// Empty for a key written without `=`, which git reads as true
+    pub value: String,
+    pub scope: ConfigScope,
+    /// e.g. `file:.git/config` or `command line:`
+    pub origin: String,
+}
+
+/// What `set_config` did to the key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigChange {
+    Set,
+    Unset,
+    /// An empty value for a key that was not set in the scope
+    NotSet,
+}
+
+/// Which version of a conflicted file to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+}
+
+impl ConflictSide {
+    const fn flag(self) -> &'static str {
+        match self {
+            Self::Ours => "--ours",
+            Self::Theirs => "--theirs",
+        }
+    }
+}
+
+/// One `<<<<<<< ... >>>>>>>` region of a conflicted file. `base` is only present with
+/// `merge.conflictStyle = diff3` (or `zdiff3`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConflictHunk {
+    /// Line number of the `<<<<<<<` marker (1-based)
+    pub line: usize,
+    pub ours: Vec<String>,
+    pub base: Option<Vec<String>>,
+    pub theirs: Vec<String>,
+}
+
+impl ConflictHunk {
+    /// Extracts the conflict regions from a file containing merge markers
+    pub fn parse_all(content: &str) -> Vec<Self> {
+        enum Part {
+            Outside,
+            Ours,
+            Base,
+            Theirs,
+        }
+
+        let mut hunks = Vec::new();
+        let mut current = Self::default();
+        let mut part = Part::Outside;
+        for (i, line) in content.lines().enumerate() {
+            match part {
+                Part::Outside if line.starts_with("<<<<<<<") => {
+                    current = Self {
+                        line: i + 1,
+                        ..Self::default()
+                    };
+                    part = Part::Ours;
+                },
+                Part::Outside => {},
+                Part::Ours | Part::Base if line.starts_with("=======") => part = Part::Theirs,
+                Part::Ours if line.starts_with("|||||||") => {
+                    current.base = Some(Vec::new());
+                    part = Part::Base;
+                },
+                Part::Theirs if line.starts_with(">>>>>>>") => {
+                    hunks.push(std::mem::take(&mut current));
+                    part = Part::Outside;
+                },
+                Part::Ours => current.ours.push(line.to_string()),
+                Part::Base => current
+                    .base
+                    .get_or_insert_with(Vec::new)
+                    .push(line.to_string()),
+                Part::Theirs => current.theirs.push(line.to_string()),
+            }
+        }
+        hunks
+    }
+}
+
+/// One entry of `git stash list`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StashEntry {
+    pub index: usize,
+    pub message: String,
+}
+
+impl StashEntry {
+    /// Parses a `stash@{N}<TAB>subject` line as produced by `--format=%gd%x09%gs`
+    fn parse(line: &str) -> Option<Self> {
+        let (reference, message) = line.split_once('\t')?;
+        let index = reference
+            .strip_prefix("stash@{")?
+            .strip_suffix('}')?
+            .parse()
+            .ok()?;
+        Some(Self {
+            index,
+            message: message.to_string(),
+        })
+    }
+
+    pub fn reference(&self) -> String {
+        stash_ref(self.index)
+    }
+}
+
+/// One HEAD movement from `git reflog`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReflogEntry {
+    /// Abbreviated hash HEAD pointed to after the movement
+    pub hash: String,
+    /// `HEAD@{N}`
+    pub selector: String,
+    /// What moved HEAD, e.g. `commit: Add feature` or `reset: moving to HEAD~1`
+    pub message: String,
+}
+
+impl ReflogEntry {
+    /// Parses a `hash<TAB>HEAD@{N}<TAB>subject` line as produced by `--format=%h%x09%gd%x09%gs`
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(3, '\t');
+        let hash = fields.next().filter(|hash| !hash.is_empty())?;
+        let selector = fields.next().filter(|selector| selector.ends_with('}'))?;
+        Some(Self {
+            hash: hash.to_string(),
+            selector: selector.to_string(),
+            message: fields.next().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+/// A local or remote-tracking branch from `git for-each-ref`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchEntry {
+    /// `main` for a local branch, `origin/main` for a remote-tracking one
+    pub name: String,
+    /// Remote of a remote-tracking branch
+    pub remote: Option<String>,
+    /// Whether this is the checked-out branch
+    pub is_head: bool,
+    /// Upstream of a local branch, e.g. `origin/main`
+    pub upstream: Option<String>,
+    /// Whether the upstream was deleted on the remote and pruned
+    pub upstream_gone: bool,
+    /// Whether the tip is reachable from HEAD, i.e. deleting the branch loses no commits
+    pub merged: bool,
+    pub hash: String,
+    pub subject: String,
+}
+
+impl BranchEntry {
+    /// Parses a NUL separated `refname, HEAD, upstream, track, hash, subject` record as
+    /// produced by `BRANCH_FORMAT`
+    fn parse(record: &str) -> Option<Self> {
+        let mut fields = record.splitn(6, '\0');
+        let refname = fields.next()?;
+        let (name, remote) = if let Some(name) = refname.strip_prefix("refs/heads/") {
+            (name.to_string(), None)
+        } else {
+            let name = refname.strip_prefix("refs/remotes/")?;
+            let (remote, _) = name.split_once('/')?;
+            (name.to_string(), Some(remote.to_string()))
+        };
+        let is_head = fields.next()? == "*";
+        let upstream = fields.next().filter(|upstream| !upstream.is_empty());
+        let upstream_gone = fields.next()? == "[gone]";
+        Some(Self {
+            name,
+            remote,
+            is_head,
+            upstream: upstream.map(ToString::to_string),
+            upstream_gone,
+            merged: false,
+            hash: fields.next()?.to_string(),
+            subject: fields.next().unwrap_or_default().to_string(),
+        })
+    }
+
+    /// Name of the branch on its remote, e.g. `main` for `origin/main`
+    pub fn remote_branch(&self) -> Option<&str> {
+        let remote = self.remote.as_deref()?;
+        self.name.strip_prefix(remote)?.strip_prefix('/')
+    }
+}
+
+/// `git for-each-ref` format read by `BranchEntry::parse`. Symbolic refs such as
+/// `origin/HEAD` get a `%(symref)` and are skipped.
+const BRANCH_FORMAT: &str =
+    "--format=%(symref)%00%(refname)%00%(HEAD)%00%(upstream:short)%00%(upstream:track)%00%(objectname:short)%00%(contents:subject)";
+
+/// Inserted and deleted line counts of one file from `git diff --numstat`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffStat {
+    pub added: usize,
+    pub deleted: usize,
+}
+
+impl DiffStat {
+    /// Parses `git diff --numstat -z` output into counts by path. Renames are keyed by their
+    /// new path; binary files (`-` counts) are left out.
+    fn parse_numstat(output: &[u8]) -> HashMap<String, Self> {
+        let mut stats = HashMap::new();
+        let mut records = output.split(|&b| b == 0);
+        while let Some(record) = records.next() {
+            let record = decode_path(record);
+            let mut fields = record.splitn(3, '\t');
+            let (Some(added), Some(deleted), Some(path)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            // A rename has an empty path field followed by the old and new paths
+            let path = if path.is_empty() {
+                records.next();
+                records.next().map(decode_path).unwrap_or_default()
+            } else {
+                path.to_string()
+            };
+            if let (Ok(added), Ok(deleted)) = (added.parse(), deleted.parse()) {
+                stats.insert(path, Self { added, deleted });
+            }
+        }
+        stats
+    }
+}
+
+/// Author or committer of a commit
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Person {
+    pub name: String,
+    pub email: String,
+    /// As `%ai` prints it, e.g. `2024-05-01 12:00:00 +0900`
+    pub date: String,
+}
+
+/// GPG verification result of a commit, from `%G?`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SignatureStatus {
+    #[default]
+    Unsigned,
+    Good,
+    /// Good signature from a key whose validity is unknown
+    GoodUntrusted,
+    Bad,
+    ExpiredSignature,
+    ExpiredKey,
+    RevokedKey,
+    /// The key is missing or gpg could not run
+    Unverifiable,
+}
+
+impl SignatureStatus {
+    fn parse(code: &str) -> Self {
+        match code {
+            "G" => Self::Good,
+            "U" => Self::GoodUntrusted,
+            "B" => Self::Bad,
+            "X" => Self::ExpiredSignature,
+            "Y" => Self::ExpiredKey,
+            "R" => Self::RevokedKey,
+            "E" => Self::Unverifiable,
+            _ => Self::Unsigned,
+        }
+    }
+}
+
+/// A file changed by a commit, with the letter `--name-status` gives it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitFile {
+    /// `A`, `M`, `D`, `R`, `C` or `T`
+    pub status: char,
+    pub path: String,
+    /// Source of a rename or copy
+    pub old_path: Option<String>,
+    /// `None` for binary files
+    pub stat: Option<DiffStat>,
+}
+
+impl CommitFile {
+    /// Parses `git diff-tree --name-status -z` output; renames and copies carry two paths
+    fn parse_name_status(output: &[u8]) -> Vec<Self> {
+        let mut files = Vec::new();
+        let mut records = output
+            .split(|&b| b == 0)
+            .filter(|record| !record.is_empty());
+        while let Some(status) = records.next() {
+            let Some(&letter) = status.first() else {
+                continue;
+            };
+            let status = char::from(letter);
+            let Some(first) = records.next().map(decode_path) else {
+                break;
+            };
+            let (path, old_path) = if matches!(status, 'R' | 'C') {
+                let Some(second) = records.next().map(decode_path) else {
+                    break;
+                };
+                (second, Some(first))
+            } else {
+                (first, None)
+            };
+            files.push(Self {
+                status,
+                path,
+                old_path,
+                stat: None,
+            });
+        }
+        files
+    }
+}
+
+/// Metadata and changed files of one commit, for the commit details view
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitDetails {
+    pub hash: String,
+    pub parents: Vec<String>,
+    pub author: Person,
+    pub committer: Person,
+    /// Subject and body
+    pub message: String,
+    pub signature: SignatureStatus,
+    /// Signer from `%GS`, empty for unsigned commits
+    pub signer: String,
+    /// Compared to the first parent for merges
+    pub files: Vec<CommitFile>,
+}
+
+impl CommitDetails {
+    /// Parses a record of `COMMIT_DETAILS_FORMAT`; the message comes last so it may contain
+    /// anything but NUL
+    fn parse(record: &str) -> Option<Self> {
+        let mut fields = record.splitn(11, '\0');
+        let mut next = || fields.next().map(ToString::to_string);
+        let hash = next().filter(|hash| !hash.is_empty())?;
+        let parents = next()?
+            .split_whitespace()
+            .map(ToString::to_string)
+            .collect();
+        let author = Person {
+            name: next()?,
+            email: next()?,
+            date: next()?,
+        };
+        let committer = Person {
+            name: next()?,
+            email: next()?,
+            date: next()?,
+        };
+        let signature = SignatureStatus::parse(&next()?);
+        let signer = next()?;
+        let message = next().unwrap_or_default().trim_end().to_string();
+        Some(Self {
+            hash,
+            parents,
+            author,
+            committer,
+            message,
+            signature,
+            signer,
+            files: Vec::new(),
+        })
+    }
+
+    /// `diff-tree` arguments comparing the commit to its first parent, or to the empty tree
+    /// for a root commit
+    fn tree_args(&self) -> Vec<&str> {
+        match self.parents.first() {
+            Some(parent) if self.parents.len() > 1 => vec![parent.as_str(), self.hash.as_str()],
+            _ => vec!["--root", self.hash.as_str()],
+        }
+    }
+}
+
+/// `git show` format read by `CommitDetails::parse`
+const COMMIT_DETAILS_FORMAT: &str =
+    "--format=%H%x00%P%x00%an%x00%ae%x00%ai%x00%cn%x00%ce%x00%ci%x00%G?%x00%GS%x00%B";
+
+/// Renders an untracked file like `git diff --no-index /dev/null <file>` would, so it goes
+/// through the same diff viewer. Binary content is detected the way git does, by looking for a
+/// NUL byte near the start of the file.
+fn format_new_file(file_path: &str, bytes: &[u8]) -> String {
+    if binary::is_binary(bytes) {
+        return format!(
+            "new file: {file_path}\nBinary file: {}{}",
+            binary::describe(bytes),
+            hex_dump_hint(bytes.len())
+        );
+    }
+    let content = String::from_utf8_lossy(bytes);
+    let line_count = content.lines().count();
+    let mut preview = format!("new file: {file_path}\n@@ -0,0 +1,{line_count} @@\n");
+    for line in content.lines() {
+        preview.push('+');
+        preview.push_str(line);
+        preview.push('\n');
+    }
+    preview
+}
+
+/// Where to find the hex dump of a binary small enough for one
+const fn hex_dump_hint(len: usize) -> &'static str {
+    if len <= binary::HEX_DUMP_LIMIT {
+        "\n\nPress x in the fullscreen preview (d) for a hex dump"
+    } else {
+        ""
+    }
+}
+
+/// git's `Binary files a/x and b/x differ`
+fn is_binary_diff(diff: &str) -> bool {
+    diff::parse(diff).iter().any(|file| file.binary)
+}
+
+/// `message`, saying which changes were ignored when whitespace options hid them all
+fn no_changes(message: &str, options: DiffOptions) -> String {
+    let ignored: Vec<&str> = [
+        (options.ignore_whitespace, "whitespace"),
+        (options.ignore_blank_lines, "blank lines"),
+    ]
+    .into_iter()
+    .filter_map(|(on, what)| on.then_some(what))
+    .collect();
+    if ignored.is_empty() {
+        message.to_string()
+    } else {
+        format!("{message} (ignoring {})", ignored.join(" and "))
+    }
+}
+
+/// Picks the most useful line out of a failed signed commit, or `None` when the failure is
+/// unrelated to signing. gpg and ssh-keygen report the real cause (missing key, pinentry)
+/// on lines before git's generic "failed to sign the data".
+fn signing_failure(stderr: &str) -> Option<String> {
+    const MARKERS: [&str; 6] = [
+        "failed to sign",
+        "gpg:",
+        "pinentry",
+        "secret key",
+        "ssh-keygen",
+        "signing key",
+    ];
+
+    let lines: Vec<&str> = stderr
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            let line = line.to_lowercase();
+            MARKERS.iter().any(|marker| line.contains(marker))
+        })
+        .collect();
+    let detail = lines
+        .iter()
+        .find(|line| !line.contains("failed to sign the data"))
+        .or_else(|| lines.first())?;
+    Some(
+        detail
+            .trim_start_matches("error: ")
+            .trim_start_matches("fatal: ")
+            .to_string(),
+    )
+}
+
+/// A commit message without `#` comment lines and surrounding blank lines
+fn strip_commit_comments(message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// `.gitignore` patterns offered for a path: the file itself, its extension and its
+/// directory. Patterns are anchored to the repository root.
+pub fn ignore_candidates(path: &str) -> Vec<String> {
+    let trimmed = path.trim_end_matches('/');
+    let mut candidates = vec![format!("/{path}")];
+    let name = trimmed.rsplit('/').next().unwrap_or(trimmed);
+    if !path.ends_with('/') {
+        if let Some((stem, ext)) = name.rsplit_once('.') {
+            if !stem.is_empty() && !ext.is_empty() {
+                candidates.push(format!("*.{ext}"));
+            }
+        }
+    }
+    if let Some((dir, _)) = trimmed.rsplit_once('/') {
+        candidates.push(format!("/{dir}/"));
+    }
+    candidates
+}
+
+/// Percentage of a git progress line such as `Receiving objects:  45% (45/100)`
+fn parse_progress_percent(line: &str) -> Option<u8> {
+    let (before, _) = line.split_once('%')?;
+    let digits_start = before
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    let (label, digits) = before.split_at(digits_start);
+    if !label.ends_with(' ') {
+        return None;
+    }
+    digits.parse::<u8>().ok().filter(|percent| *percent <= 100)
+}
+
+/// The `git archive --format` a file name such as `snapshot.tar.gz` asks for
+pub fn archive_format(path: &str) -> Option<&'static str> {
+    let path = path.to_ascii_lowercase();
+    [
+        (".zip", "zip"),
+        (".tar.gz", "tar.gz"),
+        (".tgz", "tgz"),
+        (".tar", "tar"),
+    ]
+    .into_iter()
+    .find(|(extension, _)| path.ends_with(extension))
+    .map(|(_, format)| format)
+}
+
+/// `git config --list --show-scope --show-origin -z`: `scope NUL origin NUL key LF value NUL`
+/// per entry, without the LF and value for a key that has none
+fn parse_config_list(output: &str) -> Vec<ConfigEntry> {
+    let mut fields = output.split('\0');
+    let mut entries = Vec::new();
+    while let (Some(scope), Some(origin), Some(item)) =
+        (fields.next(), fields.next(), fields.next())
+    {
+        let Some(scope) = ConfigScope::parse(scope) else {
+            continue;
+        };
+        let (key, value) = item.split_once('\n').unwrap_or((item, ""));
+        entries.push(ConfigEntry {
+            key: key.to_string(),
+            value: value.to_string(),
+            scope,
+            origin: origin.to_string(),
+        });
+    }
+    entries
+}
+
+fn stash_ref(index: usize) -> String {
+    format!("stash@{{{index}}}")
+}
+
+/// Reflog entries searched for the branch a detached HEAD came from
+const DETACHED_REFLOG_COUNT: usize = 100;
+
+/// The branch of the latest `checkout: moving from <branch> to ...` in `reflog` that still
+/// exists; moves between detached commits name hashes, which are skipped
+fn previous_branch(reflog: &[ReflogEntry], is_branch: impl Fn(&str) -> bool) -> Option<&str> {
+    reflog.iter().find_map(|entry| {
+        let (from, _) = entry
+            .message
+            .strip_prefix("checkout: moving from ")?
+            .split_once(" to ")?;
+        is_branch(from).then_some(from)
+    })
+}
+
 impl Default for GitOperations {
     fn default() -> Self {
         Self::new()
     }
-}
+}
+
+impl GitOperations {
+    pub const fn new() -> Self {
+        Self
+    }
+
+    pub fn get_status(repo: &RepoContext) -> Result<Vec<FileEntry>, GitError> {
+        Self::get_status_with(repo, &StatusOptions::default())
+    }
+
+    pub fn get_status_with(
+        repo: &RepoContext,
+        options: &StatusOptions,
+    ) -> Result<Vec<FileEntry>, GitError> {
+        backend::active().status(repo, options)
+    }
+
+    /// Toggles the staging state of a porcelain status line (`XY path`)
+    pub fn stage_file(repo: &RepoContext, file_status: &str) -> Result<Message, GitError> {
+        let entry = FileEntry::parse(file_status)
+            .filter(|_| file_status.len() >= 3)
+            .ok_or_else(|| GitError::Invalid("Invalid file status format".to_string()))?;
+
+        if entry.is_staged() {
+            Self::unstage_entry(repo, &entry)
+        } else {
+            Self::stage_entry(repo, &entry)
+        }
+    }
+
+    /// Stages an entry. A rename in the working tree is staged on both paths, so the removal
+    /// of the old path goes along with the new one. A rename already in the index only needs
+    /// the new path.
+    pub fn stage_entry(repo: &RepoContext, entry: &FileEntry) -> Result<Message, GitError> {
+        if let Some(orig) = entry
+            .orig_path
+            .as_ref()
+            .filter(|_| entry.worktree_status == 'R')
+        {
+            backend::active().stage_path(repo, OsStr::new(orig))?;
+        }
+        backend::active().stage_path(repo, OsStr::new(&entry.path))?;
+        Ok(Message::new(Msg::StagedFile, &[&entry.display_path()]))
+    }
+
+    /// Unstages an entry, restoring the old path of a rename in the index as well
+    pub fn unstage_entry(repo: &RepoContext, entry: &FileEntry) -> Result<Message, GitError> {
+        if let Some(orig) = &entry.orig_path {
+            backend::active().unstage_path(repo, OsStr::new(orig))?;
+        }
+        backend::active().unstage_path(repo, OsStr::new(&entry.path))?;
+        Ok(Message::new(Msg::UnstagedFile, &[&entry.display_path()]))
+    }
+
+    pub fn stage_path(repo: &RepoContext, path: &str) -> Result<Message, GitError> {
+        backend::active().stage_path(repo, OsStr::new(path))?;
+        Ok(Message::new(Msg::StagedFile, &[&path]))
+    }
+
+    pub fn unstage_path(repo: &RepoContext, path: &str) -> Result<Message, GitError> {
+        backend::active().unstage_path(repo, OsStr::new(path))?;
+        Ok(Message::new(Msg::UnstagedFile, &[&path]))
+    }
+
+    /// Throws away working tree changes to `path`. Tracked files are restored from the index
+    /// with `git checkout`, untracked files are deleted.
+    pub fn discard_changes(repo: &RepoContext, path: &str) -> Result<Message, GitError> {
+        // Checking out an intent-to-add file would empty it; it is deleted like an untracked one
+        if Self::is_intent_to_add(repo, path)? {
+            Self::undo_intent_to_add(repo, path)?;
+        }
+        if !Self::is_tracked(repo, path)? {
+            let target = repo.path(path);
+            if target.is_dir() {
+                std::fs::remove_dir_all(&target)
+            } else {
+                std::fs::remove_file(&target)
+            }
+            .map_err(|e| GitError::io(format!("delete {path}"), e))?;
+            return Ok(Message::new(Msg::DeletedUntracked, &[&path]));
+        }
+
+        let output = repo
+            .git()
+            .args(["checkout", "--", path])
+            .logged_output()
+            .map_err(|e| GitError::io("discard changes", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("checkout", &output));
+        }
+        Ok(Message::new(Msg::DiscardedChanges, &[&path]))
+    }
+
+    /// `git add -N`: records an untracked path in the index without its content, so its
+    /// lines show up in `git diff` and can be staged one by one
+    pub fn intent_to_add(repo: &RepoContext, path: &str) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .env("GIT_LITERAL_PATHSPECS", "1")
+            .args(["add", "--intent-to-add", "--", path])
+            .logged_output()
+            .map_err(|e| GitError::io("add file", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("add", &output));
+        }
+        Ok(Message::new(Msg::MarkedIntentToAdd, &[&path]))
+    }
+
+    /// Drops an intent-to-add entry from the index, leaving the file untracked again
+    pub fn undo_intent_to_add(repo: &RepoContext, path: &str) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .env("GIT_LITERAL_PATHSPECS", "1")
+            .args(["rm", "--cached", "-q", "-r", "--", path])
+            .logged_output()
+            .map_err(|e| GitError::io("remove file from the index", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("rm", &output));
+        }
+        Ok(Message::new(Msg::UntrackedAgain, &[&path]))
+    }
+
+    fn is_intent_to_add(repo: &RepoContext, path: &str) -> Result<bool, GitError> {
+        let options = StatusOptions {
+            untracked: Some(UntrackedFiles::No),
+            paths: vec![path.to_string()],
+        };
+        Ok(Self::get_status_with(repo, &options)?
+            .iter()
+            .any(FileEntry::is_intent_to_add))
+    }
+
+    pub fn stage_all_files(repo: &RepoContext, files: &[FileEntry]) -> Result<Message, GitError> {
+        let has_unstaged = files.iter().any(|f| f.is_unstaged() || f.is_untracked());
+
+        if has_unstaged {
+            let output = repo
+                .git()
+                .args(["add", "."])
+                .logged_output()
+                .map_err(|e| GitError::io("stage all files", e))?;
+
+            if !output.status.success() {
+                return Err(GitError::command("add", &output));
+            }
+            Ok(Msg::AllStaged.into())
+        } else {
+            let output = repo
+                .git()
+                .args(["reset"])
+                .logged_output()
+                .map_err(|e| GitError::io("unstage all files", e))?;
+
+            if !output.status.success() {
+                return Err(GitError::command("reset", &output));
+            }
+            Ok(Msg::AllUnstaged.into())
+        }
+    }
+
+    /// Appends `pattern` to the top-level `.gitignore` unless it is already listed
+    pub fn add_to_gitignore(repo: &RepoContext, pattern: &str) -> Result<Message, GitError> {
+        let path = repo.path(".gitignore");
+        let mut content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(GitError::io("read .gitignore", e)),
+        };
+        if content.lines().any(|line| line.trim() == pattern) {
+            return Ok(Message::new(Msg::AlreadyIgnored, &[&pattern]));
+        }
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(pattern);
+        content.push('\n');
+        std::fs::write(&path, content).map_err(|e| GitError::io("write .gitignore", e))?;
+        Ok(Message::new(Msg::AddedToGitignore, &[&pattern]))
+    }
+
+    /// The given paths that match an ignore rule. Tracked files are checked too, so files
+    /// that were committed before being ignored can be flagged.
+    pub fn check_ignore(repo: &RepoContext, paths: &[&str]) -> Result<Vec<String>, GitError> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let output = repo
+            .git()
+            .args(["check-ignore", "--no-index", "--"])
+            .args(paths)
+            .logged_output()
+            .map_err(|e| GitError::io("check ignore rules", e))?;
+
+        // Exit code 1 means none of the paths is ignored
+        if !matches!(output.status.code(), Some(0 | 1)) {
+            return Err(GitError::command("check-ignore", &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    pub fn stash_changes(
+        repo: &RepoContext,
+        message: Option<&str>,
+        options: StashOptions,
+    ) -> Result<Message, GitError> {
+        let mut args = vec!["stash", "push"];
+        args.extend(options.args());
+
+        if let Some(msg) = message {
+            args.push("-m");
+            args.push(msg);
+        }
+
+        let output = repo
+            .git()
+            .args(&args)
+            .logged_output()
+            .map_err(|e| GitError::io("stash changes", e))?;
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        let error = String::from_utf8_lossy(&output.stderr);
+
+        if !error.is_empty() {
+            Ok(Message::new(Msg::StashError, &[&error]))
+        } else if result.contains("No local changes to save") {
+            Ok(Msg::NoChangesToStash.into())
+        } else {
+            Ok(Message::new(Msg::ChangesStashed, &[&result]))
+        }
+    }
+
+    /// `git stash push -- <paths>`: stashes only the given files, untracked ones included
+    /// whatever `options` says, and leaves every other change in place
+    pub fn stash_paths(
+        repo: &RepoContext,
+        paths: &[String],
+        message: Option<&str>,
+        options: StashOptions,
+    ) -> Result<Message, GitError> {
+        let options = StashOptions {
+            include_untracked: true,
+            ..options
+        };
+        let mut command = repo.git();
+        command
+            .env("GIT_LITERAL_PATHSPECS", "1")
+            .args(["stash", "push"])
+            .args(options.args());
+        if let Some(message) = message {
+            command.args(["-m", message]);
+        }
+        let output = command
+            .arg("--")
+            .args(paths)
+            .logged_output()
+            .map_err(|e| GitError::io("stash files", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("stash", &output));
+        }
+        if String::from_utf8_lossy(&output.stdout).contains("No local changes to save") {
+            return Ok(Msg::NoChangesToStash.into());
+        }
+        Ok(Message::new(Msg::StashedPaths, &[&paths.len()]))
+    }
+
+    pub fn list_stashes(repo: &RepoContext) -> Result<String, GitError> {
+        let output = repo
+            .git()
+            .args(["stash", "list"])
+            .logged_output()
+            .map_err(|e| GitError::io("list stashes", e))?;
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        if result.is_empty() {
+            Ok("No stashes found".to_string())
+        } else {
+            Ok(format!("Stashes:\n{result}"))
+        }
+    }
+
+    pub fn apply_latest_stash(repo: &RepoContext) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .args(["stash", "apply"])
+            .logged_output()
+            .map_err(|e| GitError::io("apply stash", e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            if error.contains("No stash entries found") || error.contains("No stash found") {
+                return Ok(Msg::NoStashToApply.into());
+            }
+            return Err(GitError::command("stash apply", &output));
+        }
+        Ok(Msg::LatestStashApplied.into())
+    }
+
+    pub fn get_stashes(repo: &RepoContext) -> Result<Vec<StashEntry>, GitError> {
+        let output = repo
+            .git()
+            .args(["stash", "list", "--format=%gd%x09%gs"])
+            .logged_output()
+            .map_err(|e| GitError::io("list stashes", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("stash list", &output));
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        Ok(output_str.lines().filter_map(StashEntry::parse).collect())
+    }
+
+    pub fn show_stash(repo: &RepoContext, index: usize) -> Result<String, GitError> {
+        let output = repo
+            .git()
+            .args(["stash", "show", "--patch", "--stat", &stash_ref(index)])
+            .logged_output()
+            .map_err(|e| GitError::io("show stash", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("stash show", &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    pub fn apply_stash(repo: &RepoContext, index: usize) -> Result<Message, GitError> {
+        Self::run_stash_command(repo, "apply", index)?;
+        Ok(Message::new(Msg::StashApplied, &[&stash_ref(index)]))
+    }
+
+    pub fn pop_stash(repo: &RepoContext, index: usize) -> Result<Message, GitError> {
+        Self::run_stash_command(repo, "pop", index)?;
+        Ok(Message::new(Msg::StashPopped, &[&stash_ref(index)]))
+    }
+
+    pub fn drop_stash(repo: &RepoContext, index: usize) -> Result<Message, GitError> {
+        Self::run_stash_command(repo, "drop", index)?;
+        Ok(Message::new(Msg::StashDropped, &[&stash_ref(index)]))
+    }
+
+    fn run_stash_command(
+        repo: &RepoContext,
+        subcommand: &str,
+        index: usize,
+    ) -> Result<(), GitError> {
+        let output = repo
+            .git()
+            .args(["stash", subcommand, &stash_ref(index)])
+            .logged_output()
+            .map_err(|e| GitError::io(format!("{subcommand} stash"), e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command(format!("stash {subcommand}"), &output));
+        }
+        Ok(())
+    }
+
+    /// Commits the staged changes. `sign` maps to `-S` / `--no-gpg-sign` so the per-commit
+    /// toggle overrides `commit.gpgsign` in both directions; `all` stages modified and deleted
+    /// tracked files first, like `git commit -a`.
+    pub fn commit(
+        repo: &RepoContext,
+        message: &str,
+        sign: bool,
+        all: bool,
+    ) -> Result<Message, GitError> {
+        let options = CommitOptions {
+            sign,
+            all,
+            ..CommitOptions::default()
+        };
+        Self::commit_with(repo, message, &options)
+    }
+
+    /// Like `commit`, with every flag of `CommitOptions`, and reports a rejection by the
+    /// pre-commit or commit-msg hook together with everything the hook printed
+    pub fn commit_with(
+        repo: &RepoContext,
+        message: &str,
+        options: &CommitOptions,
+    ) -> Result<Message, GitError> {
+        let CommitOptions {
+            sign,
+            no_verify,
+            all,
+            exclude,
+            identity,
+        } = *options;
+        let sign_flag = if sign { "-S" } else { "--no-gpg-sign" };
+        let mut command = repo.git();
+        command.args(["commit", sign_flag]);
+        if no_verify {
+            command.arg("--no-verify");
+        }
+        // `--author` only sets the author, so the committer comes from the environment
+        if let Some(identity) = identity {
+            command
+                .arg(format!("--author={identity}"))
+                .env("GIT_COMMITTER_NAME", &identity.name)
+                .env("GIT_COMMITTER_EMAIL", &identity.email);
+        }
+        let split_index = if exclude.is_empty() {
+            if all {
+                command.arg("--all");
+            }
+            None
+        } else {
+            // `--all` would stage the left-out files again, so the other tracked changes are
+            // staged by hand; unlike `--all` they stay staged if the commit fails
+            if all {
+                Self::stage_tracked_changes(repo, exclude)?;
+            }
+            Some(Self::split_index(repo, exclude)?)
+        };
+        if let Some(index) = &split_index {
+            command.env("GIT_INDEX_FILE", index);
+        }
+        let output = command.args(["-m", message]).logged_output();
+        if let Some(index) = &split_index {
+            std::fs::remove_file(index).ok();
+        }
+        let output = output.map_err(|e| GitError::io("commit", e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            if error.contains("nothing to commit") {
+                return Ok(Msg::NothingToCommit.into());
+            }
+            if let Some(reason) = signing_failure(&error) {
+                return Err(GitError::Signing(reason));
+            }
+            if let Some(hook) = Self::commit_hook(repo).filter(|_| !no_verify) {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let output = [stdout.trim_end(), error.trim_end()]
+                    .iter()
+                    .filter(|text| !text.is_empty())
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                return Err(GitError::Hook { hook, output });
+            }
+            return Err(GitError::command("commit", &output));
+        }
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        if result.contains("create mode")
+            || result.contains("delete mode")
+            || result.contains("file changed")
+        {
+            Ok(Message::new(Msg::CommittedWithSummary, &[&result.trim()]))
+        } else {
+            Ok(Msg::Committed.into())
+        }
+    }
+
+    /// `git add -u` for every tracked file except the `exclude` entries
+    fn stage_tracked_changes(repo: &RepoContext, exclude: &[FileEntry]) -> Result<(), GitError> {
+        let paths: Vec<String> = Self::get_status(repo)?
+            .into_iter()
+            .filter(|entry| {
+                entry.is_unstaged() && !exclude.iter().any(|excluded| excluded.path == entry.path)
+            })
+            .map(|entry| entry.path)
+            .collect();
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let output = repo
+            .git()
+            .env("GIT_LITERAL_PATHSPECS", "1")
+            .args(["add", "--update", "--"])
+            .args(&paths)
+            .logged_output()
+            .map_err(|e| GitError::io("stage tracked changes", e))?;
+        if !output.status.success() {
+            return Err(GitError::command("add --update", &output));
+        }
+        Ok(())
+    }
+
+    fn has_head(repo: &RepoContext) -> bool {
+        repo.git()
+            .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+            .logged_output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    /// Copies the index and unstages `exclude` in the copy. Committing the copy leaves the
+    /// real index as it was, so the excluded changes are still staged afterwards.
+    fn split_index(repo: &RepoContext, exclude: &[FileEntry]) -> Result<PathBuf, GitError> {
+        let index = Self::git_path(repo, "index")?;
+        let split = Self::git_path(repo, "pretty-git-ui-split-index")?;
+        std::fs::copy(&index, &split).map_err(|e| GitError::io("copy the index", e))?;
+
+        let paths = exclude
+            .iter()
+            .flat_map(|entry| std::iter::once(&entry.path).chain(&entry.orig_path));
+        let mut command = repo.git();
+        let subcommand = if Self::has_head(repo) {
+            command.args(["reset", "--quiet", "HEAD", "--"]);
+            "reset"
+        } else {
+            command.args(["rm", "--cached", "--quiet", "--ignore-unmatch", "--"]);
+            "rm"
+        };
+        let output = command
+            .args(paths)
+            .env("GIT_INDEX_FILE", &split)
+            .logged_output()
+            .map_err(|e| GitError::io("prepare the commit", e))?;
+
+        if !output.status.success() {
+            std::fs::remove_file(&split).ok();
+            return Err(GitError::command(subcommand, &output));
+        }
+        Ok(split)
+    }
+
+    /// The first installed hook that can reject a commit. Git runs pre-commit before
+    /// commit-msg, and neither says which one failed.
+    fn commit_hook(repo: &RepoContext) -> Option<String> {
+        ["pre-commit", "commit-msg"]
+            .into_iter()
+            .find(|name| {
+                Self::git_path(repo, &format!("hooks/{name}")).is_ok_and(|hook| hook.is_file())
+            })
+            .map(String::from)
+    }
+
+    /// The value of a git config key as it applies to this repository, `None` when unset
+    pub fn get_config(repo: &RepoContext, key: &str) -> Option<String> {
+        repo.git()
+            .args(["config", "--get", key])
+            .logged_output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Every config value git reads for this repository, in the order it reads them: system,
+    /// global, local, worktree, then the command line. A later value of a key overrides
+    /// the earlier ones.
+    pub fn get_config_list(repo: &RepoContext) -> Result<Vec<ConfigEntry>, GitError> {
+        let output = repo
+            .git()
+            .args(["config", "--list", "--show-scope", "--show-origin", "-z"])
+            .logged_output()
+            .map_err(|e| GitError::io("run git config", e))?;
+        if !output.status.success() {
+            return Err(GitError::command("config --list", &output));
+        }
+        Ok(parse_config_list(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// `git config --<scope> key value`, or `--unset` when `value` is empty
+    pub fn set_config(
+        repo: &RepoContext,
+        scope: ConfigScope,
+        key: &str,
+        value: &str,
+    ) -> Result<ConfigChange, GitError> {
+        if !ConfigScope::EDITABLE.contains(&scope) {
+            return Err(GitError::Invalid(format!(
+                "The {} config cannot be written",
+                scope.as_str()
+            )));
+        }
+        let flag = format!("--{}", scope.as_str());
+        // `--unset` fails with exit code 5 both for a key that is not set and for one with
+        // several values, so the values are counted first
+        if value.is_empty() {
+            let values = repo
+                .git()
+                .args(["config", &flag, "--null", "--get-all", key])
+                .logged_output()
+                .map_err(|e| GitError::io(format!("get {key}"), e))?;
+            let count = String::from_utf8_lossy(&values.stdout)
+                .matches('\0')
+                .count();
+            match count {
+                0 => return Ok(ConfigChange::NotSet),
+                1 => {},
+                _ => {
+                    return Err(GitError::Invalid(format!(
+                        "{key} has {count} values in the {} config; use git config --unset-all",
+                        scope.as_str()
+                    )))
+                },
+            }
+        }
+        let mut command = repo.git();
+        command.args(["config", &flag]);
+        if value.is_empty() {
+            command.args(["--unset", key]);
+        } else {
+            command.args([key, value]);
+        }
+        let output = command
+            .logged_output()
+            .map_err(|e| GitError::io(format!("set {key}"), e))?;
+        if !output.status.success() {
+            return Err(GitError::command(format!("config {key}"), &output));
+        }
+        Ok(if value.is_empty() {
+            ConfigChange::Unset
+        } else {
+            ConfigChange::Set
+        })
+    }
+
+    /// `git --version`, e.g. `git version 2.43.0`
+    pub fn get_git_version(repo: &RepoContext) -> Result<String, GitError> {
+        let output = repo
+            .git()
+            .arg("--version")
+            .logged_output()
+            .map_err(|e| GitError::io("run git", e))?;
+        if !output.status.success() {
+            return Err(GitError::command("--version", &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// `user.name` and `user.email` as git resolves them for this repository, `None` while
+    /// either is unset
+    pub fn get_identity(repo: &RepoContext) -> Option<Identity> {
+        let config = |key: &str| {
+            repo.git()
+                .args(["config", "--get", key])
+                .logged_output()
+                .ok()
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Some(Identity {
+            label: String::new(),
+            name: config("user.name")?,
+            email: config("user.email")?,
+        })
+    }
+
+    /// Writes `user.name` and `user.email` to the repository's own config
+    pub fn set_identity(repo: &RepoContext, identity: &Identity) -> Result<Message, GitError> {
+        for (key, value) in [
+            ("user.name", &identity.name),
+            ("user.email", &identity.email),
+        ] {
+            let output = repo
+                .git()
+                .args(["config", "--local", key, value])
+                .logged_output()
+                .map_err(|e| GitError::io(format!("set {key}"), e))?;
+            if !output.status.success() {
+                return Err(GitError::command(format!("config {key}"), &output));
+            }
+        }
+        Ok(Message::new(Msg::IdentitySet, &[identity]))
+    }
+
+    /// Authors of the last `max_count` commits, most recent first and each email once.
+    /// Empty in a repository without commits.
+    pub fn recent_authors(repo: &RepoContext, max_count: usize) -> Result<Vec<Identity>, GitError> {
+        let output = repo
+            .git()
+            .args([
+                "log",
+                "--format=%an%x09%ae",
+                &format!("--max-count={max_count}"),
+            ])
+            .logged_output()
+            .map_err(|e| GitError::io("get recent authors", e))?;
+        if !output.status.success() {
+            if String::from_utf8_lossy(&output.stderr).contains("does not have any commits") {
+                return Ok(Vec::new());
+            }
+            return Err(GitError::command("log", &output));
+        }
+
+        let mut authors: Vec<Identity> = Vec::new();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let Some((name, email)) = line.split_once('\t') else {
+                continue;
+            };
+            if !authors.iter().any(|author| author.email == email) {
+                authors.push(Identity {
+                    label: String::new(),
+                    name: name.to_string(),
+                    email: email.to_string(),
+                });
+            }
+        }
+        Ok(authors)
+    }
+
+    /// Whether `commit.gpgsign` is enabled, i.e. commits are signed unless told otherwise
+    pub fn is_commit_signing_enabled(repo: &RepoContext) -> bool {
+        repo.git()
+            .args(["config", "--bool", "commit.gpgsign"])
+            .logged_output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+    }
+
+    /// Initial commit message built from `commit.template` (or `.git/commit_template`) and the
+    /// repository's `prepare-commit-msg` hook. Comment lines are removed because the message is
+    /// later passed to `git commit -m`, which keeps them. Returns `None` when nothing applies.
+    pub fn get_commit_template(repo: &RepoContext) -> Result<Option<String>, GitError> {
+        let configured = repo
+            .git()
+            .args(["config", "--path", "commit.template"])
+            .logged_output()
+            .map_err(|e| GitError::io("read commit.template", e))?;
+        let configured = String::from_utf8_lossy(&configured.stdout)
+            .trim()
+            .to_string();
+        let template_path = if configured.is_empty() {
+            Self::git_path(repo, "commit_template")?
+        } else {
+            repo.path(configured)
+        };
+        let template = std::fs::read_to_string(&template_path).ok();
+
+        let hook = Self::git_path(repo, "hooks/prepare-commit-msg")?;
+        let message = if hook.is_file() {
+            Self::run_prepare_commit_msg(repo, &hook, template.as_deref())?
+        } else {
+            template.unwrap_or_default()
+        };
+
+        let message = strip_commit_comments(&message);
+        Ok(if message.is_empty() {
+            None
+        } else {
+            Some(message)
+        })
+    }
+
+    /// Writes the draft and the files to be committed to `COMMIT_EDITMSG` for editing in an
+    /// external editor, the way `git commit` without `-m` does
+    pub fn write_commit_editmsg(
+        repo: &RepoContext,
+        draft: &str,
+        files: &[&str],
+    ) -> Result<PathBuf, GitError> {
+        let path = Self::git_path(repo, "COMMIT_EDITMSG")?;
+        let mut content = format!(
+            "{draft}\n\n# Please enter the commit message for your changes. Lines starting\n\
+             # with '#' will be ignored.\n#\n# Changes to be committed:\n"
+        );
+        for file in files {
+            content.push_str("#\t");
+            content.push_str(file);
+            content.push('\n');
+        }
+        std::fs::write(&path, content)
+            .map_err(|e| GitError::io(format!("write {}", path.display()), e))?;
+        Ok(path)
+    }
+
+    /// Reads back a message written by `write_commit_editmsg`, without the comment lines
+    pub fn read_commit_editmsg(path: &Path) -> Result<String, GitError> {
+        std::fs::read_to_string(path)
+            .map(|content| strip_commit_comments(&content))
+            .map_err(|e| GitError::io(format!("read {}", path.display()), e))
+    }
+
+    /// The editor `git commit` would open: `GIT_EDITOR`, `core.editor`, `VISUAL`, `EDITOR`,
+    /// then `vi`
+    pub fn editor(repo: &RepoContext) -> String {
+        repo.git()
+            .args(["var", "GIT_EDITOR"])
+            .logged_output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|editor| !editor.is_empty())
+            .unwrap_or_else(|| "vi".to_string())
+    }
+
+    /// Runs the hook on a scratch message file the same way `git commit` does
+    fn run_prepare_commit_msg(
+        repo: &RepoContext,
+        hook: &Path,
+        template: Option<&str>,
+    ) -> Result<String, GitError> {
+        let message_file = Self::git_path(repo, "COMMIT_EDITMSG")?;
+        std::fs::write(&message_file, template.unwrap_or_default())
+            .map_err(|e| GitError::io("write commit message file", e))?;
+
+        let mut command = repo.command(hook);
+        command.arg(&message_file);
+        if template.is_some() {
+            command.arg("template");
+        }
+        let output = command
+            .logged_output()
+            .map_err(|e| GitError::io("run prepare-commit-msg hook", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::Hook {
+                hook: "prepare-commit-msg".to_string(),
+                output: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        std::fs::read_to_string(&message_file)
+            .map_err(|e| GitError::io("read commit message file", e))
+    }
+
+    /// Resolves a path inside the git directory (`git rev-parse --git-path`)
+    fn git_path(repo: &RepoContext, name: &str) -> Result<PathBuf, GitError> {
+        let output = repo
+            .git()
+            .args(["rev-parse", "--git-path", name])
+            .logged_output()
+            .map_err(|e| GitError::io("get git path", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("rev-parse", &output));
+        }
+        Ok(repo.path(String::from_utf8_lossy(&output.stdout).trim()))
+    }
+
+    pub fn get_current_branch(repo: &RepoContext) -> Result<String, GitError> {
+        backend::active().current_branch(repo)
+    }
+
+    /// `git init` in the directory of `repo`, returning the new repository
+    pub fn init_repo(repo: &RepoContext) -> Result<RepoContext, GitError> {
+        Self::init_with_options(repo, &InitOptions::default())
+    }
+
+    /// `git init` in the directory of `repo` with an initial branch name, a `.gitignore`
+    /// and a first commit as `options` asks. An error after `git init` itself leaves the
+    /// new repository in place.
+    pub fn init_with_options(
+        repo: &RepoContext,
+        options: &InitOptions,
+    ) -> Result<RepoContext, GitError> {
+        let mut command = repo.git();
+        command.arg("init");
+        if !options.branch.is_empty() {
+            command.arg(format!("--initial-branch={}", options.branch));
+        }
+        let output = command
+            .logged_output()
+            .map_err(|e| GitError::io("run git init", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("init", &output));
+        }
+        let repo = repo.reopen()?;
+
+        if let Some(template) = options.gitignore {
+            let path = repo.dir().join(".gitignore");
+            if !path.exists() {
+                std::fs::write(&path, template.content)
+                    .map_err(|e| GitError::io("write .gitignore", e))?;
+            }
+        }
+        if options.initial_commit {
+            if options.gitignore.is_some() {
+                let output = repo
+                    .git()
+                    .args(["add", "--", ".gitignore"])
+                    .logged_output()
+                    .map_err(|e| GitError::io("run git add", e))?;
+                if !output.status.success() {
+                    return Err(GitError::command("add", &output));
+                }
+            }
+            let output = repo
+                .git()
+                .args(["commit", "--quiet", "--allow-empty", "-m", "Initial commit"])
+                .logged_output()
+                .map_err(|e| GitError::io("run git commit", e))?;
+            if !output.status.success() {
+                return Err(GitError::command("commit", &output));
+            }
+        }
+        Ok(repo)
+    }
+
+    /// Top-level directory of the repository, `None` outside a repository
+    pub fn get_repo_root(repo: &RepoContext) -> Option<PathBuf> {
+        backend::active().repo_root(repo).ok().flatten()
+    }
+
+    pub fn get_repo_name(repo: &RepoContext) -> Result<String, GitError> {
+        Ok(backend::active()
+            .repo_root(repo)?
+            .and_then(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or_else(|| "(no repository)".to_string()))
+    }
+
+    /// Reads the conflict markers of an unmerged file
+    pub fn get_conflicts(
+        repo: &RepoContext,
+        file_path: &str,
+    ) -> Result<Vec<ConflictHunk>, GitError> {
+        match std::fs::read_to_string(repo.path(file_path)) {
+            Ok(content) => Ok(ConflictHunk::parse_all(&content)),
+            // Deleted on one side: there is nothing to show, only a side to pick
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(GitError::io(format!("read {file_path}"), e)),
+        }
+    }
+
+    /// Resolves a conflicted file by taking one side entirely and marking it resolved.
+    /// When the chosen side deleted the file, the file is removed instead.
+    pub fn resolve_conflict(
+        repo: &RepoContext,
+        file_path: &str,
+        side: ConflictSide,
+    ) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .args(["checkout", side.flag(), "--", file_path])
+            .logged_output()
+            .map_err(|e| GitError::io("resolve conflict", e))?;
+
+        let error = String::from_utf8_lossy(&output.stderr);
+        let resolve_args: &[&str] = if output.status.success() {
+            &["add", "--", file_path]
+        } else if error.contains("does not have our version")
+            || error.contains("does not have their version")
+        {
+            &["rm", "--quiet", "--", file_path]
+        } else {
+            return Err(GitError::command("checkout", &output));
+        };
+
+        let output = repo
+            .git()
+            .args(resolve_args)
+            .logged_output()
+            .map_err(|e| GitError::io("mark resolved", e))?;
+        if !output.status.success() {
+            return Err(GitError::command(resolve_args[0], &output));
+        }
+
+        let side = match side {
+            ConflictSide::Ours => "ours",
+            ConflictSide::Theirs => "theirs",
+        };
+        Ok(Message::new(Msg::ConflictResolved, &[&file_path, &side]))
+    }
+
+    /// Merges `branch` into the current branch. Conflicts make git fail, leaving the
+    /// conflicted files in the working tree for the conflict view.
+    pub fn merge_branch(
+        repo: &RepoContext,
+        branch: &str,
+        strategy: MergeStrategy,
+    ) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .args(["merge", "--no-edit", strategy.flag(), branch])
+            .logged_output()
+            .map_err(|e| GitError::io("merge", e))?;
+
+        if !output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let error = if stderr.trim().is_empty() {
+                stdout
+            } else {
+                stderr
+            };
+            return Err(GitError::CommandFailed {
+                command: "merge".to_string(),
+                code: output.status.code(),
+                stderr: error.trim().to_string(),
+            });
+        }
+        let msg = match strategy {
+            MergeStrategy::Squash => Msg::SquashMerged,
+            MergeStrategy::FastForward | MergeStrategy::NoFastForward => Msg::Merged,
+        };
+        Ok(Message::new(msg, &[&branch]))
+    }
+
+    /// Whether a rebase stopped, e.g. at a conflict of `git pull --rebase`
+    pub fn is_rebasing(repo: &RepoContext) -> bool {
+        ["rebase-merge", "rebase-apply"]
+            .iter()
+            .any(|dir| Self::git_path(repo, dir).is_ok_and(|path| path.is_dir()))
+    }
+
+    /// `git merge --abort`, or `git rebase --abort` while a rebase is stopped
+    pub fn merge_abort(repo: &RepoContext) -> Result<Message, GitError> {
+        let operation = if Self::is_rebasing(repo) {
+            "rebase"
+        } else {
+            "merge"
+        };
+        let output = repo
+            .git()
+            .args([operation, "--abort"])
+            .logged_output()
+            .map_err(|e| GitError::io(format!("abort {operation}"), e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command(format!("{operation} --abort"), &output));
+        }
+        Ok(if operation == "rebase" {
+            Msg::RebaseAborted.into()
+        } else {
+            Msg::MergeAborted.into()
+        })
+    }
+
+    /// Concludes the merge with the prepared merge message, or lets a stopped rebase go on.
+    /// The editor is disabled because the TUI owns the terminal.
+    pub fn merge_continue(repo: &RepoContext) -> Result<Message, GitError> {
+        let operation = if Self::is_rebasing(repo) {
+            "rebase"
+        } else {
+            "merge"
+        };
+        let output = repo
+            .git()
+            .args([operation, "--continue"])
+            .env("GIT_EDITOR", "true")
+            .logged_output()
+            .map_err(|e| GitError::io(format!("continue {operation}"), e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command(
+                format!("{operation} --continue"),
+                &output,
+            ));
+        }
+        Ok(if operation == "rebase" {
+            Msg::RebaseCompleted.into()
+        } else {
+            Msg::MergeCompleted.into()
+        })
+    }
+
+    /// Working tree vs index. Untracked files are shown as entirely added.
+    pub fn get_unstaged_diff(repo: &RepoContext, file_path: &str) -> Result<String, GitError> {
+        Self::unstaged_diff(repo, file_path, DiffOptions::default(), usize::MAX)
+    }
+
+    fn unstaged_diff(
+        repo: &RepoContext,
+        file_path: &str,
+        options: DiffOptions,
+        max_lines: usize,
+    ) -> Result<String, GitError> {
+        let diff = Self::run_file_diff(repo, &[], file_path, options, max_lines)?;
+        if is_binary_diff(&diff) {
+            return Self::describe_binary_change(repo, file_path, DiffView::Unstaged);
+        }
+        if !diff.trim().is_empty() {
+            return Ok(diff);
+        }
+        if Self::is_tracked(repo, file_path)? {
+            return Ok(no_changes("No unstaged changes", options));
+        }
+
+        let path = repo.path(file_path);
+        if path.is_dir() {
+            return Ok(format!("Untracked directory: {file_path}"));
+        }
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                let content = format_new_file(file_path, &bytes);
+                Ok(content
+                    .lines()
+                    .take(max_lines)
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok("No changes to preview".to_string())
+            },
+            Err(e) => Err(GitError::io("read file", e)),
+        }
+    }
+
+    /// Index vs HEAD, i.e. what the next commit will contain
+    pub fn get_staged_diff(repo: &RepoContext, file_path: &str) -> Result<String, GitError> {
+        Self::staged_diff(repo, file_path, DiffOptions::default(), usize::MAX)
+    }
+
+    fn staged_diff(
+        repo: &RepoContext,
+        file_path: &str,
+        options: DiffOptions,
+        max_lines: usize,
+    ) -> Result<String, GitError> {
+        let diff = Self::run_file_diff(repo, &["--cached"], file_path, options, max_lines)?;
+        if is_binary_diff(&diff) {
+            Self::describe_binary_change(repo, file_path, DiffView::Staged)
+        } else if diff.trim().is_empty() {
+            Ok(no_changes("No staged changes", options))
+        } else {
+            Ok(diff)
+        }
+    }
+
+    /// Stands in for git's `Binary files ... differ`: the type and size of both copies, the
+    /// dimensions of images and how much they changed
+    fn describe_binary_change(
+        repo: &RepoContext,
+        file_path: &str,
+        view: DiffView,
+    ) -> Result<String, GitError> {
+        let (before, after) = view.versions();
+        let old = Self::get_file_bytes(repo, file_path, before)?;
+        let new = Self::get_file_bytes(repo, file_path, after)?;
+        let line = |label: &str, description: String| format!("\n  {label:<13} {description}");
+        let side = |version: FileVersion, bytes: Option<&[u8]>| {
+            line(
+                &format!("{}:", version.name()),
+                bytes.map_or_else(|| "(does not exist)".to_string(), binary::describe),
+            )
+        };
+        let mut text = format!("Binary file changed: {file_path}");
+        text.push_str(&side(before, old.as_deref()));
+        text.push_str(&side(after, new.as_deref()));
+        if let (Some(old), Some(new)) = (&old, &new) {
+            text.push_str(&line("change:", binary::describe_change(old, new)));
+        }
+        text.push_str(new.as_ref().map_or("", |new| hex_dump_hint(new.len())));
+        Ok(text)
+    }
+
+    /// Line counts of the changed files on one side, for the file list. An empty `paths`
+    /// means every file.
+    pub fn get_diff_stats(
+        repo: &RepoContext,
+        view: DiffView,
+        paths: &[String],
+    ) -> Result<HashMap<String, DiffStat>, GitError> {
+        let mut command = repo.git();
+        command.args(["diff", "--numstat", "-z"]);
+        if view == DiffView::Staged {
+            command.arg("--cached");
+        }
+        if !paths.is_empty() {
+            command
+                .env("GIT_LITERAL_PATHSPECS", "1")
+                .arg("--")
+                .args(paths);
+        }
+        let output = command
+            .logged_output()
+            .map_err(|e| GitError::io("get diff stats", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("diff", &output));
+        }
+        Ok(DiffStat::parse_numstat(&output.stdout))
+    }
+
+    /// `git diff --cached --stat` of `paths`: one line per file and a total, showing what a
+    /// commit of them contains. With `all` the working tree is compared to HEAD instead, as
+    /// `git commit -a` stages it.
+    pub fn get_staged_summary(
+        repo: &RepoContext,
+        paths: &[String],
+        all: bool,
+    ) -> Result<String, GitError> {
+        let mut command = repo.git();
+        command.env("GIT_LITERAL_PATHSPECS", "1").arg("diff");
+        if all && Self::has_head(repo) {
+            command.arg("HEAD");
+        } else {
+            command.arg("--cached");
+        }
+        let output = command
+            .args(["--stat", "--no-color", "-M", "--"])
+            .args(paths)
+            .logged_output()
+            .map_err(|e| GitError::io("get staged summary", e))?;
+        if !output.status.success() {
+            return Err(GitError::command("diff", &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    pub fn get_diff(
+        repo: &RepoContext,
+        file_path: &str,
+        view: DiffView,
+        options: DiffOptions,
+    ) -> Result<String, GitError> {
+        Self::get_diff_head(repo, file_path, view, options, usize::MAX)
+    }
+
+    /// The first `max_lines` lines of `get_diff`. git is stopped once enough has been read,
+    /// so a page of a multi-megabyte diff is as quick to load as a small diff.
+    pub fn get_diff_head(
+        repo: &RepoContext,
+        file_path: &str,
+        view: DiffView,
+        options: DiffOptions,
+        max_lines: usize,
+    ) -> Result<String, GitError> {
+        match view {
+            DiffView::Unstaged => Self::unstaged_diff(repo, file_path, options, max_lines),
+            DiffView::Staged => Self::staged_diff(repo, file_path, options, max_lines),
+        }
+    }
+
+    /// The raw diff of one file for line staging. Untracked files have no diff against the
+    /// index and have to be staged as a whole.
+    pub fn get_patch(
+        repo: &RepoContext,
+        file_path: &str,
+        view: DiffView,
+    ) -> Result<FilePatch, GitError> {
+        let mut args = vec!["diff", "--no-color", "--no-ext-diff"];
+        if view == DiffView::Staged {
+            args.push("--cached");
+        }
+        args.extend(["--", file_path]);
+        let diff = Self::run_diff(repo, &args)?;
+        if diff.trim().is_empty()
+            && view == DiffView::Unstaged
+            && !Self::is_tracked(repo, file_path)?
+        {
+            return Err(GitError::Invalid(
+                "Untracked files can only be staged as a whole; add them with intent to add first"
+                    .to_string(),
+            ));
+        }
+        Ok(FilePatch::parse(&diff))
+    }
+
+    /// Stages the selected lines of an unstaged patch, or unstages the selected lines of a
+    /// staged one, through `git apply --cached`
+    pub fn stage_lines(
+        repo: &RepoContext,
+        patch: &FilePatch,
+        selected: &BTreeSet<usize>,
+        view: DiffView,
+    ) -> Result<Message, GitError> {
+        let reverse = view == DiffView::Staged;
+        let content = patch.build(selected, reverse).map_err(GitError::Invalid)?;
+
+        let mut command = repo.git();
+        command.args(["apply", "--cached", "--whitespace=nowarn"]);
+        if reverse {
+            command.arg("--reverse");
+        }
+        let started = Instant::now();
+        let mut child = command
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| GitError::io("apply patch", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(content.as_bytes())
+                .map_err(|e| GitError::io("apply patch", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| GitError::io("apply patch", e))?;
+        command_log::record(CommandRecord::new(
+            &command,
+            started,
+            output.status.code(),
+            &String::from_utf8_lossy(&output.stderr),
+        ));
+
+        if !output.status.success() {
+            return Err(GitError::command("apply", &output));
+        }
+        let msg = if reverse {
+            Msg::UnstagedLines
+        } else {
+            Msg::StagedLines
+        };
+        Ok(Message::new(msg, &[&selected.len()]))
+    }
+
+    /// `git diff [extra] [options] -- file_path`, read up to `max_lines` lines
+    fn run_file_diff(
+        repo: &RepoContext,
+        extra: &[&str],
+        file_path: &str,
+        options: DiffOptions,
+        max_lines: usize,
+    ) -> Result<String, GitError> {
+        let option_args = options.args();
+        let mut args = vec!["diff"];
+        args.extend(extra);
+        args.extend(option_args.iter().map(String::as_str));
+        args.extend(["--", file_path]);
+        Self::run_diff_head(repo, &args, max_lines)
+    }
+
+    /// Runs a diff command and reads at most `max_lines` lines of its output
+    fn run_diff_head(
+        repo: &RepoContext,
+        args: &[&str],
+        max_lines: usize,
+    ) -> Result<String, GitError> {
+        let started = Instant::now();
+        let mut command = repo.git();
+        let mut child = command
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| GitError::io("get diff", e))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| GitError::Parse("Failed to read diff output".to_string()))?;
+
+        let mut reader = BufReader::new(stdout);
+        let mut diff = Vec::new();
+        let mut lines = 0;
+        while lines < max_lines {
+            let read = reader
+                .read_until(b'\n', &mut diff)
+                .map_err(|e| GitError::io("read diff", e))?;
+            if read == 0 {
+                break;
+            }
+            lines += 1;
+        }
+        if lines == max_lines {
+            // The rest is not needed; git may still be writing it
+            child.kill().ok();
+            let code = child.wait().ok().and_then(|status| status.code());
+            command_log::record(CommandRecord::new(&command, started, code, ""));
+            return Ok(String::from_utf8_lossy(&diff).into_owned());
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| GitError::io("get diff", e))?;
+        command_log::record(CommandRecord::new(
+            &command,
+            started,
+            output.status.code(),
+            &String::from_utf8_lossy(&output.stderr),
+        ));
+        if !output.status.success() {
+            return Err(GitError::command("diff", &output));
+        }
+        Ok(String::from_utf8_lossy(&diff).into_owned())
+    }
+
+    fn run_diff(repo: &RepoContext, args: &[&str]) -> Result<String, GitError> {
+        let output = repo
+            .git()
+            .args(args)
+            .logged_output()
+            .map_err(|e| GitError::io("get diff", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("diff", &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn is_tracked(repo: &RepoContext, path: &str) -> Result<bool, GitError> {
+        Ok(repo
+            .git()
+            .args(["ls-files", "--error-unmatch", "--", path])
+            .logged_output()
+            .map_err(|e| GitError::io("check file", e))?
+            .status
+            .success())
+    }
+
+    /// Runs a network operation (push/pull/fetch). Terminal prompts are disabled because
+    /// the TUI owns the terminal in raw mode and a credential prompt would hang the app;
+    /// credentials and SSH passphrases are asked for in the TUI through `askpass` instead.
+    /// Progress lines git writes to stderr are passed to `on_progress` as they arrive.
+    fn run_remote_command(
+        repo: &RepoContext,
+        args: &[&str],
+        action: &str,
+        on_progress: &dyn Fn(u8),
+    ) -> Result<String, GitError> {
+        let mut command = repo.git();
+        command
+            .args(args)
+            .arg("--progress")
+            .env("GIT_TERMINAL_PROMPT", "0");
+        askpass::configure(&mut command);
+        Self::run_streaming(command, action, on_progress)
+    }
+
+    /// Runs a long command, passing progress percentages to `on_progress` and publishing
+    /// its stderr to the command log while it runs
+    fn run_streaming(
+        command: Command,
+        action: &str,
+        on_progress: &dyn Fn(u8),
+    ) -> Result<String, GitError> {
+        Self::run_streaming_with(command, action, &mut parse_progress_percent, on_progress)
+    }
+
+    /// Like `run_streaming`, with `progress_of` telling which stderr lines report progress
+    /// and how far along they say the command is
+    fn run_streaming_with(
+        mut command: Command,
+        action: &str,
+        progress_of: &mut dyn FnMut(&str) -> Option<u8>,
+        on_progress: &dyn Fn(u8),
+    ) -> Result<String, GitError> {
+        let started = Instant::now();
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| GitError::io(action, e))?;
+
+        // Read stdout on its own thread so a full pipe cannot block git
+        let stdout = child.stdout.take();
+        let stdout_reader = std::thread::spawn(move || {
+            let mut text = String::new();
+            if let Some(mut stdout) = stdout {
+                let _ = stdout.read_to_string(&mut text);
+            }
+            text
+        });
+
+        // Progress lines are terminated by '\r' while they update, so split on both
+        let mut messages = String::new();
+        if let Some(stderr) = child.stderr.take() {
+            let mut handle_line = |line: &[u8]| {
+                let line = String::from_utf8_lossy(line);
+                if let Some(percent) = progress_of(&line) {
+                    on_progress(percent);
+                    // Only the latest state of a progress line is shown
+                    let output = format!("{messages}{}", line.trim());
+                    command_log::update_running(&command, started, &output);
+                } else if !line.trim().is_empty() {
+                    messages.push_str(&line);
+                    messages.push('\n');
+                    command_log::update_running(&command, started, &messages);
+                }
+            };
+            let mut line = Vec::new();
+            for byte in BufReader::new(stderr).bytes() {
+                let Ok(byte) = byte else { break };
+                if byte == b'\r' || byte == b'\n' {
+                    handle_line(&line);
+                    line.clear();
+                } else {
+                    line.push(byte);
+                }
+            }
+            handle_line(&line);
+        }
+
+        let status = child.wait().map_err(|e| GitError::io(action, e))?;
+        let stdout = stdout_reader.join().unwrap_or_default();
+        command_log::record(CommandRecord::new(
+            &command,
+            started,
+            status.code(),
+            &messages,
+        ));
+
+        if !status.success() {
+            return Err(GitError::CommandFailed {
+                command: action.to_string(),
+                code: status.code(),
+                stderr: messages.trim().to_string(),
+            });
+        }
+
+        // git reports the outcome of remote operations on stderr even on success
+        let mut result = stdout.trim().to_string();
+        if result.is_empty() {
+            result = messages.trim().to_string();
+        }
+        Ok(result)
+    }
+
+    pub fn push(repo: &RepoContext, on_progress: &dyn Fn(u8)) -> Result<Message, GitError> {
+        let result = Self::run_remote_command(repo, &["push"], "push", on_progress)?;
+        if result.contains("Everything up-to-date") {
+            Ok(Msg::EverythingUpToDate.into())
+        } else {
+            Ok(Msg::Pushed.into())
+        }
+    }
+
+    /// `git push --set-upstream <remote> <branch>`, for a branch that was never pushed
+    pub fn push_set_upstream(
+        repo: &RepoContext,
+        remote: &str,
+        branch: &str,
+        on_progress: &dyn Fn(u8),
+    ) -> Result<Message, GitError> {
+        Self::run_remote_command(
+            repo,
+            &["push", "--set-upstream", remote, branch],
+            "push",
+            on_progress,
+        )?;
+        Ok(Message::new(Msg::PushedSetUpstream, &[&remote, &branch]))
+    }
+
+    /// `git push --force-with-lease`: overwrites the upstream branch, unless it moved since
+    /// it was last fetched
+    pub fn force_push(repo: &RepoContext, on_progress: &dyn Fn(u8)) -> Result<Message, GitError> {
+        let result =
+            Self::run_remote_command(repo, &["push", "--force-with-lease"], "push", on_progress)?;
+        if result.contains("Everything up-to-date") {
+            Ok(Msg::EverythingUpToDate.into())
+        } else {
+            Ok(Msg::ForcePushed.into())
+        }
+    }
+
+    /// Names of the configured remotes, e.g. `origin`
+    pub fn get_remotes(repo: &RepoContext) -> Result<Vec<String>, GitError> {
+        let output = repo
+            .git()
+            .arg("remote")
+            .logged_output()
+            .map_err(|e| GitError::io("list remotes", e))?;
+        if !output.status.success() {
+            return Err(GitError::command("remote", &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Object counts and sizes of the repository, and when it was last repacked
+    pub fn get_repo_stats(repo: &RepoContext) -> Result<RepoStats, GitError> {
+        let output = repo
+            .git()
+            .args(["count-objects", "-v"])
+            .logged_output()
+            .map_err(|e| GitError::io("count objects", e))?;
+        if !output.status.success() {
+            return Err(GitError::command("count-objects", &output));
+        }
+        let mut stats = RepoStats::parse(&String::from_utf8_lossy(&output.stdout));
+        // Repacking rewrites the pack list, which nothing else touches
+        stats.last_gc = Self::git_path(repo, "objects/info/packs")
+            .ok()
+            .and_then(|path| std::fs::metadata(path).ok())
+            .and_then(|metadata| metadata.modified().ok());
+        Ok(stats)
+    }
+
+    /// Runs a housekeeping command, publishing its output to the command log as it goes
+    pub fn run_maintenance(
+        repo: &RepoContext,
+        task: MaintenanceTask,
+        on_progress: &dyn Fn(u8),
+    ) -> Result<Message, GitError> {
+        let mut command = repo.git();
+        // Progress is otherwise held back for the first two seconds of each step
+        command.args(task.args()).env("GIT_PROGRESS_DELAY", "0");
+        Self::run_streaming(command, task.name(), on_progress)?;
+        Ok(Message::new(Msg::MaintenanceFinished, &[&task.name()]))
+    }
+
+    /// `git archive` of `target` into the file `output`, in the format its name ends with.
+    /// Progress counts the entries `--verbose` lists against those in the tree.
+    pub fn archive(
+        repo: &RepoContext,
+        target: &str,
+        output: &Path,
+        on_progress: &dyn Fn(u8),
+    ) -> Result<Message, GitError> {
+        let format = archive_format(&output.to_string_lossy()).ok_or_else(|| {
+            GitError::Invalid(String::from("Name the archive .zip, .tar, .tar.gz or .tgz"))
+        })?;
+        let tree = repo
+            .git()
+            .args(["ls-tree", "-r", "-t", "--name-only", target, "--"])
+            .logged_output()
+            .map_err(|e| GitError::io("list archive entries", e))?;
+        if !tree.status.success() {
+            return Err(GitError::command("ls-tree", &tree));
+        }
+        let total = tree.stdout.split(|&b| b == b'\n').count().max(1);
+
+        let mut command = repo.git();
+        command
+            .args([
+                "archive",
+                "--verbose",
+                &format!("--format={format}"),
+                "--output",
+            ])
+            .arg(output)
+            .args([target, "--"]);
+        let mut listed = 0;
+        let mut progress_of = |line: &str| {
+            if line.is_empty()
+                || ["fatal:", "error:", "warning:"]
+                    .iter()
+                    .any(|p| line.starts_with(p))
+            {
+                return None;
+            }
+            listed += 1;
+            u8::try_from((listed * 100 / total).min(100)).ok()
+        };
+        Self::run_streaming_with(command, "archive", &mut progress_of, on_progress)?;
+        let size = std::fs::metadata(output).map_or(0, |metadata| metadata.len());
+        Ok(Message::new(
+            Msg::Exported,
+            &[
+                &target,
+                &output.display(),
+                &binary::format_size(usize::try_from(size).unwrap_or(usize::MAX)),
+            ],
+        ))
+    }
+
+    /// URL of `remote` as configured, with any `insteadOf` rewrite applied
+    pub fn get_remote_url(repo: &RepoContext, remote: &str) -> Result<String, GitError> {
+        let output = repo
+            .git()
+            .args(["remote", "get-url", remote])
+            .logged_output()
+            .map_err(|e| GitError::io(format!("get the URL of {remote}"), e))?;
+        if !output.status.success() {
+            return Err(GitError::command("remote get-url", &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
 
-impl GitOperations {
-    pub const fn new() -> Self {
-        Self
+    /// Full hash of a commit, e.g. of an abbreviated one from the log
+    pub fn resolve_commit(repo: &RepoContext, rev: &str) -> Result<String, GitError> {
+        let output = repo
+            .git()
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("{rev}^{{commit}}"))
+            .logged_output()
+            .map_err(|e| GitError::io(format!("resolve {rev}"), e))?;
+        if !output.status.success() {
+            return Err(GitError::Invalid(format!("'{rev}' is not a commit")));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
 
-    pub fn get_status() -> Result<Vec<String>, String> {
-        let output = Command::new("git")
-            .args(["status", "--porcelain"])
-            .output()
-            .map_err(|e| {
-                format!(
-                    "Failed to run git status: {}. Are you in a git repository?",
-                    e
-                )
-            })?;
+    /// The strategy plain `git pull` uses in this repository
+    pub fn get_pull_strategy(repo: &RepoContext) -> PullStrategy {
+        let config = |key: &str| {
+            repo.git()
+                .args(["config", "--get", key])
+                .logged_output()
+                .map(|output| {
+                    String::from_utf8_lossy(&output.stdout)
+                        .trim()
+                        .to_lowercase()
+                })
+                .unwrap_or_default()
+        };
+        PullStrategy::from_config(&config("pull.rebase"), &config("pull.ff"))
+    }
+
+    pub fn pull(
+        repo: &RepoContext,
+        strategy: PullStrategy,
+        on_progress: &dyn Fn(u8),
+    ) -> Result<Message, GitError> {
+        let result =
+            Self::run_remote_command(repo, &["pull", strategy.flag()], "pull", on_progress)?;
+        if result.contains("Already up to date") {
+            Ok(Msg::AlreadyUpToDate.into())
+        } else {
+            Ok(Msg::Pulled.into())
+        }
+    }
+
+    /// `git fetch`; with `prune`, remote-tracking branches deleted on the remote go too,
+    /// which leaves local branches tracking them with a gone upstream
+    pub fn fetch(
+        repo: &RepoContext,
+        prune: bool,
+        on_progress: &dyn Fn(u8),
+    ) -> Result<Message, GitError> {
+        if prune {
+            Self::run_remote_command(repo, &["fetch", "--prune"], "fetch", on_progress)?;
+            Ok(Msg::FetchedAndPruned.into())
+        } else {
+            Self::run_remote_command(repo, &["fetch"], "fetch", on_progress)?;
+            Ok(Msg::Fetched.into())
+        }
+    }
+
+    /// Short hash and subject of HEAD and the number of commits in its history; `None`
+    /// before the first commit
+    pub fn get_head_summary(repo: &RepoContext) -> Result<Option<HeadSummary>, GitError> {
+        if !Self::has_head(repo) {
+            return Ok(None);
+        }
+        let head = repo
+            .git()
+            .args(["log", "-1", "--format=%h%x09%s", "HEAD", "--"])
+            .logged_output()
+            .map_err(|e| GitError::io("read HEAD", e))?;
+        if !head.status.success() {
+            return Err(GitError::command("log", &head));
+        }
+        let count = repo
+            .git()
+            .args(["rev-list", "--count", "HEAD", "--"])
+            .logged_output()
+            .map_err(|e| GitError::io("count commits", e))?;
+        if !count.status.success() {
+            return Err(GitError::command("rev-list", &count));
+        }
+        let line = String::from_utf8_lossy(&head.stdout);
+        HeadSummary::parse(&line, &String::from_utf8_lossy(&count.stdout))
+            .map(Some)
+            .ok_or_else(|| GitError::Parse(format!("Unexpected git log output: {}", line.trim())))
+    }
+
+    /// Returns `(ahead, behind)` relative to the upstream branch, or `None` when the
+    /// current branch has no upstream configured.
+    pub fn get_ahead_behind(repo: &RepoContext) -> Result<Option<(usize, usize)>, GitError> {
+        let output = repo
+            .git()
+            .args(["rev-list", "--left-right", "--count", "HEAD...@{upstream}"])
+            .logged_output()
+            .map_err(|e| GitError::io("get ahead/behind", e))?;
 
         if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Git status failed: {}", error.trim()));
+            return Ok(None);
         }
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        Ok(output_str.lines().map(String::from).collect())
+        Ok(Self::parse_ahead_behind(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    /// Upstream name plus ahead/behind counts, or `None` when the current branch does not
+    /// track anything (or HEAD is detached)
+    pub fn get_upstream_status(repo: &RepoContext) -> Result<Option<UpstreamStatus>, GitError> {
+        let output = repo
+            .git()
+            .args([
+                "rev-parse",
+                "--abbrev-ref",
+                "--symbolic-full-name",
+                "@{upstream}",
+            ])
+            .logged_output()
+            .map_err(|e| GitError::io("get upstream", e))?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(
+            Self::get_ahead_behind(repo)?.map(|(ahead, behind)| UpstreamStatus {
+                name,
+                ahead,
+                behind,
+            }),
+        )
     }
 
-    pub fn stage_file(file_status: &str) -> Result<String, String> {
-        if file_status.len() < 3 {
-            return Err("Invalid file status format".to_string());
+    /// `None` unless a `git bisect` is in progress
+    pub fn get_bisect_status(repo: &RepoContext) -> Result<Option<BisectStatus>, GitError> {
+        if !Self::git_path(repo, "BISECT_START")?.is_file() {
+            return Ok(None);
+        }
+        let refs = repo
+            .git()
+            .args(["for-each-ref", "--format=%(refname)", "refs/bisect/"])
+            .logged_output()
+            .map_err(|e| GitError::io("list bisect refs", e))?;
+        let refs = String::from_utf8_lossy(&refs.stdout);
+        let good: Vec<&str> = refs
+            .lines()
+            .filter(|name| name.starts_with("refs/bisect/good-"))
+            .collect();
+        if good.is_empty() || !refs.lines().any(|name| name == "refs/bisect/bad") {
+            return Ok(Some(BisectStatus::Waiting));
         }
 
-        // Git status format: XY filename where X and Y are status codes
-        let chars: Vec<char> = file_status.chars().collect();
-        if chars.len() < 3 {
-            return Err("Invalid file status format".to_string());
+        let mut args = vec!["rev-list", "--bisect-vars", "refs/bisect/bad", "--not"];
+        args.extend(good);
+        let vars = repo
+            .git()
+            .args(&args)
+            .logged_output()
+            .map_err(|e| GitError::io("get bisect progress", e))?;
+        if !vars.status.success() {
+            return Err(GitError::command("rev-list", &vars));
         }
+        let head = repo
+            .git()
+            .args(["rev-parse", "HEAD"])
+            .logged_output()
+            .map_err(|e| GitError::io("get HEAD", e))?;
+        let head = String::from_utf8_lossy(&head.stdout);
+        Ok(BisectStatus::from_bisect_vars(
+            &String::from_utf8_lossy(&vars.stdout),
+            short_hash(head.trim()).to_string(),
+        ))
+    }
+
+    pub fn bisect_start(repo: &RepoContext) -> Result<Message, GitError> {
+        Self::run_bisect(repo, &["start"])?;
+        Ok(Msg::BisectStarted.into())
+    }
+
+    /// Marks `rev` (HEAD when `None`) as good
+    pub fn bisect_good(repo: &RepoContext, rev: Option<&str>) -> Result<Message, GitError> {
+        Self::bisect_mark(repo, "good", rev)
+    }
+
+    /// Marks `rev` (HEAD when `None`) as bad
+    pub fn bisect_bad(repo: &RepoContext, rev: Option<&str>) -> Result<Message, GitError> {
+        Self::bisect_mark(repo, "bad", rev)
+    }
+
+    /// Leaves `rev` (HEAD when `None`) out, e.g. because it does not build
+    pub fn bisect_skip(repo: &RepoContext, rev: Option<&str>) -> Result<Message, GitError> {
+        Self::bisect_mark(repo, "skip", rev)
+    }
+
+    fn bisect_mark(repo: &RepoContext, term: &str, rev: Option<&str>) -> Result<Message, GitError> {
+        let mut args = vec![term];
+        args.extend(rev);
+        Self::run_bisect(repo, &args)
+    }
+
+    /// Ends the bisect and returns to the branch it was started from
+    pub fn bisect_reset(repo: &RepoContext) -> Result<Message, GitError> {
+        Self::run_bisect(repo, &["reset"])?;
+        Ok(Msg::BisectEnded.into())
+    }
+
+    /// Runs `git bisect <args>` and returns its first line of output, e.g.
+    /// `Bisecting: 3 revisions left to test after this (roughly 2 steps)`
+    fn run_bisect(repo: &RepoContext, args: &[&str]) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .arg("bisect")
+            .args(args)
+            .logged_output()
+            .map_err(|e| GitError::io("run git bisect", e))?;
 
-        let status_chars: String = chars.iter().take(2).collect();
-        let file_path: String = chars.iter().skip(2).collect::<String>().trim().to_string();
+        if !output.status.success() {
+            return Err(GitError::command("bisect", &output));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let summary = stdout.lines().map(str::trim).find(|line| !line.is_empty());
+        Ok(summary.map_or_else(
+            || Msg::BisectUpdated.into(),
+            |summary| Message::new(Msg::BisectStep, &[&summary]),
+        ))
+    }
 
-        // Check if file is staged (first character is not space)
-        let is_staged = !status_chars.chars().next().unwrap_or(' ').is_whitespace();
-        let cmd = if is_staged { "reset" } else { "add" };
+    fn parse_ahead_behind(output: &str) -> Option<(usize, usize)> {
+        let mut counts = output.split_whitespace().map(str::parse::<usize>);
+        match (counts.next(), counts.next()) {
+            (Some(Ok(ahead)), Some(Ok(behind))) => Some((ahead, behind)),
+            _ => None,
+        }
+    }
 
-        let output = Command::new("git")
-            .args([cmd, "--", &file_path])
-            .output()
-            .map_err(|e| format!("Failed to {} file: {}", cmd, e))?;
+    pub fn get_log(repo: &RepoContext, max_count: usize) -> Result<Vec<String>, GitError> {
+        let output = repo
+            .git()
+            .args([
+                "log",
+                "--oneline",
+                "--graph",
+                "--decorate",
+                &format!("--max-count={max_count}"),
+            ])
+            .logged_output()
+            .map_err(|e| GitError::io("get log", e))?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Git {} failed: {}", cmd, error.trim()));
+            // A freshly initialised repository has no commits yet
+            if error.contains("does not have any commits") {
+                return Ok(Vec::new());
+            }
+            return Err(GitError::command("log", &output));
         }
 
-        Ok(format!(
-            "✓ {} file: {}",
-            if is_staged { "Unstaged" } else { "Staged" },
-            &file_path
-        ))
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        Ok(output_str.lines().map(String::from).collect())
     }
 
-    pub fn stage_all_files(files: &[String]) -> Result<String, String> {
-        // Check if any file is unstaged (first character is space)
-        let has_unstaged = files
-            .iter()
-            .any(|f| f.len() >= 2 && f.chars().next().unwrap_or(' ').is_whitespace());
+    /// Full content of `path` as of HEAD, in the index or in the working tree. `None` when the
+    /// file does not exist in that version, e.g. a new file at HEAD.
+    pub fn get_file_version(
+        repo: &RepoContext,
+        path: &str,
+        version: FileVersion,
+    ) -> Result<Option<String>, GitError> {
+        let Some(bytes) = Self::get_file_bytes(repo, path, version)? else {
+            return Ok(None);
+        };
+        if binary::is_binary(&bytes) {
+            return Ok(Some(format!(
+                "Binary file: {}{}",
+                binary::describe(&bytes),
+                hex_dump_hint(bytes.len())
+            )));
+        }
+        Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+    }
 
-        if has_unstaged {
-            let output = Command::new("git")
-                .args(["add", "."])
-                .output()
-                .map_err(|e| format!("Failed to stage all files: {}", e))?;
+    /// The raw bytes behind `get_file_version`
+    pub fn get_file_bytes(
+        repo: &RepoContext,
+        path: &str,
+        version: FileVersion,
+    ) -> Result<Option<Vec<u8>>, GitError> {
+        let bytes = match version.revision(path) {
+            Some(revision) => {
+                let exists = repo
+                    .git()
+                    .args(["cat-file", "-e", &revision])
+                    .logged_output()
+                    .map_err(|e| GitError::io("read file", e))?
+                    .status
+                    .success();
+                if !exists {
+                    return Ok(None);
+                }
+                let output = repo
+                    .git()
+                    .args(["cat-file", "blob", &revision])
+                    .logged_output()
+                    .map_err(|e| GitError::io("read file", e))?;
+                if !output.status.success() {
+                    return Err(GitError::command("cat-file", &output));
+                }
+                output.stdout
+            },
+            None if repo.path(path).is_dir() => {
+                return Err(GitError::Invalid(format!("{path} is a directory")))
+            },
+            None => match std::fs::read(repo.path(path)) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(GitError::io("read file", e)),
+            },
+        };
+        Ok(Some(bytes))
+    }
 
-            if !output.status.success() {
-                let error = String::from_utf8_lossy(&output.stderr);
-                return Err(format!("Git add failed: {}", error.trim()));
+    /// Abbreviated hashes of the commits in the log that match `query`, newest first. They
+    /// are abbreviated like the hashes of `get_log`, so the two can be compared.
+    pub fn search_log(
+        repo: &RepoContext,
+        query: &LogQuery,
+        max_count: usize,
+    ) -> Result<Vec<String>, GitError> {
+        let output = repo
+            .git()
+            .args(["log", "--format=%h", &format!("--max-count={max_count}")])
+            .args(query.args())
+            .logged_output()
+            .map_err(|e| GitError::io("search log", e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            if error.contains("does not have any commits") {
+                return Ok(Vec::new());
             }
-            Ok("✓ All files staged".to_string())
-        } else {
-            let output = Command::new("git")
-                .args(["reset"])
-                .output()
-                .map_err(|e| format!("Failed to unstage all files: {}", e))?;
+            return Err(GitError::command("log", &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect())
+    }
 
-            if !output.status.success() {
-                let error = String::from_utf8_lossy(&output.stderr);
-                return Err(format!("Git reset failed: {}", error.trim()));
+    /// The latest `max_count` movements of HEAD, newest first
+    pub fn get_reflog(repo: &RepoContext, max_count: usize) -> Result<Vec<ReflogEntry>, GitError> {
+        let output = repo
+            .git()
+            .args([
+                "reflog",
+                "show",
+                "--format=%h%x09%gd%x09%gs",
+                &format!("--max-count={max_count}"),
+                "HEAD",
+                "--",
+            ])
+            .logged_output()
+            .map_err(|e| GitError::io("get reflog", e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            // HEAD has never pointed at a commit in a fresh repository
+            if error.contains("unknown revision") {
+                return Ok(Vec::new());
             }
-            Ok("✓ All files unstaged".to_string())
+            return Err(GitError::command("reflog", &output));
         }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        Ok(output_str.lines().filter_map(ReflogEntry::parse).collect())
     }
 
-    pub fn stash_changes(message: Option<&str>) -> Result<String, String> {
-        let mut args = vec!["stash", "push"];
+    /// `None` while a branch is checked out. Run on every refresh, so it is a couple of cheap
+    /// commands only when HEAD is detached.
+    pub fn get_detached_head(repo: &RepoContext) -> Result<Option<DetachedHead>, GitError> {
+        let symbolic = repo
+            .git()
+            .args(["symbolic-ref", "--quiet", "HEAD"])
+            .logged_output()
+            .map_err(|e| GitError::io("check HEAD", e))?;
+        if symbolic.status.success() {
+            return Ok(None);
+        }
 
-        if let Some(msg) = message {
-            args.push("-m");
-            args.push(msg);
+        let unreachable = repo
+            .git()
+            .args(["rev-list", "--count", "HEAD", "--not", "--branches"])
+            .logged_output()
+            .map_err(|e| GitError::io("count unreachable commits", e))?;
+        if !unreachable.status.success() {
+            return Err(GitError::command("rev-list", &unreachable));
         }
+        let branches = repo
+            .git()
+            .args(["for-each-ref", "--format=%(refname:short)", "refs/heads"])
+            .logged_output()
+            .map_err(|e| GitError::io("list branches", e))?;
+        let branches = String::from_utf8_lossy(&branches.stdout);
+        let branches: BTreeSet<&str> = branches.lines().collect();
+        let reflog = Self::get_reflog(repo, DETACHED_REFLOG_COUNT)?;
+        Ok(Some(DetachedHead {
+            previous_branch: previous_branch(&reflog, |name| branches.contains(name))
+                .map(String::from),
+            unreachable: String::from_utf8_lossy(&unreachable.stdout)
+                .trim()
+                .parse()
+                .unwrap_or(0),
+        }))
+    }
 
-        let output = Command::new("git")
-            .args(&args)
-            .output()
-            .map_err(|_| "Failed to stash changes")?;
+    /// `git checkout <branch>`
+    pub fn checkout_branch(repo: &RepoContext, name: &str) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .args(["checkout", "--quiet", name, "--"])
+            .logged_output()
+            .map_err(|e| GitError::io("checkout", e))?;
 
-        let result = String::from_utf8_lossy(&output.stdout);
-        let error = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            return Err(GitError::command("checkout", &output));
+        }
+        Ok(Message::new(Msg::SwitchedBranch, &[&name]))
+    }
 
-        if !error.is_empty() {
-            Ok(format!("Stash error: {error}"))
-        } else if result.contains("No local changes to save") {
-            Ok("No changes to stash".to_string())
-        } else {
-            Ok(format!("Changes stashed: {result}"))
+    /// `git checkout -b`: a new branch at HEAD, checked out
+    pub fn create_branch(repo: &RepoContext, name: &str) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .args(["checkout", "--quiet", "-b", name])
+            .logged_output()
+            .map_err(|e| GitError::io("create branch", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("checkout", &output));
         }
+        Ok(Message::new(Msg::CreatedBranch, &[&name]))
     }
 
-    pub fn list_stashes() -> Result<String, String> {
-        let output = Command::new("git")
-            .args(["stash", "list"])
-            .output()
-            .map_err(|_| "Failed to list stashes")?;
+    /// Local branches followed by remote-tracking branches, each marked with whether HEAD
+    /// already contains it
+    pub fn get_branches(repo: &RepoContext) -> Result<Vec<BranchEntry>, GitError> {
+        let output = repo
+            .git()
+            .args(["for-each-ref", BRANCH_FORMAT, "refs/heads", "refs/remotes"])
+            .logged_output()
+            .map_err(|e| GitError::io("list branches", e))?;
 
-        let result = String::from_utf8_lossy(&output.stdout);
-        if result.is_empty() {
-            Ok("No stashes found".to_string())
+        if !output.status.success() {
+            return Err(GitError::command("for-each-ref", &output));
+        }
+
+        // Fails without a commit on HEAD, where nothing is merged yet
+        let merged = repo
+            .git()
+            .args([
+                "for-each-ref",
+                "--merged=HEAD",
+                "--format=%(refname:lstrip=2)",
+                "refs/heads",
+                "refs/remotes",
+            ])
+            .logged_output()
+            .map_err(|e| GitError::io("list merged branches", e))?;
+        let merged = String::from_utf8_lossy(&merged.stdout);
+        let merged: BTreeSet<&str> = merged.lines().collect();
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        Ok(output_str
+            .lines()
+            .filter_map(|line| line.strip_prefix('\0'))
+            .filter_map(BranchEntry::parse)
+            .map(|mut branch| {
+                branch.merged = merged.contains(branch.name.as_str());
+                branch
+            })
+            .collect())
+    }
+
+    /// `git branch -d`, or `-D` with `force`, which also deletes unmerged branches
+    pub fn delete_branch(repo: &RepoContext, name: &str, force: bool) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .args(["branch", if force { "-D" } else { "-d" }, name])
+            .logged_output()
+            .map_err(|e| GitError::io("delete branch", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("branch", &output));
+        }
+        Ok(Message::new(Msg::DeletedBranch, &[&name]))
+    }
+
+    /// `git branch -m`: renames a local branch, keeping its upstream and reflog
+    pub fn rename_branch(
+        repo: &RepoContext,
+        name: &str,
+        new_name: &str,
+    ) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .args(["branch", "-m", name, new_name])
+            .logged_output()
+            .map_err(|e| GitError::io("rename branch", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("branch", &output));
+        }
+        Ok(Message::new(Msg::RenamedBranch, &[&name, &new_name]))
+    }
+
+    /// `git branch --set-upstream-to`, or `--unset-upstream` when `upstream` is empty
+    pub fn set_branch_upstream(
+        repo: &RepoContext,
+        name: &str,
+        upstream: &str,
+    ) -> Result<Message, GitError> {
+        let set_upstream = format!("--set-upstream-to={upstream}");
+        let flag = if upstream.is_empty() {
+            "--unset-upstream"
         } else {
-            Ok(format!("Stashes:\n{result}"))
+            &set_upstream
+        };
+        let output = repo
+            .git()
+            .args(["branch", flag, name])
+            .logged_output()
+            .map_err(|e| GitError::io("set upstream", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("branch", &output));
         }
+        Ok(if upstream.is_empty() {
+            Message::new(Msg::UpstreamUnset, &[&name])
+        } else {
+            Message::new(Msg::UpstreamSet, &[&name, &upstream])
+        })
     }
 
-    pub fn apply_latest_stash() -> Result<String, String> {
-        let output = Command::new("git")
-            .args(["stash", "apply"])
-            .output()
-            .map_err(|_| "Failed to apply stash")?;
+    /// `git push <remote> --delete <branch>`
+    pub fn delete_remote_branch(
+        repo: &RepoContext,
+        remote: &str,
+        branch: &str,
+        on_progress: &dyn Fn(u8),
+    ) -> Result<Message, GitError> {
+        Self::run_remote_command(
+            repo,
+            &["push", remote, "--delete", branch],
+            "push",
+            on_progress,
+        )?;
+        Ok(Message::new(Msg::DeletedRemoteBranch, &[&branch, &remote]))
+    }
+
+    /// `git reset --hard`: moves the current branch to `target` and discards all changes
+    pub fn reset_hard(repo: &RepoContext, target: &str) -> Result<Message, GitError> {
+        Self::reset(repo, ResetMode::Hard, target)
+    }
+
+    /// `git reset --soft|--mixed|--hard`: moves the current branch to `target`
+    pub fn reset(repo: &RepoContext, mode: ResetMode, target: &str) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .args(["reset", "--quiet", mode.flag(), target, "--"])
+            .logged_output()
+            .map_err(|e| GitError::io("reset", e))?;
 
         if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            if error.contains("No stash entries found") || error.contains("No stash found") {
-                return Ok("No stash to apply".to_string());
-            }
-            return Err(format!("Failed to apply stash: {}", error.trim()));
+            return Err(GitError::command("reset", &output));
         }
-        Ok("✓ Latest stash applied successfully".to_string())
+        Ok(match mode {
+            ResetMode::Hard => Message::new(Msg::ResetHard, &[&target]),
+            ResetMode::Soft | ResetMode::Mixed => {
+                Message::new(Msg::ResetKeeping, &[&mode.flag(), &target])
+            },
+        })
     }
 
-    pub fn commit(message: &str) -> Result<String, String> {
-        let output = Command::new("git")
-            .args(["commit", "-m", message])
-            .output()
-            .map_err(|e| format!("Failed to commit: {}", e))?;
+    /// `git commit --fixup=<target>` of the staged changes, or with `all` of every tracked
+    /// change, for `rebase --autosquash` to fold into `target` later
+    pub fn commit_fixup(
+        repo: &RepoContext,
+        target: &str,
+        sign: bool,
+        all: bool,
+    ) -> Result<Message, GitError> {
+        let sign_flag = if sign { "-S" } else { "--no-gpg-sign" };
+        let mut command = repo.git();
+        command.args(["commit", "--quiet", sign_flag, &format!("--fixup={target}")]);
+        if all {
+            command.arg("--all");
+        }
+        let output = command
+            .logged_output()
+            .map_err(|e| GitError::io("commit", e))?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            if error.contains("nothing to commit") {
-                return Ok("Nothing to commit (no staged changes)".to_string());
+            if let Some(reason) = signing_failure(&error) {
+                return Err(GitError::Signing(reason));
             }
-            return Err(format!("Commit failed: {}", error.trim()));
+            return Err(GitError::command("commit --fixup", &output));
         }
+        Ok(Message::new(Msg::FixupCommitted, &[&target]))
+    }
 
-        let result = String::from_utf8_lossy(&output.stdout);
-        if result.contains("create mode")
-            || result.contains("delete mode")
-            || result.contains("file changed")
-        {
-            Ok(format!("✓ Committed successfully!\n{}", result.trim()))
-        } else {
-            Ok("✓ Committed successfully!".to_string())
+    /// `git rebase -i --autosquash` from the parent of `target`, or from the root commit,
+    /// taking the todo list as git writes it so that fixup commits are folded in without an
+    /// editor. `--autostash` keeps uncommitted changes out of the way.
+    pub fn autosquash(repo: &RepoContext, target: &str) -> Result<Message, GitError> {
+        let parent = format!("{target}^");
+        let has_parent = repo
+            .git()
+            .args(["rev-parse", "--verify", "--quiet", &parent])
+            .logged_output()
+            .is_ok_and(|output| output.status.success());
+        let output = repo
+            .git()
+            .args(["rebase", "--interactive", "--autosquash", "--autostash"])
+            .arg(if has_parent {
+                parent.as_str()
+            } else {
+                "--root"
+            })
+            .env("GIT_SEQUENCE_EDITOR", "true")
+            .env("GIT_EDITOR", "true")
+            .logged_output()
+            .map_err(|e| GitError::io("rebase", e))?;
+
+        if !output.status.success() {
+            return Err(GitError::command("rebase --autosquash", &output));
         }
+        Ok(Message::new(Msg::FixupsSquashed, &[&target]))
     }
 
-    pub fn get_current_branch() -> Result<String, String> {
-        let output = Command::new("git")
-            .args(["branch", "--show-current"])
-            .output()
-            .map_err(|e| format!("Failed to get branch: {}", e))?;
+    /// Checks out `target` as a detached HEAD
+    pub fn checkout_detached(repo: &RepoContext, target: &str) -> Result<Message, GitError> {
+        let output = repo
+            .git()
+            .args(["checkout", "--detach", target, "--"])
+            .logged_output()
+            .map_err(|e| GitError::io("checkout", e))?;
 
         if !output.status.success() {
-            return Ok("(no branch)".to_string());
+            return Err(GitError::command("checkout", &output));
         }
-
-        let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        Ok(if branch.is_empty() {
-            "(detached HEAD)".to_string()
-        } else {
-            branch
-        })
+        Ok(Message::new(Msg::CheckedOutDetached, &[&target]))
     }
 
-    pub fn get_repo_name() -> Result<String, String> {
-        let output = Command::new("git")
-            .args(["rev-parse", "--show-toplevel"])
-            .output()
-            .map_err(|e| format!("Failed to get repo path: {}", e))?;
+    pub fn get_commit_diff(repo: &RepoContext, commit_hash: &str) -> Result<String, GitError> {
+        let output = repo
+            .git()
+            .args(["show", "--stat", "--patch", commit_hash])
+            .logged_output()
+            .map_err(|e| GitError::io("show commit", e))?;
 
         if !output.status.success() {
-            return Ok("(no repository)".to_string());
+            return Err(GitError::command("show", &output));
         }
 
-        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        Ok(path.split('/').last().unwrap_or("repository").to_string())
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
-    pub fn get_file_diff(file_path: &str) -> Result<String, String> {
-        // First try to get diff for tracked files
-        let output = Command::new("git")
-            .args(["diff", "HEAD", "--", file_path])
-            .output()
-            .map_err(|e| format!("Failed to get diff: {}", e))?;
+    /// Metadata, signature status and changed files of `commit_hash`
+    pub fn get_commit_details(
+        repo: &RepoContext,
+        commit_hash: &str,
+    ) -> Result<CommitDetails, GitError> {
+        let output = repo
+            .git()
+            .args(["show", "-s", COMMIT_DETAILS_FORMAT, commit_hash, "--"])
+            .logged_output()
+            .map_err(|e| GitError::io("show commit", e))?;
+        if !output.status.success() {
+            return Err(GitError::command("show", &output));
+        }
+        let mut details = CommitDetails::parse(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| {
+                GitError::Parse(format!("Unexpected git show output for {commit_hash}"))
+            })?;
 
-        if output.status.success() {
-            let diff = String::from_utf8_lossy(&output.stdout);
-            if !diff.trim().is_empty() {
-                return Ok(diff.to_string());
+        let diff_tree = |format: &str| {
+            let output = repo
+                .git()
+                .args(["diff-tree", "-r", "-M", "--no-commit-id", "-z", format])
+                .args(details.tree_args())
+                .logged_output()
+                .map_err(|e| GitError::io("list changed files", e))?;
+            if output.status.success() {
+                Ok(output.stdout)
+            } else {
+                Err(GitError::command("diff-tree", &output))
             }
+        };
+        let mut files = CommitFile::parse_name_status(&diff_tree("--name-status")?);
+        let stats = DiffStat::parse_numstat(&diff_tree("--numstat")?);
+        for file in &mut files {
+            file.stat = stats.get(&file.path).copied();
         }
+        details.files = files;
+        Ok(details)
+    }
 
-        // If no diff from HEAD, try staged vs working directory
-        let output = Command::new("git")
-            .args(["diff", "--", file_path])
-            .output()
-            .map_err(|e| format!("Failed to get working diff: {}", e))?;
+    /// Diff of one file of a commit, against the first parent for merges
+    pub fn get_commit_file_diff(
+        repo: &RepoContext,
+        details: &CommitDetails,
+        file: &CommitFile,
+        options: DiffOptions,
+    ) -> Result<String, GitError> {
+        let output = repo
+            .git()
+            .env("GIT_LITERAL_PATHSPECS", "1")
+            .args(["diff-tree", "-r", "-M", "-p", "--no-commit-id"])
+            .args(options.args())
+            .args(details.tree_args())
+            .arg("--")
+            .args(file.old_path.iter())
+            .arg(&file.path)
+            .logged_output()
+            .map_err(|e| GitError::io("get diff", e))?;
+        if !output.status.success() {
+            return Err(GitError::command("diff-tree", &output));
+        }
+        let diff = String::from_utf8_lossy(&output.stdout).to_string();
+        if diff.is_empty() {
+            return Ok(no_changes("No changes", options));
+        }
+        Ok(diff)
+    }
 
-        if output.status.success() {
-            let diff = String::from_utf8_lossy(&output.stdout);
-            if !diff.trim().is_empty() {
-                return Ok(diff.to_string());
+    /// Files that differ between the two ends of `range` (`A..B` or `A...B`), as
+    /// `git diff --name-status` lists them
+    pub fn get_range_files(repo: &RepoContext, range: &str) -> Result<Vec<CommitFile>, GitError> {
+        let diff = |format: &str| {
+            let output = repo
+                .git()
+                .args(["diff", "-M", "-z", format, range, "--"])
+                .logged_output()
+                .map_err(|e| GitError::io("list changed files", e))?;
+            if output.status.success() {
+                Ok(output.stdout)
+            } else {
+                Err(GitError::command("diff", &output))
             }
+        };
+        let mut files = CommitFile::parse_name_status(&diff("--name-status")?);
+        let stats = DiffStat::parse_numstat(&diff("--numstat")?);
+        for file in &mut files {
+            file.stat = stats.get(&file.path).copied();
         }
+        Ok(files)
+    }
 
-        // If still no diff, try to show file content for untracked files
-        let output = Command::new("cat")
-            .arg(file_path)
-            .output()
-            .map_err(|e| format!("Failed to read file: {}", e))?;
+    /// `git diff <range>` of one file, or of every file when `file` is `None`
+    pub fn get_range_diff(
+        repo: &RepoContext,
+        range: &str,
+        file: Option<&CommitFile>,
+        options: DiffOptions,
+    ) -> Result<String, GitError> {
+        let mut command = repo.git();
+        command
+            .env("GIT_LITERAL_PATHSPECS", "1")
+            .args(["diff", "-M"])
+            .args(options.args())
+            .args([range, "--"]);
+        if let Some(file) = file {
+            command.args(file.old_path.iter()).arg(&file.path);
+        }
+        let output = command
+            .logged_output()
+            .map_err(|e| GitError::io("get diff", e))?;
+        if !output.status.success() {
+            return Err(GitError::command("diff", &output));
+        }
+        let diff = String::from_utf8_lossy(&output.stdout).to_string();
+        if diff.is_empty() {
+            return Ok(no_changes("No changes", options));
+        }
+        Ok(diff)
+    }
 
-        if output.status.success() {
-            let content = String::from_utf8_lossy(&output.stdout);
-            Ok(format!("New file content:\n{}", content))
-        } else {
-            Ok("No changes to preview".to_string())
+    /// Extracts the abbreviated commit hash from a `git log --oneline --graph` line.
+    /// Returns `None` for graph-only lines such as `|\` or `|/`.
+    pub fn extract_commit_hash(log_line: &str) -> Option<&str> {
+        let rest = log_line.trim_start_matches(|c: char| "*|/\\_ ".contains(c));
+        let graph = &log_line[..log_line.len() - rest.len()];
+        if !graph.contains('*') {
+            return None;
         }
+        let hash = rest.split_whitespace().next()?;
+        (hash.len() >= 4 && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some(hash)
     }
 }
 
@@ -261,14 +3811,17 @@ mod tests {
     #[test]
     fn test_git_operations_new() {
         let git_ops = GitOperations::new();
-        assert!(std::mem::size_of_val(&git_ops) == 0);
+        assert_eq!(std::mem::size_of_val(&git_ops), 0);
     }
 
     #[test]
     fn test_stage_file_invalid_format() {
-        let result = GitOperations::stage_file("M");
+        let result = GitOperations::stage_file(&RepoContext::current(), "M");
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid file status format"));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid file status format"));
     }
 
     #[test]
@@ -323,6 +3876,388 @@ mod tests {
         assert_eq!(args, vec!["stash", "push"]);
     }
 
+    #[test]
+    fn test_format_new_file() {
+        assert_eq!(
+            format_new_file("notes.txt", b"first\nsecond\n"),
+            "new file: notes.txt\n@@ -0,0 +1,2 @@\n+first\n+second\n"
+        );
+        assert_eq!(
+            format_new_file("logo.png", b"\x89PNG\r\n\x1a\n\0\0"),
+            "new file: logo.png\nBinary file: PNG image, 10 bytes\n\nPress x in the fullscreen preview (d) for a hex dump"
+        );
+    }
+
+    #[test]
+    fn test_signing_failure() {
+        let gpg = "error: gpg failed to sign the data\nfatal: failed to write commit object\n";
+        assert_eq!(
+            signing_failure(gpg),
+            Some("gpg failed to sign the data".to_string())
+        );
+
+        let missing_key = "gpg: skipped \"ABCD\": No secret key\n[GNUPG:] INV_SGNR 9 ABCD\nerror: gpg failed to sign the data\n";
+        assert_eq!(
+            signing_failure(missing_key),
+            Some("gpg: skipped \"ABCD\": No secret key".to_string())
+        );
+
+        assert_eq!(signing_failure("error: pathspec 'x' did not match"), None);
+    }
+
+    #[test]
+    fn test_file_entry_parse_and_sections() {
+        let partial = FileEntry::parse("MM src/main.rs").unwrap();
+        assert_eq!(partial.path, "src/main.rs");
+        assert!(partial.in_section(Section::Staged));
+        assert!(partial.in_section(Section::Unstaged));
+        assert!(!partial.in_section(Section::Untracked));
+
+        let untracked = FileEntry::parse("?? new.txt").unwrap();
+        assert!(untracked.is_untracked());
+        assert!(!untracked.is_staged());
+        assert!(!untracked.is_unstaged());
+        assert_eq!(untracked.status_code(), "??");
+
+        let modified = FileEntry::parse(" M lib.rs").unwrap();
+        assert!(!modified.is_staged());
+        assert!(modified.is_unstaged());
+
+        assert!(FileEntry::parse("M").is_none());
+        assert!(FileEntry::parse("M  ").is_none());
+    }
+
+    #[test]
+    fn test_ignore_candidates() {
+        assert_eq!(
+            ignore_candidates("logs/debug.log"),
+            vec!["/logs/debug.log", "*.log", "/logs/"]
+        );
+        assert_eq!(ignore_candidates("notes.txt"), vec!["/notes.txt", "*.txt"]);
+        assert_eq!(ignore_candidates("build/"), vec!["/build/"]);
+        assert_eq!(ignore_candidates("a/.env"), vec!["/a/.env", "/a/"]);
+    }
+
+    #[test]
+    fn test_parse_progress_percent() {
+        assert_eq!(
+            parse_progress_percent("Receiving objects:  45% (45/100), 1.20 MiB | 2 MiB/s"),
+            Some(45)
+        );
+        assert_eq!(
+            parse_progress_percent("Writing objects: 100% (3/3), 250 bytes, done."),
+            Some(100)
+        );
+        assert_eq!(parse_progress_percent("To /tmp/remote.git"), None);
+        assert_eq!(parse_progress_percent("rate is 5%"), Some(5));
+        assert_eq!(parse_progress_percent("x50%"), None);
+    }
+
+    #[test]
+    fn test_status_summary() {
+        let files: Vec<FileEntry> = ["MM a", "M  b", " M c", "?? d", "UU e"]
+            .iter()
+            .filter_map(|line| FileEntry::parse(line))
+            .collect();
+        assert_eq!(
+            StatusSummary::from_entries(&files),
+            StatusSummary {
+                staged: 2,
+                modified: 2,
+                untracked: 1,
+                conflicted: 1,
+            }
+        );
+        assert!(StatusSummary::from_entries(&[]).is_clean());
+    }
+
+    #[test]
+    fn test_parse_renamed_entry() {
+        let entry = FileEntry::parse("R  old name.txt -> new.txt").unwrap();
+        assert_eq!(entry.path, "new.txt");
+        assert_eq!(entry.orig_path.as_deref(), Some("old name.txt"));
+        assert_eq!(entry.display_path(), "old name.txt → new.txt");
+        assert!(entry.is_staged());
+
+        // Only rename and copy statuses carry an arrow
+        let plain = FileEntry::parse("?? a -> b").unwrap();
+        assert_eq!(plain.path, "a -> b");
+        assert_eq!(plain.orig_path, None);
+    }
+
+    #[test]
+    fn test_parse_porcelain_v2() {
+        let output = b"1 .M N... 100644 100644 100644 aaa aaa fi le.txt\0\
+2 R. N... 100644 100644 100644 aaa aaa R100 new \xe5\x90\x8d.txt\0old.txt\0\
+u UU N... 100644 100644 100644 100644 aaa bbb ccc both.txt\0\
+? dir/\0! ignored.log\0";
+        let entries = FileEntry::parse_porcelain_v2(output);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0], FileEntry::new("fi le.txt", ' ', 'M'));
+        assert_eq!(entries[1].path, "new 名.txt");
+        assert_eq!(entries[1].orig_path.as_deref(), Some("old.txt"));
+        assert_eq!(entries[1].status_code(), "R ");
+        assert!(entries[2].is_conflicted());
+        assert_eq!(entries[3], FileEntry::new("dir/", '?', '?'));
+    }
+
+    #[test]
+    fn test_conflicted_entries() {
+        for code in ["UU", "AA", "DU", "UD", "DD", "AU", "UA"] {
+            let entry = FileEntry::parse(&format!("{code} file.txt")).unwrap();
+            assert!(entry.is_conflicted(), "{code}");
+            assert!(entry.in_section(Section::Conflicted));
+            assert!(!entry.in_section(Section::Staged));
+            assert!(!entry.in_section(Section::Unstaged));
+        }
+        assert!(!FileEntry::parse("MM file.txt").unwrap().is_conflicted());
+    }
+
+    #[test]
+    fn test_parse_conflict_hunks() {
+        let content = "start\n<<<<<<< HEAD\nours line\n||||||| base\nbase line\n=======\ntheirs line\n>>>>>>> feature\nmiddle\n<<<<<<< HEAD\n=======\nonly theirs\n>>>>>>> feature\n";
+        let hunks = ConflictHunk::parse_all(content);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].line, 2);
+        assert_eq!(hunks[0].ours, vec!["ours line"]);
+        assert_eq!(hunks[0].base, Some(vec!["base line".to_string()]));
+        assert_eq!(hunks[0].theirs, vec!["theirs line"]);
+        assert_eq!(hunks[1].ours, Vec::<String>::new());
+        assert_eq!(hunks[1].base, None);
+        assert_eq!(hunks[1].theirs, vec!["only theirs"]);
+    }
+
+    #[test]
+    fn test_stash_entry_parse() {
+        let entry = StashEntry::parse("stash@{2}\tOn main: work in progress").unwrap();
+        assert_eq!(entry.index, 2);
+        assert_eq!(entry.message, "On main: work in progress");
+        assert_eq!(entry.reference(), "stash@{2}");
+
+        assert!(StashEntry::parse("garbage").is_none());
+        assert!(StashEntry::parse("stash@{x}\tmessage").is_none());
+    }
+
+    #[test]
+    fn test_parse_branch_entry() {
+        let local =
+            BranchEntry::parse("refs/heads/main\0*\0origin/main\0[ahead 1]\0abc1234\0Add x")
+                .unwrap();
+        assert_eq!(local.name, "main");
+        assert!(local.is_head);
+        assert_eq!(local.upstream.as_deref(), Some("origin/main"));
+        assert_eq!(local.remote_branch(), None);
+        assert_eq!(local.subject, "Add x");
+        assert!(!local.upstream_gone);
+        let gone = BranchEntry::parse("refs/heads/old\0 \0origin/old\0[gone]\0abc1234\0x").unwrap();
+        assert!(gone.upstream_gone);
+
+        let remote =
+            BranchEntry::parse("refs/remotes/origin/feature/x\0 \0\0\0def5678\0Fix: y").unwrap();
+        assert_eq!(remote.name, "origin/feature/x");
+        assert_eq!(remote.remote.as_deref(), Some("origin"));
+        assert_eq!(remote.remote_branch(), Some("feature/x"));
+        assert!(!remote.is_head);
+        assert_eq!(remote.upstream, None);
+        assert!(BranchEntry::parse("refs/tags/v1\0 \0\0\0abc\0tag").is_none());
+    }
+
+    #[test]
+    fn test_parse_reflog_entry() {
+        let entry = ReflogEntry::parse("1a2b3c4\tHEAD@{3}\treset: moving to HEAD~1").unwrap();
+        assert_eq!(entry.hash, "1a2b3c4");
+        assert_eq!(entry.selector, "HEAD@{3}");
+        assert_eq!(entry.message, "reset: moving to HEAD~1");
+
+        assert!(ReflogEntry::parse("garbage").is_none());
+        assert!(ReflogEntry::parse("\tHEAD@{0}\tcommit").is_none());
+    }
+
+    #[test]
+    fn test_parse_head_summary() {
+        let head = HeadSummary::parse("1a2b3c4\tFix: tabs\tin subject\n", "42\n").unwrap();
+        assert_eq!(head.hash, "1a2b3c4");
+        assert_eq!(head.subject, "Fix: tabs\tin subject");
+        assert_eq!(head.commit_count, 42);
+
+        assert!(HeadSummary::parse("garbage", "1").is_none());
+        assert!(HeadSummary::parse("1a2b3c4\tx", "").is_none());
+    }
+
+    #[test]
+    fn test_parse_commit_details() {
+        let record = "abc123\0p1 p2\0Ann\0ann@example.com\x002024-05-01 12:00:00 +0900\0Bob\0\
+                      bob@example.com\x002024-05-02 08:00:00 +0000\0G\0Ann <ann@example.com>\0\
+                      Merge feature\n\nDetails\n";
+        let details = CommitDetails::parse(record).unwrap();
+        assert_eq!(details.hash, "abc123");
+        assert_eq!(details.parents, ["p1", "p2"]);
+        assert_eq!(details.author.name, "Ann");
+        assert_eq!(details.committer.date, "2024-05-02 08:00:00 +0000");
+        assert_eq!(details.signature, SignatureStatus::Good);
+        assert_eq!(details.signer, "Ann <ann@example.com>");
+        assert_eq!(details.message, "Merge feature\n\nDetails");
+        assert_eq!(details.tree_args(), ["p1", "abc123"]);
+        assert_eq!(CommitDetails::parse(""), None);
+
+        let files =
+            CommitFile::parse_name_status(b"M\0src/a.rs\0R087\0old.txt\0new.txt\0D\0gone\0");
+        let files: Vec<(char, &str, Option<&str>)> = files
+            .iter()
+            .map(|file| (file.status, file.path.as_str(), file.old_path.as_deref()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ('M', "src/a.rs", None),
+                ('R', "new.txt", Some("old.txt")),
+                ('D', "gone", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_numstat() {
+        let output = b"3\t1\tsrc/main.rs\x000\t2\t\x00old.txt\x00new.txt\x00-\t-\timage.png\x00";
+        let stats = DiffStat::parse_numstat(output);
+        assert_eq!(
+            stats.get("src/main.rs"),
+            Some(&DiffStat {
+                added: 3,
+                deleted: 1
+            })
+        );
+        assert_eq!(
+            stats.get("new.txt"),
+            Some(&DiffStat {
+                added: 0,
+                deleted: 2
+            })
+        );
+        assert_eq!(stats.len(), 2);
+    }
+
+    #[test]
+    fn test_bisect_status_from_vars() {
+        let vars = "bisect_rev='8a51d13a918e00d3'\nbisect_nr=3\nbisect_good=3\nbisect_bad=2\nbisect_all=7\nbisect_steps=2\n";
+        assert_eq!(
+            BisectStatus::from_bisect_vars(vars, "8a51d13".to_string()),
+            Some(BisectStatus::Testing {
+                current: "8a51d13".to_string(),
+                remaining: 3,
+                steps: 2,
+            })
+        );
+
+        let found = "bisect_rev='ebcc85698e0d19eb'\nbisect_nr=0\nbisect_good=-1\nbisect_bad=0\nbisect_all=1\nbisect_steps=0\n";
+        let status = BisectStatus::from_bisect_vars(found, "80d8317".to_string()).unwrap();
+        assert_eq!(status.commit(), Some("ebcc856"));
+        assert_eq!(BisectStatus::from_bisect_vars("", String::new()), None);
+    }
+
+    #[test]
+    fn test_diff_options() {
+        let options = DiffOptions::default();
+        assert_eq!(options.args(), Vec::<String>::new());
+        let options = options.with_context_step(-1);
+        assert_eq!(options.args(), vec!["-U2"]);
+        let options = DiffOptions {
+            ignore_whitespace: true,
+            ignore_blank_lines: true,
+            ..options.with_context_step(-5)
+        };
+        assert_eq!(options.args(), vec!["-U0", "-w", "--ignore-blank-lines"]);
+        assert_eq!(
+            options.with_context_step(500).context,
+            Some(DiffOptions::MAX_CONTEXT)
+        );
+        assert_eq!(
+            no_changes("No staged changes", options),
+            "No staged changes (ignoring whitespace and blank lines)"
+        );
+        assert_eq!(
+            no_changes("No staged changes", DiffOptions::default()),
+            "No staged changes"
+        );
+    }
+
+    #[test]
+    fn test_repo_stats_parse() {
+        let stats = RepoStats::parse(
+            "count: 3\nsize: 2\nin-pack: 10\npacks: 1\nsize-pack: 5\nprune-packable: 0\ngarbage: 1\nsize-garbage: 0\n",
+        );
+        assert_eq!(stats.loose_objects, 3);
+        assert_eq!(stats.loose_size, 2048);
+        assert_eq!(stats.packed_objects, 10);
+        assert_eq!(stats.packs, 1);
+        assert_eq!(stats.pack_size, 5120);
+        assert_eq!(stats.garbage, 1);
+        assert_eq!(stats.total_size(), 7168);
+        assert_eq!(stats.last_gc, None);
+    }
+
+    #[test]
+    fn test_pull_strategy_from_config() {
+        assert_eq!(PullStrategy::from_config("", ""), PullStrategy::Merge);
+        assert_eq!(PullStrategy::from_config("true", ""), PullStrategy::Rebase);
+        assert_eq!(
+            PullStrategy::from_config("merges", "only"),
+            PullStrategy::Rebase
+        );
+        assert_eq!(
+            PullStrategy::from_config("false", "only"),
+            PullStrategy::Merge
+        );
+        assert_eq!(
+            PullStrategy::from_config("", "only"),
+            PullStrategy::FastForwardOnly
+        );
+    }
+
+    #[test]
+    fn test_parse_ahead_behind() {
+        assert_eq!(GitOperations::parse_ahead_behind("2\t1\n"), Some((2, 1)));
+        assert_eq!(GitOperations::parse_ahead_behind("0\t0"), Some((0, 0)));
+        assert_eq!(GitOperations::parse_ahead_behind(""), None);
+        assert_eq!(GitOperations::parse_ahead_behind("x\t1"), None);
+    }
+
+    #[test]
+    fn test_extract_commit_hash() {
+        assert_eq!(
+            GitOperations::extract_commit_hash("* a1b2c3d Initial commit"),
+            Some("a1b2c3d")
+        );
+        assert_eq!(
+            GitOperations::extract_commit_hash("| * 0f9e8d7 (HEAD -> main) Fix bug"),
+            Some("0f9e8d7")
+        );
+        assert_eq!(GitOperations::extract_commit_hash("|\\"), None);
+        assert_eq!(GitOperations::extract_commit_hash("| |/"), None);
+        assert_eq!(
+            GitOperations::extract_commit_hash("* not-a-hash message"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_log_query() {
+        assert_eq!(
+            LogQuery::parse(" fix bug "),
+            Some(LogQuery::Message("fix bug".to_string()))
+        );
+        assert_eq!(
+            LogQuery::parse("author: Alice"),
+            Some(LogQuery::Author("Alice".to_string()))
+        );
+        assert_eq!(
+            LogQuery::parse("path:src/main.rs"),
+            Some(LogQuery::Path("src/main.rs".to_string()))
+        );
+        assert_eq!(LogQuery::parse("  "), None);
+        assert_eq!(LogQuery::parse("author:"), None);
+    }
+
     #[test]
     fn test_file_path_extraction() {
         let file_status = "M  src/main.rs";
@@ -333,4 +4268,81 @@ mod tests {
         let file_path = file_status_with_spaces[3..].trim();
         assert_eq!(file_path, "src/test.rs");
     }
+
+    #[test]
+    fn test_parse_config_list() {
+        let output = "system\0file:/etc/gitconfig\0core.autocrlf\ninput\0\
+                      global\0file:/home/me/.gitconfig\0user.name\nMe\0\
+                      local\0file:.git/config\0core.bare\0\
+                      command\0command line:\0alias.lg\nlog --graph\nx\0";
+        let entries = parse_config_list(output);
+        let summary: Vec<(&str, &str, ConfigScope, &str)> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.key.as_str(),
+                    entry.value.as_str(),
+                    entry.scope,
+                    entry.origin.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "core.autocrlf",
+                    "input",
+                    ConfigScope::System,
+                    "file:/etc/gitconfig"
+                ),
+                (
+                    "user.name",
+                    "Me",
+                    ConfigScope::Global,
+                    "file:/home/me/.gitconfig"
+                ),
+                ("core.bare", "", ConfigScope::Local, "file:.git/config"),
+                (
+                    "alias.lg",
+                    "log --graph\nx",
+                    ConfigScope::Command,
+                    "command line:"
+                ),
+            ]
+        );
+        assert_eq!(ConfigScope::Local.next(), ConfigScope::Global);
+        assert_eq!(ConfigScope::System.next(), ConfigScope::Local);
+        assert_eq!(ConfigScope::Worktree.next(), ConfigScope::Local);
+    }
+
+    #[test]
+    fn test_archive_format_from_file_name() {
+        assert_eq!(archive_format("/tmp/app-main.zip"), Some("zip"));
+        assert_eq!(archive_format("app.TAR.GZ"), Some("tar.gz"));
+        assert_eq!(archive_format("app.tgz"), Some("tgz"));
+        assert_eq!(archive_format("app.tar"), Some("tar"));
+        assert_eq!(archive_format("app.7z"), None);
+        assert_eq!(archive_format("zip"), None);
+    }
+
+    #[test]
+    fn test_previous_branch_from_reflog() {
+        let entry = |message: &str| ReflogEntry {
+            hash: String::from("1234567"),
+            selector: String::from("HEAD@{0}"),
+            message: message.to_string(),
+        };
+        let reflog = [
+            entry("checkout: moving from 1234567 to 89abcde"),
+            entry("commit: Experiment"),
+            entry("checkout: moving from deleted to 1234567"),
+            entry("checkout: moving from main to deleted"),
+        ];
+        assert_eq!(
+            previous_branch(&reflog, |name| name == "main"),
+            Some("main")
+        );
+        assert_eq!(previous_branch(&reflog[..2], |_| false), None);
+    }
 }
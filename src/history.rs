@@ -0,0 +1,183 @@
+//! What was done during the session, for the history pane.
+//!
+//! Every operation that reported success or failure is kept with when and in which
+//! repository it happened. With `history_file` set in the config it is appended to that file
+//! as well.
+
+use crate::status_line::{Severity, StatusMessage};
+use ratatui::layout::Rect;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// How many entries the pane keeps; the oldest are dropped first
+pub const CAPACITY: usize = 500;
+
+/// One finished operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub at: SystemTime,
+    /// Name of the repository it was done in
+    pub repo: String,
+    pub succeeded: bool,
+    /// The message it reported, e.g. `✓ Staged 2 file(s)`
+    pub text: String,
+}
+
+impl HistoryEntry {
+    /// The entry for a success or error `message` shown while `repo` was open; `None` for
+    /// info messages, which are not outcomes
+    pub fn from_message(message: &StatusMessage, repo: &str) -> Option<Self> {
+        let succeeded = match message.severity {
+            Severity::Info => return None,
+            Severity::Success => true,
+            Severity::Error => false,
+        };
+        let at = SystemTime::now() - Instant::now().saturating_duration_since(message.at);
+        Some(Self {
+            at,
+            repo: repo.to_string(),
+            succeeded,
+            text: message.text.clone(),
+        })
+    }
+
+    /// `2026-10-16T09:15:02Z<TAB>project<TAB>ok<TAB>✓ Staged 2 file(s)`, one line per entry
+    pub fn log_line(&self) -> String {
+        let outcome = if self.succeeded { "ok" } else { "error" };
+        format!(
+            "{}\t{}\t{outcome}\t{}",
+            format_utc(self.at),
+            self.repo,
+            self.text.replace('\n', " ")
+        )
+    }
+}
+
+/// The entries of the session, oldest first, and where they are also written
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    pub entries: VecDeque<HistoryEntry>,
+    /// `history_file` from the config
+    pub file: Option<PathBuf>,
+    pub scroll: u16,
+    pub area: Rect,
+}
+
+impl History {
+    /// Keeps `entry` and appends it to the file. Writing stops after the first failure, so
+    /// the error is not repeated for every later entry.
+    pub fn record(&mut self, entry: HistoryEntry) -> Result<(), String> {
+        let written = self
+            .file
+            .as_deref()
+            .map_or(Ok(()), |path| append(path, &entry));
+        if written.is_err() {
+            self.file = None;
+        }
+        if self.entries.len() >= CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        written
+    }
+}
+
+/// Appends the log line of `entry` to `path`, creating the file and its directory
+pub fn append(path: &Path, entry: &HistoryEntry) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", entry.log_line()))
+        .map_err(|e| format!("Failed to write history to {}: {e}", path.display()))
+}
+
+/// `2026-10-16T09:15:02Z`
+pub fn format_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (year, month, day) = civil_from_days(secs / 86400);
+    let secs = secs % 86400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// The date `days` after 1970-01-01, from Howard Hinnant's `civil_from_days`
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_record_and_log_lines() {
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_utc(UNIX_EPOCH + Duration::from_secs(1_709_251_199)),
+            "2024-02-29T23:59:59Z"
+        );
+
+        let message = |severity, text: &str| StatusMessage {
+            text: text.to_string(),
+            severity,
+            at: Instant::now(),
+        };
+        assert_eq!(
+            HistoryEntry::from_message(&message(Severity::Info, "Running push..."), "repo"),
+            None
+        );
+        let mut entry =
+            HistoryEntry::from_message(&message(Severity::Error, "Error: rejected"), "repo")
+                .unwrap();
+        assert!(!entry.succeeded);
+        entry.at = UNIX_EPOCH + Duration::from_secs(86400);
+        assert_eq!(
+            entry.log_line(),
+            "1970-01-02T00:00:00Z\trepo\terror\tError: rejected"
+        );
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("state").join("history.log");
+        let mut history = History {
+            file: Some(path.clone()),
+            ..History::default()
+        };
+        history.record(entry.clone()).unwrap();
+        history.record(entry.clone()).unwrap();
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        // A directory where the file should be: the entry is kept, writing stops
+        let mut broken = History {
+            file: Some(temp.path().to_path_buf()),
+            ..History::default()
+        };
+        assert!(broken.record(entry.clone()).is_err());
+        assert_eq!(broken.file, None);
+        assert_eq!(broken.record(entry), Ok(()));
+        assert_eq!(broken.entries.len(), 2);
+    }
+}
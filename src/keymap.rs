@@ -0,0 +1,711 @@
+//! Key bindings and the hint bar built from them. Normal mode keys are dispatched through
+//! `NORMAL_BINDINGS`, so the hints always show the keys that are actually bound.
+
+use crate::app::{App, FileRow, InitStep, InputMode, ScrollJump};
+use crate::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::git::{FileEntry, Section};
+use crate::status_line::Severity;
+use crate::strings::Msg;
+
+/// What a normal mode key does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Next,
+    Previous,
+    Stage,
+    LineStage,
+    StageAll,
+    Discard,
+    Ignore,
+    /// `git add -N` on an untracked file, or undoing it
+    IntentToAdd,
+    ToggleSection,
+    /// Orders the files by path, directory, status or modification time
+    CycleSort,
+    /// Lists the files as a directory tree or by full path
+    ToggleTree,
+    /// Folds a section header, expands an untracked directory, or opens the conflict view
+    /// of a file
+    Open,
+    AbortMerge,
+    ContinueMerge,
+    Commit,
+    Stash,
+    /// Stashes only the selected file, or the files of the selected section
+    StashSelected,
+    ListStashes,
+    Log,
+    Reflog,
+    Branches,
+    /// `git diff A..B` between two refs
+    Compare,
+    /// `git archive` of HEAD into a zip or tar file
+    Archive,
+    /// `git checkout -b` at a detached HEAD
+    CreateBranchAtHead,
+    /// Back from a detached HEAD to the branch checked out before it
+    SwitchBack,
+    Bisect,
+    BisectGood,
+    BisectBad,
+    BisectSkip,
+    RepoSwitcher,
+    ApplyStash,
+    /// The selected file on GitHub, GitLab or Bitbucket, in the browser
+    OpenOnForge,
+    /// Copies the selected file's path to the clipboard
+    CopyPath,
+    /// Copies the diff in the preview panel to the clipboard
+    CopyDiff,
+    Push,
+    /// `git push --force-with-lease`, after typing the branch name
+    ForcePush,
+    Pull,
+    Fetch,
+    Refresh,
+    Help,
+    /// Every git command run, with its exit status and stderr
+    CommandLog,
+    /// The operations done this session and how they ended
+    History,
+    /// Repository size with `git gc`, `git prune` and `git maintenance run`
+    Maintenance,
+    /// Checks of git, its config, the terminal and optional tools, as `--doctor` prints
+    Doctor,
+    /// Every git config value with where it is set, and an editor for the common keys
+    Config,
+    Preview,
+    /// Full content of the selected file at HEAD, in the index or in the working tree
+    FileVersions,
+    TogglePreviewPanel,
+    /// Preview panel on the right, at the bottom or hidden
+    CyclePreviewPosition,
+    ShrinkPreview,
+    GrowPreview,
+    ToggleDiffView,
+    /// Fewer / more lines of context around changes (`-U<n>`)
+    LessContext,
+    MoreContext,
+    /// `-w` for the preview
+    IgnoreWhitespace,
+    /// `--ignore-blank-lines` for the preview
+    IgnoreBlankLines,
+    /// Removes the shown status message, such as an error that stays until dismissed
+    DismissMessage,
+}
+
+impl Action {
+    /// What the action does, as `--help` lists it
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Quit => "Quit application",
+            Self::Next => "Move down the file list",
+            Self::Previous => "Move up the file list",
+            Self::Stage => "Stage/unstage selected file (or whole section on a header)",
+            Self::LineStage => "Stage/unstage individual lines of the selected file",
+            Self::StageAll => "Stage/unstage all files",
+            Self::Discard => "Discard changes to selected file (with confirmation)",
+            Self::Ignore => "Add the selected untracked file, its extension or directory to .gitignore",
+            Self::IntentToAdd => "git add -N the selected untracked file, so `e` can stage its lines (again to undo)",
+            Self::ToggleSection => "Collapse/expand the section, or the directory in the tree view",
+            Self::CycleSort => "Sort files by path / directory / status / modification time",
+            Self::ToggleTree => "Show files as a directory tree / flat list",
+            Self::Open => "Fold a section header, list an untracked directory file by file, or open the conflict view",
+            Self::AbortMerge => "Abort the merge (with confirmation)",
+            Self::ContinueMerge => "Continue the merge",
+            Self::Commit => "Enter commit mode (uses commit.template if set)",
+            Self::Stash => "Enter stash message mode (Ctrl+K --keep-index, Tab --include-untracked)",
+            Self::StashSelected => "Stash only the selected file (or whole section on a header)",
+            Self::ListStashes => "Open stash list",
+            Self::Log => "Show commit log",
+            Self::Reflog => "Show reflog (HEAD history)",
+            Self::Branches => "Show branches",
+            Self::Compare => "Compare two refs (A..B, A...B or A for A..HEAD)",
+            Self::Archive => "Export HEAD as a .zip/.tar/.tar.gz/.tgz archive (git archive)",
+            Self::CreateBranchAtHead => "On a detached HEAD: create a branch at it",
+            Self::SwitchBack => "On a detached HEAD: switch back to the previous branch",
+            Self::Bisect => "Start bisect / end it (with confirmation)",
+            Self::BisectGood => "Mark the checked-out commit good (while bisecting)",
+            Self::BisectBad => "Mark the checked-out commit bad (while bisecting)",
+            Self::BisectSkip => "Skip the checked-out commit (while bisecting)",
+            Self::RepoSwitcher => "Switch to a recently opened repository",
+            Self::ApplyStash => "Apply latest stash",
+            Self::OpenOnForge => "Open the selected file on GitHub/GitLab/Bitbucket in the browser",
+            Self::CopyPath => "Copy the selected file's path",
+            Self::CopyDiff => "Copy the diff in the preview panel",
+            Self::Push => "Push current branch (offers --set-upstream for a new branch)",
+            Self::ForcePush => "Force-push with --force-with-lease (type the branch name to confirm)",
+            Self::Pull => "Pull current branch (rebase, merge or fast-forward only)",
+            Self::Fetch => "Fetch from remote",
+            Self::Refresh => "Refresh file list",
+            Self::Help => "Show the key help",
+            Self::CommandLog => "Show every git command run, with exit status and stderr",
+            Self::History => "Show what was done this session, with outcome and time",
+            Self::Maintenance => "Repository maintenance: size, git gc / prune / maintenance run",
+            Self::Doctor => "Diagnostics: git, its config, the terminal, git-lfs and gpg",
+            Self::Config => "Git config: every value with its scope and origin, editable",
+            Self::Preview => "Show diff preview (fullscreen)",
+            Self::FileVersions => "Show the whole file; Tab or 1/2/3 switch between HEAD, index and working tree",
+            Self::TogglePreviewPanel => "Toggle preview panel",
+            Self::CyclePreviewPosition => "Move the preview panel right / bottom / hidden (saved to the config)",
+            Self::ShrinkPreview => "Shrink the preview panel (saved to the config)",
+            Self::GrowPreview => "Grow the preview panel (saved to the config)",
+            Self::ToggleDiffView => "Switch the preview between unstaged and staged changes",
+            Self::LessContext => "Fewer lines of context in the diff (-U<n>)",
+            Self::MoreContext => "More lines of context in the diff (-U<n>)",
+            Self::IgnoreWhitespace => "Toggle ignoring whitespace in the diff (-w)",
+            Self::IgnoreBlankLines => "Toggle ignoring blank lines in the diff",
+            Self::DismissMessage => "Dismiss the status message (errors stay until dismissed)",
+        }
+    }
+}
+
+/// Keys bound to one action. The first key is the one shown in hints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub keys: &'static [KeyCode],
+    pub action: Action,
+}
+
+impl Binding {
+    /// All of the keys, e.g. `x/D`
+    pub fn keys_label(&self) -> String {
+        self.keys
+            .iter()
+            .map(|&code| key_label(code))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+const fn bind(keys: &'static [KeyCode], action: Action) -> Binding {
+    Binding { keys, action }
+}
+
+pub const NORMAL_BINDINGS: &[Binding] = &[
+    bind(&[KeyCode::Char('q')], Action::Quit),
+    bind(&[KeyCode::Char('j'), KeyCode::Down], Action::Next),
+    bind(&[KeyCode::Char('k'), KeyCode::Up], Action::Previous),
+    bind(&[KeyCode::Char('s')], Action::Stage),
+    bind(&[KeyCode::Char('e')], Action::LineStage),
+    bind(&[KeyCode::Char('a')], Action::StageAll),
+    bind(&[KeyCode::Char('x'), KeyCode::Char('D')], Action::Discard),
+    bind(&[KeyCode::Char('i')], Action::Ignore),
+    bind(&[KeyCode::Char('N')], Action::IntentToAdd),
+    bind(&[KeyCode::Char('z')], Action::ToggleSection),
+    bind(&[KeyCode::Char('S')], Action::CycleSort),
+    bind(&[KeyCode::Char('f')], Action::ToggleTree),
+    bind(&[KeyCode::Enter], Action::Open),
+    bind(&[KeyCode::Char('A')], Action::AbortMerge),
+    bind(&[KeyCode::Char('C')], Action::ContinueMerge),
+    bind(&[KeyCode::Char('c')], Action::Commit),
+    bind(&[KeyCode::Char('t')], Action::Stash),
+    bind(&[KeyCode::Char('T')], Action::StashSelected),
+    bind(&[KeyCode::Char('l')], Action::ListStashes),
+    bind(&[KeyCode::Char('L')], Action::Log),
+    bind(&[KeyCode::Char('R')], Action::Reflog),
+    bind(&[KeyCode::Char('w')], Action::Branches),
+    bind(&[KeyCode::Char('=')], Action::Compare),
+    bind(&[KeyCode::Char('Z')], Action::Archive),
+    bind(&[KeyCode::Char('+')], Action::CreateBranchAtHead),
+    bind(&[KeyCode::Char('-')], Action::SwitchBack),
+    bind(&[KeyCode::Char('B')], Action::Bisect),
+    bind(&[KeyCode::Char('g')], Action::BisectGood),
+    bind(&[KeyCode::Char('b')], Action::BisectBad),
+    bind(&[KeyCode::Char('n')], Action::BisectSkip),
+    bind(&[KeyCode::Char('o')], Action::RepoSwitcher),
+    bind(&[KeyCode::Char('p')], Action::ApplyStash),
+    bind(&[KeyCode::Char('O')], Action::OpenOnForge),
+    bind(&[KeyCode::Char('y')], Action::CopyPath),
+    bind(&[KeyCode::Char('Y')], Action::CopyDiff),
+    bind(&[KeyCode::Char('P')], Action::Push),
+    bind(&[KeyCode::Char('!')], Action::ForcePush),
+    bind(&[KeyCode::Char('U')], Action::Pull),
+    bind(&[KeyCode::Char('F')], Action::Fetch),
+    bind(&[KeyCode::Char('r')], Action::Refresh),
+    bind(&[KeyCode::Char('h')], Action::Help),
+    bind(&[KeyCode::Char('@')], Action::CommandLog),
+    bind(&[KeyCode::Char('H')], Action::History),
+    bind(&[KeyCode::Char('M')], Action::Maintenance),
+    bind(&[KeyCode::Char('I')], Action::Doctor),
+    bind(&[KeyCode::Char('G')], Action::Config),
+    bind(&[KeyCode::Char('d')], Action::Preview),
+    bind(&[KeyCode::Char('V')], Action::FileVersions),
+    bind(&[KeyCode::Char('v')], Action::TogglePreviewPanel),
+    bind(&[KeyCode::Char('|')], Action::CyclePreviewPosition),
+    bind(&[KeyCode::Char('<')], Action::ShrinkPreview),
+    bind(&[KeyCode::Char('>')], Action::GrowPreview),
+    bind(&[KeyCode::Tab], Action::ToggleDiffView),
+    bind(&[KeyCode::Char('{')], Action::LessContext),
+    bind(&[KeyCode::Char('}')], Action::MoreContext),
+    bind(&[KeyCode::Char('W')], Action::IgnoreWhitespace),
+    bind(&[KeyCode::Char('E')], Action::IgnoreBlankLines),
+    bind(&[KeyCode::Esc], Action::DismissMessage),
+];
+
+/// The normal mode action bound to `code`
+pub fn normal_action(code: KeyCode) -> Option<Action> {
+    NORMAL_BINDINGS
+        .iter()
+        .find(|binding| binding.keys.contains(&code))
+        .map(|binding| binding.action)
+}
+
+/// The page-wise or top/bottom move of the scrolled views bound to `key`
+pub const fn scroll_jump(key: KeyEvent) -> Option<ScrollJump> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::PageDown => Some(ScrollJump::PageDown),
+        KeyCode::PageUp => Some(ScrollJump::PageUp),
+        KeyCode::Char('d') if ctrl => Some(ScrollJump::HalfPageDown),
+        KeyCode::Char('u') if ctrl => Some(ScrollJump::HalfPageUp),
+        KeyCode::Char('g') | KeyCode::Home => Some(ScrollJump::Top),
+        KeyCode::Char('G') | KeyCode::End => Some(ScrollJump::Bottom),
+        _ => None,
+    }
+}
+
+/// How a key is written in hints, e.g. `j`, `Enter`, `↓`
+pub fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "BS".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Keys of the given actions joined with `/`, e.g. `j/k` for next and previous
+fn action_keys(actions: &[Action]) -> String {
+    actions
+        .iter()
+        .filter_map(|action| {
+            NORMAL_BINDINGS
+                .iter()
+                .find(|binding| binding.action == *action)
+                .and_then(|binding| binding.keys.first())
+        })
+        .map(|&code| key_label(code))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// One entry of the hint bar: keys and what they do
+pub type Hint = (String, &'static str);
+
+/// The most useful keys for the current mode. Normal mode hints follow the selection and
+/// the repository state, e.g. merge keys only show up while there are conflicts.
+pub fn hints(app: &App) -> Vec<Hint> {
+    if app.input_mode == InputMode::Normal {
+        return normal_hints(app);
+    }
+    if app.preview_search_input.is_some() {
+        return vec![
+            ("Enter".to_string(), Msg::HintSearch.text(app.language())),
+            ("Esc".to_string(), Msg::HintCancel.text(app.language())),
+        ];
+    }
+    mode_hints(&app.input_mode)
+        .iter()
+        .map(|&(keys, label)| (keys.to_string(), label.text(app.language())))
+        .collect()
+}
+
+fn normal_hints(app: &App) -> Vec<Hint> {
+    let mut hints: Vec<(&[Action], Msg)> = vec![(&[Action::Next, Action::Previous], Msg::HintMove)];
+    match app.selected_row() {
+        Some(FileRow::Header { .. } | FileRow::Dir { .. }) => {
+            hints.push((&[Action::Open], Msg::HintFold));
+        },
+        Some(FileRow::File {
+            section: Section::Conflicted,
+            ..
+        }) => hints.push((&[Action::Open], Msg::HintResolve)),
+        Some(FileRow::File {
+            section: Section::Staged,
+            ..
+        }) => hints.push((&[Action::Stage], Msg::HintUnstage)),
+        Some(FileRow::File {
+            section: Section::Untracked,
+            ..
+        }) => {
+            hints.push((&[Action::Stage], Msg::HintStage));
+            if let Some(dir) = app.selected_untracked_dir() {
+                let label = if app.expanded_dirs.contains(&dir) {
+                    Msg::HintCollapse
+                } else {
+                    Msg::HintExpand
+                };
+                hints.push((&[Action::Open], label));
+            }
+            hints.push((&[Action::IntentToAdd], Msg::HintAddIntent));
+            hints.push((&[Action::Discard], Msg::HintDelete));
+        },
+        Some(FileRow::File { .. }) => {
+            hints.push((&[Action::Stage], Msg::HintStage));
+            hints.push((&[Action::Discard], Msg::HintDiscard));
+        },
+        None => {},
+    }
+    if app.selected_file().is_some() {
+        hints.push((&[Action::Preview], Msg::HintDiff));
+    }
+    if app.files.iter().any(FileEntry::is_conflicted) {
+        hints.push((&[Action::AbortMerge], Msg::HintAbortMerge));
+    }
+    if app.files.iter().any(FileEntry::is_staged) {
+        hints.push((&[Action::Commit], Msg::HintCommit));
+    }
+    if app.detached.is_some() && app.bisect.is_none() {
+        hints.push((&[Action::CreateBranchAtHead], Msg::HintCreateBranch));
+        hints.push((&[Action::SwitchBack], Msg::HintSwitchBack));
+    }
+    if app.bisect.is_some() {
+        hints.push((
+            &[Action::BisectGood, Action::BisectBad, Action::BisectSkip],
+            Msg::HintBisect,
+        ));
+    }
+    match &app.upstream {
+        Some(upstream) if upstream.behind > 0 => hints.push((&[Action::Pull], Msg::HintPull)),
+        Some(upstream) if upstream.ahead > 0 => hints.push((&[Action::Push], Msg::HintPush)),
+        _ => {},
+    }
+    // The full stderr of a failed command is one key away
+    if app.status.severity() == Some(Severity::Error) {
+        hints.push((&[Action::CommandLog], Msg::HintCommandLog));
+        hints.push((&[Action::DismissMessage], Msg::HintDismiss));
+    }
+    hints.push((&[Action::Help], Msg::HintHelp));
+    hints.push((&[Action::Quit], Msg::HintQuit));
+    hints
+        .into_iter()
+        .map(|(actions, label)| (action_keys(actions), label.text(app.language())))
+        .collect()
+}
+
+const COMMIT_HINTS: &[(&str, Msg)] = &[
+    ("Enter", Msg::HintSubmit),
+    ("↑/↓", Msg::HintSelectFile),
+    ("Ctrl+T", Msg::HintIncludeFile),
+    ("Tab", Msg::HintPrefix),
+    ("Ctrl+P", Msg::HintIdentity),
+    ("Ctrl+R", Msg::HintTrailers),
+    ("Ctrl+D", Msg::HintSignOff),
+    ("Ctrl+E", Msg::HintEditor),
+    ("Ctrl+S", Msg::HintSign),
+    ("Ctrl+N", Msg::HintSkipHooks),
+    ("Ctrl+A", Msg::HintAutoStage),
+    ("Ctrl+O", Msg::HintForce),
+    ("Esc", Msg::HintCancel),
+];
+
+const PREVIEW_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintScroll),
+    ("PgUp/PgDn", Msg::HintPage),
+    ("g/G", Msg::HintTopBottom),
+    ("/", Msg::HintSearch),
+    ("n/N", Msg::HintMatches),
+    ("x", Msg::HintHexDump),
+    ("y", Msg::HintCopy),
+    ("{/}", Msg::HintContext),
+    ("W/E", Msg::HintIgnoreSpace),
+    ("q/Esc", Msg::HintClose),
+];
+
+const LOG_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("PgUp/PgDn", Msg::HintPage),
+    ("Enter", Msg::HintDetails),
+    ("d", Msg::HintShowDiff),
+    ("/", Msg::HintSearch),
+    ("n/N", Msg::HintMatches),
+    ("g/b", Msg::HintMarkBisect),
+    ("O", Msg::HintBrowser),
+    ("y", Msg::HintCopyHash),
+    ("S/M/X", Msg::HintReset),
+    ("f", Msg::HintFixup),
+    ("A", Msg::HintAutosquash),
+    ("c", Msg::HintCompare),
+    ("Z", Msg::HintArchive),
+    ("r", Msg::HintReload),
+    ("q/Esc", Msg::HintBack),
+];
+
+const BRANCH_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("m", Msg::HintMerge),
+    ("R", Msg::HintRename),
+    ("u", Msg::HintUpstream),
+    ("c", Msg::HintCompare),
+    ("Z", Msg::HintArchive),
+    ("x", Msg::HintDelete),
+    ("f", Msg::HintFetchPrune),
+    ("X", Msg::HintDeleteGone),
+    ("r", Msg::HintReload),
+    ("q/Esc", Msg::HintBack),
+];
+
+const PULL_PICKER_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("Enter", Msg::HintPull),
+    ("q/Esc", Msg::HintCancel),
+];
+
+const COMPARE_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("Enter", Msg::HintShowDiff),
+    ("D", Msg::HintWholeDiff),
+    ("r", Msg::HintReload),
+    ("q/Esc", Msg::HintBack),
+];
+
+const REFLOG_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("Enter", Msg::HintShowDiff),
+    ("c", Msg::HintCheckout),
+    ("x", Msg::HintResetHard),
+    ("r", Msg::HintReload),
+    ("q/Esc", Msg::HintBack),
+];
+
+const MAINTENANCE_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("Enter", Msg::HintRun),
+    ("r", Msg::HintReload),
+    ("q/Esc", Msg::HintBack),
+];
+
+const IDENTITY_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("Enter", Msg::HintUseIdentity),
+    ("s", Msg::HintSaveIdentity),
+    ("q/Esc", Msg::HintCancel),
+];
+
+const TRAILER_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("Enter/Space", Msg::HintToggleTrailer),
+    ("Tab", Msg::HintTrailerKey),
+    ("q/Esc", Msg::HintBack),
+];
+
+const COMMIT_DETAILS_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("n/N", Msg::HintNextFile),
+    ("Enter", Msg::HintExpandDiff),
+    ("d", Msg::HintDiff),
+    ("D", Msg::HintWholeCommit),
+    ("y", Msg::HintCopyHash),
+    ("q/Esc", Msg::HintBack),
+];
+
+const COMMAND_LOG_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintScroll),
+    ("PgUp/PgDn", Msg::HintPage),
+    ("g/G", Msg::HintTopBottom),
+    ("@/q/Esc", Msg::HintClose),
+];
+
+const HISTORY_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintScroll),
+    ("PgUp/PgDn", Msg::HintPage),
+    ("g/G", Msg::HintTopBottom),
+    ("H/q/Esc", Msg::HintClose),
+];
+
+const DOCTOR_HINTS: &[(&str, Msg)] = &[
+    ("y", Msg::HintCopy),
+    ("r", Msg::HintReload),
+    ("I/q/Esc", Msg::HintClose),
+];
+
+const CONFIG_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("Enter", Msg::HintEdit),
+    ("r", Msg::HintReload),
+    ("q/Esc", Msg::HintClose),
+];
+
+const CONFIG_EDIT_HINTS: &[(&str, Msg)] = &[
+    ("Enter", Msg::HintSubmit),
+    ("Tab", Msg::HintScope),
+    ("Esc", Msg::HintCancel),
+];
+
+const TYPED_CONFIRM_HINTS: &[(&str, Msg)] =
+    &[("Enter", Msg::HintConfirm), ("Esc", Msg::HintCancel)];
+
+const NO_REPOSITORY_HINTS: &[(&str, Msg)] = &[
+    ("i", Msg::HintInit),
+    ("I", Msg::HintInitOptions),
+    ("j/k", Msg::HintMove),
+    ("Enter", Msg::HintOpen),
+    ("q", Msg::HintQuit),
+];
+
+const MERGE_PICKER_HINTS: &[(&str, Msg)] = &[
+    ("j/k", Msg::HintMove),
+    ("Enter", Msg::HintMerge),
+    ("q/Esc", Msg::HintBack),
+];
+
+const STASH_MESSAGE_HINTS: &[(&str, Msg)] = &[
+    ("Enter", Msg::HintStash),
+    ("Tab", Msg::HintIncludeUntracked),
+    ("Ctrl+K", Msg::HintKeepIndex),
+    ("Esc", Msg::HintCancel),
+];
+
+/// Keys of the modes other than normal mode, which handle their keys directly
+const fn mode_hints(mode: &InputMode) -> &'static [(&'static str, Msg)] {
+    match mode {
+        InputMode::Normal => &[],
+        InputMode::Commit => COMMIT_HINTS,
+        InputMode::StashMessage => STASH_MESSAGE_HINTS,
+        InputMode::LogSearch { .. } => &[
+            ("Enter", Msg::HintSearchOrClear),
+            ("author:", Msg::HintAuthor),
+            ("path:", Msg::HintPath),
+            ("Esc", Msg::HintCancel),
+        ],
+        InputMode::Confirm { .. } => &[
+            ("y", Msg::HintYes),
+            ("n", Msg::HintNo),
+            ("Esc", Msg::HintCancel),
+        ],
+        InputMode::Preview { .. } => PREVIEW_HINTS,
+        InputMode::Help => &[
+            ("j/k", Msg::HintScroll),
+            ("PgUp/PgDn", Msg::HintPage),
+            ("g/G", Msg::HintTopBottom),
+            ("q/Esc", Msg::HintClose),
+        ],
+        InputMode::FileVersion { .. } => &[
+            ("Tab", Msg::HintVersions),
+            ("1/2/3", Msg::HintPickVersion),
+            ("j/k", Msg::HintScroll),
+            ("g/G", Msg::HintTopBottom),
+            ("q/Esc", Msg::HintClose),
+        ],
+        InputMode::Log => LOG_HINTS,
+        InputMode::Reflog => REFLOG_HINTS,
+        InputMode::StashList => &[
+            ("j/k", Msg::HintMove),
+            ("Enter", Msg::HintDiff),
+            ("a", Msg::HintApply),
+            ("p", Msg::HintPop),
+            ("x", Msg::HintDelete),
+            ("q/Esc", Msg::HintBack),
+        ],
+        InputMode::RepoSwitcher => &[
+            ("j/k", Msg::HintMove),
+            ("Enter", Msg::HintOpen),
+            ("q/Esc", Msg::HintBack),
+        ],
+        InputMode::Conflict { .. } => &[
+            ("o", Msg::HintOurs),
+            ("t", Msg::HintTheirs),
+            ("e", Msg::HintEditor),
+            ("j/k", Msg::HintScroll),
+            ("q/Esc", Msg::HintBack),
+        ],
+        InputMode::LineStage { .. } => &[
+            ("j/k", Msg::HintMove),
+            ("Space", Msg::HintSelectLine),
+            ("a", Msg::HintHunk),
+            ("Enter/s", Msg::HintApply),
+            ("q/Esc", Msg::HintBack),
+        ],
+        InputMode::IgnoreChooser { .. } => &[
+            ("j/k", Msg::HintMove),
+            ("Enter", Msg::HintAdd),
+            ("q/Esc", Msg::HintCancel),
+        ],
+        InputMode::Branches => BRANCH_HINTS,
+        InputMode::MergePicker { .. } => MERGE_PICKER_HINTS,
+        InputMode::PullPicker { .. } => PULL_PICKER_HINTS,
+        InputMode::Maintenance => MAINTENANCE_HINTS,
+        InputMode::CommitDetails => COMMIT_DETAILS_HINTS,
+        InputMode::Compare => COMPARE_HINTS,
+        InputMode::IdentityPicker => IDENTITY_HINTS,
+        InputMode::TrailerPicker => TRAILER_HINTS,
+        InputMode::BranchPrompt { .. }
+        | InputMode::Credential { .. }
+        | InputMode::CompareInput { .. }
+        | InputMode::ArchivePrompt { .. } => {
+            &[("Enter", Msg::HintSubmit), ("Esc", Msg::HintCancel)]
+        },
+        InputMode::PrefixPicker => &[
+            ("j/k", Msg::HintMove),
+            ("Enter", Msg::HintInsert),
+            ("q/Esc", Msg::HintBack),
+        ],
+        InputMode::PrefixScope { .. } => {
+            &[("Enter", Msg::HintInsertScope), ("Esc", Msg::HintCancel)]
+        },
+        InputMode::TypedConfirm { .. } => TYPED_CONFIRM_HINTS,
+        InputMode::CommandLog => COMMAND_LOG_HINTS,
+        InputMode::History => HISTORY_HINTS,
+        InputMode::Doctor => DOCTOR_HINTS,
+        InputMode::Config => CONFIG_HINTS,
+        InputMode::ConfigEdit { .. } => CONFIG_EDIT_HINTS,
+        InputMode::NoRepository { .. } => NO_REPOSITORY_HINTS,
+        InputMode::InitSetup { step, .. } => init_setup_hints(*step),
+        InputMode::HookOutput { .. } => &[
+            ("j/k", Msg::HintScroll),
+            ("n", Msg::HintNoVerify),
+            ("q/Esc", Msg::HintEditMessage),
+        ],
+    }
+}
+
+const fn init_setup_hints(step: InitStep) -> &'static [(&'static str, Msg)] {
+    match step {
+        InitStep::Branch => &[("Enter", Msg::HintNextStep), ("Esc", Msg::HintCancel)],
+        InitStep::Gitignore => &[
+            ("j/k", Msg::HintMove),
+            ("Enter", Msg::HintNextStep),
+            ("Esc", Msg::HintBack),
+        ],
+        InitStep::InitialCommit => &[
+            ("y", Msg::HintYes),
+            ("n", Msg::HintNo),
+            ("Esc", Msg::HintBack),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_normal_key_is_bound_once() {
+        let keys: Vec<KeyCode> = NORMAL_BINDINGS
+            .iter()
+            .flat_map(|binding| binding.keys.iter().copied())
+            .collect();
+        for (i, key) in keys.iter().enumerate() {
+            assert!(!keys[i + 1..].contains(key), "{key:?} is bound twice");
+        }
+        assert_eq!(normal_action(KeyCode::Char('D')), Some(Action::Discard));
+        assert_eq!(normal_action(KeyCode::Char('Z')), Some(Action::Archive));
+        assert_eq!(normal_action(KeyCode::Char('Q')), None);
+    }
+
+    #[test]
+    fn test_hint_keys_come_from_bindings() {
+        assert_eq!(action_keys(&[Action::Next, Action::Previous]), "j/k");
+        assert_eq!(action_keys(&[Action::Open]), "Enter");
+        assert_eq!(action_keys(&[Action::Discard]), "x");
+    }
+}
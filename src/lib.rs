@@ -1,8 +1,41 @@
 pub mod app;
+pub mod askpass;
+pub mod backend;
+pub mod binary;
+pub mod cli;
+pub mod clipboard;
+pub mod command_log;
+pub mod config;
+pub mod diff;
+pub mod diff_cache;
+pub mod diff_lines;
+pub mod doctor;
+pub mod error;
+pub mod event;
+pub mod file_tree;
+pub mod forge;
 pub mod git;
+pub mod history;
+pub mod keymap;
+pub mod model;
+pub mod notify;
+pub mod patch;
+pub mod repo;
+pub mod sort;
+pub mod status_line;
+pub mod strings;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod text_input;
+pub mod theme;
 pub mod ui;
 pub mod ui_help;
+pub mod worker;
 
 pub use app::{App, InputMode};
+pub use backend::GitBackend;
+pub use error::GitError;
+pub use event::AppEvent;
 pub use git::GitOperations;
+pub use repo::RepoContext;
 pub use ui::render_ui;
@@ -1,64 +1,131 @@
-mod app;
-mod git;
-mod ui;
-mod ui_help;
-
-use app::{App, InputMode};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use pretty_git_ui::app::StartView;
+use pretty_git_ui::askpass;
+use pretty_git_ui::cli;
+use pretty_git_ui::clipboard;
+use pretty_git_ui::config::Config;
+use pretty_git_ui::doctor;
+use pretty_git_ui::event::FrameScheduler;
+use pretty_git_ui::git::GitOperations;
+use pretty_git_ui::keymap::NORMAL_BINDINGS;
+use pretty_git_ui::repo::RecentRepos;
+use pretty_git_ui::sort::SortModes;
+use pretty_git_ui::strings::{Language, Msg};
+use pretty_git_ui::{backend, render_ui, App, AppEvent, RepoContext};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
 use std::{
     error::Error,
     io,
+    path::Path,
+    process::Command,
     time::{Duration, Instant},
 };
-use tui::{
-    backend::{Backend, CrosstermBackend},
-    Terminal,
-};
-use ui::render_ui;
 
 const VERSION: &str = "0.1.0";
+/// 溜まった入力を処理し続ける最長時間。これを過ぎたら一度描画する (約60fps)
+const FRAME: Duration = Duration::from_millis(16);
+
+/// What the command line asks for
+enum Cli {
+    Version,
+    Help,
+    /// A wrong option or a missing value: the message, then the help
+    Usage(String),
+    Doctor(RepoContext),
+    /// A headless subcommand with the arguments after its name
+    Command {
+        repo: RepoContext,
+        name: String,
+        args: Vec<String>,
+    },
+    /// Start the TUI
+    Run {
+        repo: RepoContext,
+        view: StartView,
+        file: Option<String>,
+    },
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() > 1 {
-        match args[1].as_str() {
-            "-v" | "--version" => {
-                println!("pretty-git-ui version {VERSION}");
-                return Ok(());
-            },
-            "-h" | "--help" => {
-                print_help();
-                return Ok(());
-            },
-            _ => {
-                println!("Unknown option: {}", args[1]);
-                print_help();
-                return Ok(());
-            },
-        }
+    // Started by git or ssh as GIT_ASKPASS/SSH_ASKPASS: pass the prompt on to the app
+    if let Some(socket) = std::env::var_os(askpass::SOCKET_ENV) {
+        let prompt = std::env::args().nth(1).unwrap_or_default();
+        std::process::exit(askpass::run_helper(&socket, &prompt));
     }
+    let (repo, view, file) = match parse_args(std::env::args().skip(1))? {
+        Cli::Version => {
+            println!(
+                "pretty-git-ui version {VERSION} (git backend: {})",
+                backend::active().name()
+            );
+            return Ok(());
+        },
+        Cli::Help => {
+            print_help();
+            return Ok(());
+        },
+        Cli::Usage(message) => {
+            println!("{message}");
+            print_help();
+            return Ok(());
+        },
+        Cli::Doctor(repo) => {
+            run_doctor(&repo);
+            return Ok(());
+        },
+        Cli::Command { repo, name, args } => return run_command(&repo, &name, &args),
+        Cli::Run { repo, view, file } => (repo, view, file),
+    };
     // ターミナルのセットアップ
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // アプリの実行
     let tick_rate = Duration::from_millis(250);
-    let app = App::new();
+    let mut app = App::with_repo(repo);
+    if app.language.is_none() {
+        app.set_language(Language::from_env());
+    }
+    if let Some(path) = RecentRepos::default_path() {
+        app.enable_recent_repos(path);
+    }
+    if let Some(path) = SortModes::default_path() {
+        app.enable_sort_modes(path);
+    }
+    app.config_file = Config::path();
+    if let Some(path) = &file {
+        app.select_path(path);
+    }
+    app.open_start_view(view);
+    // Without it remote commands fail on prompts like before, which is all that can be done
+    let _ = askpass::start();
     let res = run_app(&mut terminal, app, tick_rate);
+    askpass::stop();
 
     // 後処理
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
@@ -69,176 +136,402 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Parses the arguments after the program name. Fails when the repository cannot be opened or
+/// the view is unknown
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Cli, Box<dyn Error>> {
+    let mut repo = RepoContext::current();
+    let mut view = StartView::default();
+    let mut file = None;
+    let mut doctor = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-v" | "--version" => return Ok(Cli::Version),
+            "-h" | "--help" => return Ok(Cli::Help),
+            "--doctor" => doctor = true,
+            "-C" => {
+                let Some(path) = args.next() else {
+                    return Ok(Cli::Usage(String::from("Missing path after -C")));
+                };
+                repo = RepoContext::open(path)?;
+            },
+            "--view" => {
+                let Some(name) = args.next() else {
+                    return Ok(Cli::Usage(String::from("Missing view after --view")));
+                };
+                view = StartView::parse(&name)?;
+            },
+            "--file" => {
+                let Some(path) = args.next() else {
+                    return Ok(Cli::Usage(String::from("Missing path after --file")));
+                };
+                file = Some(path);
+            },
+            name if cli::Command::NAMES.contains(&name) => {
+                return Ok(Cli::Command {
+                    repo,
+                    name: arg,
+                    args: args.collect(),
+                });
+            },
+            path if !path.starts_with('-') => {
+                repo = RepoContext::open(path)?;
+            },
+            _ => return Ok(Cli::Usage(format!("Unknown option: {arg}"))),
+        }
+    }
+    if doctor {
+        return Ok(Cli::Doctor(repo));
+    }
+    Ok(Cli::Run { repo, view, file })
+}
+
+/// Runs a headless subcommand and prints its JSON result. Exits with status 1 on failure.
+fn run_command(repo: &RepoContext, name: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let result = cli::Command::parse(name, args)
+        .and_then(|command| command.run(repo).map_err(|e| e.to_string()));
+    match result {
+        Ok(value) => {
+            println!("{}", serde_json::to_string_pretty(&value)?);
+            Ok(())
+        },
+        Err(e) => {
+            println!("{}", cli::error_json(&e));
+            std::process::exit(1);
+        },
+    }
+}
+
+/// Prints the environment checks; exit status 1 when one of them failed
+fn run_doctor(repo: &RepoContext) {
+    let checks = doctor::run(repo);
+    print!("{}", doctor::format_report(&checks));
+    if checks
+        .iter()
+        .any(|check| check.level == doctor::Level::Error)
+    {
+        std::process::exit(1);
+    }
+}
+
 fn print_help() {
     println!("pretty-git-ui - A beautiful terminal UI for Git");
-    println!("\nUsage: pretty-git-ui [OPTIONS]");
+    println!("\nUsage: pretty-git-ui [OPTIONS] [PATH]");
+    println!("       pretty-git-ui [-C <PATH>] <COMMAND>");
+    println!("\nArguments:");
+    println!("  [PATH]         Repository to open (default: current directory)");
+    println!("\nCommands (no TUI, print JSON; exit status 1 on error):");
+    println!("  status [--json]          Branch, upstream, summary and changed files");
+    println!("  stage <path>...          Stage files");
+    println!("  unstage <path>...        Unstage files");
+    println!("  commit -m <message>      Commit the staged changes");
     println!("\nOptions:");
+    println!("  -C <PATH>      Same as passing PATH");
+    println!("  --view <VIEW>  Start in status, log, branches or stash (default: status)");
+    println!("  --file <PATH>  Select this changed file (relative to the repository root)");
+    println!(
+        "  --doctor       Check git, its config, the terminal, git-lfs and gpg for bug reports;"
+    );
+    println!("                 exit status 1 when something is broken");
     println!("  -h, --help     Show this help message");
     println!("  -v, --version  Show version information");
+    println!("\nEnvironment:");
+    println!("  PRETTY_GIT_UI_BACKEND=command|libgit2");
+    println!("                 Select the git backend (libgit2 requires the `libgit2` feature)");
+    println!("  PRETTY_GIT_UI_STATE=<path>");
+    println!("                 Recent repositories file (default: ~/.local/state/pretty-git-ui/recent_repos)");
+    println!("  PRETTY_GIT_UI_CONFIG=<path>");
+    println!("                 Config file (default: ~/.config/pretty-git-ui/config.toml)");
+    println!("  LC_ALL, LC_MESSAGES, LANG");
+    println!("                 UI language when config.toml sets no `language` (ja_*: Japanese,");
+    println!("                 other locales, C or none: English)");
+    println!("  EDITOR         Editor for conflicts and commit messages; GIT_EDITOR, core.editor");
+    println!("                 and VISUAL come first, as in git (default: vi)");
     println!("\nKeyboard shortcuts:");
-    println!("  q              Quit application");
-    println!("  j/k or ↓/↑    Navigate files");
-    println!("  s              Stage/unstage selected file");
-    println!("  a              Stage/unstage all files");
-    println!("  c              Enter commit mode");
-    println!("  t              Enter stash message mode");
-    println!("  l              List stashes");
-    println!("  p              Apply latest stash");
-    println!("  r              Refresh file list");
-    println!("  d              Show diff preview (fullscreen)");
-    println!("  v              Toggle preview panel");
+    for binding in NORMAL_BINDINGS {
+        println!(
+            "  {:<15}{}",
+            binding.keys_label(),
+            binding.action.description()
+        );
+    }
+    print_mode_help();
+    print_view_help();
+}
+
+/// Keys of the modes opened from the file list
+fn print_mode_help() {
     println!("\nIn commit/stash mode:");
     println!("  Enter          Submit");
+    println!("  Ctrl+S         Toggle signing for this commit (commit mode)");
+    println!("  Ctrl+N         Skip the commit hooks for this commit, --no-verify (commit mode)");
+    println!("  Ctrl+A         Stage modified tracked files too, like commit -a (commit mode)");
+    println!("  Ctrl+O         Commit despite commit lint problems (commit mode)");
+    println!("  ↑/↓ Ctrl+T     Select a staged file / leave it out of this commit (commit mode)");
+    println!("  Ctrl+E         Write the commit message in $EDITOR (commit mode)");
+    println!("  Tab            Pick a commit type or gitmoji prefix (commit mode)");
+    println!("  Ctrl+P         Pick the name and email to commit as (commit mode)");
+    println!("  Ctrl+D         Toggle the Signed-off-by trailer (commit mode)");
+    println!(
+        "  Ctrl+R         Add recent authors as Co-authored-by and other trailers (commit mode)"
+    );
+    println!(
+        "  {{ticket}}       Typed, becomes the ticket id of the branch; also {{branch}}, snippets"
+    );
+    println!("  ←/→ Home/End   Move the cursor (all text inputs)");
+    println!("  Ctrl+W         Delete the word before the cursor (also Alt+Backspace)");
+    println!("  Ctrl+U         Delete everything before the cursor");
     println!("  Esc            Cancel");
+    println!("\nWhen a commit hook rejects the commit:");
+    println!("  j/k or ↓/↑    Scroll the hook output");
+    println!("  n              Retry with --no-verify (with confirmation)");
+    println!("  q/Esc          Back to the commit message");
     println!("\nIn preview mode:");
     println!("  j/k or ↓/↑    Scroll preview");
+    println!("  PgUp/PgDn      Scroll a page (Ctrl+u/Ctrl+d: half a page)");
+    println!("  g/G            Go to the top / bottom (also Home/End)");
+    println!("  /              Search the diff (case-insensitive unless the text has capitals)");
+    println!("  n/N            Next / previous match");
+    println!("  x              Hex dump of a binary file (up to 64 KiB)");
+    println!("  y              Copy the diff to the clipboard");
+    println!("  {{ / }} W / E    Context lines, ignore whitespace / blank lines");
     println!("  q/Esc          Exit preview");
+    println!("\nIn stash list:");
+    println!("  j/k or ↓/↑    Select stash");
+    println!("  Enter/d        Show stash diff");
+    println!("  a / p          Apply / pop stash");
+    println!("  x/D            Drop stash (with confirmation)");
+    println!("  q/Esc          Back to file list");
+    println!("\nIn commit prefix picker:");
+    println!("  j/k or ↓/↑    Select prefix");
+    println!("  Enter          Insert it (conventional types ask for an optional scope first)");
+    println!("  q/Esc          Back to the commit message");
+    println!("\nIn identity picker:");
+    println!("  j/k or ↓/↑    Select the repository setting or an [[identities]] profile");
+    println!("  Enter          Commit as it, for this commit only");
+    println!("  s              Save it as the repository's user.name and user.email");
+    println!("  q/Esc          Back to the commit message");
+    println!("\nWhen a push, pull or fetch asks for a password or passphrase:");
+    println!("  Enter          Send it to git (masked while typing)");
+    println!("  Esc            Cancel; the command fails as without a prompt");
+    println!("\nIn .gitignore chooser:");
+    println!("  j/k or ↓/↑    Select pattern");
+    println!("  Enter          Append it to .gitignore");
+    println!("  q/Esc          Cancel");
+    println!("\nOutside a git repository:");
+    println!("  i              git init the current directory");
+    println!("  I              git init choosing branch name, .gitignore and initial commit");
+    println!("  j/k, Enter     Open a recently opened repository");
+    println!("  q/Esc          Quit");
+    println!("\nIn repository switcher:");
+    println!("  j/k or ↓/↑    Select repository");
+    println!("  Enter          Open repository");
+    println!("  q/Esc          Back to file list");
+    println!("\nIn line staging editor:");
+    println!("  j/k or ↓/↑    Move to next/previous changed line");
+    println!("  Space          Select/deselect line");
+    println!("  a              Select/deselect the whole hunk");
+    println!("  Enter/s        Stage (or unstage) the selected lines");
+    println!("  q/Esc          Back to file list");
+    println!("\nIn conflict view:");
+    println!("  o / t          Resolve with ours / theirs");
+    println!("  e              Edit the file in $EDITOR");
+    println!("  j/k or ↓/↑    Scroll");
+    println!("  q/Esc          Back to file list");
+}
+
+/// Keys of the log, commit details, reflog, branch list, maintenance and config screens, then
+/// the mouse
+fn print_view_help() {
+    println!("\nIn commit log:");
+    println!("  j/k or ↓/↑    Select commit");
+    println!("  PgUp/PgDn      Move a page (Ctrl+u/Ctrl+d: half a page)");
+    println!("  Home/G or End  Go to the first / last commit");
+    println!("  Enter          Show commit details: message, author, parents, signature, files");
+    println!("  d              Show the whole commit diff");
+    println!("  /              Search commits: message text, author:NAME or path:PATH");
+    println!("  n / N          Jump to the next / previous match");
+    println!("  g / b          Mark commit good / bad for bisect (starts one if needed)");
+    println!("  O              Open the commit on GitHub/GitLab/Bitbucket in the browser");
+    println!("  y              Copy the full commit hash to the clipboard");
+    println!("  S / M / X      Reset the branch to the commit: --soft / --mixed / --hard");
+    println!(
+        "  f              Commit the staged (or all tracked) changes as a fixup! of the commit"
+    );
+    println!("  A              Squash the fixup! commits since the commit (rebase --autosquash)");
+    println!("  c              Compare the commit with HEAD");
+    println!("  Z              Export the commit as an archive");
+    println!("  r              Reload log");
+    println!("  q/Esc          Back to file list");
+    println!("\nIn commit details:");
+    println!("  j/k or ↓/↑    Move through the changed files and their expanded diffs");
+    println!("  n / N          Jump to the next / previous file");
+    println!("  Enter/Space    Expand the file to its diff, or collapse it");
+    println!("  d / D          Show the file's diff / the whole commit fullscreen");
+    println!("  y              Copy the full commit hash to the clipboard");
+    println!("  q/Esc          Back to commit log");
+    println!("\nIn compare view:");
+    println!("  j/k or ↓/↑    Select a file that differs between the two refs");
+    println!("  Enter/d / D    Show the file's diff / the whole diff fullscreen");
+    println!("  r              Reload");
+    println!("  q/Esc          Back to where the comparison was started");
+    println!("\nIn reflog:");
+    println!("  j/k or ↓/↑    Select entry");
+    println!("  Enter/d        Show the entry's commit diff");
+    println!("  c              Checkout entry as detached HEAD (with confirmation)");
+    println!("  x              Reset --hard to entry (with confirmation)");
+    println!("  r              Reload reflog");
+    println!("  q/Esc          Back to file list");
+    println!("\nIn branch list:");
+    println!("  j/k or ↓/↑    Select branch");
+    println!(
+        "  x/D            Delete branch; remote branches with push --delete (with confirmation)"
+    );
+    println!("  m              Merge into the current branch: --ff-only, --no-ff or --squash");
+    println!("  R              Rename the local branch (branch -m)");
+    println!("  u              Set the upstream of the local branch; empty unsets it");
+    println!("  c              Compare the current branch with the selected one");
+    println!("  Z              Export the branch as an archive");
+    println!("  f              Fetch with --prune, marking branches whose upstream is gone");
+    println!("  X              Delete all branches whose upstream is gone (with confirmation)");
+    println!("  r              Reload branches");
+    println!("  q/Esc          Back to file list");
+    println!("\nIn maintenance screen:");
+    println!("  j/k or ↓/↑    Select gc, prune or maintenance run");
+    println!("  Enter          Run it, showing its progress in the command log (prune asks first)");
+    println!("  r              Reload the repository statistics");
+    println!("  q/Esc          Back to file list");
+    println!("\nIn git config screen:");
+    println!("  j/k or ↓/↑    Select a key: user.name, user.email, pull.rebase, core.editor first");
+    println!("  Enter/e        Edit its value; Tab picks the local, global or system config");
+    println!("                 and an emptied value unsets the key");
+    println!("  r              Reload");
+    println!("  q/Esc          Back to file list");
+    println!("\nMouse:");
+    println!("  Click          Select file, commit or stash");
+    println!("  Wheel          Scroll the list, preview panel or help under the cursor");
     println!("\nWith preview panel:");
     println!("  Shift+j/k      Scroll preview panel");
-    println!("  v              Toggle preview panel");
 }
 
 /// イベントループで画面描画、入力処理、状態更新を行う
+///
+/// 描画するのは入力・リサイズ・tick の後だけ。キーリピートなどで溜まった入力は
+/// まとめて処理し、1フレームに1回だけ描画する
 fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     mut app: App,
     tick_rate: Duration,
 ) -> io::Result<()> {
-    let last_tick = Instant::now();
+    let mut last_tick = Instant::now();
+    let mut frames = FrameScheduler::new(FRAME);
 
     loop {
-        terminal.draw(|f| render_ui(f, &mut app))?;
+        if frames.take_draw() {
+            terminal.draw(|f| render_ui(f, &mut app))?;
+        }
 
         // タイムアウト計算
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
 
-        // イベントのポーリング
+        // イベントのポーリング。届いている入力はフレームの間まとめて処理する
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                match app.input_mode {
-                    // 通常モードのキー処理
-                    InputMode::Normal => match key.code {
-                        KeyCode::Char('q') => return Ok(()),
-                        KeyCode::Char('j') | KeyCode::Down => {
-                            if app.show_preview_panel {
-                                // Check if Shift is held for preview scroll
-                                if key
-                                    .modifiers
-                                    .contains(crossterm::event::KeyModifiers::SHIFT)
-                                {
-                                    app.scroll_preview_down();
-                                } else {
-                                    app.next();
-                                }
-                            } else {
-                                app.next();
-                            }
-                        },
-                        KeyCode::Char('k') | KeyCode::Up => {
-                            if app.show_preview_panel {
-                                // Check if Shift is held for preview scroll
-                                if key
-                                    .modifiers
-                                    .contains(crossterm::event::KeyModifiers::SHIFT)
-                                {
-                                    app.scroll_preview_up();
-                                } else {
-                                    app.previous();
-                                }
-                            } else {
-                                app.previous();
-                            }
-                        },
-                        KeyCode::Char('s') => app.stage_file(),
-                        KeyCode::Char('a') => app.stage_all_files(),
-                        KeyCode::Char('c') => {
-                            app.input_mode = InputMode::Commit;
-                        },
-                        KeyCode::Char('t') => {
-                            app.input_mode = InputMode::StashMessage;
-                        },
-                        KeyCode::Char('l') => app.list_stashes(),
-                        KeyCode::Char('p') => app.apply_latest_stash(),
-                        KeyCode::Char('r') => app.refresh_files(),
-                        KeyCode::Char('h') => app.show_help(),
-                        KeyCode::Char('d') => app.show_preview(),
-                        KeyCode::Char('v') => app.toggle_preview_panel(),
-                        _ => {},
-                    },
-                    // コミットモードのキー処理
-                    InputMode::Commit => match key.code {
-                        KeyCode::Esc => {
-                            app.input_mode = InputMode::Normal;
-                        },
-                        KeyCode::Enter => {
-                            app.commit();
-                        },
-                        KeyCode::Char(c) => {
-                            app.commit_message.push(c);
-                        },
-                        KeyCode::Backspace => {
-                            app.commit_message.pop();
-                        },
-                        _ => {},
-                    },
-                    // スタッシュメッセージモードのキー処理
-                    InputMode::StashMessage => match key.code {
-                        KeyCode::Esc => {
-                            app.input_mode = InputMode::Normal;
-                            app.stash_message.clear();
-                        },
-                        KeyCode::Enter => {
-                            app.stash_changes();
-                        },
-                        KeyCode::Char(c) => {
-                            app.stash_message.push(c);
-                        },
-                        KeyCode::Backspace => {
-                            app.stash_message.pop();
-                        },
-                        _ => {},
-                    },
-                    // Confirm mode key processing
-                    InputMode::Confirm { .. } => match key.code {
-                        KeyCode::Char('y') | KeyCode::Char('Y') => {
-                            app.handle_confirm(true);
-                        },
-                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                            app.handle_confirm(false);
-                        },
-                        _ => {},
-                    },
-                    // Help mode key processing
-                    InputMode::Help => match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc | KeyCode::Char('h') => {
-                            app.exit_help();
-                        },
-                        KeyCode::Char('j') | KeyCode::Down => {
-                            app.scroll_help_down();
-                        },
-                        KeyCode::Char('k') | KeyCode::Up => {
-                            app.scroll_help_up();
-                        },
-                        _ => {},
-                    },
-                    // Preview mode key processing (fullscreen)
-                    InputMode::Preview { .. } => match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => {
-                            app.exit_preview();
-                        },
-                        KeyCode::Char('j') | KeyCode::Down => {
-                            app.scroll_preview_down();
-                        },
-                        KeyCode::Char('k') | KeyCode::Up => {
-                            app.scroll_preview_up();
-                        },
-                        _ => {},
-                    },
+            frames.start_frame(Instant::now());
+            loop {
+                // 扱わないイベントだけなら描き直さない
+                if let Some(event) = AppEvent::from_crossterm(event::read()?) {
+                    frames.invalidate();
+                    if handle_terminal_event(terminal, &mut app, event)? {
+                        return Ok(());
+                    }
+                }
+                if frames.frame_over(Instant::now()) || !crossterm::event::poll(Duration::ZERO)? {
+                    break;
                 }
             }
         }
 
+        if last_tick.elapsed() >= tick_rate {
+            app.handle_event(AppEvent::Tick);
+            last_tick = Instant::now();
+            frames.invalidate();
+        }
+    }
+}
+
+/// 1つの入力を処理する。終了する場合は true を返す
+fn handle_terminal_event<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    event: AppEvent,
+) -> io::Result<bool> {
+    match event {
+        AppEvent::Key(_) => {
+            if app.handle_event(event) {
+                return Ok(true);
+            }
+            if let Some(path) = app.pending_editor.take() {
+                run_editor(terminal, app, &path)?;
+            }
+            if let Some(text) = app.pending_clipboard.take() {
+                if let Err(e) = clipboard::copy(&text) {
+                    app.status.error(Msg::Error.fill(app.language(), &[&e]));
+                }
+            }
+        },
+        // The new layout has to be drawn before the scroll limits can follow it
+        AppEvent::Resize => {
+            terminal.draw(|f| render_ui(f, app))?;
+            app.handle_event(AppEvent::Resize);
+        },
+        event => {
+            app.handle_event(event);
+        },
     }
+    Ok(false)
+}
+
+/// 端末を一時的に通常モードへ戻して `$EDITOR` でファイルを開く
+fn run_editor<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    path: &Path,
+) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )?;
+
+    // Like git, the shell splits the editor so quoted paths and arguments in it work
+    let editor = GitOperations::editor(&app.repo);
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$@\""))
+        .arg(&editor)
+        .arg(path)
+        .status();
+
+    enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    terminal.clear()?;
+
+    match status {
+        Ok(status) if status.success() => app.editor_closed(path),
+        Ok(status) => app
+            .status
+            .error(Msg::EditorExited.fill(app.language(), &[&editor, &status])),
+        Err(e) => app
+            .status
+            .error(Msg::EditorFailed.fill(app.language(), &[&editor, &e])),
+    }
+    Ok(())
 }
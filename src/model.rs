@@ -0,0 +1,78 @@
+//! Serializable view of a repository for tools that use this crate as a git-status library.
+//!
+//! ```no_run
+//! use pretty_git_ui::{model, RepoContext};
+//!
+//! let repo = RepoContext::open(".").unwrap();
+//! let snapshot = model::snapshot(&repo).unwrap();
+//! println!("{} files changed on {}", snapshot.files.len(), snapshot.branch.name);
+//! ```
+
+use crate::error::GitError;
+use crate::git::GitOperations;
+pub use crate::git::{FileEntry, StashEntry, StatusSummary, UpstreamStatus};
+use crate::repo::RepoContext;
+use serde::{Deserialize, Serialize};
+
+/// The checked-out branch and its upstream
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BranchInfo {
+    /// Branch name as shown in the status bar, `(detached HEAD)` when no branch is checked out
+    pub name: String,
+    pub upstream: Option<UpstreamStatus>,
+}
+
+/// Everything the file list and status bar show, in one value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoSnapshot {
+    pub branch: BranchInfo,
+    pub summary: StatusSummary,
+    pub files: Vec<FileEntry>,
+    pub stashes: Vec<StashEntry>,
+}
+
+/// Reads the branch, working tree status and stashes of `repo`
+pub fn snapshot(repo: &RepoContext) -> Result<RepoSnapshot, GitError> {
+    let files = GitOperations::get_status(repo)?;
+    Ok(RepoSnapshot {
+        branch: BranchInfo {
+            name: GitOperations::get_current_branch(repo)?,
+            upstream: GitOperations::get_upstream_status(repo)?,
+        },
+        summary: StatusSummary::from_entries(&files),
+        files,
+        stashes: GitOperations::get_stashes(repo)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trips_through_json() {
+        let files = vec![
+            FileEntry::new("a.txt", 'M', ' '),
+            FileEntry::new("b.txt", '?', '?'),
+        ];
+        let snapshot = RepoSnapshot {
+            branch: BranchInfo {
+                name: "main".to_string(),
+                upstream: Some(UpstreamStatus {
+                    name: "origin/main".to_string(),
+                    ahead: 1,
+                    behind: 0,
+                }),
+            },
+            summary: StatusSummary::from_entries(&files),
+            files,
+            stashes: Vec::new(),
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.contains("\"origin/main\""));
+        assert_eq!(
+            serde_json::from_str::<RepoSnapshot>(&json).unwrap(),
+            snapshot
+        );
+    }
+}
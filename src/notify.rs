@@ -0,0 +1,41 @@
+//! Announcing finished background operations outside the UI: the terminal bell and desktop
+//! notifications.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+pub fn bell() -> Result<(), String> {
+    let mut stdout = io::stdout();
+    stdout
+        .write_all(b"\x07")
+        .and_then(|()| stdout.flush())
+        .map_err(|e| format!("Failed to ring the bell: {e}"))
+}
+
+/// `osascript` on macOS, `notify-send` elsewhere; does not wait for it
+pub fn desktop(title: &str, body: &str) -> Result<(), String> {
+    let mut command = if cfg!(target_os = "macos") {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            &format!(
+                "display notification {} with title {}",
+                quote(body),
+                quote(title)
+            ),
+        ]);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args([title, body]);
+        command
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+        .map_err(|e| format!("Failed to send a desktop notification: {e}"))
+}
@@ -0,0 +1,252 @@
+use crate::diff::{self, HunkHeader, Line, LineKind};
+use std::collections::BTreeSet;
+
+/// The diff of one file, split into lines so individual added/removed lines can be staged.
+/// Selections are indices into `lines`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilePatch {
+    pub lines: Vec<Line>,
+}
+
+impl FilePatch {
+    /// The first file of `diff`, flattened into its header, hunk header and hunk lines
+    pub fn parse(diff: &str) -> Self {
+        let lines = diff::parse(diff)
+            .first()
+            .map(|file| file.lines().collect())
+            .unwrap_or_default();
+        Self { lines }
+    }
+
+    /// Whether the line is an added or removed line that can be selected
+    pub fn is_change(&self, index: usize) -> bool {
+        self.lines
+            .get(index)
+            .is_some_and(|line| line.kind.is_change())
+    }
+
+    pub fn has_changes(&self) -> bool {
+        (0..self.lines.len()).any(|i| self.is_change(i))
+    }
+
+    /// Indices of the changed lines in the hunk containing `index`
+    pub fn hunk_changes(&self, index: usize) -> Vec<usize> {
+        let start = (0..=index.min(self.lines.len().saturating_sub(1)))
+            .rev()
+            .find(|&i| self.lines[i].kind == LineKind::HunkHeader)
+            .unwrap_or(0);
+        (start + 1..self.lines.len())
+            .take_while(|&i| self.lines[i].kind != LineKind::HunkHeader)
+            .filter(|&i| self.is_change(i))
+            .collect()
+    }
+
+    /// Builds a patch containing only the selected changes, the way `git add -p`'s edit mode
+    /// does: unselected lines on the side the patch is applied to become context, the others
+    /// are dropped. With `reverse` the patch is meant for `git apply -R`, i.e. the new side is
+    /// what is currently in the index. A new file can be staged in part only when it is in
+    /// the index as intent-to-add (`git add -N`), so the patch applies to an empty entry.
+    pub fn build(&self, selected: &BTreeSet<usize>, reverse: bool) -> Result<String, String> {
+        let selected: BTreeSet<usize> = selected
+            .iter()
+            .copied()
+            .filter(|&i| self.is_change(i))
+            .collect();
+        if selected.is_empty() {
+            return Err("No lines selected".to_string());
+        }
+
+        let header: Vec<&str> = self
+            .lines
+            .iter()
+            .take_while(|line| line.kind == LineKind::FileHeader)
+            .map(|line| line.text.as_str())
+            .collect();
+        let whole_file_only = header.iter().any(|line| {
+            line.starts_with("deleted file mode") || (reverse && line.starts_with("new file mode"))
+        });
+        let total_changes = (0..self.lines.len()).filter(|&i| self.is_change(i)).count();
+        if whole_file_only && selected.len() < total_changes {
+            return Err("New and deleted files can only be staged as a whole".to_string());
+        }
+
+        let mut patch = String::new();
+        for line in &header {
+            patch.push_str(line);
+            patch.push('\n');
+        }
+
+        // Sum of (new - old) line counts of the hunks emitted so far
+        let mut delta: i64 = 0;
+        let mut i = header.len();
+        while i < self.lines.len() {
+            let header = HunkHeader::parse(&self.lines[i].text);
+            let (old_start, new_start) = match header.as_ref().map(|h| (h.old.as_slice(), h.new)) {
+                Some(([old], new)) => (i64::from(old.start), i64::from(new.start)),
+                _ => return Err(format!("Invalid hunk header: {}", self.lines[i].text)),
+            };
+            i += 1;
+
+            let mut body = String::new();
+            let (mut old_count, mut new_count) = (0_i64, 0_i64);
+            let mut changed = false;
+            let mut last_dropped = false;
+            while i < self.lines.len() && self.lines[i].kind != LineKind::HunkHeader {
+                let line = &self.lines[i];
+                let keep = selected.contains(&i);
+                let as_context = || format!(" {}", &line.text[1..]);
+                let text = match line.kind {
+                    LineKind::Added if keep => {
+                        new_count += 1;
+                        Some(line.text.clone())
+                    },
+                    LineKind::Removed if keep => {
+                        old_count += 1;
+                        Some(line.text.clone())
+                    },
+                    // The line exists on the side being patched: keep it unchanged
+                    LineKind::Added if reverse => Some(as_context()),
+                    LineKind::Removed if !reverse => Some(as_context()),
+                    LineKind::Added | LineKind::Removed => None,
+                    LineKind::NoNewline if last_dropped => None,
+                    _ => Some(line.text.clone()),
+                };
+                match line.kind {
+                    LineKind::Added | LineKind::Removed if keep => changed = true,
+                    LineKind::Added | LineKind::Removed if text.is_some() => {
+                        old_count += 1;
+                        new_count += 1;
+                    },
+                    LineKind::Context => {
+                        old_count += 1;
+                        new_count += 1;
+                    },
+                    _ => {},
+                }
+                if line.kind != LineKind::NoNewline {
+                    last_dropped = text.is_none();
+                }
+                if let Some(text) = text {
+                    body.push_str(&text);
+                    body.push('\n');
+                }
+                i += 1;
+            }
+            if !changed {
+                continue;
+            }
+
+            let (old_start, new_start) = if reverse {
+                (
+                    shift_start(new_start, new_count, old_count, -delta),
+                    new_start,
+                )
+            } else {
+                (
+                    old_start,
+                    shift_start(old_start, old_count, new_count, delta),
+                )
+            };
+            let hunk_header = format!("@@ -{old_start},{old_count} +{new_start},{new_count} @@\n");
+            patch.push_str(&hunk_header);
+            patch.push_str(&body);
+            delta += new_count - old_count;
+        }
+        Ok(patch)
+    }
+}
+
+/// Start of the other side of a hunk. An empty side names the line before the change.
+const fn shift_start(start: i64, count: i64, other_count: i64, delta: i64) -> i64 {
+    let first = if count == 0 { start + 1 } else { start };
+    let other_first = first + delta;
+    if other_count == 0 {
+        other_first - 1
+    } else {
+        other_first
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/f.txt b/f.txt
+index 1111111..2222222 100644
+--- a/f.txt
++++ b/f.txt
+@@ -1,3 +1,3 @@
+ one
+-two
++TWO
+ three
+@@ -10,2 +10,3 @@
+ ten
++new
+ eleven
+";
+
+    #[test]
+    fn test_parse_classifies_lines() {
+        let patch = FilePatch::parse(DIFF);
+        assert_eq!(patch.lines[2].kind, LineKind::FileHeader);
+        assert_eq!(patch.lines[4].kind, LineKind::HunkHeader);
+        assert_eq!(patch.lines[6].kind, LineKind::Removed);
+        assert_eq!(patch.lines[7].kind, LineKind::Added);
+        assert!(patch.is_change(11));
+        assert!(!patch.is_change(2));
+        assert_eq!(patch.hunk_changes(5), vec![6, 7]);
+        assert_eq!(patch.hunk_changes(12), vec![11]);
+    }
+
+    #[test]
+    fn test_build_selected_lines() {
+        let patch = FilePatch::parse(DIFF);
+        // Only the added line of the second hunk; the first hunk is left out
+        assert_eq!(
+            patch.build(&BTreeSet::from([11]), false).unwrap(),
+            "diff --git a/f.txt b/f.txt
+index 1111111..2222222 100644
+--- a/f.txt
++++ b/f.txt
+@@ -10,2 +10,3 @@
+ ten
++new
+ eleven
+"
+        );
+        // Only the removal: the unselected addition is dropped, so the next hunk shifts
+        let built = patch.build(&BTreeSet::from([6, 11]), false).unwrap();
+        assert!(built.contains("@@ -1,3 +1,2 @@\n one\n-two\n three\n"));
+        assert!(built.contains("@@ -10,2 +9,3 @@\n"));
+        assert!(patch.build(&BTreeSet::from([2]), false).is_err());
+    }
+
+    #[test]
+    fn test_build_reverse_keeps_new_side() {
+        let patch = FilePatch::parse(DIFF);
+        // Unstaging only the addition: the unselected removal is dropped
+        let built = patch.build(&BTreeSet::from([7]), true).unwrap();
+        assert!(built.contains("@@ -1,2 +1,3 @@\n one\n+TWO\n three\n"));
+    }
+
+    #[test]
+    fn test_new_file_must_be_unstaged_whole() {
+        let diff = "diff --git a/n b/n\nnew file mode 100644\n--- /dev/null\n+++ b/n\n@@ -0,0 +1,2 @@\n+a\n+b\n";
+        let patch = FilePatch::parse(diff);
+        assert!(patch.build(&BTreeSet::from([5]), true).is_err());
+        assert!(patch
+            .build(&BTreeSet::from([5, 6]), true)
+            .unwrap()
+            .contains("@@ -0,0 +1,2 @@"));
+        // Staging part of an intent-to-add file
+        assert!(patch
+            .build(&BTreeSet::from([6]), false)
+            .unwrap()
+            .ends_with("@@ -0,0 +1,1 @@\n+b\n"));
+        let deleted = "diff --git a/n b/n\ndeleted file mode 100644\n--- a/n\n+++ /dev/null\n@@ -1,2 +0,0 @@\n-a\n-b\n";
+        assert!(FilePatch::parse(deleted)
+            .build(&BTreeSet::from([5]), false)
+            .is_err());
+    }
+}
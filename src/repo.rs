@@ -0,0 +1,233 @@
+use crate::command_log::LoggedCommand;
+use crate::error::GitError;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Environment variable that points at an explicit recent-repositories state file
+pub const STATE_ENV: &str = "PRETTY_GIT_UI_STATE";
+
+/// How many repositories the switcher remembers
+const MAX_RECENT_REPOS: usize = 10;
+
+/// The repository every git operation runs against.
+///
+/// `RepoContext::current()` follows the process working directory, which is what the CLI uses
+/// when started without a path. `RepoContext::open` pins a repository so the library can be
+/// embedded without changing the working directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoContext {
+    workdir: Option<PathBuf>,
+    /// Set on every command run for the repository, e.g. `GIT_DIR`
+    env: Vec<(OsString, OsString)>,
+}
+
+impl RepoContext {
+    pub const fn current() -> Self {
+        Self {
+            workdir: None,
+            env: Vec::new(),
+        }
+    }
+
+    /// Opens the repository containing `path`, resolved to its top-level directory
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, GitError> {
+        let path = path.as_ref();
+        let output = Command::new("git")
+            .args(["rev-parse", "--show-toplevel"])
+            .current_dir(path)
+            .logged_output()
+            .map_err(|e| GitError::io(format!("open {}", path.display()), e))?;
+
+        if !output.status.success() {
+            return Err(GitError::NotARepo(path.to_path_buf()));
+        }
+        let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Self {
+            workdir: Some(PathBuf::from(root)),
+            env: Vec::new(),
+        })
+    }
+
+    /// Opens the repository now at this context's directory, such as one `git init` just
+    /// created there, keeping the environment set with `with_env`
+    pub fn reopen(&self) -> Result<Self, GitError> {
+        Ok(Self {
+            env: self.env.clone(),
+            ..Self::open(self.dir())?
+        })
+    }
+
+    /// A work tree whose repository is kept elsewhere, like
+    /// `git --git-dir=<git_dir> --work-tree=<work_tree>` for dotfiles in a bare repository
+    pub fn open_separate(
+        git_dir: impl AsRef<Path>,
+        work_tree: impl AsRef<Path>,
+    ) -> Result<Self, GitError> {
+        let absolute = |path: &Path| {
+            path.canonicalize()
+                .map_err(|e| GitError::io(format!("open {}", path.display()), e))
+        };
+        let git_dir = absolute(git_dir.as_ref())?;
+        let work_tree = absolute(work_tree.as_ref())?;
+        let repo = Self {
+            workdir: Some(work_tree.clone()),
+            env: Vec::new(),
+        }
+        .with_env("GIT_DIR", &git_dir)
+        .with_env("GIT_WORK_TREE", &work_tree);
+
+        let output = repo
+            .git()
+            .args(["rev-parse", "--git-dir"])
+            .logged_output()
+            .map_err(|e| GitError::io(format!("open {}", git_dir.display()), e))?;
+        if !output.status.success() {
+            return Err(GitError::NotARepo(git_dir));
+        }
+        Ok(repo)
+    }
+
+    /// Adds `key=value` to the environment of every command run for the repository
+    #[must_use]
+    pub fn with_env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        let key = key.as_ref().to_os_string();
+        self.env.retain(|(existing, _)| *existing != key);
+        self.env.push((key, value.as_ref().to_os_string()));
+        self
+    }
+
+    /// The value `with_env` set for `key`
+    pub fn env(&self, key: &str) -> Option<&OsStr> {
+        self.env
+            .iter()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value.as_os_str())
+    }
+
+    pub fn workdir(&self) -> Option<&Path> {
+        self.workdir.as_deref()
+    }
+
+    /// Directory git commands run in (`.` for the process working directory)
+    pub fn dir(&self) -> &Path {
+        self.workdir().unwrap_or_else(|| Path::new("."))
+    }
+
+    /// A `git` command that runs inside this repository
+    pub fn git(&self) -> Command {
+        self.command("git")
+    }
+
+    /// A command that runs inside this repository with its environment, such as a hook
+    pub fn command(&self, program: impl AsRef<OsStr>) -> Command {
+        let mut command = Command::new(program);
+        if let Some(dir) = &self.workdir {
+            command.current_dir(dir);
+        }
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        command
+    }
+
+    /// Resolves a path printed by git (relative to the repository) for file system access
+    pub fn path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.dir().join(relative)
+    }
+}
+
+/// Most recently opened repositories, newest first, stored one path per line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecentRepos {
+    pub repos: Vec<PathBuf>,
+}
+
+impl RecentRepos {
+    /// `$PRETTY_GIT_UI_STATE`, else `$XDG_STATE_HOME/pretty-git-ui/recent_repos`,
+    /// else `~/.local/state/pretty-git-ui/recent_repos`
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(STATE_ENV) {
+            return Some(PathBuf::from(path));
+        }
+        let state_dir = std::env::var_os("XDG_STATE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+            })?;
+        Some(state_dir.join("pretty-git-ui").join("recent_repos"))
+    }
+
+    /// Reads the state file; a missing or unreadable file yields an empty list
+    pub fn load(path: &Path) -> Self {
+        let repos = std::fs::read_to_string(path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(PathBuf::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { repos }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        let mut content = String::new();
+        for repo in &self.repos {
+            content.push_str(&repo.to_string_lossy());
+            content.push('\n');
+        }
+        std::fs::write(path, content)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Moves `repo` to the front, dropping the oldest entries beyond the limit
+    pub fn push(&mut self, repo: &Path) {
+        self.repos.retain(|existing| existing != repo);
+        self.repos.insert(0, repo.to_path_buf());
+        self.repos.truncate(MAX_RECENT_REPOS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_context_uses_working_directory() {
+        let repo = RepoContext::current();
+        assert_eq!(repo.workdir(), None);
+        assert_eq!(repo.path("a.txt"), Path::new("./a.txt"));
+    }
+
+    #[test]
+    fn test_recent_repos_push_and_persist() {
+        let mut recent = RecentRepos::default();
+        recent.push(Path::new("/work/a"));
+        recent.push(Path::new("/work/b"));
+        recent.push(Path::new("/work/a"));
+        assert_eq!(
+            recent.repos,
+            vec![PathBuf::from("/work/a"), PathBuf::from("/work/b")]
+        );
+
+        for i in 0..20 {
+            recent.push(&PathBuf::from(format!("/work/{i}")));
+        }
+        assert_eq!(recent.repos.len(), MAX_RECENT_REPOS);
+        assert_eq!(recent.repos[0], PathBuf::from("/work/19"));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state").join("recent_repos");
+        recent.save(&path).unwrap();
+        assert_eq!(RecentRepos::load(&path), recent);
+        assert_eq!(
+            RecentRepos::load(&dir.path().join("missing")),
+            RecentRepos::default()
+        );
+    }
+}
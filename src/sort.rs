@@ -0,0 +1,262 @@
+//! Order of the files within each section of the file list, and the sort mode chosen for
+//! each repository.
+//!
+//! `App::files` stays in path order as git lists it; the file list orders the entries of
+//! every section with `sort_section` when it builds its rows.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::git::{FileEntry, Section};
+use crate::repo::RecentRepos;
+use crate::strings::Msg;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// Alphabetical by path, as `git status` lists them
+    #[default]
+    Path,
+    /// The files directly in a directory before those in its subdirectories
+    Directory,
+    /// Added, modified, renamed, then deleted files
+    Status,
+    /// Most recently modified first; deleted files last
+    Modified,
+}
+
+/// Status codes in the order `SortMode::Status` lists them
+const STATUS_ORDER: &str = "AMRCTDU?";
+
+impl SortMode {
+    pub const ALL: [Self; 4] = [Self::Path, Self::Directory, Self::Status, Self::Modified];
+
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Path => Self::Directory,
+            Self::Directory => Self::Status,
+            Self::Status => Self::Modified,
+            Self::Modified => Self::Path,
+        }
+    }
+
+    /// How the mode is written to the state file
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Path => "path",
+            Self::Directory => "directory",
+            Self::Status => "status",
+            Self::Modified => "modified",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.as_str() == value)
+    }
+
+    pub const fn label(self) -> Msg {
+        match self {
+            Self::Path => Msg::SortPath,
+            Self::Directory => Msg::SortDirectory,
+            Self::Status => Msg::SortStatus,
+            Self::Modified => Msg::SortModified,
+        }
+    }
+
+    /// Whether sorting needs the modification times of the files
+    pub const fn uses_mtime(self) -> bool {
+        matches!(self, Self::Modified)
+    }
+}
+
+/// Orders `indices` into `files`, the entries listed under `section`. `mtime` looks up the
+/// working tree modification time of a path; only `SortMode::Modified` calls it.
+pub fn sort_section(
+    mode: SortMode,
+    files: &[FileEntry],
+    section: Section,
+    indices: &mut [usize],
+    mtime: impl Fn(&str) -> Option<SystemTime>,
+) {
+    let entry = |index: &usize| &files[*index];
+    match mode {
+        SortMode::Path => indices.sort_by(|a, b| entry(a).path.cmp(&entry(b).path)),
+        SortMode::Directory => indices.sort_by(|a, b| {
+            split_dir(&entry(a).path)
+                .cmp(&split_dir(&entry(b).path))
+                .then_with(|| entry(a).path.cmp(&entry(b).path))
+        }),
+        SortMode::Status => indices.sort_by(|a, b| {
+            status_rank(entry(a), section)
+                .cmp(&status_rank(entry(b), section))
+                .then_with(|| entry(a).path.cmp(&entry(b).path))
+        }),
+        SortMode::Modified => indices.sort_by(|a, b| {
+            let newest_first = |index: &usize| Reverse(mtime(&entry(index).path));
+            newest_first(a)
+                .cmp(&newest_first(b))
+                .then_with(|| entry(a).path.cmp(&entry(b).path))
+        }),
+    }
+}
+
+/// `(directory, file name)`, with `""` for files at the root so they come first. Untracked
+/// directories (`dir/`) sort with the files of their parent.
+fn split_dir(path: &str) -> (&str, &str) {
+    path.trim_end_matches('/')
+        .rsplit_once('/')
+        .unwrap_or(("", path))
+}
+
+fn status_rank(entry: &FileEntry, section: Section) -> usize {
+    let code = match section {
+        Section::Unstaged => entry.worktree_status,
+        Section::Conflicted | Section::Staged | Section::Untracked => entry.index_status,
+    };
+    STATUS_ORDER.find(code).unwrap_or(STATUS_ORDER.len())
+}
+
+/// Working tree modification times of `files`; deleted files are left out
+pub fn modification_times(
+    files: &[FileEntry],
+    resolve: impl Fn(&str) -> PathBuf,
+) -> HashMap<String, SystemTime> {
+    files
+        .iter()
+        .filter_map(|entry| {
+            let modified = std::fs::symlink_metadata(resolve(&entry.path))
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            Some((entry.path.clone(), modified))
+        })
+        .collect()
+}
+
+/// The sort mode of every repository it was changed in, stored as `mode<TAB>path` lines
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SortModes {
+    pub modes: BTreeMap<PathBuf, SortMode>,
+}
+
+impl SortModes {
+    /// `sort_modes` next to the recent repositories state file
+    pub fn default_path() -> Option<PathBuf> {
+        Some(RecentRepos::default_path()?.with_file_name("sort_modes"))
+    }
+
+    /// Reads the state file; a missing file and lines that do not parse are ignored
+    pub fn load(path: &Path) -> Self {
+        let content = std::fs::read_to_string(path).unwrap_or_default();
+        let modes = content
+            .lines()
+            .filter_map(|line| {
+                let (mode, repo) = line.split_once('\t')?;
+                Some((PathBuf::from(repo), SortMode::parse(mode)?))
+            })
+            .collect();
+        Self { modes }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        let mut content = String::new();
+        for (repo, mode) in &self.modes {
+            content.push_str(mode.as_str());
+            content.push('\t');
+            content.push_str(&repo.to_string_lossy());
+            content.push('\n');
+        }
+        std::fs::write(path, content)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    pub fn get(&self, repo: &Path) -> SortMode {
+        self.modes.get(repo).copied().unwrap_or_default()
+    }
+
+    /// Records `mode` for `repo`; the default mode is not stored
+    pub fn set(&mut self, repo: &Path, mode: SortMode) {
+        if mode == SortMode::default() {
+            self.modes.remove(repo);
+        } else {
+            self.modes.insert(repo.to_path_buf(), mode);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn sorted(mode: SortMode, files: &[FileEntry], section: Section) -> Vec<&str> {
+        let mtimes: HashMap<String, SystemTime> = files
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.worktree_status != 'D')
+            .map(|(i, entry)| {
+                let seconds = u64::try_from(i).unwrap();
+                (
+                    entry.path.clone(),
+                    SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+                )
+            })
+            .collect();
+        let mut indices: Vec<usize> = (0..files.len()).collect();
+        sort_section(mode, files, section, &mut indices, |path| {
+            mtimes.get(path).copied()
+        });
+        indices.iter().map(|&i| files[i].path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_modes() {
+        let files = [
+            FileEntry::new("a.txt", ' ', 'M'),
+            FileEntry::new("a/b/c.txt", ' ', 'D'),
+            FileEntry::new("a/d.txt", ' ', 'M'),
+            FileEntry::new("z.txt", ' ', 'A'),
+        ];
+        assert_eq!(
+            sorted(SortMode::Path, &files, Section::Unstaged),
+            vec!["a.txt", "a/b/c.txt", "a/d.txt", "z.txt"]
+        );
+        assert_eq!(
+            sorted(SortMode::Directory, &files, Section::Unstaged),
+            vec!["a.txt", "z.txt", "a/d.txt", "a/b/c.txt"]
+        );
+        assert_eq!(
+            sorted(SortMode::Status, &files, Section::Unstaged),
+            vec!["z.txt", "a.txt", "a/d.txt", "a/b/c.txt"]
+        );
+        assert_eq!(
+            sorted(SortMode::Modified, &files, Section::Unstaged),
+            vec!["z.txt", "a/d.txt", "a.txt", "a/b/c.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_modes_persist_per_repo() {
+        let mut modes = SortModes::default();
+        modes.set(Path::new("/work/a"), SortMode::Modified);
+        modes.set(Path::new("/work/b"), SortMode::Status);
+        modes.set(Path::new("/work/b"), SortMode::Path);
+        assert_eq!(modes.get(Path::new("/work/a")), SortMode::Modified);
+        assert_eq!(modes.get(Path::new("/work/b")), SortMode::Path);
+        assert_eq!(modes.modes.len(), 1);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state").join("sort_modes");
+        modes.save(&path).unwrap();
+        assert_eq!(SortModes::load(&path), modes);
+        assert_eq!(
+            SortModes::load(&dir.path().join("missing")),
+            SortModes::default()
+        );
+    }
+}
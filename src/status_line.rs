@@ -0,0 +1,149 @@
+//! The message line at the bottom: what recent operations reported, newest last. Successes
+//! fade after a few seconds, errors stay until dismissed with `Esc`.
+
+use std::time::{Duration, Instant};
+
+/// How long a success message is shown before it expires on tick
+pub const SUCCESS_TIMEOUT: Duration = Duration::from_secs(5);
+/// Messages kept at most; the oldest are dropped first
+const MAX_MESSAGES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Progress and hints, replaced by the next message
+    Info,
+    /// A finished operation, expiring after `SUCCESS_TIMEOUT`
+    Success,
+    /// A failure, kept until dismissed
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusMessage {
+    pub text: String,
+    pub severity: Severity,
+    pub at: Instant,
+}
+
+/// The queue of messages; the newest one is shown
+#[derive(Debug, Clone, Default)]
+pub struct StatusLine {
+    messages: Vec<StatusMessage>,
+    /// Successes and errors not yet taken into the history
+    outcomes: Vec<StatusMessage>,
+}
+
+impl StatusLine {
+    /// A line showing the info message `text`
+    pub fn new(text: impl Into<String>) -> Self {
+        let mut line = Self::default();
+        line.info(text);
+        line
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(Severity::Info, text.into(), Instant::now());
+    }
+
+    pub fn success(&mut self, text: impl Into<String>) {
+        self.push(Severity::Success, text.into(), Instant::now());
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(Severity::Error, text.into(), Instant::now());
+    }
+
+    /// Adds a message from `at`. An info message only lasts until the next message.
+    pub fn push(&mut self, severity: Severity, text: String, at: Instant) {
+        self.messages
+            .retain(|message| message.severity != Severity::Info);
+        let message = StatusMessage { text, severity, at };
+        if severity != Severity::Info {
+            self.outcomes.push(message.clone());
+        }
+        self.messages.push(message);
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+    }
+
+    /// The message shown on the line
+    pub fn current(&self) -> Option<&StatusMessage> {
+        self.messages.last()
+    }
+
+    /// Text of the shown message, empty when there is none
+    pub fn text(&self) -> &str {
+        self.current().map_or("", |message| message.text.as_str())
+    }
+
+    pub fn severity(&self) -> Option<Severity> {
+        self.current().map(|message| message.severity)
+    }
+
+    /// Errors behind the shown message, which come back as newer messages go away
+    pub fn hidden_errors(&self) -> usize {
+        let hidden = self.messages.len().saturating_sub(1);
+        self.messages[..hidden]
+            .iter()
+            .filter(|message| message.severity == Severity::Error)
+            .count()
+    }
+
+    /// Drops success messages older than `SUCCESS_TIMEOUT` at `now`
+    pub fn expire(&mut self, now: Instant) {
+        self.messages.retain(|message| {
+            message.severity != Severity::Success
+                || now.saturating_duration_since(message.at) < SUCCESS_TIMEOUT
+        });
+    }
+
+    /// The successes and errors reported since the last call, oldest first
+    pub fn take_outcomes(&mut self) -> Vec<StatusMessage> {
+        std::mem::take(&mut self.outcomes)
+    }
+
+    /// Removes the shown message, bringing back the one before it
+    pub fn dismiss(&mut self) {
+        self.messages.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_expire_and_dismiss() {
+        let start = Instant::now();
+        let mut line = StatusLine::default();
+        line.push(Severity::Info, "Ready".into(), start);
+        line.push(Severity::Error, "Error: push rejected".into(), start);
+        line.push(Severity::Info, "Running fetch...".into(), start);
+        line.push(Severity::Success, "Fetched".into(), start);
+        assert_eq!(line.text(), "Fetched");
+        assert_eq!(line.hidden_errors(), 1);
+        let outcomes = line.take_outcomes();
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[1].text, "Fetched");
+        assert_eq!(line.take_outcomes(), Vec::new());
+
+        line.expire(start + Duration::from_secs(1));
+        assert_eq!(line.severity(), Some(Severity::Success));
+        line.expire(start + SUCCESS_TIMEOUT);
+        assert_eq!(line.text(), "Error: push rejected");
+        assert_eq!(line.hidden_errors(), 0);
+
+        line.expire(start + Duration::from_secs(3600));
+        assert_eq!(line.severity(), Some(Severity::Error));
+        line.dismiss();
+        assert_eq!(line.current(), None);
+        assert_eq!(line.text(), "");
+
+        for i in 0..=MAX_MESSAGES {
+            line.push(Severity::Error, format!("Error {i}"), start);
+        }
+        assert_eq!(line.hidden_errors(), MAX_MESSAGES - 1);
+        assert_eq!(line.text(), format!("Error {MAX_MESSAGES}"));
+    }
+}
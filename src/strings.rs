@@ -0,0 +1,927 @@
+//! Text of the UI in every supported language. Status and confirm messages, including the
+//! outcomes git operations report as a `Message`, screens, titles and hints all go through
+//! `Msg`.
+
+use serde::Deserialize;
+use std::fmt::Display;
+
+/// Language the UI is shown in, set by `language` in config.toml or taken from the locale
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Ja,
+    #[default]
+    En,
+}
+
+impl Language {
+    /// The language of the first set variable of `LC_ALL`, `LC_MESSAGES` and `LANG`
+    pub fn from_env() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty());
+        Self::from_locale(locale.as_deref())
+    }
+
+    /// Japanese for `ja_*` locales, English for every other locale and when no real
+    /// locale is set (unset, `C`, `POSIX`)
+    pub fn from_locale(locale: Option<&str>) -> Self {
+        match locale {
+            Some(locale) if locale.starts_with("ja") => Self::Ja,
+            _ => Self::En,
+        }
+    }
+
+    /// `ja` or `en`, whichever is in this language
+    pub const fn pick<T: Copy>(self, ja: T, en: T) -> T {
+        match self {
+            Self::Ja => ja,
+            Self::En => en,
+        }
+    }
+}
+
+macro_rules! messages {
+    ($($id:ident => $ja:literal, $en:literal;)*) => {
+        /// A piece of UI text. `{}` marks where `fill` puts its arguments.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Msg {
+            $($id,)*
+        }
+
+        impl Msg {
+            pub const ALL: &'static [Self] = &[$(Self::$id,)*];
+
+            pub const fn text(self, language: Language) -> &'static str {
+                match self {
+                    $(Self::$id => language.pick($ja, $en),)*
+                }
+            }
+        }
+    };
+}
+
+messages! {
+    // Status messages of the app itself
+    Ready => "準備完了", "Ready";
+    SignCommit => "このコミットに署名します", "This commit will be signed";
+    NoSignCommit => "このコミットには署名しません", "This commit will not be signed";
+    SkipHooks => "⚠ このコミットではフックを実行しません (--no-verify)", "⚠ Hooks will be skipped for this commit (--no-verify)";
+    RunHooks => "このコミットではフックを実行します", "Hooks will run for this commit";
+    SignOff => "Signed-off-by を付けてコミットします", "Signed-off-by will be added to this commit";
+    NoSignOff => "Signed-off-by を付けません", "No Signed-off-by for this commit";
+    StashKeepIndex =>
+        "ステージ済みの変更はスタッシュ後もステージに残します (--keep-index)",
+        "Staged changes stay staged after stashing (--keep-index)";
+    StashResetIndex => "ステージ済みの変更もスタッシュで片付けます", "Staged changes are stashed away too";
+    StashUntracked =>
+        "未追跡ファイルもスタッシュします (--include-untracked)",
+        "Untracked files are stashed too (--include-untracked)";
+    StashTrackedOnly => "追跡中のファイルだけをスタッシュします", "Only tracked files are stashed";
+    AutoStage =>
+        "変更された追跡ファイルを自動でステージしてコミットします (-a)",
+        "Modified tracked files will be staged and committed (-a)";
+    NoAutoStage => "ステージ済みの変更だけをコミットします", "Only staged changes will be committed";
+    Loading => "読み込み中...", "Loading...";
+    Error => "エラー: {}", "Error: {}";
+    EditorExited => "エラー: {} が {} で終了しました", "Error: {} exited with {}";
+    EditorFailed => "エラー: {} を実行できませんでした: {}", "Error: failed to run {}: {}";
+    PreviewError => "プレビューのエラー: {}", "Preview error: {}";
+    ConfigError => "設定ファイルのエラー: {}", "Config error: {}";
+    ShowingDirFiles => "{} のファイルを表示しています", "Showing the files in {}";
+    CollapsedDir => "{} を折りたたみました", "Collapsed {}";
+    SlowStatus =>
+        "git status に {}ms かかったため、以降は未追跡ファイルを表示しません (設定の untracked_files で選べます)",
+        "git status took {}ms; untracked files are hidden from now on (set untracked_files in the config to choose)";
+    InitializedEmpty => "{} に空のリポジトリを作成しました", "Initialized an empty repository in {}";
+    Opened => "{} を開きました", "Opened {}";
+    ShowingList => "ファイルを一覧で表示しています", "Showing files as a list";
+    ShowingTree => "ファイルをツリーで表示しています", "Showing files as a tree";
+    NoChangesIn => "{} に変更はありません", "No changes in {}";
+    NoReflogEntrySelected => "リフログのエントリが選択されていません", "No reflog entry selected";
+    TypeCompareRefs =>
+        "比較する2つの ref を入力してください (例: main..HEAD)",
+        "Type two refs to compare, e.g. main..HEAD";
+    PullConflicts =>
+        "{}件のコンフリクトでプルが停止しました: 解決 (Enter) してから続行 (C) か中止 (A) してください",
+        "Pull stopped with {} conflict(s): resolve them (Enter), then continue (C) or abort (A)";
+    CannotMergeHead =>
+        "チェックアウト中のブランチを自身にはマージできません",
+        "Cannot merge the checked-out branch into itself";
+    MergeConflicts =>
+        "{} のマージが{}件のコンフリクトで停止しました: 解決 (Enter) するかマージを中止 (A) してください",
+        "Merging {} stopped with {} conflict(s): resolve them (Enter) or abort the merge (A)";
+    SquashConflicts =>
+        "{} のマージが{}件のコンフリクトで停止しました: 解決 (Enter) してからコミットしてください",
+        "Merging {} stopped with {} conflict(s): resolve them (Enter) and commit";
+    NoGoneBranches =>
+        "上流が削除されたブランチはありません (先に f で fetch --prune してください)",
+        "No branches with a gone upstream (fetch with f to prune first)";
+    DeletedGone => "✓ 上流が削除されたブランチを{}件削除しました", "✓ Deleted {} gone branch(es)";
+    DeleteGoneFailed =>
+        "エラー: {}/{}件のブランチを削除しました: {}",
+        "Error: deleted {} of {} branch(es): {}";
+    UntrackedNotOnRemote => "{} は未追跡のため、リモートにはありません", "{} is untracked, so it is not on the remote";
+    NothingToCopy => "コピーするものがありません", "Nothing to copy";
+    TooLargeToCopy =>
+        "エラー: {} はコピーするには大きすぎます ({}、上限 {})",
+        "Error: {} is too large to copy ({}, at most {})";
+    Copied => "✓ {} をコピーしました", "✓ Copied {}";
+    CopyPath => "パス {}", "path {}";
+    CopyDiff => "差分 ({}行)", "diff ({} lines)";
+    CopyHash => "コミットハッシュ {}", "commit hash {}";
+    CopyDoctorReport => "診断レポート", "diagnostics report";
+    CommitDetailsError => "コミットの詳細のエラー: {}", "Commit details error: {}";
+    NoCommitSelected => "コミットが選択されていません", "No commit selected";
+    NoBranchSelected => "ブランチが選択されていません", "No branch selected";
+    NotBisecting => "bisect 中ではありません (B で開始)", "Not bisecting (press B to start)";
+    CannotDeleteHead =>
+        "チェックアウト中のブランチは削除できません",
+        "Cannot delete the checked-out branch";
+    SearchCleared => "検索を解除しました", "Search cleared";
+    NoCommitsMatch => "'{}' に一致するコミットはありません", "No commits match '{}'";
+    CommitsMatch => "{}件のコミットが '{}' に一致しました", "{} commit(s) match '{}'";
+    NoLogSearch => "検索していません: / でコミットを検索できます", "No search: press / to search commits";
+    MatchPosition => "一致 {}/{}", "Match {}/{}";
+    NoPreview => "プレビューできません", "No preview available";
+    PreviewRight => "プレビューパネルを右に表示します", "Preview panel on the right";
+    PreviewBottom => "プレビューパネルを下に表示します", "Preview panel at the bottom";
+    PreviewHidden => "プレビューパネルを隠しました", "Preview panel hidden";
+    PreviewIsHidden => "プレビューパネルは非表示です", "Preview panel is hidden";
+    PreviewSize => "プレビューパネル: {}%", "Preview panel: {}%";
+    NotInVersion => "{} はこのバージョンに存在しません", "{} does not exist in this version";
+    NotFound => "見つかりません: {}", "Not found: {}";
+    DiffContext => "差分の前後の行数: {}行", "Diff context: {} line(s)";
+    IgnoringWhitespace => "空白の変更を無視しています", "Ignoring whitespace changes";
+    ShowingWhitespace => "空白の変更を表示しています", "Showing whitespace changes";
+    IgnoringBlankLines => "空行の変更を無視しています", "Ignoring blank line changes";
+    ShowingBlankLines => "空行の変更を表示しています", "Showing blank line changes";
+    DiffOptionsWorktreeOnly =>
+        "差分のオプションは作業ツリーのファイルでのみ使えます",
+        "Diff options are only available for working tree files";
+    NoFileToPreview => "プレビューするファイルが選択されていません", "No file selected for preview";
+    HexDumpWorktreeOnly =>
+        "16進ダンプは作業ツリーのファイルでのみ使えます",
+        "Hex dump is only available for working tree files";
+    NotBinary => "{} はバイナリファイルではありません", "{} is not a binary file";
+    TooLargeForHexDump => "{} は16進ダンプするには大きすぎます ({})", "{} is too large for a hex dump ({})";
+    HexDumpTitle => "16進ダンプ: {} ({})", "Hex dump: {} ({})";
+    FileMissing => "{} は存在しません", "{} does not exist";
+    Cancelled => "キャンセルしました", "Operation cancelled";
+    HistoryWriteError =>
+        "エラー: {}。操作履歴のファイルには今後書き込みません",
+        "Error: {}; the history file is no longer written";
+    NoTrailers =>
+        "トレーラーが設定されていません。config.toml の [commit.trailers] にキーを追加してください",
+        "No trailers configured; add keys to [commit.trailers] in config.toml";
+    NoOtherAuthors => "最近のコミットに他の作者はいません", "No other authors in the recent commits";
+    TrailerRemoved => "'{}' を外しました", "Removed '{}'";
+    TrailerAdded => "'{}' を追加します", "'{}' will be added";
+    EmptyCommitMessage => "コミットメッセージが空です", "Commit message cannot be empty";
+    CommitProblems =>
+        "コミットメッセージに問題が{}件あります。修正するか Ctrl+O でそのままコミットしてください",
+        "Commit message has {} problem(s); fix it or press Ctrl+O to commit anyway";
+    NoFilesIncluded =>
+        "このコミットに含めるステージ済みファイルがありません",
+        "No staged files selected for this commit";
+    HooksSkipped => "{} (--no-verify でフックを省略)", "{} (hooks skipped with --no-verify)";
+    CommitRejected =>
+        "{} フックがコミットを拒否しました (n: --no-verify で再試行)",
+        "Commit rejected by the {} hook (n: retry with --no-verify)";
+    SignOffNeedsIdentity =>
+        "Signed-off-by を付けるには user.name と user.email を設定するか、プロファイルを選んでください",
+        "Set user.name and user.email (or pick an identity) to sign off";
+    SelectStagedFile =>
+        "含める/外すステージ済みファイルを選択してください",
+        "Select a staged file to include or leave out";
+    FileIncluded => "'{}' をコミットします", "'{}' will be committed";
+    FileLeftOut =>
+        "'{}' はステージしたままこのコミットから外します",
+        "'{}' stays staged but is left out of this commit";
+    AlreadyRunning => "{} はすでに実行中です", "{} is already running";
+    RunningTask => "{} を実行中...", "Running {}...";
+    NoStashSelected => "スタッシュが選択されていません", "No stash selected";
+    ResolveBeforeContinue =>
+        "マージを続行する前にすべてのコンフリクトを解決してください",
+        "Resolve all conflicts before continuing the merge";
+    StoppedAgain =>
+        "{}件のコンフリクトで再び停止しました: 解決してから続行 (C) か中止 (A) してください",
+        "Stopped again with {} conflict(s): resolve them, then continue (C) or abort (A)";
+    NothingStagedYet =>
+        "まだ何もステージされていません。コミットする前にファイルをステージしてください",
+        "Nothing is staged yet; stage files before committing";
+    NoTicketId => "ブランチ名 '{}' にチケット番号がありません", "No ticket id in branch name '{}'";
+    NoCommitPrefixes => "コミット種別が設定されていません", "No commit prefixes configured";
+    NoIdentities =>
+        "プロファイルが設定されていません。config.toml に [[identities]] を追加してください",
+        "No identities configured; add [[identities]] to config.toml";
+    CommitAsRepo =>
+        "このコミットではリポジトリの user.name と user.email を使います",
+        "This commit will use the repository's user.name and user.email";
+    CommitAsProfile => "このコミットは {} として作成します", "This commit will be made as {}";
+    SelectProfileToSave =>
+        "このリポジトリに保存するプロファイルを選択してください",
+        "Select a profile to save for this repository";
+    CredentialCancelled => "認証の入力をキャンセルしました", "Credential prompt cancelled";
+    StagedFiles => "✓ {}個のファイルをステージしました", "✓ Staged {} file(s)";
+    UnstagedFiles => "✓ {}個のファイルをアンステージしました", "✓ Unstaged {} file(s)";
+    NoFilesToStage => "ステージするファイルがありません", "No files to stage";
+    IntentToAddUntrackedOnly =>
+        "intent to add にできるのは未追跡ファイルだけです",
+        "Only untracked files can be marked as intent to add";
+    NoConflictsIn => "'{}' にコンフリクトはありません", "No conflicts in '{}'";
+    LineStageUntracked =>
+        "行単位でステージするには intent to add (N) にしてください",
+        "Mark the file as intent to add (N) to stage its lines";
+    LineStageModifiedOnly =>
+        "行単位のステージは変更されたファイルでのみ使えます",
+        "Line staging is only available for modified files";
+    IgnoreUntrackedOnly =>
+        ".gitignore に追加できるのは未追跡ファイルだけです",
+        "Only untracked files can be added to .gitignore";
+    PushNeedsBranch =>
+        "ブランチ上にいません。push するにはブランチをチェックアウトしてください",
+        "Not on a branch; check out a branch to push it";
+    NoRemoteToPush =>
+        "push 先のリモートがありません。git remote add で追加してください",
+        "No remote to push to; add one with git remote add";
+    NoUpstreamToForcePush =>
+        "'{}' には force-push 先の upstream がありません。先に P で push してください",
+        "'{}' has no upstream to force-push to; push it with P first";
+    TypedConfirmMismatch =>
+        "確認するには '{}' と入力してください (Esc でキャンセル)",
+        "Type '{}' to confirm, or Esc to cancel";
+    SelectCommitToReset => "リセット先のコミットを選択してください", "Select a commit to reset to";
+    SelectLocalBranch =>
+        "ローカルブランチを選択してください。これはリモートのブランチです",
+        "Select a local branch; this one is on a remote";
+    EmptyBranchName => "ブランチ名を入力してください", "Branch name cannot be empty";
+    HeadNotDetached => "HEAD は detached ではありません", "HEAD is not detached";
+    NoPreviousBranch =>
+        "リフログに戻り先のブランチが見つかりませんでした",
+        "No branch to go back to was found in the reflog";
+    SelectCommitToFixup => "fixup 先のコミットを選択してください", "Select a commit to fix up";
+    NoFixupChanges =>
+        "fixup としてコミットする変更がありません",
+        "No changes to commit as a fixup";
+    SelectAutosquashBase =>
+        "fixup をまとめる一番古いコミットを選択してください",
+        "Select the oldest commit to squash fixups into";
+    NoFixupCommits => "{} 以降に fixup コミットはありません", "No fixup commits after {}";
+    RebaseConflicts =>
+        "{}件のコンフリクトでリベースが停止しました: 解決 (Enter) してから続行 (C) か中止 (A) してください",
+        "The rebase stopped with {} conflict(s): resolve them (Enter), then continue (C) or abort (A)";
+    NothingToExport => "エクスポートするものが選択されていません", "Nothing selected to export";
+    ArchiveFormat =>
+        "アーカイブ名は .zip、.tar、.tar.gz か .tgz にしてください",
+        "Name the archive .zip, .tar, .tar.gz or .tgz";
+    AlreadyExists => "{} はすでに存在します", "{} already exists";
+    ConfigSet => "✓ {} を {} に設定しました ({} の設定)", "✓ Set {} to {} in the {} config";
+    ConfigUnset => "✓ {} を削除しました ({} の設定)", "✓ Unset {} in the {} config";
+    ConfigNotSetIn => "{} は {} の設定にありません", "{} is not set in the {} config";
+    InitializedWith =>
+        "{} にリポジトリを作成しました (ブランチ: {}{}{})",
+        "Initialized a repository in {} on {}{}{}";
+    InitGitignore => "、.gitignore: {}", " with a {} .gitignore";
+    InitCommit => "、初回コミットあり", " and an initial commit";
+
+    // Confirmations
+    ConfirmPrune =>
+        "削除したスタッシュを含め、到達不能なオブジェクトを今すぐすべて削除しますか? (y/n)",
+        "Delete all unreachable objects now, including dropped stashes? (y/n)";
+    ConfirmResetToReflog =>
+        "{} ({}) に reset --hard しますか? コミットしていない変更は失われます (y/n)",
+        "Reset --hard to {} ({})? Uncommitted changes will be lost (y/n)";
+    ConfirmCheckoutReflog =>
+        "{} ({}) を detached HEAD としてチェックアウトしますか? (y/n)",
+        "Checkout {} ({}) as a detached HEAD? (y/n)";
+    ConfirmPush =>
+        "{}件のコミットを '{}' から '{}' にプッシュしますか? (y/n)",
+        "Push {} commit(s) from '{}' to '{}'? (y/n)";
+    ConfirmDeleteGone =>
+        "上流が削除された{}件のブランチ ({}) を削除しますか? そのうち{}件は HEAD にマージされていません (y/n)",
+        "Delete {} branch(es) whose upstream is gone ({})? {} of them are NOT merged into HEAD (y/n)";
+    ConfirmEndBisect =>
+        "bisect を終了して元のブランチに戻りますか? (y/n)",
+        "End bisect and return to the original branch? (y/n)";
+    BranchMerged => "HEAD にマージ済みです", "it is merged into HEAD";
+    BranchNotMerged => "HEAD にマージされていません", "it is NOT merged into HEAD";
+    ConfirmDeleteRemoteBranch =>
+        "'{}' を {} から削除しますか (git push --delete)? {} (y/n)",
+        "Delete '{}' on {} (git push --delete)? {} (y/n)";
+    ConfirmDeleteBranch => "ブランチ '{}' を削除しますか? {} (y/n)", "Delete branch '{}'? {} (y/n)";
+    ConfirmForceDeleteBranch =>
+        "'{}' にはマージされていないコミットがあり、削除するとリフログにしか残りません。強制削除しますか (git branch -D)? (y/n)",
+        "'{}' has commits that are not merged and would only stay in the reflog. Force delete (git branch -D)? (y/n)";
+    ConfirmQuit => "{}ですが終了しますか? (y/n)", "Quit with {}? (y/n)";
+    QuitUnsavedMessage => "コミットメッセージが未保存", "an unsaved commit message";
+    QuitStillRunning => "{} が実行中", "{} still running";
+    And => "、", " and ";
+    ConfirmCommitNoVerify =>
+        "{} フックを省略してもう一度コミットしますか (--no-verify)? (y/n)",
+        "Commit again skipping the {} hook (--no-verify)? (y/n)";
+    ConfirmAbortMerge =>
+        "{} を中止して変更を破棄しますか? (y/n)",
+        "Abort the {} and discard its changes? (y/n)";
+    ConfirmDropStash => "{} ({}) を削除しますか? (y/n)", "Drop {} ({})? (y/n)";
+    ConfirmStageAll => "{}個のファイルをすべてステージしますか? (y/n)", "Stage all {} files? (y/n)";
+    ConfirmUnstageAll =>
+        "{}個のファイルをすべてアンステージしますか? (y/n)",
+        "Unstage all {} files? (y/n)";
+    ConfirmDeleteUntracked =>
+        "未追跡ファイル '{}' を削除しますか? (y/n)",
+        "Delete untracked file '{}'? (y/n)";
+    ConfirmDiscard => "'{}' の変更を破棄しますか? (y/n)", "Discard changes to '{}'? (y/n)";
+    ConfirmPushSetUpstream =>
+        "'{}' には upstream がありません。push して '{}/{}' を追跡しますか? (y/n)",
+        "'{}' has no upstream. Push it and track '{}/{}'? (y/n)";
+    ConfirmForcePush =>
+        "'{}' を '{}' に force-push (--force-with-lease) しますか? リモートにしかないコミットは失われます",
+        "Force-push '{}' to '{}' (--force-with-lease)? Commits only on the remote are lost";
+    ConfirmSoftReset =>
+        "'{}' を {} にリセット ({}) しますか? 以降のコミットは取り消され、その変更はステージされたまま残ります (y/n)",
+        "Reset '{}' to {} ({})? Later commits are undone and their changes stay staged (y/n)";
+    ConfirmMixedReset =>
+        "⚠ '{}' を {} にリセット ({}) しますか? 以降のコミットは取り消され、何もステージされません。作業ツリーはそのままです (y/n)",
+        "⚠ Reset '{}' to {} ({})? Later commits are undone and nothing stays staged; the working tree is kept (y/n)";
+    ConfirmHardReset =>
+        "⚠ '{}' を {} にリセット ({}) しますか? 以降のコミットと {} 件のコミットしていない変更はすべて失われます",
+        "⚠ Reset '{}' to {} ({})? Later commits and all {} uncommitted change(s) are lost";
+    ConfirmLeaveDetached =>
+        "⚠ detached HEAD で作った {} 件のコミットはどのブランチにもなく、リフログにしか残りません (+ でブランチを作成できます)。それでも '{}' に切り替えますか? (y/n)",
+        "⚠ {} commit(s) made on the detached HEAD are on no branch and will only stay in the reflog (+ creates a branch for them). Switch to '{}' anyway? (y/n)";
+    ConfirmAutosquash =>
+        "'{}' を {} からリベースして、{} 件の fixup コミットを対象のコミットにまとめますか? 以降のコミットのハッシュは変わります (y/n)",
+        "Rebase '{}' from {} to squash {} fixup commit(s) into their targets? Later commits get new hashes (y/n)";
+
+    // Outcomes of git operations
+    StagedFile => "✓ ステージしました: {}", "✓ Staged file: {}";
+    UnstagedFile => "✓ ステージを取り消しました: {}", "✓ Unstaged file: {}";
+    DeletedUntracked => "✓ 未追跡ファイルを削除しました: {}", "✓ Deleted untracked file: {}";
+    DiscardedChanges => "✓ 変更を破棄しました: {}", "✓ Discarded changes: {}";
+    MarkedIntentToAdd => "✓ intent-to-add にしました: {}", "✓ Marked as intent to add: {}";
+    UntrackedAgain => "✓ 未追跡に戻しました: {}", "✓ Untracked again: {}";
+    AllStaged => "✓ すべてのファイルをステージしました", "✓ All files staged";
+    AllUnstaged => "✓ すべてのファイルのステージを取り消しました", "✓ All files unstaged";
+    AlreadyIgnored => "'{}' はすでに .gitignore にあります", "'{}' is already in .gitignore";
+    AddedToGitignore => "✓ '{}' を .gitignore に追加しました", "✓ Added '{}' to .gitignore";
+    StashError => "スタッシュのエラー: {}", "Stash error: {}";
+    NoChangesToStash => "スタッシュする変更がありません", "No changes to stash";
+    ChangesStashed => "変更をスタッシュしました: {}", "Changes stashed: {}";
+    StashedPaths => "✓ {}件のパスをスタッシュしました", "✓ Stashed {} path(s)";
+    NoStashToApply => "適用するスタッシュがありません", "No stash to apply";
+    LatestStashApplied => "✓ 最新のスタッシュを適用しました", "✓ Latest stash applied successfully";
+    StashApplied => "✓ {} を適用しました", "✓ Applied {}";
+    StashPopped => "✓ {} を取り出しました", "✓ Popped {}";
+    StashDropped => "✓ {} を削除しました", "✓ Dropped {}";
+    NothingToCommit =>
+        "コミットするものがありません (ステージされた変更なし)",
+        "Nothing to commit (no staged changes)";
+    Committed => "✓ コミットしました", "✓ Committed successfully!";
+    CommittedWithSummary => "✓ コミットしました\n{}", "✓ Committed successfully!\n{}";
+    IdentitySet =>
+        "✓ このリポジトリは {} としてコミットします",
+        "✓ This repository now commits as {}";
+    ConflictResolved => "✓ {} を {} で解決しました", "✓ Resolved {} using {}";
+    SquashMerged =>
+        "✓ {} をインデックスにスカッシュしました。コミットして完了してください",
+        "✓ Squashed {} into the index; commit to finish";
+    Merged => "✓ {} をマージしました", "✓ Merged {}";
+    RebaseAborted => "✓ リベースを中止しました", "✓ Rebase aborted";
+    MergeAborted => "✓ マージを中止しました", "✓ Merge aborted";
+    RebaseCompleted => "✓ リベースが完了しました", "✓ Rebase completed";
+    MergeCompleted => "✓ マージが完了しました", "✓ Merge completed";
+    StagedLines => "✓ {}行をステージしました", "✓ Staged {} line(s)";
+    UnstagedLines => "✓ {}行のステージを取り消しました", "✓ Unstaged {} line(s)";
+    EverythingUpToDate => "すべて最新です", "Everything up-to-date";
+    Pushed => "✓ リモートに push しました", "✓ Pushed to remote";
+    PushedSetUpstream =>
+        "✓ push して upstream を {}/{} に設定しました",
+        "✓ Pushed and set upstream to {}/{}";
+    ForcePushed =>
+        "✓ リモートに force-push しました (--force-with-lease)",
+        "✓ Force-pushed to remote (--force-with-lease)";
+    MaintenanceFinished => "✓ git {} が完了しました", "✓ git {} finished";
+    Exported => "✓ {} を {} にエクスポートしました ({})", "✓ Exported {} to {} ({})";
+    AlreadyUpToDate => "すでに最新です", "Already up to date";
+    Pulled => "✓ リモートから pull しました", "✓ Pulled from remote";
+    Fetched => "✓ リモートから fetch しました", "✓ Fetched from remote";
+    FetchedAndPruned =>
+        "✓ リモートから fetch し、削除されたブランチを整理しました",
+        "✓ Fetched from remote and pruned deleted branches";
+    BisectStarted =>
+        "✓ bisect を開始しました: good と bad のコミットをマークしてください",
+        "✓ Bisect started: mark a good and a bad commit";
+    BisectEnded => "✓ bisect を終了しました", "✓ Bisect ended";
+    BisectStep => "✓ {}", "✓ {}";
+    BisectUpdated => "✓ bisect を更新しました", "✓ Bisect updated";
+    SwitchedBranch => "✓ ブランチ {} に切り替えました", "✓ Switched to branch {}";
+    CreatedBranch =>
+        "✓ ブランチ {} を作成して切り替えました",
+        "✓ Created branch {} and switched to it";
+    DeletedBranch => "✓ ブランチ {} を削除しました", "✓ Deleted branch {}";
+    RenamedBranch => "✓ ブランチ {} の名前を {} に変更しました", "✓ Renamed branch {} to {}";
+    UpstreamUnset => "✓ {} は upstream を追跡しなくなりました", "✓ {} no longer tracks an upstream";
+    UpstreamSet => "✓ {} は {} を追跡します", "✓ {} now tracks {}";
+    DeletedRemoteBranch => "✓ {} を {} から削除しました", "✓ Deleted {} from {}";
+    ResetHard => "✓ {} にリセットしました", "✓ Reset to {}";
+    ResetKeeping => "✓ {} で {} にリセットしました", "✓ Reset {} to {}";
+    FixupCommitted => "✓ {} の fixup をコミットしました", "✓ Committed a fixup for {}";
+    FixupsSquashed =>
+        "✓ {} 以降の fixup コミットをまとめました",
+        "✓ Squashed the fixup commits since {}";
+    CheckedOutDetached =>
+        "✓ {} をチェックアウトしました (detached HEAD)",
+        "✓ Checked out {} (detached HEAD)";
+
+    // Status bar
+    RepoStatus => "リポジトリの状態", "Git Repository Status";
+    WorkingTreeClean => "作業ツリーはクリーンです", "working tree clean";
+    CommitCount => " ({}コミット)", " ({} commits)";
+    SummaryStaged => "ステージ済み", "staged";
+    SummaryModified => "変更", "modified";
+    SummaryUntracked => "未追跡", "untracked";
+    SummaryConflict => "コンフリクト", "conflict";
+    SummaryConflicts => "コンフリクト", "conflicts";
+    BisectWaiting =>
+        " bisect中: good と bad のコミットをマークしてください  [g]good [b]bad [B]終了",
+        " Bisecting: mark a good and a bad commit  [g]good [b]bad [B]end";
+    BisectTesting =>
+        " bisect中: {} をテスト中 (残り {} リビジョン, 約 {} ステップ)  [g]good [b]bad [n]skip [B]終了",
+        " Bisecting: testing {} ({} revisions left, about {} steps)  [g]good [b]bad [n]skip [B]end";
+    BisectFound =>
+        " bisect完了: 最初の bad コミットは {} です  [B]終了",
+        " Bisect done: the first bad commit is {}  [B]end";
+    DetachedHead =>
+        " ⚠ HEAD が切り離されています ({}): ここでのコミットはどのブランチにも属しません",
+        " ⚠ Detached HEAD at {}: commits made here belong to no branch";
+    DetachedUnreachable =>
+        " ⚠ HEAD が切り離されています ({}): どのブランチにもないコミットが {} 件あります",
+        " ⚠ Detached HEAD at {}: {} commit(s) are on no branch";
+    DetachedCreate => "  [+]ブランチ作成", "  [+]create branch";
+    DetachedBack => " [-]{} に戻る", " [-]back to {}";
+
+    // File list
+    NoChangedFiles => "変更されたファイルはありません", "No changed files";
+    FilesTitle => "Git ファイル", "Git files";
+    FilesTitleCount => "Git ファイル ({}個)", "Git files ({})";
+    FilesTitleSorted => "Git ファイル ({}個, {}順)", "Git files ({}, by {})";
+    SortedBy => "{}順に並べ替えました", "Sorted by {}";
+    SortPath => "パス", "path";
+    SortDirectory => "ディレクトリ", "directory";
+    SortStatus => "状態", "status";
+    SortModified => "更新日時", "modification time";
+    CommitFilesTitle =>
+        "コミット対象 ({}/{}個) [↑/↓]選択 [Ctrl+T]含める/外す",
+        "To commit ({}/{}) [↑/↓]select [Ctrl+T]include/leave out";
+    CommitSummaryTitle => "コミットされる変更", "Changes to be committed";
+    NothingStaged =>
+        "⚠ ステージされた変更がありません。Esc で戻り s でステージしてください",
+        "⚠ Nothing is staged. Press Esc, then s to stage files";
+    NothingIncluded =>
+        "⚠ すべてのファイルが外されています。Ctrl+T で含めてください",
+        "⚠ Every staged file is left out. Press Ctrl+T to include one";
+    IgnoredTracked => " (.gitignore対象)", " (in .gitignore)";
+    SectionConflicted => "コンフリクト", "Conflicts";
+    SectionStaged => "ステージ済み", "Staged";
+    SectionUnstaged => "未ステージ", "Unstaged";
+    SectionUntracked => "未追跡", "Untracked";
+    CommitViolations =>
+        "コミットメッセージの問題 ([Ctrl+O]無視してコミット)",
+        "Commit message problems ([Ctrl+O] commit anyway)";
+
+    // Input area
+    Status => "ステータス", "Status";
+    Running => "{} 実行中: {}", "{} Running: {}";
+    HiddenErrors => " (他にエラー{}件)", " (+{} more errors)";
+    CommitMessage => "コミットメッセージ", "Commit message";
+    MoreLines => " (+{}行)", " (+{} lines)";
+    CommitAs => " — {}", " — as {}";
+    CommitAsOverride => " — {} (このコミットのみ)", " — as {} (this commit only)";
+    TrailerCount => "+{}トレーラー ", "+{} trailers ";
+    NoIdentity => " — ⚠ user.name/user.email 未設定", " — ⚠ user.name/user.email not set";
+    ScopeTitle => "{} のスコープ (省略可)", "Scope for {} (optional)";
+    ConfigPrompt =>
+        "{} の値 ({} の設定に書き込み、Tab で切替、空にすると削除)",
+        "Value of {} (written to the {} config, Tab to switch, empty to unset)";
+    InitBranchPrompt =>
+        "最初のブランチの名前 (空にすると git の既定)",
+        "Name of the initial branch (empty for git's default)";
+    InitCommitPrompt =>
+        "最初のコミットを作成しますか? (y/n)",
+        "Create an initial commit? (y/n)";
+    ArchivePrompt =>
+        "{} のアーカイブの保存先 (.zip / .tar / .tar.gz / .tgz)",
+        "Export {} to (.zip / .tar / .tar.gz / .tgz)";
+    CreateBranchPrompt => "{} に作成するブランチの名前", "Name of the new branch at {}";
+    RenameBranchPrompt => "ブランチ '{}' の新しい名前", "New name for branch '{}'";
+    UpstreamPrompt =>
+        "'{}' の上流ブランチ (空にすると解除)",
+        "Upstream of '{}' (empty to unset)";
+    LogSearchPrompt =>
+        "コミット検索 (メッセージ / author:作者 / path:パス)",
+        "Search commits (message / author:NAME / path:PATH)";
+    StashMessage => "スタッシュメッセージ", "Stash message";
+    StashMessageFiles => "スタッシュメッセージ ({}個のファイル)", "Stash message ({} files)";
+    Confirm => "確認", "Confirm";
+    TypeToConfirm => "(「{}」と入力して Enter)", "(type '{}' and press Enter)";
+
+    // Titles of the list-style modes
+    ModeLog => "コミット履歴", "Commit log";
+    ModeLogSearch => "コミット検索", "Commit search";
+    ModeFileVersion => "ファイル内容", "File content";
+    ModeReflog => "リフログ", "Reflog";
+    ModeBranches => "ブランチ", "Branches";
+    ModeMerge => "マージ", "Merge";
+    ModePull => "プル", "Pull";
+    ModeMaintenance => "メンテナンス", "Maintenance";
+    ModeCommitDetails => "コミットの詳細", "Commit details";
+    ModeCompare => "比較", "Compare";
+    ModeStashes => "スタッシュ", "Stashes";
+    ModeRepos => "リポジトリ", "Repositories";
+    ModeNoRepository => "リポジトリなし", "No repository";
+    ModeInit => ".gitignore テンプレート", ".gitignore template";
+    ModeConflict => "コンフリクト", "Conflict";
+    ModeLineStage => "行単位ステージ", "Line staging";
+    ModeHookOutput => "コミットが拒否されました", "Commit rejected";
+    ModeIgnore => ".gitignore", ".gitignore";
+    ModePrefix => "コミット種別", "Commit type";
+    ModeIdentity => "コミットする人", "Identity";
+    ModeTrailers => "トレーラー", "Trailers";
+    ModeCommandLog => "gitコマンド履歴", "Git commands";
+    ModeHistory => "操作履歴", "History";
+    ModeDoctor => "環境診断", "Diagnostics";
+    ModeConfig => "git 設定", "Git config";
+
+    // Lists
+    NoStashes => "スタッシュはありません", "No stashes";
+    ConfigTitle => "git 設定 ({}件)", "Git config ({} values)";
+    ConfigNotSet => "(未設定)", "(not set)";
+    ConfigOverridden => " (上書きされています)", " (overridden)";
+    StashesTitle => "スタッシュ ({}件)", "Stashes ({})";
+    NoRecentRepos => "最近開いたリポジトリはありません", "No recently opened repositories";
+    RecentReposTitle => "最近のリポジトリ ({}件)", "Recent repositories ({})";
+    NotARepository => "gitリポジトリではありません: {}", "Not a git repository: {}";
+    InitHere => "このディレクトリで git init", "git init in this directory";
+    InitWithOptions =>
+        "ブランチ名・.gitignore・最初のコミットを選んで git init",
+        "git init choosing the branch name, a .gitignore and an initial commit";
+    InitTitle => "git init: {}", "git init: {}";
+    InitNoGitignore => "なし", "none";
+    OpenRecentBelow => "下の一覧から最近のリポジトリを開く", "open a recent repository from the list below";
+    IgnoreTitle => ".gitignore に追加: {}", "Add to .gitignore: {}";
+    IgnoreExtension => "この拡張子のすべてのファイル", "every file with this extension";
+    IgnoreDirectory => "ディレクトリ全体", "the whole directory";
+    IgnoreFile => "このファイルのみ", "this file only";
+    PrefixPickerTitle => "コミットメッセージの種別", "Commit message type";
+    IdentityPickerTitle => "コミットする名前とメールアドレス", "Commit as";
+    RepoIdentity => "リポジトリの設定", "repository setting";
+    IdentityUnset => "(未設定)", "(not set)";
+    TrailerPickerTitle => "最近の作者を追加: {}", "Add a recent author as {}";
+    LineStageTitle => "行単位ステージ: {} ({}行選択)", "Stage lines: {} ({} selected)";
+    LineUnstageTitle => "行単位アンステージ: {} ({}行選択)", "Unstage lines: {} ({} selected)";
+    NoConflictMarkers =>
+        "コンフリクトマーカーはありません (一方で削除されたファイルか、編集済みです)",
+        "No conflict markers (the file was deleted on one side, or is already edited)";
+    ConflictTitle => "コンフリクト: {} ({}箇所)", "Conflict: {} ({} hunks)";
+    ConflictHunk => "@@ コンフリクト {} (行 {})", "@@ Conflict {} (line {})";
+    Ours => "ours (現在のブランチ)", "ours (current branch)";
+    Base => "base (共通の祖先)", "base (common ancestor)";
+    Theirs => "theirs (マージ元)", "theirs (incoming branch)";
+    HookNoOutput => "(フックは何も出力しませんでした)", "(the hook printed nothing)";
+    HookOutputTitle => "{} フックの出力", "Output of the {} hook";
+    NoCommits => "コミットはまだありません", "No commits yet";
+    LogTitle => "コミット履歴 ({}件)", "Commit log ({})";
+    LogSearchResult => "{} 検索: {} ({}件一致)", "{} search: {} ({} matches)";
+    BisectMarkerFound => "  ◀ 最初の bad コミット", "  ◀ first bad commit";
+    BisectMarkerTesting => "  ◀ bisect テスト中", "  ◀ testing";
+    NoReflog => "リフログはまだありません", "No reflog entries yet";
+    ReflogTitle => "リフログ ({}件)", "Reflog ({})";
+    NoBranches => "ブランチはまだありません", "No branches yet";
+    BranchesTitle => "ブランチ ({}件)", "Branches ({})";
+    Unmerged => " 未マージ", " unmerged";
+    MergeTitle => "{} を {} にマージ", "Merge {} into {}";
+    MergeFastForward =>
+        "fast-forward (--ff-only)   マージコミットを作らずに進める",
+        "fast-forward (--ff-only)   move ahead without a merge commit";
+    MergeNoFastForward =>
+        "マージコミット (--no-ff)   常にマージコミットを作成",
+        "merge commit (--no-ff)     always create a merge commit";
+    MergeSquash =>
+        "スカッシュ (--squash)      変更をまとめてステージし、自分でコミット",
+        "squash (--squash)          stage the changes together and commit yourself";
+    PullTitle => "{} にプル", "Pull into {}";
+    PullRebase =>
+        "リベース (--rebase)        ローカルのコミットを上流の上に積み直す",
+        "rebase (--rebase)          replay local commits on top of the upstream";
+    PullMerge =>
+        "マージ (--no-rebase)       上流をマージ、分岐していればマージコミット",
+        "merge (--no-rebase)        merge the upstream, with a merge commit if diverged";
+    PullFastForward =>
+        "fast-forward (--ff-only)   分岐していれば中止",
+        "fast-forward (--ff-only)   stop if the branches have diverged";
+    PullDefault => "  ← git pull の既定", "  ← git pull default";
+
+    // Maintenance
+    MaintenanceTitle => "リポジトリのメンテナンス", "Repository maintenance";
+    StatsSize => "サイズ:             {}", "Size:            {}";
+    StatsLoose => "ルーズオブジェクト: {} ({})", "Loose objects:   {} ({})";
+    StatsPacked => "パック済み:         {} ({}パック, {})", "Packed objects:  {} in {} pack(s) ({})";
+    StatsGarbage => "不要なファイル:     {}", "Garbage files:   {}";
+    StatsLastGc => "最終 gc:            {}", "Last gc:         {}";
+    Never => "なし", "never";
+    Ago => "{}前", "{} ago";
+    MaintenanceGc =>
+        "git gc                オブジェクトをパックし、期限切れの不要なものを削除",
+        "git gc                pack objects and remove expired unreachable ones";
+    MaintenancePrune =>
+        "git prune             到達不能なルーズオブジェクトを今すぐ削除",
+        "git prune             delete unreachable loose objects right away";
+    MaintenanceRun =>
+        "git maintenance run   git maintenance に設定されたタスク (既定は gc)",
+        "git maintenance run   the tasks configured for git maintenance (gc by default)";
+
+    // Commit details
+    CommitDetailsTitle => "コミット {}", "Commit {}";
+    DetailsParents => "親:         {}", "Parents:   {}";
+    DetailsRoot => "(なし、ルートコミット)", "(none, root commit)";
+    DetailsAuthor => "作者:       {} <{}>  {}", "Author:    {} <{}>  {}";
+    DetailsCommitter => "コミッター: {} <{}>  {}", "Committer: {} <{}>  {}";
+    DetailsSignature => "署名:       {}", "Signature: {}";
+    SignatureUnsigned => "なし", "none";
+    SignatureGood => "有効 ({})", "good ({})";
+    SignatureGoodUntrusted => "有効、信頼度不明 ({})", "good, unknown validity ({})";
+    SignatureBad => "不正な署名 ({})", "BAD signature ({})";
+    SignatureExpired => "有効期限切れの署名 ({})", "expired signature ({})";
+    SignatureExpiredKey => "有効期限切れの鍵 ({})", "expired key ({})";
+    SignatureRevokedKey => "失効した鍵 ({})", "revoked key ({})";
+    SignatureUnverifiable => "検証できません (鍵がないかgpgを実行できません)",
+        "cannot be checked (missing key or gpg)";
+    ChangedFilesTitle => "変更されたファイル ({}件, +{} -{})", "Changed files ({}, +{} -{})";
+    ChangedFilesMergeTitle => "変更されたファイル (第1親との差分, {}件, +{} -{})",
+        "Changed files (against the first parent, {}, +{} -{})";
+
+    // Compare
+    ComparePrompt =>
+        "比較する ref (A..B、A...B は共通祖先から、A だけなら A..HEAD)",
+        "Refs to compare (A..B, A...B from their merge base, just A for A..HEAD)";
+    CompareTitle => "{} の差分 ({}件, +{} -{})", "Diff of {} ({}, +{} -{})";
+    NoRangeChanges => "2つの ref の間に差分はありません", "No differences between the two refs";
+
+    // Terminal size
+    TerminalTooSmall => "端末が小さすぎます ({}x{})", "Terminal too small ({}x{})";
+    EnlargeTerminal => "{}x{} 以上に広げてください", "Please enlarge it to at least {}x{}";
+
+    // Command log
+    NoCommandsRun => "まだgitコマンドは実行されていません", "No git commands run yet";
+    HistoryTitle => "操作履歴 (新しい順, {}件)", "History (newest first, {})";
+    NoHistory => "このセッションではまだ操作していません", "Nothing done in this session yet";
+    DoctorTitle => "環境診断 (!: 一部の機能が使えません, ✗: 要対応)",
+        "Diagnostics (!: some features will not work, ✗: needs fixing)";
+    CommandLogTitle => "gitコマンド履歴 (新しい順, {}件)", "Git commands (newest first, {})";
+    ExitCode => "終了コード {}", "exit {}";
+    NoExitCode => "終了コードなし", "no exit code";
+    Repeats => " ×{}", " ×{}";
+    StillRunning => "実行中", "running";
+
+    // Previews
+    PreviewTitle =>
+        "差分: {} (j/kでスクロール, /で検索, q/Escで閉じる)",
+        "Preview: {} (j/k to scroll, / to search, q/Esc to exit)";
+    PreviewSearchPrompt => " 検索: {}_{}", " Search: {}_{}";
+    PreviewSearchResult => " 検索: {} ({}/{}件)", " Search: {} ({}/{})";
+    LineRange => " {}–{}/{}行", " lines {}–{}/{}";
+    VersionIndex => "インデックス", "index";
+    VersionWorktree => "作業ツリー", "working tree";
+    FileVersionTitle => "{}  {} (Tab で切替)", "{}  {} (Tab to switch)";
+    NoFileSelected => "ファイル未選択", "No file selected";
+    SelectFile =>
+        "ファイルを選択してください\n\n[v] でパネル切り替え",
+        "Select a file\n\n[v] toggles this panel";
+    PreviewPanel => "プレビュー", "Preview";
+    DiffUnstaged => "未ステージ", "unstaged";
+    DiffStaged => "ステージ済み", "staged";
+    DiffPanelTitle => "差分 [{}]: {} ([Tab]切替)", "Diff [{}]: {} ([Tab] switch)";
+    PreviewHasMore =>
+        " 先頭{}行 (続きはスクロールで読み込み)",
+        " first {} lines (scroll to load more)";
+
+    // Help
+    Help => "ヘルプ", "Help";
+    HelpHeading => "Pretty Git UI - ヘルプ", "Pretty Git UI - Help";
+    HelpScroll => " (j/k PgUp/PgDn g/Gでスクロール {}/{})", " (j/k PgUp/PgDn g/G to scroll {}/{})";
+    HelpFileStatus => "ファイル状態:", "File status:";
+    HelpGreen => "緑色", "Green";
+    HelpGreenMeaning => "  ステージ済み (コミット準備完了)", "  staged (ready to commit)";
+    HelpRed => "赤色", "Red";
+    HelpRedMeaning => "  変更済み (未ステージ) / 未追跡", "  modified (unstaged) / untracked";
+    HelpBarNavigation => "操作: ", "Navigation: ";
+    HelpBarScroll => " スクロール | ", " scroll | ";
+    HelpBarClose => " 閉じる | ", " close | ";
+    HelpBarMode => " - ヘルプ", " - Help Mode";
+
+    // Key hints
+    HintMove => "移動", "move";
+    HintFold => "折りたたみ", "fold";
+    HintResolve => "コンフリクト解決", "resolve";
+    HintStage => "ステージ", "stage";
+    HintUnstage => "アンステージ", "unstage";
+    HintAddIntent => "add -N", "add -N";
+    HintDelete => "削除", "delete";
+    HintDiscard => "破棄", "discard";
+    HintDiff => "差分", "diff";
+    HintAbortMerge => "マージ中止", "abort merge";
+    HintCommit => "コミット", "commit";
+    HintBisect => "good/bad/skip", "good/bad/skip";
+    HintPull => "pull", "pull";
+    HintRun => "実行", "run";
+    HintPush => "push", "push";
+    HintHelp => "ヘルプ", "help";
+    HintQuit => "終了", "quit";
+    HintSearch => "検索", "search";
+    HintCancel => "キャンセル", "cancel";
+    HintSubmit => "送信", "submit";
+    HintSelectFile => "ファイル選択", "select file";
+    HintIncludeFile => "含める/外す", "include/leave out";
+    HintPrefix => "種別", "type";
+    HintEditor => "エディタで編集", "edit in editor";
+    HintSign => "署名切替", "toggle signing";
+    HintSkipHooks => "フック省略", "skip hooks";
+    HintAutoStage => "自動ステージ", "auto-stage";
+    HintForce => "強制", "force";
+    HintScroll => "スクロール", "scroll";
+    HintPage => "ページ", "page";
+    HintTopBottom => "先頭/末尾", "top/bottom";
+    HintMatches => "次/前の一致", "next/prev match";
+    HintHexDump => "16進ダンプ", "hex dump";
+    HintContext => "前後の行数", "context";
+    HintIgnoreSpace => "空白/空行を無視", "ignore space/blank";
+    HintClose => "閉じる", "close";
+    HintDismiss => "メッセージを消す", "dismiss";
+    HintShowDiff => "差分表示", "show diff";
+    HintMarkBisect => "bisect good/bad", "bisect good/bad";
+    HintBrowser => "ブラウザで開く", "open in browser";
+    HintCopy => "コピー", "copy";
+    HintCopyHash => "ハッシュをコピー", "copy hash";
+    HintReload => "再読み込み", "reload";
+    HintBack => "戻る", "back";
+    HintStash => "スタッシュ", "stash";
+    HintReset => "リセット (soft/mixed/hard)", "reset (soft/mixed/hard)";
+    HintFixup => "fixup コミット", "fixup commit";
+    HintAutosquash => "autosquash", "autosquash";
+    HintIncludeUntracked => "未追跡も含める", "untracked too";
+    HintKeepIndex => "ステージを残す", "keep index";
+    HintSearchOrClear => "検索 (空欄で解除)", "search (empty clears)";
+    HintAuthor => "作者", "author";
+    HintPath => "パス", "path";
+    HintYes => "はい", "yes";
+    HintConfirm => "実行", "confirm";
+    HintNo => "いいえ", "no";
+    HintVersions => "HEAD/インデックス/作業ツリー", "HEAD/index/working tree";
+    HintPickVersion => "直接切替", "pick version";
+    HintCheckout => "チェックアウト", "checkout";
+    HintResetHard => "reset --hard", "reset --hard";
+    HintApply => "適用", "apply";
+    HintPop => "pop", "pop";
+    HintOpen => "開く", "open";
+    HintExpand => "展開", "expand";
+    HintCollapse => "折りたたむ", "collapse";
+    HintUseIdentity => "このコミットで使う", "use for this commit";
+    HintSaveIdentity => "リポジトリに保存", "save to repository";
+    HintIdentity => "名前/メール", "identity";
+    HintSignOff => "Signed-off-by", "Signed-off-by";
+    HintTrailers => "トレーラー", "trailers";
+    HintToggleTrailer => "追加/削除", "add/remove";
+    HintTrailerKey => "種類切替", "switch key";
+    HintDetails => "詳細", "details";
+    HintExpandDiff => "差分を展開/折りたたむ", "expand/collapse diff";
+    HintNextFile => "次/前のファイル", "next/prev file";
+    HintWholeCommit => "コミット全体", "whole commit";
+    HintWholeDiff => "全体の差分", "whole diff";
+    HintInit => "git init", "git init";
+    HintEdit => "編集", "edit";
+    HintScope => "スコープ切替", "scope";
+    HintInitOptions => "オプション付き git init", "git init with options";
+    HintNextStep => "次へ", "next";
+    HintOurs => "ours採用", "take ours";
+    HintTheirs => "theirs採用", "take theirs";
+    HintSelectLine => "行を選択", "select line";
+    HintHunk => "ハンク全体", "whole hunk";
+    HintAdd => "追加", "add";
+    HintMerge => "マージ", "merge";
+    HintCreateBranch => "ブランチ作成", "create branch";
+    HintSwitchBack => "元のブランチへ", "back to branch";
+    HintRename => "名前変更", "rename";
+    HintUpstream => "上流を設定", "set upstream";
+    HintCompare => "比較", "compare";
+    HintArchive => "アーカイブ出力", "export archive";
+    HintFetchPrune => "fetch --prune", "fetch --prune";
+    HintDeleteGone => "gone一括削除", "delete gone";
+    HintInsert => "挿入", "insert";
+    HintInsertScope => "挿入 (空欄でスコープなし)", "insert (empty: no scope)";
+    HintNoVerify => "--no-verifyで再試行", "retry with --no-verify";
+    HintEditMessage => "メッセージ編集に戻る", "back to the message";
+    HintCommandLog => "コマンド履歴", "command log";
+}
+
+impl Msg {
+    /// The text with each `{}` replaced by the next of `args`
+    pub fn fill(self, language: Language, args: &[&dyn Display]) -> String {
+        let mut args = args.iter();
+        let mut text = String::new();
+        for (i, part) in self.text(language).split("{}").enumerate() {
+            if i > 0 {
+                if let Some(arg) = args.next() {
+                    text.push_str(&arg.to_string());
+                }
+            }
+            text.push_str(part);
+        }
+        text
+    }
+}
+
+/// A `Msg` together with its arguments, for text put together away from the UI, such as
+/// the outcome of a git operation. `App` shows it in the UI language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    msg: Msg,
+    args: Vec<String>,
+}
+
+impl Message {
+    pub fn new(msg: Msg, args: &[&dyn Display]) -> Self {
+        Self {
+            msg,
+            args: args.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    pub const fn msg(&self) -> Msg {
+        self.msg
+    }
+
+    pub fn text(&self, language: Language) -> String {
+        let args: Vec<&dyn Display> = self.args.iter().map(|arg| arg as &dyn Display).collect();
+        self.msg.fill(language, &args)
+    }
+}
+
+impl From<Msg> for Message {
+    fn from(msg: Msg) -> Self {
+        Self::new(msg, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_from_locale() {
+        assert_eq!(Language::from_locale(None), Language::En);
+        assert_eq!(Language::from_locale(Some("ja_JP.UTF-8")), Language::Ja);
+        assert_eq!(Language::from_locale(Some("C")), Language::En);
+        assert_eq!(Language::from_locale(Some("POSIX")), Language::En);
+        assert_eq!(Language::from_locale(Some("C.UTF-8")), Language::En);
+        assert_eq!(Language::from_locale(Some("en_US.UTF-8")), Language::En);
+        assert_eq!(Language::from_locale(Some("de_DE")), Language::En);
+    }
+
+    #[test]
+    fn test_every_message_is_translated() {
+        for msg in Msg::ALL {
+            let (ja, en) = (msg.text(Language::Ja), msg.text(Language::En));
+            assert!(!ja.is_empty() && !en.is_empty(), "{msg:?}");
+            assert_eq!(
+                ja.matches("{}").count(),
+                en.matches("{}").count(),
+                "{msg:?}"
+            );
+        }
+        assert_eq!(
+            Msg::ConflictTitle.fill(Language::En, &[&"a.txt", &2]),
+            "Conflict: a.txt (2 hunks)"
+        );
+        assert_eq!(
+            Msg::ConflictTitle.fill(Language::Ja, &[&"a.txt", &2]),
+            "コンフリクト: a.txt (2箇所)"
+        );
+    }
+
+    #[test]
+    fn test_message_is_filled_in_the_language_asked_for() {
+        let message = Message::new(Msg::RenamedBranch, &[&"main", &"trunk"]);
+        assert_eq!(message.msg(), Msg::RenamedBranch);
+        assert_eq!(message.text(Language::En), "✓ Renamed branch main to trunk");
+        assert_eq!(
+            message.text(Language::Ja),
+            "✓ ブランチ main の名前を trunk に変更しました"
+        );
+        assert_eq!(
+            Message::from(Msg::Pushed).text(Language::En),
+            "✓ Pushed to remote"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Driving the app with scripted keys for end-to-end tests.
+//!
+//! Built with the `testing` feature. A [`Harness`] feeds keys to `App::handle_event` like the
+//! event loop does and draws into a `TestBackend`, so a test can check what the screen shows
+//! after a flow such as stage → commit:
+//!
+//! ```no_run
+//! use pretty_git_ui::testing::Harness;
+//! use pretty_git_ui::{App, RepoContext};
+//!
+//! let app = App::with_repo(RepoContext::open(".")?);
+//! let mut harness = Harness::new(app, 100, 30)?;
+//! harness.keys("scFix typo<Enter>")?;
+//! assert!(harness.screen().contains("Committed successfully"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::app::App;
+use crate::event::{AppEvent, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseKind};
+use crate::ui::render_ui;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::Terminal;
+use std::time::{Duration, Instant};
+
+/// How long `settle` waits between ticks for background tasks
+const SETTLE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// An app on a fake terminal, fed one event at a time
+pub struct Harness {
+    pub app: App,
+    terminal: Terminal<TestBackend>,
+    /// What the app copied, oldest first, instead of the system clipboard
+    pub clipboard: Vec<String>,
+    /// Set once a key quit the app; later keys are ignored
+    pub quit: bool,
+}
+
+impl Harness {
+    /// Draws `app` once on a `width` × `height` terminal, like the event loop does on start
+    pub fn new(app: App, width: u16, height: u16) -> Result<Self, String> {
+        let terminal = Terminal::new(TestBackend::new(width, height))
+            .map_err(|e| format!("Failed to create the test terminal: {e}"))?;
+        let mut harness = Self {
+            app,
+            terminal,
+            clipboard: Vec::new(),
+            quit: false,
+        };
+        harness.draw();
+        Ok(harness)
+    }
+
+    /// Handles `key` and draws the result. An editor the key asked for is left in
+    /// `app.pending_editor` for the test to deal with.
+    pub fn press(&mut self, key: KeyEvent) {
+        if self.quit {
+            return;
+        }
+        self.quit = self.app.handle_event(AppEvent::Key(key));
+        if let Some(text) = self.app.pending_clipboard.take() {
+            self.clipboard.push(text);
+        }
+        self.draw();
+    }
+
+    /// Presses every key of `script`, see [`parse_keys`]
+    pub fn keys(&mut self, script: &str) -> Result<(), String> {
+        for key in parse_keys(script)? {
+            self.press(key);
+        }
+        Ok(())
+    }
+
+    /// A left click at `column`, `row`
+    pub fn click(&mut self, column: u16, row: u16) {
+        self.app.handle_event(AppEvent::Mouse(MouseEvent {
+            kind: MouseKind::Click,
+            column,
+            row,
+        }));
+        self.draw();
+    }
+
+    /// One tick of the event loop
+    pub fn tick(&mut self) {
+        self.app.handle_event(AppEvent::Tick);
+        self.draw();
+    }
+
+    /// Ticks until no background task (push, stage all, a diff, ...) is running
+    pub fn settle(&mut self, timeout: Duration) -> Result<(), String> {
+        let start = Instant::now();
+        loop {
+            self.tick();
+            if self.app.worker.in_flight().is_empty() {
+                return Ok(());
+            }
+            if start.elapsed() >= timeout {
+                let running: Vec<String> = self
+                    .app
+                    .worker
+                    .in_flight()
+                    .iter()
+                    .map(crate::worker::Task::label)
+                    .collect();
+                return Err(format!("Still running: {}", running.join(", ")));
+            }
+            std::thread::sleep(SETTLE_INTERVAL);
+        }
+    }
+
+    pub const fn buffer(&self) -> &Buffer {
+        self.terminal.backend().buffer()
+    }
+
+    /// The screen as text, see [`buffer_text`]
+    pub fn screen(&self) -> String {
+        buffer_text(self.buffer())
+    }
+
+    fn draw(&mut self) {
+        let app = &mut self.app;
+        // Drawing on a `TestBackend` does not fail
+        let _ = self.terminal.draw(|f| render_ui(f, app));
+    }
+}
+
+/// The symbols of `buffer`, one line per row without trailing spaces, for snapshots
+pub fn buffer_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        let mut line = String::new();
+        let mut skip = 0;
+        for x in area.left()..area.right() {
+            // The cell after a wide character holds a placeholder
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let symbol = buffer[(x, y)].symbol();
+            skip = unicode_width::UnicodeWidthStr::width(symbol).saturating_sub(1);
+            line.push_str(symbol);
+        }
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Keys for a script like `"sc"`, `"Fix typo<Enter>"` or `"<C-s><Esc>"`.
+///
+/// Characters stand for themselves. Named keys go in angle brackets: `<Enter>`, `<Esc>`,
+/// `<Tab>`, `<BackTab>`, `<Backspace>`, `<Delete>`, `<Up>`, `<Down>`, `<Left>`, `<Right>`,
+/// `<Home>`, `<End>`, `<PageUp>`, `<PageDown>`, `<Space>` and `<lt>` for `<` itself. `C-`
+/// adds Ctrl and `A-` Alt, as in `<C-w>` or `<A-Enter>`.
+pub fn parse_keys(script: &str) -> Result<Vec<KeyEvent>, String> {
+    let mut keys = Vec::new();
+    let mut rest = script;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            let end = rest
+                .find('>')
+                .ok_or_else(|| format!("Unclosed '<' in key script: {rest}"))?;
+            keys.push(parse_named_key(&rest[1..end])?);
+            rest = &rest[end + 1..];
+        } else {
+            keys.push(char_key(c, KeyModifiers::NONE));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    Ok(keys)
+}
+
+/// `C-w`, `A-Enter`, `Esc`, ... without the brackets
+fn parse_named_key(name: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = name;
+    loop {
+        if let Some(rest) = name.strip_prefix("C-") {
+            modifiers |= KeyModifiers::CONTROL;
+            name = rest;
+        } else if let Some(rest) = name.strip_prefix("A-") {
+            modifiers |= KeyModifiers::ALT;
+            name = rest;
+        } else {
+            break;
+        }
+    }
+    let code = match name {
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "BackTab" => KeyCode::BackTab,
+        "Backspace" => KeyCode::Backspace,
+        "Delete" => KeyCode::Delete,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        "Space" => return Ok(char_key(' ', modifiers)),
+        "lt" => return Ok(char_key('<', modifiers)),
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => return Ok(char_key(c, modifiers)),
+                _ => return Err(format!("Unknown key in key script: <{name}>")),
+            }
+        },
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+/// A typed character the way the terminal reports it: capitals come with Shift
+fn char_key(c: char, modifiers: KeyModifiers) -> KeyEvent {
+    let shift = if c.is_uppercase() {
+        KeyModifiers::SHIFT
+    } else {
+        KeyModifiers::NONE
+    };
+    KeyEvent::new(KeyCode::Char(c), modifiers | shift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys("aB<Enter><C-w><lt><Space>").unwrap(),
+            vec![
+                KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('B'), KeyModifiers::SHIFT),
+                KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL),
+                KeyEvent::new(KeyCode::Char('<'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+            ]
+        );
+        assert_eq!(parse_keys("").unwrap(), Vec::new());
+        assert!(parse_keys("<Enter").is_err());
+        assert!(parse_keys("<Return>").is_err());
+    }
+
+    #[test]
+    fn test_buffer_text() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "日本a", ratatui::style::Style::default());
+        assert_eq!(buffer_text(&buffer), "日本a\n\n");
+    }
+}
@@ -0,0 +1,224 @@
+use crate::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use std::ops::Deref;
+
+/// Editable text with a cursor, shared by the commit message, the stash message and the
+/// search and scope prompts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInput {
+    text: String,
+    /// Byte offset of the cursor, always on a char boundary
+    cursor: usize,
+}
+
+impl TextInput {
+    /// Input holding `text` with the cursor at its end
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let cursor = text.len();
+        Self { text, cursor }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The text left of the cursor, which decides where the terminal cursor is drawn
+    pub fn before_cursor(&self) -> &str {
+        &self.text[..self.cursor]
+    }
+
+    pub fn after_cursor(&self) -> &str {
+        &self.text[self.cursor..]
+    }
+
+    /// Replaces the text and moves the cursor to its end
+    pub fn set(&mut self, text: impl Into<String>) {
+        *self = Self::new(text);
+    }
+
+    pub fn clear(&mut self) {
+        self.set(String::new());
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    pub fn backspace(&mut self) {
+        let start = self.previous_boundary();
+        self.text.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    pub fn delete(&mut self) {
+        let end = self.next_boundary();
+        self.text.drain(self.cursor..end);
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.previous_boundary();
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = self.next_boundary();
+    }
+
+    pub const fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// Deletes the word before the cursor together with the spaces after it, like `Ctrl+W`
+    /// in a shell
+    pub fn delete_word(&mut self) {
+        let before = self.before_cursor().trim_end();
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        self.text.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    /// Deletes everything before the cursor, like `Ctrl+U` in a shell
+    pub fn delete_to_start(&mut self) {
+        self.text.drain(..self.cursor);
+        self.cursor = 0;
+    }
+
+    /// Applies an editing key. Returns false for keys the input leaves to its mode, such as
+    /// Enter, Esc and other Ctrl shortcuts.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('w') if ctrl => self.delete_word(),
+            KeyCode::Char('u') if ctrl => self.delete_to_start(),
+            KeyCode::Backspace if alt || ctrl => self.delete_word(),
+            KeyCode::Char(_) if ctrl || alt => return false,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Home => self.move_home(),
+            KeyCode::End => self.move_end(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.before_cursor()
+            .char_indices()
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+}
+
+impl From<String> for TextInput {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<&str> for TextInput {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl Deref for TextInput {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl PartialEq<str> for TextInput {
+    fn eq(&self, other: &str) -> bool {
+        self.text == other
+    }
+}
+
+impl PartialEq<&str> for TextInput {
+    fn eq(&self, other: &&str) -> bool {
+        self.text == *other
+    }
+}
+
+impl fmt::Display for TextInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(input: &mut TextInput, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        input.handle_key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_edit_in_the_middle() {
+        let mut input = TextInput::new("修正 bug");
+        input.move_home();
+        input.move_right();
+        input.insert('正');
+        assert_eq!(input.as_str(), "修正正 bug");
+        assert_eq!(input.before_cursor(), "修正");
+        input.backspace();
+        input.delete();
+        assert_eq!(input.as_str(), "修 bug");
+        input.move_end();
+        input.move_right();
+        input.insert_str("s!");
+        assert_eq!(input.as_str(), "修 bugs!");
+    }
+
+    #[test]
+    fn test_shell_style_deletion_keys() {
+        let mut input = TextInput::new("fix the  parser");
+        assert!(press(&mut input, KeyCode::Char('w'), KeyModifiers::CONTROL));
+        assert_eq!(input.as_str(), "fix the  ");
+        assert!(press(&mut input, KeyCode::Backspace, KeyModifiers::ALT));
+        assert_eq!(input.as_str(), "fix ");
+
+        input.set("feat: add log");
+        input.move_left();
+        input.move_left();
+        assert!(press(&mut input, KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!(input.as_str(), "og");
+        assert_eq!(input.cursor, 0);
+
+        assert!(!press(
+            &mut input,
+            KeyCode::Char('s'),
+            KeyModifiers::CONTROL
+        ));
+        assert!(!press(&mut input, KeyCode::Enter, KeyModifiers::NONE));
+        assert!(press(&mut input, KeyCode::Char('l'), KeyModifiers::SHIFT));
+        assert_eq!(input.as_str(), "log");
+    }
+}
@@ -0,0 +1,442 @@
+use crate::diff::LineKind;
+use crate::git::Section;
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+/// Colors used across the UI. Every widget takes its colors from here so the palette can be
+/// switched for light terminals or customised in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub staged: Color,
+    pub modified: Color,
+    pub untracked: Color,
+    /// Added and removed diff lines and hunk headers
+    pub added: Color,
+    pub removed: Color,
+    pub hunk: Color,
+    /// Titles and key names
+    pub accent: Color,
+    /// Section headings, commit hashes and the commit input
+    pub heading: Color,
+    pub text: Color,
+    pub muted: Color,
+    pub border: Color,
+    pub highlight_fg: Color,
+    pub highlight_bg: Color,
+    /// Destructive keys such as quit and discard
+    pub danger: Color,
+    pub confirm: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// The original palette, meant for dark terminal backgrounds
+    pub const fn dark() -> Self {
+        Self {
+            staged: Color::Green,
+            modified: Color::Red,
+            untracked: Color::Red,
+            added: Color::Green,
+            removed: Color::Red,
+            hunk: Color::Cyan,
+            accent: Color::Cyan,
+            heading: Color::Yellow,
+            text: Color::White,
+            muted: Color::DarkGray,
+            border: Color::Blue,
+            highlight_fg: Color::Yellow,
+            highlight_bg: Color::DarkGray,
+            danger: Color::Red,
+            confirm: Color::Magenta,
+        }
+    }
+
+    /// Darker foregrounds that stay readable on a light background
+    pub const fn light() -> Self {
+        Self {
+            staged: Color::Rgb(0x1a, 0x7f, 0x37),
+            modified: Color::Rgb(0xb3, 0x1d, 0x28),
+            untracked: Color::Rgb(0x9a, 0x67, 0x00),
+            added: Color::Rgb(0x1a, 0x7f, 0x37),
+            removed: Color::Rgb(0xb3, 0x1d, 0x28),
+            hunk: Color::Rgb(0x05, 0x50, 0xae),
+            accent: Color::Rgb(0x05, 0x50, 0xae),
+            heading: Color::Rgb(0x8a, 0x46, 0x00),
+            text: Color::Black,
+            muted: Color::Rgb(0x6e, 0x77, 0x81),
+            border: Color::Rgb(0x57, 0x60, 0x6a),
+            highlight_fg: Color::Black,
+            highlight_bg: Color::Rgb(0xdd, 0xe4, 0xee),
+            danger: Color::Rgb(0xb3, 0x1d, 0x28),
+            confirm: Color::Rgb(0x82, 0x50, 0xdf),
+        }
+    }
+
+    /// Builds a theme from the `[theme]` table: a preset plus per-color overrides, fitted to
+    /// `detected` unless the table sets `colors`
+    pub fn from_config(config: &ThemeConfig, detected: ColorDepth) -> Result<Self, String> {
+        let mut theme = match config.preset.as_deref() {
+            None | Some("dark") => Self::dark(),
+            Some("light") => Self::light(),
+            Some(other) => return Err(format!("Unknown theme preset: {other}")),
+        };
+
+        let overrides = [
+            (&config.staged, &mut theme.staged),
+            (&config.modified, &mut theme.modified),
+            (&config.untracked, &mut theme.untracked),
+            (&config.added, &mut theme.added),
+            (&config.removed, &mut theme.removed),
+            (&config.hunk, &mut theme.hunk),
+            (&config.accent, &mut theme.accent),
+            (&config.heading, &mut theme.heading),
+            (&config.text, &mut theme.text),
+            (&config.muted, &mut theme.muted),
+            (&config.border, &mut theme.border),
+            (&config.highlight_fg, &mut theme.highlight_fg),
+            (&config.highlight_bg, &mut theme.highlight_bg),
+            (&config.danger, &mut theme.danger),
+            (&config.confirm, &mut theme.confirm),
+        ];
+        for (value, slot) in overrides {
+            if let Some(value) = value {
+                *slot = parse_color(value).ok_or_else(|| format!("Invalid color: {value}"))?;
+            }
+        }
+
+        let depth = match config.colors.as_deref() {
+            None | Some("auto") => detected,
+            Some(value) => {
+                ColorDepth::parse(value).ok_or_else(|| format!("Unknown color depth: {value}"))?
+            },
+        };
+        Ok(theme.map(|color| depth.fit(color)))
+    }
+
+    fn map(self, f: impl Fn(Color) -> Color) -> Self {
+        Self {
+            staged: f(self.staged),
+            modified: f(self.modified),
+            untracked: f(self.untracked),
+            added: f(self.added),
+            removed: f(self.removed),
+            hunk: f(self.hunk),
+            accent: f(self.accent),
+            heading: f(self.heading),
+            text: f(self.text),
+            muted: f(self.muted),
+            border: f(self.border),
+            highlight_fg: f(self.highlight_fg),
+            highlight_bg: f(self.highlight_bg),
+            danger: f(self.danger),
+            confirm: f(self.confirm),
+        }
+    }
+
+    pub const fn section_color(&self, section: Section) -> Color {
+        match section {
+            Section::Conflicted => self.danger,
+            Section::Staged => self.staged,
+            Section::Unstaged => self.modified,
+            Section::Untracked => self.untracked,
+        }
+    }
+
+    /// Style for one line of a unified diff
+    pub fn diff_style(&self, kind: LineKind) -> Style {
+        match kind {
+            LineKind::Added => Style::default().fg(self.added),
+            LineKind::Removed => Style::default().fg(self.removed),
+            LineKind::HunkHeader => Style::default().fg(self.hunk),
+            LineKind::FileHeader => Style::default().add_modifier(Modifier::BOLD),
+            LineKind::Text | LineKind::Context | LineKind::NoNewline => Style::default(),
+        }
+    }
+}
+
+/// How many colors the terminal can show. Colors the terminal lacks are replaced by the
+/// nearest one it has, so RGB presets and overrides still look close in plain xterm or tmux.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+/// The 16 ANSI colors with xterm's default values, which most terminals stay close to
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel values of the 6x6x6 color cube at indexes 16-231
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Guesses from `COLORTERM` and `TERM`, the way most terminal programs do. `env` looks up
+    /// an environment variable.
+    pub fn detect(env: impl Fn(&str) -> Option<String>) -> Self {
+        let colorterm = env("COLORTERM").unwrap_or_default();
+        let term = env("TERM").unwrap_or_default();
+        if matches!(colorterm.as_str(), "truecolor" | "24bit")
+            || term.ends_with("-direct")
+            || env("WT_SESSION").is_some()
+        {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// The `colors` value of the `[theme]` table
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "truecolor" | "24bit" => Some(Self::TrueColor),
+            "256" => Some(Self::Ansi256),
+            "16" => Some(Self::Ansi16),
+            _ => None,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::TrueColor => "truecolor",
+            Self::Ansi256 => "256 colors",
+            Self::Ansi16 => "16 colors",
+        }
+    }
+
+    /// `color` if the terminal can show it, otherwise the nearest color it can
+    pub fn fit(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256((r, g, b))),
+            (Self::Ansi16, Color::Rgb(r, g, b)) => nearest_16((r, g, b)),
+            (Self::Ansi16, Color::Indexed(index)) if index < 16 => ANSI16[usize::from(index)].0,
+            (Self::Ansi16, Color::Indexed(index)) => nearest_16(indexed_rgb(index)),
+            _ => color,
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel = |x: u8, y: u8| u32::from(x.abs_diff(y)).pow(2);
+    channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, value)| distance(rgb, *value))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+/// The closest of the color cube and the gray ramp at 232-255
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |value: u8| match value {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (value - 35) / 40,
+    };
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+    let average = (u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3;
+    let gray = 232 + u8::try_from(average.saturating_sub(3) / 10).map_or(23, |step| step.min(23));
+    if distance(rgb, indexed_rgb(gray)) < distance(rgb, indexed_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// The RGB value xterm uses for a 256-color index
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[usize::from(index)].1,
+        16..=231 => {
+            let cube = index - 16;
+            let level = |step: u8| CUBE_LEVELS[usize::from(step)];
+            (level(cube / 36), level(cube / 6 % 6), level(cube % 6))
+        },
+        _ => {
+            let value = 8 + 10 * (index - 232);
+            (value, value, value)
+        },
+    }
+}
+
+/// The `[theme]` table of the config file. Colors are names (`"green"`, `"darkgray"`),
+/// `"#rrggbb"` or a 256-color index.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// `"dark"` (default) or `"light"`
+    pub preset: Option<String>,
+    /// `"auto"` (default), `"truecolor"`, `"256"` or `"16"`
+    pub colors: Option<String>,
+    pub staged: Option<String>,
+    pub modified: Option<String>,
+    pub untracked: Option<String>,
+    pub added: Option<String>,
+    pub removed: Option<String>,
+    pub hunk: Option<String>,
+    pub accent: Option<String>,
+    pub heading: Option<String>,
+    pub text: Option<String>,
+    pub muted: Option<String>,
+    pub border: Option<String>,
+    pub highlight_fg: Option<String>,
+    pub highlight_bg: Option<String>,
+    pub danger: Option<String>,
+    pub confirm: Option<String>,
+}
+
+pub fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim().to_ascii_lowercase();
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    if let Ok(index) = value.parse::<u8>() {
+        return Some(Color::Indexed(index));
+    }
+    let color = match value.replace(['_', '-', ' '], "").as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("green"), Some(Color::Green));
+        assert_eq!(parse_color("Dark_Gray"), Some(Color::DarkGray));
+        assert_eq!(parse_color("#1a7F37"), Some(Color::Rgb(0x1a, 0x7f, 0x37)));
+        assert_eq!(parse_color("208"), Some(Color::Indexed(208)));
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("chartreuse"), None);
+    }
+
+    #[test]
+    fn test_theme_from_config() {
+        assert_eq!(
+            Theme::from_config(&ThemeConfig::default(), ColorDepth::Ansi16),
+            Ok(Theme::dark())
+        );
+
+        let config = ThemeConfig {
+            preset: Some("light".to_string()),
+            staged: Some("blue".to_string()),
+            ..ThemeConfig::default()
+        };
+        let theme = Theme::from_config(&config, ColorDepth::TrueColor).unwrap();
+        assert_eq!(theme.staged, Color::Blue);
+        assert_eq!(theme.modified, Theme::light().modified);
+        assert_eq!(theme.section_color(Section::Staged), Color::Blue);
+
+        let bad = ThemeConfig {
+            modified: Some("nope".to_string()),
+            ..ThemeConfig::default()
+        };
+        assert!(Theme::from_config(&bad, ColorDepth::TrueColor).is_err());
+    }
+
+    #[test]
+    fn test_detect_color_depth() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (*value).to_string())
+            }
+        };
+        assert_eq!(
+            ColorDepth::detect(env(&[
+                ("TERM", "xterm-256color"),
+                ("COLORTERM", "truecolor")
+            ])),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::detect(env(&[("TERM", "tmux-256color")])),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            ColorDepth::detect(env(&[("TERM", "xterm")])),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(ColorDepth::detect(env(&[])), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn test_fit_colors_to_depth() {
+        let green = Color::Rgb(0x1a, 0x7f, 0x37);
+        assert_eq!(ColorDepth::TrueColor.fit(green), green);
+        assert_eq!(ColorDepth::Ansi256.fit(green), Color::Indexed(29));
+        assert_eq!(ColorDepth::Ansi16.fit(green), Color::Green);
+        assert_eq!(
+            ColorDepth::Ansi256.fit(Color::Rgb(0xdd, 0xe4, 0xee)),
+            Color::Indexed(254)
+        );
+        assert_eq!(ColorDepth::Ansi16.fit(Color::Indexed(9)), Color::LightRed);
+        assert_eq!(ColorDepth::Ansi16.fit(Color::Indexed(208)), Color::Yellow);
+        assert_eq!(ColorDepth::Ansi16.fit(Color::Cyan), Color::Cyan);
+
+        let config = ThemeConfig {
+            preset: Some("light".to_string()),
+            colors: Some("16".to_string()),
+            ..ThemeConfig::default()
+        };
+        let theme = Theme::from_config(&config, ColorDepth::TrueColor).unwrap();
+        assert_eq!(theme.modified, Color::Red);
+        assert_eq!(theme.muted, Color::DarkGray);
+
+        let bad = ThemeConfig {
+            colors: Some("8".to_string()),
+            ..ThemeConfig::default()
+        };
+        assert!(Theme::from_config(&bad, ColorDepth::TrueColor).is_err());
+    }
+}
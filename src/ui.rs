@@ -1,37 +1,62 @@
-use crate::app::{App, InputMode};
-use tui::{
-    backend::Backend,
-    layout::{Alignment, Constraint, Direction, Layout},
+use crate::app::{
+    App, BranchPrompt, CommitDetailsRow, CommitDetailsScreen, FileListView, FileRow, InitStep,
+    InputMode, COMMON_CONFIG_KEYS,
+};
+use crate::askpass;
+use crate::binary;
+use crate::command_log::{self, CommandRecord};
+use crate::config::PreviewPosition;
+use crate::diff_lines::{match_ranges, DiffLines};
+use crate::doctor;
+use crate::file_tree;
+use crate::git::{
+    BisectStatus, BranchEntry, CommitDetails, CommitFile, ConfigEntry, ConflictHunk, DetachedHead,
+    DiffOptions, DiffStat, DiffView, FileEntry, FileVersion, GitOperations, HeadSummary,
+    MaintenanceTask, MergeStrategy, PullStrategy, ReflogEntry, RepoStats, Section, SignatureStatus,
+    StatusSummary, UpstreamStatus, GITIGNORE_TEMPLATES,
+};
+use crate::history::{self, HistoryEntry};
+use crate::keymap;
+use crate::sort::SortMode;
+use crate::status_line::{Severity, StatusLine};
+use crate::strings::{Language, Msg};
+use crate::text_input::TextInput;
+use crate::theme::Theme;
+use crate::worker::Worker;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
-    text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Widget, Wrap,
+    },
     Frame,
 };
+use std::borrow::Cow;
+use std::time::{Instant, SystemTime};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-pub fn render_ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
-    // Handle fullscreen preview mode
-    if let InputMode::Preview { content, file_path } = &app.input_mode {
-        render_preview(f, content, file_path, app.preview_scroll, f.size());
-        return;
+pub fn render_ui(f: &mut Frame, app: &mut App) {
+    render_screen(f, app);
+    if let Some(toast) = &app.toast {
+        render_toast(f, &app.theme, &toast.message);
     }
+}
 
-    // Handle help mode with proper layout
-    if let InputMode::Help = &app.input_mode {
-        let help_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Length(3), // Status bar
-                    Constraint::Min(5),    // Help content
-                    Constraint::Length(3), // Bottom status
-                ]
-                .as_ref(),
-            )
-            .split(f.size());
+fn render_screen(f: &mut Frame, app: &mut App) {
+    if !fits_main_layout(f.area()) {
+        render_too_small(f, app);
+        return;
+    }
+    if render_fullscreen_view(f, app) {
+        return;
+    }
 
-        render_status_bar(f, app, help_chunks[0]);
-        crate::ui_help::render_clean_help(f, app, help_chunks[1]);
-        render_help_status(f, help_chunks[2]);
+    // Help is a popup over the current screen, or fullscreen when the terminal is too small
+    if app.input_mode == InputMode::Help && !fits_help_popup(f.area()) {
+        render_fullscreen_help(f, app);
         return;
     }
 
@@ -42,80 +67,453 @@ pub fn render_ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
                 Constraint::Length(3), // Status bar
                 Constraint::Min(5),    // Main content
                 Constraint::Length(3), // Input area
+                Constraint::Length(1), // Key hints
             ]
             .as_ref(),
         )
-        .split(f.size());
+        .split(f.area());
 
     render_status_bar(f, app, main_chunks[0]);
+    render_hint_bar(f, app, main_chunks[3]);
+
+    // A running bisect or a detached HEAD keeps a one-line banner above every view. Bisect
+    // detaches HEAD itself, so its banner comes first.
+    let mut main_area = main_chunks[1];
+    let banner = match (&app.bisect, &app.detached) {
+        (Some(bisect), _) => Some((
+            format_bisect_banner(bisect, app.language()),
+            app.theme.confirm,
+        )),
+        (None, Some(detached)) => {
+            let hash = app.head.as_ref().map_or("HEAD", |head| head.hash.as_str());
+            Some((
+                format_detached_banner(detached, hash, app.language()),
+                app.theme.danger,
+            ))
+        },
+        (None, None) => None,
+    };
+    if let Some((text, color)) = banner {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(4)].as_ref())
+            .split(main_area);
+        let banner = Paragraph::new(text).style(
+            Style::default()
+                .fg(app.theme.highlight_fg)
+                .bg(color)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_widget(banner, chunks[0]);
+        main_area = chunks[1];
+    }
+
+    if render_mode_view(f, app, main_area) {
+        render_input_area(f, app, main_chunks[2]);
+        return;
+    }
+
+    // Commit lint problems are listed right above the commit message input
+    let mut content_area = main_area;
+    if app.input_mode == InputMode::Commit {
+        let violations = app.commit_violations();
+        if !violations.is_empty() {
+            let height = u16::try_from(violations.len() + 2).unwrap_or(u16::MAX);
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(height)].as_ref())
+                .split(content_area);
+            content_area = chunks[0];
+            render_commit_violations(f, app, &violations, chunks[1]);
+        }
+    }
+
+    render_content(f, app, content_area);
+    render_input_area(f, app, main_chunks[2]);
+
+    if app.input_mode == InputMode::Help {
+        render_help_popup(f, app);
+    }
+}
 
-    // Split main content horizontally if preview panel is enabled
-    if app.show_preview_panel {
+/// The file list and, next to or above it, the preview panel and the summary of the commit
+/// being written
+fn render_content(f: &mut Frame, app: &mut App, content_area: Rect) {
+    let committing = app.input_mode == InputMode::Commit;
+    // Split main content next to or above the preview panel if it is enabled
+    if app.show_preview_panel || committing {
+        let direction = if app.layout.preview == PreviewPosition::Bottom {
+            Direction::Vertical
+        } else {
+            Direction::Horizontal
+        };
+        let preview_percent = app.layout.preview_percent();
         let content_chunks = Layout::default()
-            .direction(Direction::Horizontal)
+            .direction(direction)
             .constraints(
                 [
-                    Constraint::Percentage(50), // File list
-                    Constraint::Percentage(50), // Preview panel
+                    Constraint::Percentage(100 - preview_percent), // File list
+                    Constraint::Percentage(preview_percent),       // Preview panel
                 ]
                 .as_ref(),
             )
-            .split(main_chunks[1]);
+            .split(content_area);
 
         render_file_list(f, app, content_chunks[0]);
-        render_preview_panel(f, app, content_chunks[1]);
+        let mut preview_area = content_chunks[1];
+        if committing {
+            preview_area = render_commit_summary(f, app, preview_area);
+        }
+        if !app.show_preview_panel {
+            return;
+        }
+        app.areas.preview_panel = preview_area;
+        if app.input_mode == InputMode::Normal {
+            app.clamp_preview_scroll();
+        }
+        render_preview_panel(f, app, preview_area);
     } else {
-        render_file_list(f, app, main_chunks[1]);
+        render_file_list(f, app, content_area);
     }
+}
 
-    render_input_area(f, app, main_chunks[2]);
+/// `git diff --cached --stat` of what the commit will contain, or a warning when it contains
+/// nothing. Takes the top of `area`, or all of it without the preview panel, and returns
+/// what is left.
+fn render_commit_summary(f: &mut Frame, app: &App, area: Rect) -> Rect {
+    let theme = &app.theme;
+    let language = app.language();
+    let (lines, color) = if app.commit_summary.is_empty() {
+        let warning = if app.files.iter().any(FileEntry::is_staged) {
+            Msg::NothingIncluded
+        } else {
+            Msg::NothingStaged
+        };
+        (vec![Line::from(warning.text(language))], theme.danger)
+    } else {
+        let lines = app
+            .commit_summary
+            .lines()
+            .map(|line| format_stat_line(line, theme));
+        (lines.collect(), theme.heading)
+    };
+    let (summary_area, rest) = if app.show_preview_panel {
+        let height = u16::try_from(lines.len() + 2)
+            .unwrap_or(u16::MAX)
+            .min(area.height / 2);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(height), Constraint::Min(3)].as_ref())
+            .split(area);
+        (chunks[0], chunks[1])
+    } else {
+        (area, Rect::default())
+    };
+    let summary = Paragraph::new(lines)
+        .style(Style::default().fg(color))
+        .block(
+            Block::default()
+                .title(Msg::CommitSummaryTitle.text(language))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color)),
+        );
+    f.render_widget(summary, summary_area);
+    rest
+}
+
+/// A `--stat` line with its `+++--` graph in the added and removed colors
+fn format_stat_line(line: &str, theme: &Theme) -> Line<'static> {
+    let Some((name, graph)) = line.rsplit_once('|') else {
+        return Line::from(Span::styled(
+            line.to_string(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    };
+    let count = graph.trim_end_matches(['+', '-']);
+    let signs = &graph[count.len()..];
+    let added = signs.trim_end_matches('-');
+    Line::from(vec![
+        Span::styled(format!("{name}|{count}"), Style::default().fg(theme.text)),
+        Span::styled(added.to_string(), Style::default().fg(theme.added)),
+        Span::styled(
+            signs[added.len()..].to_string(),
+            Style::default().fg(theme.removed),
+        ),
+    ])
+}
+
+/// Draws the main area of modes that replace the file list. Returns false for the modes
+/// that keep showing it.
+fn render_mode_view(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) -> bool {
+    // The refs to compare and the archive file are typed below the view they came from
+    let origin = match &app.input_mode {
+        InputMode::CompareInput { .. } => Some(app.compare.origin.clone()),
+        InputMode::ArchivePrompt { previous, .. } => Some(previous.as_ref().clone()),
+        _ => None,
+    };
+    if let Some(origin) = origin {
+        match origin {
+            InputMode::Log => render_log(f, app, area),
+            InputMode::Branches => render_branches(f, app, area),
+            _ => return false,
+        }
+        return true;
+    }
+    match app.input_mode {
+        InputMode::Log | InputMode::LogSearch { .. } => render_log(f, app, area),
+        InputMode::Reflog => render_reflog(f, app, area),
+        // A branch created at a detached HEAD is named below the file list
+        InputMode::BranchPrompt {
+            prompt: BranchPrompt::Create,
+            ..
+        } => return false,
+        InputMode::Branches | InputMode::BranchPrompt { .. } => render_branches(f, app, area),
+        InputMode::MergePicker { .. } => render_merge_picker(f, app, area),
+        InputMode::PullPicker { default } => render_pull_picker(f, app, default, area),
+        InputMode::Maintenance => render_maintenance(f, app, area),
+        InputMode::CommitDetails => render_commit_details(f, app, area),
+        InputMode::Compare => render_compare(f, app, area),
+        InputMode::StashList => render_stash_list(f, app, area),
+        InputMode::RepoSwitcher => render_repo_switcher(f, app, area),
+        InputMode::NoRepository { .. } => render_no_repository(f, app, area),
+        InputMode::InitSetup { .. } => render_init_setup(f, app, area),
+        InputMode::Conflict { .. } => render_conflict(f, app, area),
+        InputMode::LineStage { .. } => render_line_stage(f, app, area),
+        InputMode::IgnoreChooser { .. } => render_ignore_chooser(f, app, area),
+        InputMode::HookOutput { .. } => render_hook_output(f, app, area),
+        InputMode::CommandLog => render_command_log(f, app, area),
+        InputMode::History => render_history(f, app, area),
+        InputMode::Doctor => render_doctor(f, app, area),
+        InputMode::Config | InputMode::ConfigEdit { .. } => render_config(f, app, area),
+        InputMode::PrefixPicker | InputMode::PrefixScope { .. } => {
+            render_prefix_picker(f, app, area);
+        },
+        InputMode::IdentityPicker => render_identity_picker(f, app, area),
+        InputMode::TrailerPicker => render_trailer_picker(f, app, area),
+        _ => return false,
+    }
+    true
+}
+
+fn render_commit_violations(
+    f: &mut Frame,
+    app: &App,
+    violations: &[String],
+    area: ratatui::layout::Rect,
+) {
+    let theme = &app.theme;
+    let lines: Vec<Line> = violations
+        .iter()
+        .map(|violation| {
+            Line::from(Span::styled(
+                format!("✗ {violation}"),
+                Style::default().fg(theme.danger),
+            ))
+        })
+        .collect();
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(Msg::CommitViolations.text(app.language()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.danger)),
+    );
+    f.render_widget(widget, area);
 }
 
-fn render_status_bar<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+fn render_status_bar(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let mut repo_line = vec![
+        Span::styled(
+            "Pretty Git UI v0.1.0",
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" | ", Style::default().fg(theme.muted)),
+        Span::styled(
+            format!("{}@{}", app.repo_name, app.current_branch),
+            Style::default().fg(theme.staged),
+        ),
+        Span::styled(
+            format_upstream(app.upstream.as_ref()),
+            Style::default().fg(theme.heading),
+        ),
+    ];
+    if let Some(head) = &app.head {
+        repo_line.extend(format_head(&theme, app.language(), head));
+    }
     let status_content = vec![
-        Spans::from(vec![
-            Span::styled("Pretty Git UI v0.1.0", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-            Span::styled(" | ", Style::default().fg(Color::DarkGray)),
-            Span::styled(format!("{}@{}", &app.repo_name, &app.current_branch), Style::default().fg(Color::Green)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Press "),
-            Span::styled("[h]", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-            Span::raw(" for help, "),
-            Span::styled("[q]", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
-            Span::raw(" to quit"),
-        ]),
+        Line::from(repo_line),
+        format_status_summary(
+            &theme,
+            app.language(),
+            StatusSummary::from_entries(&app.files),
+        ),
     ];
 
     let status = Paragraph::new(status_content).block(
         Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Blue))
-            .title("Git Repository Status"),
+            .border_style(Style::default().fg(theme.border))
+            .title(Msg::RepoStatus.text(app.language())),
     );
 
     f.render_widget(status, area);
 }
 
-fn render_file_list<B: Backend>(f: &mut Frame<B>, app: &mut App, area: tui::layout::Rect) {
+/// Bisect progress and the keys that drive it
+fn format_bisect_banner(status: &BisectStatus, language: Language) -> String {
+    match status {
+        BisectStatus::Waiting => Msg::BisectWaiting.text(language).to_string(),
+        BisectStatus::Testing {
+            current,
+            remaining,
+            steps,
+        } => Msg::BisectTesting.fill(language, &[current, remaining, steps]),
+        BisectStatus::Found { first_bad } => Msg::BisectFound.fill(language, &[first_bad]),
+    }
+}
+
+/// The detached HEAD warning with the keys that leave it safely
+fn format_detached_banner(detached: &DetachedHead, hash: &str, language: Language) -> String {
+    let mut banner = match detached.unreachable {
+        0 => Msg::DetachedHead.fill(language, &[&hash]),
+        count => Msg::DetachedUnreachable.fill(language, &[&hash, &count]),
+    };
+    banner.push_str(Msg::DetachedCreate.text(language));
+    if let Some(branch) = &detached.previous_branch {
+        banner.push_str(&Msg::DetachedBack.fill(language, &[branch]));
+    }
+    banner
+}
+
+/// "3 staged · 5 modified · 2 untracked · 1 conflict", leaving out kinds with no files
+fn format_status_summary(
+    theme: &Theme,
+    language: Language,
+    summary: StatusSummary,
+) -> Line<'static> {
+    if summary.is_clean() {
+        return Line::from(Span::styled(
+            Msg::WorkingTreeClean.text(language),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    let conflict_label = if summary.conflicted == 1 {
+        Msg::SummaryConflict
+    } else {
+        Msg::SummaryConflicts
+    };
+    let parts = [
+        (summary.staged, Msg::SummaryStaged, theme.staged),
+        (summary.modified, Msg::SummaryModified, theme.modified),
+        (summary.untracked, Msg::SummaryUntracked, theme.untracked),
+        (summary.conflicted, conflict_label, theme.danger),
+    ];
+    let mut spans = Vec::new();
+    for (count, label, color) in parts.into_iter().filter(|(count, ..)| *count > 0) {
+        if !spans.is_empty() {
+            spans.push(Span::styled(" · ", Style::default().fg(theme.muted)));
+        }
+        spans.push(Span::styled(
+            format!("{count} {}", label.text(language)),
+            Style::default().fg(color),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// " | 1a2b3c4 Fix typo (42 commits)": the commit HEAD points at and the length of its history
+fn format_head(theme: &Theme, language: Language, head: &HeadSummary) -> Vec<Span<'static>> {
+    vec![
+        Span::styled(" | ", Style::default().fg(theme.muted)),
+        Span::styled(format!("{} ", head.hash), Style::default().fg(theme.accent)),
+        Span::styled(head.subject.clone(), Style::default().fg(theme.text)),
+        Span::styled(
+            Msg::CommitCount.fill(language, &[&head.commit_count]),
+            Style::default().fg(theme.muted),
+        ),
+    ]
+}
+
+/// " ↑2 ↓1 origin/main", " ✓ origin/main" when in sync, nothing without an upstream
+fn format_upstream(upstream: Option<&UpstreamStatus>) -> String {
+    let Some(upstream) = upstream else {
+        return String::new();
+    };
+    let counts = match (upstream.ahead, upstream.behind) {
+        (0, 0) => String::from(" ✓"),
+        (ahead, 0) => format!(" ↑{ahead}"),
+        (0, behind) => format!(" ↓{behind}"),
+        (ahead, behind) => format!(" ↑{ahead} ↓{behind}"),
+    };
+    format!("{counts} {}", upstream.name)
+}
+
+fn render_file_list(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let language = app.language();
+    let rows = app.file_rows();
+    app.areas
+        .file_list
+        .update(area, app.files_state.selected(), rows.len());
+    // The line counts start in the same column on every row
+    let name_width = rows
+        .iter()
+        .filter_map(|row| match *row {
+            FileRow::File { section, index } => {
+                Some(Span::raw(format_file_name(app, &app.files[index], section)).width())
+            },
+            FileRow::Header { .. } | FileRow::Dir { .. } => None,
+        })
+        .max()
+        .unwrap_or(0);
     let files: Vec<ListItem> = if app.files.is_empty() {
-        vec![ListItem::new("変更されたファイルはありません")]
+        vec![ListItem::new(Msg::NoChangedFiles.text(language))]
     } else {
-        app.files
-            .iter()
-            .enumerate()
-            .map(|(_, file_status)| {
-                let formatted = format_file_status(file_status);
-                let color = get_file_color(file_status);
-                ListItem::new(formatted).style(Style::default().fg(color))
+        rows.iter()
+            .map(|row| match *row {
+                FileRow::Header {
+                    section,
+                    count,
+                    collapsed,
+                } => ListItem::new(format_section_header(section, count, collapsed, language))
+                    .style(
+                        Style::default()
+                            .fg(app.theme.section_color(section))
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                FileRow::Dir {
+                    section,
+                    index,
+                    depth,
+                    count,
+                    collapsed,
+                } => ListItem::new(format_dir_row(
+                    &app.dir_path(index, depth),
+                    depth,
+                    count,
+                    collapsed,
+                ))
+                .style(Style::default().fg(app.theme.section_color(section))),
+                FileRow::File { section, index } => {
+                    ListItem::new(format_file_row(app, &app.files[index], section, name_width))
+                },
             })
             .collect()
     };
 
+    let staged = app.files.iter().filter(|entry| entry.is_staged()).count();
     let title = if app.files.is_empty() {
-        "Git ファイル".to_string()
+        Msg::FilesTitle.text(language).to_string()
+    } else if app.input_mode == InputMode::Commit && staged > 0 {
+        let included = staged - app.commit_excluded.len().min(staged);
+        Msg::CommitFilesTitle.fill(language, &[&included, &staged])
+    } else if app.sort_mode == SortMode::default() {
+        Msg::FilesTitleCount.fill(language, &[&app.files.len()])
     } else {
-        format!("Git ファイル ({}個)", app.files.len())
+        let label = app.sort_mode.label().text(language);
+        Msg::FilesTitleSorted.fill(language, &[&app.files.len(), &label])
     };
 
     let files_widget = List::new(files)
@@ -123,620 +521,2348 @@ fn render_file_list<B: Backend>(f: &mut Frame<B>, app: &mut App, area: tui::layo
         .highlight_style(
             Style::default()
                 .add_modifier(Modifier::BOLD)
-                .bg(Color::DarkGray)
-                .fg(Color::Yellow),
+                .bg(theme.highlight_bg)
+                .fg(theme.highlight_fg),
         )
         .highlight_symbol("► ");
 
     f.render_stateful_widget(files_widget, area, &mut app.files_state);
 }
 
-fn render_input_area<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+/// Status and path of a file row, with a checkbox for staged files while committing. The
+/// tree view indents the row under its directory and shows only the file name.
+fn format_file_name(app: &App, entry: &FileEntry, section: Section) -> String {
+    let text = if app.file_list_view == FileListView::Tree {
+        let name = file_tree::file_name(&entry.path);
+        let label = entry
+            .orig_path
+            .as_ref()
+            .map_or_else(|| name.to_string(), |orig| format!("{orig} → {name}"));
+        let indent = "  ".repeat(file_tree::parent_dirs(&entry.path).count());
+        format!("{indent}{}", format_status_label(entry, section, &label))
+    } else {
+        format_file_status(entry, section)
+    };
+    if app.input_mode == InputMode::Commit && section == Section::Staged {
+        let checkbox = if app.commit_excluded.contains(&entry.path) {
+            "[ ] "
+        } else {
+            "[x] "
+        };
+        return format!("{checkbox}{text}");
+    }
+    text
+}
+
+/// A file row: name padded to `name_width`, then `+N -M` line counts in green and red
+fn format_file_row(
+    app: &App,
+    entry: &FileEntry,
+    section: Section,
+    name_width: usize,
+) -> Line<'static> {
+    let theme = &app.theme;
+    let name = format_file_name(app, entry, section);
+    let padding = name_width.saturating_sub(Span::raw(name.as_str()).width());
+    let mut spans = vec![Span::styled(
+        name,
+        Style::default().fg(theme.section_color(section)),
+    )];
+    if let Some(stat) = app.diff_stat(entry, section) {
+        let (added, deleted) = format_diff_stat(stat);
+        spans.push(Span::raw(" ".repeat(padding + 1)));
+        spans.push(Span::styled(added, Style::default().fg(theme.added)));
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(deleted, Style::default().fg(theme.removed)));
+    }
+    if app.ignored_tracked.contains(&entry.path) {
+        spans.push(Span::styled(
+            Msg::IgnoredTracked.text(app.language()),
+            Style::default().fg(theme.section_color(section)),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// `+N` and `-M`, right-aligned so counts of different sizes line up
+fn format_diff_stat(stat: DiffStat) -> (String, String) {
+    let added = format!("+{}", stat.added);
+    let deleted = format!("-{}", stat.deleted);
+    (format!("{added:>5}"), format!("{deleted:>5}"))
+}
+
+fn render_input_area(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let language = app.language();
     match &app.input_mode {
         InputMode::Normal => {
-            let status_msg = Paragraph::new(format!("> {}", app.status_message))
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title("ステータス")
-                        .border_style(Style::default().fg(Color::Blue)),
-                )
-                .style(Style::default().fg(Color::White));
-            f.render_widget(status_msg, area);
+            let status = Msg::Status.text(language);
+            let title = format_running_tasks(&app.worker, app.spinner_frame, language).map_or_else(
+                || status.to_string(),
+                |running| format!("{status} {running}"),
+            );
+            render_status_message(f, app, title, area);
         },
-        InputMode::Commit => {
-            let input = Paragraph::new(app.commit_message.as_ref())
-                .style(Style::default().fg(Color::Yellow))
-                .block(
-                    Block::default()
-                        .title("コミットメッセージ ([Enter]送信 [Esc]キャンセル)")
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::Yellow)),
-                );
-            f.render_widget(input, area);
-            f.set_cursor(area.x + app.commit_message.len() as u16 + 1, area.y + 1);
+        InputMode::Commit => render_commit_input(f, app, area),
+        InputMode::TypedConfirm {
+            message,
+            expected,
+            typed,
+            ..
+        } => {
+            let title = format!(
+                "⚠ {message} {}",
+                Msg::TypeToConfirm.fill(language, &[expected])
+            );
+            render_prompt(f, &title, typed, theme.danger, area);
+        },
+        InputMode::Credential { prompt, input, .. } => {
+            render_credential_input(f, &theme, prompt, input, area);
         },
-        InputMode::StashMessage => {
-            let input = Paragraph::new(app.stash_message.as_ref())
-                .style(Style::default().fg(Color::Blue))
+        InputMode::InitSetup { step, branch, .. } => render_init_input(f, app, *step, branch, area),
+        InputMode::PrefixScope { .. }
+        | InputMode::BranchPrompt { .. }
+        | InputMode::ArchivePrompt { .. }
+        | InputMode::CompareInput { .. }
+        | InputMode::LogSearch { .. }
+        | InputMode::ConfigEdit { .. } => render_text_prompt(f, app, area),
+        InputMode::StashMessage => render_stash_input(f, app, area),
+        InputMode::Confirm { message, .. } => {
+            let confirm_title = Msg::Confirm.text(language);
+            let confirm = Paragraph::new(format!("{confirm_title}: {message}"))
+                .style(Style::default().fg(theme.confirm))
                 .block(
                     Block::default()
-                        .title("スタッシュメッセージ ([Enter]スタッシュ [Esc]キャンセル)")
+                        .title(confirm_title)
                         .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::Blue)),
+                        .border_style(Style::default().fg(theme.confirm)),
                 );
-            f.render_widget(input, area);
-            f.set_cursor(area.x + app.stash_message.len() as u16 + 1, area.y + 1);
-        },
-        InputMode::Confirm { message, .. } => {
-            let confirm = Paragraph::new(format!(
-                "確認: {}\n[y]はい [n]いいえ [Esc]キャンセル",
-                message
-            ))
-            .style(Style::default().fg(Color::Magenta))
-            .block(
-                Block::default()
-                    .title("確認")
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Magenta)),
-            );
             f.render_widget(confirm, area);
         },
         InputMode::Preview { content, file_path } => {
-            render_preview(f, content, file_path, app.preview_scroll, area);
+            render_diff_preview(f, app, content, file_path, area);
         },
-        InputMode::Help => {
-            // Help is handled at the top level, this shouldn't be reached
+        InputMode::Help => render_help_status(f, &theme, language, area),
+        InputMode::Log
+        | InputMode::Reflog
+        | InputMode::Branches
+        | InputMode::MergePicker { .. }
+        | InputMode::PullPicker { .. }
+        | InputMode::Maintenance
+        | InputMode::CommitDetails
+        | InputMode::Compare
+        | InputMode::FileVersion { .. }
+        | InputMode::StashList
+        | InputMode::RepoSwitcher
+        | InputMode::NoRepository { .. }
+        | InputMode::Conflict { .. }
+        | InputMode::LineStage { .. }
+        | InputMode::IgnoreChooser { .. }
+        | InputMode::HookOutput { .. }
+        | InputMode::CommandLog
+        | InputMode::History
+        | InputMode::Doctor
+        | InputMode::Config
+        | InputMode::PrefixPicker
+        | InputMode::IdentityPicker
+        | InputMode::TrailerPicker => {
+            let title = mode_title(&app.input_mode).text(language).to_string();
+            render_status_message(f, app, title, area);
         },
     }
 }
 
-fn get_file_color(file_status: &str) -> Color {
-    if file_status.is_empty() || file_status.len() < 2 {
-        return Color::White;
+/// The shown status message in a box titled `title`
+fn render_status_message(f: &mut Frame, app: &App, title: String, area: Rect) {
+    let line = format_status_line(&app.theme, app.language(), &app.status, Instant::now());
+    let status = Paragraph::new(line).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(app.theme.border)),
+    );
+    f.render_widget(status, area);
+}
+
+/// The message colored by severity, with its age once it is a few seconds old and the
+/// number of errors waiting behind it
+fn format_status_line(
+    theme: &Theme,
+    language: Language,
+    status: &StatusLine,
+    now: Instant,
+) -> Line<'static> {
+    let Some(message) = status.current() else {
+        return Line::from(Span::raw("> "));
+    };
+    let color = match message.severity {
+        Severity::Info => theme.text,
+        Severity::Success => theme.added,
+        Severity::Error => theme.danger,
+    };
+    let mut spans = vec![Span::styled(
+        format!("> {}", message.text),
+        Style::default().fg(color),
+    )];
+    let age = now.saturating_duration_since(message.at);
+    if age.as_secs() >= 5 {
+        spans.push(Span::styled(
+            format!(" · {}", Msg::Ago.fill(language, &[&format_age(age)])),
+            Style::default().fg(theme.muted),
+        ));
     }
+    let hidden = status.hidden_errors();
+    if hidden > 0 {
+        spans.push(Span::styled(
+            Msg::HiddenErrors.fill(language, &[&hidden]),
+            Style::default().fg(theme.danger),
+        ));
+    }
+    Line::from(spans)
+}
 
-    // Check first character for staging status
-    let is_staged = !file_status.chars().next().unwrap_or(' ').is_whitespace();
+/// The stash message box; its title names the selected files and the stash options
+fn render_stash_input(f: &mut Frame, app: &App, area: Rect) {
+    let language = app.language();
+    let stash_message = Msg::StashMessage.text(language);
+    let keep_index = if app.stash_options.keep_index {
+        "--keep-index "
+    } else {
+        ""
+    };
+    // Stashing selected files always takes their untracked ones along
+    let untracked = if app.stash_options.include_untracked || !app.stash_paths.is_empty() {
+        "--include-untracked "
+    } else {
+        ""
+    };
+    let title = match app.stash_paths.len() {
+        0 => stash_message.to_string(),
+        1 => format!("{stash_message} ({})", app.stash_paths[0]),
+        count => Msg::StashMessageFiles.fill(language, &[&count]),
+    };
+    let title = format!("{keep_index}{untracked}{title}");
+    render_text_input(f, &title, &app.stash_message, app.theme.border, area);
+    set_input_cursor(f, area, app.stash_message.before_cursor());
+}
+
+/// The reply to a git or ssh prompt, masked unless it asks for a username or yes/no
+fn render_credential_input(
+    f: &mut Frame,
+    theme: &Theme,
+    prompt: &str,
+    input: &TextInput,
+    area: Rect,
+) {
+    let title = format!("🔑 {prompt}");
+    if askpass::is_secret(prompt) {
+        let masked = "•".repeat(input.chars().count());
+        let before_cursor = "•".repeat(input.before_cursor().chars().count());
+        render_text_input(f, &title, &masked, theme.confirm, area);
+        set_input_cursor(f, area, &before_cursor);
+    } else {
+        render_prompt(f, &title, input, theme.confirm, area);
+    }
+}
 
-    if is_staged {
-        Color::Green
+/// A bordered one-line text box in `color`
+/// The commit message box; its title shows signing and skipped hooks, and turns red while
+/// the hooks are skipped
+fn render_commit_input(f: &mut Frame, app: &App, area: Rect) {
+    let language = app.language();
+    let signing = if app.commit_flags.sign { "🔒 " } else { "" };
+    let no_verify = if app.commit_flags.no_verify {
+        "⚠ --no-verify "
+    } else {
+        ""
+    };
+    let all = if app.commit_flags.all { "✚ -a " } else { "" };
+    let signoff = if app.trailers.signoff { "✍ -s " } else { "" };
+    let trailers = match app.trailers.added.len() {
+        0 => String::new(),
+        count => Msg::TrailerCount.fill(language, &[&count]),
+    };
+    // Only the subject fits the box; a body written in the editor is counted instead
+    let more = match app.commit_message.lines().count() {
+        0 | 1 => String::new(),
+        lines => Msg::MoreLines.fill(language, &[&(lines - 1)]),
+    };
+    let title = format!(
+        "{signing}{no_verify}{all}{signoff}{trailers}{}{more}{}",
+        Msg::CommitMessage.text(language),
+        format_commit_identity(app, language)
+    );
+    let color = if app.commit_flags.no_verify {
+        app.theme.danger
     } else {
-        Color::Red
+        app.theme.heading
+    };
+    render_text_input(f, &title, &app.commit_message, color, area);
+    if more.is_empty() {
+        set_input_cursor(f, area, app.commit_message.before_cursor());
     }
 }
 
-fn format_file_status(file_status: &str) -> String {
-    let chars: Vec<char> = file_status.chars().collect();
-    if chars.len() < 3 {
-        return file_status.to_string();
+/// Who the commit will be made as, so a wrong email shows before committing
+fn format_commit_identity(app: &App, language: Language) -> String {
+    match (&app.identity.commit_override, &app.identity.configured) {
+        (Some(identity), _) => Msg::CommitAsOverride.fill(language, &[identity]),
+        (None, Some(identity)) => Msg::CommitAs.fill(language, &[identity]),
+        (None, None) => Msg::NoIdentity.text(language).to_string(),
     }
+}
 
-    let status_code: String = chars.iter().take(2).collect();
-    let file_path: String = chars.iter().skip(2).collect::<String>().trim().to_string();
+fn render_text_input(f: &mut Frame, title: &str, text: &str, color: Color, area: Rect) {
+    let input = Paragraph::new(text)
+        .style(Style::default().fg(color))
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color)),
+        );
+    f.render_widget(input, area);
+}
 
-    let (status_symbol, status_text) = match status_code.as_str() {
-        "M " => ("✓", "STAGED   "),
-        " M" => ("Δ", "MODIFIED "),
-        "A " => ("+", "ADDED    "),
-        "D " => ("✗", "DELETED  "),
-        " D" => ("✗", "DELETED  "),
-        "??" => ("?", "UNTRACKED"),
-        "MM" => ("±", "PARTIAL  "),
-        "AM" => ("±", "PARTIAL  "),
-        _ => ("•", "CHANGED  "),
+/// A text input with the cursor where it is being edited
+fn render_prompt(f: &mut Frame, title: &str, input: &TextInput, color: Color, area: Rect) {
+    render_text_input(f, title, input, color, area);
+    set_input_cursor(f, area, input.before_cursor());
+}
+
+/// The one-line prompts typed below the view they belong to, titled by what is typed
+fn render_text_prompt(f: &mut Frame, app: &App, area: Rect) {
+    let language = app.language();
+    let (title, input) = match &app.input_mode {
+        InputMode::PrefixScope { prefix, scope } => {
+            (Msg::ScopeTitle.fill(language, &[prefix]), scope)
+        },
+        InputMode::BranchPrompt {
+            branch,
+            prompt,
+            input,
+        } => {
+            let title = match prompt {
+                BranchPrompt::Create => Msg::CreateBranchPrompt,
+                BranchPrompt::Rename => Msg::RenameBranchPrompt,
+                BranchPrompt::SetUpstream => Msg::UpstreamPrompt,
+            };
+            (title.fill(language, &[branch]), input)
+        },
+        InputMode::ArchivePrompt { target, input, .. } => {
+            (Msg::ArchivePrompt.fill(language, &[target]), input)
+        },
+        InputMode::CompareInput { input } => (Msg::ComparePrompt.text(language).to_string(), input),
+        InputMode::LogSearch { query } => (Msg::LogSearchPrompt.text(language).to_string(), query),
+        InputMode::ConfigEdit { key, scope, input } => (
+            Msg::ConfigPrompt.fill(language, &[key, &scope.as_str()]),
+            input,
+        ),
+        _ => return,
     };
+    render_prompt(f, &title, input, app.theme.heading, area);
+}
 
-    format!("{} [{}] {}", status_symbol, status_text, &file_path)
+/// Puts the cursor after `text` in a bordered one-line input, counting display columns so
+/// wide characters move it by two. Stays inside the box when the text is longer.
+fn set_input_cursor(f: &mut Frame, area: Rect, text: &str) {
+    f.set_cursor_position((input_cursor_x(area, text), area.y + 1));
 }
 
-fn render_preview<B: Backend>(
-    f: &mut Frame<B>,
-    content: &str,
-    file_path: &str,
-    scroll: u16,
-    area: tui::layout::Rect,
-) {
-    let lines: Vec<&str> = content.lines().collect();
-    let start_line = scroll as usize;
-    let visible_lines: Vec<Spans> = lines
+fn input_cursor_x(area: Rect, text: &str) -> u16 {
+    let last_column = area.width.saturating_sub(3);
+    let width = u16::try_from(text.width()).unwrap_or(u16::MAX);
+    area.x + 1 + width.min(last_column)
+}
+
+/// `text` cut to at most `max_width` display columns, ending in `...` when it was cut
+fn truncate_to_width(text: &str, max_width: usize) -> Cow<'_, str> {
+    if text.width() <= max_width {
+        return Cow::Borrowed(text);
+    }
+    let limit = max_width.saturating_sub(3);
+    let mut width = 0;
+    let mut truncated: String = text
+        .chars()
+        .take_while(|c| {
+            width += c.width().unwrap_or(0);
+            width <= limit
+        })
+        .collect();
+    truncated.push_str("...");
+    Cow::Owned(truncated)
+}
+
+/// `text` followed by spaces up to `width` display columns
+fn pad_to_width(text: &str, width: usize) -> String {
+    format!("{text}{}", " ".repeat(width.saturating_sub(text.width())))
+}
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Spinner plus the running operations and their progress, e.g. "⠙ 実行中: push 45%".
+/// Preview diffs load in the background too but are not worth a spinner.
+fn format_running_tasks(worker: &Worker, frame: usize, language: Language) -> Option<String> {
+    let running: Vec<String> = worker
+        .in_flight()
         .iter()
-        .skip(start_line)
-        .take((area.height.saturating_sub(2)) as usize)
-        .enumerate()
-        .map(|(i, line)| {
-            let line_number = start_line + i + 1;
-            let line_style = if line.starts_with('+') {
-                Style::default().fg(Color::Green)
-            } else if line.starts_with('-') {
-                Style::default().fg(Color::Red)
-            } else if line.starts_with("@@") {
-                Style::default().fg(Color::Cyan)
-            } else {
-                Style::default()
-            };
+        .filter(|task| !task.is_preview())
+        .map(|task| {
+            worker.progress(task).map_or_else(
+                || task.label(),
+                |percent| format!("{} {percent}%", task.label()),
+            )
+        })
+        .collect();
+    if running.is_empty() {
+        return None;
+    }
+    let spinner = SPINNER[frame % SPINNER.len()];
+    Some(Msg::Running.fill(language, &[&spinner, &running.join(", ")]))
+}
+
+/// Title of the status box of the list-style modes
+const fn mode_title(mode: &InputMode) -> Msg {
+    match mode {
+        InputMode::Log => Msg::ModeLog,
+        InputMode::LogSearch { .. } => Msg::ModeLogSearch,
+        InputMode::FileVersion { .. } => Msg::ModeFileVersion,
+        InputMode::Reflog => Msg::ModeReflog,
+        InputMode::Branches
+        | InputMode::BranchPrompt {
+            prompt: BranchPrompt::Rename | BranchPrompt::SetUpstream,
+            ..
+        } => Msg::ModeBranches,
+        InputMode::MergePicker { .. } => Msg::ModeMerge,
+        InputMode::PullPicker { .. } => Msg::ModePull,
+        InputMode::Maintenance => Msg::ModeMaintenance,
+        InputMode::CommitDetails => Msg::ModeCommitDetails,
+        InputMode::Compare => Msg::ModeCompare,
+        InputMode::StashList => Msg::ModeStashes,
+        InputMode::RepoSwitcher => Msg::ModeRepos,
+        InputMode::NoRepository { .. } => Msg::ModeNoRepository,
+        InputMode::InitSetup { .. } => Msg::ModeInit,
+        InputMode::Conflict { .. } => Msg::ModeConflict,
+        InputMode::LineStage { .. } => Msg::ModeLineStage,
+        InputMode::IgnoreChooser { .. } => Msg::ModeIgnore,
+        InputMode::HookOutput { .. } => Msg::ModeHookOutput,
+        InputMode::CommandLog => Msg::ModeCommandLog,
+        InputMode::History => Msg::ModeHistory,
+        InputMode::Doctor => Msg::ModeDoctor,
+        InputMode::Config | InputMode::ConfigEdit { .. } => Msg::ModeConfig,
+        InputMode::PrefixPicker => Msg::ModePrefix,
+        InputMode::IdentityPicker => Msg::ModeIdentity,
+        InputMode::TrailerPicker => Msg::ModeTrailers,
+        _ => Msg::Status,
+    }
+}
 
-            Spans::from(vec![
+/// One line at the very bottom with the keys that matter in the current mode
+fn render_hint_bar(f: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let spans: Vec<Span> = keymap::hints(app)
+        .into_iter()
+        .flat_map(|(keys, label)| {
+            [
                 Span::styled(
-                    format!("{:4} ", line_number),
-                    Style::default().fg(Color::DarkGray),
+                    format!(" {keys} "),
+                    Style::default()
+                        .fg(theme.highlight_fg)
+                        .bg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
                 ),
-                Span::styled(line.to_string(), line_style),
-            ])
+                Span::styled(format!(" {label} "), Style::default().fg(theme.text)),
+            ]
         })
         .collect();
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
 
-    let preview = Paragraph::new(visible_lines)
+fn render_stash_list(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    app.areas
+        .stash_list
+        .update(area, app.stash_state.selected(), app.stashes.len());
+    let items: Vec<ListItem> = if app.stashes.is_empty() {
+        vec![ListItem::new(Msg::NoStashes.text(app.language()))]
+    } else {
+        app.stashes
+            .iter()
+            .map(|stash| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", stash.reference()),
+                        Style::default().fg(theme.heading),
+                    ),
+                    Span::raw(stash.message.clone()),
+                ]))
+            })
+            .collect()
+    };
+
+    let stash_widget = List::new(items)
         .block(
             Block::default()
-                .title(format!(
-                    "Preview: {} (j/k to scroll, q/Esc to exit)",
-                    file_path
-                ))
+                .title(Msg::StashesTitle.fill(app.language(), &[&app.stashes.len()]))
                 .borders(Borders::ALL),
         )
-        .wrap(Wrap { trim: false });
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
 
-    f.render_widget(preview, area);
+    f.render_stateful_widget(stash_widget, area, &mut app.stash_state);
 }
 
-fn render_help<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
-    let help_text = vec![
-        Spans::from(vec![
-            Span::styled("Pretty Git UI - Help", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan))
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        // Navigation
-        Spans::from(vec![
-            Span::styled("Navigation:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow))
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[j/k]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled(" or ", Style::default().fg(Color::DarkGray)),
-            Span::styled(
-                "[↓/↑]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled(" → ", Style::default().fg(Color::Yellow)),
-            Span::raw("Traverse file_tree[]"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
+fn render_repo_switcher(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    app.areas.repo_list.update(
+        area,
+        app.repo_state.selected(),
+        app.recent_repos.repos.len(),
+    );
+    let current = GitOperations::get_repo_root(&app.repo);
+    let items: Vec<ListItem> = if app.recent_repos.repos.is_empty() {
+        vec![ListItem::new(Msg::NoRecentRepos.text(app.language()))]
+    } else {
+        app.recent_repos
+            .repos
+            .iter()
+            .map(|repo| {
+                let style = if current.as_ref() == Some(repo) {
+                    Style::default().fg(theme.staged)
+                } else {
+                    Style::default().fg(theme.text)
+                };
+                ListItem::new(repo.display().to_string()).style(style)
+            })
+            .collect()
+    };
+
+    let repo_widget = List::new(items)
+        .block(
+            Block::default()
+                .title(Msg::RecentReposTitle.fill(app.language(), &[&app.recent_repos.repos.len()]))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(repo_widget, area, &mut app.repo_state);
+}
+
+/// What to do when started outside a repository, above the recent repositories
+fn render_no_repository(f: &mut Frame, app: &mut App, area: Rect) {
+    let InputMode::NoRepository { dir } = &app.input_mode else {
+        return;
+    };
+    let theme = app.theme;
+    let language = app.language();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(6), Constraint::Min(3)])
+        .split(area);
+    let action = |key: &str, msg: Msg| {
+        Line::from(vec![
             Span::styled(
-                "[h]",
+                format!(" {key:<6}"),
                 Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("Toggle help_system()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[q]",
-                Style::default().add_modifier(Modifier::BOLD).fg(Color::Red),
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
             ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("Process::exit(0)"),
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::styled(
-            "└─────────────────────────────────────────────",
-            Style::default().fg(Color::Green),
-        )]),
-        Spans::from(vec![Span::raw("")]),
-        // File Operations
-        Spans::from(vec![
-            Span::styled("┌──[ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "FILE_OPS",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Yellow),
-            ),
-            Span::styled(
-                " ]───────────────────────────────",
-                Style::default().fg(Color::Green),
-            ),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[s]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("git.stage_toggle(selected)"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[a]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("git.stage_all() // bulk operation"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[r]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("fs.refresh() && git.status()"),
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        // Git Operations
-        Spans::from(vec![Span::styled(
-            "└─────────────────────────────────────────────",
-            Style::default().fg(Color::Green),
-        )]),
-        Spans::from(vec![Span::raw("")]),
-        // Git Operations
-        Spans::from(vec![
-            Span::styled("┌──[ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "GIT_OPS",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Yellow),
-            ),
-            Span::styled(
-                " ]────────────────────────────────",
-                Style::default().fg(Color::Green),
-            ),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[c]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("git.commit_mode()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[t]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("git.stash_mode()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[l]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("git.list_stashes()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[p]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("git.apply_stash()"),
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::styled(
-            "└─────────────────────────────────────────────",
-            Style::default().fg(Color::Green),
-        )]),
-        Spans::from(vec![Span::raw("")]),
-        // Preview
-        Spans::from(vec![
-            Span::styled("┌──[ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "PREVIEW_SYS",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Yellow),
-            ),
-            Span::styled(
-                " ]──────────────────────────",
-                Style::default().fg(Color::Green),
-            ),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[v]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("toggle_preview_panel()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[d]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("           → ", Style::default().fg(Color::Yellow)),
-            Span::raw("render_fullscreen_diff()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[Shift+j/k]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("   → ", Style::default().fg(Color::Yellow)),
-            Span::raw("scroll_preview_buffer()"),
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::styled(
-            "└─────────────────────────────────────────────",
-            Style::default().fg(Color::Green),
-        )]),
-        Spans::from(vec![Span::raw("")]),
-        // Input Modes
-        Spans::from(vec![
-            Span::styled("┌──[ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "INPUT_MODES",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Yellow),
-            ),
-            Span::styled(
-                " ]─────────────────────────",
-                Style::default().fg(Color::Green),
-            ),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[Enter]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Cyan),
-            ),
-            Span::styled("       → ", Style::default().fg(Color::Yellow)),
-            Span::raw("submit_buffer()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[Esc]",
-                Style::default().add_modifier(Modifier::BOLD).fg(Color::Red),
-            ),
-            Span::styled("         → ", Style::default().fg(Color::Yellow)),
-            Span::raw("abort_operation()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[y/n]",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Magenta),
-            ),
-            Span::styled("         → ", Style::default().fg(Color::Yellow)),
-            Span::raw("confirm_dialog()"),
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::styled(
-            "└─────────────────────────────────────────────",
-            Style::default().fg(Color::Green),
-        )]),
-        Spans::from(vec![Span::raw("")]),
-        // File Status Colors
-        Spans::from(vec![
-            Span::styled("┌──[ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "STATUS_CODES",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Yellow),
-            ),
-            Span::styled(
-                " ]────────────────────────",
-                Style::default().fg(Color::Green),
-            ),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "✓[STAGED]   ",
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled("→ ", Style::default().fg(Color::Yellow)),
-            Span::raw("ready_for_commit()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "Δ[MODIFIED] ",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            Span::styled(msg.text(language), Style::default().fg(theme.text)),
+        ])
+    };
+    let lines = vec![
+        Line::from(Span::styled(
+            Msg::NotARepository.fill(language, &[&dir.display()]),
+            Style::default()
+                .fg(theme.danger)
+                .add_modifier(Modifier::BOLD),
+        )),
+        action("i", Msg::InitHere),
+        action("I", Msg::InitWithOptions),
+        action("Enter", Msg::OpenRecentBelow),
+    ];
+    let message = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(message, chunks[0]);
+    render_repo_switcher(f, app, chunks[1]);
+}
+
+/// The `.gitignore` templates of `git init` with options, the first entry writing none
+fn render_init_setup(f: &mut Frame, app: &mut App, area: Rect) {
+    let InputMode::InitSetup { dir, .. } = &app.input_mode else {
+        return;
+    };
+    let theme = app.theme;
+    let language = app.language();
+    let title = Msg::InitTitle.fill(language, &[&dir.display()]);
+    let names = std::iter::once(Msg::InitNoGitignore.text(language))
+        .chain(GITIGNORE_TEMPLATES.iter().map(|template| template.name));
+    let items: Vec<ListItem> = names
+        .map(|name| ListItem::new(name).style(Style::default().fg(theme.text)))
+        .collect();
+    app.init
+        .area
+        .update(area, app.init.state.selected(), items.len());
+
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+    f.render_stateful_widget(list, area, &mut app.init.state);
+}
+
+/// The branch name prompt, the template step's status or the initial commit question
+fn render_init_input(f: &mut Frame, app: &App, step: InitStep, branch: &TextInput, area: Rect) {
+    let theme = app.theme;
+    let language = app.language();
+    match step {
+        InitStep::Branch => {
+            let title = Msg::InitBranchPrompt.text(language);
+            render_prompt(f, title, branch, theme.heading, area);
+        },
+        InitStep::Gitignore => {
+            let title = Msg::ModeInit.text(language).to_string();
+            render_status_message(f, app, title, area);
+        },
+        InitStep::InitialCommit => {
+            let question = Paragraph::new(Msg::InitCommitPrompt.text(language))
+                .style(Style::default().fg(theme.confirm))
+                .block(
+                    Block::default()
+                        .title(Msg::Confirm.text(language))
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.confirm)),
+                );
+            f.render_widget(question, area);
+        },
+    }
+}
+
+fn render_ignore_chooser(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let InputMode::IgnoreChooser { file_path } = &app.input_mode else {
+        return;
+    };
+    let title = Msg::IgnoreTitle.fill(app.language(), &[file_path]);
+    app.ignore
+        .area
+        .update(area, app.ignore.state.selected(), app.ignore_options.len());
+    let items: Vec<ListItem> = app
+        .ignore_options
+        .iter()
+        .map(|pattern| {
+            ListItem::new(format_ignore_option(pattern, app.language()))
+                .style(Style::default().fg(theme.text))
+        })
+        .collect();
+
+    let options_widget = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(options_widget, area, &mut app.ignore.state);
+}
+
+fn render_prefix_picker(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    app.prefix
+        .area
+        .update(area, app.prefix.state.selected(), app.prefix_options.len());
+    let items: Vec<ListItem> = app
+        .prefix_options
+        .iter()
+        .map(|option| {
+            ListItem::new(format!(
+                "{} {}",
+                pad_to_width(&option.prefix, 24),
+                option.description
+            ))
+            .style(Style::default().fg(theme.text))
+        })
+        .collect();
+
+    let options_widget = List::new(items)
+        .block(
+            Block::default()
+                .title(Msg::PrefixPickerTitle.text(app.language()))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(options_widget, area, &mut app.prefix.state);
+}
+
+/// Recent authors with the trailers already added for them; the title names the key
+/// `Enter` adds
+fn render_trailer_picker(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let language = app.language();
+    let keys = &app.commit_lint.trailers.keys;
+    let key = keys.get(app.trailers.key).map_or("", String::as_str);
+    let items: Vec<ListItem> = app
+        .trailers
+        .authors
+        .iter()
+        .map(|author| {
+            let added: Vec<&str> = keys
+                .iter()
+                .filter(|key| app.trailers.added.contains(&format!("{key}: {author}")))
+                .map(String::as_str)
+                .collect();
+            if added.is_empty() {
+                ListItem::new(author.to_string())
+            } else {
+                ListItem::new(format!("{author} ✓ {}", added.join(", ")))
+            }
+        })
+        .collect();
+    let len = items.len();
+    let widget = List::new(items)
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+                .title(Msg::TrailerPickerTitle.fill(language, &[&key]))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+    app.trailers
+        .picker
+        .area
+        .update(area, app.trailers.picker.state.selected(), len);
+    f.render_stateful_widget(widget, area, &mut app.trailers.picker.state);
+}
+
+/// The repository's own identity, then the configured profiles; ✓ marks the one the commit
+/// is made as
+fn render_identity_picker(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let language = app.language();
+    let identity = &app.identity;
+    let configured = identity.configured.as_ref().map_or_else(
+        || Msg::IdentityUnset.text(language).to_string(),
+        ToString::to_string,
+    );
+    let mark = |active: bool| if active { " ✓" } else { "" };
+    let mut items = vec![ListItem::new(format!(
+        "{} {configured}{}",
+        pad_to_width(Msg::RepoIdentity.text(language), 20),
+        mark(identity.commit_override.is_none())
+    ))];
+    items.extend(identity.profiles.iter().map(|profile| {
+        let active = identity.commit_override.as_ref() == Some(profile);
+        ListItem::new(format!(
+            "{} {profile}{}",
+            pad_to_width(&profile.label, 20),
+            mark(active)
+        ))
+    }));
+    let len = items.len();
+    let widget = List::new(items)
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+                .title(Msg::IdentityPickerTitle.text(language))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+    app.identity
+        .picker
+        .area
+        .update(area, app.identity.picker.state.selected(), len);
+    f.render_stateful_widget(widget, area, &mut app.identity.picker.state);
+}
+
+/// Describes what an ignore pattern from `ignore_candidates` covers
+fn format_ignore_option(pattern: &str, language: Language) -> String {
+    let scope = if pattern.starts_with("*.") {
+        Msg::IgnoreExtension
+    } else if pattern.ends_with('/') {
+        Msg::IgnoreDirectory
+    } else {
+        Msg::IgnoreFile
+    };
+    format!("{} {}", pad_to_width(pattern, 30), scope.text(language))
+}
+
+fn render_line_stage(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let InputMode::LineStage { file_path, view } = &app.input_mode else {
+        return;
+    };
+    app.areas
+        .line_stage
+        .update(area, app.line_state.selected(), app.line_patch.lines.len());
+    let items: Vec<ListItem> = app
+        .line_patch
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let selected = app.line_selection.contains(&i);
+            let mut style = theme.diff_style(line.kind);
+            if selected {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            let text = format_patch_line(&line.text, app.line_patch.is_change(i), selected);
+            ListItem::new(text).style(style)
+        })
+        .collect();
+
+    let title = match view {
+        DiffView::Unstaged => Msg::LineStageTitle,
+        DiffView::Staged => Msg::LineUnstageTitle,
+    }
+    .fill(app.language(), &[file_path, &app.line_selection.len()]);
+    let lines_widget = List::new(items)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.accent)),
+        )
+        .highlight_style(Style::default().bg(theme.highlight_bg))
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(lines_widget, area, &mut app.line_state);
+}
+
+/// Prefixes changed lines with a checkbox so the selection is visible
+fn format_patch_line(text: &str, is_change: bool, selected: bool) -> String {
+    match (is_change, selected) {
+        (true, true) => format!("[x] {text}"),
+        (true, false) => format!("[ ] {text}"),
+        (false, _) => format!("    {text}"),
+    }
+}
+
+fn render_conflict(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let language = app.language();
+    let InputMode::Conflict { file_path } = &app.input_mode else {
+        return;
+    };
+    let lines = if app.conflict_hunks.is_empty() {
+        vec![Line::from(Span::styled(
+            Msg::NoConflictMarkers.text(language),
+            Style::default().fg(theme.muted),
+        ))]
+    } else {
+        format_conflict_lines(&theme, language, &app.conflict_hunks)
+    };
+
+    let conflict = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(Msg::ConflictTitle.fill(language, &[file_path, &app.conflict_hunks.len()]))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.danger)),
+        )
+        .scroll((app.conflict_scroll, 0));
+    f.render_widget(conflict, area);
+}
+
+fn render_hook_output(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let InputMode::HookOutput { hook, output } = &app.input_mode else {
+        return;
+    };
+    let text = if output.is_empty() {
+        Msg::HookNoOutput.text(app.language())
+    } else {
+        output.as_str()
+    };
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(Msg::HookOutputTitle.fill(app.language(), &[hook]))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(app.theme.danger)),
+        )
+        .scroll((app.preview_scroll, 0));
+    f.render_widget(paragraph, area);
+}
+
+/// The git commands run so far, newest first, each followed by its stderr
+fn render_command_log(f: &mut Frame, app: &mut App, area: Rect) {
+    app.areas.command_log = area;
+    let theme = app.theme;
+    let language = app.language();
+    let lines: Vec<Line> = if app.command_log.is_empty() && app.running_commands.is_empty() {
+        vec![Line::from(Span::styled(
+            Msg::NoCommandsRun.text(language),
+            Style::default().fg(theme.muted),
+        ))]
+    } else {
+        let running = app
+            .running_commands
+            .iter()
+            .rev()
+            .flat_map(|record| format_command_record(&theme, language, record, true));
+        let finished = app
+            .command_log
+            .iter()
+            .rev()
+            .flat_map(|record| format_command_record(&theme, language, record, false));
+        running.chain(finished).collect()
+    };
+    let total = app
+        .command_log
+        .iter()
+        .chain(&app.running_commands)
+        .map(CommandRecord::line_count)
+        .sum();
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(format!(
+                    "{}{}",
+                    Msg::CommandLogTitle.fill(language, &[&app.command_log.len()]),
+                    format_line_range(app.command_log_scroll, area, total, language)
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        )
+        .scroll((app.command_log_scroll, 0));
+    f.render_widget(paragraph, area);
+}
+
+/// The outcomes of this session's operations, newest first
+fn render_history(f: &mut Frame, app: &mut App, area: Rect) {
+    app.history.area = area;
+    let theme = app.theme;
+    let language = app.language();
+    let lines: Vec<Line> = if app.history.entries.is_empty() {
+        vec![Line::from(Span::styled(
+            Msg::NoHistory.text(language),
+            Style::default().fg(theme.muted),
+        ))]
+    } else {
+        app.history
+            .entries
+            .iter()
+            .rev()
+            .map(|entry| format_history_entry(&theme, entry))
+            .collect()
+    };
+    let total = app.history.entries.len();
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(format!(
+                    "{}{}",
+                    Msg::HistoryTitle.fill(language, &[&total]),
+                    format_line_range(app.history.scroll, area, total, language)
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        )
+        .scroll((app.history.scroll, 0));
+    f.render_widget(paragraph, area);
+}
+
+/// The environment checks, one per line: `✓ git  git version 2.43.0`
+fn render_doctor(f: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let width = app
+        .doctor
+        .iter()
+        .map(|check| check.name.width())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = app
+        .doctor
+        .iter()
+        .map(|check| {
+            let color = match check.level {
+                doctor::Level::Ok => theme.added,
+                doctor::Level::Warning => theme.modified,
+                doctor::Level::Error => theme.danger,
+            };
+            let padding = " ".repeat(width - check.name.width());
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", check.level.mark()),
+                    Style::default().fg(color),
+                ),
+                Span::styled(
+                    format!("{}{padding}  ", check.name),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::styled(check.detail.clone(), Style::default().fg(theme.text)),
+            ])
+        })
+        .collect();
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(Msg::DoctorTitle.text(app.language()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+/// The common keys with the value in effect, then every value git reads with its scope and
+/// origin; values a later scope overrides are dimmed
+fn render_config(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let language = app.language();
+    let screen = &app.config;
+    let width = COMMON_CONFIG_KEYS
+        .iter()
+        .map(|key| key.width())
+        .max()
+        .unwrap_or(0);
+    let origin = |entry: &ConfigEntry| {
+        Span::styled(
+            format!("  [{}] {}", entry.scope.as_str(), entry.origin),
+            Style::default().fg(theme.muted),
+        )
+    };
+    let common = COMMON_CONFIG_KEYS.iter().map(|key| {
+        let mut spans = vec![Span::styled(
+            format!("{key:<width$}  "),
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        )];
+        match screen.effective(key) {
+            Some(entry) => {
+                spans.push(Span::styled(
+                    entry.value.replace('\n', "↵"),
+                    Style::default().fg(theme.text),
+                ));
+                spans.push(origin(entry));
+            },
+            None => spans.push(Span::styled(
+                Msg::ConfigNotSet.text(language),
+                Style::default().fg(theme.muted),
+            )),
+        }
+        ListItem::new(Line::from(spans))
+    });
+    let entries = screen.entries.iter().enumerate().map(|(i, entry)| {
+        let overridden = screen.is_overridden(i);
+        let color = if overridden { theme.muted } else { theme.text };
+        let mut spans = vec![Span::styled(
+            format!("{} = {}", entry.key, entry.value.replace('\n', "↵")),
+            Style::default().fg(color),
+        )];
+        spans.push(origin(entry));
+        if overridden {
+            spans.push(Span::styled(
+                Msg::ConfigOverridden.text(language),
+                Style::default().fg(theme.muted),
+            ));
+        }
+        ListItem::new(Line::from(spans))
+    });
+    let items: Vec<ListItem> = common.chain(entries).collect();
+    let title = Msg::ConfigTitle.fill(language, &[&screen.entries.len()]);
+    app.config
+        .area
+        .update(area, app.config.state.selected(), items.len());
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+    f.render_stateful_widget(list, area, &mut app.config.state);
+}
+
+/// `✓ 2026-10-16T09:15:02Z  project  ✓ Staged 2 file(s)`
+fn format_history_entry(theme: &Theme, entry: &HistoryEntry) -> Line<'static> {
+    let (mark, color) = if entry.succeeded {
+        ("✓", theme.staged)
+    } else {
+        ("✗", theme.danger)
+    };
+    Line::from(vec![
+        Span::styled(format!("{mark} "), Style::default().fg(color)),
+        Span::styled(
+            format!("{}  {}  ", history::format_utc(entry.at), entry.repo),
+            Style::default().fg(theme.muted),
+        ),
+        Span::styled(entry.text.clone(), Style::default().fg(theme.text)),
+    ])
+}
+
+/// `✗ git add -- a.txt  12ms  exit 128` and the indented stderr below it; a command that is
+/// still `running` shows its output so far
+fn format_command_record(
+    theme: &Theme,
+    language: Language,
+    record: &CommandRecord,
+    running: bool,
+) -> Vec<Line<'static>> {
+    let (mark, color) = if running {
+        ("⟳", theme.accent)
+    } else if record.succeeded() {
+        ("✓", theme.staged)
+    } else {
+        ("✗", theme.danger)
+    };
+    let exit = if running {
+        Msg::StillRunning.text(language).to_string()
+    } else {
+        record.exit_code.map_or_else(
+            || Msg::NoExitCode.text(language).to_string(),
+            |code| Msg::ExitCode.fill(language, &[&code]),
+        )
+    };
+    let repeats = if record.repeats > 1 {
+        Msg::Repeats.fill(language, &[&record.repeats])
+    } else {
+        String::new()
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::styled(format!("{mark} "), Style::default().fg(color)),
+        Span::styled(record.command.clone(), Style::default().fg(theme.text)),
+        Span::styled(
+            format!(
+                "  {}  {exit}{repeats}",
+                command_log::format_duration(record.duration)
             ),
-            Span::styled("→ ", Style::default().fg(Color::Yellow)),
-            Span::raw("working_tree_changes()"),
-        ]),
-        Spans::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "?[UNTRACKED]",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.muted),
+        ),
+    ])];
+    let stderr_color = if running || record.succeeded() {
+        theme.muted
+    } else {
+        theme.danger
+    };
+    lines.extend(record.stderr.lines().map(|line| {
+        Line::from(Span::styled(
+            format!("    {line}"),
+            Style::default().fg(stderr_color),
+        ))
+    }));
+    lines
+}
+
+/// Lays out each conflict as ours / base / theirs blocks
+fn format_conflict_lines(
+    theme: &Theme,
+    language: Language,
+    hunks: &[ConflictHunk],
+) -> Vec<Line<'static>> {
+    let heading = |text: String, color| {
+        Line::from(Span::styled(
+            text,
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ))
+    };
+    let body = |line: &String, color| {
+        Line::from(Span::styled(
+            format!("  {line}"),
+            Style::default().fg(color),
+        ))
+    };
+
+    let mut lines = Vec::new();
+    for (i, hunk) in hunks.iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::default());
+        }
+        lines.push(heading(
+            Msg::ConflictHunk.fill(language, &[&(i + 1), &hunk.line]),
+            theme.hunk,
+        ));
+        lines.push(heading(Msg::Ours.text(language).to_string(), theme.added));
+        lines.extend(hunk.ours.iter().map(|line| body(line, theme.added)));
+        if let Some(base) = &hunk.base {
+            lines.push(heading(Msg::Base.text(language).to_string(), theme.muted));
+            lines.extend(base.iter().map(|line| body(line, theme.muted)));
+        }
+        lines.push(heading(
+            Msg::Theirs.text(language).to_string(),
+            theme.removed,
+        ));
+        lines.extend(hunk.theirs.iter().map(|line| body(line, theme.removed)));
+    }
+    lines
+}
+
+fn render_log(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    app.areas
+        .log
+        .update(area, app.log_state.selected(), app.log_entries.len());
+    let items: Vec<ListItem> = if app.log_entries.is_empty() {
+        vec![ListItem::new(Msg::NoCommits.text(app.language()))]
+    } else {
+        app.log_entries
+            .iter()
+            .map(|line| {
+                let mut spans = format_log_line(&app.theme, line, app.is_log_match(line));
+                if let Some(marker) = bisect_marker(app.bisect.as_ref(), line) {
+                    spans.push_span(Span::styled(
+                        marker.text(app.language()),
+                        Style::default()
+                            .fg(theme.confirm)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+                ListItem::new(spans)
+            })
+            .collect()
+    };
+
+    let log_widget = List::new(items)
+        .block(
+            Block::default()
+                .title(format_log_title(app))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(log_widget, area, &mut app.log_state);
+}
+
+fn render_reflog(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    app.areas
+        .reflog
+        .update(area, app.reflog_state.selected(), app.reflog_entries.len());
+    let items: Vec<ListItem> = if app.reflog_entries.is_empty() {
+        vec![ListItem::new(Msg::NoReflog.text(app.language()))]
+    } else {
+        app.reflog_entries
+            .iter()
+            .map(|entry| ListItem::new(format_reflog_entry(&theme, entry)))
+            .collect()
+    };
+
+    let reflog_widget = List::new(items)
+        .block(
+            Block::default()
+                .title(Msg::ReflogTitle.fill(app.language(), &[&app.reflog_entries.len()]))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(reflog_widget, area, &mut app.reflog_state);
+}
+
+fn render_branches(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    app.areas
+        .branch
+        .update(area, app.branch_state.selected(), app.branches.len());
+    let items: Vec<ListItem> = if app.branches.is_empty() {
+        vec![ListItem::new(Msg::NoBranches.text(app.language()))]
+    } else {
+        let width = app
+            .branches
+            .iter()
+            .map(|branch| branch.name.width())
+            .max()
+            .unwrap_or(0);
+        app.branches
+            .iter()
+            .map(|branch| ListItem::new(format_branch(&theme, app.language(), branch, width)))
+            .collect()
+    };
+
+    let branch_widget = List::new(items)
+        .block(
+            Block::default()
+                .title(Msg::BranchesTitle.fill(app.language(), &[&app.branches.len()]))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(branch_widget, area, &mut app.branch_state);
+}
+
+fn render_merge_picker(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let InputMode::MergePicker { branch } = &app.input_mode else {
+        return;
+    };
+    let title = Msg::MergeTitle.fill(app.language(), &[branch, &app.current_branch]);
+    app.merge
+        .area
+        .update(area, app.merge.state.selected(), MergeStrategy::ALL.len());
+    let items: Vec<ListItem> = MergeStrategy::ALL
+        .iter()
+        .map(|&strategy| {
+            ListItem::new(format_merge_strategy(strategy).text(app.language()))
+                .style(Style::default().fg(theme.text))
+        })
+        .collect();
+
+    let strategy_widget = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(strategy_widget, area, &mut app.merge.state);
+}
+
+/// Repository size and the housekeeping commands on the left, the command log with their
+/// output on the right
+fn render_maintenance(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let language = app.language();
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+    let stats_lines = format_repo_stats(&app.maintenance.stats, SystemTime::now(), language);
+    let stats_height = u16::try_from(stats_lines.len() + 2).unwrap_or(u16::MAX);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(stats_height), Constraint::Min(3)].as_ref())
+        .split(columns[0]);
+
+    let stats = Paragraph::new(stats_lines.join("\n"))
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+                .title(Msg::MaintenanceTitle.text(language))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        );
+    f.render_widget(stats, rows[0]);
+
+    app.maintenance.area.update(
+        rows[1],
+        app.maintenance.tasks.selected(),
+        MaintenanceTask::ALL.len(),
+    );
+    let items: Vec<ListItem> = MaintenanceTask::ALL
+        .iter()
+        .map(|&task| {
+            ListItem::new(format_maintenance_task(task).text(language))
+                .style(Style::default().fg(theme.text))
+        })
+        .collect();
+    let tasks = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+    f.render_stateful_widget(tasks, rows[1], &mut app.maintenance.tasks);
+
+    render_command_log(f, app, columns[1]);
+}
+
+/// Metadata and message of the commit on top, its changed files with the expanded diffs below
+fn render_commit_details(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let language = app.language();
+    let header_height = u16::try_from(app.commit_details.details.message.lines().count() + 7)
+        .unwrap_or(u16::MAX)
+        .min(area.height / 2);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(header_height), Constraint::Min(3)].as_ref())
+        .split(area);
+    let file_rows = app.commit_details.rows();
+    let selected = app.commit_details.state.selected();
+    app.commit_details
+        .area
+        .update(rows[1], selected, file_rows.len());
+    let screen = &app.commit_details;
+
+    let header = Paragraph::new(format_commit_details(&theme, &screen.details, language))
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+                .title(Msg::CommitDetailsTitle.fill(language, &[&screen.details.hash]))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(header, rows[0]);
+
+    let items: Vec<ListItem> = if file_rows.is_empty() {
+        vec![ListItem::new(Msg::NoChangedFiles.text(language))]
+    } else {
+        file_rows
+            .iter()
+            .map(|&row| ListItem::new(format_commit_details_row(&theme, screen, row)))
+            .collect()
+    };
+    let files = List::new(items)
+        .block(
+            Block::default()
+                .title(format_changed_files_title(&screen.details, language))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+    f.render_stateful_widget(files, rows[1], &mut app.commit_details.state);
+}
+
+/// Parents, author, committer and signature, then the full message
+fn format_commit_details<'a>(
+    theme: &Theme,
+    details: &'a CommitDetails,
+    language: Language,
+) -> Vec<Line<'a>> {
+    let parents = if details.parents.is_empty() {
+        Msg::DetailsRoot.text(language).to_string()
+    } else {
+        details.parents.join(" ")
+    };
+    let (author, committer) = (&details.author, &details.committer);
+    let signature_style = match details.signature {
+        SignatureStatus::Unsigned => Style::default().fg(theme.muted),
+        SignatureStatus::Good => Style::default().fg(theme.added),
+        SignatureStatus::Bad | SignatureStatus::RevokedKey => Style::default()
+            .fg(theme.danger)
+            .add_modifier(Modifier::BOLD),
+        _ => Style::default().fg(theme.confirm),
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(
+            Msg::DetailsParents.fill(language, &[&parents]),
+            Style::default().fg(theme.heading),
+        )),
+        Line::from(Msg::DetailsAuthor.fill(language, &[&author.name, &author.email, &author.date])),
+        Line::from(Msg::DetailsCommitter.fill(
+            language,
+            &[&committer.name, &committer.email, &committer.date],
+        )),
+        Line::from(Span::styled(
+            Msg::DetailsSignature.fill(
+                language,
+                &[&format_signature(
+                    details.signature,
+                    &details.signer,
+                    language,
+                )],
             ),
-            Span::styled("→ ", Style::default().fg(Color::Yellow)),
-            Span::raw("new_file_detected()"),
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::styled(
-            "└─────────────────────────────────────────────",
-            Style::default().fg(Color::Green),
-        )]),
+            signature_style,
+        )),
+        Line::default(),
     ];
+    lines.extend(details.message.lines().map(Line::from));
+    lines
+}
+
+fn format_signature(status: SignatureStatus, signer: &str, language: Language) -> String {
+    let msg = match status {
+        SignatureStatus::Unsigned => Msg::SignatureUnsigned,
+        SignatureStatus::Good => Msg::SignatureGood,
+        SignatureStatus::GoodUntrusted => Msg::SignatureGoodUntrusted,
+        SignatureStatus::Bad => Msg::SignatureBad,
+        SignatureStatus::ExpiredSignature => Msg::SignatureExpired,
+        SignatureStatus::ExpiredKey => Msg::SignatureExpiredKey,
+        SignatureStatus::RevokedKey => Msg::SignatureRevokedKey,
+        SignatureStatus::Unverifiable => Msg::SignatureUnverifiable,
+    };
+    msg.fill(language, &[&signer])
+}
+
+/// File count and line totals; merges are compared to their first parent
+fn format_changed_files_title(details: &CommitDetails, language: Language) -> String {
+    let (added, deleted) = details
+        .files
+        .iter()
+        .filter_map(|file| file.stat)
+        .fold((0, 0), |(added, deleted), stat| {
+            (added + stat.added, deleted + stat.deleted)
+        });
+    let msg = if details.parents.len() > 1 {
+        Msg::ChangedFilesMergeTitle
+    } else {
+        Msg::ChangedFilesTitle
+    };
+    msg.fill(language, &[&details.files.len(), &added, &deleted])
+}
+
+fn format_commit_details_row(
+    theme: &Theme,
+    screen: &CommitDetailsScreen,
+    row: CommitDetailsRow,
+) -> Line<'static> {
+    match row {
+        CommitDetailsRow::File(index) => {
+            let file = &screen.details.files[index];
+            let marker = if screen.expanded.contains(&index) {
+                "▼"
+            } else {
+                "▶"
+            };
+            format_commit_file(theme, file, marker)
+        },
+        CommitDetailsRow::Diff(index, line) => {
+            let diff = &screen.diffs[&index];
+            Line::from(Span::styled(
+                format!("    {}", diff.line(line).unwrap_or_default()),
+                theme.diff_style(diff.kind(line)),
+            ))
+        },
+    }
+}
+
+fn render_compare(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let language = app.language();
+    let screen = &mut app.compare;
+    screen
+        .area
+        .update(area, screen.state.selected(), screen.files.len());
+    let items: Vec<ListItem> = if screen.files.is_empty() {
+        vec![ListItem::new(Msg::NoRangeChanges.text(language))]
+    } else {
+        screen
+            .files
+            .iter()
+            .map(|file| ListItem::new(format_commit_file(&theme, file, "")))
+            .collect()
+    };
+    let (added, deleted) = screen
+        .files
+        .iter()
+        .filter_map(|file| file.stat)
+        .fold((0, 0), |(added, deleted), stat| {
+            (added + stat.added, deleted + stat.deleted)
+        });
+    let title = Msg::CompareTitle.fill(
+        language,
+        &[&screen.range, &screen.files.len(), &added, &deleted],
+    );
+    let files = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+    f.render_stateful_widget(files, area, &mut screen.state);
+}
+
+/// Status letter, path and line counts of a changed file, after `marker` if not empty
+fn format_commit_file(theme: &Theme, file: &CommitFile, marker: &str) -> Line<'static> {
+    let color = match file.status {
+        'A' => theme.added,
+        'D' => theme.removed,
+        _ => theme.modified,
+    };
+    let status = if marker.is_empty() {
+        format!("{} ", file.status)
+    } else {
+        format!("{marker} {} ", file.status)
+    };
+    Line::from(vec![
+        Span::styled(status, Style::default().fg(color)),
+        Span::raw(format_commit_file_path(file)),
+        Span::styled(
+            format_commit_file_stat(file),
+            Style::default().fg(theme.muted),
+        ),
+    ])
+}
+
+/// `new` or `old → new` for renames and copies
+fn format_commit_file_path(file: &CommitFile) -> String {
+    file.old_path.as_ref().map_or_else(
+        || file.path.clone(),
+        |old_path| format!("{old_path} → {}", file.path),
+    )
+}
+
+/// `  +3 -1`, or `  binary` when git counts no lines
+fn format_commit_file_stat(file: &CommitFile) -> String {
+    file.stat.map_or_else(
+        || String::from("  binary"),
+        |stat| format!("  +{} -{}", stat.added, stat.deleted),
+    )
+}
+
+const fn format_maintenance_task(task: MaintenanceTask) -> Msg {
+    match task {
+        MaintenanceTask::Gc => Msg::MaintenanceGc,
+        MaintenanceTask::Prune => Msg::MaintenancePrune,
+        MaintenanceTask::MaintenanceRun => Msg::MaintenanceRun,
+    }
+}
+
+/// Size, object counts and the time since the last gc, one line each
+fn format_repo_stats(stats: &RepoStats, now: SystemTime, language: Language) -> Vec<String> {
+    let size = |bytes: u64| binary::format_size(usize::try_from(bytes).unwrap_or(usize::MAX));
+    let mut lines = vec![
+        Msg::StatsSize.fill(language, &[&size(stats.total_size())]),
+        Msg::StatsLoose.fill(language, &[&stats.loose_objects, &size(stats.loose_size)]),
+        Msg::StatsPacked.fill(
+            language,
+            &[&stats.packed_objects, &stats.packs, &size(stats.pack_size)],
+        ),
+    ];
+    if stats.garbage > 0 {
+        lines.push(Msg::StatsGarbage.fill(language, &[&stats.garbage]));
+    }
+    let last_gc = stats.last_gc.map_or_else(
+        || Msg::Never.text(language).to_string(),
+        |time| {
+            let age = now.duration_since(time).unwrap_or_default();
+            Msg::Ago.fill(language, &[&format_age(age)])
+        },
+    );
+    lines.push(Msg::StatsLastGc.fill(language, &[&last_gc]));
+    lines
+}
+
+/// `45s`, `12m`, `3h` or `5d`
+fn format_age(age: std::time::Duration) -> String {
+    match age.as_secs() {
+        secs @ 0..=59 => format!("{secs}s"),
+        secs @ 60..=3599 => format!("{}m", secs / 60),
+        secs @ 3600..=86399 => format!("{}h", secs / 3600),
+        secs => format!("{}d", secs / 86400),
+    }
+}
+
+fn render_pull_picker(
+    f: &mut Frame,
+    app: &mut App,
+    default: PullStrategy,
+    area: ratatui::layout::Rect,
+) {
+    let theme = app.theme;
+    let language = app.language();
+    let title = Msg::PullTitle.fill(language, &[&app.current_branch]);
+    app.pull
+        .area
+        .update(area, app.pull.state.selected(), PullStrategy::ALL.len());
+    let items: Vec<ListItem> = PullStrategy::ALL
+        .iter()
+        .map(|&strategy| {
+            let mut text = format_pull_strategy(strategy).text(language).to_string();
+            if strategy == default {
+                text.push_str(Msg::PullDefault.text(language));
+            }
+            ListItem::new(text).style(Style::default().fg(theme.text))
+        })
+        .collect();
+
+    let strategy_widget = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.highlight_bg),
+        )
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(strategy_widget, area, &mut app.pull.state);
+}
+
+const fn format_pull_strategy(strategy: PullStrategy) -> Msg {
+    match strategy {
+        PullStrategy::Rebase => Msg::PullRebase,
+        PullStrategy::Merge => Msg::PullMerge,
+        PullStrategy::FastForwardOnly => Msg::PullFastForward,
+    }
+}
+
+const fn format_merge_strategy(strategy: MergeStrategy) -> Msg {
+    match strategy {
+        MergeStrategy::FastForward => Msg::MergeFastForward,
+        MergeStrategy::NoFastForward => Msg::MergeNoFastForward,
+        MergeStrategy::Squash => Msg::MergeSquash,
+    }
+}
+
+/// `* main  1a2b3c4 subject [origin/main]`, with remote-tracking branches dimmed and
+/// unmerged branches marked
+fn format_branch<'a>(
+    theme: &Theme,
+    language: Language,
+    branch: &'a BranchEntry,
+    width: usize,
+) -> Line<'a> {
+    let name_color = if branch.remote.is_some() {
+        theme.muted
+    } else {
+        theme.text
+    };
+    let mut spans = vec![
+        Span::styled(
+            if branch.is_head { "* " } else { "  " },
+            Style::default().fg(theme.staged),
+        ),
+        Span::styled(
+            pad_to_width(&branch.name, width),
+            Style::default().fg(name_color),
+        ),
+        Span::raw(" "),
+        Span::styled(branch.hash.as_str(), Style::default().fg(theme.heading)),
+        Span::raw(" "),
+        Span::raw(branch.subject.as_str()),
+    ];
+    match &branch.upstream {
+        Some(upstream) if branch.upstream_gone => spans.push(Span::styled(
+            format!(" [{upstream}: gone]"),
+            Style::default().fg(theme.danger),
+        )),
+        Some(upstream) => spans.push(Span::styled(
+            format!(" [{upstream}]"),
+            Style::default().fg(theme.muted),
+        )),
+        None => {},
+    }
+    if !branch.merged && !branch.is_head {
+        spans.push(Span::styled(
+            Msg::Unmerged.text(language),
+            Style::default().fg(theme.danger),
+        ));
+    }
+    Line::from(spans)
+}
 
-    let total_lines = help_text.len();
-    let visible_lines = (area.height.saturating_sub(2)) as usize;
-    let max_scroll = total_lines.saturating_sub(visible_lines);
+/// `1a2b3c4 HEAD@{2} commit: message` with the hash and selector highlighted
+fn format_reflog_entry<'a>(theme: &Theme, entry: &'a ReflogEntry) -> Line<'a> {
+    Line::from(vec![
+        Span::styled(entry.hash.as_str(), Style::default().fg(theme.heading)),
+        Span::raw(" "),
+        Span::styled(entry.selector.as_str(), Style::default().fg(theme.muted)),
+        Span::raw(" "),
+        Span::raw(entry.message.as_str()),
+    ])
+}
 
-    // Apply scroll offset
-    let visible_help_text: Vec<Spans> = help_text
-        .into_iter()
-        .skip(app.help_scroll as usize)
-        .take(visible_lines)
-        .collect();
+/// Marker for the log line of the commit under test or the commit bisect found
+fn bisect_marker(status: Option<&BisectStatus>, line: &str) -> Option<Msg> {
+    let status = status?;
+    let hash = GitOperations::extract_commit_hash(line)?;
+    let commit = status.commit()?;
+    if !(hash.starts_with(commit) || commit.starts_with(hash)) {
+        return None;
+    }
+    Some(if matches!(status, BisectStatus::Found { .. }) {
+        Msg::BisectMarkerFound
+    } else {
+        Msg::BisectMarkerTesting
+    })
+}
+
+/// Splits a `git log --oneline --graph` line into graph, hash and subject spans
+/// A log line with the graph muted and the hash colored; search matches stand out
+fn format_log_line<'a>(theme: &Theme, line: &'a str, matched: bool) -> Line<'a> {
+    let Some(hash) = GitOperations::extract_commit_hash(line) else {
+        return Line::from(Span::styled(line, Style::default().fg(theme.muted)));
+    };
+    let hash_start = line.find(hash).unwrap_or(0);
+    let (graph, rest) = line.split_at(hash_start);
+    let subject = &rest[hash.len()..];
 
-    let scroll_info = if total_lines > visible_lines {
-        format!(
-            " (j/k to scroll {}/{})",
-            app.help_scroll + 1,
-            max_scroll + 1
+    let (hash_style, subject_style) = if matched {
+        (
+            Style::default()
+                .fg(theme.heading)
+                .add_modifier(Modifier::REVERSED),
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
         )
+    } else {
+        (Style::default().fg(theme.heading), Style::default())
+    };
+    Line::from(vec![
+        Span::styled(graph, Style::default().fg(theme.muted)),
+        Span::styled(hash, hash_style),
+        Span::styled(subject, subject_style),
+    ])
+}
+
+/// `コミット履歴 (N件)`, followed by the search and its match count while searching
+fn format_log_title(app: &App) -> String {
+    let title = Msg::LogTitle.fill(app.language(), &[&app.log_entries.len()]);
+    if app.log_search.is_empty() {
+        return title;
+    }
+    let matches = app
+        .log_entries
+        .iter()
+        .filter(|line| app.is_log_match(line))
+        .count();
+    Msg::LogSearchResult.fill(app.language(), &[&title, &app.log_search, &matches])
+}
+
+fn format_section_header(
+    section: Section,
+    count: usize,
+    collapsed: bool,
+    language: Language,
+) -> String {
+    let marker = if collapsed { "▶" } else { "▼" };
+    let title = match section {
+        Section::Conflicted => Msg::SectionConflicted,
+        Section::Staged => Msg::SectionStaged,
+        Section::Unstaged => Msg::SectionUnstaged,
+        Section::Untracked => Msg::SectionUntracked,
+    };
+    format!("{marker} {} ({count})", title.text(language))
+}
+
+/// A directory of the tree view: `  ▼ ui/ (2)`, indented by its depth
+fn format_dir_row(path: &str, depth: usize, count: usize, collapsed: bool) -> String {
+    let marker = if collapsed { "▶" } else { "▼" };
+    let indent = "  ".repeat(depth);
+    format!(
+        "  {indent}{marker} {} ({count})",
+        file_tree::file_name(path)
+    )
+}
+
+/// Formats a file row using the status column that belongs to its section
+fn format_file_status(entry: &FileEntry, section: Section) -> String {
+    format_status_label(entry, section, &entry.display_path())
+}
+
+/// The status of `entry` in `section` followed by `label`
+fn format_status_label(entry: &FileEntry, section: Section, label: &str) -> String {
+    let code = match section {
+        Section::Conflicted => 'U',
+        Section::Staged => entry.index_status,
+        Section::Unstaged => entry.worktree_status,
+        Section::Untracked => '?',
+    };
+
+    let (status_symbol, status_text) = match code {
+        'A' if section == Section::Unstaged && entry.is_intent_to_add() => ("◌", "INTENT   "),
+        'M' => ("Δ", "MODIFIED "),
+        'A' => ("+", "ADDED    "),
+        'D' => ("✗", "DELETED  "),
+        'R' => ("→", "RENAMED  "),
+        '?' => ("?", "UNTRACKED"),
+        'U' => ("!", "CONFLICT "),
+        _ => ("•", "CHANGED  "),
+    };
+
+    format!("  {status_symbol} [{status_text}] {label}")
+}
+
+/// Diff previews and the file viewer take the whole screen
+fn render_fullscreen_view(f: &mut Frame, app: &mut App) -> bool {
+    if !matches!(
+        app.input_mode,
+        InputMode::Preview { .. } | InputMode::FileVersion { .. }
+    ) {
+        return false;
+    }
+    app.areas.fullscreen = f.area();
+    app.clamp_preview_scroll();
+    let app = &*app;
+    match &app.input_mode {
+        InputMode::Preview { content, file_path } => {
+            render_diff_preview(f, app, content, file_path, f.area());
+        },
+        InputMode::FileVersion {
+            file_path,
+            version,
+            content,
+        } => {
+            let view = PreviewView {
+                title: format_file_version_title(file_path, *version, app.language()),
+                diff: false,
+                search: "",
+                current_match: None,
+            };
+            render_preview(f, app, content, &view, f.area());
+        },
+        _ => {},
+    }
+    true
+}
+
+/// How `render_preview` draws its lines
+struct PreviewView<'a> {
+    title: String,
+    /// Color the lines as a diff
+    diff: bool,
+    /// Text to highlight, and the line of the match `n`/`N` moved to
+    search: &'a str,
+    current_match: Option<usize>,
+}
+
+/// The fullscreen diff, with its search prompt or results in the title
+fn render_diff_preview(
+    f: &mut Frame,
+    app: &App,
+    content: &DiffLines,
+    file_path: &str,
+    area: ratatui::layout::Rect,
+) {
+    let language = app.language();
+    let search = match (&app.preview_search_input, app.preview_query.as_str()) {
+        (Some(input), _) => Msg::PreviewSearchPrompt
+            .fill(language, &[&input.before_cursor(), &input.after_cursor()]),
+        (None, "") => String::new(),
+        (None, query) => Msg::PreviewSearchResult.fill(
+            language,
+            &[
+                &query,
+                &app.preview_match.map_or(0, |i| i + 1),
+                &app.preview_matches.len(),
+            ],
+        ),
+    };
+    // Commit and stash diffs, and hex dumps, are not affected by the diff options
+    let options = if app.preview_return_mode == InputMode::Normal && !app.preview_hex {
+        format_diff_options(app.diff_options)
     } else {
         String::new()
     };
+    let title = format!(
+        "{}{options}{search}",
+        Msg::PreviewTitle.fill(language, &[&file_path])
+    );
+    let view = PreviewView {
+        title,
+        diff: true,
+        search: &app.preview_query,
+        current_match: app
+            .preview_match
+            .and_then(|i| app.preview_matches.get(i).copied()),
+    };
+    render_preview(f, app, content, &view, area);
+}
+
+/// Numbered lines of `content` from the preview scroll position on
+fn render_preview(
+    f: &mut Frame,
+    app: &App,
+    content: &DiffLines,
+    view: &PreviewView,
+    area: ratatui::layout::Rect,
+) {
+    let theme = &app.theme;
+    let start_line = app.preview_scroll as usize;
+    let visible_lines: Vec<Line> = content
+        .window(start_line, area.height.saturating_sub(2) as usize)
+        .enumerate()
+        .map(|(i, line)| {
+            let index = start_line + i;
+            let line_style = if view.diff {
+                theme.diff_style(content.kind(index))
+            } else {
+                Style::default().fg(theme.text)
+            };
+            let number_style = if view.current_match == Some(index) {
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.muted)
+            };
+
+            let mut spans = vec![Span::styled(format!("{:4} ", index + 1), number_style)];
+            spans.extend(highlight_matches(line, view.search, line_style));
+            Line::from(spans)
+        })
+        .collect();
 
-    let help = Paragraph::new(visible_help_text)
+    let preview = Paragraph::new(visible_lines)
         .block(
             Block::default()
                 .title(format!(
-                    "┌─[ HELP_SYSTEM ]── KEYBIND_REFERENCE{} ──────────┐",
-                    scroll_info
+                    "{}{}",
+                    view.title,
+                    format_line_range(
+                        app.preview_scroll,
+                        area,
+                        content.line_count(),
+                        app.language()
+                    )
                 ))
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .borders(Borders::ALL),
         )
         .wrap(Wrap { trim: false });
 
-    f.render_widget(help, area);
+    f.render_widget(preview, area);
+    render_scrollbar(f, theme, area, app.preview_scroll, content.line_count());
+}
+
+/// A scrollbar over the right border of `area` while the `total` lines do not fit inside
+fn render_scrollbar(f: &mut Frame, theme: &Theme, area: Rect, scroll: u16, total: usize) {
+    let visible = usize::from(area.height.saturating_sub(2));
+    if total <= visible {
+        return;
+    }
+    let mut state = ScrollbarState::new(total - visible)
+        .position(usize::from(scroll))
+        .viewport_content_length(visible);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .track_symbol(Some(ratatui::symbols::line::VERTICAL))
+        .thumb_style(Style::default().fg(theme.accent));
+    let track = area.inner(Margin {
+        vertical: 1,
+        horizontal: 0,
+    });
+    f.render_stateful_widget(scrollbar, track, &mut state);
+}
+
+/// The lines of `total` shown from `scroll` in the bordered `area`, e.g. ` 11–40/120行`
+fn format_line_range(scroll: u16, area: Rect, total: usize, language: Language) -> String {
+    if total == 0 {
+        return String::new();
+    }
+    let first = usize::from(scroll).min(total - 1) + 1;
+    let last = (first - 1 + usize::from(area.height.saturating_sub(2))).clamp(first, total);
+    Msg::LineRange.fill(language, &[&first, &last, &total])
+}
+
+/// `line` in `style`, with the occurrences of `search` reversed
+fn highlight_matches(line: &str, search: &str, style: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for range in match_ranges(line, search) {
+        if range.start > end {
+            spans.push(Span::styled(line[end..range.start].to_string(), style));
+        }
+        spans.push(Span::styled(
+            line[range.clone()].to_string(),
+            style.add_modifier(Modifier::REVERSED),
+        ));
+        end = range.end;
+    }
+    if end < line.len() || spans.is_empty() {
+        spans.push(Span::styled(line[end..].to_string(), style));
+    }
+    spans
+}
+
+/// `path  HEAD | [インデックス] | 作業ツリー`, with the shown version in brackets
+fn format_file_version_title(file_path: &str, shown: FileVersion, language: Language) -> String {
+    let versions: Vec<String> = FileVersion::ALL
+        .iter()
+        .map(|&version| {
+            let label = match version {
+                FileVersion::Head => "HEAD",
+                FileVersion::Index => Msg::VersionIndex.text(language),
+                FileVersion::Worktree => Msg::VersionWorktree.text(language),
+            };
+            if version == shown {
+                format!("[{label}]")
+            } else {
+                label.to_string()
+            }
+        })
+        .collect();
+    Msg::FileVersionTitle.fill(language, &[&file_path, &versions.join(" | ")])
+}
+
+/// Smallest terminal the panels are laid out in: status bar, five rows of content, input
+/// area and hint bar
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 12;
+
+const fn fits_main_layout(size: Rect) -> bool {
+    size.width >= MIN_WIDTH && size.height >= MIN_HEIGHT
+}
+
+/// Asks for a larger terminal instead of squeezing the panels into it
+fn render_too_small(f: &mut Frame, app: &App) {
+    let size = f.area();
+    let language = app.language();
+    let lines = vec![
+        Line::from(Span::styled(
+            Msg::TerminalTooSmall.fill(language, &[&size.width, &size.height]),
+            Style::default()
+                .fg(app.theme.danger)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            Msg::EnlargeTerminal.fill(language, &[&MIN_WIDTH, &MIN_HEIGHT]),
+            Style::default().fg(app.theme.text),
+        )),
+    ];
+    let top = size.height.saturating_sub(2) / 2;
+    let area = Rect::new(size.x, size.y + top, size.width, size.height - top);
+    let message = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(message, area);
+}
+
+/// Smallest terminal that still shows the help as a popup
+const HELP_POPUP_MIN_WIDTH: u16 = 80;
+const HELP_POPUP_MIN_HEIGHT: u16 = 24;
+
+const fn fits_help_popup(size: Rect) -> bool {
+    size.width >= HELP_POPUP_MIN_WIDTH && size.height >= HELP_POPUP_MIN_HEIGHT
+}
+
+fn render_fullscreen_help(f: &mut Frame, app: &mut App) {
+    let help_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3), // Status bar
+                Constraint::Min(5),    // Help content
+                Constraint::Length(3), // Bottom status
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+
+    render_status_bar(f, app, help_chunks[0]);
+    app.areas.help = help_chunks[1];
+    crate::ui_help::render_clean_help(f, app, help_chunks[1]);
+    render_help_status(f, &app.theme, app.language(), help_chunks[2]);
+}
+
+/// A one-line box in the top right corner, red for errors
+fn render_toast(f: &mut Frame, theme: &Theme, message: &str) {
+    let size = f.area();
+    let width = u16::try_from(message.width() + 4)
+        .unwrap_or(u16::MAX)
+        .min(size.width);
+    if width < 5 || size.height < 3 {
+        return;
+    }
+    let area = Rect::new(size.width - width, 0, width, 3);
+    let color = if message.starts_with("Error") {
+        theme.danger
+    } else {
+        theme.staged
+    };
+    f.render_widget(Clear, area);
+    let toast = Paragraph::new(format!(" {message} "))
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color)),
+        );
+    f.render_widget(toast, area);
+}
+
+/// Dims the screen behind the popup and draws the help in a centered box
+fn render_help_popup(f: &mut Frame, app: &mut App) {
+    let size = f.area();
+    f.render_widget(Dim, size);
+    let popup = centered_rect(size, 70, 80);
+    f.render_widget(Clear, popup);
+    app.areas.help = popup;
+    crate::ui_help::render_clean_help(f, app, popup);
+}
+
+/// A `width_percent` x `height_percent` rectangle in the middle of `area`
+fn centered_rect(area: Rect, width_percent: u16, height_percent: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - height_percent) / 2),
+                Constraint::Percentage(height_percent),
+                Constraint::Percentage((100 - height_percent) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - width_percent) / 2),
+                Constraint::Percentage(width_percent),
+                Constraint::Percentage((100 - width_percent) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
+}
+
+/// Fades whatever was already drawn in the area, to push it behind a popup
+struct Dim;
+
+impl Widget for Dim {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, Style::default().add_modifier(Modifier::DIM));
+    }
 }
 
-fn render_help_status<B: Backend>(f: &mut Frame<B>, area: tui::layout::Rect) {
-    let status_text = vec![Spans::from(vec![
-        Span::styled("Navigation: ", Style::default().fg(Color::Yellow)),
+fn render_help_status(
+    f: &mut Frame,
+    theme: &Theme,
+    language: Language,
+    area: ratatui::layout::Rect,
+) {
+    let status_text = vec![Line::from(vec![
+        Span::styled(
+            Msg::HelpBarNavigation.text(language),
+            Style::default().fg(theme.heading),
+        ),
         Span::styled(
             "j/k",
             Style::default()
                 .add_modifier(Modifier::BOLD)
-                .fg(Color::Cyan),
+                .fg(theme.accent),
         ),
-        Span::raw(" scroll | "),
+        Span::raw(Msg::HelpBarScroll.text(language)),
         Span::styled(
             "h",
             Style::default()
                 .add_modifier(Modifier::BOLD)
-                .fg(Color::Green),
+                .fg(theme.staged),
         ),
         Span::raw("/"),
         Span::styled(
             "q",
-            Style::default().add_modifier(Modifier::BOLD).fg(Color::Red),
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(theme.danger),
         ),
         Span::raw("/"),
         Span::styled(
             "Esc",
-            Style::default().add_modifier(Modifier::BOLD).fg(Color::Red),
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(theme.danger),
         ),
-        Span::raw(" close | "),
-        Span::styled("Pretty Git UI v0.1.0", Style::default().fg(Color::Cyan)),
-        Span::raw(" - Help Mode"),
+        Span::raw(Msg::HelpBarClose.text(language)),
+        Span::styled("Pretty Git UI v0.1.0", Style::default().fg(theme.accent)),
+        Span::raw(Msg::HelpBarMode.text(language)),
     ])];
 
     let help_status = Paragraph::new(status_text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Green)),
+                .border_style(Style::default().fg(theme.staged)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(help_status, area);
 }
 
-fn render_preview_panel<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+/// ` [-U1 -w]` for the options that differ from git's defaults, or nothing
+fn format_diff_options(options: DiffOptions) -> String {
+    let args = options.args();
+    if args.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", args.join(" "))
+    }
+}
+
+const fn diff_view_label(view: DiffView) -> Msg {
+    match view {
+        DiffView::Unstaged => Msg::DiffUnstaged,
+        DiffView::Staged => Msg::DiffStaged,
+    }
+}
+
+fn render_preview_panel(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    let language = app.language();
     let file_path = app
         .get_current_file_path()
-        .unwrap_or_else(|| "No file selected".to_string());
+        .unwrap_or_else(|| Msg::NoFileSelected.text(language).to_string());
 
     if app.preview_content.is_empty() {
-        let empty_preview = Paragraph::new(
-            "ファイルを選択してください\n\n[v] でパネル切り替え",
-        )
-        .block(
-            Block::default()
-                .title("プレビュー")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Blue)),
-        )
-        .style(Style::default().fg(Color::DarkGray));
+        let empty_preview = Paragraph::new(Msg::SelectFile.text(language))
+            .block(
+                Block::default()
+                    .title(Msg::PreviewPanel.text(language))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border)),
+            )
+            .style(Style::default().fg(theme.muted));
         f.render_widget(empty_preview, area);
         return;
     }
 
-    let lines: Vec<&str> = app.preview_content.lines().collect();
     let start_line = app.preview_scroll as usize;
-    let visible_lines: Vec<Spans> = lines
-        .iter()
-        .skip(start_line)
-        .take((area.height.saturating_sub(2)) as usize)
+    let visible_lines: Vec<Line> = app
+        .preview_content
+        .window(start_line, area.height.saturating_sub(2) as usize)
         .enumerate()
         .map(|(i, line)| {
             let line_number = start_line + i + 1;
-            let line_style = if line.starts_with('+') {
-                Style::default().fg(Color::Green)
-            } else if line.starts_with('-') {
-                Style::default().fg(Color::Red)
-            } else if line.starts_with("@@") {
-                Style::default().fg(Color::Cyan)
-            } else {
-                Style::default()
-            };
+            let line_style = theme.diff_style(app.preview_content.kind(start_line + i));
 
-            // Truncate long lines to fit the panel (Unicode-safe)
+            // Truncate long lines to the columns left next to the line number
             let max_width = (area.width as usize).saturating_sub(8);
-            let display_line = if line.chars().count() > max_width {
-                let truncate_width = max_width.saturating_sub(3);
-                let truncated: String = line.chars().take(truncate_width).collect();
-                format!("{}...", truncated)
-            } else {
-                line.to_string()
-            };
+            let display_line = truncate_to_width(line, max_width).into_owned();
 
-            Spans::from(vec![
+            Line::from(vec![
                 Span::styled(
-                    format!("{:3} ", line_number),
-                    Style::default().fg(Color::DarkGray),
+                    format!("{line_number:3} "),
+                    Style::default().fg(theme.muted),
                 ),
                 Span::styled(display_line, line_style),
             ])
@@ -746,27 +2872,69 @@ fn render_preview_panel<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layo
     let preview = Paragraph::new(visible_lines)
         .block(
             Block::default()
-                .title(format!("差分: {}", file_path))
+                .title(format!(
+                    "{}{}{}{}",
+                    Msg::DiffPanelTitle.fill(
+                        language,
+                        &[&diff_view_label(app.diff_view).text(language), &file_path]
+                    ),
+                    format_diff_options(app.diff_options),
+                    format_line_range(
+                        app.preview_scroll,
+                        area,
+                        app.preview_content.line_count(),
+                        language
+                    ),
+                    if app.preview_has_more() {
+                        Msg::PreviewHasMore.fill(language, &[&app.preview_content.line_count()])
+                    } else {
+                        String::new()
+                    }
+                ))
                 .borders(Borders::ALL),
         )
         .wrap(Wrap { trim: false });
 
     f.render_widget(preview, area);
+    render_scrollbar(
+        f,
+        &theme,
+        area,
+        app.preview_scroll,
+        app.preview_content.line_count(),
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diff_cache::DiffKey;
+    use crate::worker::Task;
 
     #[test]
-    fn test_get_file_color() {
-        assert_eq!(get_file_color("M  file.txt"), Color::Green);
-        assert_eq!(get_file_color("A  file.txt"), Color::Green);
-        assert_eq!(get_file_color(" M file.txt"), Color::Red);
-        assert_eq!(get_file_color("?? file.txt"), Color::Green); // untracked is considered staged
-        assert_eq!(get_file_color("D  file.txt"), Color::Green);
-        assert_eq!(get_file_color(""), Color::White);
-        assert_eq!(get_file_color("M"), Color::White);
+    fn test_format_file_rows() {
+        assert_eq!(
+            format_section_header(Section::Staged, 2, false, Language::Ja),
+            "▼ ステージ済み (2)"
+        );
+        assert_eq!(
+            format_section_header(Section::Untracked, 1, true, Language::Ja),
+            "▶ 未追跡 (1)"
+        );
+        assert_eq!(
+            format_section_header(Section::Untracked, 1, true, Language::En),
+            "▶ Untracked (1)"
+        );
+
+        let partial = FileEntry::new("src/main.rs", 'A', 'M');
+        assert_eq!(
+            format_file_status(&partial, Section::Staged),
+            "  + [ADDED    ] src/main.rs"
+        );
+        assert_eq!(
+            format_file_status(&partial, Section::Unstaged),
+            "  Δ [MODIFIED ] src/main.rs"
+        );
     }
 
     #[test]
@@ -784,6 +2952,8 @@ mod tests {
         assert!(!untracked.chars().next().unwrap().is_whitespace());
     }
 
+    // Long only because it lists every mode
+    #[allow(clippy::too_many_lines)]
     #[test]
     fn test_input_mode_display() {
         let app = App::new();
@@ -807,9 +2977,374 @@ mod tests {
             InputMode::Help => {
                 assert!(matches!(app.input_mode, InputMode::Help));
             },
+            InputMode::Log => {
+                assert!(matches!(app.input_mode, InputMode::Log));
+            },
+            InputMode::LogSearch { .. } => {
+                assert!(matches!(app.input_mode, InputMode::LogSearch { .. }));
+            },
+            InputMode::FileVersion { .. } => {
+                assert!(matches!(app.input_mode, InputMode::FileVersion { .. }));
+            },
+            InputMode::Reflog => {
+                assert!(matches!(app.input_mode, InputMode::Reflog));
+            },
+            InputMode::Branches => {
+                assert!(matches!(app.input_mode, InputMode::Branches));
+            },
+            InputMode::BranchPrompt { .. } => {
+                assert!(matches!(app.input_mode, InputMode::BranchPrompt { .. }));
+            },
+            InputMode::MergePicker { .. } => {
+                assert!(matches!(app.input_mode, InputMode::MergePicker { .. }));
+            },
+            InputMode::PullPicker { .. } => {
+                assert!(matches!(app.input_mode, InputMode::PullPicker { .. }));
+            },
+            InputMode::Maintenance => assert_eq!(app.input_mode, InputMode::Maintenance),
+            InputMode::CommitDetails => assert_eq!(app.input_mode, InputMode::CommitDetails),
+            InputMode::Compare => assert_eq!(app.input_mode, InputMode::Compare),
+            InputMode::CompareInput { .. } => {
+                assert!(matches!(app.input_mode, InputMode::CompareInput { .. }));
+            },
+            InputMode::ArchivePrompt { .. } => {
+                assert!(matches!(app.input_mode, InputMode::ArchivePrompt { .. }));
+            },
+            InputMode::StashList => {
+                assert!(matches!(app.input_mode, InputMode::StashList));
+            },
+            InputMode::RepoSwitcher => {
+                assert!(matches!(app.input_mode, InputMode::RepoSwitcher));
+            },
+            InputMode::NoRepository { .. } => {
+                assert!(matches!(app.input_mode, InputMode::NoRepository { .. }));
+            },
+            InputMode::InitSetup { .. } => {
+                assert!(matches!(app.input_mode, InputMode::InitSetup { .. }));
+            },
+            InputMode::Conflict { .. } => {
+                assert!(matches!(app.input_mode, InputMode::Conflict { .. }));
+            },
+            InputMode::LineStage { .. } => {
+                assert!(matches!(app.input_mode, InputMode::LineStage { .. }));
+            },
+            InputMode::IgnoreChooser { .. } => {
+                assert!(matches!(app.input_mode, InputMode::IgnoreChooser { .. }));
+            },
+            InputMode::HookOutput { .. } => {
+                assert!(matches!(app.input_mode, InputMode::HookOutput { .. }));
+            },
+            InputMode::CommandLog => {
+                assert!(matches!(app.input_mode, InputMode::CommandLog));
+            },
+            InputMode::History => assert_eq!(app.input_mode, InputMode::History),
+            InputMode::Doctor => assert_eq!(app.input_mode, InputMode::Doctor),
+            InputMode::Config => assert_eq!(app.input_mode, InputMode::Config),
+            InputMode::ConfigEdit { .. } => {
+                assert!(matches!(app.input_mode, InputMode::ConfigEdit { .. }));
+            },
+            InputMode::IdentityPicker => {
+                assert_eq!(app.input_mode, InputMode::IdentityPicker);
+            },
+            InputMode::TrailerPicker => assert_eq!(app.input_mode, InputMode::TrailerPicker),
+            InputMode::PrefixPicker => {
+                assert!(matches!(app.input_mode, InputMode::PrefixPicker));
+            },
+            InputMode::PrefixScope { .. } => {
+                assert!(matches!(app.input_mode, InputMode::PrefixScope { .. }));
+            },
+            InputMode::TypedConfirm { .. } => {
+                assert!(matches!(app.input_mode, InputMode::TypedConfirm { .. }));
+            },
+            InputMode::Credential { .. } => {
+                assert!(matches!(app.input_mode, InputMode::Credential { .. }));
+            },
         }
     }
 
+    #[test]
+    fn test_format_diff_stat() {
+        assert_eq!(
+            format_diff_stat(DiffStat {
+                added: 12,
+                deleted: 0
+            }),
+            ("  +12".to_string(), "   -0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_conflict_lines() {
+        let hunks = vec![
+            ConflictHunk {
+                line: 3,
+                ours: vec!["a".to_string()],
+                base: Some(vec!["b".to_string()]),
+                theirs: vec!["c".to_string(), "d".to_string()],
+            },
+            ConflictHunk {
+                line: 10,
+                ..ConflictHunk::default()
+            },
+        ];
+        let lines = format_conflict_lines(&Theme::dark(), Language::Ja, &hunks);
+        // 3 headings + base heading + 4 body lines, blank separator, 3 headings
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0].spans[0].content, "@@ コンフリクト 1 (行 3)");
+        assert_eq!(lines[2].spans[0].content, "  a");
+        assert_eq!(lines[9].spans[0].content, "@@ コンフリクト 2 (行 10)");
+    }
+
+    #[test]
+    fn test_format_ignore_option() {
+        assert!(format_ignore_option("/a.log", Language::Ja).ends_with("このファイルのみ"));
+        assert!(
+            format_ignore_option("*.log", Language::Ja).ends_with("この拡張子のすべてのファイル")
+        );
+        assert!(format_ignore_option("/logs/", Language::Ja).ends_with("ディレクトリ全体"));
+    }
+
+    #[test]
+    fn test_bisect_banner_and_log_marker() {
+        let testing = BisectStatus::Testing {
+            current: "8a51d13".to_string(),
+            remaining: 3,
+            steps: 2,
+        };
+        assert!(format_bisect_banner(&testing, Language::Ja).contains("残り 3 リビジョン"));
+        assert!(format_bisect_banner(&testing, Language::En).contains("3 revisions left"));
+        assert!(format_bisect_banner(&BisectStatus::Waiting, Language::Ja)
+            .contains("マークしてください"));
+
+        assert_eq!(
+            bisect_marker(Some(&testing), "* 8a51d13 c4"),
+            Some(Msg::BisectMarkerTesting)
+        );
+        assert_eq!(bisect_marker(Some(&testing), "* 1234567 c1"), None);
+        assert_eq!(bisect_marker(None, "* 8a51d13 c4"), None);
+        let found = BisectStatus::Found {
+            first_bad: "ebcc856".to_string(),
+        };
+        assert_eq!(
+            bisect_marker(Some(&found), "* ebcc856 (HEAD) c2"),
+            Some(Msg::BisectMarkerFound)
+        );
+    }
+
+    #[test]
+    fn test_format_reflog_entry() {
+        let entry = ReflogEntry {
+            hash: "1a2b3c4".to_string(),
+            selector: "HEAD@{1}".to_string(),
+            message: "commit: Add feature".to_string(),
+        };
+        let spans = format_reflog_entry(&Theme::dark(), &entry);
+        let text: String = spans
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(text, "1a2b3c4 HEAD@{1} commit: Add feature");
+    }
+
+    #[test]
+    fn test_format_patch_line() {
+        assert_eq!(format_patch_line("+new", true, true), "[x] +new");
+        assert_eq!(format_patch_line("-old", true, false), "[ ] -old");
+        assert_eq!(format_patch_line(" same", false, false), "     same");
+    }
+
+    #[test]
+    fn test_format_status_summary() {
+        let text = |summary| {
+            format_status_summary(&Theme::dark(), Language::En, summary)
+                .spans
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<String>()
+        };
+        let summary = StatusSummary {
+            staged: 3,
+            modified: 5,
+            untracked: 2,
+            conflicted: 1,
+        };
+        assert_eq!(
+            text(summary),
+            "3 staged · 5 modified · 2 untracked · 1 conflict"
+        );
+        assert_eq!(
+            text(StatusSummary {
+                modified: 1,
+                ..StatusSummary::default()
+            }),
+            "1 modified"
+        );
+        assert_eq!(text(StatusSummary::default()), "working tree clean");
+        let japanese = format_status_summary(&Theme::dark(), Language::Ja, summary);
+        assert_eq!(japanese.spans[0].content, "3 ステージ済み");
+    }
+
+    #[test]
+    fn test_format_status_line() {
+        use std::time::Duration;
+
+        let theme = Theme::dark();
+        let start = Instant::now();
+        let mut status = StatusLine::default();
+        let text = |status: &StatusLine, now| {
+            format_status_line(&theme, Language::En, status, now)
+                .spans
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<String>()
+        };
+        assert_eq!(text(&status, start), "> ");
+
+        status.push(Severity::Error, "Error: rejected".into(), start);
+        status.push(Severity::Success, "✓ Staged 1 file(s)".into(), start);
+        let line = format_status_line(&theme, Language::En, &status, start);
+        assert_eq!(line.spans[0].style.fg, Some(theme.added));
+        assert_eq!(
+            text(&status, start + Duration::from_secs(2)),
+            "> ✓ Staged 1 file(s) (+1 more errors)"
+        );
+
+        status.dismiss();
+        let later = start + Duration::from_secs(150);
+        let line = format_status_line(&theme, Language::Ja, &status, later);
+        assert_eq!(line.spans[0].style.fg, Some(theme.danger));
+        assert_eq!(line.spans[1].content, " · 2m前");
+    }
+
+    #[test]
+    fn test_format_running_tasks() {
+        let mut worker = Worker::new();
+        assert_eq!(format_running_tasks(&worker, 0, Language::Ja), None);
+
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let key = DiffKey {
+            path: "a".to_string(),
+            view: DiffView::Unstaged,
+            options: DiffOptions::default(),
+            max_lines: 1000,
+        };
+        worker.spawn(Task::Diff(key.clone()), || Ok(String::new()));
+        worker.spawn(Task::Prefetch(key), || Ok(String::new()));
+        worker.spawn(Task::StageAll, move || {
+            wait.recv().unwrap();
+            Ok(String::new())
+        });
+        assert_eq!(
+            format_running_tasks(&worker, 1, Language::Ja).as_deref(),
+            Some("⠙ 実行中: stage all")
+        );
+        release.send(()).unwrap();
+    }
+
+    #[test]
+    fn test_format_upstream() {
+        let upstream = |ahead, behind| UpstreamStatus {
+            name: "origin/main".to_string(),
+            ahead,
+            behind,
+        };
+        assert_eq!(format_upstream(None), "");
+        assert_eq!(format_upstream(Some(&upstream(0, 0))), " ✓ origin/main");
+        assert_eq!(format_upstream(Some(&upstream(2, 0))), " ↑2 origin/main");
+        assert_eq!(format_upstream(Some(&upstream(2, 1))), " ↑2 ↓1 origin/main");
+    }
+
+    #[test]
+    fn test_format_diff_options() {
+        assert_eq!(format_diff_options(DiffOptions::default()), "");
+        let options = DiffOptions {
+            context: Some(1),
+            ignore_whitespace: true,
+            ignore_blank_lines: false,
+        };
+        assert_eq!(format_diff_options(options), " [-U1 -w]");
+    }
+
+    #[test]
+    fn test_format_repo_stats() {
+        let now = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(100_000);
+        let mut stats = RepoStats {
+            loose_objects: 3,
+            loose_size: 512,
+            packed_objects: 10,
+            packs: 1,
+            pack_size: 2048,
+            garbage: 0,
+            last_gc: None,
+        };
+        assert_eq!(
+            format_repo_stats(&stats, now, Language::En),
+            vec![
+                "Size:            2.5 KiB (2560 bytes)",
+                "Loose objects:   3 (512 bytes)",
+                "Packed objects:  10 in 1 pack(s) (2.0 KiB (2048 bytes))",
+                "Last gc:         never",
+            ]
+        );
+        stats.garbage = 2;
+        stats.last_gc = Some(now - std::time::Duration::from_secs(7200));
+        let lines = format_repo_stats(&stats, now, Language::En);
+        assert_eq!(lines[3], "Garbage files:   2");
+        assert_eq!(lines[4], "Last gc:         2h ago");
+        assert_eq!(format_age(std::time::Duration::from_secs(59)), "59s");
+        assert_eq!(format_age(std::time::Duration::from_secs(600)), "10m");
+        assert_eq!(format_age(std::time::Duration::from_secs(3 * 86400)), "3d");
+    }
+
+    #[test]
+    fn test_format_file_version_title() {
+        assert_eq!(
+            format_file_version_title("src/a.rs", FileVersion::Index, Language::Ja),
+            "src/a.rs  HEAD | [インデックス] | 作業ツリー (Tab で切替)"
+        );
+        assert_eq!(
+            format_file_version_title("src/a.rs", FileVersion::Worktree, Language::En),
+            "src/a.rs  HEAD | index | [working tree] (Tab to switch)"
+        );
+    }
+
+    #[test]
+    fn test_format_line_range() {
+        let area = Rect::new(0, 0, 80, 12);
+        assert_eq!(format_line_range(0, area, 120, Language::Ja), " 1–10/120行");
+        assert_eq!(
+            format_line_range(115, area, 120, Language::Ja),
+            " 116–120/120行"
+        );
+        assert_eq!(format_line_range(0, area, 3, Language::Ja), " 1–3/3行");
+        assert_eq!(format_line_range(0, area, 0, Language::Ja), "");
+        assert_eq!(format_line_range(0, area, 3, Language::En), " lines 1–3/3");
+    }
+
+    #[test]
+    fn test_format_log_line() {
+        let line = "| * abc1234 (HEAD -> main) Add log view";
+        let spans = format_log_line(&Theme::dark(), line, false);
+        assert_eq!(spans.spans.len(), 3);
+        assert_eq!(spans.spans[0].content, "| * ");
+        assert_eq!(spans.spans[1].content, "abc1234");
+        assert_eq!(spans.spans[2].content, " (HEAD -> main) Add log view");
+        assert!(!spans.spans[1]
+            .style
+            .add_modifier
+            .contains(Modifier::REVERSED));
+
+        let matched = format_log_line(&Theme::dark(), line, true);
+        assert!(matched.spans[1]
+            .style
+            .add_modifier
+            .contains(Modifier::REVERSED));
+
+        let graph_only = format_log_line(&Theme::dark(), "|\\", true);
+        assert_eq!(graph_only.spans.len(), 1);
+    }
+
     #[test]
     fn test_cursor_position_calculation() {
         let message = "test commit message";
@@ -818,6 +3353,20 @@ mod tests {
         assert_eq!(actual_cursor_x, expected_cursor_x);
     }
 
+    #[test]
+    fn test_wide_characters_use_two_columns() {
+        let area = Rect::new(2, 0, 20, 3);
+        assert_eq!(input_cursor_x(area, "fix"), 6);
+        assert_eq!(input_cursor_x(area, "修正"), 7);
+        assert_eq!(input_cursor_x(area, &"長".repeat(30)), 20);
+
+        assert_eq!(truncate_to_width("short", 10), "short");
+        assert_eq!(truncate_to_width("日本語のテキスト", 10), "日本語...");
+        assert_eq!(truncate_to_width("🎉 done and more", 8), "🎉 do...");
+        assert_eq!(pad_to_width("機能", 6), "機能  ");
+        assert_eq!(pad_to_width("feature", 4), "feature");
+    }
+
     #[test]
     fn test_layout_constraints() {
         let constraints = [
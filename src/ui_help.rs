@@ -1,144 +1,727 @@
-use tui::{
-    backend::Backend,
+use crate::app::App;
+use crate::strings::{Language, Msg};
+use crate::theme::Theme;
+use ratatui::{
     style::{Color, Modifier, Style},
-    text::{Span, Spans},
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
 };
-use crate::app::App;
 
-pub fn render_clean_help<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
-    let help_text = vec![
-        Spans::from(vec![
-            Span::styled("Pretty Git UI - ヘルプ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan))
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        
-        // Navigation
-        Spans::from(vec![
-            Span::styled("ナビゲーション:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow))
-        ]),
-        Spans::from(vec![
-            Span::styled("  j/k ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("または "),
-            Span::styled("↓/↑  ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("ファイル移動")
-        ]),
-        Spans::from(vec![
-            Span::styled("  h     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("ヘルプ表示")
-        ]),
-        Spans::from(vec![
-            Span::styled("  q     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Red)),
-            Span::raw("アプリケーション終了")
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        
-        // File Operations
-        Spans::from(vec![
-            Span::styled("ファイル操作:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow))
-        ]),
-        Spans::from(vec![
-            Span::styled("  s     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("選択ファイルをステージ/アンステージ")
-        ]),
-        Spans::from(vec![
-            Span::styled("  a     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("すべてのファイルをステージ/アンステージ")
-        ]),
-        Spans::from(vec![
-            Span::styled("  r     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("ファイルリスト更新")
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        
-        // Git Operations
-        Spans::from(vec![
-            Span::styled("Git操作:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow))
-        ]),
-        Spans::from(vec![
-            Span::styled("  c     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("コミットメッセージ入力")
-        ]),
-        Spans::from(vec![
-            Span::styled("  t     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("スタッシュメッセージ入力")
-        ]),
-        Spans::from(vec![
-            Span::styled("  l     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("スタッシュ一覧表示")
-        ]),
-        Spans::from(vec![
-            Span::styled("  p     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("最新スタッシュ適用")
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        
-        // Preview
-        Spans::from(vec![
-            Span::styled("プレビュー:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow))
-        ]),
-        Spans::from(vec![
-            Span::styled("  v     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("プレビューパネル切り替え")
-        ]),
-        Spans::from(vec![
-            Span::styled("  d     ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("フルスクリーン差分表示")
-        ]),
-        Spans::from(vec![
-            Span::styled("  Shift+j/k ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("プレビューパネルスクロール")
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        
-        // Input Modes
-        Spans::from(vec![
-            Span::styled("入力モード:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow))
-        ]),
-        Spans::from(vec![
-            Span::styled("  Enter ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-            Span::raw("送信 (コミット/スタッシュモード)")
-        ]),
-        Spans::from(vec![
-            Span::styled("  Esc   ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Red)),
-            Span::raw("キャンセル")
-        ]),
-        Spans::from(vec![
-            Span::styled("  y/n   ", Style::default().add_modifier(Modifier::BOLD).fg(Color::Magenta)),
-            Span::raw("確認/拒否 (確認モード)")
-        ]),
-        Spans::from(vec![Span::raw("")]),
-        
-        // File Status
-        Spans::from(vec![
-            Span::styled("ファイル状態:", Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow))
-        ]),
-        Spans::from(vec![
-            Span::styled("  ", Style::default()),
-            Span::styled("緑色", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-            Span::raw("  ステージ済み (コミット準備完了)")
-        ]),
-        Spans::from(vec![
-            Span::styled("  ", Style::default()),
-            Span::styled("赤色", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
-            Span::raw("  変更済み (未ステージ)")
-        ]),
+/// キーの種類。表示色はテーマから決まる
+#[derive(Clone, Copy)]
+enum KeyKind {
+    Normal,
+    Danger,
+    Confirm,
+}
+
+impl KeyKind {
+    const fn color(self, theme: &Theme) -> Color {
+        match self {
+            Self::Normal => theme.accent,
+            Self::Danger => theme.danger,
+            Self::Confirm => theme.confirm,
+        }
+    }
+}
+
+/// ヘルプの1セクション: 見出しと (キー, 説明, キーの種類) の一覧
+struct HelpSection {
+    title: &'static str,
+    entries: &'static [(&'static str, &'static str, KeyKind)],
+}
+
+const HELP_SECTIONS_JA: &[HelpSection] = &[
+    HelpSection {
+        title: "ナビゲーション:",
+        entries: &[
+            ("j/k または ↓/↑", "ファイル移動", KeyKind::Normal),
+            ("h", "ヘルプ表示", KeyKind::Normal),
+            ("@", "実行したgitコマンドと終了コード・stderr", KeyKind::Normal),
+            ("H", "このセッションの操作履歴と結果・時刻", KeyKind::Normal),
+            (
+                "Esc",
+                "ステータスメッセージを消す (エラーは消すまで残る)",
+                KeyKind::Normal,
+            ),
+            ("M", "メンテナンス (サイズ、gc / prune / maintenance run)", KeyKind::Normal),
+            (
+                "G",
+                "git 設定 (スコープと出所、user.name などを編集)",
+                KeyKind::Normal,
+            ),
+            ("q", "アプリケーション終了", KeyKind::Danger),
+        ],
+    },
+    HelpSection {
+        title: "ファイル操作:",
+        entries: &[
+            (
+                "s",
+                "選択ファイルをステージ/アンステージ (見出しではセクション全体)",
+                KeyKind::Normal,
+            ),
+            (
+                "e",
+                "行単位でステージ/アンステージ (Space:選択 a:ハンク Enter:適用)",
+                KeyKind::Normal,
+            ),
+            ("z / Enter", "セクションの折りたたみ/展開", KeyKind::Normal),
+            (
+                "S",
+                "並べ替え: パス / ディレクトリ / 状態 / 更新日時 (リポジトリごとに保存)",
+                KeyKind::Normal,
+            ),
+            (
+                "f",
+                "ツリー表示/一覧表示の切り替え (z でディレクトリを折りたたみ)",
+                KeyKind::Normal,
+            ),
+            (
+                "Enter",
+                "未追跡ディレクトリをファイルごとに展開/折りたたみ",
+                KeyKind::Normal,
+            ),
+            (
+                "x/D",
+                "選択ファイルの変更を破棄 (確認あり)",
+                KeyKind::Danger,
+            ),
+            (
+                "i",
+                "未追跡ファイルを .gitignore に追加 (ファイル/拡張子/ディレクトリ)",
+                KeyKind::Normal,
+            ),
+            (
+                "N",
+                "未追跡ファイルを git add -N (e で行単位にステージ可能に、再度押すと元に戻す)",
+                KeyKind::Normal,
+            ),
+            (
+                "a",
+                "すべてのファイルをステージ/アンステージ",
+                KeyKind::Normal,
+            ),
+            ("r", "ファイルリスト更新", KeyKind::Normal),
+        ],
+    },
+    HelpSection {
+        title: "Git操作:",
+        entries: &[
+            ("c", "コミットメッセージ入力", KeyKind::Normal),
+            (
+                "t",
+                "スタッシュメッセージ入力 (Ctrl+K: --keep-index, Tab: --include-untracked)",
+                KeyKind::Normal,
+            ),
+            (
+                "T",
+                "選択したファイルだけをスタッシュ (ヘッダー上ではセクション全体)",
+                KeyKind::Normal,
+            ),
+            (
+                "l",
+                "スタッシュ一覧 (a:適用 p:pop x:削除 Enter:差分)",
+                KeyKind::Normal,
+            ),
+            ("p", "最新スタッシュ適用", KeyKind::Normal),
+            (
+                "L",
+                "コミット履歴表示 (Enter:詳細 d:差分 /:検索 n/N:次/前の一致 PgUp/PgDn:ページ S/M/X:リセット f:fixup A:autosquash c:HEADと比較 Z:アーカイブ)",
+                KeyKind::Normal,
+            ),
+            (
+                "R",
+                "リフログ表示 (Enter:差分 c:チェックアウト x:reset --hard)",
+                KeyKind::Normal,
+            ),
+            (
+                "w",
+                "ブランチ一覧 (m:マージ R:名前変更 u:上流設定 c:比較 Z:アーカイブ x:削除 f:fetch --prune X:gone一括削除)",
+                KeyKind::Normal,
+            ),
+            (
+                "=",
+                "2つの ref を比較 (A..B、Enter:差分 D:全体)",
+                KeyKind::Normal,
+            ),
+            (
+                "Z",
+                "HEAD を zip / tar にアーカイブ出力 (git archive)",
+                KeyKind::Normal,
+            ),
+            ("o", "最近のリポジトリに切り替え", KeyKind::Normal),
+            (
+                "P",
+                "プッシュ (確認あり、upstream がなければ --set-upstream)",
+                KeyKind::Normal,
+            ),
+            (
+                "!",
+                "強制プッシュ --force-with-lease (ブランチ名の入力で確認)",
+                KeyKind::Danger,
+            ),
+            ("U", "プル (リベース / マージ / fast-forward のみ を選択)", KeyKind::Normal),
+            ("F", "フェッチ", KeyKind::Normal),
+            (
+                "O",
+                "origin の GitHub/GitLab/Bitbucket をブラウザで開く (ファイル、履歴ではコミット)",
+                KeyKind::Normal,
+            ),
+        ],
+    },
+    HelpSection {
+        title: "コンフリクト:",
+        entries: &[
+            (
+                "Enter",
+                "コンフリクトを表示 (競合ファイル上)",
+                KeyKind::Normal,
+            ),
+            ("o / t", "ours / theirs を採用 (表示中)", KeyKind::Normal),
+            ("e", "$EDITOR で編集 (表示中)", KeyKind::Normal),
+            ("A", "マージ中止 (確認あり)", KeyKind::Danger),
+            ("C", "マージ続行", KeyKind::Normal),
+        ],
+    },
+    HelpSection {
+        title: "bisect:",
+        entries: &[
+            ("B", "bisect 開始/終了 (終了は確認あり)", KeyKind::Normal),
+            (
+                "g / b / n",
+                "チェックアウト中のコミットを good / bad / skip",
+                KeyKind::Normal,
+            ),
+            (
+                "g / b",
+                "選択コミットを good / bad にマーク (コミット履歴)",
+                KeyKind::Normal,
+            ),
+            (
+                "+ / -",
+                "detached HEAD: ここにブランチを作成 / 元のブランチに戻る",
+                KeyKind::Normal,
+            ),
+        ],
+    },
+    HelpSection {
+        title: "プレビュー:",
+        entries: &[
+            ("v", "プレビューパネル切り替え", KeyKind::Normal),
+            (
+                "| / < >",
+                "プレビューの位置 (右/下/非表示) とサイズを変更 (設定に保存)",
+                KeyKind::Normal,
+            ),
+            (
+                "d",
+                "フルスクリーン差分表示 (/: 検索, n/N: 次/前の一致, x: バイナリの16進ダンプ, y: コピー, PgUp/PgDn g/G: ページ/先頭/末尾)",
+                KeyKind::Normal,
+            ),
+            (
+                "V",
+                "ファイル全体を表示 (Tab/1/2/3: HEAD・インデックス・作業ツリー)",
+                KeyKind::Normal,
+            ),
+            (
+                "Tab",
+                "未ステージ/ステージ済みの差分を切り替え",
+                KeyKind::Normal,
+            ),
+            ("Shift+j/k", "プレビューパネルスクロール", KeyKind::Normal),
+            ("{ / }", "差分の前後の行数を減らす / 増やす (-U<n>)", KeyKind::Normal),
+            (
+                "W / E",
+                "空白の変更 (-w) / 空行の追加・削除を無視 (切り替え)",
+                KeyKind::Normal,
+            ),
+            (
+                "y / Y",
+                "選択ファイルのパス / 表示中の差分をクリップボードにコピー (履歴では y でハッシュ)",
+                KeyKind::Normal,
+            ),
+        ],
+    },
+    HelpSection {
+        title: "入力モード:",
+        entries: &[
+            ("Enter", "送信 (コミット/スタッシュモード)", KeyKind::Normal),
+            (
+                "Ctrl+S",
+                "コミット署名の切り替え (コミットモード)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+N",
+                "フックを省略してコミット (--no-verify) の切り替え (コミットモード)",
+                KeyKind::Danger,
+            ),
+            (
+                "Ctrl+A",
+                "変更された追跡ファイルを自動でステージ (commit -a) の切り替え (コミットモード)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+O",
+                "メッセージの問題を無視してコミット (コミットモード)",
+                KeyKind::Danger,
+            ),
+            (
+                "Ctrl+T",
+                "選択したステージ済みファイルをコミットに含める/外す (コミットモード)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+E",
+                "コミットメッセージを $EDITOR で編集 (コミットモード)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+P",
+                "コミットする名前とメールアドレスを選択 (コミットモード)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+D",
+                "Signed-off-by の付与を切り替え (コミットモード)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+R",
+                "最近の作者を Co-authored-by などのトレーラーとして追加 (コミットモード)",
+                KeyKind::Normal,
+            ),
+            (
+                "Tab",
+                "コミット種別・gitmojiを選んで先頭に挿入 (コミットモード)",
+                KeyKind::Normal,
+            ),
+            (
+                "{ticket}",
+                "ブランチ名のチケット番号に展開 ({branch}・スニペットも可、コミットモード)",
+                KeyKind::Normal,
+            ),
+            (
+                "Enter",
+                "パスワード・パスフレーズを git に渡す (認証の入力、Escで中止)",
+                KeyKind::Normal,
+            ),
+            ("←/→ Home/End", "カーソル移動 (貼り付けも可)", KeyKind::Normal),
+            (
+                "Ctrl+W",
+                "カーソル前の単語を削除 (Alt+Backspaceも可)",
+                KeyKind::Normal,
+            ),
+            ("Ctrl+U", "カーソルより前をすべて削除", KeyKind::Normal),
+            ("Esc", "キャンセル", KeyKind::Danger),
+            (
+                "n",
+                "フックに拒否されたコミットを --no-verify で再試行 (フック出力)",
+                KeyKind::Danger,
+            ),
+            ("y/n", "確認/拒否 (確認モード)", KeyKind::Confirm),
+        ],
+    },
+];
+
+const HELP_SECTIONS_EN: &[HelpSection] = &[
+    HelpSection {
+        title: "Navigation:",
+        entries: &[
+            ("j/k or ↓/↑", "Move between files", KeyKind::Normal),
+            ("h", "Show this help", KeyKind::Normal),
+            (
+                "@",
+                "Git commands run, with exit status and stderr",
+                KeyKind::Normal,
+            ),
+            (
+                "H",
+                "What was done this session, with outcome and time",
+                KeyKind::Normal,
+            ),
+            (
+                "Esc",
+                "Dismiss the status message (errors stay until dismissed)",
+                KeyKind::Normal,
+            ),
+            (
+                "M",
+                "Maintenance: repo size, gc / prune / maintenance run",
+                KeyKind::Normal,
+            ),
+            (
+                "I",
+                "Diagnostics: git, config, terminal, git-lfs and gpg (y copies)",
+                KeyKind::Normal,
+            ),
+            (
+                "G",
+                "Git config with scopes and origins; edit user.name and others",
+                KeyKind::Normal,
+            ),
+            ("q", "Quit", KeyKind::Danger),
+        ],
+    },
+    HelpSection {
+        title: "Files:",
+        entries: &[
+            (
+                "s",
+                "Stage/unstage the selected file (the whole section on a header)",
+                KeyKind::Normal,
+            ),
+            (
+                "e",
+                "Stage/unstage single lines (Space:select a:hunk Enter:apply)",
+                KeyKind::Normal,
+            ),
+            ("z / Enter", "Fold/unfold a section", KeyKind::Normal),
+            (
+                "S",
+                "Sort by path / directory / status / modification time (saved per repository)",
+                KeyKind::Normal,
+            ),
+            (
+                "f",
+                "Show files as a directory tree or a flat list (z folds a directory)",
+                KeyKind::Normal,
+            ),
+            (
+                "Enter",
+                "List an untracked directory file by file / fold it",
+                KeyKind::Normal,
+            ),
+            (
+                "x/D",
+                "Discard the changes of the selected file (asks first)",
+                KeyKind::Danger,
+            ),
+            (
+                "i",
+                "Add an untracked file to .gitignore (file/extension/directory)",
+                KeyKind::Normal,
+            ),
+            (
+                "N",
+                "git add -N an untracked file so e can stage its lines (again to undo)",
+                KeyKind::Normal,
+            ),
+            ("a", "Stage/unstage all files", KeyKind::Normal),
+            ("r", "Refresh the file list", KeyKind::Normal),
+        ],
+    },
+    HelpSection {
+        title: "Git:",
+        entries: &[
+            ("c", "Write a commit message", KeyKind::Normal),
+            (
+                "t",
+                "Write a stash message (Ctrl+K: --keep-index, Tab: --include-untracked)",
+                KeyKind::Normal,
+            ),
+            (
+                "T",
+                "Stash only the selected file (the whole section on a header)",
+                KeyKind::Normal,
+            ),
+            (
+                "l",
+                "Stash list (a:apply p:pop x:drop Enter:diff)",
+                KeyKind::Normal,
+            ),
+            ("p", "Apply the latest stash", KeyKind::Normal),
+            (
+                "L",
+                "Commit log (Enter:details d:diff /:search n/N:next/prev match PgUp/PgDn:page S/M/X:reset f:fixup A:autosquash c:compare with HEAD Z:archive)",
+                KeyKind::Normal,
+            ),
+            (
+                "R",
+                "Reflog (Enter:diff c:checkout x:reset --hard)",
+                KeyKind::Normal,
+            ),
+            (
+                "w",
+                "Branches (m:merge R:rename u:upstream c:compare Z:archive x:delete f:fetch --prune X:delete gone)",
+                KeyKind::Normal,
+            ),
+            (
+                "=",
+                "Compare two refs (A..B, Enter:diff D:whole diff)",
+                KeyKind::Normal,
+            ),
+            (
+                "Z",
+                "Export HEAD as a zip / tar archive (git archive)",
+                KeyKind::Normal,
+            ),
+            ("o", "Switch to a recent repository", KeyKind::Normal),
+            (
+                "P",
+                "Push (asks first; --set-upstream without an upstream)",
+                KeyKind::Normal,
+            ),
+            (
+                "!",
+                "Force-push with --force-with-lease (type the branch name)",
+                KeyKind::Danger,
+            ),
+            ("U", "Pull (pick rebase, merge or fast-forward only)", KeyKind::Normal),
+            ("F", "Fetch", KeyKind::Normal),
+            (
+                "O",
+                "Open on GitHub/GitLab/Bitbucket (the file; the commit in the log)",
+                KeyKind::Normal,
+            ),
+        ],
+    },
+    HelpSection {
+        title: "Conflicts:",
+        entries: &[
+            (
+                "Enter",
+                "Show the conflicts (on a conflicted file)",
+                KeyKind::Normal,
+            ),
+            ("o / t", "Take ours / theirs (in the view)", KeyKind::Normal),
+            ("e", "Edit in $EDITOR (in the view)", KeyKind::Normal),
+            ("A", "Abort the merge (asks first)", KeyKind::Danger),
+            ("C", "Continue the merge", KeyKind::Normal),
+        ],
+    },
+    HelpSection {
+        title: "bisect:",
+        entries: &[
+            ("B", "Start/end bisect (ending asks first)", KeyKind::Normal),
+            (
+                "g / b / n",
+                "Mark the checked-out commit good / bad / skip",
+                KeyKind::Normal,
+            ),
+            (
+                "g / b",
+                "Mark the selected commit good / bad (commit log)",
+                KeyKind::Normal,
+            ),
+            (
+                "+ / -",
+                "Detached HEAD: create a branch at it / switch back to the previous branch",
+                KeyKind::Normal,
+            ),
+        ],
+    },
+    HelpSection {
+        title: "Preview:",
+        entries: &[
+            ("v", "Toggle the preview panel", KeyKind::Normal),
+            (
+                "| / < >",
+                "Move (right/bottom/hidden) and resize the preview (saved to the config)",
+                KeyKind::Normal,
+            ),
+            (
+                "d",
+                "Fullscreen diff (/: search, n/N: next/prev match, x: hex dump of a binary, y: copy, PgUp/PgDn g/G: page/top/bottom)",
+                KeyKind::Normal,
+            ),
+            (
+                "V",
+                "Whole file (Tab/1/2/3: HEAD, index, working tree)",
+                KeyKind::Normal,
+            ),
+            (
+                "Tab",
+                "Switch between the unstaged and staged diff",
+                KeyKind::Normal,
+            ),
+            ("Shift+j/k", "Scroll the preview panel", KeyKind::Normal),
+            ("{ / }", "Fewer / more context lines (-U<n>)", KeyKind::Normal),
+            (
+                "W / E",
+                "Toggle ignoring whitespace (-w) / blank lines",
+                KeyKind::Normal,
+            ),
+            (
+                "y / Y",
+                "Copy the file path / the shown diff (y in the log: the commit hash)",
+                KeyKind::Normal,
+            ),
+        ],
+    },
+    HelpSection {
+        title: "Input:",
+        entries: &[
+            ("Enter", "Submit (commit/stash)", KeyKind::Normal),
+            ("Ctrl+S", "Toggle commit signing (commit)", KeyKind::Normal),
+            (
+                "Ctrl+N",
+                "Toggle skipping the hooks, --no-verify (commit)",
+                KeyKind::Danger,
+            ),
+            (
+                "Ctrl+A",
+                "Toggle staging modified tracked files, commit -a (commit)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+O",
+                "Commit despite message problems (commit)",
+                KeyKind::Danger,
+            ),
+            (
+                "Ctrl+T",
+                "Include/leave out the selected staged file (commit)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+E",
+                "Edit the commit message in $EDITOR (commit)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+P",
+                "Pick the name and email to commit as (commit)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+D",
+                "Toggle the Signed-off-by trailer (commit)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+R",
+                "Add recent authors as Co-authored-by or other trailers (commit)",
+                KeyKind::Normal,
+            ),
+            (
+                "Tab",
+                "Insert a commit type or gitmoji at the start (commit)",
+                KeyKind::Normal,
+            ),
+            (
+                "{ticket}",
+                "Expands to the branch's ticket id ({branch} and snippets too, commit)",
+                KeyKind::Normal,
+            ),
+            (
+                "Enter",
+                "Send a password or passphrase to git (credential prompt, Esc cancels)",
+                KeyKind::Normal,
+            ),
+            (
+                "←/→ Home/End",
+                "Move the cursor (pasting works too)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+W",
+                "Delete the word before the cursor (also Alt+Backspace)",
+                KeyKind::Normal,
+            ),
+            (
+                "Ctrl+U",
+                "Delete everything before the cursor",
+                KeyKind::Normal,
+            ),
+            ("Esc", "Cancel", KeyKind::Danger),
+            (
+                "n",
+                "Retry a commit rejected by a hook with --no-verify (hook output)",
+                KeyKind::Danger,
+            ),
+            ("y/n", "Confirm/decline (confirmation)", KeyKind::Confirm),
+        ],
+    },
+];
+
+/// Number of lines the help scrolls through
+pub fn help_line_count(theme: &Theme, language: Language) -> usize {
+    help_lines(theme, language).len()
+}
+
+fn help_lines(theme: &Theme, language: Language) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(vec![Span::styled(
+            Msg::HelpHeading.text(language),
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(theme.accent),
+        )]),
+        Line::from(vec![Span::raw("")]),
     ];
 
+    for section in language.pick(HELP_SECTIONS_JA, HELP_SECTIONS_EN) {
+        lines.push(Line::from(vec![Span::styled(
+            section.title,
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(theme.heading),
+        )]));
+        for (key, description, kind) in section.entries {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {key:<10} "),
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .fg(kind.color(theme)),
+                ),
+                Span::raw(*description),
+            ]));
+        }
+        lines.push(Line::from(vec![Span::raw("")]));
+    }
+
+    // File Status
+    lines.push(Line::from(vec![Span::styled(
+        Msg::HelpFileStatus.text(language),
+        Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(theme.heading),
+    )]));
+    lines.push(Line::from(vec![
+        Span::styled("  ", Style::default()),
+        Span::styled(
+            Msg::HelpGreen.text(language),
+            Style::default()
+                .fg(theme.staged)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(Msg::HelpGreenMeaning.text(language)),
+    ]));
+    lines.push(Line::from(vec![
+        Span::styled("  ", Style::default()),
+        Span::styled(
+            Msg::HelpRed.text(language),
+            Style::default()
+                .fg(theme.modified)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(Msg::HelpRedMeaning.text(language)),
+    ]));
+
+    lines
+}
+
+pub fn render_clean_help(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let language = app.language();
+    let help_text = help_lines(&app.theme, language);
+
     let total_lines = help_text.len();
     let visible_lines = (area.height.saturating_sub(2)) as usize;
     let max_scroll = total_lines.saturating_sub(visible_lines);
-    
+
     // Apply scroll offset
-    let visible_help_text: Vec<Spans> = help_text
+    let visible_help_text: Vec<Line> = help_text
         .into_iter()
         .skip(app.help_scroll as usize)
         .take(visible_lines)
         .collect();
 
     let scroll_info = if total_lines > visible_lines {
-        format!(" (j/kでスクロール {}/{})", app.help_scroll + 1, max_scroll + 1)
+        Msg::HelpScroll.fill(language, &[&(app.help_scroll + 1), &(max_scroll + 1)])
     } else {
         String::new()
     };
@@ -146,11 +729,11 @@ pub fn render_clean_help<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::lay
     let help = Paragraph::new(visible_help_text)
         .block(
             Block::default()
-                .title(format!("ヘルプ{}", scroll_info))
+                .title(format!("{}{scroll_info}", Msg::Help.text(language)))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .border_style(Style::default().fg(app.theme.accent)),
         )
         .wrap(Wrap { trim: false });
 
     f.render_widget(help, area);
-}
\ No newline at end of file
+}
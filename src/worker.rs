@@ -0,0 +1,246 @@
+use crate::diff_cache::DiffKey;
+use crate::error::GitError;
+use crate::git::MaintenanceTask;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Git operations that run off the render thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Task {
+    Push,
+    Pull,
+    Fetch,
+    /// `git add .` / `git reset`, which can take a while in large working trees
+    StageAll,
+    /// Loads the diff shown in the preview panel
+    Diff(DiffKey),
+    /// Loads the diff of a file near the selected one into the cache ahead of time
+    Prefetch(DiffKey),
+    /// `git push --delete` of a remote-tracking branch such as `origin/feature`
+    DeleteRemoteBranch(String),
+    /// `git gc`, `git prune` or `git maintenance run`
+    Maintenance(MaintenanceTask),
+    /// `git archive` of a ref into a zip or tar file
+    Archive(String),
+}
+
+impl Task {
+    /// Short label used in status messages while the task is running
+    pub fn label(&self) -> String {
+        match self {
+            Self::Push => "push".to_string(),
+            Self::Pull => "pull".to_string(),
+            Self::Fetch => "fetch".to_string(),
+            Self::StageAll => "stage all".to_string(),
+            Self::Diff(key) => format!("diff {}", key.path),
+            Self::Prefetch(key) => format!("prefetch {}", key.path),
+            Self::DeleteRemoteBranch(name) => format!("delete {name}"),
+            Self::Maintenance(task) => task.name().to_string(),
+            Self::Archive(target) => format!("archive {target}"),
+        }
+    }
+
+    /// Loads a preview diff, which is not worth a spinner or asking before quitting
+    pub const fn is_preview(&self) -> bool {
+        matches!(self, Self::Diff(_) | Self::Prefetch(_))
+    }
+}
+
+#[derive(Debug)]
+pub struct TaskResult {
+    pub task: Task,
+    pub result: Result<String, GitError>,
+}
+
+/// Messages sent from a running task back to the event loop
+#[derive(Debug)]
+enum Event {
+    Progress(Task, u8),
+    Finished(TaskResult),
+}
+
+/// Handed to a running job so it can report how far along it is
+#[derive(Debug, Clone)]
+pub struct Progress {
+    task: Task,
+    sender: Sender<Event>,
+}
+
+impl Progress {
+    /// Reports completion in percent (clamped to 100)
+    pub fn report(&self, percent: u8) {
+        let _ = self
+            .sender
+            .send(Event::Progress(self.task.clone(), percent.min(100)));
+    }
+}
+
+/// Runs blocking git commands on background threads and hands the results back over a
+/// channel, which `App::on_tick` drains from the event loop.
+///
+/// A task is started by `spawn`, may report progress while it runs and is finished once its
+/// result has been drained.
+#[derive(Debug)]
+pub struct Worker {
+    sender: Sender<Event>,
+    receiver: Receiver<Event>,
+    in_flight: Vec<Task>,
+    /// Last reported percentage of running tasks that report progress
+    progress: Vec<(Task, u8)>,
+}
+
+impl Default for Worker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Worker {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            in_flight: Vec::new(),
+            progress: Vec::new(),
+        }
+    }
+
+    pub fn spawn<F>(&mut self, task: Task, job: F)
+    where
+        F: FnOnce() -> Result<String, GitError> + Send + 'static,
+    {
+        self.spawn_with_progress(task, move |_| job());
+    }
+
+    /// Like `spawn`, for jobs that report their progress
+    pub fn spawn_with_progress<F>(&mut self, task: Task, job: F)
+    where
+        F: FnOnce(&Progress) -> Result<String, GitError> + Send + 'static,
+    {
+        let progress = Progress {
+            task: task.clone(),
+            sender: self.sender.clone(),
+        };
+        self.in_flight.push(task.clone());
+        thread::spawn(move || {
+            let result = job(&progress);
+            // The receiver only disappears when the app is shutting down
+            let _ = progress
+                .sender
+                .send(Event::Finished(TaskResult { task, result }));
+        });
+    }
+
+    /// Applies progress reports and returns every result that has arrived since the last
+    /// call without blocking.
+    pub fn drain(&mut self) -> Vec<TaskResult> {
+        let mut results = Vec::new();
+        for event in self.receiver.try_iter() {
+            match event {
+                Event::Progress(task, percent) => {
+                    self.progress.retain(|(running, _)| *running != task);
+                    self.progress.push((task, percent));
+                },
+                Event::Finished(finished) => results.push(finished),
+            }
+        }
+        for finished in &results {
+            if let Some(i) = self.in_flight.iter().position(|t| *t == finished.task) {
+                self.in_flight.remove(i);
+            }
+            if !self.in_flight.contains(&finished.task) {
+                self.progress
+                    .retain(|(running, _)| *running != finished.task);
+            }
+        }
+        results
+    }
+
+    /// Last reported percentage of a running task
+    pub fn progress(&self, task: &Task) -> Option<u8> {
+        self.progress
+            .iter()
+            .find(|(running, _)| running == task)
+            .map(|(_, percent)| *percent)
+    }
+
+    pub fn is_running(&self, task: &Task) -> bool {
+        self.in_flight.contains(task)
+    }
+
+    pub fn in_flight(&self) -> &[Task] {
+        &self.in_flight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::{DiffOptions, DiffView};
+    use std::time::{Duration, Instant};
+
+    fn wait_for_results(worker: &mut Worker, count: usize) -> Vec<TaskResult> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut results = Vec::new();
+        while results.len() < count && Instant::now() < deadline {
+            results.extend(worker.drain());
+            thread::sleep(Duration::from_millis(5));
+        }
+        results
+    }
+
+    #[test]
+    fn test_worker_returns_results() {
+        let mut worker = Worker::new();
+        worker.spawn(Task::Fetch, || Ok("fetched".to_string()));
+        assert!(worker.is_running(&Task::Fetch));
+
+        let results = wait_for_results(&mut worker, 1);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].task, Task::Fetch);
+        assert_eq!(results[0].result.as_deref().ok(), Some("fetched"));
+        assert!(!worker.is_running(&Task::Fetch));
+    }
+
+    #[test]
+    fn test_worker_reports_progress() {
+        let mut worker = Worker::new();
+        let (release, wait) = mpsc::channel::<()>();
+        worker.spawn_with_progress(Task::Push, move |progress| {
+            progress.report(40);
+            wait.recv().unwrap();
+            Ok("pushed".to_string())
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while worker.progress(&Task::Push).is_none() && Instant::now() < deadline {
+            assert_eq!(worker.drain().len(), 0);
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(worker.progress(&Task::Push), Some(40));
+
+        release.send(()).unwrap();
+        assert_eq!(wait_for_results(&mut worker, 1).len(), 1);
+        assert_eq!(worker.progress(&Task::Push), None);
+        assert!(!worker.is_running(&Task::Push));
+    }
+
+    #[test]
+    fn test_worker_reports_errors() {
+        let mut worker = Worker::new();
+        let key = DiffKey {
+            path: "a.txt".to_string(),
+            view: DiffView::Unstaged,
+            options: DiffOptions::default(),
+            max_lines: 1000,
+        };
+        worker.spawn(Task::Diff(key.clone()), || {
+            Err(GitError::Invalid("no diff".to_string()))
+        });
+        assert!(worker.is_running(&Task::Diff(key)));
+
+        let results = wait_for_results(&mut worker, 1);
+        assert!(matches!(&results[0].result, Err(GitError::Invalid(e)) if e == "no diff"));
+    }
+}